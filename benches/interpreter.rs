@@ -0,0 +1,72 @@
+//! Tight integer loops in the interpreter, with and without the fast path for
+//! expressions over int (`BinaryOp::int_only`).
+//!
+//! `cargo bench --bench interpreter`
+//!
+//! Both variants run the same program and must return the same value; only
+//! the time differs.
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use rono_lang::{parse_source, Interpreter};
+
+const PROGRAM: &str = r#"
+fn fib_loop() int {
+    var total: int = 0;
+    for (var round: int = 0; round < 100; round = round + 1) {
+        var a: int = 0;
+        var b: int = 1;
+        for (var i: int = 0; i < 80; i = i + 1) {
+            var next: int = (a + b) % 1000000007;
+            a = b;
+            b = next;
+        }
+        total = (total + a * 3 - round) % 1000000007;
+    }
+    ret total;
+}
+
+fn prime_sieve() int {
+    var limit: int = 1000;
+    var composite: list[int] = [];
+    for (var i: int = 0; i <= limit; i = i + 1) {
+        composite.add(0);
+    }
+    var count: int = 0;
+    for (var n: int = 2; n <= limit; n = n + 1) {
+        if (composite[n] == 0) {
+            count = count + 1;
+            for (var multiple: int = n * n; multiple <= limit; multiple = multiple + n) {
+                composite[multiple] = 1;
+            }
+        }
+    }
+    ret count;
+}
+"#;
+
+fn interpreter(fast_path: bool) -> Interpreter {
+    let program = parse_source(PROGRAM).expect("benchmark program should parse");
+    let mut interpreter = Interpreter::new();
+    interpreter.set_int_fast_path(fast_path);
+    interpreter.load(&program).expect("benchmark program should load");
+    interpreter
+}
+
+fn integer_loops(c: &mut Criterion) {
+    for function in ["fib_loop", "prime_sieve"] {
+        let run = |interpreter: &mut Interpreter| format!("{:?}", interpreter.call_function_by_name(function).expect("benchmark should run"));
+        let expected = run(&mut interpreter(false));
+        let mut group = c.benchmark_group(function);
+        for (name, fast_path) in [("generic", false), ("int fast path", true)] {
+            let mut interpreter = interpreter(fast_path);
+            assert_eq!(run(&mut interpreter), expected);
+            group.bench_function(BenchmarkId::from_parameter(name), |b| {
+                b.iter(|| interpreter.call_function_by_name(function).expect("benchmark should run"))
+            });
+        }
+        group.finish();
+    }
+}
+
+criterion_group!(benches, integer_loops);
+criterion_main!(benches);
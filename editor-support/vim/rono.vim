@@ -8,7 +8,7 @@ if exists("b:current_syntax")
 endif
 
 " Keywords
-syn keyword ronoKeyword chif fn struct var list array fn_for
+syn keyword ronoKeyword chif fn struct var list array fn_for priv export
 syn keyword ronoControl if else for while break continue ret import
 syn keyword ronoConstant true false nil
 syn keyword ronoSelf self
@@ -1,4 +1,6 @@
 use crate::types::{ChifType, ChifValue};
+use std::collections::{HashMap, HashSet};
+use std::fmt;
 
 #[derive(Debug, Clone)]
 pub struct Program {
@@ -11,12 +13,126 @@ pub enum Item {
     Function(Function),
     Struct(StructDef),
     StructImpl(StructImpl),
+    /// `var counter: int = 0;` вне функций: глобальная переменная. Значения
+    /// вычисляются по порядку при загрузке программы, до вызова `chif main`
+    Global(VarDecl),
 }
 
 #[derive(Debug, Clone)]
 pub struct ImportStatement {
     pub path: String,
     pub alias: Option<String>,
+    /// Имена из `use (...)`; `None` импортирует весь модуль
+    pub names: Option<Vec<String>>,
+    /// Строка `import` в исходнике, 0 если неизвестна
+    pub line: usize,
+}
+
+/// Форма пути в `import`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ImportScheme {
+    /// `./utils`, `../lib/utils` — от каталога импортирующего файла
+    Relative,
+    /// `/opt/rono/utils`
+    Absolute,
+    /// `pkg:prelude` — модуль, встроенный в крейт
+    Package,
+    /// `utils`, `lib/utils` — прежняя форма без схемы: ищется так же, как
+    /// `./utils`, но устарела (W0006)
+    Bare,
+}
+
+impl ImportStatement {
+    pub fn scheme(&self) -> ImportScheme {
+        if self.path.starts_with("pkg:") {
+            ImportScheme::Package
+        } else if self.path.starts_with("./") || self.path.starts_with("../") {
+            ImportScheme::Relative
+        } else if std::path::Path::new(&self.path).is_absolute() {
+            ImportScheme::Absolute
+        } else {
+            ImportScheme::Bare
+        }
+    }
+    
+    /// Путь файла модуля: расширение `.rono` необязательно
+    pub fn file_path(&self) -> String {
+        if self.path.ends_with(".rono") {
+            self.path.clone()
+        } else {
+            format!("{}.rono", self.path)
+        }
+    }
+    
+    /// Имя, под которым модуль виден в файле: псевдоним или имя файла без
+    /// расширения. Если имя файла не годится в идентификаторы (`..`, `dir/`,
+    /// `my-utils`), модулю нужен псевдоним
+    pub fn module_name(&self) -> Result<String, String> {
+        if let Some(alias) = &self.alias {
+            return Ok(alias.clone());
+        }
+        let file_path = self.file_path();
+        let file_path = file_path.strip_prefix("pkg:").unwrap_or(&file_path);
+        match std::path::Path::new(file_path).file_stem().and_then(|stem| stem.to_str()) {
+            Some(stem) if is_name(stem) => Ok(stem.to_string()),
+            _ => Err(format!(
+                "Cannot name the module imported from '{}'; give it a name with 'as'",
+                self.path
+            )),
+        }
+    }
+}
+
+/// Идентификатор Rono: буква или `_`, затем буквы, цифры и `_`
+fn is_name(name: &str) -> bool {
+    name.chars().next().is_some_and(|first| first.is_alphabetic() || first == '_')
+        && name.chars().all(|c| c.is_alphanumeric() || c == '_')
+}
+
+/// Функция, с которой начинается выполнение программы
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EntryPoint {
+    /// Имя импортированного модуля; `None` — функция самого файла
+    pub module: Option<String>,
+    pub function: String,
+}
+
+impl EntryPoint {
+    /// Значение `--entry`: `start` или `app.start`
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let (module, function) = match text.split_once('.') {
+            Some((module, function)) => (Some(module), function),
+            None => (None, text),
+        };
+        if !is_name(function) || module.is_some_and(|module| !is_name(module)) {
+            return Err(format!("entry must be 'function' or 'module.function', found '{}'", text));
+        }
+        Ok(EntryPoint {
+            module: module.map(str::to_string),
+            function: function.to_string(),
+        })
+    }
+}
+
+impl fmt::Display for EntryPoint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.module {
+            Some(module) => write!(f, "{}.{}", module, self.function),
+            None => write!(f, "{}", self.function),
+        }
+    }
+}
+
+/// Почему функция не годится в точку входа: у неё параметры или она
+/// возвращает не int. `None` — годится
+pub fn entry_signature_problem(entry: &EntryPoint, params: &[Parameter], return_type: Option<&ChifType>) -> Option<String> {
+    if !params.is_empty() {
+        return Some(format!("Entry function '{}' must take no parameters", entry));
+    }
+    match return_type {
+        None | Some(ChifType::Int) | Some(ChifType::Nil) => None,
+        Some(other) => Some(format!("Entry function '{}' must return int or nothing, found {}", entry, other)),
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -26,12 +142,18 @@ pub struct Function {
     pub return_type: Option<ChifType>,
     pub body: Block,
     pub is_main: bool,
+    /// Сообщение из `@deprecated("...")`, если функция устарела
+    pub deprecated: Option<String>,
+    /// `export fn`: функция видна файлам, которые импортируют модуль
+    pub is_exported: bool,
 }
 
 #[derive(Debug, Clone)]
 pub struct Parameter {
     pub name: String,
     pub param_type: ChifType,
+    /// Параметр `&mut T` (или `ref`): функция может писать через него,
+    /// и изменения видны вызывающему. Через `&T` и `pointer[T]` только читают
     pub is_reference: bool,
 }
 
@@ -39,12 +161,16 @@ pub struct Parameter {
 pub struct StructDef {
     pub name: String,
     pub fields: Vec<StructField>,
+    /// `export struct`: структура и её методы видны импортирующим файлам
+    pub is_exported: bool,
 }
 
 #[derive(Debug, Clone)]
 pub struct StructField {
     pub name: String,
     pub field_type: ChifType,
+    /// `priv`: поле доступно только методам из `fn_for` этой структуры
+    pub is_private: bool,
 }
 
 #[derive(Debug, Clone)]
@@ -65,13 +191,19 @@ pub enum Statement {
     Expression(Expression),
     If(IfStatement),
     For(ForStatement),
+    ForIn(ForInStatement),
     While(WhileStatement),
     Switch(SwitchStatement),
     Return(Option<Expression>),
+    Destructure(Destructure),
     Break,
     Continue,
 }
 
+/// Имя `_` связывает значение, которое сразу отбрасывается: символ не
+/// объявляется, а читать `_` нельзя
+pub const DISCARD: &str = "_";
+
 #[derive(Debug, Clone)]
 pub struct VarDecl {
     pub name: String,
@@ -80,6 +212,15 @@ pub struct VarDecl {
     pub is_mutable: bool,
 }
 
+// var (q, r) = divmod(7, 2);
+#[derive(Debug, Clone)]
+pub struct Destructure {
+    pub names: Vec<String>,
+    pub var_type: Option<ChifType>,
+    pub value: Expression,
+    pub is_mutable: bool,
+}
+
 #[derive(Debug, Clone)]
 pub struct Assignment {
     pub target: Expression,
@@ -101,6 +242,26 @@ pub struct ForStatement {
     pub body: Block,
 }
 
+// for i in range(0, 10, 2) { ... }
+#[derive(Debug, Clone)]
+pub struct ForInStatement {
+    pub variable: String,
+    pub iterable: Expression,
+    pub body: Block,
+}
+
+impl ForInStatement {
+    /// Arguments of the `range(...)` call the loop iterates over, if it is one
+    pub fn range_args(&self) -> Option<&[Expression]> {
+        match &self.iterable {
+            Expression::Call(func_call) if func_call.name == "range" && func_call.named_args.is_empty() => {
+                Some(&func_call.args)
+            }
+            _ => None,
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct WhileStatement {
     pub condition: Expression,
@@ -131,9 +292,10 @@ pub enum Expression {
     Index(IndexAccess),
     FieldAccess(FieldAccess),
     ArrayLiteral(Vec<Expression>),
+    TupleLiteral(Vec<Expression>),
     MapLiteral(Vec<(Expression, Expression)>),
     StructLiteral(StructLiteral),
-    Reference(Box<Expression>),
+    Reference(AddressOf),
     Dereference(Box<Expression>),
 }
 
@@ -142,6 +304,10 @@ pub struct BinaryOp {
     pub left: Box<Expression>,
     pub operator: BinaryOperator,
     pub right: Box<Expression>,
+    /// Операнды — только литералы int и переменные, объявленные как int:
+    /// интерпретатор считает такое выражение на i64. Ставит
+    /// `Function::mark_int_expressions`, парсер оставляет `false`
+    pub int_only: bool,
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -157,6 +323,8 @@ pub enum BinaryOperator {
     Greater,
     LessEqual,
     GreaterEqual,
+    /// `x in collection`: ключ словаря, элемент списка или массива, подстрока
+    In,
     And,
     Or,
 }
@@ -173,10 +341,709 @@ pub enum UnaryOperator {
     Minus,
 }
 
+// f(1, y: 2): positional arguments first, then named ones in source order
 #[derive(Debug, Clone)]
 pub struct FunctionCall {
     pub name: String,
     pub args: Vec<Expression>,
+    pub named_args: Vec<(String, Expression)>,
+}
+
+impl FunctionCall {
+    /// Arguments in source order: the positional ones, then the named ones as written
+    pub fn source_args(&self) -> impl Iterator<Item = &Expression> {
+        self.args.iter().chain(self.named_args.iter().map(|(_, arg)| arg))
+    }
+
+    /// For each argument of `source_args`, the index of the parameter it is passed
+    /// to. The backends evaluate arguments in source order and only then place the
+    /// values by parameter, so `f(b: g(), a: h())` still calls `g()` first
+    pub fn parameter_positions(&self, params: &[Parameter]) -> Result<Vec<usize>, String> {
+        if self.args.len() > params.len() {
+            return Err(format!(
+                "Function '{}' expects {} arguments, got {}",
+                self.name, params.len(), self.args.len() + self.named_args.len()
+            ));
+        }
+
+        let mut positions: Vec<usize> = (0..self.args.len()).collect();
+        for (arg_name, _) in &self.named_args {
+            let position = params.iter().position(|param| &param.name == arg_name).ok_or_else(|| {
+                format!("Function '{}' has no parameter named '{}'", self.name, arg_name)
+            })?;
+            if positions.contains(&position) {
+                return Err(format!("Argument '{}' of '{}' is given more than once", arg_name, self.name));
+            }
+            positions.push(position);
+        }
+
+        if let Some(missing) = (0..params.len()).find(|index| !positions.contains(index)) {
+            return Err(format!("Missing argument '{}' in call to '{}'", params[missing].name, self.name));
+        }
+        Ok(positions)
+    }
+}
+
+/// Puts values computed in source order into parameter order, given the
+/// `FunctionCall::parameter_positions` of the call
+pub fn in_parameter_order<T>(values: Vec<T>, positions: &[usize]) -> Vec<T> {
+    let mut placed: Vec<(usize, T)> = positions.iter().copied().zip(values).collect();
+    placed.sort_by_key(|(position, _)| *position);
+    placed.into_iter().map(|(_, value)| value).collect()
+}
+
+impl Program {
+    /// Точка входа программы. Её назначают `chif main` файла, импорт
+    /// `import "app" use (main);` и `requested` (флаг `--entry`); назначений
+    /// больше одного — ошибка. `None`, если точку входа не назначили
+    pub fn entry_point(&self, requested: Option<&EntryPoint>) -> Result<Option<EntryPoint>, String> {
+        let mut designations: Vec<(EntryPoint, String)> = Vec::new();
+        for item in &self.items {
+            match item {
+                Item::Function(func) if func.is_main => designations.push((
+                    EntryPoint { module: None, function: func.name.clone() },
+                    "'chif main' in this file".to_string(),
+                )),
+                Item::Import(import) if import.names.as_ref().is_some_and(|names| names.iter().any(|name| name == "main")) => {
+                    designations.push((
+                        EntryPoint { module: Some(import.module_name()?), function: "main".to_string() },
+                        format!("'import \"{}\" use (main)'", import.path),
+                    ))
+                }
+                _ => {}
+            }
+        }
+        if let Some(requested) = requested {
+            designations.push((requested.clone(), format!("'--entry {}'", requested)));
+        }
+        
+        if designations.len() > 1 {
+            let sources: Vec<String> = designations.into_iter().map(|(_, source)| source).collect();
+            return Err(format!("Duplicate entry point: {}", sources.join(" and ")));
+        }
+        Ok(designations.pop().map(|(entry, _)| entry))
+    }
+    
+    /// Имена функций и структур с `export`. `None`, если в файле нет ни одного
+    /// `export`: такой модуль по-прежнему открывает всё
+    pub fn exported_names(&self) -> Option<Vec<String>> {
+        let names: Vec<String> = self.items.iter()
+            .filter_map(|item| match item {
+                Item::Function(func) if func.is_exported => Some(func.name.clone()),
+                Item::Struct(struct_def) if struct_def.is_exported => Some(struct_def.name.clone()),
+                _ => None,
+            })
+            .collect();
+        (!names.is_empty()).then_some(names)
+    }
+    
+    /// Functions and structs of this program reachable from `names`: the listed
+    /// functions and structs, everything their bodies call, and the structs they
+    /// declare, build or call associated functions of, together with those
+    /// structs' methods and the structs of their fields
+    pub fn reachable(&self, names: &[String]) -> Reachable {
+        let mut functions: HashMap<&str, &Function> = HashMap::new();
+        let mut structs: HashMap<&str, &StructDef> = HashMap::new();
+        let mut methods: HashMap<&str, Vec<&Function>> = HashMap::new();
+        for item in &self.items {
+            match item {
+                Item::Function(func) => {
+                    functions.insert(func.name.as_str(), func);
+                }
+                Item::Struct(struct_def) => {
+                    structs.insert(struct_def.name.as_str(), struct_def);
+                }
+                Item::StructImpl(impl_block) => {
+                    methods.entry(impl_block.struct_name.as_str()).or_default().extend(&impl_block.methods);
+                }
+                Item::Import(_) | Item::Global(_) => {}
+            }
+        }
+        
+        let mut reachable = Reachable::default();
+        let mut pending_functions: Vec<&Function> = Vec::new();
+        let mut pending_structs: Vec<&str> = names.iter().map(String::as_str).collect();
+        
+        // Глобальные переменные инициализируются всегда, поэтому то, что
+        // вызывают их значения, нужно при любом наборе `names`
+        let mut roots: Vec<&str> = names.iter().map(String::as_str).collect();
+        for item in &self.items {
+            if let Item::Global(var_decl) = item {
+                let mut references = References::default();
+                struct_names(&var_decl.var_type, &mut references.structs);
+                if let Some(value) = &var_decl.value {
+                    value.walk(&mut references);
+                }
+                roots.extend(references.calls);
+                pending_structs.extend(references.structs);
+            }
+        }
+        for name in roots {
+            if let Some(func) = functions.get(name) {
+                if reachable.functions.insert(name.to_string()) {
+                    pending_functions.push(func);
+                }
+            }
+        }
+        
+        while !pending_functions.is_empty() || !pending_structs.is_empty() {
+            while let Some(name) = pending_structs.pop() {
+                if (!structs.contains_key(name) && !methods.contains_key(name)) || !reachable.structs.insert(name.to_string()) {
+                    continue;
+                }
+                if let Some(struct_def) = structs.get(name) {
+                    for field in &struct_def.fields {
+                        struct_names(&field.field_type, &mut pending_structs);
+                    }
+                }
+                pending_functions.extend(methods.get(name).into_iter().flatten());
+            }
+            
+            while let Some(func) = pending_functions.pop() {
+                let mut references = References::default();
+                for param in &func.params {
+                    struct_names(&param.param_type, &mut references.structs);
+                }
+                if let Some(return_type) = &func.return_type {
+                    struct_names(return_type, &mut references.structs);
+                }
+                func.body.walk(&mut references);
+                for callee in references.calls {
+                    if let Some(func) = functions.get(callee) {
+                        if reachable.functions.insert(callee.to_string()) {
+                            pending_functions.push(func);
+                        }
+                    }
+                }
+                pending_structs.extend(references.structs);
+            }
+        }
+        reachable
+    }
+}
+
+/// What `Program::reachable` found
+#[derive(Debug, Default)]
+pub struct Reachable {
+    pub functions: HashSet<String>,
+    /// Structs whose definitions and `fn_for` methods are needed
+    pub structs: HashSet<String>,
+}
+
+/// Names of the structs a type mentions: `Point`, `list[Point]`, `pointer[Point]`...
+fn struct_names<'a>(chif_type: &'a ChifType, names: &mut Vec<&'a str>) {
+    match chif_type {
+        ChifType::Struct(name) => names.push(name),
+        ChifType::Array(element_type, _) | ChifType::List(element_type, _) | ChifType::Pointer(element_type) => {
+            struct_names(element_type, names);
+        }
+        ChifType::Map(key_type, value_type) => {
+            struct_names(key_type, names);
+            struct_names(value_type, names);
+        }
+        ChifType::Tuple(element_types) => {
+            for element_type in element_types {
+                struct_names(element_type, names);
+            }
+        }
+        ChifType::Int | ChifType::Float | ChifType::Str | ChifType::Bool | ChifType::Nil => {}
+    }
+}
+
+/// Functions a body calls and structs it mentions, for `Program::reachable`.
+/// `Point.new()` and `p.len()` look the same here, so every method call on a
+/// plain name counts as a possible struct; names that are not structs are skipped
+#[derive(Default)]
+struct References<'a> {
+    calls: Vec<&'a str>,
+    structs: Vec<&'a str>,
+}
+
+impl<'a> Visitor<'a> for References<'a> {
+    fn statement(&mut self, statement: &'a Statement) {
+        match statement {
+            Statement::VarDecl(var_decl) => struct_names(&var_decl.var_type, &mut self.structs),
+            Statement::Destructure(Destructure { var_type: Some(var_type), .. }) => struct_names(var_type, &mut self.structs),
+            _ => {}
+        }
+    }
+    
+    fn expression(&mut self, expression: &'a Expression) {
+        match expression {
+            Expression::Call(call) => self.calls.push(&call.name),
+            Expression::StructLiteral(struct_literal) => self.structs.push(&struct_literal.struct_name),
+            Expression::MethodCall(method_call) => {
+                if let Expression::Identifier(name) = method_call.object.as_ref() {
+                    self.structs.push(name);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Read-only walk over a function body: `Block::walk` calls `statement` for
+/// every statement, nested ones included, and `expression` for every
+/// expression and subexpression, without copying the tree
+pub trait Visitor<'a> {
+    fn statement(&mut self, _statement: &'a Statement) {}
+    fn expression(&mut self, _expression: &'a Expression) {}
+}
+
+impl Block {
+    pub fn walk<'a>(&'a self, visitor: &mut impl Visitor<'a>) {
+        for statement in &self.statements {
+            statement.walk(visitor);
+        }
+    }
+}
+
+impl Statement {
+    fn walk<'a>(&'a self, visitor: &mut impl Visitor<'a>) {
+        visitor.statement(self);
+        match self {
+            Statement::VarDecl(var_decl) => {
+                if let Some(value) = &var_decl.value {
+                    value.walk(visitor);
+                }
+            }
+            Statement::Assignment(assignment) => {
+                assignment.target.walk(visitor);
+                assignment.value.walk(visitor);
+            }
+            Statement::Expression(expr) => expr.walk(visitor),
+            Statement::If(if_stmt) => {
+                if_stmt.condition.walk(visitor);
+                if_stmt.then_block.walk(visitor);
+                if let Some(else_block) = &if_stmt.else_block {
+                    else_block.walk(visitor);
+                }
+            }
+            Statement::For(for_stmt) => {
+                if let Some(init) = &for_stmt.init {
+                    init.walk(visitor);
+                }
+                if let Some(condition) = &for_stmt.condition {
+                    condition.walk(visitor);
+                }
+                if let Some(update) = &for_stmt.update {
+                    update.walk(visitor);
+                }
+                for_stmt.body.walk(visitor);
+            }
+            Statement::ForIn(for_in) => {
+                for_in.iterable.walk(visitor);
+                for_in.body.walk(visitor);
+            }
+            Statement::While(while_stmt) => {
+                while_stmt.condition.walk(visitor);
+                while_stmt.body.walk(visitor);
+            }
+            Statement::Switch(switch_stmt) => {
+                switch_stmt.expr.walk(visitor);
+                for case in &switch_stmt.cases {
+                    case.value.walk(visitor);
+                    case.body.walk(visitor);
+                }
+                if let Some(default_case) = &switch_stmt.default_case {
+                    default_case.walk(visitor);
+                }
+            }
+            Statement::Return(value) => {
+                if let Some(value) = value {
+                    value.walk(visitor);
+                }
+            }
+            Statement::Destructure(destructure) => destructure.value.walk(visitor),
+            Statement::Break | Statement::Continue => {}
+        }
+    }
+}
+
+impl Expression {
+    fn walk<'a>(&'a self, visitor: &mut impl Visitor<'a>) {
+        visitor.expression(self);
+        match self {
+            Expression::Literal(_) | Expression::Identifier(_) => {}
+            Expression::Binary(binary_op) => {
+                binary_op.left.walk(visitor);
+                binary_op.right.walk(visitor);
+            }
+            Expression::Unary(unary_op) => unary_op.operand.walk(visitor),
+            Expression::Call(call) => {
+                for arg in call.args.iter().chain(call.named_args.iter().map(|(_, arg)| arg)) {
+                    arg.walk(visitor);
+                }
+            }
+            Expression::MethodCall(method_call) => {
+                method_call.object.walk(visitor);
+                for arg in &method_call.args {
+                    arg.walk(visitor);
+                }
+            }
+            Expression::Index(index_access) => {
+                index_access.object.walk(visitor);
+                for index in &index_access.indices {
+                    index.walk(visitor);
+                }
+            }
+            Expression::ArrayLiteral(elements) | Expression::TupleLiteral(elements) => {
+                for element in elements {
+                    element.walk(visitor);
+                }
+            }
+            Expression::MapLiteral(entries) => {
+                for (key, value) in entries {
+                    key.walk(visitor);
+                    value.walk(visitor);
+                }
+            }
+            Expression::StructLiteral(struct_literal) => {
+                for (_, value) in &struct_literal.fields {
+                    value.walk(visitor);
+                }
+                if let Some(base) = &struct_literal.base {
+                    base.walk(visitor);
+                }
+            }
+            Expression::FieldAccess(FieldAccess { object: inner, .. })
+            | Expression::Reference(AddressOf { target: inner, .. })
+            | Expression::Dereference(inner) => inner.walk(visitor),
+        }
+    }
+}
+
+impl Block {
+    /// Calls `visit` for every function call in the block, including nested ones
+    pub fn visit_calls_mut<E>(&mut self, visit: &mut impl FnMut(&mut FunctionCall) -> Result<(), E>) -> Result<(), E> {
+        for statement in &mut self.statements {
+            statement.visit_calls_mut(visit)?;
+        }
+        Ok(())
+    }
+}
+
+impl Statement {
+    fn visit_calls_mut<E>(&mut self, visit: &mut impl FnMut(&mut FunctionCall) -> Result<(), E>) -> Result<(), E> {
+        match self {
+            Statement::VarDecl(var_decl) => {
+                if let Some(value) = &mut var_decl.value {
+                    value.visit_calls_mut(visit)?;
+                }
+            }
+            Statement::Assignment(assignment) => {
+                assignment.target.visit_calls_mut(visit)?;
+                assignment.value.visit_calls_mut(visit)?;
+            }
+            Statement::Expression(expr) => expr.visit_calls_mut(visit)?,
+            Statement::If(if_stmt) => {
+                if_stmt.condition.visit_calls_mut(visit)?;
+                if_stmt.then_block.visit_calls_mut(visit)?;
+                if let Some(else_block) = &mut if_stmt.else_block {
+                    else_block.visit_calls_mut(visit)?;
+                }
+            }
+            Statement::For(for_stmt) => {
+                if let Some(init) = &mut for_stmt.init {
+                    init.visit_calls_mut(visit)?;
+                }
+                if let Some(condition) = &mut for_stmt.condition {
+                    condition.visit_calls_mut(visit)?;
+                }
+                if let Some(update) = &mut for_stmt.update {
+                    update.visit_calls_mut(visit)?;
+                }
+                for_stmt.body.visit_calls_mut(visit)?;
+            }
+            Statement::ForIn(for_in) => {
+                for_in.iterable.visit_calls_mut(visit)?;
+                for_in.body.visit_calls_mut(visit)?;
+            }
+            Statement::While(while_stmt) => {
+                while_stmt.condition.visit_calls_mut(visit)?;
+                while_stmt.body.visit_calls_mut(visit)?;
+            }
+            Statement::Switch(switch_stmt) => {
+                switch_stmt.expr.visit_calls_mut(visit)?;
+                for case in &mut switch_stmt.cases {
+                    case.value.visit_calls_mut(visit)?;
+                    case.body.visit_calls_mut(visit)?;
+                }
+                if let Some(default_case) = &mut switch_stmt.default_case {
+                    default_case.visit_calls_mut(visit)?;
+                }
+            }
+            Statement::Return(value) => {
+                if let Some(value) = value {
+                    value.visit_calls_mut(visit)?;
+                }
+            }
+            Statement::Destructure(destructure) => destructure.value.visit_calls_mut(visit)?,
+            Statement::Break | Statement::Continue => {}
+        }
+        Ok(())
+    }
+}
+
+impl Expression {
+    fn visit_calls_mut<E>(&mut self, visit: &mut impl FnMut(&mut FunctionCall) -> Result<(), E>) -> Result<(), E> {
+        match self {
+            Expression::Literal(_) | Expression::Identifier(_) => {}
+            Expression::Binary(binary_op) => {
+                binary_op.left.visit_calls_mut(visit)?;
+                binary_op.right.visit_calls_mut(visit)?;
+            }
+            Expression::Unary(unary_op) => unary_op.operand.visit_calls_mut(visit)?,
+            Expression::Call(call) => {
+                for arg in &mut call.args {
+                    arg.visit_calls_mut(visit)?;
+                }
+                for (_, arg) in &mut call.named_args {
+                    arg.visit_calls_mut(visit)?;
+                }
+                visit(call)?;
+            }
+            Expression::MethodCall(method_call) => {
+                method_call.object.visit_calls_mut(visit)?;
+                for arg in &mut method_call.args {
+                    arg.visit_calls_mut(visit)?;
+                }
+            }
+            Expression::Index(index_access) => {
+                index_access.object.visit_calls_mut(visit)?;
+                for index in &mut index_access.indices {
+                    index.visit_calls_mut(visit)?;
+                }
+            }
+            Expression::FieldAccess(field_access) => field_access.object.visit_calls_mut(visit)?,
+            Expression::ArrayLiteral(elements) | Expression::TupleLiteral(elements) => {
+                for element in elements {
+                    element.visit_calls_mut(visit)?;
+                }
+            }
+            Expression::MapLiteral(entries) => {
+                for (key, value) in entries {
+                    key.visit_calls_mut(visit)?;
+                    value.visit_calls_mut(visit)?;
+                }
+            }
+            Expression::StructLiteral(struct_literal) => {
+                for (_, value) in &mut struct_literal.fields {
+                    value.visit_calls_mut(visit)?;
+                }
+                if let Some(base) = &mut struct_literal.base {
+                    base.visit_calls_mut(visit)?;
+                }
+            }
+            Expression::Reference(AddressOf { target: inner, .. }) | Expression::Dereference(inner) => inner.visit_calls_mut(visit)?,
+        }
+        Ok(())
+    }
+}
+
+impl Function {
+    /// Отмечает `BinaryOp::int_only` в теле функции. Переменная считается int,
+    /// только если все её объявления в функции (параметр, `var`, `for ... in range`)
+    /// имеют тип int: повторное объявление с другим типом снимает отметку
+    pub fn mark_int_expressions(&mut self) {
+        let mut declared: HashMap<String, bool> = HashMap::new();
+        let mut declare = |name: &str, is_int: bool| {
+            *declared.entry(name.to_string()).or_insert(true) &= is_int;
+        };
+        for param in &self.params {
+            declare(&param.name, param.param_type == ChifType::Int && !param.is_reference);
+        }
+        self.body.visit_statements_mut(&mut |statement| match statement {
+            Statement::VarDecl(var_decl) => declare(&var_decl.name, var_decl.var_type == ChifType::Int),
+            Statement::Destructure(destructure) => {
+                for name in &destructure.names {
+                    declare(name, false);
+                }
+            }
+            Statement::ForIn(for_in) => declare(&for_in.variable, for_in.range_args().is_some()),
+            _ => {}
+        });
+
+        let ints: HashSet<String> = declared.into_iter().filter_map(|(name, is_int)| is_int.then_some(name)).collect();
+        self.body.visit_statements_mut(&mut |statement| {
+            for expression in statement.expressions_mut() {
+                expression.mark_int(&ints);
+            }
+        });
+    }
+}
+
+impl Block {
+    /// Calls `visit` for every statement in the block, including nested ones
+    /// and the init and update clauses of `for`
+    pub fn visit_statements_mut(&mut self, visit: &mut impl FnMut(&mut Statement)) {
+        for statement in &mut self.statements {
+            statement.visit_statements_mut(visit);
+        }
+    }
+}
+
+impl Statement {
+    fn visit_statements_mut(&mut self, visit: &mut impl FnMut(&mut Statement)) {
+        visit(self);
+        match self {
+            Statement::If(if_stmt) => {
+                if_stmt.then_block.visit_statements_mut(visit);
+                if let Some(else_block) = &mut if_stmt.else_block {
+                    else_block.visit_statements_mut(visit);
+                }
+            }
+            Statement::For(for_stmt) => {
+                if let Some(init) = &mut for_stmt.init {
+                    init.visit_statements_mut(visit);
+                }
+                if let Some(update) = &mut for_stmt.update {
+                    update.visit_statements_mut(visit);
+                }
+                for_stmt.body.visit_statements_mut(visit);
+            }
+            Statement::ForIn(ForInStatement { body, .. }) | Statement::While(WhileStatement { body, .. }) => {
+                body.visit_statements_mut(visit);
+            }
+            Statement::Switch(switch_stmt) => {
+                for case in &mut switch_stmt.cases {
+                    case.body.visit_statements_mut(visit);
+                }
+                if let Some(default_case) = &mut switch_stmt.default_case {
+                    default_case.visit_statements_mut(visit);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Expressions of the statement itself, without those of nested statements
+    fn expressions_mut(&mut self) -> Vec<&mut Expression> {
+        match self {
+            Statement::VarDecl(var_decl) => var_decl.value.iter_mut().collect(),
+            Statement::Assignment(assignment) => vec![&mut assignment.target, &mut assignment.value],
+            Statement::Destructure(destructure) => vec![&mut destructure.value],
+            Statement::Expression(expr) => vec![expr],
+            Statement::If(if_stmt) => vec![&mut if_stmt.condition],
+            Statement::For(for_stmt) => for_stmt.condition.iter_mut().collect(),
+            Statement::ForIn(for_in) => vec![&mut for_in.iterable],
+            Statement::While(while_stmt) => vec![&mut while_stmt.condition],
+            Statement::Switch(SwitchStatement { expr, cases, .. }) => {
+                std::iter::once(expr).chain(cases.iter_mut().map(|case| &mut case.value)).collect()
+            }
+            Statement::Return(value) => value.iter_mut().collect(),
+            Statement::Break | Statement::Continue => Vec::new(),
+        }
+    }
+}
+
+impl Expression {
+    /// Отмечает `int_only` у выражения и вложенных в него; `true`, если
+    /// значение выражения — int, посчитанный из литералов и переменных `ints`
+    fn mark_int(&mut self, ints: &HashSet<String>) -> bool {
+        match self {
+            Expression::Literal(value) => matches!(value, ChifValue::Int(_)),
+            Expression::Identifier(name) => ints.contains(name),
+            Expression::Unary(unary_op) => unary_op.operand.mark_int(ints) && unary_op.operator == UnaryOperator::Minus,
+            Expression::Binary(binary_op) => {
+                let left = binary_op.left.mark_int(ints);
+                let right = binary_op.right.mark_int(ints);
+                binary_op.int_only = left && right && !matches!(binary_op.operator, BinaryOperator::And | BinaryOperator::Or);
+                binary_op.int_only && binary_op.operator.is_arithmetic()
+            }
+            Expression::Call(call) => {
+                for arg in call.args.iter_mut().chain(call.named_args.iter_mut().map(|(_, arg)| arg)) {
+                    arg.mark_int(ints);
+                }
+                false
+            }
+            Expression::MethodCall(method_call) => {
+                method_call.object.mark_int(ints);
+                for arg in &mut method_call.args {
+                    arg.mark_int(ints);
+                }
+                false
+            }
+            Expression::Index(index_access) => {
+                index_access.object.mark_int(ints);
+                for index in &mut index_access.indices {
+                    index.mark_int(ints);
+                }
+                false
+            }
+            Expression::ArrayLiteral(elements) | Expression::TupleLiteral(elements) => {
+                for element in elements {
+                    element.mark_int(ints);
+                }
+                false
+            }
+            Expression::MapLiteral(entries) => {
+                for (key, value) in entries {
+                    key.mark_int(ints);
+                    value.mark_int(ints);
+                }
+                false
+            }
+            Expression::StructLiteral(struct_literal) => {
+                for (_, value) in &mut struct_literal.fields {
+                    value.mark_int(ints);
+                }
+                if let Some(base) = &mut struct_literal.base {
+                    base.mark_int(ints);
+                }
+                false
+            }
+            Expression::FieldAccess(FieldAccess { object: inner, .. })
+            | Expression::Reference(AddressOf { target: inner, .. })
+            | Expression::Dereference(inner) => {
+                inner.mark_int(ints);
+                false
+            }
+        }
+    }
+}
+
+impl Item {
+    /// `true`, если функция, методы или значение глобальной переменной вызывают
+    /// методы встроенного объекта `object`, например `args.count()`
+    pub fn calls_methods_of(&self, object: &str) -> bool {
+        let functions = match self {
+            Item::Function(func) => std::slice::from_ref(func),
+            Item::StructImpl(impl_block) => impl_block.methods.as_slice(),
+            Item::Struct(_) | Item::Import(_) | Item::Global(_) => &[],
+        };
+        let mut calls = MethodCallsOf { object, found: false };
+        for func in functions {
+            func.body.walk(&mut calls);
+        }
+        if let Item::Global(VarDecl { value: Some(value), .. }) = self {
+            value.walk(&mut calls);
+        }
+        calls.found
+    }
+}
+
+struct MethodCallsOf<'o> {
+    object: &'o str,
+    found: bool,
+}
+
+impl Visitor<'_> for MethodCallsOf<'_> {
+    fn expression(&mut self, expression: &Expression) {
+        if let Expression::MethodCall(method_call) = expression {
+            self.found |= matches!(method_call.object.as_ref(), Expression::Identifier(name) if name == self.object);
+        }
+    }
+}
+
+impl BinaryOperator {
+    /// `+ - * / %`: у int-операндов результат тоже int
+    pub fn is_arithmetic(&self) -> bool {
+        matches!(
+            self,
+            BinaryOperator::Add | BinaryOperator::Subtract | BinaryOperator::Multiply | BinaryOperator::Divide | BinaryOperator::Modulo
+        )
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -192,6 +1059,13 @@ pub struct IndexAccess {
     pub indices: Vec<Expression>,
 }
 
+/// `&x` или `&mut x`; через `&mut` вызываемая функция может менять `x`
+#[derive(Debug, Clone)]
+pub struct AddressOf {
+    pub target: Box<Expression>,
+    pub mutable: bool,
+}
+
 #[derive(Debug, Clone)]
 pub struct FieldAccess {
     pub object: Box<Expression>,
@@ -202,4 +1076,6 @@ pub struct FieldAccess {
 pub struct StructLiteral {
     pub struct_name: String,
     pub fields: Vec<(String, Expression)>,
+    /// `Point { x = 1, ..p }`: поля, которых нет в `fields`, берутся из `base`
+    pub base: Option<Box<Expression>>,
 }
\ No newline at end of file
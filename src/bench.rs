@@ -0,0 +1,163 @@
+use crate::ast::Program;
+use crate::error::{ChifError, Result};
+use crate::interpreter::Interpreter;
+use std::fmt;
+use std::time::{Duration, Instant};
+
+/// Параметры запуска `rono bench`
+#[derive(Debug, Clone)]
+pub struct BenchOptions {
+    pub function: String,
+    pub setup_function: Option<String>,
+    pub iterations: usize,
+    pub warmup: usize,
+    pub remove_outliers: bool,
+}
+
+impl BenchOptions {
+    pub fn new(function: &str) -> Self {
+        Self {
+            function: function.to_string(),
+            setup_function: None,
+            iterations: 1000,
+            warmup: 100,
+            remove_outliers: false,
+        }
+    }
+}
+
+/// Статистика по времени одной итерации
+#[derive(Debug, Clone, PartialEq)]
+pub struct BenchStats {
+    pub samples: usize,
+    pub min: Duration,
+    pub max: Duration,
+    pub median: Duration,
+    pub mean: Duration,
+    pub p99: Duration,
+}
+
+impl BenchStats {
+    /// Считает статистику по замерам; при `remove_outliers` отбрасывает
+    /// самый медленный 1% (но всегда оставляет хотя бы один замер)
+    pub fn from_samples(mut samples: Vec<Duration>, remove_outliers: bool) -> Option<Self> {
+        if samples.is_empty() {
+            return None;
+        }
+
+        samples.sort();
+        if remove_outliers {
+            let dropped = samples.len() / 100;
+            samples.truncate(samples.len() - dropped);
+        }
+
+        let count = samples.len();
+        if count == 0 {
+            return None;
+        }
+        // Сумма и середина считаются в наносекундах u128: сумма Duration может
+        // переполниться, а число замеров — не влезть в u32
+        let total: u128 = samples.iter().map(Duration::as_nanos).sum();
+        let median = if count % 2 == 1 {
+            samples[count / 2]
+        } else {
+            from_nanos((samples[count / 2 - 1].as_nanos() + samples[count / 2].as_nanos()) / 2)
+        };
+
+        Some(Self {
+            samples: count,
+            min: samples[0],
+            max: samples[count - 1],
+            median,
+            mean: from_nanos(total / count as u128),
+            p99: percentile(&samples, 99),
+        })
+    }
+}
+
+/// Среднее замеров не больше самого долгого из них, поэтому влезает в Duration
+fn from_nanos(nanos: u128) -> Duration {
+    Duration::new((nanos / 1_000_000_000) as u64, (nanos % 1_000_000_000) as u32)
+}
+
+/// Перцентиль по методу nearest-rank для отсортированных замеров
+fn percentile(sorted: &[Duration], p: usize) -> Duration {
+    let rank = (sorted.len() as f64 * p as f64 / 100.0).ceil() as usize;
+    sorted[rank.saturating_sub(1).min(sorted.len() - 1)]
+}
+
+/// Результат бенчмарка: имя функции и статистика
+#[derive(Debug, Clone)]
+pub struct BenchReport {
+    pub function: String,
+    pub stats: BenchStats,
+}
+
+impl fmt::Display for BenchReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "Benchmark: {} ({} samples)", self.function, self.stats.samples)?;
+        writeln!(f, "{:<8} {:>14}", "stat", "time")?;
+        writeln!(f, "{:-<8} {:->14}", "", "")?;
+        let rows = [
+            ("min", self.stats.min),
+            ("max", self.stats.max),
+            ("median", self.stats.median),
+            ("mean", self.stats.mean),
+            ("p99", self.stats.p99),
+        ];
+        for (name, value) in rows {
+            writeln!(f, "{:<8} {:>14}", name, format_duration(value))?;
+        }
+        Ok(())
+    }
+}
+
+pub(crate) fn format_duration(d: Duration) -> String {
+    let nanos = d.as_nanos();
+    if nanos < 1_000 {
+        format!("{} ns", nanos)
+    } else if nanos < 1_000_000 {
+        format!("{:.2} µs", nanos as f64 / 1_000.0)
+    } else if nanos < 1_000_000_000 {
+        format!("{:.2} ms", nanos as f64 / 1_000_000.0)
+    } else {
+        format!("{:.2} s", d.as_secs_f64())
+    }
+}
+
+/// Запускает функцию программы `iterations` раз в интерпретаторе и замеряет
+/// каждую итерацию. Перед замерами выполняются setup-функция (один раз; данные
+/// для замеров она готовит в глобальных переменных) и `warmup` прогревочных вызовов.
+pub fn run_benchmark(program: &Program, options: &BenchOptions) -> Result<BenchReport> {
+    if options.iterations == 0 {
+        return Err(ChifError::InvalidOperation {
+            message: "bench requires at least one iteration".to_string(),
+        });
+    }
+
+    let mut interpreter = Interpreter::new();
+    interpreter.load(program)?;
+
+    if let Some(setup) = &options.setup_function {
+        interpreter.call_function_by_name(setup)?;
+    }
+
+    for _ in 0..options.warmup {
+        interpreter.call_function_by_name(&options.function)?;
+    }
+
+    let mut samples = Vec::with_capacity(options.iterations);
+    for _ in 0..options.iterations {
+        let start = Instant::now();
+        interpreter.call_function_by_name(&options.function)?;
+        samples.push(start.elapsed());
+    }
+
+    let stats = BenchStats::from_samples(samples, options.remove_outliers)
+        .expect("at least one sample was collected");
+
+    Ok(BenchReport {
+        function: options.function.clone(),
+        stats,
+    })
+}
@@ -0,0 +1,87 @@
+#[cfg(test)]
+mod tests {
+    use crate::bench::{run_benchmark, BenchOptions, BenchStats};
+    use crate::lexer::Lexer;
+    use crate::parser::Parser;
+    use crate::ast::Program;
+    use std::time::Duration;
+
+    fn parse(source: &str) -> Program {
+        let tokens = Lexer::new(source).tokenize().expect("source should tokenize");
+        Parser::new(tokens).parse().expect("source should parse")
+    }
+
+    fn micros(values: &[u64]) -> Vec<Duration> {
+        values.iter().map(|v| Duration::from_micros(*v)).collect()
+    }
+
+    #[test]
+    fn test_stats_from_samples() {
+        let stats = BenchStats::from_samples(micros(&[5, 1, 4, 2, 3]), false).unwrap();
+        assert_eq!(stats.samples, 5);
+        assert_eq!(stats.min, Duration::from_micros(1));
+        assert_eq!(stats.max, Duration::from_micros(5));
+        assert_eq!(stats.median, Duration::from_micros(3));
+        assert_eq!(stats.mean, Duration::from_micros(3));
+        assert_eq!(stats.p99, Duration::from_micros(5));
+
+        assert!(BenchStats::from_samples(Vec::new(), false).is_none());
+    }
+
+    #[test]
+    fn test_stats_of_long_samples_do_not_overflow() {
+        // Сумма трёх таких замеров больше Duration::MAX
+        let long = Duration::from_secs(u64::MAX / 2);
+        let stats = BenchStats::from_samples(vec![long, long, long + Duration::from_secs(3)], false).unwrap();
+        assert_eq!(stats.mean, long + Duration::from_secs(1));
+        assert_eq!(stats.median, long);
+
+        let stats = BenchStats::from_samples(vec![long, long + Duration::from_secs(2)], false).unwrap();
+        assert_eq!(stats.median, long + Duration::from_secs(1));
+        assert_eq!(stats.mean, long + Duration::from_secs(1));
+    }
+
+    #[test]
+    fn test_stats_remove_outliers_drops_slowest_percent() {
+        let mut values: Vec<u64> = (1..=200).collect();
+        values[0] = 1_000_000;
+        values[1] = 900_000;
+        let stats = BenchStats::from_samples(micros(&values), true).unwrap();
+        assert_eq!(stats.samples, 198);
+        assert_eq!(stats.max, Duration::from_micros(200));
+    }
+
+    #[test]
+    fn test_bench_runs_function_with_setup_globals() {
+        let program = parse(r#"
+            var counter: int = 0;
+            var step: int = 0;
+
+            fn setup() {
+                step = 2;
+            }
+
+            fn work() {
+                counter = counter + step;
+            }
+
+            chif main() {
+            }
+        "#);
+
+        let mut options = BenchOptions::new("work");
+        options.setup_function = Some("setup".to_string());
+        options.iterations = 50;
+        options.warmup = 5;
+
+        let report = run_benchmark(&program, &options).expect("benchmark should run");
+        assert_eq!(report.stats.samples, 50);
+        assert!(report.to_string().contains("median"));
+    }
+
+    #[test]
+    fn test_bench_unknown_function_fails() {
+        let program = parse("chif main() { }");
+        assert!(run_benchmark(&program, &BenchOptions::new("missing")).is_err());
+    }
+}
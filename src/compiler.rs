@@ -1,12 +1,33 @@
-use crate::ast::Program;
+use crate::ast::{EntryPoint, Program};
+use crate::header::{check_exportable, generate_c_header};
 use crate::semantic::SemanticAnalyzer;
-use crate::ir_gen::IRGenerator;
+use crate::diagnostics::{WarningAction, WarningPolicy};
+use crate::ir_gen::{CodegenContext, IRGenerator};
+use crate::lang::LangVersion;
+use crate::logger;
+use crate::progress::{self, CompileSummary, ProgressEvent, ProgressSink, Stage};
+use crate::style::{self, Stream, Style};
+pub use crate::error::SourceLocation;
 
+use cranelift::codegen::isa::OwnedTargetIsa;
 use cranelift::prelude::settings::{self, Configurable};
 use cranelift_object::{ObjectBuilder, ObjectModule};
+use object::{Object, ObjectSection, ObjectSymbol, RelocationTarget, SectionKind};
 use target_lexicon::Triple;
 use thiserror::Error;
+use std::collections::{BTreeSet, HashMap};
 use std::fs;
+use std::hash::{Hash, Hasher};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Instant;
+
+/// Source of the C runtime (`rono_print_int`, `rono_http_get`, ...). It is part of
+/// the binary, so compiling does not depend on the working directory
+pub const RUNTIME_SOURCE: &str = include_str!("runtime.c");
+
+static NEXT_RUNTIME_BUILD: AtomicUsize = AtomicUsize::new(0);
+static NEXT_PENDING_OUTPUT: AtomicUsize = AtomicUsize::new(0);
 
 #[derive(Debug, Error)]
 pub enum CompilerError {
@@ -19,6 +40,10 @@ pub enum CompilerError {
     #[error("Semantic analysis error: {0}")]
     SemanticAnalysis(String),
     
+    /// Ошибка анализатора; хранится целиком, чтобы не потерять её код
+    #[error("Semantic analysis error: {0}")]
+    Analysis(Box<crate::semantic::SemanticError>),
+    
     #[error("IR generation error: {0}")]
     IRGeneration(String),
     
@@ -31,6 +56,9 @@ pub enum CompilerError {
     #[error("Object write error: {0}")]
     ObjectWrite(String),
     
+    #[error("Output error: {0}")]
+    Output(String),
+    
     #[error("IO error: {0}")]
     Io(#[from] std::io::Error),
     
@@ -38,33 +66,6 @@ pub enum CompilerError {
     Parse(#[from] crate::error::ChifError),
 }
 
-#[derive(Debug, Clone)]
-pub struct SourceLocation {
-    pub file: String,
-    pub line: usize,
-    pub column: usize,
-}
-
-impl std::fmt::Display for SourceLocation {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{}:{}:{}", self.file, self.line, self.column)
-    }
-}
-
-impl SourceLocation {
-    pub fn new(file: String, line: usize, column: usize) -> Self {
-        Self { file, line, column }
-    }
-    
-    pub fn unknown() -> Self {
-        Self {
-            file: "<unknown>".to_string(),
-            line: 0,
-            column: 0,
-        }
-    }
-}
-
 #[derive(Debug, Clone, Copy)]
 pub enum Target {
     X86_64Linux,
@@ -103,11 +104,123 @@ impl OptLevel {
     }
 }
 
+/// Result of `compile_to_object`: the object file plus a summary of its symbols,
+/// so codegen can be inspected without linking or running anything.
+#[derive(Debug, Clone)]
+pub struct CompiledArtifact {
+    pub object: Vec<u8>,
+    /// Symbols defined in the object, sorted by name
+    pub defined_symbols: Vec<DefinedSymbol>,
+    /// Undefined symbols referenced by relocations, i.e. what the linker has to provide
+    pub imported_symbols: Vec<String>,
+    /// Cranelift IR per function; only filled when compiling with debug info
+    pub function_ir: HashMap<String, String>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct DefinedSymbol {
+    pub name: String,
+    pub size: u64,
+    pub section: SymbolSection,
+}
+
+/// Kind of section a defined symbol lives in
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SymbolSection {
+    Text,
+    Data,
+    ReadOnlyData,
+    Other,
+}
+
+impl CompiledArtifact {
+    pub fn from_object(object: Vec<u8>, function_ir: HashMap<String, String>) -> Result<Self, CompilerError> {
+        let file = object::File::parse(&*object)
+            .map_err(|e| CompilerError::ObjectWrite(format!("Failed to parse emitted object: {}", e)))?;
+        
+        let mut defined_symbols = Vec::new();
+        for symbol in file.symbols() {
+            let name = match symbol.name() {
+                Ok(name) if !name.is_empty() => name,
+                _ => continue,
+            };
+            if !symbol.is_definition() {
+                continue;
+            }
+            let section = match symbol.section_index().and_then(|index| file.section_by_index(index).ok()) {
+                Some(section) => match section.kind() {
+                    SectionKind::Text => SymbolSection::Text,
+                    SectionKind::Data | SectionKind::UninitializedData => SymbolSection::Data,
+                    SectionKind::ReadOnlyData | SectionKind::ReadOnlyString => SymbolSection::ReadOnlyData,
+                    _ => SymbolSection::Other,
+                },
+                None => SymbolSection::Other,
+            };
+            defined_symbols.push(DefinedSymbol {
+                name: name.to_string(),
+                size: symbol.size(),
+                section,
+            });
+        }
+        defined_symbols.sort_by(|a, b| a.name.cmp(&b.name));
+        
+        // Every runtime function is declared up front, so only count imports that are actually used
+        let mut imported = BTreeSet::new();
+        for section in file.sections() {
+            for (_, relocation) in section.relocations() {
+                if let RelocationTarget::Symbol(index) = relocation.target() {
+                    if let Ok(symbol) = file.symbol_by_index(index) {
+                        if symbol.is_undefined() {
+                            if let Ok(name) = symbol.name() {
+                                imported.insert(name.to_string());
+                            }
+                        }
+                    }
+                }
+            }
+        }
+        
+        Ok(Self {
+            object,
+            defined_symbols,
+            imported_symbols: imported.into_iter().collect(),
+            function_ir,
+        })
+    }
+    
+    pub fn defined_symbol(&self, name: &str) -> Option<&DefinedSymbol> {
+        self.defined_symbols.iter().find(|symbol| symbol.name == name)
+    }
+    
+    pub fn imports(&self, name: &str) -> bool {
+        self.imported_symbols.iter().any(|import| import == name)
+    }
+}
+
 pub struct Compiler {
     target: Target,
     optimization_level: OptLevel,
     debug_info: bool,
+    /// Built once in `new` and shared by every compilation of this compiler
+    isa: OwnedTargetIsa,
     diagnostics: Vec<CompilerDiagnostic>,
+    source_file: String,
+    source_text: String,
+    codegen_context: CodegenContext,
+    /// Prebuilt runtime object or archive from `--runtime`; the embedded runtime otherwise
+    runtime: Option<PathBuf>,
+    /// Linker command from `--linker`; `cc` otherwise
+    linker: Option<PathBuf>,
+    /// Entry function from `--entry`, used instead of `chif main`
+    entry: Option<EntryPoint>,
+    /// Compiles the prelude functions the program calls (off with `--no-prelude`)
+    prelude: bool,
+    /// `--allow`/`--deny`: which warnings are dropped and which become errors
+    warning_policy: WarningPolicy,
+    /// Receives module loading, stage and summary events while compiling
+    progress: Option<Box<dyn ProgressSink>>,
+    /// Threads compiling function bodies (`--jobs`); the number of CPUs by default
+    jobs: usize,
 }
 
 #[derive(Debug, Clone)]
@@ -143,6 +256,13 @@ impl Compiler {
         let mut builder = settings::builder();
         builder.set("opt_level", &optimization_level.to_cranelift_opt_level().to_string())
             .map_err(|e| CompilerError::CodeGeneration(format!("Failed to set optimization level: {}", e)))?;
+            
+        // Enable PIC for macOS ARM64
+        #[cfg(target_os = "macos")]
+        {
+            builder.set("is_pic", "true")
+                .map_err(|e| CompilerError::CodeGeneration(format!("Failed to set PIC: {}", e)))?;
+        }
         
         let flags = settings::Flags::new(builder);
         let isa = cranelift::codegen::isa::lookup(triple.clone())
@@ -154,118 +274,358 @@ impl Compiler {
             target,
             optimization_level,
             debug_info,
+            isa,
             diagnostics: Vec::new(),
+            source_file: SourceLocation::unknown().file,
+            source_text: String::new(),
+            codegen_context: CodegenContext::default(),
+            runtime: None,
+            linker: None,
+            entry: None,
+            prelude: true,
+            warning_policy: WarningPolicy::default(),
+            progress: None,
+            jobs: std::thread::available_parallelism().map_or(1, |jobs| jobs.get()),
         })
     }
     
+    /// Array literals above this size are moved from the stack to static data
+    pub fn set_max_stack_literal_bytes(&mut self, bytes: u32) {
+        self.codegen_context.max_stack_literal_bytes = bytes;
+    }
+    
+    /// Functions whose stack frame exceeds this size produce a warning
+    pub fn set_stack_warning_bytes(&mut self, bytes: u32) {
+        self.codegen_context.stack_warning_bytes = bytes;
+    }
+    
+    /// Language level the program is analyzed and compiled at
+    pub fn set_lang_version(&mut self, version: LangVersion) {
+        self.codegen_context.lang_version = version;
+    }
+    
+    /// Links `path` (an object file or static library defining the `rono_*`
+    /// functions) instead of the embedded runtime
+    pub fn set_runtime(&mut self, path: impl Into<PathBuf>) {
+        self.runtime = Some(path.into());
+    }
+    
+    /// Links with `command` (called like `cc`: `-o <output> <objects> <libraries>`)
+    /// instead of `cc`
+    pub fn set_linker(&mut self, command: impl Into<PathBuf>) {
+        self.linker = Some(command.into());
+    }
+    
+    /// Starts the executable at `entry` (`--entry app.start`): the generated
+    /// `main` calls it and exits with its result
+    pub fn set_entry(&mut self, entry: Option<EntryPoint>) {
+        self.entry = entry;
+    }
+    
+    /// Makes the prelude available to the program; it is on by default
+    pub fn set_prelude(&mut self, enabled: bool) {
+        self.prelude = enabled;
+    }
+    
+    /// Drops the warnings `policy` allows and reports the ones it denies as errors
+    pub fn set_warning_policy(&mut self, policy: WarningPolicy) {
+        self.warning_policy = policy;
+    }
+    
+    /// Reports progress to `sink` during `compile`, `compile_library` and
+    /// `compile_to_object`; without one, events go to the thread's sink, if any
+    pub fn set_progress_sink(&mut self, sink: Option<Box<dyn ProgressSink>>) {
+        self.progress = sink;
+    }
+    
+    /// Generates the IR of function bodies and compiles it to machine code on
+    /// `jobs` threads. The object is the same for any number; 1 does everything
+    /// on the calling thread, which is easier to follow when debugging the code
+    /// generator
+    pub fn set_jobs(&mut self, jobs: usize) {
+        self.jobs = jobs.max(1);
+    }
+    
+    /// Sets the source file being compiled so errors can name it and its
+    /// imports are resolved from its directory
+    pub fn set_source(&mut self, source_file: &str, source_text: &str) {
+        self.source_file = source_file.to_string();
+        self.source_text = source_text.to_string();
+    }
+    
+    /// Directory of the file from `set_source`; without one, imports are
+    /// resolved from the working directory
+    fn source_dir(&self) -> Option<PathBuf> {
+        if self.source_file == SourceLocation::unknown().file {
+            return None;
+        }
+        Path::new(&self.source_file).parent().map(Path::to_path_buf)
+    }
+    
     pub fn compile(&mut self, ast: &Program, output_path: &str) -> Result<(), CompilerError> {
-        println!("Starting compilation for target: {:?}", self.target);
-        println!("Optimization level: {:?}", self.optimization_level);
-        println!("Debug info: {}", self.debug_info);
+        self.with_progress(|compiler| compiler.compile_executable(ast, output_path))
+    }
+    
+    fn compile_executable(&mut self, ast: &Program, output_path: &str) -> Result<(), CompilerError> {
+        let started = Instant::now();
+        if ast.entry_point(self.entry.as_ref()).map_err(CompilerError::SemanticAnalysis)?.is_none() {
+            return Err(CompilerError::SemanticAnalysis(
+                "Executable has no 'chif main' function; import one with 'use (main)', pass --entry, or compile with --crate-type lib to build a library".to_string()
+            ));
+        }
         
-        // 1. Semantic analysis
-        println!("Performing semantic analysis...");
-        let mut analyzer = SemanticAnalyzer::new();
-        let analyzed_program = analyzer.analyze(ast)
-            .map_err(|e| CompilerError::SemanticAnalysis(e.to_string()))?;
+        let object_path = format!("build/{}.o", output_path);
+        let executable_path = format!("build/{}", output_path);
         
-        // 2. Setup Cranelift
-        println!("Setting up code generator...");
-        let triple = self.target.to_triple();
+        // The object and the executable are written under temporary names and
+        // replace the previous build only after linking succeeded
+        let mut outputs = PendingOutputs::default();
+        let object_file = outputs.add(Path::new(&object_path))?;
+        let executable_file = outputs.add(Path::new(&executable_path))?;
         
-        // Create ISA builder
-        let mut builder = settings::builder();
-        builder.set("opt_level", &self.optimization_level.to_cranelift_opt_level().to_string())
-            .map_err(|e| CompilerError::CodeGeneration(format!("Failed to set optimization level: {}", e)))?;
-            
-        // Enable PIC for macOS ARM64
-        #[cfg(target_os = "macos")]
+        let (artifact, modules) = self.build_object(ast)?;
+        
+        // 5. Write object file
+        // Create build directory if it doesn't exist
+        std::fs::create_dir_all("build")?;
+        fs::write(&object_file, &artifact.object)?;
+        
+        // 6. Link to create executable
+        logger::verbose("Linking executable...");
+        let linking = Instant::now();
+        self.link_executable(&object_file, &executable_file)?;
+        #[cfg(unix)]
         {
-            builder.set("is_pic", "true")
-                .map_err(|e| CompilerError::CodeGeneration(format!("Failed to set PIC: {}", e)))?;
+            use std::os::unix::fs::PermissionsExt;
+            fs::set_permissions(&executable_file, fs::Permissions::from_mode(0o755))?;
         }
+        outputs.commit()?;
+        progress::stage_finished(Stage::Linking, linking);
         
-        let flags = settings::Flags::new(builder);
-        let isa = cranelift::codegen::isa::lookup(triple.clone())
-            .map_err(|e| CompilerError::CodeGeneration(format!("Failed to lookup ISA: {}", e)))?
-            .finish(flags)
-            .map_err(|e| CompilerError::CodeGeneration(format!("Failed to create ISA: {}", e)))?;
+        logger::verbose(format!("Object file created: {}", object_path));
+        logger::verbose(format!("Executable created: {}", executable_path));
+        Self::report_finished(&artifact, modules, started);
+        Ok(())
+    }
+    
+    /// Compiles a program without `chif main` to `build/<output>.o` plus the
+    /// runtime object `runtime.o` in the same directory, for linking into a
+    /// C program. Top-level functions keep their Rono names as exported symbols
+    pub fn compile_library(&mut self, ast: &Program, output_path: &str) -> Result<String, CompilerError> {
+        self.with_progress(|compiler| compiler.compile_library_object(ast, output_path))
+    }
+    
+    fn compile_library_object(&mut self, ast: &Program, output_path: &str) -> Result<String, CompilerError> {
+        let started = Instant::now();
+        if ast.entry_point(self.entry.as_ref()).map_err(CompilerError::SemanticAnalysis)?.is_some() {
+            return Err(CompilerError::SemanticAnalysis(
+                "A library cannot define 'chif main' or another entry point".to_string()
+            ));
+        }
+        check_exportable(ast)?;
         
-        let mut object_builder = ObjectBuilder::new(
-            isa,
+        let object_path = format!("build/{}.o", output_path);
+        let runtime_path = library_runtime_path(&object_path);
+        if runtime_path == Path::new(&object_path) {
+            return Err(CompilerError::Output(format!(
+                "Library '{}' would overwrite the runtime object; choose another output name", output_path
+            )));
+        }
+        std::fs::create_dir_all("build")?;
+        if let Some(dir) = runtime_path.parent().filter(|dir| !dir.is_dir()) {
+            return Err(CompilerError::Output(format!("Output directory '{}' does not exist", dir.display())));
+        }
+        
+        let mut outputs = PendingOutputs::default();
+        let object_file = outputs.add(Path::new(&object_path))?;
+        let runtime_file = outputs.add(&runtime_path)?;
+        
+        let (artifact, modules) = self.build_object(ast)?;
+        fs::write(&object_file, &artifact.object)?;
+        fs::copy(self.runtime_object()?, &runtime_file)?;
+        outputs.commit()?;
+        logger::verbose(format!("Object file created: {}", object_path));
+        logger::verbose(format!("Runtime object created: {}", runtime_path.display()));
+        
+        Self::report_finished(&artifact, modules, started);
+        Ok(object_path)
+    }
+    
+    /// Runs analysis and code generation and returns the object file without linking
+    pub fn compile_to_object(&mut self, ast: &Program) -> Result<CompiledArtifact, CompilerError> {
+        self.with_progress(|compiler| {
+            let started = Instant::now();
+            let (artifact, modules) = compiler.build_object(ast)?;
+            Self::report_finished(&artifact, modules, started);
+            Ok(artifact)
+        })
+    }
+    
+    /// Installs the compiler's own progress sink for the thread while `compile` runs
+    fn with_progress<T>(&mut self, compile: impl FnOnce(&mut Self) -> T) -> T {
+        let Some(sink) = self.progress.take() else {
+            return compile(self);
+        };
+        let previous = progress::set_sink(Some(sink));
+        let result = compile(self);
+        self.progress = progress::set_sink(previous);
+        result
+    }
+    
+    fn report_finished(artifact: &CompiledArtifact, modules: usize, started: Instant) {
+        progress::emit(ProgressEvent::Finished(CompileSummary {
+            modules,
+            functions: artifact.defined_symbols.iter().filter(|symbol| symbol.section == SymbolSection::Text).count(),
+            object_bytes: artifact.object.len(),
+            elapsed: started.elapsed(),
+        }));
+    }
+    
+    /// Analysis and code generation; also returns how many source files
+    /// (the program and its imported modules) went into the object
+    fn build_object(&mut self, ast: &Program) -> Result<(CompiledArtifact, usize), CompilerError> {
+        progress::start(ast);
+        logger::verbose(format!("Starting compilation for target: {:?}", self.target));
+        logger::verbose(format!("Optimization level: {:?}", self.optimization_level));
+        logger::verbose(format!("Debug info: {}", self.debug_info));
+        
+        // 1. Semantic analysis
+        logger::verbose("Performing semantic analysis...");
+        let mut started = Instant::now();
+        let mut analyzer = SemanticAnalyzer::new();
+        analyzer.lang_version = self.codegen_context.lang_version;
+        analyzer.entry = self.entry.clone();
+        analyzer.prelude = self.prelude;
+        analyzer.source_dir = self.source_dir();
+        let mut analyzed_program = analyzer.analyze(ast)
+            .map_err(|e| CompilerError::Analysis(Box::new(e)))?;
+        analyzed_program.source_file = self.source_file.clone();
+        analyzed_program.source_text = self.source_text.clone();
+        
+        for warning in std::mem::take(&mut analyzer.warnings) {
+            let mut location = warning.location().clone();
+            if location.file == "<unknown>" {
+                location.file = self.source_file.clone();
+            }
+            self.add_warning(location, warning.to_string(), Some(warning.code().to_string()));
+        }
+        
+        progress::stage_finished(Stage::Analysis, started);
+        let modules = 1 + analyzer.parsed_modules.len();
+        
+        // 2. Setup Cranelift
+        // Every compilation gets a fresh module and IR generator, so no symbols,
+        // string constants or data ids carry over; only the ISA is shared
+        logger::verbose("Setting up code generator...");
+        let object_builder = ObjectBuilder::new(
+            self.isa.clone(),
             "rono_program".to_string(),
             cranelift_module::default_libcall_names(),
         ).map_err(|e| CompilerError::CodeGeneration(format!("Failed to create object builder: {}", e)))?;
         
-        // Enable PIC for macOS ARM64
-        #[cfg(target_os = "macos")]
-        {
-            // This should help with text relocations
-        }
-        
         let module = ObjectModule::new(object_builder);
         
         // 3. IR generation
-        println!("Generating IR...");
+        logger::verbose("Generating IR...");
+        started = Instant::now();
         let mut ir_generator = IRGenerator::new(module);
+        ir_generator.module_cache = std::mem::take(&mut analyzer.parsed_modules);
+        ir_generator.context = self.codegen_context.clone();
+        ir_generator.capture_ir = self.debug_info;
+        ir_generator.jobs = self.jobs;
+        ir_generator.source_dir = self.source_dir();
+        ir_generator.entry = ast.entry_point(self.entry.as_ref()).map_err(CompilerError::SemanticAnalysis)?;
         ir_generator.generate(&analyzed_program)
-            .map_err(|e| CompilerError::IRGeneration(e.to_string()))?;
+            .map_err(|e| CompilerError::IRGeneration(format!("{}: {}", analyzed_program.source_file, e)))?;
+        
+        for warning in std::mem::take(&mut ir_generator.context.warnings) {
+            self.add_warning(SourceLocation::new(analyzed_program.source_file.clone(), 0, 0), warning, None);
+        }
+        
+        progress::stage_finished(Stage::IrGeneration, started);
         
         // 4. Code generation and object file creation
-        println!("Generating object file...");
+        logger::verbose("Generating object file...");
+        started = Instant::now();
+        let function_ir = std::mem::take(&mut ir_generator.function_ir);
         let object_product = ir_generator.finalize().finish();
         
-        // 5. Write object file
         let object_bytes = object_product.emit()
             .map_err(|e| CompilerError::ObjectWrite(e.to_string()))?;
+        progress::stage_finished(Stage::ObjectEmission, started);
+        
+        Ok((CompiledArtifact::from_object(object_bytes, function_ir)?, modules))
+    }
+    
+    /// Writes a C header for the program's non-main functions to `build/<output>.h`
+    pub fn emit_header(&self, ast: &Program, output_path: &str) -> Result<String, CompilerError> {
+        let library_name = std::path::Path::new(output_path)
+            .file_stem()
+            .and_then(|s| s.to_str())
+            .unwrap_or("rono");
+        let header = generate_c_header(ast, library_name)?;
         
-        // Create build directory if it doesn't exist
         std::fs::create_dir_all("build")?;
+        let header_path = format!("build/{}.h", output_path);
+        fs::write(&header_path, header)?;
         
-        let object_path = format!("build/{}.o", output_path);
-        let executable_path = format!("build/{}", output_path);
+        Ok(header_path)
+    }
+    
+    /// The runtime object to link: the `--runtime` override, or the embedded
+    /// runtime compiled once into a cache directory named after its contents
+    fn runtime_object(&self) -> Result<PathBuf, CompilerError> {
+        use std::process::Command;
         
-        fs::write(&object_path, object_bytes)?;
+        if let Some(runtime) = &self.runtime {
+            if !runtime.is_file() {
+                return Err(CompilerError::CodeGeneration(format!("Runtime '{}' not found", runtime.display())));
+            }
+            return Ok(runtime.clone());
+        }
         
-        println!("Object file created: {}", object_path);
+        let dir = runtime_cache_dir();
+        let runtime_obj = dir.join("runtime.o");
+        if runtime_obj.is_file() {
+            return Ok(runtime_obj);
+        }
         
-        // 6. Link to create executable
-        println!("Linking executable...");
-        self.link_executable(&object_path, &executable_path)?;
+        logger::verbose(format!("Compiling runtime library into {}...", dir.display()));
+        fs::create_dir_all(&dir)?;
+        // Several compilers may fill the cache at once: each builds under its own
+        // names and the finished object is renamed into place
+        let unique = format!("{}-{}", std::process::id(), NEXT_RUNTIME_BUILD.fetch_add(1, Ordering::SeqCst));
+        let source = dir.join(format!("runtime-{}.c", unique));
+        let partial = dir.join(format!("runtime-{}.o", unique));
+        fs::write(&source, RUNTIME_SOURCE)?;
         
-        Ok(())
-    }
-    
-    pub fn compile_to_object(&mut self, _ast: &Program) -> Result<Vec<u8>, CompilerError> {
-        // TODO: Implement object file generation
-        Err(CompilerError::CodeGeneration("Object compilation not yet implemented".to_string()))
+        let compile_output = Command::new("cc")
+            .arg("-c")
+            .arg(&source)
+            .arg("-o")
+            .arg(&partial)
+            .output();
+        let _ = fs::remove_file(&source);
+        let compile_output = compile_output
+            .map_err(|e| CompilerError::CodeGeneration(format!("Failed to compile runtime: {}", e)))?;
+        
+        if !compile_output.status.success() {
+            let _ = fs::remove_file(&partial);
+            let stderr = String::from_utf8_lossy(&compile_output.stderr);
+            return Err(CompilerError::CodeGeneration(format!("Runtime compilation failed: {}", stderr)));
+        }
+        fs::rename(&partial, &runtime_obj)?;
+        Ok(runtime_obj)
     }
     
-    fn link_executable(&self, object_file: &str, output_path: &str) -> Result<(), CompilerError> {
+    fn link_executable(&self, object_file: &Path, output_path: &Path) -> Result<(), CompilerError> {
         use std::process::Command;
         
         // First, compile runtime library if needed
-        let runtime_obj = "build/runtime.o";
-        if !std::path::Path::new(runtime_obj).exists() {
-            println!("Compiling runtime library...");
-            std::fs::create_dir_all("build")?;
-            let mut compile_cmd = Command::new("cc");
-            compile_cmd.arg("-c")
-                      .arg("src/runtime.c")
-                      .arg("-o")
-                      .arg(runtime_obj);
-            
-            let compile_output = compile_cmd.output()
-                .map_err(|e| CompilerError::CodeGeneration(format!("Failed to compile runtime: {}", e)))?;
-            
-            if !compile_output.status.success() {
-                let stderr = String::from_utf8_lossy(&compile_output.stderr);
-                return Err(CompilerError::CodeGeneration(format!("Runtime compilation failed: {}", stderr)));
-            }
-        }
+        let runtime_obj = self.runtime_object()?;
         
         // Use system linker to create executable
-        let mut cmd = Command::new("cc"); // Use system C compiler as linker
+        let mut cmd = Command::new(self.linker.as_deref().unwrap_or(Path::new("cc"))); // Use system C compiler as linker
         cmd.arg("-o").arg(output_path);
         cmd.arg(object_file);
         cmd.arg(runtime_obj); // Link with runtime
@@ -285,6 +645,7 @@ impl Compiler {
         #[cfg(target_os = "linux")]
         {
             cmd.arg("-lc");
+            cmd.arg("-lm"); // fmod for float remainder
             cmd.arg("-lcurl"); // Link with libcurl
         }
         #[cfg(target_os = "windows")]
@@ -300,12 +661,17 @@ impl Compiler {
             let stderr = String::from_utf8_lossy(&output.stderr);
             return Err(CompilerError::CodeGeneration(format!("Linking failed: {}", stderr)));
         }
-        
-        println!("Executable created: {}", output_path);
         Ok(())
     }
 
-    pub fn add_diagnostic(&mut self, diagnostic: CompilerDiagnostic) {
+    pub fn add_diagnostic(&mut self, mut diagnostic: CompilerDiagnostic) {
+        if matches!(diagnostic.level, DiagnosticLevel::Warning) {
+            match self.warning_policy.action(diagnostic.code.as_deref()) {
+                WarningAction::Warn => {}
+                WarningAction::Allow => return,
+                WarningAction::Deny => diagnostic.level = DiagnosticLevel::Error,
+            }
+        }
         self.diagnostics.push(diagnostic);
     }
     
@@ -342,9 +708,19 @@ impl Compiler {
     
     pub fn print_diagnostics(&self) {
         for diagnostic in &self.diagnostics {
-            eprintln!("{}: {}: {}", diagnostic.level, diagnostic.location, diagnostic.message);
+            let role = match diagnostic.level {
+                DiagnosticLevel::Error => Style::Error,
+                DiagnosticLevel::Warning => Style::Warning,
+                DiagnosticLevel::Info => Style::Info,
+            };
+            let level = style::paint(&diagnostic.level, role, Stream::Stderr);
+            let mut text = format!("{}: {}: {}", level, diagnostic.location, diagnostic.message);
             if let Some(code) = &diagnostic.code {
-                eprintln!("  Code: {}", code);
+                text.push_str(&format!("\n  Code: {}", code));
+            }
+            match diagnostic.level {
+                DiagnosticLevel::Error => logger::error(text),
+                DiagnosticLevel::Warning | DiagnosticLevel::Info => logger::warn(text),
             }
         }
     }
@@ -352,6 +728,80 @@ impl Compiler {
     pub fn clear_diagnostics(&mut self) {
         self.diagnostics.clear();
     }
+    
+    pub fn diagnostics(&self) -> &[CompilerDiagnostic] {
+        &self.diagnostics
+    }
+    
+    /// Prepares the compiler for the next program: drops diagnostics and the
+    /// source set by `set_source`, keeping the target ISA and codegen context
+    pub fn reset(&mut self) {
+        self.clear_diagnostics();
+        self.source_file = SourceLocation::unknown().file;
+        self.source_text.clear();
+        self.codegen_context.warnings.clear();
+    }
+}
+
+/// Files of one build that are still being written. Each is created next to its
+/// target under a unique name and replaces the target only in `commit`; the
+/// ones not committed, after an error or a panic, are removed on drop
+#[derive(Default)]
+struct PendingOutputs {
+    /// (temporary file, target) pairs
+    files: Vec<(PathBuf, PathBuf)>,
+}
+
+impl PendingOutputs {
+    /// Temporary path for `target`, after checking that the target may be replaced
+    fn add(&mut self, target: &Path) -> Result<PathBuf, CompilerError> {
+        match fs::metadata(target) {
+            Ok(metadata) if metadata.is_dir() => {
+                return Err(CompilerError::Output(format!("'{}' is a directory", target.display())));
+            }
+            Ok(metadata) if metadata.permissions().readonly() => {
+                return Err(CompilerError::Output(format!("'{}' is not writable", target.display())));
+            }
+            _ => {}
+        }
+        let name = target.file_name().map_or_else(|| "output".into(), |name| name.to_string_lossy());
+        let unique = format!("{}-{}", std::process::id(), NEXT_PENDING_OUTPUT.fetch_add(1, Ordering::SeqCst));
+        let file = target.with_file_name(format!(".{}.{}.tmp", name, unique));
+        self.files.push((file.clone(), target.to_path_buf()));
+        Ok(file)
+    }
+    
+    /// Renames every file over its target
+    fn commit(mut self) -> Result<(), CompilerError> {
+        while let Some((file, target)) = self.files.first() {
+            fs::rename(file, target)?;
+            self.files.remove(0);
+        }
+        Ok(())
+    }
+}
+
+impl Drop for PendingOutputs {
+    fn drop(&mut self) {
+        for (file, _) in &self.files {
+            let _ = fs::remove_file(file);
+        }
+    }
+}
+
+/// Runtime object written next to the library object `object_path` by `compile_library`
+pub fn library_runtime_path(object_path: &str) -> PathBuf {
+    Path::new(object_path).with_file_name("runtime.o")
+}
+
+/// Cache directory of the compiled runtime. Its name depends on the runtime
+/// source and the compiler version, so a changed runtime is never mixed up
+/// with an object built from an older one
+fn runtime_cache_dir() -> PathBuf {
+    let mut hasher = std::collections::hash_map::DefaultHasher::new();
+    RUNTIME_SOURCE.hash(&mut hasher);
+    env!("CARGO_PKG_VERSION").hash(&mut hasher);
+    std::env::temp_dir().join(format!("rono-runtime-{:016x}", hasher.finish()))
 }
 
 // Helper function to detect host target
@@ -365,7 +815,7 @@ pub fn detect_host_target() -> Target {
         (target_lexicon::Architecture::Aarch64(_), target_lexicon::OperatingSystem::Linux) => Target::Aarch64Linux,
         (target_lexicon::Architecture::Aarch64(_), target_lexicon::OperatingSystem::Darwin) => Target::Aarch64MacOS,
         _ => {
-            eprintln!("Warning: Unsupported target architecture, defaulting to x86_64 Linux");
+            logger::warn("Warning: Unsupported target architecture, defaulting to x86_64 Linux");
             Target::X86_64Linux
         }
     }
@@ -0,0 +1,679 @@
+#[cfg(test)]
+mod tests {
+    use crate::ast::Program;
+    use crate::compiler::{detect_host_target, CompiledArtifact, Compiler, CompilerError, OptLevel, SymbolSection, Target};
+    use crate::lang::LangVersion;
+    use crate::lexer::Lexer;
+    use crate::parser::Parser;
+    use crate::progress::{ProgressEvent, ProgressSink, Stage};
+    use crate::semantic::SemanticAnalyzer;
+    use object::{Object, ObjectSection, SectionKind};
+    use std::cell::RefCell;
+    use std::fs;
+    use std::rc::Rc;
+    
+    fn compile_source(source: &str, debug: bool) -> CompiledArtifact {
+        let mut compiler = Compiler::new(detect_host_target(), OptLevel::None, debug)
+            .expect("compiler should be created for the host target");
+        compiler.compile_to_object(&parse(source)).expect("source should compile")
+    }
+    
+    fn parse(source: &str) -> Program {
+        let tokens = Lexer::new(source).tokenize().expect("source should tokenize");
+        Parser::new(tokens).parse().expect("source should parse")
+    }
+
+    #[test]
+    fn test_ir_errors_name_the_source_file() {
+        // Tuple variables pass analysis but cannot be lowered yet, so IR generation fails
+        let source = r#"
+            fn divmod(a: int, b: int) (int, int) {
+                ret (a / b, a - (a / b) * b);
+            }
+
+            chif main() {
+                let pair: (int, int) = divmod(7, 2);
+            }
+        "#;
+        let tokens = Lexer::new(source).tokenize().expect("source should tokenize");
+        let ast = Parser::new(tokens).parse().expect("source should parse");
+
+        let mut compiler = Compiler::new(detect_host_target(), OptLevel::None, false)
+            .expect("compiler should be created for the host target");
+        compiler.set_source("examples/divmod.rono", source);
+
+        match compiler.compile(&ast, "divmod_test") {
+            Err(CompilerError::IRGeneration(message)) => {
+                assert!(message.starts_with("examples/divmod.rono: "), "Unexpected message: {}", message);
+            }
+            other => panic!("Expected an IR generation error, got {:?}", other),
+        }
+    }
+    
+    #[test]
+    fn test_artifact_lists_defined_functions() {
+        let artifact = compile_source(r#"
+            fn add(a: int, b: int) int {
+                ret a + b;
+            }
+            
+            chif main() {
+                con.out(add(1, 2));
+            }
+        "#, false);
+        
+        for name in ["main", "add"] {
+            let symbol = artifact.defined_symbol(name)
+                .unwrap_or_else(|| panic!("'{}' should be defined, got {:?}", name, artifact.defined_symbols));
+            assert_eq!(symbol.section, SymbolSection::Text);
+            assert!(symbol.size > 0, "'{}' should have a non-zero size", name);
+        }
+        assert!(artifact.function_ir.is_empty(), "IR text is only collected with debug info");
+    }
+    
+    #[test]
+    fn test_print_float_is_imported_only_when_floats_are_printed() {
+        let ints = compile_source(r#"
+            chif main() {
+                con.out(42);
+            }
+        "#, false);
+        assert!(ints.imports("rono_print_int"), "imports: {:?}", ints.imported_symbols);
+        assert!(!ints.imports("rono_print_float"), "imports: {:?}", ints.imported_symbols);
+        
+        let floats = compile_source(r#"
+            chif main() {
+                con.out(1.5);
+            }
+        "#, false);
+        assert!(floats.imports("rono_print_float"), "imports: {:?}", floats.imported_symbols);
+    }
+    
+    #[test]
+    fn test_large_literal_lands_in_read_only_data() {
+        let elements: Vec<String> = (0..1000).map(|i| i.to_string()).collect();
+        let source = format!(
+            "chif main() {{\n let big: array[int] = [{}];\n con.out(big[999]);\n}}",
+            elements.join(", ")
+        );
+        let artifact = compile_source(&source, false);
+        
+        let file = object::File::parse(&*artifact.object).expect("object should parse");
+        let read_only_bytes: u64 = file.sections()
+            .filter(|section| section.kind() == SectionKind::ReadOnlyData)
+            .map(|section| section.size())
+            .sum();
+        assert!(read_only_bytes >= 8000, "Expected the literal in read-only data, got {} bytes", read_only_bytes);
+        assert!(!artifact.imports("rono_copy_data"), "imports: {:?}", artifact.imported_symbols);
+        
+        // An array that leaves the function may be written to: it is copied out of the template
+        let passed = compile_source(&format!(
+            "fn first(xs: array[int]) int {{\n ret xs[0];\n}}\nchif main() {{\n let big: array[int] = [{}];\n con.out(first(big));\n}}",
+            elements.join(", ")
+        ), false);
+        assert!(passed.imports("rono_copy_data"), "imports: {:?}", passed.imported_symbols);
+    }
+    
+    /// Kinds of the object sections whose contents include `needle`
+    fn sections_containing(artifact: &CompiledArtifact, needle: &[u8]) -> Vec<SectionKind> {
+        let file = object::File::parse(&*artifact.object).expect("object should parse");
+        file.sections()
+            .filter(|section| section.data().is_ok_and(|data| data.windows(needle.len()).any(|window| window == needle)))
+            .map(|section| section.kind())
+            .collect()
+    }
+    
+    #[test]
+    fn test_string_literals_land_in_read_only_data() {
+        let artifact = compile_source(r#"
+            fn greeting() str {
+                ret "hello from rodata";
+            }
+            
+            chif main() {
+                con.out(greeting());
+            }
+        "#, false);
+        assert_eq!(sections_containing(&artifact, b"hello from rodata\0"), vec![SectionKind::ReadOnlyData]);
+    }
+    
+    #[test]
+    fn test_unused_module_functions_leave_no_code_or_data() {
+        let dir = tempfile::tempdir().expect("temp dir should be created");
+        fs::write(dir.path().join("texts.rono"), r#"
+            fn used() str {
+                ret "kept message";
+            }
+            fn unused() str {
+                ret "dropped message";
+            }
+        "#).expect("module should be written");
+        
+        let artifact = compile_source(&format!(r#"
+            import "{}/texts" use (used);
+            chif main() {{
+                con.out(texts.used());
+            }}
+        "#, dir.path().display()), false);
+        assert!(artifact.defined_symbol("texts_used").is_some(), "{:?}", artifact.defined_symbols);
+        assert!(artifact.defined_symbol("texts_unused").is_none(), "{:?}", artifact.defined_symbols);
+        assert_eq!(sections_containing(&artifact, b"kept message\0"), vec![SectionKind::ReadOnlyData]);
+        assert!(sections_containing(&artifact, b"dropped message").is_empty());
+    }
+    
+    #[test]
+    fn test_debug_build_keeps_function_ir() {
+        let artifact = compile_source(r#"
+            chif main() {
+                con.out(1);
+            }
+        "#, true);
+        let ir = artifact.function_ir.get("main").expect("main IR should be captured");
+        assert!(ir.contains("function"), "Unexpected IR text: {}", ir);
+    }
+    
+    #[test]
+    fn test_string_methods_call_character_aware_runtime() {
+        let artifact = compile_source(r#"
+            chif main() {
+                let word: str = "héllo";
+                con.out(word.index_of("l"));
+                con.out(word.char_count());
+            }
+        "#, false);
+        assert!(artifact.imports("rono_str_index_of"), "imports: {:?}", artifact.imported_symbols);
+        assert!(artifact.imports("rono_str_char_count"), "imports: {:?}", artifact.imported_symbols);
+    }
+    
+    #[test]
+    fn test_int_radix_methods_call_runtime_formatters() {
+        let artifact = compile_source(r#"
+            chif main() {
+                con.out(255.to_hex_str());
+                con.out(255.to_binary_str());
+            }
+        "#, false);
+        assert!(artifact.imports("rono_int_to_hex"), "imports: {:?}", artifact.imported_symbols);
+        assert!(artifact.imports("rono_int_to_binary"), "imports: {:?}", artifact.imported_symbols);
+        assert!(artifact.imports("rono_print_string"), "imports: {:?}", artifact.imported_symbols);
+    }
+    
+    #[test]
+    fn test_float_modulo_calls_runtime_fmod_unless_folded() {
+        let dynamic = compile_source(r#"
+            chif main() {
+                let x: float = 7.5;
+                con.out(x % 2.0);
+            }
+        "#, false);
+        assert!(dynamic.imports("rono_fmod"), "imports: {:?}", dynamic.imported_symbols);
+        
+        let folded = compile_source(r#"
+            chif main() {
+                con.out(7.5 % 2.0);
+            }
+        "#, false);
+        assert!(!folded.imports("rono_fmod"), "imports: {:?}", folded.imported_symbols);
+    }
+    
+    #[test]
+    fn test_module_imported_twice_is_generated_once() {
+        let dir = tempfile::tempdir().expect("temp dir should be created");
+        std::fs::write(dir.path().join("utils.rono"), r#"
+            fn add(a: int, b: int) int {
+                ret a + b;
+            }
+        "#).expect("module should be written");
+        
+        let base = dir.path().display();
+        let artifact = compile_source(&format!(r#"
+            import "{base}/utils";
+            import "{base}/./utils.rono";
+            chif main() {{
+                con.out(utils_add(1, 2));
+            }}
+        "#, base = base), false);
+        
+        let defined = artifact.defined_symbols.iter().filter(|symbol| symbol.name == "utils_add").count();
+        assert_eq!(defined, 1, "symbols: {:?}", artifact.defined_symbols);
+    }
+    
+    #[test]
+    fn test_diamond_imports_are_analyzed_and_generated_once() {
+        // main -> left, right; left и right оба импортируют shared, right — под псевдонимом
+        let dir = tempfile::tempdir().expect("temp dir should be created");
+        let base = dir.path().display();
+        fs::write(dir.path().join("shared.rono"), r#"
+            @deprecated("use scale")
+            fn old_scale(x: int) int {
+                ret x * 3;
+            }
+            fn scale(x: int) int {
+                ret old_scale(x);
+            }
+        "#).expect("module should be written");
+        fs::write(dir.path().join("left.rono"), format!(r#"
+            import "{base}/shared";
+            fn left() int {{
+                ret shared.scale(1);
+            }}
+        "#, base = base)).expect("module should be written");
+        fs::write(dir.path().join("right.rono"), format!(r#"
+            import "{base}/shared" as s;
+            fn right() int {{
+                ret s.scale(2);
+            }}
+        "#, base = base)).expect("module should be written");
+        let program = parse(&format!(r#"
+            import "{base}/left";
+            import "{base}/right";
+            chif main() {{
+                con.out(left.left() + right.right());
+            }}
+        "#, base = base));
+        
+        let mut analyzer = SemanticAnalyzer::new();
+        analyzer.analyze(&program).expect("diamond imports should type-check");
+        let shared = fs::canonicalize(dir.path().join("shared.rono")).unwrap();
+        assert_eq!(analyzer.analysis_count(&shared), 1);
+        for importer in ["left.rono", "right.rono"] {
+            assert_eq!(analyzer.analysis_count(&fs::canonicalize(dir.path().join(importer)).unwrap()), 1);
+        }
+        assert_eq!(analyzer.parsed_modules.len(), 3);
+        // Предупреждение из тела shared — одно, хотя модуль импортирован дважды
+        let deprecated: Vec<_> = analyzer.warnings.iter().filter(|warning| warning.code() == "W0001").collect();
+        assert_eq!(deprecated.len(), 1, "{:?}", analyzer.warnings);
+        assert_eq!(deprecated[0].location().file, shared.display().to_string());
+        assert_eq!(analyzer.module_diagnostics[&shared].len(), 1);
+        
+        let artifact = Compiler::new(detect_host_target(), OptLevel::None, false)
+            .expect("compiler should be created for the host target")
+            .compile_to_object(&program)
+            .expect("diamond imports should compile");
+        let scale: Vec<&str> = artifact.defined_symbols.iter()
+            .filter(|symbol| symbol.section == SymbolSection::Text && symbol.name.ends_with("_scale"))
+            .map(|symbol| symbol.name.as_str())
+            .collect();
+        assert_eq!(scale, ["shared_old_scale", "shared_scale"], "symbols: {:?}", artifact.defined_symbols);
+        assert!(artifact.defined_symbol("left_left").is_some() && artifact.defined_symbol("right_right").is_some());
+    }
+    
+    #[test]
+    fn test_reused_compiler_matches_fresh_compilers() {
+        let program = |n: usize| parse(&format!(r#"
+            fn value_{n}(x: int) int {{
+                var scale: int = {n};
+                var ratio: float = 1.5;
+                let steps: array[int] = [x, {n}];
+                ret steps[0] * scale;
+            }}
+            
+            chif main() {{
+                con.out("program {n}");
+                con.out(value_{n}(2));
+            }}
+        "#, n = n));
+        
+        let mut reused = Compiler::new(detect_host_target(), OptLevel::None, false)
+            .expect("compiler should be created for the host target");
+        reused.set_stack_warning_bytes(1);
+        let mut diagnostics = None;
+        
+        for n in 0..100 {
+            reused.reset();
+            let artifact = reused.compile_to_object(&program(n)).expect("program should compile");
+            
+            let mut fresh = Compiler::new(detect_host_target(), OptLevel::None, false)
+                .expect("compiler should be created for the host target");
+            fresh.set_stack_warning_bytes(1);
+            let expected = fresh.compile_to_object(&program(n)).expect("program should compile");
+            
+            assert_eq!(artifact.object, expected.object, "program {} differs from a fresh compilation", n);
+            assert_eq!(artifact.defined_symbols, expected.defined_symbols);
+            
+            // Stack warnings are reported for every program but must not pile up
+            let count = reused.diagnostics().len();
+            assert!(count > 0, "program {} should produce stack warnings", n);
+            assert_eq!(*diagnostics.get_or_insert(count), count, "diagnostics grow across compilations");
+        }
+    }
+    
+    #[test]
+    fn test_reused_compiler_resolves_every_symbol_of_large_programs() {
+        const FUNCTIONS: usize = 300;
+        const STRUCTS: usize = 40;
+        // Hundreds of functions and structs make the generator's maps grow and
+        // rehash; struct layouts differ between programs, so a layout left over
+        // from the previous compilation would change the code
+        let program = |n: usize| {
+            let mut source = String::new();
+            for s in 0..STRUCTS {
+                let fields: Vec<String> = (0..=(s + n) % 5).map(|f| format!("f{}: int,", f)).collect();
+                source += &format!("struct S{}_{} {{ {} }}\n", n % 2, s, fields.join(" "));
+                source += &format!(
+                    "fn make_{s}(x: int) int {{\n let v: S{p}_{s} = S{p}_{s} {{ {} }};\n ret v.f0;\n}}\n",
+                    (0..=(s + n) % 5).map(|f| format!("f{} = x + {}", f, f)).collect::<Vec<_>>().join(", "),
+                    p = n % 2, s = s,
+                );
+            }
+            for i in 0..FUNCTIONS {
+                let body = if i == 0 { format!("x + {}", n) } else { format!("f_{}_{}(x) + make_{}(x)", n, i - 1, i % STRUCTS) };
+                source += &format!("fn f_{n}_{i}(x: int) int {{\n ret {body};\n}}\n", n = n, i = i, body = body);
+            }
+            source += &format!("chif main() {{\n con.out(f_{}_{}(1));\n}}\n", n, FUNCTIONS - 1);
+            parse(&source)
+        };
+        
+        let mut reused = Compiler::new(detect_host_target(), OptLevel::None, false)
+            .expect("compiler should be created for the host target");
+        for n in 0..6 {
+            reused.reset();
+            let artifact = reused.compile_to_object(&program(n)).expect("program should compile");
+            let expected = Compiler::new(detect_host_target(), OptLevel::None, false)
+                .expect("compiler should be created for the host target")
+                .compile_to_object(&program(n))
+                .expect("program should compile");
+            assert_eq!(artifact.object, expected.object, "program {} differs from a fresh compilation", n);
+            
+            let functions: Vec<&str> = artifact.defined_symbols.iter()
+                .filter(|symbol| symbol.section == SymbolSection::Text && symbol.name.starts_with("f_"))
+                .map(|symbol| symbol.name.as_str())
+                .collect();
+            assert_eq!(functions.len(), FUNCTIONS, "program {}", n);
+            assert!(functions.iter().all(|name| name.starts_with(&format!("f_{}_", n))), "program {}: {:?}", n, functions);
+            for s in 0..STRUCTS {
+                assert!(artifact.defined_symbol(&format!("make_{}", s)).is_some(), "program {}: make_{} is missing", n, s);
+            }
+            assert!(reused.diagnostics().is_empty(), "{:?}", reused.diagnostics());
+        }
+    }
+    
+    #[test]
+    fn test_selective_import_generates_only_reachable_functions() {
+        let dir = tempfile::tempdir().expect("temp dir should be created");
+        std::fs::write(dir.path().join("utils.rono"), r#"
+            fn helper(x: int) int {
+                ret x * 2;
+            }
+            fn parse_config(x: int) int {
+                ret helper(x) + 1;
+            }
+            fn unused() int {
+                ret 0;
+            }
+        "#).expect("module should be written");
+        
+        let program = |import: &str| format!(r#"
+            import "{}/utils"{};
+            chif main() {{
+                con.out(utils.parse_config(20));
+            }}
+        "#, dir.path().display(), import);
+        
+        // helper is generated because parse_config calls it
+        let selective = compile_source(&program(" use (parse_config)"), false);
+        assert!(selective.defined_symbol("utils_parse_config").is_some(), "{:?}", selective.defined_symbols);
+        assert!(selective.defined_symbol("utils_helper").is_some(), "{:?}", selective.defined_symbols);
+        assert!(selective.defined_symbol("utils_unused").is_none(), "{:?}", selective.defined_symbols);
+        
+        let whole = compile_source(&program(""), false);
+        assert!(whole.defined_symbol("utils_unused").is_some(), "{:?}", whole.defined_symbols);
+    }
+    
+    #[test]
+    fn test_exported_module_generates_exports_and_their_callees() {
+        let dir = tempfile::tempdir().expect("temp dir should be created");
+        std::fs::write(dir.path().join("utils.rono"), r#"
+            fn scale(x: int) int {
+                ret x * 2;
+            }
+            fn helper(x: int) int {
+                ret scale(x) + 1;
+            }
+            export fn parse_config(x: int) int {
+                ret helper(x);
+            }
+            fn unused() int {
+                ret 0;
+            }
+        "#).expect("module should be written");
+        
+        let source = format!(r#"
+            import "{}/utils";
+            chif main() {{
+                con.out(utils.parse_config(20));
+            }}
+        "#, dir.path().display());
+        
+        // Private helpers are generated only when an export reaches them, even transitively
+        let artifact = compile_source(&source, false);
+        for name in ["utils_parse_config", "utils_helper", "utils_scale"] {
+            assert!(artifact.defined_symbol(name).is_some(), "{}: {:?}", name, artifact.defined_symbols);
+        }
+        assert!(artifact.defined_symbol("utils_unused").is_none(), "{:?}", artifact.defined_symbols);
+    }
+    
+    #[test]
+    fn test_lang_version_selects_http_method_form() {
+        // Одна программа на двух уровнях: до уровня 2 http.get возвращает тело ответа
+        let source = r#"
+            chif main() {
+                con.out(http.get("http://127.0.0.1:9/status"));
+                ret 0;
+            }
+        "#;
+        let mut compiler = Compiler::new(detect_host_target(), OptLevel::None, false)
+            .expect("compiler should be created for the host target");
+        let level1 = compiler.compile_to_object(&parse(source)).expect("level 1 should compile");
+        assert!(level1.imports("rono_http_get"), "imports: {:?}", level1.imported_symbols);
+        assert!(!level1.imports("rono_http_request"), "imports: {:?}", level1.imported_symbols);
+        let warning = &compiler.diagnostics()[0];
+        assert_eq!(
+            warning.message,
+            "'http.get' is deprecated at language level 1: it returns the response body as str; \
+             from language level 2 it returns HttpResponse (--lang-version 2)"
+        );
+        assert_eq!(warning.code.as_deref(), Some("W0002"));
+        
+        compiler.reset();
+        compiler.set_lang_version(LangVersion::V2);
+        let level2 = compiler.compile_to_object(&parse(source)).expect("level 2 should compile");
+        assert!(level2.imports("rono_http_request"), "imports: {:?}", level2.imported_symbols);
+        assert!(!level2.imports("rono_http_get"), "imports: {:?}", level2.imported_symbols);
+        assert!(compiler.diagnostics().is_empty(), "{:?}", compiler.diagnostics());
+        
+        // Поля ответа есть только у формы уровня 2
+        let typed = parse(r#"
+            chif main() {
+                var response: HttpResponse = http.delete("http://127.0.0.1:9/items");
+                ret response.status;
+            }
+        "#);
+        assert!(compiler.compile_to_object(&typed).is_ok());
+        compiler.reset();
+        compiler.set_lang_version(LangVersion::V1);
+        assert!(matches!(compiler.compile_to_object(&typed), Err(CompilerError::Analysis(_))));
+    }
+    
+    /// Records events; the test keeps a second handle to read them back
+    struct RecordingSink(Rc<RefCell<Vec<ProgressEvent>>>);
+    
+    impl ProgressSink for RecordingSink {
+        fn event(&mut self, event: &ProgressEvent) {
+            self.0.borrow_mut().push(event.clone());
+        }
+    }
+    
+    #[test]
+    fn test_progress_reports_modules_stages_and_summary() {
+        let dir = tempfile::tempdir().expect("temp dir should be created");
+        for (name, body) in [("geometry", "ret n * n;"), ("strings", "ret n + 1;"), ("util", "ret n - 1;")] {
+            fs::write(dir.path().join(format!("{}.rono", name)), format!("fn f(n: int) int {{ {} }}", body))
+                .expect("module should be written");
+        }
+        let source = format!(r#"
+            import "{dir}/geometry";
+            import "{dir}/strings";
+            import "{dir}/util";
+            chif main() {{
+                ret geometry.f(2) + strings.f(3) + util.f(4);
+            }}
+        "#, dir = dir.path().display());
+        
+        let events = Rc::new(RefCell::new(Vec::new()));
+        let mut compiler = Compiler::new(detect_host_target(), OptLevel::None, false)
+            .expect("compiler should be created for the host target");
+        compiler.set_progress_sink(Some(Box::new(RecordingSink(events.clone()))));
+        let artifact = compiler.compile_to_object(&parse(&source)).expect("program should compile");
+        
+        let recorded = events.borrow().clone();
+        let described: Vec<String> = recorded.iter()
+            .map(|event| match event {
+                ProgressEvent::StageFinished { stage, .. } => format!("{:?}", stage),
+                ProgressEvent::Finished(summary) => format!("finished {} modules, {} functions", summary.modules, summary.functions),
+                event => event.to_string(),
+            })
+            .collect();
+        assert_eq!(described, [
+            "Parsing geometry.rono (1/3)",
+            "Parsing strings.rono (2/3)",
+            "Parsing util.rono (3/3)",
+            &format!("{:?}", Stage::Analysis),
+            &format!("{:?}", Stage::IrGeneration),
+            &format!("{:?}", Stage::ObjectEmission),
+            "finished 4 modules, 4 functions",
+        ]);
+        match recorded.last() {
+            Some(ProgressEvent::Finished(summary)) => assert_eq!(summary.object_bytes, artifact.object.len()),
+            other => panic!("expected a summary last, got {:?}", other),
+        }
+        
+        // The sink is only installed while the compiler runs
+        crate::progress::emit(ProgressEvent::StageFinished { stage: Stage::Linking, elapsed: Default::default() });
+        assert_eq!(events.borrow().len(), recorded.len());
+    }
+
+    #[test]
+    fn test_platform_builtins_are_constants_of_the_target() {
+        let source = r#"
+            chif main() {
+                con.out(os.name());
+                con.out(os.arch());
+                if (os.name() == "windows") {
+                    con.out("on windows");
+                }
+                ret 0;
+            }
+        "#;
+        let contains = |object: &[u8], text: &str| {
+            let needle = [text.as_bytes(), &[0]].concat();
+            object.windows(needle.len()).any(|window| window == needle)
+        };
+        // Cross-compiling reports the target, whatever the host is. Cranelift
+        // may be built with the host architecture only, so other ISAs are skipped
+        let targets = [
+            (Target::X86_64Windows, "windows", "x86_64"),
+            (Target::X86_64MacOS, "macos", "x86_64"),
+            (Target::Aarch64MacOS, "macos", "aarch64"),
+        ];
+        let mut compiled = 0;
+        for (target, name, arch) in targets {
+            let Ok(mut compiler) = Compiler::new(target, OptLevel::None, false) else { continue };
+            compiled += 1;
+            let artifact = compiler.compile_to_object(&parse(source)).expect("source should compile");
+            assert!(contains(&artifact.object, name), "{} should be embedded for {:?}", name, target);
+            assert!(contains(&artifact.object, arch), "{} should be embedded for {:?}", arch, target);
+            // The comparison is folded, not a call to the runtime
+            assert!(!artifact.imports("rono_str_equal"), "imports: {:?}", artifact.imported_symbols);
+        }
+        assert!(compiled > 0, "no cross target is available");
+    }
+
+    #[test]
+    fn test_object_does_not_depend_on_the_number_of_jobs() {
+        let mut source = String::from("struct Counter { value: int, }\nfn_for Counter {\n    fn next(self) int { ret self.value + 1; }\n}\n");
+        for n in 0..40 {
+            source.push_str(&format!(
+                "fn step_{n}(x: int) int {{\n    con.out(\"step {n}\");\n    var total: int = 0;\n    for (var i: int = 0; i < x; i = i + 1) {{ total = total + i * {n}; }}\n    ret total;\n}}\n",
+                n = n
+            ));
+        }
+        source.push_str("chif main() {\n    var c: Counter = Counter { value = 1 };\n    con.out(c.next());\n");
+        for n in 0..40 {
+            source.push_str(&format!("    con.out(step_{}({}));\n", n, n));
+        }
+        source.push_str("}\n");
+        let program = parse(&source);
+        
+        let compile = |jobs: usize| {
+            let mut compiler = Compiler::new(detect_host_target(), OptLevel::Speed, false)
+                .expect("compiler should be created for the host target");
+            compiler.set_jobs(jobs);
+            compiler.compile_to_object(&program).expect("program should compile")
+        };
+        let sequential = compile(1);
+        for jobs in [2, 3, 8, 8, 8] {
+            let parallel = compile(jobs);
+            assert!(parallel.object == sequential.object, "{} jobs produced a different object", jobs);
+            assert_eq!(parallel.defined_symbols, sequential.defined_symbols);
+        }
+    }
+
+    #[test]
+    fn test_first_failing_body_is_reported_with_any_number_of_jobs() {
+        // Tuple variables pass analysis but cannot be lowered yet, so these two bodies
+        // fail, each with its own tuple type in the message
+        let mut source = String::from(concat!(
+            "fn divmod(a: int, b: int) (int, int) {\n    ret (a / b, a % b);\n}\n",
+            "fn named(a: int) (int, str) {\n    ret (a, \"a\");\n}\n",
+        ));
+        for n in 0..30 {
+            let body = match n {
+                7 => "    let first: (int, int) = divmod(x, 2);\n    ret x;".to_string(),
+                21 => "    let second: (int, str) = named(x);\n    ret x;".to_string(),
+                _ => format!("    con.out(\"work {}\");\n    ret x + {};", n, n),
+            };
+            source.push_str(&format!("fn work_{}(x: int) int {{\n{}\n}}\n", n, body));
+        }
+        source.push_str("chif main() {\n    con.out(work_0(1));\n}\n");
+        let program = parse(&source);
+        
+        let error = |jobs: usize| {
+            let mut compiler = Compiler::new(detect_host_target(), OptLevel::None, false)
+                .expect("compiler should be created for the host target");
+            compiler.set_jobs(jobs);
+            match compiler.compile_to_object(&program) {
+                Err(CompilerError::IRGeneration(message)) => message,
+                other => panic!("Expected an IR generation error, got {:?}", other.map(|artifact| artifact.defined_symbols)),
+            }
+        };
+        let sequential = error(1);
+        assert!(sequential.contains("Tuple([Int, Int])"), "Unexpected message: {}", sequential);
+        for jobs in [2, 4, 8, 8] {
+            assert_eq!(error(jobs), sequential, "{} jobs reported another body", jobs);
+        }
+    }
+    
+    #[test]
+    fn test_data_of_parallel_bodies_is_declared_in_order() {
+        let mut source = String::new();
+        for n in 0..12 {
+            source.push_str(&format!("fn platform_{}() {{\n    con.out(os.name());\n    con.out({});\n}}\n", n, n));
+        }
+        source.push_str("chif main() {\n    platform_0();\n    con.out(os.arch());\n}\n");
+        let program = parse(&source);
+        let compile = |jobs: usize| {
+            let mut compiler = Compiler::new(detect_host_target(), OptLevel::None, true)
+                .expect("compiler should be created for the host target");
+            compiler.set_jobs(jobs);
+            compiler.compile_to_object(&program).expect("program should compile")
+        };
+        let sequential = compile(1);
+        let parallel = compile(4);
+        // Every body keeps its string constants in its own data object; the
+        // objects are declared in the order of the bodies before any is generated
+        assert!(parallel.object == sequential.object, "4 jobs produced a different object");
+        assert_eq!(parallel.function_ir, sequential.function_ir);
+        assert!(parallel.function_ir["platform_3"].contains("symbol colocated"), "{}", parallel.function_ir["platform_3"]);
+    }
+}
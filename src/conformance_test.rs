@@ -0,0 +1,2501 @@
+#[cfg(test)]
+mod tests {
+    use crate::ast::{EntryPoint, Program};
+    use crate::interpreter::Interpreter;
+    use crate::ir_gen::{IRError, IRGenerator};
+    use crate::lang::LangVersion;
+    use crate::lexer::Lexer;
+    use crate::parser::Parser;
+    use crate::semantic::{AnalyzedProgram, SemanticAnalyzer};
+    use crate::types::ChifValue;
+
+    use cranelift::prelude::{settings, Configurable};
+    use cranelift_object::{ObjectBuilder, ObjectModule};
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+    use std::io::Write;
+    use std::process::{Command, Stdio};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use target_lexicon::Triple;
+
+    static NEXT_BINARY: AtomicUsize = AtomicUsize::new(0);
+
+    fn parse(source: &str) -> Program {
+        let tokens = Lexer::new(source).tokenize().expect("source should tokenize");
+        Parser::new(tokens).parse().expect("source should parse")
+    }
+
+    fn pic_host_module() -> ObjectModule {
+        let mut flag_builder = settings::builder();
+        flag_builder.set("is_pic", "true").expect("is_pic should be a valid flag");
+        let isa = cranelift::codegen::isa::lookup(Triple::host())
+            .expect("host ISA should be available")
+            .finish(settings::Flags::new(flag_builder))
+            .expect("host ISA should build");
+        let builder = ObjectBuilder::new(
+            isa,
+            "rono_conformance".to_string(),
+            cranelift_module::default_libcall_names(),
+        ).expect("object builder should be created");
+        ObjectModule::new(builder)
+    }
+
+    fn linker_available() -> bool {
+        Command::new("cc").arg("--version").output().is_ok()
+    }
+
+    /// Value returned from main by the interpreter; a bare `ret;` counts as exit code 0
+    fn run_interpreted(program: &Program) -> i64 {
+        let mut interpreter = Interpreter::new();
+        interpreter.load(program).expect("program should load");
+        match interpreter.call_function_by_name("main").expect("main should run") {
+            ChifValue::Int(value) => value,
+            ChifValue::Nil => 0,
+            other => panic!("main should return an int, got {:?}", other),
+        }
+    }
+
+    /// Exit code of the compiled program; main's return value becomes the exit code.
+    /// Links the C runtime, built without HTTP so libcurl is not needed
+    fn run_compiled_with_runtime(program: &Program) -> i32 {
+        let runtime = concat!(env!("CARGO_MANIFEST_DIR"), "/src/runtime.c");
+        run_compiled_linking(program, &[runtime, "-DRONO_NO_HTTP", "-lm"])
+    }
+
+    fn run_compiled_linking(program: &Program, link_args: &[&str]) -> i32 {
+        let binary = build_compiled(program, link_args);
+        let code = Command::new(&binary)
+            .status()
+            .expect("compiled program should run")
+            .code()
+            .expect("compiled program should exit normally");
+        let _ = std::fs::remove_file(&binary);
+        code
+    }
+
+    /// Standard output of the compiled program linked with the C runtime
+    fn run_compiled_output(program: &Program) -> String {
+        let runtime = concat!(env!("CARGO_MANIFEST_DIR"), "/src/runtime.c");
+        let binary = build_compiled(program, &[runtime, "-DRONO_NO_HTTP", "-lm"]);
+        let output = Command::new(&binary).output().expect("compiled program should run");
+        let _ = std::fs::remove_file(&binary);
+        String::from_utf8(output.stdout).expect("output should be UTF-8")
+    }
+
+    /// Exit code of the compiled program (runtime linked) with `input` on its stdin
+    fn run_compiled_with_input(program: &Program, input: &str) -> i32 {
+        let runtime = concat!(env!("CARGO_MANIFEST_DIR"), "/src/runtime.c");
+        let binary = build_compiled(program, &[runtime, "-DRONO_NO_HTTP", "-lm"]);
+        let mut child = Command::new(&binary)
+            .stdin(Stdio::piped())
+            .spawn()
+            .expect("compiled program should run");
+        child.stdin.take().expect("stdin should be piped")
+            .write_all(input.as_bytes())
+            .expect("input should be written");
+        let code = child.wait().expect("compiled program should exit").code()
+            .expect("compiled program should exit normally");
+        let _ = std::fs::remove_file(&binary);
+        code
+    }
+
+    /// Exit code of the compiled program (runtime linked) started with `args` after its name
+    fn run_compiled_with_args(program: &Program, args: &[&str]) -> i32 {
+        let runtime = concat!(env!("CARGO_MANIFEST_DIR"), "/src/runtime.c");
+        let binary = build_compiled(program, &[runtime, "-DRONO_NO_HTTP", "-lm"]);
+        let code = Command::new(&binary)
+            .args(args)
+            .status()
+            .expect("compiled program should run")
+            .code()
+            .expect("compiled program should exit normally");
+        let _ = std::fs::remove_file(&binary);
+        code
+    }
+
+    /// Compiles and links an executable; the caller removes it
+    fn build_compiled(program: &Program, link_args: &[&str]) -> std::path::PathBuf {
+        build_compiled_from(program, None, link_args)
+    }
+
+    /// Like `build_compiled`, starting at `entry` (`--entry`) instead of `chif main`
+    fn build_compiled_from(program: &Program, entry: Option<&EntryPoint>, link_args: &[&str]) -> std::path::PathBuf {
+        let mut generator = IRGenerator::new(pic_host_module());
+        generator.entry = program.entry_point(entry).expect("entry point should be unambiguous");
+        generator
+            .generate(&AnalyzedProgram::new(program.items.clone()))
+            .expect("program should compile");
+        let object = generator.finalize().finish().emit().expect("object should be emitted");
+
+        let id = NEXT_BINARY.fetch_add(1, Ordering::SeqCst);
+        let base = std::env::temp_dir().join(format!("rono_conformance_{}_{}", std::process::id(), id));
+        let object_path = base.with_extension("o");
+        std::fs::write(&object_path, object).expect("object should be written");
+
+        let status = Command::new("cc")
+            .arg(&object_path)
+            .args(link_args)
+            .arg("-o")
+            .arg(&base)
+            .status()
+            .expect("cc should run");
+        assert!(status.success(), "Linking the compiled program failed");
+
+        let _ = std::fs::remove_file(&object_path);
+        base
+    }
+
+    fn assert_backends_agree(source: &str) -> i64 {
+        let program = parse(source);
+        let interpreted = run_interpreted(&program);
+        if linker_available() {
+            let compiled = run_compiled_with_runtime(&program);
+            assert_eq!(
+                (interpreted & 0xff) as i32, compiled,
+                "Interpreter returned {} but the compiled program exited with {}\n{}",
+                interpreted, compiled, source
+            );
+        }
+        interpreted
+    }
+
+    #[test]
+    fn test_for_continue_as_last_statement() {
+        let count = assert_backends_agree(r#"
+            chif main() {
+                var count: int = 0;
+                for (var i: int = 0; i < 10; i = i + 1) {
+                    count = count + 1;
+                    continue;
+                }
+                ret count;
+            }
+        "#);
+        assert_eq!(count, 10);
+    }
+
+    #[test]
+    fn test_loop_conditions_and_updates_run_once_per_check() {
+        // The condition counts its own calls: once per iteration plus the failing
+        // check, and never again after break or ret
+        let result = assert_backends_agree(r#"
+            fn below(calls: &mut int, i: int, limit: int) bool {
+                *calls = *calls + 1;
+                ret i < limit;
+            }
+
+            fn step(updates: &mut int, i: int) int {
+                *updates = *updates + 1;
+                ret i + 1;
+            }
+
+            fn first_square_over(limit: int, checks: &mut int) int {
+                var i: int = 0;
+                while (below(checks, i, 100)) {
+                    if (i * i > limit) {
+                        ret i;
+                    }
+                    i = i + 1;
+                }
+                ret 0;
+            }
+
+            chif main() {
+                var checks: int = 0;
+                var updates: int = 0;
+                for (var i: int = 0; below(&mut checks, i, 5); i = step(&mut updates, i)) {
+                    if (i == 1) {
+                        continue;
+                    }
+                    if (i == 3) {
+                        break;
+                    }
+                }
+                if (checks != 4) {
+                    ret 1;
+                }
+                if (updates != 3) {
+                    ret 2;
+                }
+
+                var finished: int = 0;
+                for (var i: int = 0; below(&mut finished, i, 3); i = i + 1) { }
+                if (finished != 4) {
+                    ret 3;
+                }
+
+                var while_checks: int = 0;
+                var inner_checks: int = 0;
+                var j: int = 0;
+                while (below(&mut while_checks, j, 6)) {
+                    j = j + 1;
+                    if (j % 2 == 0) {
+                        continue;
+                    }
+                    var k: int = 0;
+                    while (below(&mut inner_checks, k, 10)) {
+                        k = k + 1;
+                        if (k == 2) {
+                            break;
+                        }
+                    }
+                }
+                if (while_checks != 7) {
+                    ret 4;
+                }
+                if (inner_checks != 6) {
+                    ret 5;
+                }
+
+                var never: int = 0;
+                while (below(&mut never, 10, 4)) { }
+                if (never != 1) {
+                    ret 6;
+                }
+                var returned: int = 0;
+                if (first_square_over(10, &mut returned) != 4) {
+                    ret 7;
+                }
+                if (returned != 5) {
+                    ret 8;
+                }
+                ret 42;
+            }
+        "#);
+        assert_eq!(result, 42);
+    }
+
+    #[test]
+    fn test_for_init_reuses_variable_or_infers_type() {
+        // `i = 0` assigns the outer i, so its final value is visible after the loop
+        let result = assert_backends_agree(r#"
+            chif main() {
+                var sum: int = 0;
+                var i: int = 100;
+                for (i = 0; i < 4; i = i + 1) {
+                    sum = sum + i;
+                }
+                for (var j = 10; j > 7; j = j - 1) {
+                    sum = sum + j;
+                }
+                ret sum * 10 + i;
+            }
+        "#);
+        assert_eq!(result, (6 + 27) * 10 + 4);
+    }
+
+    #[test]
+    fn test_for_continue_in_the_middle() {
+        // The update still runs after continue, so every i in 0..10 is visited once
+        let count = assert_backends_agree(r#"
+            chif main() {
+                var count: int = 0;
+                for (var i: int = 0; i < 10; i = i + 1) {
+                    count = count + 1;
+                    if (i < 5) {
+                        continue;
+                    }
+                    count = count + 100;
+                }
+                ret count;
+            }
+        "#);
+        assert_eq!(count, 10 + 5 * 100);
+    }
+
+    #[test]
+    fn test_for_continue_inside_nested_ifs() {
+        let count = assert_backends_agree(r#"
+            chif main() {
+                var count: int = 0;
+                for (var i: int = 0; i < 12; i = i + 1) {
+                    if (i > 2) {
+                        if (i < 8) {
+                            continue;
+                        } else {
+                            count = count + 10;
+                        }
+                    }
+                    count = count + 1;
+                }
+                ret count;
+            }
+        "#);
+        // i = 0..2 add 1, i = 3..7 skip, i = 8..11 add 11
+        assert_eq!(count, 3 + 4 * 11);
+    }
+
+    #[test]
+    fn test_for_break_skips_update() {
+        let last = assert_backends_agree(r#"
+            chif main() {
+                var last: int = 0;
+                for (var i: int = 0; i < 100; i = i + 1) {
+                    last = i;
+                    if (i == 7) {
+                        break;
+                    }
+                }
+                ret last;
+            }
+        "#);
+        assert_eq!(last, 7);
+    }
+
+    #[test]
+    fn test_if_else_branches_break_and_continue() {
+        let sum = assert_backends_agree(r#"
+            chif main() {
+                var sum: int = 0;
+                var i: int = 0;
+                while (i < 100) {
+                    i = i + 1;
+                    if (i > 10) {
+                        break;
+                    } else {
+                        if (i % 2 == 0) {
+                            continue;
+                        } else {
+                            sum = sum + i;
+                            continue;
+                        }
+                    }
+                }
+                for (var j: int = 0; j < 5; j = j + 1) {
+                    if (j == 3) {
+                        continue;
+                    } else {
+                        sum = sum + 10;
+                    }
+                }
+                ret sum;
+            }
+        "#);
+        // 1 + 3 + 5 + 7 + 9, then four iterations of the for loop
+        assert_eq!(sum, 25 + 40);
+    }
+
+    #[test]
+    fn test_return_inside_loop_branches() {
+        let found = assert_backends_agree(r#"
+            fn first_multiple(step: int, limit: int) int {
+                for (var i: int = 1; i < limit; i = i + 1) {
+                    if (i % step == 0) {
+                        ret i;
+                    } else {
+                        continue;
+                    }
+                }
+                ret 0;
+            }
+
+            chif main() {
+                if (first_multiple(7, 100) == 7) {
+                    ret first_multiple(13, 10) + 42;
+                } else {
+                    ret 1;
+                }
+            }
+        "#);
+        assert_eq!(found, 42);
+    }
+
+    /// Every pair mixes an int and a float operand. Both backends must print what
+    /// the interpreter prints for the same expressions with the ints written as
+    /// floats, which is what the int operands are promoted to.
+    #[test]
+    fn test_mixed_int_float_arithmetic_is_promoted() {
+        const GRID: &str = r#"
+            fn half() float {
+                ret 0.5;
+            }
+
+            fn three() INT {
+                ret THREE;
+            }
+
+            chif main() {
+                var x: float = 1.5;
+                var y: float = -2.25;
+                var n: INT = TWO;
+                var m: INT = MINUS_THREE;
+                con.out(x * n);
+                con.out(n * x);
+                con.out(x + m);
+                con.out(m - y);
+                con.out(y / n);
+                con.out(m / x);
+                con.out(x * FOUR);
+                con.out(FOUR - y);
+                con.out(half() * n);
+                con.out(three() + y);
+                con.out(-x * n);
+                con.out((x + n) * m);
+                con.out(x < n);
+                con.out(m > y);
+                con.out(n == 2.0);
+            }
+        "#;
+        let mixed = GRID.replace("INT", "int").replace("MINUS_THREE", "-3").replace("TWO", "2").replace("THREE", "3").replace("FOUR", "4");
+        let floats = GRID.replace("INT", "float").replace("MINUS_THREE", "-3.0").replace("TWO", "2.0").replace("THREE", "3.0").replace("FOUR", "4.0");
+
+        let mut interpreter = Interpreter::new();
+        interpreter.capture_output();
+        interpreter.execute(&parse(&floats)).expect("float program should run");
+        let expected = interpreter.take_output();
+        assert_eq!(expected.len(), 15);
+
+        let mut interpreter = Interpreter::new();
+        interpreter.capture_output();
+        interpreter.execute(&parse(&mixed)).expect("mixed program should run");
+        assert_eq!(interpreter.take_output(), expected);
+
+        if !linker_available() {
+            return;
+        }
+        let output = run_compiled_output(&parse(&mixed));
+        let actual: Vec<&str> = output.lines().collect();
+        assert_eq!(actual.len(), expected.len(), "{}", output);
+        for (line, (actual, expected)) in actual.iter().zip(&expected).enumerate() {
+            match (actual.parse::<f64>(), expected.parse::<f64>()) {
+                (Ok(a), Ok(e)) => assert!((a - e).abs() < 1e-6, "line {}: compiled {} vs interpreted {}", line, a, e),
+                _ => assert_eq!(actual, expected, "line {}", line),
+            }
+        }
+    }
+
+    #[test]
+    fn test_ints_in_float_places_are_promoted_in_both_backends() {
+        let program = parse(r#"
+            fn half(x: float) float {
+                ret x / 2;
+            }
+
+            fn one() float {
+                ret 1;
+            }
+
+            chif main() {
+                var x: float = 1;
+                var n: int = 3;
+                var y: float = n * 1.5;
+                con.out(x);
+                con.out(y);
+                con.out(half(3) + one());
+                con.out(7 % 2.5);
+                if (n < 3.5) {
+                    con.out("less");
+                }
+                if (n == 3.0) {
+                    con.out("equal");
+                }
+                y = n;
+                y = y + n;
+                con.out(y);
+            }
+        "#);
+        let expected = ["1.0", "4.5", "2.5", "2.0", "less", "equal", "6.0"];
+
+        let mut interpreter = Interpreter::new();
+        interpreter.capture_output();
+        interpreter.execute(&program).expect("mixed program should run");
+        assert_eq!(interpreter.take_output(), expected);
+
+        if linker_available() {
+            let output = run_compiled_output(&program);
+            assert_eq!(output.lines().collect::<Vec<_>>(), expected);
+        }
+    }
+
+    #[test]
+    fn test_arguments_are_passed_by_value_unless_taken_as_mut_reference() {
+        let program = parse(r#"
+            struct Point {
+                x: int,
+                y: int,
+            }
+
+            fn_for Point {
+                fn bump(self) {
+                    self.x = self.x + 50;
+                }
+            }
+
+            fn set_field(p: Point) {
+                p.x = 100;
+                con.out(p.x);
+            }
+
+            fn call_method(p: Point) {
+                p.bump();
+                con.out(p.x);
+            }
+
+            fn set_ref(p: &mut Point) {
+                p.x = 7;
+            }
+
+            chif main() {
+                var p: Point = Point { x = 1, y = 2 };
+                set_field(p);
+                con.out(p.x);
+                call_method(p);
+                con.out(p.x);
+                set_ref(&mut p);
+                con.out(p.x);
+            }
+        "#);
+        let expected = ["100", "1", "51", "1", "7"];
+
+        // Изменения параметров-значений пропадают, и анализатор об этом предупреждает
+        let mut analyzer = SemanticAnalyzer::new();
+        analyzer.analyze(&program).expect("program should pass the analyzer");
+        let warned: Vec<String> = analyzer.warnings.iter().map(|warning| warning.to_string()).collect();
+        assert_eq!(warned.len(), 2, "{:?}", warned);
+        assert!(warned[0].starts_with("'set_field' changes 'p', which is passed by value"), "{:?}", warned);
+        assert!(warned[1].starts_with("'call_method' changes 'p'"), "{:?}", warned);
+
+        let mut interpreter = Interpreter::new();
+        interpreter.capture_output();
+        interpreter.execute(&program).expect("program should run");
+        assert_eq!(interpreter.take_output(), expected);
+
+        if linker_available() {
+            let output = run_compiled_output(&program);
+            assert_eq!(output.lines().collect::<Vec<_>>(), expected);
+        }
+
+        // Списки: методов списков нет в компилируемом режиме, поэтому только интерпретатор
+        let program = parse(r#"
+            fn grow(xs: list[int]) {
+                xs.add(4);
+            }
+
+            fn grow_ref(xs: &mut list[int]) {
+                xs.add(4);
+                xs[0] = 10;
+            }
+
+            chif main() {
+                var xs: list[int] = [1, 2, 3];
+                grow(xs);
+                con.out(xs.len());
+                grow_ref(&mut xs);
+                con.out(xs.len());
+                con.out(xs[0]);
+            }
+        "#);
+        let mut analyzer = SemanticAnalyzer::new();
+        analyzer.analyze(&program).expect("list references should pass the analyzer");
+        let codes: Vec<String> = analyzer.warnings.iter().map(|warning| warning.code().to_string()).collect();
+        assert_eq!(codes, ["W0005"]);
+
+        let mut interpreter = Interpreter::new();
+        interpreter.capture_output();
+        interpreter.execute(&program).expect("program should run");
+        assert_eq!(interpreter.take_output(), ["3", "4", "10"]);
+    }
+
+    #[test]
+    fn test_mutating_methods_evaluate_arguments_before_the_change() {
+        // Метод структуры, меняющий self, получает аргументы, прочитанные из self
+        // до изменения, а изменения из аргументов видит
+        let result = assert_backends_agree(r#"
+            struct Counter {
+                n: int,
+            }
+
+            fn_for Counter {
+                fn grow(self, k: int) {
+                    self.n = self.n + k;
+                }
+
+                fn next(self) int {
+                    self.n = self.n + 1;
+                    ret self.n;
+                }
+            }
+
+            chif main() {
+                var c: Counter = Counter { n = 5 };
+                c.grow(c.n);
+                c.grow(c.next());
+                ret c.n;
+            }
+        "#);
+        assert_eq!(result, 22);
+
+        // Списки: методов списков нет в компилируемом режиме, поэтому только интерпретатор
+        let program = parse(r#"
+            fn take_first(xs: &mut list[int]) int {
+                var first: int = xs[0];
+                xs.del(0);
+                ret first;
+            }
+
+            chif main() {
+                var xs: list[int] = [3, 1];
+                xs.add(xs.len());
+                xs.add(xs[0]);
+                xs.addAt(xs[1], xs.len());
+                con.out(xs.len());
+                xs.del(xs.len() - 1);
+                xs.addAt(xs[xs.len() - 1], 0);
+                xs.add(take_first(&mut xs));
+                for (var i: int = 0; i < xs.len(); i = i + 1) {
+                    con.out(xs[i]);
+                }
+            }
+        "#);
+        let mut interpreter = Interpreter::new();
+        interpreter.capture_output();
+        interpreter.execute(&program).expect("program should run");
+        assert_eq!(interpreter.take_output(), ["5", "3", "1", "2", "3", "3"]);
+
+        // del: индекс читается из списка до удаления, а удаление применяется к
+        // списку, каким его оставил аргумент
+        let program = parse(r#"
+            fn take_first(xs: &mut list[int]) int {
+                var first: int = xs[0];
+                xs.del(0);
+                ret first;
+            }
+
+            chif main() {
+                var xs: list[int] = [1, 0, 4, 5, 6];
+                xs.del(xs[0]);
+                xs.del(take_first(&mut xs));
+                for (var i: int = 0; i < xs.len(); i = i + 1) {
+                    con.out(xs[i]);
+                }
+            }
+        "#);
+        let mut interpreter = Interpreter::new();
+        interpreter.capture_output();
+        interpreter.execute(&program).expect("program should run");
+        // del(xs[0]) удаляет индекс 1: [1, 4, 5, 6]; take_first снимает 1, и del(1)
+        // применяется к [4, 5, 6], а не к списку до аргумента
+        assert_eq!(interpreter.take_output(), ["4", "6"]);
+
+        let program = parse("chif main() {\n    var xs: list[int] = [1, 2];\n    xs.add(xs[0]);\n}\n");
+        let error = IRGenerator::new(pic_host_module())
+            .generate(&AnalyzedProgram::new(program.items.clone()))
+            .expect_err("lists cannot grow in compiled code yet");
+        assert!(matches!(&error, IRError::UnsupportedFeature(message) if message.contains("'add'")), "{}", error);
+    }
+
+    #[test]
+    fn test_in_checks_membership_in_maps_lists_arrays_and_strings() {
+        // Массивы и строки есть в обоих режимах
+        let program = parse(r#"
+            chif main() {
+                var numbers: array[int] = [1, 2, 3];
+                var ratios: array[float] = [0.5, 2.0];
+                var names: array[str] = ["ann", "bob"];
+                con.out(3 in numbers);
+                con.out(1 + 3 in numbers);
+                con.out(2 in ratios);
+                con.out("bob" in names);
+                con.out("eve" in names);
+                con.out("ell" in "hello");
+                con.out("" in "hello");
+                con.out(5 in [4, 5]);
+                if (!(9 in numbers)) {
+                    con.out("no nine");
+                }
+            }
+        "#);
+        let expected = ["true", "false", "true", "true", "false", "true", "true", "true", "no nine"];
+        SemanticAnalyzer::new().analyze(&program).expect("membership tests should type-check");
+        let mut interpreter = Interpreter::new();
+        interpreter.capture_output();
+        interpreter.execute(&program).expect("program should run");
+        assert_eq!(interpreter.take_output(), expected);
+        if linker_available() {
+            let output = run_compiled_output(&program);
+            assert_eq!(output.lines().collect::<Vec<_>>(), expected);
+        }
+
+        // Словари и списки пока только в интерпретаторе
+        let program = parse(r#"
+            chif main() {
+                var ages: map[str:int] = {"ann": 3};
+                var xs: list[int] = [1, 2];
+                con.out("ann" in ages);
+                con.out("bob" in ages);
+                xs.add(5);
+                con.out(5 in xs);
+                con.out(7 in xs);
+            }
+        "#);
+        SemanticAnalyzer::new().analyze(&program).expect("membership tests should type-check");
+        let mut interpreter = Interpreter::new();
+        interpreter.capture_output();
+        interpreter.execute(&program).expect("program should run");
+        assert_eq!(interpreter.take_output(), ["true", "false", "true", "false"]);
+
+        let program = parse("chif main() {\n    var xs: list[int] = [1, 2];\n    con.out(2 in xs);\n}\n");
+        let error = IRGenerator::new(pic_host_module())
+            .generate(&AnalyzedProgram::new(program.items.clone()))
+            .expect_err("lists have no compiled 'in' yet");
+        assert!(matches!(&error, IRError::UnsupportedFeature(message) if message.starts_with("'in' over list[int]")), "{}", error);
+
+        // Левый операнд должен быть ключом или элементом
+        for (declaration, test) in [
+            ("var xs: list[int] = [1];", "\"a\" in xs"),
+            ("var ages: map[str:int] = {\"a\": 1};", "1 in ages"),
+            ("var s: str = \"abc\";", "1 in s"),
+        ] {
+            let source = format!("chif main() {{\n    {}\n    con.out({});\n}}\n", declaration, test);
+            let error = SemanticAnalyzer::new().analyze(&parse(&source)).expect_err(test);
+            assert!(error.to_string().contains("Type mismatch"), "{}: {}", test, error);
+        }
+        let error = SemanticAnalyzer::new().analyze(&parse("chif main() {\n    con.out(1 in 5);\n}\n")).expect_err("int is not a collection");
+        assert!(error.to_string().contains("'in' needs a map, list, array or str on the right, found int"), "{}", error);
+    }
+
+    #[test]
+    fn test_unique_keeps_first_occurrences_of_literals_and_variables() {
+        let program = parse(r#"
+            chif main() {
+                var numbers: array[int] = [3, 1, 3, 2, 1];
+                var names: array[str] = ["bob", "ann", "bob"];
+                list from_literal: int[] = [3, 1, 2, 1, 3].unique();
+                list from_variable: int[] = numbers.unique();
+                list distinct_names: str[] = names.unique();
+                con.out(from_literal.len());
+                con.out(from_literal[0] * 100 + from_literal[1] * 10 + from_literal[2]);
+                con.out(from_variable.len());
+                con.out(from_variable[0] * 100 + from_variable[1] * 10 + from_variable[2]);
+                con.out(distinct_names.len());
+                con.out(distinct_names[0] == "bob");
+                con.out(distinct_names[1] == "ann");
+                con.out([5, 5, 5].unique().len());
+                con.out(numbers.len());
+            }
+        "#);
+        let expected = ["3", "312", "3", "312", "2", "true", "true", "1", "5"];
+        SemanticAnalyzer::new().analyze(&program).expect("unique() should type-check");
+        let mut interpreter = Interpreter::new();
+        interpreter.capture_output();
+        interpreter.execute(&program).expect("program should run");
+        assert_eq!(interpreter.take_output(), expected);
+        if linker_available() {
+            let output = run_compiled_output(&program);
+            assert_eq!(output.lines().collect::<Vec<_>>(), expected);
+        }
+
+        let program = parse("chif main() {\n    var xs: list[int] = [1, 2];\n    list u: int[] = xs.unique();\n}\n");
+        let error = IRGenerator::new(pic_host_module())
+            .generate(&AnalyzedProgram::new(program.items.clone()))
+            .expect_err("lists have no compiled length yet");
+        assert!(matches!(&error, IRError::UnsupportedFeature(message) if message.starts_with("unique() over list[int]")), "{}", error);
+    }
+
+    #[test]
+    fn test_associated_function_constructors() {
+        let result = assert_backends_agree(r#"
+            struct Point {
+                x: int,
+                y: int,
+            }
+
+            fn_for Point {
+                fn new(x: int, y: int) Self {
+                    ret Point { x = x, y = y };
+                }
+
+                fn origin() Point {
+                    ret Point { x = 0, y = 0 };
+                }
+
+                fn sum(self) int {
+                    ret self.x + self.y;
+                }
+            }
+
+            chif main() {
+                var p: Point = Point.new(30, 12);
+                var o: Point = Point.origin();
+                ret p.sum() + o.sum();
+            }
+        "#);
+        assert_eq!(result, 42);
+    }
+
+    #[test]
+    fn test_returned_local_structs_outlive_the_callee_frame() {
+        // `clobber` reuses the stack the returned structs were built in
+        let result = assert_backends_agree(r#"
+            struct Point {
+                x: int,
+                y: int,
+            }
+
+            fn make(x: int, y: int) Point {
+                var p: Point = Point { x = x, y = y };
+                p.x = p.x + 1;
+                ret p;
+            }
+
+            fn doubled(p: Point) Point {
+                p.y = p.y * 2;
+                ret p;
+            }
+
+            fn clobber(a: int) int {
+                var q: Point = Point { x = a * 3, y = a * 5 };
+                ret q.x + q.y;
+            }
+
+            chif main() {
+                var a: Point = make(1, 2);
+                var b: Point = make(10, 20);
+                var c: int = clobber(7);
+                var d: Point = doubled(a);
+                ret a.x + a.y * 10 + b.x + b.y + c + d.y;
+            }
+        "#);
+        assert_eq!(result, 2 + 20 + 11 + 20 + 56 + 4);
+    }
+
+    #[test]
+    fn test_struct_literal_with_base_copies_the_remaining_fields() {
+        let result = assert_backends_agree(r#"
+            struct Point {
+                x: int,
+                y: int,
+                z: int,
+            }
+
+            chif main() {
+                var p: Point = Point { x = 1, y = 2, z = 3 };
+                var moved: Point = Point { x = p.x + 10, ..p };
+                var copy: Point = Point { ..p };
+                p.y = 50;
+                ret moved.x * 100 + moved.y * 10 + moved.z + copy.y + p.y;
+            }
+        "#);
+        assert_eq!(result, 1123 + 2 + 50);
+    }
+
+    #[test]
+    fn test_unary_operators_apply_to_postfix_results() {
+        let result = assert_backends_agree(r#"
+            struct Point {
+                x: int,
+                y: int,
+            }
+
+            fn_for Point {
+                fn sum(self) int {
+                    ret self.x + self.y;
+                }
+
+                fn is_origin(self) bool {
+                    if (self.x != 0) {
+                        ret false;
+                    }
+                    ret self.y == 0;
+                }
+            }
+
+            fn twice(n: int) int {
+                ret n * 2;
+            }
+
+            chif main() {
+                var p: Point = Point { x = 3, y = 4 };
+                array arr: int[2] = [5, 6];
+                var a: int = 7;
+                var b: int = 2;
+
+                // 100 - 3 - 7 - 4 - 5 - 14 + 12 = 79
+                var result: int = 100 + -p.x + -p.sum() + -twice(2) + -arr[0] + -a * b + -p.x * -p.y;
+                if (!p.is_origin()) {
+                    result = result + 1;
+                }
+
+                var f: float = 1.5;
+                if (-f * 2.0 != -3.0) {
+                    ret 1;
+                }
+                ret result;
+            }
+        "#);
+        assert_eq!(result, 80);
+    }
+
+    #[test]
+    fn test_mutable_references_write_back_to_the_caller() {
+        let result = assert_backends_agree(r#"
+            struct Counter {
+                n: int,
+            }
+
+            fn swap(a: &mut int, b: &mut int) {
+                var t: int = *a;
+                *a = *b;
+                *b = t;
+            }
+
+            fn bump(c: &mut Counter) {
+                c.n = c.n + 1;
+            }
+
+            fn read(v: &int, c: &Counter) int {
+                ret *v + c.n;
+            }
+
+            chif main() {
+                // Names of the caller's variables match the parameters on purpose
+                var a: int = 1;
+                var b: int = 2;
+                swap(&mut a, &mut b);
+                var c: Counter = Counter { n = 5 };
+                bump(&mut c);
+                bump(&mut c);
+                // Reading through & leaves the variables usable as values
+                if (read(&a, &c) != 9) {
+                    ret 1;
+                }
+                ret a * 100 + b * 10 + c.n;
+            }
+        "#);
+        assert_eq!(result, 217);
+    }
+
+    #[test]
+    fn test_methods_added_to_an_imported_struct() {
+        let dir = tempfile::tempdir().expect("temp dir should be created");
+        std::fs::write(dir.path().join("geometry.rono"), r#"
+            struct Point {
+                x: int,
+                y: int,
+            }
+
+            fn_for Point {
+                fn sum(self) int {
+                    ret self.x + self.y;
+                }
+            }
+
+            fn origin() Point {
+                ret Point { x = 0, y = 0 };
+            }
+        "#).expect("module should be written");
+
+        let source = format!(r#"
+            import "{}/geometry";
+
+            fn_for Point {{
+                fn scaled_sum(self, k: int) int {{
+                    ret self.sum() * k;
+                }}
+            }}
+
+            chif main() {{
+                var p: Point = Point {{ x = 3, y = 4 }};
+                var o: Point = geometry.origin();
+                ret p.sum() + p.scaled_sum(2) + o.scaled_sum(5) + 21;
+            }}
+        "#, dir.path().display());
+        crate::analyze_source(&source).expect("extension methods should pass the analyzer");
+        assert_eq!(assert_backends_agree(&source), 42);
+    }
+
+    #[test]
+    fn test_selected_function_keeps_the_structs_it_uses() {
+        let dir = tempfile::tempdir().expect("temp dir should be created");
+        std::fs::write(dir.path().join("config.rono"), r#"
+            struct Config {
+                scale: int,
+                offset: int,
+            }
+
+            fn_for Config {
+                fn new(scale: int) Self {
+                    ret Config { scale = scale, offset = 0 };
+                }
+
+                fn apply(self, x: int) int {
+                    ret x * self.scale + self.offset;
+                }
+            }
+
+            struct Limits {
+                max: int,
+            }
+
+            fn_for Limits {
+                fn clamp(self, x: int) int {
+                    if (x > self.max) {
+                        ret self.max;
+                    }
+                    ret x;
+                }
+            }
+
+            fn parse_config(x: int) int {
+                var settings: Config = Config.new(3);
+                settings.offset = 2;
+                var limits: Limits = Limits { max = 40 };
+                ret limits.clamp(settings.apply(x));
+            }
+        "#).expect("module should be written");
+
+        // Neither Config nor Limits is listed: parse_config alone brings them in
+        let source = format!(r#"
+            import "{}/config" use (parse_config);
+
+            chif main() {{
+                ret config.parse_config(5) + config.parse_config(20);
+            }}
+        "#, dir.path().display());
+        crate::analyze_source(&source).expect("selective import should pass the analyzer");
+        assert_eq!(assert_backends_agree(&source), 17 + 40);
+    }
+
+    #[test]
+    fn test_aliased_and_default_module_names_call_the_same_functions() {
+        let dir = tempfile::tempdir().expect("temp dir should be created");
+        std::fs::create_dir(dir.path().join("dir")).expect("module dir should be created");
+        std::fs::write(dir.path().join("dir/utils.rono"), r#"
+            fn helper(x: int) int {
+                ret x * 2;
+            }
+        "#).expect("module should be written");
+
+        let source = format!(r#"
+            import "{base}/dir/utils" as u;
+            import "{base}/dir/utils";
+
+            chif main() {{
+                ret u.helper(5) + utils.helper(8) + u_helper(2);
+            }}
+        "#, base = dir.path().display());
+        // Внутреннее имя `u_helper` ещё работает в обоих режимах, но устарело
+        let mut analyzer = SemanticAnalyzer::new();
+        analyzer.analyze(&parse(&source)).expect("both module names should pass the analyzer");
+        let codes: Vec<String> = analyzer.warnings.iter().map(|warning| warning.code().to_string()).collect();
+        assert_eq!(codes, ["W0004"]);
+        assert_eq!(assert_backends_agree(&source), 30);
+
+        for path in ["..", "dir/", "my-utils"] {
+            let source = format!("import \"{}\";\nchif main() {{\n}}\n", path);
+            let error = crate::analyze_source(&source).expect_err("the module has no usable name");
+            assert!(error.to_string().contains("give it a name with 'as'"), "{}: {}", path, error);
+        }
+    }
+
+    #[test]
+    fn test_methods_defined_twice_are_reported_by_name() {
+        // left и right импортируют shared и оба добавляют Point метод area
+        let dir = tempfile::tempdir().expect("temp dir should be created");
+        let base = dir.path().display();
+        std::fs::write(dir.path().join("shared.rono"), r#"
+            struct Point { x: int, y: int, }
+        "#).expect("module should be written");
+        for (file, body) in [("left.rono", "self.x * self.y"), ("right.rono", "self.x + self.y")] {
+            std::fs::write(dir.path().join(file), format!(r#"
+                import "{base}/shared";
+                fn_for Point {{
+                    fn area(self) int {{
+                        ret {body};
+                    }}
+                }}
+            "#, base = base, body = body)).expect("module should be written");
+        }
+        let diamond = format!(r#"
+            import "{base}/left";
+            import "{base}/right";
+            chif main() {{
+            }}
+        "#, base = base);
+        let twice = r#"
+            struct Rect { w: int, h: int, }
+            fn_for Rect {
+                fn area(self) int { ret self.w * self.h; }
+            }
+            fn_for Rect {
+                fn area(self) int { ret 0; }
+            }
+            chif main() {
+            }
+        "#;
+
+        for (source, expected) in [
+            (diamond.as_str(), "Method 'area' is already defined for struct 'Point' (in left.rono and right.rono)"),
+            (twice, "Method 'area' is already defined for struct 'Rect'"),
+        ] {
+            let program = parse(source);
+            match SemanticAnalyzer::new().analyze(&program) {
+                Err(error) => assert!(error.to_string().ends_with(expected), "{}", error),
+                Ok(_) => panic!("the analyzer should reject it"),
+            }
+
+            // Without the analyzer the generator reports the same instead of a cranelift DuplicateDefinition
+            let mut generator = IRGenerator::new(pic_host_module());
+            match generator.generate(&AnalyzedProgram::new(program.items.clone())) {
+                Err(IRError::Generation(message)) => assert_eq!(message, expected),
+                other => panic!("expected a generation error, got {:?}", other.map(|_| ())),
+            }
+        }
+    }
+
+    #[test]
+    fn test_entry_point_from_an_imported_module() {
+        let dir = tempfile::tempdir().expect("temp dir should be created");
+        std::fs::write(dir.path().join("app.rono"), r#"
+            fn answer() int {
+                ret 40;
+            }
+
+            chif main() {
+                ret answer() + 2;
+            }
+
+            fn start() int {
+                ret answer() + 7;
+            }
+
+            fn greet(name: str) {
+                con.out(name);
+            }
+        "#).expect("module should be written");
+        let thin = format!(r#"import "{}/app" use (main);"#, dir.path().display());
+        let plain = format!(r#"
+            import "{}/app";
+
+            fn local() {{
+                con.out("local");
+            }}
+        "#, dir.path().display());
+
+        let analyze = |source: &str, entry: Option<&str>| {
+            let mut analyzer = SemanticAnalyzer::new();
+            analyzer.entry = entry.map(|entry| EntryPoint::parse(entry).expect("entry should parse"));
+            analyzer.analyze(&parse(source)).map(|_| ()).map_err(|error| error.to_string())
+        };
+        let run_both = |source: &str, entry: Option<&str>| -> i64 {
+            analyze(source, entry).expect("entry point should pass the analyzer");
+            let program = parse(source);
+            let entry = entry.map(|entry| EntryPoint::parse(entry).expect("entry should parse"));
+            let mut interpreter = Interpreter::new();
+            interpreter.set_entry(entry.clone());
+            let interpreted = match interpreter.run_entry(&program).expect("entry should run") {
+                ChifValue::Int(value) => value,
+                ChifValue::Nil => 0,
+                other => panic!("entry should return an int, got {:?}", other),
+            };
+            if linker_available() {
+                let runtime = concat!(env!("CARGO_MANIFEST_DIR"), "/src/runtime.c");
+                let binary = build_compiled_from(&program, entry.as_ref(), &[runtime, "-DRONO_NO_HTTP", "-lm"]);
+                let status = Command::new(&binary).status().expect("compiled program should run");
+                let _ = std::fs::remove_file(&binary);
+                assert_eq!(status.code(), Some(interpreted as i32), "{}", source);
+            }
+            interpreted
+        };
+
+        assert_eq!(run_both(&thin, None), 42);
+        assert_eq!(run_both(&plain, Some("app.start")), 47);
+        assert_eq!(run_both(&plain, Some("app.main")), 42);
+        assert_eq!(run_both(&plain, Some("local")), 0);
+
+        // Импорт без `use (main)` точку входа не назначает
+        let mut interpreter = Interpreter::new();
+        let error = interpreter.run_entry(&parse(&plain)).expect_err("plain import has no entry point");
+        assert!(error.to_string().contains("No main function found"), "{}", error);
+
+        let with_main = format!("{}\nchif main() {{ }}", thin);
+        for (source, entry) in [(with_main.as_str(), None), (thin.as_str(), Some("app.start"))] {
+            let error = analyze(source, entry).expect_err("two entry points should be rejected");
+            assert!(error.contains("Duplicate entry point"), "{}", error);
+            let mut interpreter = Interpreter::new();
+            interpreter.set_entry(entry.map(|entry| EntryPoint::parse(entry).expect("entry should parse")));
+            assert!(interpreter.run_entry(&parse(source)).is_err());
+        }
+        for (entry, problem) in [
+            ("app.greet", "must take no parameters"),
+            ("app.missing", "Undefined symbol 'app.missing'"),
+            ("other.start", "Entry module 'other' is not imported"),
+        ] {
+            let error = analyze(&plain, Some(entry)).expect_err("bad entry should be rejected");
+            assert!(error.contains(problem), "{}: {}", entry, error);
+        }
+        assert!(EntryPoint::parse("app.").is_err());
+        assert!(EntryPoint::parse("1app.start").is_err());
+    }
+
+    #[test]
+    fn test_number_formatting_is_the_same_in_both_backends() {
+        let table = [
+            ("0.1", "0.1"),
+            ("0.1 + 0.2", "0.30000000000000004"),
+            ("0.0000001", "0.0000001"),
+            ("1.0", "1.0"),
+            ("-1.5", "-1.5"),
+            ("-0.0", "-0.0"),
+            ("1234567.5", "1234567.5"),
+            ("9007199254740993.0", "9007199254740992.0"),
+            ("9999999999999998.0", "9999999999999998.0"),
+            ("10000000000000000.0", "1.0e16"),
+            ("123456789012345680000.0", "1.2345678901234568e20"),
+            ("9007199254740993", "9007199254740993"),
+            ("-42", "-42"),
+        ];
+        let max = format!("{:.1}", f64::MAX);
+        let cases: Vec<(&str, &str)> = table.iter().copied()
+            .chain([(max.as_str(), "1.7976931348623157e308")])
+            .collect();
+
+        let mut body = String::new();
+        for (expression, _) in &cases {
+            body.push_str(&format!("con.out({});\ncon.out(toStr({}));\n", expression, expression));
+        }
+        let program = parse(&format!("chif main() {{\n{}}}", body));
+        crate::analyze_source(&format!("chif main() {{\n{}}}", body)).expect("table should pass the analyzer");
+        let expected: Vec<String> = cases.iter()
+            .flat_map(|(_, text)| [text.to_string(), text.to_string()])
+            .collect();
+
+        let mut interpreter = Interpreter::new();
+        interpreter.capture_output();
+        interpreter.execute(&program).expect("table should run");
+        assert_eq!(interpreter.take_output(), expected);
+
+        // Интерполяция печатает то же, что con.out
+        let mut interpreter = Interpreter::new();
+        interpreter.capture_output();
+        interpreter.execute(&parse(r#"
+            chif main() {
+                var tiny: float = 0.0000001;
+                var whole: float = 3.0;
+                con.out("{tiny} {whole}");
+            }
+        "#)).expect("interpolation should run");
+        assert_eq!(interpreter.take_output(), ["0.0000001 3.0"]);
+
+        if linker_available() {
+            let output = run_compiled_output(&program);
+            assert_eq!(output.lines().collect::<Vec<_>>(), expected);
+            
+            // C-программа, к которой рантайм подключён библиотекой, может включить
+            // локаль с запятой; printf тогда пишет "1,5", а рантайм — всё так же "1.5"
+            if let Some(locales) = comma_locale_dir() {
+                let setup = locales.join("comma_locale.c");
+                std::fs::write(&setup, COMMA_LOCALE_SETUP).expect("locale setup should be written");
+                let runtime = concat!(env!("CARGO_MANIFEST_DIR"), "/src/runtime.c");
+                let binary = build_compiled(&program, &[setup.to_str().unwrap(), runtime, "-DRONO_NO_HTTP", "-lm"]);
+                let output = Command::new(&binary).env("LOCPATH", &locales).output().expect("compiled program should run");
+                let _ = std::fs::remove_file(&binary);
+                let _ = std::fs::remove_dir_all(&locales);
+                assert_eq!(String::from_utf8_lossy(&output.stderr), "printf: 1,5\n");
+                assert_eq!(String::from_utf8_lossy(&output.stdout).lines().collect::<Vec<_>>(), expected);
+            }
+        }
+    }
+
+    /// Включает de_DE до main и показывает, как printf пишет 1.5 в этой локали
+    const COMMA_LOCALE_SETUP: &str = r#"
+        #include <locale.h>
+        #include <stdio.h>
+        __attribute__((constructor)) static void comma_locale(void) {
+            if (setlocale(LC_NUMERIC, "de_DE.UTF-8") != NULL) {
+                fprintf(stderr, "printf: %.1f\n", 1.5);
+            }
+        }
+    "#;
+
+    /// Каталог для `LOCPATH` с собранной локалью de_DE.UTF-8; `None`, если
+    /// localedef или описания локалей в системе нет
+    fn comma_locale_dir() -> Option<std::path::PathBuf> {
+        let id = NEXT_BINARY.fetch_add(1, Ordering::SeqCst);
+        let dir = std::env::temp_dir().join(format!("rono_locale_{}_{}", std::process::id(), id));
+        std::fs::create_dir_all(&dir).ok()?;
+        let built = Command::new("localedef")
+            .args(["-i", "de_DE", "-f", "UTF-8"])
+            .arg(dir.join("de_DE.UTF-8"))
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .status()
+            .is_ok_and(|status| status.success());
+        if built {
+            Some(dir)
+        } else {
+            let _ = std::fs::remove_dir_all(&dir);
+            None
+        }
+    }
+
+    #[test]
+    fn test_array_of_structs_stores_elements_inline() {
+        let program = parse(r#"
+            struct Point {
+                x: int,
+                y: int,
+            }
+
+            chif main() {
+                array pts: Point[3] = [Point { x = 1, y = 2 }, Point { y = 4, x = 3 }, Point { x = 5, y = 6 }];
+                pts[1].x = 10;
+                var copy: Point = pts[2];
+                copy.x = 99;
+                pts[0] = copy;
+                for (var i: int = 0; i < 3; i = i + 1) {
+                    con.out(pts[i].x);
+                    con.out(pts[i].y);
+                }
+                copy.y = 0;
+                con.out(pts[0].y);
+            }
+        "#);
+
+        let mut interpreter = Interpreter::new();
+        interpreter.capture_output();
+        interpreter.execute(&program).expect("program should run");
+        let expected = interpreter.take_output();
+        assert_eq!(expected, ["99", "6", "10", "4", "5", "6", "6"]);
+
+        if linker_available() {
+            let output = run_compiled_output(&program);
+            assert_eq!(output.lines().collect::<Vec<_>>(), expected);
+        }
+    }
+
+    #[test]
+    fn test_large_array_literals_in_static_data() {
+        // table is only indexed, so it stays in read-only data; big is passed to
+        // a function, so each loop iteration gets its own copy of the literal
+        let elements: Vec<String> = (0..10_000).map(|i| (i * 3).to_string()).collect();
+        let literal = elements.join(", ");
+        let program = parse(&format!(r#"
+            fn first(xs: array[int]) int {{
+                ret xs[0];
+            }}
+
+            chif main() {{
+                var table: array[int] = [{literal}];
+                con.out(table[9999] + table[1]);
+                for (var round: int = 0; round < 2; round = round + 1) {{
+                    var big: array[int] = [{literal}];
+                    con.out(big[5] + round);
+                    con.out(first(big));
+                }}
+            }}
+        "#));
+
+        let mut interpreter = Interpreter::new();
+        interpreter.capture_output();
+        interpreter.execute(&program).expect("program should run");
+        let expected = interpreter.take_output();
+        assert_eq!(expected, ["30000", "15", "0", "16", "0"]);
+
+        if linker_available() {
+            let output = run_compiled_output(&program);
+            assert_eq!(output.lines().collect::<Vec<_>>(), expected);
+        }
+    }
+
+    #[test]
+    fn test_console_input_strips_line_terminators_only() {
+        let program = parse(r#"
+            chif main() {
+                var answer: str = con.in();
+                var padded: str = con.in();
+                var result: int = 0;
+                if (answer == "yes") {
+                    result = result + 1;
+                }
+                if (padded == "  no ") {
+                    result = result + 2;
+                }
+                if (padded.trim() == "no") {
+                    result = result + 4;
+                }
+                ret result;
+            }
+        "#);
+        // Windows line ending first, then a POSIX one
+        let input = "yes\r\n  no \n";
+
+        let mut interpreter = Interpreter::new();
+        interpreter.set_input(std::io::Cursor::new(input));
+        interpreter.load(&program).expect("program should load");
+        let interpreted = interpreter.call_function_by_name("main").expect("main should run");
+        assert_eq!(interpreted.to_string(), "7");
+
+        if linker_available() {
+            assert_eq!(run_compiled_with_input(&program, input), 7);
+        }
+    }
+
+    #[test]
+    fn test_console_input_reads_lines_longer_than_a_buffer() {
+        let long = "x".repeat(5000);
+        let program = parse(&format!(r#"
+            chif main() {{
+                var long: str = con.in();
+                var tail: str = con.in();
+                var end: str = con.in();
+                var result: int = 0;
+                if (long == "{}") {{
+                    result = result + 1;
+                }}
+                if (tail == "tail") {{
+                    result = result + 2;
+                }}
+                if (end == "") {{
+                    result = result + 4;
+                }}
+                ret result;
+            }}
+        "#, long));
+        let input = format!("{}\r\ntail\n", long);
+
+        let mut interpreter = Interpreter::new();
+        interpreter.set_input(std::io::Cursor::new(input.clone()));
+        interpreter.load(&program).expect("program should load");
+        let interpreted = interpreter.call_function_by_name("main").expect("main should run");
+        assert_eq!(interpreted.to_string(), "7");
+
+        if linker_available() {
+            assert_eq!(run_compiled_with_input(&program, &input), 7);
+        }
+    }
+
+    #[test]
+    fn test_args_of_a_sample_command_line_program() {
+        let program = parse(r#"
+            chif main() {
+                var result: int = args.count();
+                if (args.get(0) == "serve") {
+                    result = result + 10;
+                }
+                if (args.get(10) == "") {
+                    result = result + 20;
+                }
+                if (args.has_flag("verbose")) {
+                    result = result + 40;
+                }
+                if (args.has_flag("quiet")) {
+                    result = result + 1000;
+                }
+                if (args.value_of("port") == "8080") {
+                    result = result + 80;
+                }
+                // The flag is the last token, so it has no value
+                if (args.value_of("verbose") == "") {
+                    result = result + 100;
+                }
+                ret result;
+            }
+        "#);
+        let argv = ["serve", "--port", "8080", "--verbose"];
+
+        let mut interpreter = Interpreter::new();
+        interpreter.set_program_args(argv.iter().map(|arg| arg.to_string()).collect());
+        interpreter.load(&program).expect("program should load");
+        let interpreted = interpreter.call_function_by_name("main").expect("main should run");
+        assert_eq!(interpreted.to_string(), "254");
+
+        if linker_available() {
+            assert_eq!(run_compiled_with_args(&program, &argv), 254);
+            // Without arguments every lookup misses
+            assert_eq!(run_compiled_with_args(&program, &[]), 120);
+        }
+    }
+
+    /// The analyzer adds the prelude functions a program calls; the compiled
+    /// program is built from its output, the interpreter loads the prelude itself
+    fn assert_backends_agree_with_prelude(source: &str) -> i64 {
+        let program = parse(source);
+        let analyzed = SemanticAnalyzer::new().analyze(&program).expect("program should type-check");
+        let interpreted = run_interpreted(&program);
+        if linker_available() {
+            let compiled = run_compiled_with_runtime(&Program { items: analyzed.items });
+            assert_eq!((interpreted & 0xff) as i32, compiled, "{}", source);
+        }
+        interpreted
+    }
+
+    #[test]
+    fn test_prelude_functions_run_in_both_backends() {
+        let result = assert_backends_agree_with_prelude(r#"
+            chif main() {
+                var result: int = clamp(15, 0, 10) + clamp(-3, 0, 10) + min(4, 9) * 10 + abs(-2) * 100;
+                if (pad_left("7", 3, "0") == "007") {
+                    result = result + 1000;
+                }
+                if (pad_right("ab", 4, ".") == "ab..") {
+                    result = result + 2000;
+                }
+                ret result / 10 + max(1, 2);
+            }
+        "#);
+        // (10 + 0 + 40 + 200 + 3000) / 10 + 2
+        assert_eq!(result, 327);
+    }
+
+    #[test]
+    fn test_program_definitions_shadow_the_prelude() {
+        let result = assert_backends_agree_with_prelude(r#"
+            fn max(a: int, b: int) int {
+                ret a + b;
+            }
+
+            chif main() {
+                ret max(20, 22) + clamp(max(1, 2), 0, 2);
+            }
+        "#);
+        assert_eq!(result, 44);
+    }
+
+    #[test]
+    fn test_variables_and_parameters_shadow_args_os_and_rono() {
+        let result = assert_backends_agree(r#"
+            fn scale(os: int, rono: int) int {
+                ret os * rono;
+            }
+
+            fn platform_known() int {
+                if (os.name() == "") {
+                    ret 0;
+                }
+                if (rono.version() == "") {
+                    ret 0;
+                }
+                ret 100;
+            }
+
+            chif main() {
+                var args: int = scale(3, 4);
+                for os in range(0, 2) {
+                    args = args + os;
+                }
+                ret args + platform_known();
+            }
+        "#);
+        // 3 * 4 + 0 + 1, and the builtin objects are still there outside the shadowing scopes
+        assert_eq!(result, 113);
+    }
+
+    /// skip-compile: lists are not lowered yet, so only the interpreter runs it
+    /// and the compiler must refuse it cleanly
+    #[test]
+    fn test_word_reversal_with_split_and_join() {
+        let program = parse(r#"
+            fn reverse_words(text: str) str {
+                list words: str[] = text.split(" ");
+                list reversed: str[] = [];
+                for (var i: int = words.len() - 1; i >= 0; i = i - 1) {
+                    reversed.add(words[i]);
+                }
+                ret reversed.join(" ");
+            }
+
+            chif main() {
+                var result: str = reverse_words("the quick brown fox");
+                if (result == "fox brown quick the") {
+                    ret 42;
+                }
+                ret 1;
+            }
+        "#);
+        assert_eq!(run_interpreted(&program), 42);
+
+        let mut generator = IRGenerator::new(pic_host_module());
+        match generator.generate(&AnalyzedProgram::new(program.items.clone())) {
+            Err(IRError::UnsupportedFeature(message)) => assert!(message.contains("split"), "{}", message),
+            other => panic!("expected an unsupported feature error, got {:?}", other.map(|_| ())),
+        }
+    }
+
+    /// skip-compile: maps are interpreter-only; the analyzer must still type every level
+    #[test]
+    fn test_nested_map_and_list_access_with_len() {
+        let program = parse(r#"
+            chif main() {
+                var config: map[str:list[map[str:str]]] = {
+                    "servers": [{"host": "alpha", "port": "80"}, {"host": "db"}],
+                    "clients": []
+                };
+                var teams: map[str:list[str]] = {"red": ["ann", "bob"], "blue": ["eve"]};
+                var groups: map[str:map[str:list[int]]] = {"a": {"b": [1, 2, 3], "c": []}};
+                list rows: map[str:str][] = [{"name": "row"}];
+
+                var total: int = 0;
+                // Три уровня: словарь, список, словарь; len у строки
+                total = total + config["servers"][0]["host"].len();
+                total = total + config["servers"][1]["host"].len() * 10;
+                // Два уровня: len у списка, строки и словаря
+                total = total + teams["red"].len() * 100;
+                total = total + teams["blue"][0].len() * 1000;
+                total = total + groups["a"].len() * 10000;
+                total = total + groups["a"]["b"].len() * 100000;
+                total = total + config["servers"][0].len() * 1000000;
+                total = total + rows[0]["name"].len() * 10000000;
+                if (config["servers"][1].keys()[0] == "host") {
+                    total = total + config["clients"].len();
+                }
+                ret total;
+            }
+        "#);
+        let analyzed = crate::semantic::SemanticAnalyzer::new().analyze(&program);
+        assert!(analyzed.is_ok(), "{:?}", analyzed.err());
+        assert_eq!(run_interpreted(&program), 32_323_225);
+    }
+
+    #[test]
+    fn test_array_literals_become_lists_where_lists_are_declared() {
+        let program = parse(r#"
+            fn grow(items: list[int]) int {
+                items.add(7);
+                ret items.len();
+            }
+
+            chif main() int {
+                var m: map[str:list[int]] = {"a": [1, 2]};
+                m["a"].add(3);
+                var xs: list[list[int]] = [[1]];
+                xs[0].add(2);
+                var nested: map[str:map[str:list[float]]] = {"x": {"y": [1]}};
+                nested["x"]["y"].add(2.5);
+                ret m["a"].len() * 1000 + m["a"][2] * 100 + xs[0][1] * 10 + grow([1, 2]) + nested["x"]["y"].len() * 10000;
+            }
+        "#);
+        let analyzed = crate::semantic::SemanticAnalyzer::new().analyze(&program);
+        assert!(analyzed.is_ok(), "{:?}", analyzed.err());
+        assert_eq!(run_interpreted(&program), 23_323);
+    }
+
+    #[test]
+    fn test_string_switch_compares_contents() {
+        let program = parse(r#"
+            fn code(command: str) int {
+                let stop: str = "stop";
+                var prefix: str = "pa";
+                switch command:
+                case "start" {
+                    ret 1;
+                }
+                case stop {
+                    ret 2;
+                }
+                // Any str expression can be a case: it is compared by contents too
+                case prefix + "use" {
+                    ret 4;
+                }
+                default {
+                    ret 3;
+                }
+            }
+
+            fn next_calls() int {
+                var calls: int = 0;
+                switch 2 + 1:
+                case 3 {
+                    calls = calls + 1;
+                }
+                default {
+                    calls = calls + 10;
+                }
+                ret calls;
+            }
+
+            chif main() {
+                // Built from a runtime helper, so it is a different pointer than the "start" literal
+                var hex: str = 255.to_hex_str();
+                var total: int = code("start") * 100 + code("stop") * 10 + code("pause") + code("resume") * 10000;
+                switch hex:
+                case "FF" {
+                    total = total + 1000;
+                }
+                ret total + next_calls() * 2000;
+            }
+        "#);
+        let interpreted = run_interpreted(&program);
+        assert_eq!(interpreted, 30_124 + 1000 + 2000);
+        if linker_available() {
+            assert_eq!(run_compiled_with_runtime(&program), (interpreted & 0xff) as i32);
+        }
+    }
+
+    #[test]
+    fn test_interned_strings_compare_by_contents() {
+        let program = parse(r#"
+            fn kind(name: str) int {
+                switch name:
+                case "apple" {
+                    ret 1;
+                }
+                case "pear" {
+                    ret 2;
+                }
+                default {
+                    ret 0;
+                }
+            }
+
+            chif main() {
+                var key: str = intern("apple");
+                // A runtime string with the same contents as the interned one
+                var built: str = 10.to_hex_str_lower() + "pple";
+                var again: str = intern(built);
+                mem.reset();
+                var score: int = 0;
+                if (key == "apple") {
+                    score = score + 1;
+                }
+                if (again == key) {
+                    score = score + 2;
+                }
+                if (intern("pear") != key) {
+                    score = score + 4;
+                }
+                if (intern("Apple") == key) {
+                    score = score + 100;
+                }
+                ret score + kind(key) * 10 + kind(intern("pear")) * 20 + kind(intern("fig")) * 40;
+            }
+        "#);
+        let interpreted = run_interpreted(&program);
+        assert_eq!(interpreted, 7 + 10 + 40);
+        if linker_available() {
+            assert_eq!(run_compiled_with_runtime(&program), interpreted as i32);
+        }
+    }
+
+    #[test]
+    fn test_str_variables_concatenate_and_compare_by_contents() {
+        let program = parse(r#"
+            fn greet(name: str) str {
+                ret "hi " + name;
+            }
+
+            chif main() {
+                var a: str = "ab";
+                var b: str = "cd";
+                var joined: str = a + b;
+                // Built at run time, so it is not the same pointer as any literal
+                var built: str = 10.to_hex_str_lower() + "bcd";
+                var score: int = 0;
+                if (joined == built) {
+                    score = score + 1;
+                }
+                if (a + b == built) {
+                    score = score + 2;
+                }
+                if ((a + b) == (10.to_hex_str_lower() + "b" + b)) {
+                    score = score + 4;
+                }
+                if (joined != a) {
+                    score = score + 8;
+                }
+                if (greet(a) == "hi ab") {
+                    score = score + 16;
+                }
+                ret score;
+            }
+        "#);
+        let interpreted = run_interpreted(&program);
+        assert_eq!(interpreted, 31);
+        if linker_available() {
+            assert_eq!(run_compiled_with_runtime(&program), interpreted as i32);
+        }
+    }
+
+    /// 100k iterations each allocating runtime strings: with mem.reset() the arena
+    /// stays bounded, without it the allocations pile up
+    #[test]
+    fn test_mem_reset_keeps_runtime_allocations_bounded() {
+        if !linker_available() {
+            return;
+        }
+        let soak = |body: &str| parse(&format!(r#"
+            chif main() {{
+                var peak: int = 0;
+                for (var i: int = 0; i < 100000; i = i + 1) {{
+                    var hex: str = i.to_hex_str();
+                    var bits: str = (i * 31).to_binary_str();
+                    if (mem.used() > peak) {{
+                        peak = mem.used();
+                    }}
+                    {}
+                }}
+                if (peak < 1024) {{
+                    ret 42;
+                }}
+                if (peak > 1000000) {{
+                    ret 43;
+                }}
+                ret 1;
+            }}
+        "#, body));
+
+        let bounded = soak("mem.reset();");
+        assert_eq!(run_compiled_with_runtime(&bounded), 42, "arena should be emptied every iteration");
+        // The interpreter has no arena: mem.used() is always 0
+        assert_eq!(run_interpreted(&bounded), 42);
+
+        assert_eq!(run_compiled_with_runtime(&soak("")), 43, "without mem.reset() the arena keeps growing");
+    }
+
+    #[test]
+    fn test_int_modulo_takes_sign_of_dividend() {
+        let encoded = assert_backends_agree(r#"
+            chif main() {
+                var a: int = 0 - 7;
+                var b: int = 3;
+                var r1: int = a % b;
+                var r2: int = (0 - a) % (0 - b);
+                var r3: int = a % (0 - b);
+                ret (r1 + 2) * 9 + (r2 + 2) * 3 + (r3 + 2);
+            }
+        "#);
+        // -7 % 3 == -1, 7 % -3 == 1, -7 % -3 == -1
+        assert_eq!(encoded, 9 + 3 * 3 + 1);
+    }
+
+    #[test]
+    fn test_float_modulo_takes_sign_of_dividend() {
+        let program = parse(r#"
+            chif main() {
+                var a: float = 0.0 - 7.5;
+                var b: float = 2.0;
+                var hits: int = 0;
+                if (a % b == 0.0 - 1.5) {
+                    hits = hits + 1;
+                }
+                if ((0.0 - a) % (0.0 - b) == 1.5) {
+                    hits = hits + 1;
+                }
+                if (5.25 % 0.5 == 0.25) {
+                    hits = hits + 1;
+                }
+                ret hits;
+            }
+        "#);
+        assert_eq!(run_interpreted(&program), 3);
+    }
+
+    #[test]
+    fn test_int_modulo_by_zero_is_a_runtime_error() {
+        let program = parse(r#"
+            chif main() {
+                var zero: int = 0;
+                ret 5 % zero;
+            }
+        "#);
+        let mut interpreter = Interpreter::new();
+        interpreter.load(&program).expect("program should load");
+        assert!(interpreter.call_function_by_name("main").is_err());
+    }
+
+    #[test]
+    fn test_named_arguments_are_reordered_by_parameter() {
+        let encoded = assert_backends_agree(r#"
+            fn area(x: int, y: int, w: int, h: int) int {
+                ret x * 1000 + y * 100 + w * 10 + h;
+            }
+
+            chif main() {
+                var all_named: int = area(h: 4, w: 3, y: 2, x: 1);
+                var mixed: int = area(1, 2, h: 4, w: 3);
+                if (all_named != 1234) {
+                    ret 1;
+                }
+                if (mixed != 1234) {
+                    ret 2;
+                }
+                ret 42;
+            }
+        "#);
+        assert_eq!(encoded, 42);
+    }
+
+    #[test]
+    fn test_named_arguments_are_evaluated_in_source_order() {
+        let program = parse(r#"
+            fn pair(a: int, b: int) int {
+                ret a * 10 + b;
+            }
+
+            fn first() int {
+                con.out("first");
+                ret 2;
+            }
+
+            fn second() int {
+                con.out("second");
+                ret 1;
+            }
+
+            chif main() {
+                con.out(pair(b: first(), a: second()));
+                con.out(pair(second(), b: first()));
+            }
+        "#);
+        let expected = ["first", "second", "12", "second", "first", "12"];
+
+        let mut interpreter = Interpreter::new();
+        interpreter.capture_output();
+        interpreter.execute(&program).expect("program should run");
+        assert_eq!(interpreter.take_output(), expected);
+
+        if linker_available() {
+            let output = run_compiled_output(&program);
+            assert_eq!(output.lines().collect::<Vec<_>>(), expected);
+        }
+    }
+
+    #[test]
+    fn test_discarded_values_are_still_evaluated() {
+        // Integer division calls the runtime, so the compiled side links it
+        let program = parse(r#"
+            fn divmod(a: int, b: int) (int, int) {
+                ret (a / b, a % b);
+            }
+
+            fn twice(x: int) int {
+                ret x * 2;
+            }
+
+            chif main() {
+                var _ = twice(3);
+                let _ = twice(4);
+                var _: int = twice(5);
+                _ = twice(6);
+                var (_, r) = divmod(17, 5);
+                var (q, _) = divmod(17, 5);
+                ret q * 10 + r;
+            }
+        "#);
+        assert_eq!(run_interpreted(&program), 32);
+        if linker_available() {
+            assert_eq!(run_compiled_with_runtime(&program), 32);
+        }
+    }
+
+    #[test]
+    fn test_bare_return_in_main_exits_zero() {
+        // `ret;` в main — код выхода 0 из любого места: верхний уровень, if, else, циклы
+        let bodies = [
+            "ret;",
+            "var n: int = 3; if (n > 1) { ret; } ret 1;",
+            "var n: int = 0; if (n > 1) { ret 1; } else { ret; }",
+            "for i in range(0, 5) { if (i == 2) { ret; } } ret 1;",
+            "var i: int = 0; while (i < 10) { i = i + 1; if (i == 4) { ret; } } ret 1;",
+        ];
+        for body in bodies {
+            let program = parse(&format!("chif main() {{ {} }}", body));
+            assert_eq!(run_interpreted(&program), 0, "{}", body);
+            if linker_available() {
+                assert_eq!(run_compiled_with_runtime(&program), 0, "{}", body);
+            }
+        }
+    }
+
+    #[test]
+    fn test_range_loops_match_compiled() {
+        let program = parse(r#"
+            chif main() {
+                var up: int = 0;
+                for i in range(0, 5) {
+                    up = up + i;
+                }
+                if (up != 10) {
+                    ret 1;
+                }
+
+                var down: int = 0;
+                var steps: int = 0;
+                for i in range(10, 0, -2) {
+                    down = down + i;
+                    steps = steps + 1;
+                }
+                if (down != 30) {
+                    ret 2;
+                }
+                if (steps != 5) {
+                    ret 2;
+                }
+
+                var empty: int = 0;
+                for i in range(5, 5) {
+                    empty = empty + 1;
+                }
+                for i in range(5, 0) {
+                    empty = empty + 1;
+                }
+                for i in range(0, 5, -1) {
+                    empty = empty + 1;
+                }
+                if (empty != 0) {
+                    ret 3;
+                }
+
+                // The bounds are evaluated once
+                var n: int = 3;
+                var runs: int = 0;
+                for i in range(0, n) {
+                    n = n + 1;
+                    runs = runs + 1;
+                }
+                if (runs != 3) {
+                    ret 4;
+                }
+
+                var kept: int = 0;
+                for i in range(0, 10) {
+                    if (i == 2) {
+                        continue;
+                    }
+                    if (i == 6) {
+                        break;
+                    }
+                    kept = kept + 1;
+                }
+                if (kept != 5) {
+                    ret 5;
+                }
+
+                // Stepping past the i64 limits ends the loop instead of wrapping
+                var near_max: int = 0;
+                for i in range(9223372036854775800, 9223372036854775807, 5) {
+                    near_max = near_max + 1;
+                }
+                var near_min: int = 0;
+                for i in range(-9223372036854775807 - 1 + 3, -9223372036854775807 - 1, -2) {
+                    near_min = near_min + 1;
+                }
+                if (near_max != 2) {
+                    ret 6;
+                }
+                if (near_min != 2) {
+                    ret 6;
+                }
+
+                // The loop variable shadows an outer one only inside the loop
+                var i: int = 100;
+                var nested: int = 0;
+                for i in range(0, 3) {
+                    for j in range(i, 3) {
+                        nested = nested + 1;
+                    }
+                }
+                if (i != 100) {
+                    ret 7;
+                }
+                if (nested != 6) {
+                    ret 7;
+                }
+                ret 42;
+            }
+        "#);
+        assert_eq!(run_interpreted(&program), 42);
+        if linker_available() {
+            assert_eq!(run_compiled_with_runtime(&program), 42);
+        }
+    }
+
+    #[test]
+    fn test_range_zero_step_is_a_runtime_error() {
+        let program = parse(r#"
+            chif main() {
+                var step: int = 0;
+                for i in range(0, 10, step) {
+                }
+                ret 42;
+            }
+        "#);
+        let mut interpreter = Interpreter::new();
+        interpreter.load(&program).expect("program should load");
+        assert!(interpreter.call_function_by_name("main").is_err());
+        if linker_available() {
+            assert_eq!(run_compiled_with_runtime(&program), 1);
+        }
+    }
+
+    #[test]
+    fn test_integer_division_truncates_toward_zero() {
+        let program = parse(r#"
+            fn check(a: int, b: int, quotient: int, remainder: int) int {
+                if (a / b != quotient) {
+                    ret 1;
+                }
+                if (a % b != remainder) {
+                    ret 1;
+                }
+                ret 0;
+            }
+
+            chif main() {
+                var failures: int = check(7, 2, 3, 1) + check(-7, 2, -3, -1);
+                failures = failures + check(7, -2, -3, 1) + check(-7, -2, 3, -1);
+                var min: int = -9223372036854775807 - 1;
+                failures = failures + check(min, 1, min, 0);
+                if (min % -1 != 0) {
+                    failures = failures + 1;
+                }
+                if (fdiv(-7, 2) != -3.5) {
+                    failures = failures + 1;
+                }
+                ret 40 + failures;
+            }
+        "#);
+        assert_eq!(run_interpreted(&program), 40);
+        if linker_available() {
+            assert_eq!(run_compiled_with_runtime(&program), 40);
+        }
+    }
+
+    #[test]
+    fn test_integer_division_overflow_is_a_runtime_error() {
+        let program = parse(r#"
+            fn divide(a: int, b: int) int {
+                ret a / b;
+            }
+
+            chif main() {
+                var min: int = -9223372036854775807 - 1;
+                ret divide(min, -1);
+            }
+        "#);
+        let mut interpreter = Interpreter::new();
+        interpreter.load(&program).expect("program should load");
+        let error = interpreter.call_function_by_name("main").expect_err("MIN / -1 should fail");
+        assert_eq!(error.to_string(), "Integer overflow in -9223372036854775808 / -1");
+        if linker_available() {
+            let runtime = concat!(env!("CARGO_MANIFEST_DIR"), "/src/runtime.c");
+            let binary = build_compiled(&program, &[runtime, "-DRONO_NO_HTTP", "-lm"]);
+            let output = Command::new(&binary).output().expect("compiled program should run");
+            let _ = std::fs::remove_file(&binary);
+            assert_eq!(output.status.code(), Some(1));
+            assert_eq!(
+                String::from_utf8_lossy(&output.stderr),
+                "Runtime error: Integer overflow in -9223372036854775808 / -1\n"
+            );
+        }
+    }
+
+    #[test]
+    fn test_wrapping_saturating_and_checked_arithmetic_at_the_limits() {
+        let program = parse(r#"
+            fn check(actual: int, expected: int) int {
+                if (actual != expected) {
+                    ret 1;
+                }
+                ret 0;
+            }
+
+            fn check_sum(a: int, b: int, expected: int, expected_ok: bool) int {
+                var (sum, ok) = checked_add(a, b);
+                if (ok != expected_ok) {
+                    ret 1;
+                }
+                ret check(sum, expected);
+            }
+
+            fn add_or_fail(a: int, b: int) (int, bool) {
+                ret checked_add(a, b);
+            }
+
+            chif main() {
+                var max: int = 9223372036854775807;
+                var min: int = -9223372036854775807 - 1;
+                var failures: int = check(wrapping_add(max, 1), min) + check(wrapping_sub(min, 1), max);
+                failures = failures + check(wrapping_mul(max, 2), -2) + check(wrapping_mul(min, -1), min);
+                failures = failures + check(wrapping_add(2, 3), 5) + check(wrapping_mul(-4, 5), -20);
+                failures = failures + check(saturating_add(max, 1), max) + check(saturating_add(min, -1), min);
+                failures = failures + check(saturating_add(max, min), -1) + check(saturating_add(-2, 7), 5);
+                failures = failures + check(saturating_sub(min, 1), min) + check(saturating_sub(max, -1), max);
+                failures = failures + check(saturating_sub(0, min), max) + check(saturating_sub(-1, min), max);
+                failures = failures + check(saturating_sub(3, 10), -7);
+                failures = failures + check_sum(max, 1, min, false) + check_sum(min, -1, max, false);
+                failures = failures + check_sum(max, min, -1, true) + check_sum(40, 2, 42, true);
+                var (_, ok) = add_or_fail(max, max);
+                if (ok) {
+                    failures = failures + 1;
+                }
+                ret 40 + failures;
+            }
+        "#);
+        assert_eq!(run_interpreted(&program), 40);
+        if linker_available() {
+            assert_eq!(run_compiled_with_runtime(&program), 40);
+        }
+    }
+
+    /// Result of calling `function` with the int fast path on and off: both
+    /// must be the same value or the same error
+    fn run_with_and_without_int_fast_path(program: &Program, function: &str) -> String {
+        let outcomes: Vec<String> = [false, true].into_iter().map(|fast_path| {
+            let mut interpreter = Interpreter::new();
+            interpreter.set_int_fast_path(fast_path);
+            interpreter.load(program).expect("program should load");
+            match interpreter.call_function_by_name(function) {
+                Ok(value) => value.to_string(),
+                Err(error) => format!("error: {}", error),
+            }
+        }).collect();
+        assert_eq!(outcomes[0], outcomes[1], "{} differs with the int fast path", function);
+        outcomes[1].clone()
+    }
+
+    #[test]
+    fn test_int_fast_path_does_not_change_results() {
+        let source = r#"
+            fn fib(n: int) int {
+                var a: int = 0;
+                var b: int = 1;
+                for (var i: int = 0; i < n; i = i + 1) {
+                    var next: int = (a + b) % 1000;
+                    a = b;
+                    b = next;
+                }
+                ret a;
+            }
+
+            fn signs() int {
+                var a: int = -7;
+                var b: int = 2;
+                var total: int = 0;
+                if (a / b == -3) {
+                    if (a % b == -1) {
+                        if (-a % b == 1) {
+                            total = total + -a * b;
+                        }
+                    }
+                }
+                for k in range(0, 5) {
+                    total = total + k * 2 - fib(k);
+                }
+                ret total;
+            }
+
+            chif main() {
+                ret fib(20) % 256 + signs();
+            }
+        "#;
+        let interpreter_only = r#"
+            fn sieve() int {
+                var limit: int = 100;
+                var composite: list[int] = [];
+                for (var i: int = 0; i <= limit; i = i + 1) {
+                    composite.add(0);
+                }
+                var count: int = 0;
+                for (var n: int = 2; n <= limit; n = n + 1) {
+                    if (composite[n] == 0) {
+                        count = count + 1;
+                        for (var multiple: int = n * n; multiple <= limit; multiple = multiple + n) {
+                            composite[multiple] = 1;
+                        }
+                    }
+                }
+                ret count;
+            }
+
+            fn zero_divisor() int {
+                var a: int = 5;
+                ret a + 1 / (a - a);
+            }
+
+            fn min_over_minus_one() int {
+                var min: int = -9223372036854775807 - 1;
+                var minus_one: int = -1;
+                ret min % minus_one + min / minus_one;
+            }
+        "#;
+        let program = parse(&format!("{}{}", source, interpreter_only));
+        assert_eq!(run_with_and_without_int_fast_path(&program, "main"), "280");
+        assert_eq!(run_with_and_without_int_fast_path(&program, "sieve"), "25");
+        assert_eq!(run_with_and_without_int_fast_path(&program, "zero_divisor"), "error: Division by zero");
+        assert_eq!(
+            run_with_and_without_int_fast_path(&program, "min_over_minus_one"),
+            "error: Integer overflow in -9223372036854775808 / -1"
+        );
+        assert_eq!(run_interpreted(&parse(source)), 280);
+        if linker_available() {
+            assert_eq!(run_compiled_with_runtime(&parse(source)), 280 & 0xff);
+        }
+    }
+
+    /// Random loop body built from counter updates, continues and breaks
+    fn random_body(rng: &mut StdRng, var: &str, depth: usize) -> String {
+        let mut body = String::new();
+        for _ in 0..rng.gen_range(1..=4) {
+            let k = rng.gen_range(0..6);
+            let statement = match rng.gen_range(0..6) {
+                0 => format!("count = count + {};", rng.gen_range(1..4)),
+                1 => format!("if ({} == {}) {{ continue; }}", var, k),
+                2 => format!("if ({} > {}) {{ count = count + 1; continue; }}", var, k),
+                3 => format!("if ({} == {}) {{ break; }}", var, k + 2),
+                4 if depth == 0 => format!(
+                    "for (var j: int = 0; j < {}; j = j + 1) {{ {} }}",
+                    rng.gen_range(0..4),
+                    random_body(rng, "j", depth + 1)
+                ),
+                _ => "continue;".to_string(),
+            };
+            body.push_str(&statement);
+            body.push('\n');
+        }
+        body
+    }
+
+    #[test]
+    fn test_random_for_loops_with_continue_match_compiled() {
+        let mut rng = StdRng::seed_from_u64(412);
+        for _ in 0..25 {
+            let source = format!(
+                "chif main() {{\n var count: int = 0;\n for (var i: int = 0; i < {}; i = i + 1) {{\n count = count + 1;\n {} }}\n ret count;\n}}",
+                rng.gen_range(0..8),
+                random_body(&mut rng, "i", 0)
+            );
+            assert_backends_agree(&source);
+        }
+    }
+
+    /// Whether the C runtime can be built with HTTP, i.e. libcurl and its headers are installed
+    fn curl_available() -> bool {
+        let probe = std::env::temp_dir().join(format!("rono_curl_probe_{}", std::process::id()));
+        let mut child = match Command::new("cc")
+            .args(["-x", "c", "-", "-lcurl", "-o"])
+            .arg(&probe)
+            .stdin(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn()
+        {
+            Ok(child) => child,
+            Err(_) => return false,
+        };
+        let source = "#include <curl/curl.h>\nint main(void) { return curl_global_init(CURL_GLOBAL_DEFAULT) != 0; }\n";
+        let written = child.stdin.take().is_some_and(|mut stdin| stdin.write_all(source.as_bytes()).is_ok());
+        let built = child.wait().map(|status| status.success()).unwrap_or(false);
+        let _ = std::fs::remove_file(&probe);
+        written && built
+    }
+
+    /// Exit code of the compiled program linked with the full runtime, HTTP included
+    fn run_compiled_with_http(program: &Program) -> i32 {
+        let runtime = concat!(env!("CARGO_MANIFEST_DIR"), "/src/runtime.c");
+        run_compiled_linking(program, &[runtime, "-lcurl", "-lm"])
+    }
+
+    /// Local server answering every request with `status_line`; serves `requests` requests
+    fn serve_status(status_line: &'static str, requests: usize) -> u16 {
+        use std::io::{BufRead, BufReader};
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").expect("server should bind");
+        let port = listener.local_addr().expect("server should have an address").port();
+        std::thread::spawn(move || {
+            for stream in listener.incoming().take(requests) {
+                let Ok(mut stream) = stream else { continue };
+                let mut reader = BufReader::new(stream.try_clone().expect("stream should clone"));
+                let mut line = String::new();
+                while reader.read_line(&mut line).map(|read| read > 0).unwrap_or(false) && line != "\r\n" {
+                    line.clear();
+                }
+                let body = "boom";
+                let _ = write!(
+                    stream,
+                    "HTTP/1.1 {}\r\nContent-Type: text/plain\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    status_line, body.len(), body
+                );
+            }
+        });
+        port
+    }
+
+    #[cfg(feature = "http")]
+    #[test]
+    fn test_http_invalid_url_is_a_runtime_error() {
+        for url in ["ftp://example.com/file", "example.com", "http://", "https:///path"] {
+            let program = parse(&format!(r#"
+                chif main() {{
+                    var response: HttpResponse = http_get("{}");
+                    ret response.status;
+                }}
+            "#, url));
+            let mut interpreter = Interpreter::new();
+            interpreter.load(&program).expect("program should load");
+            let error = interpreter.call_function_by_name("main").expect_err("an invalid URL should fail");
+            assert!(error.to_string().starts_with(&format!("Runtime error: Invalid URL '{}'", url)), "{}", error);
+
+            if linker_available() && curl_available() {
+                let runtime = concat!(env!("CARGO_MANIFEST_DIR"), "/src/runtime.c");
+                let binary = build_compiled(&program, &[runtime, "-lcurl", "-lm"]);
+                let output = Command::new(&binary).output().expect("compiled program should run");
+                let _ = std::fs::remove_file(&binary);
+                assert_eq!(output.status.code(), Some(1));
+                assert_eq!(String::from_utf8_lossy(&output.stderr), format!("{}\n", error));
+            }
+        }
+    }
+
+    #[cfg(feature = "http")]
+    #[test]
+    fn test_http_connection_failure_is_not_ok() {
+        // A port that was just free: nothing listens there, so the connection is refused
+        let port = std::net::TcpListener::bind("127.0.0.1:0")
+            .and_then(|listener| listener.local_addr())
+            .expect("port should be allocated")
+            .port();
+        let program = parse(&format!(r#"
+            chif main() {{
+                var response: HttpResponse = http_post("http://127.0.0.1:{}/items", "{{}}");
+                if (response.ok) {{
+                    ret 1;
+                }}
+                ret 10 + response.status;
+            }}
+        "#, port));
+        assert_eq!(run_interpreted(&program), 10);
+        if linker_available() && curl_available() {
+            assert_eq!(run_compiled_with_http(&program), 10);
+        }
+    }
+
+    #[cfg(feature = "http")]
+    #[test]
+    fn test_http_error_status_is_ok() {
+        let port = serve_status("500 Internal Server Error", 2);
+        let program = parse(&format!(r#"
+            chif main() {{
+                var response: HttpResponse = http_get("http://127.0.0.1:{}/fail");
+                if (response.ok) {{
+                    ret response.status - 400;
+                }}
+                ret 1;
+            }}
+        "#, port));
+        assert_eq!(run_interpreted(&program), 100);
+        if linker_available() && curl_available() {
+            assert_eq!(run_compiled_with_http(&program), 100);
+        }
+    }
+
+    #[cfg(feature = "http")]
+    #[test]
+    fn test_lang_version_selects_http_method_result() {
+        let port = serve_status("500 Internal Server Error", 2);
+        let program = parse(&format!(r#"
+            chif main() {{
+                con.out(http.get("http://127.0.0.1:{}/fail"));
+            }}
+        "#, port));
+        let run_at = |version: LangVersion| {
+            let mut interpreter = Interpreter::new();
+            interpreter.set_lang_version(version);
+            interpreter.capture_output();
+            interpreter.execute(&program).expect("program should run");
+            interpreter.take_output()
+        };
+        assert_eq!(run_at(LangVersion::V1), ["boom"]);
+        let response = &run_at(LangVersion::V2)[0];
+        assert!(response.starts_with("HttpResponse {"), "{}", response);
+        assert!(response.contains("status: 500"), "{}", response);
+    }
+}
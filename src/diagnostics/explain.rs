@@ -0,0 +1,249 @@
+//! Описания кодов для `rono --explain`. Тексты лежат рядом с кодом, поэтому
+//! меняются вместе с диагностиками, которые описывают
+
+use super::Code;
+
+pub const E0001: &str = r#"E0001: неизвестное имя
+
+Переменная, функция, структура или модуль используются, но не объявлены в
+видимой области. Частые причины — опечатка, переменная из другого блока
+или приватная (без `export`) функция импортированного модуля.
+
+Пример:
+
+    chif main() {
+        var total: int = 1;
+        con.out(totl);      // error[E0001]: Undefined symbol 'totl'
+    }
+
+Исправление: объявите имя до использования или исправьте опечатку; функции
+модуля, которые вызываются снаружи, пометьте `export`.
+"#;
+
+pub const E0002: &str = r#"E0002: несовпадение типов
+
+Значение одного типа стоит там, где ожидается другой: в объявлении
+переменной, присваивании, аргументе функции или в `ret`. Единственное
+неявное преобразование — int в float.
+
+Пример:
+
+    fn label(count: int) str {
+        ret count;                  // error[E0002]: expected str, found int
+    }
+
+    chif main() {
+        var count: int = "три";     // error[E0002]: expected int, found str
+    }
+
+Исправление: используйте значение нужного типа или поменяйте объявленный
+тип.
+"#;
+
+pub const E0003: &str = r#"E0003: имя уже объявлено
+
+В одной области видимости два объявления с одним именем: две переменные в
+одном блоке, две функции или две структуры программы.
+
+Пример:
+
+    chif main() {
+        var count: int = 1;
+        var count: int = 2;   // error[E0003]: Symbol 'count' already defined
+    }
+
+Исправление: переименуйте одно из объявлений или присвойте новое значение
+существующей переменной: `count = 2;`.
+"#;
+
+pub const E0004: &str = r#"E0004: имя встроенного объекта
+
+Имена встроенных объектов (`con`, `http`, `args`, ...) нельзя использовать
+для переменных, параметров и функций: вызовы их методов перестали бы
+работать.
+
+Пример:
+
+    chif main() {
+        var con: int = 1;   // error[E0004]: 'con' is a builtin object name
+    }
+
+Исправление: выберите другое имя, например `counter`.
+"#;
+
+pub const E0005: &str = r#"E0005: недопустимая операция
+
+Конструкция записана правильно, но не имеет смысла для своих операндов:
+неверное число аргументов, вызов неизвестного метода, поле, которого нет у
+структуры, встроенная функция, недоступная на уровне языка программы.
+Сообщение ошибки называет конкретную причину.
+
+Пример:
+
+    fn add(a: int, b: int) int {
+        ret a + b;
+    }
+
+    chif main() {
+        con.out(add(1));   // error[E0005]: Function 'add' expects 2 arguments, got 1
+    }
+
+Исправление: следуйте тексту сообщения — передайте все аргументы, проверьте
+имя метода или поля, повысьте `--lang-version`.
+"#;
+
+pub const E0006: &str = r#"E0006: break вне цикла
+
+`break` завершает ближайший цикл `for` или `while`; вне цикла ему нечего
+завершать.
+
+Пример:
+
+    chif main() {
+        if (true) {
+            break;   // error[E0006]: Break statement outside of loop
+        }
+    }
+
+Исправление: чтобы выйти из функции, используйте `ret`.
+"#;
+
+pub const E0007: &str = r#"E0007: continue вне цикла
+
+`continue` переходит к следующей итерации ближайшего цикла `for` или
+`while` и вне цикла не допускается.
+
+Пример:
+
+    chif main() {
+        continue;   // error[E0007]: Continue statement outside of loop
+    }
+
+Исправление: перенесите `continue` в тело цикла или уберите его.
+"#;
+
+pub const W0001: &str = r#"W0001: вызов устаревшей функции
+
+Вызываемая функция помечена атрибутом `@deprecated("сообщение")`. Она
+продолжает работать, а предупреждение показывает сообщение автора функции.
+
+Пример:
+
+    @deprecated("Используйте new_add")
+    fn old_add(a: int, b: int) int {
+        ret a + b;
+    }
+
+    chif main() {
+        var x: int = old_add(1, 2);   // warning: function 'old_add' is deprecated
+    }
+
+Исправление: перейдите на замену из сообщения. Скрыть предупреждение:
+`--allow W0001`, сделать ошибкой: `--deny W0001`.
+"#;
+
+pub const W0002: &str = r#"W0002: устаревшая форма встроенной функции
+
+Встроенная функция используется в форме, которую следующий уровень языка
+меняет. Например, на уровне 1 `http.get` возвращает тело ответа строкой, а
+с уровня 2 — `HttpResponse` с полями `status` и `body`.
+
+Пример (уровень 1):
+
+    chif main() {
+        con.out(http.get("http://example.com"));   // warning: 'http.get' is deprecated at language level 1
+    }
+
+Исправление: перейдите на новый уровень (`--lang-version 2` или
+`lang-version` в rono.toml) и обновите вызовы. Скрыть предупреждение:
+`--allow W0002`.
+"#;
+
+pub const W0003: &str = r#"W0003: определение перекрывает прелюдию
+
+Функция или структура программы называется так же, как функция прелюдии
+(`clamp`, `min`, `max`, `pad_left`, ...). Программа использует своё
+определение, а функция прелюдии становится недоступной.
+
+Пример:
+
+    fn clamp(value: int) int {   // warning: 'clamp' shadows the prelude definition
+        ret value;
+    }
+
+Исправление: переименуйте функцию, если перекрытие случайное. Если
+прелюдия не нужна совсем, запускайте с `--no-prelude`; скрыть только
+предупреждение: `--allow W0003`.
+"#;
+
+pub const W0004: &str = r#"W0004: функция модуля вызвана по внутреннему имени
+
+Функции модуля вызываются через его имя: `utils.helper(...)`. Внутри
+компилятора они называются `utils_helper`, и такой вызов пока работает, но
+это имя зависит от способа сборки и будет убрано.
+
+Пример:
+
+    import "dir/utils" as u;
+
+    chif main() {
+        con.out(u_helper(4));   // warning: 'u_helper' is the internal name of 'u.helper'
+    }
+
+Исправление: вызовите функцию через точку: `u.helper(4)`.
+"#;
+
+pub const W0005: &str = r#"W0005: изменение параметра, переданного по значению
+
+Аргументы передаются по значению: функция получает копию структуры, списка,
+массива или словаря. Если она меняет поле, элемент или вызывает `add`, эти
+изменения пропадают при выходе из функции, и вызывающий их не увидит.
+
+Пример:
+
+    fn reset(p: Point) {
+        p.x = 0;   // warning: 'reset' changes 'p', which is passed by value
+    }
+
+Исправление: примите параметр как `&mut Point` и вызывайте `reset(&mut p)`,
+либо верните изменённое значение (`ret p`). Скрыть предупреждение:
+`--allow W0005`.
+"#;
+
+pub const W0006: &str = r#"W0006: путь импорта без схемы
+
+Путь в `import` начинается со схемы, по которой видно, где искать модуль:
+`./` и `../` — от каталога файла с импортом, `/` — абсолютный путь, `pkg:` —
+модуль, встроенный в Rono (`pkg:prelude`). Путь без схемы ищется так же,
+как с `./`, но эта форма устарела: её легко спутать с путём от текущего
+каталога или со стандартным модулем.
+
+Пример:
+
+    import "utils";        // warning: import path 'utils' has no scheme
+
+    chif main() {
+        con.out(utils.twice(2));
+    }
+
+Исправление: `import "./utils";`. Скрыть предупреждение: `--allow W0006`.
+"#;
+
+/// Описание кода; у каждого кода оно есть
+pub fn explanation(code: Code) -> &'static str {
+    match code {
+        Code::UndefinedSymbol => E0001,
+        Code::TypeMismatch => E0002,
+        Code::SymbolAlreadyDefined => E0003,
+        Code::ReservedName => E0004,
+        Code::InvalidOperation => E0005,
+        Code::InvalidBreak => E0006,
+        Code::InvalidContinue => E0007,
+        Code::DeprecatedUse => W0001,
+        Code::DeprecatedBuiltin => W0002,
+        Code::PreludeShadowed => W0003,
+        Code::ModuleFunctionSpelling => W0004,
+        Code::ValueParameterMutated => W0005,
+        Code::BareImportPath => W0006,
+    }
+}
@@ -0,0 +1,132 @@
+//! Коды диагностик. У каждой ошибки анализатора и каждого предупреждения есть
+//! стабильный код: `E` — ошибки, `W` — предупреждения. Код выводится рядом с
+//! сообщением и в JSON, `rono --explain <код>` печатает его описание, а
+//! `--allow`/`--deny <код>` скрывают предупреждение или делают его ошибкой
+
+pub mod explain;
+
+use std::fmt;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Code {
+    UndefinedSymbol,
+    TypeMismatch,
+    SymbolAlreadyDefined,
+    ReservedName,
+    InvalidOperation,
+    InvalidBreak,
+    InvalidContinue,
+    DeprecatedUse,
+    DeprecatedBuiltin,
+    PreludeShadowed,
+    ModuleFunctionSpelling,
+    ValueParameterMutated,
+    BareImportPath,
+}
+
+impl Code {
+    /// Все коды в порядке номеров
+    pub const ALL: [Code; 13] = [
+        Code::UndefinedSymbol,
+        Code::TypeMismatch,
+        Code::SymbolAlreadyDefined,
+        Code::ReservedName,
+        Code::InvalidOperation,
+        Code::InvalidBreak,
+        Code::InvalidContinue,
+        Code::DeprecatedUse,
+        Code::DeprecatedBuiltin,
+        Code::PreludeShadowed,
+        Code::ModuleFunctionSpelling,
+        Code::ValueParameterMutated,
+        Code::BareImportPath,
+    ];
+
+    /// Код в виде `E0002`; номера не меняются и не переиспользуются
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Code::UndefinedSymbol => "E0001",
+            Code::TypeMismatch => "E0002",
+            Code::SymbolAlreadyDefined => "E0003",
+            Code::ReservedName => "E0004",
+            Code::InvalidOperation => "E0005",
+            Code::InvalidBreak => "E0006",
+            Code::InvalidContinue => "E0007",
+            Code::DeprecatedUse => "W0001",
+            Code::DeprecatedBuiltin => "W0002",
+            Code::PreludeShadowed => "W0003",
+            Code::ModuleFunctionSpelling => "W0004",
+            Code::ValueParameterMutated => "W0005",
+            Code::BareImportPath => "W0006",
+        }
+    }
+
+    /// Код по строке, регистр буквы не важен: `e0002` — это `E0002`
+    pub fn parse(code: &str) -> Option<Code> {
+        Code::ALL.into_iter().find(|known| known.as_str().eq_ignore_ascii_case(code))
+    }
+
+    /// Предупреждение, а не ошибка: только такие коды принимают `--allow` и `--deny`
+    pub fn is_warning(self) -> bool {
+        self.as_str().starts_with('W')
+    }
+
+    /// Подробное описание с примером для `rono --explain`
+    pub fn explanation(self) -> &'static str {
+        explain::explanation(self)
+    }
+}
+
+impl fmt::Display for Code {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl PartialEq<&str> for Code {
+    fn eq(&self, other: &&str) -> bool {
+        self.as_str() == *other
+    }
+}
+
+/// Код предупреждения для `--allow`/`--deny`: неизвестный код и код ошибки отклоняются
+pub fn parse_warning_code(code: &str) -> Result<Code, String> {
+    match Code::parse(code) {
+        Some(code) if code.is_warning() => Ok(code),
+        Some(code) => Err(format!("{} is an error, only warnings can be allowed or denied", code)),
+        None => Err(format!("unknown diagnostic code '{}' (see rono --explain)", code)),
+    }
+}
+
+/// Что делать с предупреждением
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WarningAction {
+    Warn,
+    /// `--allow`: не выводить
+    Allow,
+    /// `--deny`: считать ошибкой
+    Deny,
+}
+
+/// Коды из `--allow` и `--deny`. Код из обоих списков считается запрещённым
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct WarningPolicy {
+    pub allowed: Vec<Code>,
+    pub denied: Vec<Code>,
+}
+
+impl WarningPolicy {
+    /// Действие для предупреждения с кодом `code`; предупреждения без кода выводятся как есть
+    pub fn action(&self, code: Option<&str>) -> WarningAction {
+        let Some(code) = code.and_then(Code::parse) else {
+            return WarningAction::Warn;
+        };
+        if self.denied.contains(&code) {
+            WarningAction::Deny
+        } else if self.allowed.contains(&code) {
+            WarningAction::Allow
+        } else {
+            WarningAction::Warn
+        }
+    }
+}
@@ -0,0 +1,89 @@
+#[cfg(test)]
+mod tests {
+    use crate::analyze_source;
+    use crate::diagnostics::{parse_warning_code, Code, WarningAction, WarningPolicy};
+    use crate::error::{ErrorFormat, ErrorReport};
+    use std::collections::HashSet;
+
+    /// Ловит код, добавленный в enum, но забытый в `Code::ALL`: без новой
+    /// ветки этот match не компилируется
+    fn listed(code: Code) -> bool {
+        match code {
+            Code::UndefinedSymbol
+            | Code::TypeMismatch
+            | Code::SymbolAlreadyDefined
+            | Code::ReservedName
+            | Code::InvalidOperation
+            | Code::InvalidBreak
+            | Code::InvalidContinue
+            | Code::DeprecatedUse
+            | Code::DeprecatedBuiltin
+            | Code::PreludeShadowed
+            | Code::ModuleFunctionSpelling
+            | Code::ValueParameterMutated
+            | Code::BareImportPath => Code::ALL.contains(&code),
+        }
+    }
+
+    #[test]
+    fn test_every_code_has_an_explanation_with_an_example() {
+        let mut seen = HashSet::new();
+        for code in Code::ALL {
+            assert!(listed(code), "{:?} is missing from Code::ALL", code);
+            assert!(seen.insert(code.as_str()), "{} is used twice", code);
+            assert_eq!(Code::parse(code.as_str()), Some(code));
+            let explanation = code.explanation();
+            assert!(explanation.starts_with(&format!("{}: ", code)), "{}", explanation);
+            assert!(explanation.contains("Пример"), "{} has no example", code);
+            assert_eq!(code.is_warning(), code.as_str().starts_with('W'));
+        }
+        assert_eq!(Code::parse("e0002"), Some(Code::TypeMismatch));
+        assert_eq!(Code::parse("E9999"), None);
+    }
+
+    #[test]
+    fn test_semantic_errors_carry_their_codes() {
+        let cases = [
+            ("chif main() { var x: int = missing; }", "E0001"),
+            ("chif main() { var x: int = \"text\"; }", "E0002"),
+            ("chif main() { var x: int = 1; var x: int = 2; }", "E0003"),
+            ("chif main() { var con: int = 1; }", "E0004"),
+            ("chif main() { break; }", "E0006"),
+            ("chif main() { continue; }", "E0007"),
+        ];
+        for (source, expected) in cases {
+            let error = analyze_source(source).unwrap_err();
+            assert_eq!(error.code().map(Code::as_str), Some(expected), "{}", source);
+        }
+    }
+
+    #[test]
+    fn test_code_is_rendered_in_every_error_format() {
+        let error = analyze_source("chif main() {\n    var x: int = \"text\";\n}").unwrap_err();
+        let report = ErrorReport::new(&error, "types.rono", None, &[]);
+        assert!(report.render(ErrorFormat::Pretty).contains("error[E0002]"), "{}", report.render(ErrorFormat::Pretty));
+        assert!(report.render(ErrorFormat::Plain).contains("semantic error[E0002]: "), "{}", report.render(ErrorFormat::Plain));
+        assert_eq!(report.to_json()["code"], "E0002");
+
+        let error = crate::parse_source("chif main( {").unwrap_err();
+        let report = ErrorReport::new(&error, "broken.rono", None, &[]);
+        assert!(report.render(ErrorFormat::Pretty).starts_with("error[parse]"));
+        assert!(report.to_json()["code"].is_null());
+    }
+
+    #[test]
+    fn test_only_warnings_can_be_allowed_or_denied() {
+        assert_eq!(parse_warning_code("W0003"), Ok(Code::PreludeShadowed));
+        assert!(parse_warning_code("E0002").unwrap_err().contains("is an error"));
+        assert!(parse_warning_code("W4242").unwrap_err().contains("unknown diagnostic code"));
+
+        let policy = WarningPolicy {
+            allowed: vec![Code::PreludeShadowed, Code::DeprecatedUse],
+            denied: vec![Code::DeprecatedUse],
+        };
+        assert_eq!(policy.action(Some("W0003")), WarningAction::Allow);
+        assert_eq!(policy.action(Some("W0001")), WarningAction::Deny);
+        assert_eq!(policy.action(Some("W0002")), WarningAction::Warn);
+        assert_eq!(policy.action(None), WarningAction::Warn);
+    }
+}
@@ -1,3 +1,5 @@
+use crate::style::{self, Stream, Style};
+use crate::diagnostics::Code;
 use thiserror::Error;
 
 #[derive(Error, Debug)]
@@ -9,17 +11,68 @@ pub enum ChifError {
         message: String,
     },
     
-    #[error("Parser error: {message}")]
-    ParserError { message: String },
+    #[error("Parser error at line {line}, column {column}: {message}")]
+    ParserError {
+        line: usize,
+        column: usize,
+        message: String,
+    },
+    
+    #[error("Type error in {context}: expected {expected}, found {found}")]
+    TypeError {
+        expected: String,
+        found: String,
+        context: String,
+    },
+    
+    #[error("{callee}() expects {expected} argument{}, got {got}", if *.expected == 1 { "" } else { "s" })]
+    ArityError {
+        callee: String,
+        expected: usize,
+        got: usize,
+    },
     
-    #[error("Type error: {message}")]
-    TypeError { message: String },
+    #[error("Struct '{struct_name}' has no field '{field}' (available: {})", .available.join(", "))]
+    FieldNotFound {
+        struct_name: String,
+        field: String,
+        available: Vec<String>,
+    },
+    
+    #[error("Unknown method '{method}' for {receiver_type}")]
+    MethodNotFound {
+        receiver_type: String,
+        method: String,
+    },
+    
+    #[error("Division by zero")]
+    DivisionByZero,
     
+    /// Результат не помещается в int, например `i64::MIN / -1`
+    #[error("Integer overflow in {operation}")]
+    IntegerOverflow { operation: String },
+    
+    #[error("Cannot convert {from} '{value}' to {to}")]
+    InvalidConversion {
+        from: String,
+        to: String,
+        value: String,
+    },
+    
+    /// Ошибки, для которых нет отдельного варианта
     #[error("Runtime error: {message}")]
     RuntimeError { message: String },
     
-    #[error("Variable '{name}' not found")]
-    VariableNotFound { name: String },
+    /// Кроме имени — функция, где шло обращение, похожее видимое имя и
+    /// несколько видимых переменных (`omitted` — сколько не вошло в список)
+    #[error("Variable '{name}' not found{}", lookup_context(.function, .suggestion, .in_scope, *.omitted))]
+    VariableNotFound {
+        name: String,
+        function: Option<String>,
+        suggestion: Option<String>,
+        in_scope: Vec<String>,
+        omitted: usize,
+    },
     
     #[error("Function '{name}' not found")]
     FunctionNotFound { name: String },
@@ -27,12 +80,18 @@ pub enum ChifError {
     #[error("Index out of bounds: {index}")]
     IndexOutOfBounds { index: usize },
     
-    #[error("Type mismatch: expected {expected}, found {found}")]
-    TypeMismatch { expected: String, found: String },
-    
     #[error("Invalid operation: {message}")]
     InvalidOperation { message: String },
     
+    /// Читатель stdout закрыл его (`rono run app.rono | head`): программа
+    /// останавливается без сообщения об ошибке
+    #[error("Standard output was closed")]
+    OutputClosed,
+    
+    /// Выполнение дольше лимита из `Interpreter::set_time_limit`
+    #[error("Time limit of {limit:?} exceeded")]
+    TimeLimitExceeded { limit: std::time::Duration },
+    
     #[error("Return value")]
     Return(crate::types::ChifValue),
     
@@ -43,4 +102,355 @@ pub enum ChifError {
     Continue,
 }
 
-pub type Result<T> = std::result::Result<T, ChifError>;
\ No newline at end of file
+pub type Result<T> = std::result::Result<T, ChifError>;
+
+fn lookup_context(function: &Option<String>, suggestion: &Option<String>, in_scope: &[String], omitted: usize) -> String {
+    let mut context = String::new();
+    if let Some(function) = function {
+        context.push_str(&format!(" in function '{}'", function));
+    }
+    if let Some(suggestion) = suggestion {
+        context.push_str(&format!("; did you mean '{}'?", suggestion));
+    }
+    if !in_scope.is_empty() {
+        context.push_str(&format!(" (in scope: {}", in_scope.join(", ")));
+        if omitted > 0 {
+            context.push_str(&format!(" and {} more", omitted));
+        }
+        context.push(')');
+    }
+    context
+}
+
+/// Позиция в исходном файле; строка и столбец с единицы, 0 — позиция неизвестна
+#[derive(Debug, Clone)]
+pub struct SourceLocation {
+    pub file: String,
+    pub line: usize,
+    pub column: usize,
+}
+
+impl std::fmt::Display for SourceLocation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}:{}:{}", self.file, self.line, self.column)
+    }
+}
+
+impl SourceLocation {
+    pub fn new(file: String, line: usize, column: usize) -> Self {
+        Self { file, line, column }
+    }
+    
+    pub fn unknown() -> Self {
+        Self {
+            file: "<unknown>".to_string(),
+            line: 0,
+            column: 0,
+        }
+    }
+}
+
+/// Единая ошибка для всего конвейера (лексер → парсер → анализ → IR → компиляция/выполнение).
+/// Исходная ошибка стадии сохраняется внутри варианта.
+/// Сообщение (`Display`) общее для всех стадий и написано вручную ниже
+#[derive(Debug, Error)]
+pub enum RonoError {
+    Lex(#[source] ChifError),
+    Parse(#[source] ChifError),
+    Semantic(#[from] crate::semantic::SemanticError),
+    #[cfg(feature = "backend-cranelift")]
+    IR(#[source] crate::ir_gen::IRError),
+    #[cfg(feature = "backend-cranelift")]
+    Compile(#[from] crate::compiler::CompilerError),
+    Runtime(#[source] ChifError),
+    Io(#[from] std::io::Error),
+}
+
+impl RonoError {
+    /// Ошибка интерпретатора. Return/Break/Continue — это управление потоком
+    /// внутри интерпретатора и наружу попадать не должны.
+    pub fn runtime(error: ChifError) -> Self {
+        debug_assert!(
+            !matches!(error, ChifError::Return(_) | ChifError::Break | ChifError::Continue),
+            "control-flow signal escaped the interpreter: {:?}",
+            error
+        );
+        RonoError::Runtime(error)
+    }
+    
+    /// Название стадии, на которой произошла ошибка
+    pub fn stage(&self) -> &'static str {
+        match self {
+            RonoError::Lex(_) => "lex",
+            RonoError::Parse(_) => "parse",
+            RonoError::Semantic(_) => "semantic",
+            #[cfg(feature = "backend-cranelift")]
+            RonoError::IR(_) => "ir",
+            #[cfg(feature = "backend-cranelift")]
+            RonoError::Compile(_) => "compile",
+            RonoError::Runtime(_) => "runtime",
+            RonoError::Io(_) => "io",
+        }
+    }
+    
+    pub fn location(&self) -> Option<String> {
+        match self {
+            RonoError::Lex(ChifError::LexerError { line, column, .. }) => Some(format!("{}:{}", line, column)),
+            RonoError::Parse(ChifError::ParserError { line, column, .. }) if *line > 0 => Some(format!("{}:{}", line, column)),
+            RonoError::Semantic(error) => error.location().map(|location| location.to_string()),
+            #[cfg(feature = "backend-cranelift")]
+            RonoError::Compile(crate::compiler::CompilerError::Semantic { location, .. }) => Some(location.to_string()),
+            _ => None,
+        }
+    }
+    
+    /// Строка и столбец (с единицы), если стадия их знает
+    pub fn position(&self) -> Option<(usize, usize)> {
+        match self {
+            RonoError::Lex(ChifError::LexerError { line, column, .. }) => Some((*line, *column)),
+            RonoError::Parse(ChifError::ParserError { line, column, .. }) if *line > 0 => Some((*line, *column)),
+            RonoError::Semantic(error) => error.location()
+                .filter(|location| location.line > 0)
+                .map(|location| (location.line, location.column)),
+            #[cfg(feature = "backend-cranelift")]
+            RonoError::Compile(crate::compiler::CompilerError::Semantic { location, .. }) if location.line > 0 => {
+                Some((location.line, location.column))
+            }
+            _ => None,
+        }
+    }
+    
+    /// Стабильный код диагностики (`rono --explain`), если он есть у ошибки
+    pub fn code(&self) -> Option<Code> {
+        match self {
+            RonoError::Semantic(error) => Some(error.code()),
+            #[cfg(feature = "backend-cranelift")]
+            RonoError::Compile(crate::compiler::CompilerError::Analysis(error)) => Some(error.code()),
+            _ => None,
+        }
+    }
+    
+    /// Сообщение без префикса стадии и без позиции
+    pub fn message(&self) -> String {
+        match self {
+            RonoError::Lex(ChifError::LexerError { message, .. })
+            | RonoError::Parse(ChifError::ParserError { message, .. }) => message.clone(),
+            RonoError::Lex(error) | RonoError::Parse(error) | RonoError::Runtime(error) => error.to_string(),
+            RonoError::Semantic(error) => error.message(),
+            #[cfg(feature = "backend-cranelift")]
+            RonoError::Compile(crate::compiler::CompilerError::Semantic { message, .. }) => message.clone(),
+            #[cfg(feature = "backend-cranelift")]
+            RonoError::IR(error) => error.to_string(),
+            #[cfg(feature = "backend-cranelift")]
+            RonoError::Compile(error) => error.to_string(),
+            RonoError::Io(error) => error.to_string(),
+        }
+    }
+}
+
+/// Вид вывода ошибок `rono run --error-format`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ErrorFormat {
+    /// Для человека: позиция, строка исходника с указателем и трасса вызовов
+    Pretty,
+    /// Одна строка, удобная для разбора скриптами
+    Plain,
+    /// Один JSON-объект: kind, code, message, trace, location
+    Json,
+}
+
+impl ErrorFormat {
+    pub fn parse(name: &str) -> Option<Self> {
+        match name {
+            "pretty" => Some(ErrorFormat::Pretty),
+            "plain" => Some(ErrorFormat::Plain),
+            "json" => Some(ErrorFormat::Json),
+            _ => None,
+        }
+    }
+}
+
+/// Ошибка со всем, что нужно для её вывода в любом `ErrorFormat`
+#[derive(Debug, Clone)]
+pub struct ErrorReport {
+    /// Стадия (`parse`, `runtime`, ...) или `panic` для паники внутри интерпретатора
+    pub kind: String,
+    /// Код диагностики, например `E0002`
+    pub code: Option<Code>,
+    pub message: String,
+    pub file: String,
+    pub position: Option<(usize, usize)>,
+    /// Строка исходника с ошибкой, если позиция известна
+    pub source_line: Option<String>,
+    /// Функции, через которые прошла ошибка, от места возникновения к main
+    pub trace: Vec<String>,
+    /// Сколько символов строки исходника показывает `pretty`: у длинной строки
+    /// (сгенерированный или минифицированный файл) — окно вокруг столбца ошибки
+    pub snippet_width: usize,
+}
+
+/// Ширина окна строки исходника в `pretty` по умолчанию
+pub const DEFAULT_SNIPPET_WIDTH: usize = 120;
+
+/// Окно строки шириной `width` символов вокруг столбца `column` (с единицы):
+/// обрезанные края заменяются на `...`. Возвращает текст окна и столбец
+/// указателя в нём (с нуля)
+pub fn snippet_window(line: &str, column: usize, width: usize) -> (String, usize) {
+    let chars: Vec<char> = line.chars().collect();
+    if chars.len() <= width {
+        return (line.to_string(), column.saturating_sub(1));
+    }
+    let target = column.saturating_sub(1).min(chars.len());
+    let start = target.saturating_sub(width / 2).min(chars.len() - width);
+    let end = start + width;
+    let mut window = String::new();
+    let mut caret = target - start;
+    if start > 0 {
+        window.push_str("...");
+        caret += 3;
+    }
+    window.extend(&chars[start..end]);
+    if end < chars.len() {
+        window.push_str("...");
+    }
+    (window, caret)
+}
+
+impl ErrorReport {
+    pub fn new(error: &RonoError, file: &str, source: Option<&str>, trace: &[String]) -> Self {
+        let position = error.position();
+        let source_line = position
+            .and_then(|(line, _)| source?.lines().nth(line.checked_sub(1)?))
+            .map(str::to_string);
+        Self {
+            kind: error.stage().to_string(),
+            code: error.code(),
+            message: error.message(),
+            file: file.to_string(),
+            position,
+            source_line,
+            trace: trace.to_vec(),
+            snippet_width: DEFAULT_SNIPPET_WIDTH,
+        }
+    }
+    
+    pub fn panic(message: String, file: &str, trace: &[String]) -> Self {
+        Self {
+            kind: "panic".to_string(),
+            code: None,
+            message,
+            file: file.to_string(),
+            position: None,
+            source_line: None,
+            trace: trace.to_vec(),
+            snippet_width: DEFAULT_SNIPPET_WIDTH,
+        }
+    }
+    
+    pub fn render(&self, format: ErrorFormat) -> String {
+        match format {
+            ErrorFormat::Pretty => self.render_pretty(),
+            ErrorFormat::Plain => self.render_plain(),
+            ErrorFormat::Json => self.to_json().to_string(),
+        }
+    }
+    
+    fn file_and_position(&self) -> String {
+        match self.position {
+            Some((line, column)) => format!("{}:{}:{}", self.file, line, column),
+            None => self.file.clone(),
+        }
+    }
+    
+    /// Цвет по политике `style` для stderr, куда выводятся ошибки. В скобках
+    /// после `error` — код диагностики, а у ошибок без кода — стадия
+    fn render_pretty(&self) -> String {
+        let paint = |text: &str, role: Style| style::paint(text, role, Stream::Stderr);
+        let label = match self.code {
+            Some(code) => code.as_str(),
+            None => self.kind.as_str(),
+        };
+        let mut out = format!(
+            "{}: {}\n  {} {}",
+            paint(&format!("error[{}]", label), Style::Error),
+            self.message,
+            paint("-->", Style::Location),
+            self.file_and_position()
+        );
+        if let (Some(line), Some((number, column))) = (&self.source_line, self.position) {
+            let gutter = " ".repeat(number.to_string().len());
+            let (line, caret_column) = snippet_window(line, column, self.snippet_width);
+            let caret = " ".repeat(caret_column);
+            out.push_str(&format!("\n{} |\n{} | {}\n{} | {}{}", gutter, number, line, gutter, caret, paint("^", Style::Error)));
+        }
+        if !self.trace.is_empty() {
+            out.push_str("\ntrace:");
+            for (depth, function) in self.trace.iter().enumerate() {
+                out.push_str(&format!("\n  {}: {}", depth, function));
+            }
+        }
+        out
+    }
+    
+    /// Всегда одна строка: переводы строк в сообщении заменяются пробелами
+    fn render_plain(&self) -> String {
+        let code = self.code.map(|code| format!("[{}]", code)).unwrap_or_default();
+        let mut out = format!(
+            "{}: {} error{}: {}",
+            self.file_and_position(),
+            self.kind,
+            code,
+            self.message.replace('\n', " ")
+        );
+        if !self.trace.is_empty() {
+            out.push_str(&format!(" (trace: {})", self.trace.join(" <- ")));
+        }
+        out
+    }
+    
+    /// Позиция — только строка и столбец: текст исходника в JSON не попадает,
+    /// поэтому размер не зависит от длины строк файла
+    pub fn to_json(&self) -> serde_json::Value {
+        let location = self.position.map(|(line, column)| serde_json::json!({
+            "file": self.file,
+            "line": line,
+            "column": column,
+        }));
+        serde_json::json!({
+            "kind": self.kind,
+            "code": self.code.map(Code::as_str),
+            "message": self.message,
+            "trace": self.trace,
+            "location": location,
+        })
+    }
+}
+
+impl std::fmt::Display for RonoError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.location() {
+            Some(location) => write!(f, "{} error at {}: {}", self.stage(), location, self.message()),
+            None => write!(f, "{} error: {}", self.stage(), self.message()),
+        }
+    }
+}
+
+// ChifError попадает в один из трёх вариантов, а IRError хранится в Box,
+// поэтому эти From написаны вручную, а не через #[from]
+impl From<ChifError> for RonoError {
+    fn from(error: ChifError) -> Self {
+        match error {
+            ChifError::LexerError { .. } => RonoError::Lex(error),
+            ChifError::ParserError { .. } => RonoError::Parse(error),
+            other => RonoError::runtime(other),
+        }
+    }
+}
+
+#[cfg(feature = "backend-cranelift")]
+impl From<crate::ir_gen::IRError> for RonoError {
+    fn from(error: crate::ir_gen::IRError) -> Self {
+        RonoError::IR(error)
+    }
+}
+
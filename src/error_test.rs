@@ -0,0 +1,108 @@
+#[cfg(test)]
+mod tests {
+    use crate::error::{snippet_window, ChifError, ErrorFormat, ErrorReport, RonoError, DEFAULT_SNIPPET_WIDTH};
+    use crate::{analyze_source, parse_source, run_source};
+
+    #[test]
+    fn test_lex_error_reports_stage_and_location() {
+        let error = parse_source("chif main() { let x: int = 1 $ 2; }").unwrap_err();
+        assert_eq!(error.stage(), "lex");
+        assert!(matches!(error, RonoError::Lex(ChifError::LexerError { .. })));
+        assert!(error.to_string().starts_with("lex error at 1:"), "Unexpected message: {}", error);
+    }
+
+    #[test]
+    fn test_parse_and_semantic_errors_keep_inner_error() {
+        let error = parse_source("chif main( {").unwrap_err();
+        assert!(matches!(error, RonoError::Parse(ChifError::ParserError { .. })));
+        assert!(error.to_string().starts_with("parse error at 1:"), "Unexpected message: {}", error);
+
+        let error = analyze_source("chif main() { let x: int = missing; }").unwrap_err();
+        assert_eq!(error.stage(), "semantic");
+        assert_eq!(error.to_string(), "semantic error: Undefined symbol 'missing'");
+        let source = std::error::Error::source(&error).expect("the semantic error should be the source");
+        assert!(source.to_string().starts_with("Undefined symbol 'missing'"), "{}", source);
+
+        let error = RonoError::from(std::io::Error::new(std::io::ErrorKind::NotFound, "no such file"));
+        assert_eq!(error.stage(), "io");
+        assert!(std::error::Error::source(&error).is_some());
+    }
+
+    #[test]
+    fn test_break_outside_loop_does_not_escape_interpreter() {
+        // The analyzer would reject this, but the interpreter runs it directly
+        let error = run_source(r#"
+            fn helper() {
+                break;
+            }
+
+            chif main() {
+                for (var i: int = 0; i < 3; i = i + 1) {
+                    helper();
+                }
+            }
+        "#).unwrap_err();
+
+        match &error {
+            RonoError::Runtime(ChifError::RuntimeError { message }) => {
+                assert_eq!(message, "break outside of a loop");
+            }
+            other => panic!("Expected a runtime error, got {:?}", other),
+        }
+        assert!(error.to_string().starts_with("runtime error: "));
+    }
+
+    #[test]
+    fn test_color_policy() {
+        use crate::style::{use_color, ColorChoice};
+        use std::ffi::OsStr;
+
+        assert!(use_color(ColorChoice::Auto, None, true));
+        assert!(!use_color(ColorChoice::Auto, None, false));
+        assert!(!use_color(ColorChoice::Auto, Some(OsStr::new("1")), true));
+        assert!(use_color(ColorChoice::Auto, Some(OsStr::new("")), true), "empty NO_COLOR is not set");
+        assert!(use_color(ColorChoice::Always, Some(OsStr::new("1")), false), "the flag wins over NO_COLOR");
+        assert!(!use_color(ColorChoice::Never, None, true));
+    }
+
+    #[test]
+    fn test_diagnostics_for_a_huge_single_line_stay_small() {
+        // Сгенерированная программа в одну строку ~500 КБ с ошибкой у самого конца
+        let mut source = String::from("chif main() { var x: int = 0;");
+        while source.len() < 500_000 {
+            source.push_str(" x = x + 1;");
+        }
+        source.push_str(" var y: int = (1 + ; }");
+        let error = parse_source(&source).unwrap_err();
+        let (_, column) = error.position().expect("parse error should have a position");
+        assert!(column > 499_000, "{}", column);
+
+        let report = ErrorReport::new(&error, "generated.rono", Some(&source), &[]);
+        let pretty = report.render(ErrorFormat::Pretty);
+        assert!(pretty.len() < 1_000, "{}", pretty.len());
+        let lines: Vec<&str> = pretty.lines().collect();
+        let (snippet, caret) = (lines[3], lines[4]);
+        assert!(snippet.contains("...") && snippet.len() <= DEFAULT_SNIPPET_WIDTH + 20, "{}", snippet);
+        // Указатель стоит под тем же символом, что и в полной строке
+        let caret_at = caret.find('^').expect("caret should be rendered");
+        assert_eq!(snippet[caret_at..].chars().next(), source[column - 1..].chars().next());
+        assert_eq!(&snippet[caret_at..], "; }");
+
+        for format in [ErrorFormat::Plain, ErrorFormat::Json] {
+            let rendered = report.render(format);
+            assert!(rendered.len() < 300, "{:?}: {}", format, rendered);
+            assert!(!rendered.contains("x = x + 1"), "{:?} must not include the source line", format);
+        }
+    }
+
+    #[test]
+    fn test_snippet_window() {
+        let line = "abcdefghijklmnopqrstuvwxyz";
+        assert_eq!(snippet_window(line, 3, 40), (line.to_string(), 2));
+        assert_eq!(snippet_window(line, 2, 10), ("abcdefghij...".to_string(), 1));
+        assert_eq!(snippet_window(line, 14, 10), ("...ijklmnopqr...".to_string(), 8));
+        assert_eq!(snippet_window(line, 26, 10), ("...qrstuvwxyz".to_string(), 12));
+        // Позиция за концом строки (ошибка в конце файла)
+        assert_eq!(snippet_window(line, 27, 10), ("...qrstuvwxyz".to_string(), 13));
+    }
+}
@@ -0,0 +1,210 @@
+#[cfg(feature = "backend-cranelift")]
+use crate::compiler::{detect_host_target, Compiler, OptLevel};
+use crate::error::RonoError;
+use crate::interpreter::Interpreter;
+use crate::parse_source;
+use crate::semantic::SemanticAnalyzer;
+use crate::style::{self, Stream, Style};
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// Разметка примера в комментариях в начале файла:
+///
+/// ```text
+/// // expect-output: Hello, World!   — запустить и сравнить строку вывода (можно повторять)
+/// // run                            — запустить без проверки вывода
+/// // compile                        — собрать объектный файл
+/// ```
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ExampleHeader {
+    pub run: bool,
+    pub expected_output: Option<Vec<String>>,
+    pub compile: bool,
+}
+
+impl ExampleHeader {
+    /// Читает подряд идущие строки `//` до первой строки кода
+    pub fn parse(source: &str) -> Self {
+        let mut header = Self::default();
+        let comments = source.lines()
+            .map(str::trim)
+            .skip_while(|line| line.is_empty())
+            .map_while(|line| line.strip_prefix("//"));
+
+        for comment in comments {
+            let comment = comment.trim();
+            if let Some(expected) = comment.strip_prefix("expect-output:") {
+                header.run = true;
+                header.expected_output
+                    .get_or_insert_with(Vec::new)
+                    .push(expected.strip_prefix(' ').unwrap_or(expected).to_string());
+            } else if comment == "run" {
+                header.run = true;
+            } else if comment == "compile" {
+                header.compile = true;
+            }
+        }
+        header
+    }
+}
+
+/// Этап проверки примера
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExampleStage {
+    Check,
+    Run,
+    Compile,
+}
+
+impl fmt::Display for ExampleStage {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ExampleStage::Check => write!(f, "check"),
+            ExampleStage::Run => write!(f, "run"),
+            ExampleStage::Compile => write!(f, "compile"),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ExampleFailure {
+    pub path: PathBuf,
+    pub stage: ExampleStage,
+    pub message: String,
+}
+
+/// Итог `rono examples --verify`
+#[derive(Debug, Clone, Default)]
+pub struct ExamplesReport {
+    pub files: usize,
+    pub checked: usize,
+    pub run: usize,
+    pub compiled: usize,
+    pub failures: Vec<ExampleFailure>,
+}
+
+impl ExamplesReport {
+    pub fn is_success(&self) -> bool {
+        self.failures.is_empty()
+    }
+
+    fn fail(&mut self, path: &Path, stage: ExampleStage, message: String) {
+        self.failures.push(ExampleFailure {
+            path: path.to_path_buf(),
+            stage,
+            message,
+        });
+    }
+}
+
+impl fmt::Display for ExamplesReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for failure in &self.failures {
+            let mark = style::paint("FAILED", Style::Error, Stream::Stdout);
+            writeln!(f, "{} {} [{}]: {}", mark, failure.path.display(), failure.stage, failure.message)?;
+        }
+        let summary = format!(
+            "{} files: {} checked, {} run, {} compiled, {} failed",
+            self.files, self.checked, self.run, self.compiled, self.failures.len()
+        );
+        let role = if self.is_success() { Style::Success } else { Style::Error };
+        write!(f, "{}", style::paint(summary, role, Stream::Stdout))
+    }
+}
+
+/// Все `.rono` файлы каталога и подкаталогов в отсортированном порядке
+pub fn collect_examples(dir: &Path) -> io::Result<Vec<PathBuf>> {
+    let mut files = Vec::new();
+    let mut pending = vec![dir.to_path_buf()];
+    while let Some(current) = pending.pop() {
+        for entry in fs::read_dir(&current)? {
+            let path = entry?.path();
+            if path.is_dir() {
+                pending.push(path);
+            } else if path.extension().and_then(|ext| ext.to_str()) == Some("rono") {
+                files.push(path);
+            }
+        }
+    }
+    files.sort();
+    Ok(files)
+}
+
+/// Проверяет каждый пример каталога: семантический анализ для всех файлов,
+/// запуск в интерпретаторе и сборку объектного файла — по разметке заголовка.
+/// Импорты, как и в `rono run`, ищутся от каталога файла примера.
+pub fn verify_examples(dir: &Path) -> io::Result<ExamplesReport> {
+    let mut report = ExamplesReport::default();
+    for path in collect_examples(dir)? {
+        report.files += 1;
+        let source = fs::read_to_string(&path)?;
+        let header = ExampleHeader::parse(&source);
+
+        if let Err(message) = check_example(&path, &source) {
+            report.fail(&path, ExampleStage::Check, message);
+            continue;
+        }
+        report.checked += 1;
+
+        if header.run {
+            match run_example(&path, &source, header.expected_output.as_deref()) {
+                Ok(()) => report.run += 1,
+                Err(message) => report.fail(&path, ExampleStage::Run, message),
+            }
+        }
+
+        if header.compile {
+            match compile_example(&path, &source) {
+                Ok(()) => report.compiled += 1,
+                Err(message) => report.fail(&path, ExampleStage::Compile, message),
+            }
+        }
+    }
+    Ok(report)
+}
+
+fn check_example(path: &Path, source: &str) -> Result<(), String> {
+    let program = parse_source(source).map_err(|e| e.to_string())?;
+    let mut analyzer = SemanticAnalyzer::new();
+    analyzer.source_dir = path.parent().map(Path::to_path_buf);
+    analyzer.analyze_owned(program)
+        .map(|_| ())
+        .map_err(|e| RonoError::from(e).to_string())
+}
+
+fn run_example(path: &Path, source: &str, expected_output: Option<&[String]>) -> Result<(), String> {
+    let program = parse_source(source).map_err(|e| e.to_string())?;
+    let mut interpreter = Interpreter::new();
+    interpreter.capture_output();
+    interpreter.set_source_dir(path.parent().map(Path::to_path_buf));
+    interpreter.execute(&program)
+        .map_err(|e| RonoError::runtime(e).to_string())?;
+
+    if let Some(expected) = expected_output {
+        let actual = interpreter.take_output().join("\n");
+        let expected = expected.join("\n");
+        if actual != expected {
+            return Err(format!("expected output:\n{}\ngot:\n{}", expected, actual));
+        }
+    }
+    Ok(())
+}
+
+#[cfg(feature = "backend-cranelift")]
+fn compile_example(path: &Path, source: &str) -> Result<(), String> {
+    let compile = || -> Result<(), RonoError> {
+        let program = parse_source(source)?;
+        let mut compiler = Compiler::new(detect_host_target(), OptLevel::None, false)?;
+        compiler.set_source(&path.display().to_string(), source);
+        compiler.compile_to_object(&program)?;
+        Ok(())
+    };
+    compile().map_err(|e| e.to_string())
+}
+
+#[cfg(not(feature = "backend-cranelift"))]
+fn compile_example(_path: &Path, _source: &str) -> Result<(), String> {
+    Err("rono_lang was built without the `backend-cranelift` feature".to_string())
+}
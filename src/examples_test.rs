@@ -0,0 +1,76 @@
+#[cfg(test)]
+mod tests {
+    use crate::examples::{verify_examples, ExampleHeader, ExampleStage};
+
+    #[test]
+    fn test_header_markers() {
+        let header = ExampleHeader::parse(
+            "\n// expect-output: first\n//expect-output:second\n// compile\nchif main() { }\n// run\n",
+        );
+        assert!(header.run);
+        assert!(header.compile);
+        assert_eq!(header.expected_output, Some(vec!["first".to_string(), "second".to_string()]));
+
+        let plain = ExampleHeader::parse("chif main() { }\n// compile\n");
+        assert_eq!(plain, ExampleHeader::default());
+    }
+
+    #[test]
+    #[cfg(feature = "backend-cranelift")]
+    fn test_repository_examples_verify() {
+        let dir = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("examples");
+        let report = verify_examples(&dir).expect("examples directory should be readable");
+        assert!(report.is_success(), "{}", report);
+        assert!(report.files > 0 && report.run > 0 && report.compiled > 0, "{}", report);
+    }
+
+    #[test]
+    fn test_failures_name_file_and_stage() {
+        let dir = tempfile::tempdir().expect("temp dir should be created");
+        std::fs::write(dir.path().join("bad_check.rono"), "chif main() { con.out(missing); }")
+            .expect("example should be written");
+        std::fs::write(dir.path().join("bad_output.rono"), "// expect-output: 2\nchif main() { con.out(1); }")
+            .expect("example should be written");
+        std::fs::write(dir.path().join("good.rono"), "// expect-output: 1\nchif main() { con.out(1); }")
+            .expect("example should be written");
+
+        let report = verify_examples(dir.path()).expect("temp dir should be readable");
+        assert_eq!(report.files, 3);
+        assert_eq!(report.checked, 2);
+        assert_eq!(report.run, 1);
+
+        let stages: Vec<(String, ExampleStage)> = report.failures.iter()
+            .map(|failure| (failure.path.file_name().unwrap().to_string_lossy().into_owned(), failure.stage))
+            .collect();
+        assert_eq!(stages, vec![
+            ("bad_check.rono".to_string(), ExampleStage::Check),
+            ("bad_output.rono".to_string(), ExampleStage::Run),
+        ]);
+        assert!(report.to_string().contains("FAILED"), "{}", report);
+    }
+
+    /// Examples keep their legacy bare imports: they resolve from the example's
+    /// own directory, like `./`, whatever the working directory is. Without the
+    /// Cranelift back end the examples are only run
+    #[test]
+    fn test_example_imports_are_relative_to_the_example() {
+        let compile = if cfg!(feature = "backend-cranelift") { "// compile\n" } else { "" };
+        let dir = tempfile::tempdir().expect("temp dir should be created");
+        let nested = dir.path().join("modules").join("lib");
+        std::fs::create_dir_all(&nested).expect("example directories should be created");
+        std::fs::write(dir.path().join("modules").join("legacy.rono"),
+            format!("// expect-output: 8\n{}import \"lib/shapes\";\nchif main() {{ con.out(shapes.area(2)); }}", compile))
+            .expect("example should be written");
+        std::fs::write(dir.path().join("modules").join("explicit.rono"),
+            format!("// expect-output: 4\n{}import \"./lib/units\";\nchif main() {{ con.out(units.twice(2)); }}", compile))
+            .expect("example should be written");
+        std::fs::write(nested.join("shapes.rono"), "import \"./units\";\nfn area(side: int) int { ret units.twice(side * side); }")
+            .expect("module should be written");
+        std::fs::write(nested.join("units.rono"), "fn twice(x: int) int { ret x * 2; }")
+            .expect("module should be written");
+
+        let report = verify_examples(dir.path()).expect("temp dir should be readable");
+        assert!(report.is_success(), "{}", report);
+        assert_eq!((report.files, report.run), (4, 2), "{}", report);
+    }
+}
@@ -0,0 +1,169 @@
+use crate::ast::{Function, Item, Program, StructDef};
+use crate::compiler::CompilerError;
+use crate::ir_gen::StructLayout;
+use crate::types::ChifType;
+use std::collections::{BTreeSet, HashMap};
+
+/// Generates a C header with `extern` declarations for every non-main function
+/// of a compiled Rono program, plus typedefs for structs used in those signatures.
+pub fn generate_c_header(program: &Program, library_name: &str) -> Result<String, CompilerError> {
+    let guard = header_guard(library_name);
+    let structs: HashMap<&str, &StructDef> = program.items.iter()
+        .filter_map(|item| match item {
+            Item::Struct(struct_def) => Some((struct_def.name.as_str(), struct_def)),
+            _ => None,
+        })
+        .collect();
+
+    let mut used_structs = BTreeSet::new();
+    let mut declarations = Vec::new();
+    for item in &program.items {
+        if let Item::Function(func) = item {
+            if func.is_main {
+                continue;
+            }
+            match function_declaration(func) {
+                Some(declaration) => {
+                    let types = func.params.iter()
+                        .map(|param| &param.param_type)
+                        .chain(func.return_type.iter());
+                    for chif_type in types {
+                        collect_struct_names(chif_type, &mut used_structs);
+                    }
+                    declarations.push(declaration);
+                }
+                None => declarations.push(format!(
+                    "/* {}: signature is not representable in C */",
+                    func.name
+                )),
+            }
+        }
+    }
+
+    let mut header = String::new();
+    header.push_str(&format!("#ifndef {}\n#define {}\n\n", guard, guard));
+    header.push_str("#include <stdint.h>\n\n");
+    header.push_str("#ifdef __cplusplus\nextern \"C\" {\n#endif\n\n");
+
+    for name in &used_structs {
+        let struct_def = structs.get(name.as_str()).ok_or_else(|| {
+            CompilerError::CodeGeneration(format!("Unknown struct '{}' in exported signature", name))
+        })?;
+        let layout = StructLayout::compute(struct_def)
+            .map_err(|e| CompilerError::CodeGeneration(e.to_string()))?;
+        header.push_str(&struct_typedef(&layout));
+        header.push('\n');
+    }
+
+    for declaration in &declarations {
+        header.push_str(declaration);
+        header.push('\n');
+    }
+
+    header.push_str("\n#ifdef __cplusplus\n}\n#endif\n\n");
+    header.push_str(&format!("#endif /* {} */\n", guard));
+    Ok(header)
+}
+
+/// Rejects non-main functions whose signatures have no C equivalent, so a
+/// library never exports a symbol the header cannot declare
+pub fn check_exportable(program: &Program) -> Result<(), CompilerError> {
+    let rejected: Vec<&str> = program.items.iter()
+        .filter_map(|item| match item {
+            Item::Function(func) if !func.is_main && function_declaration(func).is_none() => Some(func.name.as_str()),
+            _ => None,
+        })
+        .collect();
+
+    if rejected.is_empty() {
+        Ok(())
+    } else {
+        Err(CompilerError::SemanticAnalysis(format!(
+            "Cannot export {} from a library: the signature is not representable in the C ABI",
+            rejected.iter().map(|name| format!("'{}'", name)).collect::<Vec<_>>().join(", ")
+        )))
+    }
+}
+
+/// `mylib` -> `MYLIB_H`
+fn header_guard(library_name: &str) -> String {
+    let name: String = library_name.chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c.to_ascii_uppercase() } else { '_' })
+        .collect();
+    format!("{}_H", name)
+}
+
+fn function_declaration(func: &Function) -> Option<String> {
+    let return_type = match &func.return_type {
+        None | Some(ChifType::Nil) => "void".to_string(),
+        Some(return_type) => c_type(return_type)?,
+    };
+
+    let params = if func.params.is_empty() {
+        "void".to_string()
+    } else {
+        func.params.iter()
+            .map(|param| c_type(&param.param_type).map(|c| format!("{} {}", c, param.name)))
+            .collect::<Option<Vec<_>>>()?
+            .join(", ")
+    };
+
+    Some(format!("extern {} {}({});", return_type, func.name, params))
+}
+
+/// C type matching the Cranelift ABI of compiled code; tuples have no C equivalent
+fn c_type(chif_type: &ChifType) -> Option<String> {
+    match chif_type {
+        ChifType::Int => Some("int64_t".to_string()),
+        ChifType::Float => Some("double".to_string()),
+        ChifType::Bool => Some("uint8_t".to_string()),
+        ChifType::Str => Some("const char*".to_string()),
+        // Structs are passed to and returned from compiled functions by pointer
+        ChifType::Struct(name) => Some(format!("{}*", name)),
+        ChifType::Pointer(inner) => c_type(inner).map(|c| format!("{}*", c)),
+        ChifType::Array(_, _) | ChifType::List(_, _) | ChifType::Map(_, _) => Some("void*".to_string()),
+        _ => None,
+    }
+}
+
+fn collect_struct_names(chif_type: &ChifType, names: &mut BTreeSet<String>) {
+    match chif_type {
+        ChifType::Struct(name) => {
+            names.insert(name.clone());
+        }
+        ChifType::Pointer(inner) => collect_struct_names(inner, names),
+        _ => {}
+    }
+}
+
+/// Fields follow the offsets of `StructLayout`; gaps become explicit padding and
+/// fields without a plain C equivalent (nested structs) become opaque byte arrays
+fn struct_typedef(layout: &StructLayout) -> String {
+    let mut body = String::new();
+    let mut offset = 0u32;
+    let mut padding = 0;
+
+    for field in &layout.fields {
+        if field.offset > offset {
+            body.push_str(&format!("    uint8_t _pad{}[{}];\n", padding, field.offset - offset));
+            padding += 1;
+        }
+        match &field.field_type {
+            // Pointed-to structs may have no typedef in this header
+            ChifType::Pointer(inner) if matches!(**inner, ChifType::Struct(_)) => {
+                body.push_str(&format!("    void* {};\n", field.name));
+            }
+            ChifType::Int | ChifType::Float | ChifType::Bool | ChifType::Str | ChifType::Pointer(_) => {
+                let c = c_type(&field.field_type).unwrap_or_else(|| "void*".to_string());
+                body.push_str(&format!("    {} {};\n", c, field.name));
+            }
+            _ => body.push_str(&format!("    uint8_t {}[{}];\n", field.name, field.size)),
+        }
+        offset = field.offset + field.size;
+    }
+    if layout.size > offset {
+        body.push_str(&format!("    uint8_t _pad{}[{}];\n", padding, layout.size - offset));
+    }
+
+    format!("typedef struct {{\n{}}} {};\n", body, layout.name)
+}
@@ -0,0 +1,93 @@
+#[cfg(test)]
+mod tests {
+    use crate::ast::Program;
+    use crate::header::{check_exportable, generate_c_header};
+    use crate::lexer::Lexer;
+    use crate::parser::Parser;
+    use std::process::Command;
+
+    fn parse(source: &str) -> Program {
+        let tokens = Lexer::new(source).tokenize().expect("source should tokenize");
+        Parser::new(tokens).parse().expect("source should parse")
+    }
+
+    const LIBRARY: &str = r#"
+        struct Point {
+            flag: bool,
+            x: int,
+            y: float,
+        }
+
+        fn add(a: int, b: int) int {
+            ret a + b;
+        }
+
+        fn scale(p: Point, factor: float) float {
+            ret p.y * factor;
+        }
+
+        fn greet(name: str, loud: bool) {
+            con.out(name);
+        }
+
+        chif main() {
+        }
+    "#;
+
+    #[test]
+    fn test_header_declares_exported_functions() {
+        let header = generate_c_header(&parse(LIBRARY), "mylib").expect("header should be generated");
+
+        assert!(header.starts_with("#ifndef MYLIB_H\n#define MYLIB_H\n"));
+        assert!(header.contains("#include <stdint.h>"));
+        assert!(header.contains("extern int64_t add(int64_t a, int64_t b);"));
+        assert!(header.contains("extern double scale(Point* p, double factor);"));
+        assert!(header.contains("extern void greet(const char* name, uint8_t loud);"));
+        assert!(!header.contains("main("));
+        assert!(header.trim_end().ends_with("#endif /* MYLIB_H */"));
+    }
+
+    #[test]
+    fn test_header_struct_typedef_follows_layout() {
+        let header = generate_c_header(&parse(LIBRARY), "mylib").expect("header should be generated");
+
+        // bool takes one byte, x is aligned to 8
+        assert!(header.contains(
+            "typedef struct {\n    uint8_t flag;\n    uint8_t _pad0[7];\n    int64_t x;\n    double y;\n} Point;"
+        ));
+    }
+
+    #[test]
+    fn test_header_guard_sanitizes_library_name() {
+        let header = generate_c_header(&parse("chif main() { }"), "my-lib.v2").expect("header should be generated");
+        assert!(header.starts_with("#ifndef MY_LIB_V2_H\n"));
+    }
+
+    #[test]
+    fn test_exportable_rejects_tuple_signatures() {
+        assert!(check_exportable(&parse(LIBRARY)).is_ok());
+
+        let program = parse("fn add(a: int, b: int) int { ret a + b; }\nfn pair() (int, int) { ret (1, 2); }");
+        let error = check_exportable(&program).expect_err("tuple return should be rejected").to_string();
+        assert!(error.contains("'pair'"), "{}", error);
+        assert!(!error.contains("'add'"), "{}", error);
+    }
+
+    #[test]
+    fn test_header_compiles_as_c() {
+        if Command::new("cc").arg("--version").output().is_err() {
+            return;
+        }
+        let header = generate_c_header(&parse(LIBRARY), "mylib").expect("header should be generated");
+        let path = std::env::temp_dir().join(format!("rono_header_{}.h", std::process::id()));
+        std::fs::write(&path, header).expect("header should be written");
+
+        let status = Command::new("cc")
+            .args(["-fsyntax-only", "-x", "c"])
+            .arg(&path)
+            .status()
+            .expect("cc should run");
+        let _ = std::fs::remove_file(&path);
+        assert!(status.success(), "Generated header is not valid C");
+    }
+}
@@ -0,0 +1,51 @@
+//! Интернирование имён: каждое имя хранится один раз и получает `SymbolId`.
+//! Символы областей видимости анализатор хранит по этим id, а не по строкам.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+/// Интернированный идентификатор: имя переменной, функции, структуры, поля
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct SymbolId(u32);
+
+/// Таблица интернирования: каждая строка хранится один раз, равные строки
+/// получают один `SymbolId`
+#[derive(Debug, Clone, Default)]
+pub struct Interner {
+    ids: HashMap<Arc<str>, SymbolId>,
+    names: Vec<Arc<str>>,
+}
+
+impl Interner {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn intern(&mut self, name: &str) -> SymbolId {
+        if let Some(&id) = self.ids.get(name) {
+            return id;
+        }
+        let id = SymbolId(u32::try_from(self.names.len()).expect("at most u32::MAX names can be interned"));
+        let name: Arc<str> = Arc::from(name);
+        self.names.push(Arc::clone(&name));
+        self.ids.insert(name, id);
+        id
+    }
+
+    /// Id уже интернированной строки; новых строк не добавляет
+    pub fn get(&self, name: &str) -> Option<SymbolId> {
+        self.ids.get(name).copied()
+    }
+
+    pub fn resolve(&self, id: SymbolId) -> &str {
+        &self.names[id.0 as usize]
+    }
+
+    pub fn len(&self) -> usize {
+        self.names.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.names.is_empty()
+    }
+}
@@ -0,0 +1,25 @@
+#[cfg(test)]
+mod tests {
+    use crate::interner::Interner;
+
+    #[test]
+    fn test_equal_names_share_one_id() {
+        let mut interner = Interner::new();
+        let first = interner.intern("half");
+        let other = interner.intern("rest");
+        assert_eq!(interner.intern("half"), first);
+        assert_ne!(first, other);
+        assert_eq!(interner.len(), 2);
+        assert_eq!(interner.resolve(first), "half");
+        assert_eq!(interner.resolve(other), "rest");
+    }
+
+    #[test]
+    fn test_get_does_not_intern() {
+        let mut interner = Interner::new();
+        assert!(interner.get("undeclared").is_none());
+        assert!(interner.is_empty());
+        let id = interner.intern("declared");
+        assert_eq!(interner.get("declared"), Some(id));
+    }
+}
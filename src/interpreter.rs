@@ -1,22 +1,94 @@
 use crate::ast::*;
 use crate::error::{ChifError, Result};
-use crate::types::ChifValue;
-use rand::Rng;
-use std::collections::HashMap;
-use std::io;
+use crate::lang::LangVersion;
+use crate::profile::{CallKind, Profile, Profiler};
+use crate::semantic::{is_builtin_object_name, is_builtin_object_type, is_shadowable_object_name, suggest_name};
+use crate::types::{format_float, ChifValue, StructFields};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::collections::{HashMap, HashSet};
+use std::io::{self, Write};
+use std::path::PathBuf;
+use std::rc::Rc;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// Код выхода программы, stdout которой закрыл читатель: так оболочка
+/// показывает процесс, завершённый SIGPIPE. Его же возвращает скомпилированная
+/// программа (`RONO_EXIT_OUTPUT_CLOSED` в runtime.c)
+pub const EXIT_OUTPUT_CLOSED: i32 = 141;
+
+/// Сколько видимых имён перечисляет ошибка VariableNotFound
+const MAX_LISTED_NAMES: usize = 8;
 
 pub struct Interpreter {
     globals: HashMap<String, ChifValue>,
     locals: Vec<HashMap<String, ChifValue>>,
-    functions: HashMap<String, Function>,
+    // Функции и методы хранятся в Rc: вызов не копирует их тела
+    functions: HashMap<String, Rc<Function>>,
     structs: HashMap<String, StructDef>,
-    struct_methods: HashMap<String, Vec<Function>>,
+    struct_methods: HashMap<String, Vec<Rc<Function>>>,
     modules: HashMap<String, Module>,
+    rng: StdRng,
+    // Строки con.out, если вывод перехвачен вместо печати в stdout
+    captured_output: Option<Vec<String>>,
+    // Куда con.out пишет вместо stdout
+    output: Option<Box<dyn io::Write>>,
+    // Источник строк для con.in вместо stdin
+    scripted_input: Option<Box<dyn io::BufRead>>,
+    // Имена выполняемых функций, от внешней к текущей
+    call_stack: Vec<String>,
+    // Функции, из которых вышла последняя ошибка, от внутренней к внешней
+    error_trace: Vec<String>,
+    // Таблица интернирования: ключи словарей и строки intern() хранятся по одному разу
+    interned: HashSet<Arc<str>>,
+    // Файлы модулей, которые сейчас загружаются: защита от циклических импортов.
+    // Импорты модуля ищутся от каталога последнего из них
+    importing: Vec<PathBuf>,
+    // Каталог файла программы для её импортов; `None` — текущий каталог
+    source_dir: Option<PathBuf>,
+    // Уровень языка: от него зависит, что возвращают методы `http`
+    lang_version: LangVersion,
+    // Точка входа из `--entry`; без неё выполнение начинается с `chif main`
+    entry: Option<EntryPoint>,
+    // Выражения с `BinaryOp::int_only` считаются на i64 (`evaluate_int_binary`)
+    int_fast_path: bool,
+    // Аргументы командной строки после имени программы, для `args`
+    program_args: Vec<String>,
+    // Подключать прелюдию (`--no-prelude` выключает)
+    prelude: bool,
+    // Момент, после которого выполнение прерывается, и сам лимит для сообщения
+    deadline: Option<(Instant, Duration)>,
+    // Профиль `--profile`; без него вызовы ничего не замеряют
+    profiler: Option<Profiler>,
+}
+
+/// Почему быстрый путь для int не дал значения. Ошибка выполнения
+/// (`ChifError`) создаётся только на выходе из быстрого пути
+#[derive(Debug)]
+enum IntFault {
+    /// Операнд оказался не int или оператор не арифметический
+    NotInt,
+    DivisionByZero,
+    /// Результат не помещается в i64: `i64::MAX + 1`, `i64::MIN / -1`;
+    /// строка — сама операция для сообщения
+    Overflow(String),
+}
+
+impl IntFault {
+    /// `None` для `NotInt`: это не ошибка, выражение считается обычным путём
+    fn into_error(self) -> Option<ChifError> {
+        match self {
+            IntFault::NotInt => None,
+            IntFault::DivisionByZero => Some(ChifError::DivisionByZero),
+            IntFault::Overflow(operation) => Some(ChifError::IntegerOverflow { operation }),
+        }
+    }
 }
 
 #[derive(Debug, Clone)]
 pub struct Module {
-    pub functions: HashMap<String, Function>,
+    pub functions: HashMap<String, Rc<Function>>,
     pub structs: HashMap<String, StructDef>,
 }
 
@@ -25,11 +97,19 @@ impl Interpreter {
         let mut globals = HashMap::new();
         
         // Add console object
-        let mut console_methods = HashMap::new();
+        let mut console_methods = StructFields::new();
         console_methods.insert("out".to_string(), ChifValue::Str("console_out".to_string()));
         console_methods.insert("in".to_string(), ChifValue::Str("console_in".to_string()));
         globals.insert("con".to_string(), ChifValue::Struct("Console".to_string(), console_methods));
         
+        // Add memory object: values are freed automatically, so its methods only exist
+        // for compatibility with compiled programs
+        globals.insert("mem".to_string(), ChifValue::Struct("Memory".to_string(), StructFields::new()));
+        globals.insert("http".to_string(), ChifValue::Struct("Http".to_string(), StructFields::new()));
+        globals.insert("args".to_string(), ChifValue::Struct("ProgramArgs".to_string(), StructFields::new()));
+        globals.insert("os".to_string(), ChifValue::Struct("Os".to_string(), StructFields::new()));
+        globals.insert("rono".to_string(), ChifValue::Struct("RonoInfo".to_string(), StructFields::new()));
+        
         Self {
             globals,
             locals: Vec::new(),
@@ -37,58 +117,393 @@ impl Interpreter {
             structs: HashMap::new(),
             struct_methods: HashMap::new(),
             modules: HashMap::new(),
+            rng: StdRng::from_entropy(),
+            captured_output: None,
+            output: None,
+            scripted_input: None,
+            call_stack: Vec::new(),
+            error_trace: Vec::new(),
+            interned: HashSet::new(),
+            importing: Vec::new(),
+            lang_version: LangVersion::default(),
+            entry: None,
+            int_fast_path: true,
+            program_args: Vec::new(),
+            prelude: true,
+            source_dir: None,
+            deadline: None,
+            profiler: None,
+        }
+    }
+    
+    pub fn set_lang_version(&mut self, version: LangVersion) {
+        self.lang_version = version;
+    }
+    
+    /// Начинает выполнение с `entry` (`--entry app.start`) вместо `chif main`
+    pub fn set_entry(&mut self, entry: Option<EntryPoint>) {
+        self.entry = entry;
+    }
+    
+    /// Аргументы программы для `args`: то, что в скомпилированной программе
+    /// идёт в argv после её имени
+    pub fn set_program_args(&mut self, args: Vec<String>) {
+        self.program_args = args;
+    }
+    
+    /// Подключать ли прелюдию при `load`; по умолчанию подключается
+    pub fn set_prelude(&mut self, enabled: bool) {
+        self.prelude = enabled;
+    }
+    
+    /// Включает профиль выполнения: время и число вызовов каждой функции
+    /// и встроенной функции, копии коллекций и рост списков
+    pub fn enable_profile(&mut self) {
+        self.profiler = Some(Profiler::new());
+    }
+    
+    /// Профиль с `enable_profile` до этого момента; профиль выключается
+    pub fn take_profile(&mut self) -> Option<Profile> {
+        self.profiler.take().map(Profiler::finish)
+    }
+    
+    /// Каталог файла программы: от него ищутся `import "./utils"` и `import "utils"`
+    pub fn set_source_dir(&mut self, dir: Option<PathBuf>) {
+        self.source_dir = dir;
+    }
+    
+    /// Включает или выключает быстрый путь для выражений над int. Результат
+    /// от него не зависит; выключают его, чтобы сравнить скорость
+    pub fn set_int_fast_path(&mut self, enabled: bool) {
+        self.int_fast_path = enabled;
+    }
+    
+    /// Ограничивает время выполнения, считая от текущего момента: блок, начатый
+    /// после истечения `limit`, завершается ошибкой `TimeLimitExceeded`.
+    /// `None` снимает ограничение
+    pub fn set_time_limit(&mut self, limit: Option<Duration>) {
+        self.deadline = limit.map(|limit| (Instant::now() + limit, limit));
+    }
+    
+    /// Перенаправляет con.out в буфер; строки забираются через `take_output`
+    pub fn capture_output(&mut self) {
+        self.captured_output.get_or_insert_with(Vec::new);
+    }
+    
+    /// Возвращает перехваченные строки вывода и очищает буфер
+    pub fn take_output(&mut self) -> Vec<String> {
+        self.captured_output.as_mut().map(std::mem::take).unwrap_or_default()
+    }
+    
+    /// Пишет con.out в `output` вместо stdout
+    pub fn set_output(&mut self, output: impl io::Write + 'static) {
+        self.output = Some(Box::new(output));
+    }
+    
+    /// Строка con.out. Закрытый читателем вывод (`BrokenPipe`) останавливает
+    /// программу ошибкой `OutputClosed`, которую CLI завершает без сообщения
+    fn write_output_line(&mut self, line: String) -> Result<()> {
+        let written = match (&mut self.captured_output, &mut self.output) {
+            (Some(lines), _) => {
+                lines.push(line);
+                return Ok(());
+            }
+            (None, Some(output)) => writeln!(output, "{}", line),
+            (None, None) => writeln!(io::stdout().lock(), "{}", line),
+        };
+        written.map_err(|e| match e.kind() {
+            io::ErrorKind::BrokenPipe => ChifError::OutputClosed,
+            _ => ChifError::RuntimeError {
+                message: format!("Failed to write console output: {}", e),
+            },
+        })
+    }
+    
+    /// Читает con.in из `input` вместо stdin
+    pub fn set_input(&mut self, input: impl io::BufRead + 'static) {
+        self.scripted_input = Some(Box::new(input));
+    }
+    
+    /// Одна строка ввода без завершающего "\n" или "\r\n"; остальные пробелы
+    /// сохраняются, их убирает trim(). В конце ввода возвращается пустая строка
+    fn read_console_line(&mut self) -> Result<String> {
+        let mut line = String::new();
+        let read = match &mut self.scripted_input {
+            Some(input) => input.read_line(&mut line),
+            None => io::stdin().read_line(&mut line),
+        };
+        read.map_err(|e| ChifError::RuntimeError {
+            message: format!("Failed to read console input: {}", e),
+        })?;
+        
+        if line.ends_with('\n') {
+            line.pop();
+            if line.ends_with('\r') {
+                line.pop();
+            }
         }
+        Ok(line)
     }
     
     pub fn execute(&mut self, program: &Program) -> Result<()> {
-        // First pass: process imports and collect all functions and structs
+        self.run_entry(program)?;
+        Ok(())
+    }
+    
+    /// Загружает программу и выполняет её точку входа, возвращая её результат
+    pub fn run_entry(&mut self, program: &Program) -> Result<ChifValue> {
+        self.error_trace.clear();
+        self.load(program)?;
+        let entry = self.entry_function(program)?;
+        self.call_function(&entry, Vec::new()).map(Self::void_as_nil)
+    }
+    
+    /// Функция, с которой начинается выполнение: `chif main` файла, `main`
+    /// из `import "app" use (main);` или функция из `--entry`
+    pub fn entry_function(&self, program: &Program) -> Result<Rc<Function>> {
+        let runtime_error = |message: String| ChifError::RuntimeError { message };
+        let Some(entry) = program.entry_point(self.entry.as_ref()).map_err(runtime_error)? else {
+            // `main` без `chif` не назначает точку входа
+            let message = if program.items.iter().any(|item| matches!(item, Item::Function(func) if func.name == "main")) {
+                "Main function must be marked with 'chif'"
+            } else {
+                "No main function found"
+            };
+            return Err(runtime_error(message.to_string()));
+        };
+        
+        let function = match &entry.module {
+            Some(module) => self.modules.get(module)
+                .ok_or_else(|| runtime_error(format!("Entry module '{}' is not imported", module)))?
+                .functions.get(&entry.function),
+            None => self.functions.get(&entry.function),
+        };
+        let function = function.cloned().ok_or_else(|| ChifError::FunctionNotFound { name: entry.to_string() })?;
+        if let Some(problem) = entry_signature_problem(&entry, &function.params, function.return_type.as_ref()) {
+            return Err(runtime_error(problem));
+        }
+        Ok(function)
+    }
+    
+    /// Регистрирует импорты, функции и структуры программы, не запуская main
+    /// Регистрирует всё до выполнения: сначала прелюдию и импорты (с их собственными
+    /// импортами), затем функции, структуры и методы файла. Имена ищутся при
+    /// вызове, поэтому порядок определений в файле не важен, а определение
+    /// файла перекрывает импортированное с тем же именем, где бы ни стоял import.
+    /// Последними по порядку вычисляются глобальные переменные: их значения
+    /// могут вызывать любые функции файла
+    pub fn load(&mut self, program: &Program) -> Result<()> {
+        // Прелюдия — первой, чтобы функции импортов и файла её перекрывали
+        if self.prelude {
+            for item in crate::prelude::program().items {
+                if let Item::Function(func) = item {
+                    self.functions.entry(func.name.clone()).or_insert_with(|| Self::prepare_function(&func));
+                }
+            }
+        }
+        
+        for item in &program.items {
+            if let Item::Import(import) = item {
+                self.process_import(import)?;
+            }
+        }
+        
         for item in &program.items {
             match item {
-                Item::Import(import) => {
-                    self.process_import(import)?;
-                }
+                Item::Import(_) => {}
                 Item::Function(func) => {
-                    self.functions.insert(func.name.clone(), func.clone());
+                    Self::check_not_reserved(&func.name)?;
+                    Self::check_params_not_reserved(func)?;
+                    self.functions.insert(func.name.clone(), Self::prepare_function(func));
                 }
                 Item::Struct(struct_def) => {
+                    if is_builtin_object_type(&struct_def.name) {
+                        return Err(ChifError::InvalidOperation {
+                            message: format!("'{}' is a builtin object name and cannot be redeclared", struct_def.name),
+                        });
+                    }
                     self.structs.insert(struct_def.name.clone(), struct_def.clone());
                 }
                 Item::StructImpl(impl_block) => {
+                    for method in &impl_block.methods {
+                        Self::check_params_not_reserved(method)?;
+                    }
                     self.struct_methods
                         .entry(impl_block.struct_name.clone())
                         .or_insert_with(Vec::new)
-                        .extend(impl_block.methods.clone());
+                        .extend(impl_block.methods.iter().map(Self::prepare_function));
                 }
+                Item::Global(_) => {}
             }
         }
         
-        // Find and execute main function
-        if let Some(main_func) = self.functions.get("main").cloned() {
-            if main_func.is_main {
-                self.call_function(&main_func, Vec::new())?;
-            } else {
-                return Err(ChifError::RuntimeError {
-                    message: "Main function must be marked with 'chif'".to_string(),
-                });
+        for item in &program.items {
+            if let Item::Global(var_decl) = item {
+                self.execute_statement(&Statement::VarDecl(var_decl.clone()))?;
             }
-        } else {
-            return Err(ChifError::RuntimeError {
-                message: "No main function found".to_string(),
-            });
         }
         
         Ok(())
     }
     
+    /// Копия функции для выполнения: с отметками быстрого пути для int
+    fn prepare_function(func: &Function) -> Rc<Function> {
+        let mut func = func.clone();
+        func.mark_int_expressions();
+        Rc::new(func)
+    }
+    
+    /// Вызывает функцию без параметров по имени (используется `rono bench`)
+    pub fn call_function_by_name(&mut self, name: &str) -> Result<ChifValue> {
+        let func = self.lookup_function(name)?;
+        self.call_function(&func, Vec::new()).map(Self::void_as_nil)
+    }
+    
+    /// break/continue не должны выходить за границу функции
+    fn loop_signal_outside_loop(error: ChifError) -> ChifError {
+        match error {
+            ChifError::Break => ChifError::RuntimeError {
+                message: "break outside of a loop".to_string(),
+            },
+            ChifError::Continue => ChifError::RuntimeError {
+                message: "continue outside of a loop".to_string(),
+            },
+            other => other,
+        }
+    }
+    
+    fn check_arity(callee: &str, args: &[Expression], expected: usize) -> Result<()> {
+        if args.len() != expected {
+            return Err(ChifError::ArityError {
+                callee: callee.to_string(),
+                expected,
+                got: args.len(),
+            });
+        }
+        Ok(())
+    }
+    
+    fn type_error(expected: &str, found: &ChifValue, context: impl Into<String>) -> ChifError {
+        ChifError::TypeError {
+            expected: expected.to_string(),
+            found: found.get_type().to_string(),
+            context: context.into(),
+        }
+    }
+    
+    fn expect_int(value: ChifValue, context: &str) -> Result<i64> {
+        match value {
+            ChifValue::Int(value) => Ok(value),
+            other => Err(Self::type_error("int", &other, context)),
+        }
+    }
+    
+    fn expect_float(value: ChifValue, context: &str) -> Result<f64> {
+        match value {
+            ChifValue::Float(value) => Ok(value),
+            other => Err(Self::type_error("float", &other, context)),
+        }
+    }
+    
+    fn expect_str(value: ChifValue, context: &str) -> Result<String> {
+        match value {
+            ChifValue::Str(value) => Ok(value),
+            other => Err(Self::type_error("str", &other, context)),
+        }
+    }
+    
+    fn method_not_found(receiver_type: &str, method: &str) -> ChifError {
+        ChifError::MethodNotFound {
+            receiver_type: receiver_type.to_string(),
+            method: method.to_string(),
+        }
+    }
+    
+    fn invalid_conversion(value: &ChifValue, to: &str) -> ChifError {
+        ChifError::InvalidConversion {
+            from: value.get_type().to_string(),
+            to: to.to_string(),
+            value: value.to_string(),
+        }
+    }
+    
+    /// Массивы и списки индексируются int, словари — str
+    fn index_error(container: &ChifValue, index: &ChifValue) -> ChifError {
+        match container {
+            ChifValue::Array(_) | ChifValue::List(_) => Self::type_error("int", index, "index"),
+            ChifValue::Map(_) => Self::type_error("str", index, "map key"),
+            other => Self::type_error("array, list or map", other, "indexing"),
+        }
+    }
+    
+    /// Общая копия строки из таблицы интернирования; сравнение строк от этого не меняется
+    fn intern(&mut self, text: &str) -> Arc<str> {
+        if let Some(interned) = self.interned.get(text) {
+            return Arc::clone(interned);
+        }
+        let interned: Arc<str> = Arc::from(text);
+        self.interned.insert(Arc::clone(&interned));
+        interned
+    }
+    
+    /// `ChifValue::Void` не выходит за пределы интерпретатора: снаружи это `nil`
+    fn void_as_nil(value: ChifValue) -> ChifValue {
+        match value {
+            ChifValue::Void => ChifValue::Nil,
+            value => value,
+        }
+    }
+    
+    /// Та же ошибка, что у анализатора, для вызова без значения на месте значения
+    fn no_value(expr: &Expression) -> ChifError {
+        let callee = match expr {
+            Expression::Call(call) => format!("function '{}'", call.name),
+            Expression::MethodCall(method_call) => format!("method '{}'", method_call.method),
+            _ => "expression".to_string(),
+        };
+        ChifError::RuntimeError {
+            message: format!("{} has no return value and cannot be used in an expression", callee),
+        }
+    }
+    
+    fn lookup_function(&self, name: &str) -> Result<Rc<Function>> {
+        self.functions.get(name).cloned().ok_or_else(|| ChifError::FunctionNotFound {
+            name: name.to_string(),
+        })
+    }
+    
+    /// Встроенные объекты (con, http, ...) нельзя переобъявлять, как и в семантическом анализаторе
+    fn check_not_reserved(name: &str) -> Result<()> {
+        if is_builtin_object_name(name) {
+            return Err(ChifError::InvalidOperation {
+                message: format!("'{}' is a builtin object name and cannot be redeclared", name),
+            });
+        }
+        Ok(())
+    }
+    
+    /// Переменные и параметры могут перекрыть `args`, `os` и `rono`: объект
+    /// остаётся глобальным, а локальное имя его заслоняет
+    fn check_local_not_reserved(name: &str) -> Result<()> {
+        if is_shadowable_object_name(name) {
+            return Ok(());
+        }
+        Self::check_not_reserved(name)
+    }
+    
+    fn check_params_not_reserved(func: &Function) -> Result<()> {
+        for param in &func.params {
+            Self::check_local_not_reserved(&param.name)?;
+        }
+        Ok(())
+    }
+    
     fn call_function(&mut self, func: &Function, args: Vec<ChifValue>) -> Result<ChifValue> {
         if args.len() != func.params.len() {
-            return Err(ChifError::RuntimeError {
-                message: format!(
-                    "Function '{}' expects {} arguments, got {}",
-                    func.name,
-                    func.params.len(),
-                    args.len()
-                ),
+            return Err(ChifError::ArityError {
+                callee: func.name.clone(),
+                expected: func.params.len(),
+                got: args.len(),
             });
         }
         
@@ -97,23 +512,190 @@ impl Interpreter {
         
         // Bind parameters
         for (param, arg) in func.params.iter().zip(args.iter()) {
-            scope.insert(param.name.clone(), arg.clone());
+            self.count_copy(arg);
+            scope.insert(param.name.clone(), Self::conform_to_type(&param.param_type, arg.clone()));
         }
         
+        let (result, _) = self.execute_function_body(func, scope);
+        
+        match result {
+            Ok(_) => Ok(ChifValue::Void),
+            Err(ChifError::Return(value)) => Ok(match &func.return_type {
+                Some(return_type) => Self::conform_to_type(return_type, value),
+                None => value,
+            }),
+            Err(e) => Err(Self::loop_signal_outside_loop(e)),
+        }
+    }
+    
+    /// Тело функции в новой области; пока оно выполняется, имя функции
+    /// лежит на вершине `call_stack`
+    fn execute_function_body(&mut self, func: &Function, scope: HashMap<String, ChifValue>) -> (Result<()>, HashMap<String, ChifValue>) {
+        if self.profiler.is_some() {
+            let name = self.profile_name(func, &scope);
+            if let Some(profiler) = &mut self.profiler {
+                profiler.enter(&name, CallKind::Function);
+            }
+        }
+        self.call_stack.push(func.name.clone());
+        let outcome = self.in_scope(scope, |this| this.execute_block(&func.body));
+        if let Err(error) = &outcome.0 {
+            // break/continue, вышедшие из тела функции, тоже становятся ошибкой
+            if !matches!(error, ChifError::Return(_)) {
+                self.error_trace.push(func.name.clone());
+            }
+        }
+        self.call_stack.pop();
+        if let Some(profiler) = &mut self.profiler {
+            profiler.exit();
+        }
+        outcome
+    }
+    
+    /// Имя функции в профиле; метод называется вместе со структурой `self`: `Point.sum`
+    fn profile_name(&self, func: &Function, scope: &HashMap<String, ChifValue>) -> String {
+        let receiver = match scope.get("self") {
+            Some(ChifValue::Reference(var_name)) => scope.get(var_name).or_else(|| self.lookup_variable(var_name)),
+            Some(ChifValue::Pointer(inner)) => Some(&**inner),
+            receiver => receiver,
+        };
+        match receiver {
+            Some(ChifValue::Struct(struct_name, _)) => format!("{}.{}", struct_name, func.name),
+            _ => func.name.clone(),
+        }
+    }
+    
+    /// Встроенная функция `name` — отдельная строка профиля. Имя строится,
+    /// только если профиль включён
+    fn profiled<T>(&mut self, name: impl FnOnce() -> String, body: impl FnOnce(&mut Self) -> Result<T>) -> Result<T> {
+        let Some(profiler) = &mut self.profiler else {
+            return body(self);
+        };
+        profiler.enter(&name(), CallKind::Builtin);
+        let result = body(self);
+        if let Some(profiler) = &mut self.profiler {
+            profiler.exit();
+        }
+        result
+    }
+    
+    /// Профиль считает копии списков, массивов, словарей и кортежей
+    fn count_copy(&mut self, value: &ChifValue) {
+        if let Some(profiler) = &mut self.profiler {
+            if matches!(value, ChifValue::List(_) | ChifValue::Array(_) | ChifValue::Map(_) | ChifValue::Tuple(_)) {
+                profiler.collection_copied();
+            }
+        }
+    }
+    
+    /// Функции, через которые прошла ошибка последнего `execute`: от той, где
+    /// она возникла, к `main`
+    pub fn error_trace(&self) -> &[String] {
+        &self.error_trace
+    }
+    
+    /// Функции, выполняемые сейчас, от `main` к текущей (видна в панике)
+    pub fn call_stack(&self) -> &[String] {
+        &self.call_stack
+    }
+    
+    /// Выполняет `body` в новой области видимости и возвращает её вместе с результатом.
+    /// Ошибка (а через неё и `ret`, `break`, `continue`) может прервать тело там,
+    /// где вложенный цикл ещё не снял свою область, поэтому всё, что выше новой
+    /// области, снимается здесь: после вызова глубина `locals` та же, что до него
+    fn in_scope<T>(
+        &mut self,
+        scope: HashMap<String, ChifValue>,
+        body: impl FnOnce(&mut Self) -> Result<T>,
+    ) -> (Result<T>, HashMap<String, ChifValue>) {
+        let depth = self.locals.len();
         self.locals.push(scope);
+        let result = body(self);
+        debug_assert!(self.locals.len() > depth, "a nested scope popped a frame it did not push");
+        self.locals.truncate(depth + 1);
+        let scope = self.locals.pop().unwrap_or_default();
+        (result, scope)
+    }
+    
+    /// Семантика for: init; затем пока condition истинно — тело и update.
+    /// `continue` завершает тело, но update всё равно выполняется ровно один раз
+    /// перед следующей проверкой условия; `break` выходит без update.
+    fn execute_for_loop(&mut self, for_stmt: &ForStatement) -> Result<()> {
+        if let Some(init) = &for_stmt.init {
+            self.execute_statement(init)?;
+        }
         
-        let result = self.execute_block(&func.body);
+        loop {
+            if let Some(condition) = &for_stmt.condition {
+                let cond_value = self.evaluate_expression(condition)?;
+                if !self.is_truthy(&cond_value) {
+                    break;
+                }
+            }
+            
+            match self.execute_block(&for_stmt.body) {
+                Ok(()) | Err(ChifError::Continue) => {}
+                Err(ChifError::Break) => break,
+                Err(e) => return Err(e),
+            }
+            
+            if let Some(update) = &for_stmt.update {
+                self.execute_statement(update)?;
+            }
+        }
         
-        self.locals.pop();
+        Ok(())
+    }
+    
+    /// Границы range вычисляются один раз, и список значений не создаётся.
+    /// Шаг, выводящий за пределы i64, завершает цикл вместо переполнения
+    fn execute_range_loop(&mut self, for_in: &ForInStatement) -> Result<()> {
+        Self::check_local_not_reserved(&for_in.variable)?;
+        let args = for_in.range_args().ok_or_else(|| ChifError::RuntimeError {
+            message: "for-in can only iterate over range(start, end[, step])".to_string(),
+        })?;
+        let mut bounds = Vec::with_capacity(args.len());
+        for arg in args {
+            let value = self.evaluate_expression(arg)?;
+            bounds.push(Self::expect_int(value, "range() bounds")?);
+        }
+        let (mut current, end, step) = match bounds[..] {
+            [start, end] => (start, end, 1),
+            [start, end, step] => (start, end, step),
+            _ => return Err(ChifError::RuntimeError {
+                message: format!("range() expects 2 or 3 arguments, got {}", bounds.len()),
+            }),
+        };
+        if step == 0 {
+            return Err(ChifError::RuntimeError {
+                message: "range() step cannot be zero".to_string(),
+            });
+        }
         
-        match result {
-            Ok(_) => Ok(ChifValue::Nil),
-            Err(ChifError::Return(value)) => Ok(value),
-            Err(e) => Err(e),
+        while (step > 0 && current < end) || (step < 0 && current > end) {
+            self.set_variable(&for_in.variable, ChifValue::Int(current))?;
+            match self.execute_block(&for_in.body) {
+                Ok(()) | Err(ChifError::Continue) => {}
+                Err(ChifError::Break) => break,
+                Err(e) => return Err(e),
+            }
+            match current.checked_add(step) {
+                Some(next) => current = next,
+                None => break,
+            }
         }
+        
+        Ok(())
     }
     
     fn execute_block(&mut self, block: &Block) -> Result<()> {
+        // Каждая итерация цикла и каждый вызов выполняют блок, поэтому
+        // бесконечный цикл или рекурсия остановятся здесь
+        if let Some((deadline, limit)) = self.deadline {
+            if Instant::now() >= deadline {
+                return Err(ChifError::TimeLimitExceeded { limit });
+            }
+        }
         for statement in &block.statements {
             self.execute_statement(statement)?;
         }
@@ -123,44 +705,61 @@ impl Interpreter {
     fn execute_statement(&mut self, statement: &Statement) -> Result<()> {
         match statement {
             Statement::VarDecl(var_decl) => {
+                Self::check_local_not_reserved(&var_decl.name)?;
+                
                 let value = if let Some(expr) = &var_decl.value {
-                    let mut val = self.evaluate_expression(expr)?;
-                    
-                    // Convert arrays to lists if the type is List
-                    if let crate::types::ChifType::List(_, _) = &var_decl.var_type {
-                        if let ChifValue::Array(arr) = val {
-                            val = ChifValue::List(arr);
-                        }
-                    }
-                    
-                    val
+                    let val = self.evaluate_expression(expr)?;
+                    Self::conform_to_type(&var_decl.var_type, val)
                 } else {
                     ChifValue::Nil
                 };
                 
+                // Bare `list` needs a non-empty initializer to know its element type,
+                // the same rule as in the analyzer
+                if let crate::types::ChifType::List(element_type, _) = &var_decl.var_type {
+                    let has_elements = matches!(&value, ChifValue::List(items) if !items.is_empty());
+                    if **element_type == crate::types::ChifType::Nil && !has_elements {
+                        return Err(ChifError::RuntimeError {
+                            message: format!(
+                                "Cannot infer element type of empty list '{}'; annotate as list[<type>]",
+                                var_decl.name
+                            ),
+                        });
+                    }
+                }
+                
                 self.set_variable(&var_decl.name, value)?;
             }
             Statement::Assignment(assignment) => {
                 let value = self.evaluate_expression(&assignment.value)?;
-                match &assignment.target {
-                    Expression::Identifier(name) => {
-                        self.set_variable(name, value)?;
-                    }
-                    Expression::Index(index_access) => {
-                        self.assign_to_index(index_access, value)?;
-                    }
-                    Expression::FieldAccess(field_access) => {
-                        self.assign_to_field(field_access, value)?;
-                    }
-                    _ => {
-                        return Err(ChifError::RuntimeError {
-                            message: "Invalid assignment target".to_string(),
-                        });
-                    }
+                self.assign_to_place(&assignment.target, value)?;
+            }
+            Statement::Destructure(destructure) => {
+                let value = self.evaluate_expression(&destructure.value)?;
+                
+                let values = match value {
+                    ChifValue::Tuple(values) => values,
+                    other => return Err(Self::type_error("tuple", &other, "destructuring")),
+                };
+                
+                if values.len() != destructure.names.len() {
+                    return Err(ChifError::RuntimeError {
+                        message: format!(
+                            "Cannot destructure a tuple of {} elements into {} variables",
+                            values.len(),
+                            destructure.names.len()
+                        ),
+                    });
+                }
+                
+                for (name, value) in destructure.names.iter().zip(values) {
+                    Self::check_local_not_reserved(name)?;
+                    self.set_variable(name, value)?;
                 }
             }
             Statement::Expression(expr) => {
-                self.evaluate_expression(expr)?;
+                // Единственное место, где вызов без значения допустим
+                self.evaluate_expression_or_void(expr)?;
             }
             Statement::If(if_stmt) => {
                 let condition = self.evaluate_expression(&if_stmt.condition)?;
@@ -171,76 +770,28 @@ impl Interpreter {
                 }
             }
             Statement::For(for_stmt) => {
-                // Create new scope for the for loop variables
-                self.locals.push(HashMap::new());
+                let (result, loop_scope) = self.in_scope(HashMap::new(), |this| this.execute_for_loop(for_stmt));
                 
-                // Execute initialization in the loop scope
-                if let Some(init) = &for_stmt.init {
-                    self.execute_statement(init)?;
+                // Сохраняем переменные из области видимости цикла в родительскую область,
+                // в том числе когда цикл прерван ошибкой или `ret`
+                if let Some(parent_scope) = self.locals.last_mut() {
+                    // Обновляем переменные в родительской области видимости
+                    // Включая те, которые были объявлены до цикла
+                    parent_scope.extend(loop_scope);
                 }
                 
-                // Save the current state of the loop variables after initialization
-                let loop_scope_index = self.locals.len() - 1;
+                result?;
+            }
+            Statement::ForIn(for_in) => {
+                let (result, mut loop_scope) = self.in_scope(HashMap::new(), |this| this.execute_range_loop(for_in));
                 
-                loop {
-                    if let Some(condition) = &for_stmt.condition {
-                        let cond_value = self.evaluate_expression(condition)?;
-                        if !self.is_truthy(&cond_value) {
-                            break;
-                        }
-                    }
-                    
-                    // Execute the loop body
-                    match self.execute_block(&for_stmt.body) {
-                        Ok(()) => {},
-                        Err(ChifError::Break) => break,
-                        Err(ChifError::Continue) => {
-                            // Execute update and continue
-                            if let Some(update) = &for_stmt.update {
-                                self.execute_statement(update)?;
-                            }
-                            continue;
-                        },
-                        Err(e) => return Err(e),
-                    }
-                    
-                    if let Some(update) = &for_stmt.update {
-                        // Execute update statement
-                        self.execute_statement(update)?;
-                    }
-                    
-                    // Preserve any changes to loop variables for the next iteration
-                    // This ensures variables modified in the loop body remain modified
-                    if loop_scope_index < self.locals.len() {
-                        // We're still in the same scope structure
-                        // No need to do anything special
-                    } else {
-                        // Something changed the scope structure, this is unexpected
-                        // but we'll handle it gracefully
-                        break;
-                    }
+                // Переменная цикла видна только в теле, остальное уходит в родительскую область
+                loop_scope.remove(&for_in.variable);
+                if let Some(parent_scope) = self.locals.last_mut() {
+                    parent_scope.extend(loop_scope);
                 }
                 
-                // Сохраняем переменные из области видимости цикла в родительскую область
-                if !self.locals.is_empty() {
-                    let loop_scope = self.locals.last().unwrap().clone();
-                    self.locals.pop();
-                    
-                    // Если есть родительская область видимости, копируем в неё измененные переменные
-                    if !self.locals.is_empty() {
-                        let parent_scope = self.locals.last_mut().unwrap();
-                        
-                        for (name, value) in loop_scope.iter() {
-                            // Обновляем переменные в родительской области видимости
-                            // Включая те, которые были объявлены до цикла
-                            parent_scope.insert(name.clone(), value.clone());
-                        }
-                    }
-                } else {
-                    // На всякий случай, если список областей видимости пуст
-                    // (не должно происходить, но для безопасности)
-                    self.locals.push(HashMap::new());
-                }
+                result?;
             }
             Statement::While(while_stmt) => {
                 loop {
@@ -280,7 +831,7 @@ impl Interpreter {
                 let value = if let Some(expr) = expr {
                     self.evaluate_expression(expr)?
                 } else {
-                    ChifValue::Nil
+                    ChifValue::Void
                 };
                 
                 return Err(ChifError::Return(value));
@@ -295,7 +846,27 @@ impl Interpreter {
         Ok(())
     }
     
+    /// Значение выражения; вызов без значения здесь — ошибка, так что операнды,
+    /// аргументы и присваиваемые значения никогда не получают `ChifValue::Void`
     fn evaluate_expression(&mut self, expr: &Expression) -> Result<ChifValue> {
+        match self.evaluate_expression_or_void(expr)? {
+            ChifValue::Void => Err(Self::no_value(expr)),
+            value => Ok(value),
+        }
+    }
+    
+    fn evaluate_expression_or_void(&mut self, expr: &Expression) -> Result<ChifValue> {
+        // Встроенная функция — строка профиля; функции программы профиль
+        // учитывает в `execute_function_body`
+        if let (Some(_), Expression::Call(call)) = (&self.profiler, expr) {
+            if !self.functions.contains_key(&call.name) && self.module_function(&call.name).is_none() {
+                return self.profiled(|| call.name.clone(), |this| this.evaluate_node(expr));
+            }
+        }
+        self.evaluate_node(expr)
+    }
+    
+    fn evaluate_node(&mut self, expr: &Expression) -> Result<ChifValue> {
         match expr {
             Expression::Literal(value) => {
                 match value {
@@ -313,10 +884,19 @@ impl Interpreter {
                     "randi" => Ok(ChifValue::Str("randi".to_string())), // Placeholder
                     "randf" => Ok(ChifValue::Str("randf".to_string())), // Placeholder
                     "rands" => Ok(ChifValue::Str("rands".to_string())), // Placeholder
-                    _ => self.get_variable(name),
+                    _ => {
+                        let value = self.get_variable(name)?;
+                        self.count_copy(&value);
+                        Ok(value)
+                    }
                 }
             }
             Expression::Binary(binary_op) => {
+                if binary_op.int_only && self.int_fast_path {
+                    if let Some(value) = self.evaluate_int_binary(binary_op)? {
+                        return Ok(value);
+                    }
+                }
                 let left = self.evaluate_expression(&binary_op.left)?;
                 let right = self.evaluate_expression(&binary_op.right)?;
                 self.apply_binary_op(&binary_op.operator, &left, &right)
@@ -326,14 +906,20 @@ impl Interpreter {
                 self.apply_unary_op(&unary_op.operator, &operand)
             }
             Expression::Call(call) => {
+                // Именованные аргументы бывают только у пользовательских функций
+                let positions = if call.named_args.is_empty() {
+                    None
+                } else {
+                    let func = self.functions.get(&call.name).ok_or_else(|| ChifError::RuntimeError {
+                        message: format!("Named arguments are only supported for user-defined functions, not '{}'", call.name),
+                    })?;
+                    Some(call.parameter_positions(&func.params).map_err(|message| ChifError::RuntimeError { message })?)
+                };
+                
                 // Handle built-in functions
                 match call.name.as_str() {
                     "toInt" => {
-                        if call.args.len() != 1 {
-                            return Err(ChifError::RuntimeError {
-                                message: "toInt() expects 1 argument".to_string(),
-                            });
-                        }
+                        Self::check_arity("toInt", &call.args, 1)?;
                         let value = self.evaluate_expression(&call.args[0])?;
                         
                         match value {
@@ -343,23 +929,15 @@ impl Interpreter {
                                 // Преобразование из строки
                                 match s.parse::<i64>() {
                                     Ok(i) => Ok(ChifValue::Int(i)),
-                                    Err(_) => Err(ChifError::RuntimeError {
-                                        message: format!("Cannot convert string '{}' to int", s),
-                                    }),
+                                    Err(_) => Err(Self::invalid_conversion(&ChifValue::Str(s), "int")),
                                 }
                             }
                             ChifValue::Bool(b) => Ok(ChifValue::Int(if b { 1 } else { 0 })), // Преобразование из bool
-                            _ => Err(ChifError::RuntimeError {
-                                message: format!("Cannot convert {:?} to int", value),
-                            }),
+                            _ => Err(Self::invalid_conversion(&value, "int")),
                         }
                     }
                     "toFloat" => {
-                        if call.args.len() != 1 {
-                            return Err(ChifError::RuntimeError {
-                                message: "toFloat() expects 1 argument".to_string(),
-                            });
-                        }
+                        Self::check_arity("toFloat", &call.args, 1)?;
                         let value = self.evaluate_expression(&call.args[0])?;
                         
                         match value {
@@ -369,195 +947,188 @@ impl Interpreter {
                                 // Преобразование из строки
                                 match s.parse::<f64>() {
                                     Ok(f) => Ok(ChifValue::Float(f)),
-                                    Err(_) => Err(ChifError::RuntimeError {
-                                        message: format!("Cannot convert string '{}' to float", s),
-                                    }),
+                                    Err(_) => Err(Self::invalid_conversion(&ChifValue::Str(s), "float")),
                                 }
                             }
                             ChifValue::Bool(b) => Ok(ChifValue::Float(if b { 1.0 } else { 0.0 })), // Преобразование из bool
-                            _ => Err(ChifError::RuntimeError {
-                                message: format!("Cannot convert {:?} to float", value),
-                            }),
+                            _ => Err(Self::invalid_conversion(&value, "float")),
                         }
                     }
                     "toStr" => {
-                        if call.args.len() != 1 {
-                            return Err(ChifError::RuntimeError {
-                                message: "toStr() expects 1 argument".to_string(),
-                            });
-                        }
+                        Self::check_arity("toStr", &call.args, 1)?;
                         let value = self.evaluate_expression(&call.args[0])?;
                         
                         match value {
                             ChifValue::Str(s) => Ok(ChifValue::Str(s)), // Уже строка
                             ChifValue::Int(i) => Ok(ChifValue::Str(i.to_string())), // Преобразование из int
-                            ChifValue::Float(f) => Ok(ChifValue::Str(f.to_string())), // Преобразование из float
+                            ChifValue::Float(f) => Ok(ChifValue::Str(format_float(f))), // Преобразование из float
                             ChifValue::Bool(b) => Ok(ChifValue::Str(b.to_string())), // Преобразование из bool
                             ChifValue::Nil => Ok(ChifValue::Str("nil".to_string())), // Преобразование из nil
                             _ => Ok(ChifValue::Str(format!("{:?}", value))), // Для остальных типов используем Debug
                         }
                     }
                     "randi" => {
-                        if call.args.len() != 2 {
-                            return Err(ChifError::RuntimeError {
-                                message: "randi expects 2 arguments".to_string(),
-                            });
-                        }
+                        Self::check_arity("randi", &call.args, 2)?;
                         let min = self.evaluate_expression(&call.args[0])?;
+                        let min_val = Self::expect_int(min, "randi() min")?;
                         let max = self.evaluate_expression(&call.args[1])?;
+                        let max_val = Self::expect_int(max, "randi() max")?;
                         
-                        if let (ChifValue::Int(min_val), ChifValue::Int(max_val)) = (min, max) {
-                            if min_val > max_val {
-                                return Err(ChifError::RuntimeError {
-                                    message: "randi: min cannot be greater than max".to_string(),
-                                });
-                            }
-                            let mut rng = rand::thread_rng();
-                            let result = rng.gen_range(min_val..=max_val);
-                            Ok(ChifValue::Int(result))
-                        } else {
-                            Err(ChifError::RuntimeError {
-                                message: "randi expects integer arguments".to_string(),
-                            })
-                        }
-                    }
-                    "randf" => {
-                        if call.args.len() != 2 {
+                        if min_val > max_val {
                             return Err(ChifError::RuntimeError {
-                                message: "randf expects 2 arguments".to_string(),
+                                message: "randi: min cannot be greater than max".to_string(),
                             });
                         }
+                        let result = self.rng.gen_range(min_val..=max_val);
+                        Ok(ChifValue::Int(result))
+                    }
+                    "randf" => {
+                        Self::check_arity("randf", &call.args, 2)?;
                         let min = self.evaluate_expression(&call.args[0])?;
+                        let min_val = Self::expect_float(min, "randf() min")?;
                         let max = self.evaluate_expression(&call.args[1])?;
+                        let max_val = Self::expect_float(max, "randf() max")?;
                         
-                        if let (ChifValue::Float(min_val), ChifValue::Float(max_val)) = (min, max) {
-                            if min_val > max_val {
-                                return Err(ChifError::RuntimeError {
-                                    message: "randf: min cannot be greater than max".to_string(),
-                                });
-                            }
-                            let mut rng = rand::thread_rng();
-                            let result = rng.gen_range(min_val..=max_val);
-                            Ok(ChifValue::Float(result))
-                        } else {
-                            Err(ChifError::RuntimeError {
-                                message: "randf expects float arguments".to_string(),
-                            })
-                        }
-                    }
-                    "rands" => {
-                        if call.args.len() != 2 {
+                        if min_val > max_val {
                             return Err(ChifError::RuntimeError {
-                                message: "rands expects 2 arguments".to_string(),
+                                message: "randf: min cannot be greater than max".to_string(),
                             });
                         }
+                        let result = self.rng.gen_range(min_val..=max_val);
+                        Ok(ChifValue::Float(result))
+                    }
+                    "rando" => {
+                        Self::check_arity("rando", &call.args, 0)?;
+                        Ok(ChifValue::Float(self.rng.gen::<f64>()))
+                    }
+                    "rand_seed" => {
+                        Self::check_arity("rand_seed", &call.args, 1)?;
+                        let seed = self.evaluate_expression(&call.args[0])?;
+                        let seed_val = Self::expect_int(seed, "rand_seed() seed")?;
+                        self.rng = StdRng::seed_from_u64(seed_val as u64);
+                        Ok(ChifValue::Void)
+                    }
+                    "intern" => {
+                        Self::check_arity("intern", &call.args, 1)?;
+                        let value = self.evaluate_expression(&call.args[0])?;
+                        let value_str = Self::expect_str(value, "intern() argument")?;
+                        self.intern(&value_str);
+                        Ok(ChifValue::Str(value_str))
+                    }
+                    "fdiv" => {
+                        // Деление int на int без отбрасывания дробной части
+                        Self::check_arity("fdiv", &call.args, 2)?;
+                        let a = self.evaluate_expression(&call.args[0])?;
+                        let a = Self::expect_int(a, "fdiv() dividend")?;
+                        let b = self.evaluate_expression(&call.args[1])?;
+                        let b = Self::expect_int(b, "fdiv() divisor")?;
+                        Ok(ChifValue::Float(a as f64 / b as f64))
+                    }
+                    "wrapping_add" | "wrapping_sub" | "wrapping_mul" | "saturating_add" | "saturating_sub" | "checked_add" => {
+                        Self::check_arity(&call.name, &call.args, 2)?;
+                        let a = self.evaluate_expression(&call.args[0])?;
+                        let a = Self::expect_int(a, &format!("{}() first argument", call.name))?;
+                        let b = self.evaluate_expression(&call.args[1])?;
+                        let b = Self::expect_int(b, &format!("{}() second argument", call.name))?;
+                        Ok(match call.name.as_str() {
+                            "wrapping_add" => ChifValue::Int(a.wrapping_add(b)),
+                            "wrapping_sub" => ChifValue::Int(a.wrapping_sub(b)),
+                            "wrapping_mul" => ChifValue::Int(a.wrapping_mul(b)),
+                            "saturating_add" => ChifValue::Int(a.saturating_add(b)),
+                            "saturating_sub" => ChifValue::Int(a.saturating_sub(b)),
+                            // При переполнении сумма — wrapping-результат, флаг false
+                            _ => {
+                                let (sum, overflowed) = a.overflowing_add(b);
+                                ChifValue::Tuple(vec![ChifValue::Int(sum), ChifValue::Bool(!overflowed)])
+                            }
+                        })
+                    }
+                    "rands" => {
+                        Self::check_arity("rands", &call.args, 2)?;
                         let from = self.evaluate_expression(&call.args[0])?;
+                        let from_str = Self::expect_str(from, "rands() from")?;
                         let to = self.evaluate_expression(&call.args[1])?;
+                        let to_str = Self::expect_str(to, "rands() to")?;
                         
-                        if let (ChifValue::Str(from_str), ChifValue::Str(to_str)) = (from, to) {
-                            if from_str.len() != 1 || to_str.len() != 1 {
-                                return Err(ChifError::RuntimeError {
-                                    message: "rands expects single character strings".to_string(),
-                                });
-                            }
-                            let from_char = from_str.chars().next().unwrap() as u8;
-                            let to_char = to_str.chars().next().unwrap() as u8;
-                            
-                            if from_char > to_char {
-                                return Err(ChifError::RuntimeError {
-                                    message: "rands: from cannot be greater than to".to_string(),
-                                });
-                            }
-                            
-                            let mut rng = rand::thread_rng();
-                            let result_char = rng.gen_range(from_char..=to_char) as char;
-                            Ok(ChifValue::Str(result_char.to_string()))
-                        } else {
-                            Err(ChifError::RuntimeError {
-                                message: "rands expects string arguments".to_string(),
-                            })
+                        if from_str.len() != 1 || to_str.len() != 1 {
+                            return Err(ChifError::RuntimeError {
+                                message: "rands expects single character strings".to_string(),
+                            });
                         }
-                    }
-                    "http_get" => {
-                        if call.args.len() != 1 {
+                        let from_char = from_str.chars().next().unwrap() as u8;
+                        let to_char = to_str.chars().next().unwrap() as u8;
+                        
+                        if from_char > to_char {
                             return Err(ChifError::RuntimeError {
-                                message: "http_get expects 1 argument".to_string(),
+                                message: "rands: from cannot be greater than to".to_string(),
                             });
                         }
+                        
+                        let result_char = self.rng.gen_range(from_char..=to_char) as char;
+                        Ok(ChifValue::Str(result_char.to_string()))
+                    }
+                    #[cfg(feature = "http")]
+                    "http_get" => {
+                        Self::check_arity("http_get", &call.args, 1)?;
                         let url = self.evaluate_expression(&call.args[0])?;
-                        if let ChifValue::Str(url_str) = url {
-                            self.http_get_request(&url_str)
-                        } else {
-                            Err(ChifError::RuntimeError {
-                                message: "http_get expects string URL".to_string(),
-                            })
-                        }
+                        let url_str = Self::expect_str(url, "http_get() URL")?;
+                        self.http_get_request(&url_str)
                     }
+                    #[cfg(feature = "http")]
                     "http_post" => {
-                        if call.args.len() != 2 {
-                            return Err(ChifError::RuntimeError {
-                                message: "http_post expects 2 arguments".to_string(),
-                            });
-                        }
+                        Self::check_arity("http_post", &call.args, 2)?;
                         let url = self.evaluate_expression(&call.args[0])?;
+                        let url_str = Self::expect_str(url, "http_post() URL")?;
                         let body = self.evaluate_expression(&call.args[1])?;
-                        if let (ChifValue::Str(url_str), ChifValue::Str(body_str)) = (url, body) {
-                            self.http_post_request(&url_str, &body_str)
-                        } else {
-                            Err(ChifError::RuntimeError {
-                                message: "http_post expects string arguments".to_string(),
-                            })
-                        }
+                        let body_str = Self::expect_str(body, "http_post() body")?;
+                        self.http_post_request(&url_str, &body_str)
                     }
+                    #[cfg(feature = "http")]
                     "http_put" => {
-                        if call.args.len() != 2 {
-                            return Err(ChifError::RuntimeError {
-                                message: "http_put expects 2 arguments".to_string(),
-                            });
-                        }
+                        Self::check_arity("http_put", &call.args, 2)?;
                         let url = self.evaluate_expression(&call.args[0])?;
+                        let url_str = Self::expect_str(url, "http_put() URL")?;
                         let body = self.evaluate_expression(&call.args[1])?;
-                        if let (ChifValue::Str(url_str), ChifValue::Str(body_str)) = (url, body) {
-                            self.http_put_request(&url_str, &body_str)
-                        } else {
-                            Err(ChifError::RuntimeError {
-                                message: "http_put expects string arguments".to_string(),
-                            })
-                        }
+                        let body_str = Self::expect_str(body, "http_put() body")?;
+                        self.http_put_request(&url_str, &body_str)
                     }
+                    #[cfg(feature = "http")]
                     "http_delete" => {
-                        if call.args.len() != 1 {
-                            return Err(ChifError::RuntimeError {
-                                message: "http_delete expects 1 argument".to_string(),
-                            });
-                        }
+                        Self::check_arity("http_delete", &call.args, 1)?;
                         let url = self.evaluate_expression(&call.args[0])?;
-                        if let ChifValue::Str(url_str) = url {
-                            self.http_delete_request(&url_str)
-                        } else {
-                            Err(ChifError::RuntimeError {
-                                message: "http_delete expects string URL".to_string(),
-                            })
-                        }
+                        let url_str = Self::expect_str(url, "http_delete() URL")?;
+                        self.http_delete_request(&url_str)
                     }
+                    #[cfg(not(feature = "http"))]
+                    "http_get" | "http_post" | "http_put" | "http_delete" => Err(ChifError::RuntimeError {
+                        message: format!("{}() is unavailable: rono_lang was built without the `http` feature", call.name),
+                    }),
                     _ => {
-                        // Regular function call
+                        // Regular function call. Arguments are evaluated in source
+                        // order, and named ones are placed by parameter afterwards
                         let mut args = Vec::new();
-                        for arg_expr in &call.args {
+                        for arg_expr in call.source_args() {
                             args.push(self.evaluate_expression(arg_expr)?);
                         }
+                        if let Some(positions) = &positions {
+                            args = in_parameter_order(args, positions);
+                        }
                         
                         if let Some(func) = self.functions.get(&call.name).cloned() {
                             // Check if any arguments are references
-                            let has_references = call.args.iter().any(|arg| {
+                            let has_references = call.source_args().any(|arg| {
                                 matches!(arg, Expression::Reference(_))
                             });
                             
                             if has_references {
-                                self.call_function_with_references(&func, args, &call.args)
+                                self.call_function_with_references(&func, args)
                             } else {
                                 self.call_function(&func, args)
                             }
+                        } else if let Some(func) = self.module_function(&call.name) {
+                            // Устаревшее `utils_helper(...)` вместо `utils.helper(...)`;
+                            // анализатор предупреждает о нём (W0004)
+                            self.call_function(&func, args)
                         } else {
                             Err(ChifError::FunctionNotFound {
                                 name: call.name.clone(),
@@ -584,29 +1155,56 @@ impl Interpreter {
                         }
                     }
                     
+                    // `Point.new(1, 2)`: a struct type name rather than a variable
+                    if self.structs.contains_key(module_name) && self.lookup_variable(module_name).is_none() {
+                        return self.call_associated_function(module_name, &method_call.method, &method_call.args);
+                    }
+                    
                     // Special handling for mutable methods on variables
                     if method_call.method == "add" || method_call.method == "addAt" || method_call.method == "del" {
-                        return self.call_mutable_method(module_name, &method_call.method, &method_call.args);
+                        return self.profiled(
+                            || format!("list.{}", method_call.method),
+                            |this| this.call_mutable_method(module_name, &method_call.method, &method_call.args),
+                        );
                     }
                     
                     // Check if this is a struct method that might mutate self
-                    let object = self.get_variable(module_name)?;
+                    let mut var_name = module_name.clone();
+                    let mut object = self.get_variable(&var_name)?;
+                    // Inside a method `self` refers to the caller's variable
+                    if let ChifValue::Reference(target) = &object {
+                        var_name = target.clone();
+                        object = self.get_variable(&var_name)?;
+                    }
                     if let ChifValue::Struct(struct_name, _) = &object {
-                        if let Some(methods) = self.struct_methods.get(struct_name).cloned() {
-                            for method in &methods {
-                                if method.name == method_call.method {
-                                    return self.call_mutable_struct_method(module_name, &method_call.method, &method_call.args);
-                                }
-                            }
+                        let is_method = self.struct_methods.get(struct_name)
+                            .is_some_and(|methods| methods.iter().any(|method| method.name == method_call.method));
+                        if is_method {
+                            return self.call_mutable_struct_method(&var_name, &method_call.method, &method_call.args);
                         }
                     }
                 }
                 
                 let object = self.evaluate_expression(&method_call.object)?;
+                
+                // `m["a"].add(3)` меняет список внутри словаря, а не его копию
+                if matches!(object, ChifValue::List(_))
+                    && matches!(&*method_call.object, Expression::Index(_) | Expression::FieldAccess(_))
+                    && matches!(method_call.method.as_str(), "add" | "addAt" | "del")
+                {
+                    return self.profiled(
+                        || format!("list.{}", method_call.method),
+                        |this| this.call_mutable_place_method(&method_call.object, &method_call.method, &method_call.args),
+                    );
+                }
                 self.call_method(&object, &method_call.method, &method_call.args)
             }
             Expression::Index(index_access) => {
-                let object = self.evaluate_expression(&index_access.object)?;
+                let object = match self.evaluate_expression(&index_access.object)? {
+                    ChifValue::Reference(var_name) => self.get_variable(&var_name)?,
+                    ChifValue::Pointer(inner) => *inner,
+                    object => object,
+                };
                 let mut current = object;
                 
                 for index_expr in &index_access.indices {
@@ -629,37 +1227,49 @@ impl Interpreter {
                 // For now, we'll create arrays by default
                 Ok(ChifValue::Array(values))
             }
+            Expression::TupleLiteral(elements) => {
+                let mut values = Vec::new();
+                for element in elements {
+                    values.push(self.evaluate_expression(element)?);
+                }
+                Ok(ChifValue::Tuple(values))
+            }
             Expression::MapLiteral(pairs) => {
                 let mut map = HashMap::new();
                 for (key_expr, value_expr) in pairs {
                     let key = self.evaluate_expression(key_expr)?;
                     let value = self.evaluate_expression(value_expr)?;
                     
-                    if let ChifValue::Str(key_str) = key {
-                        map.insert(key_str, value);
-                    } else {
-                        return Err(ChifError::RuntimeError {
-                            message: "Map keys must be strings".to_string(),
-                        });
-                    }
+                    let key_str = Self::expect_str(key, "map key")?;
+                    map.insert(self.intern(&key_str), value);
                 }
                 Ok(ChifValue::Map(map))
             }
             Expression::StructLiteral(struct_literal) => {
-                let mut fields = HashMap::new();
+                // Значения вычисляются в порядке литерала, а хранятся в порядке объявления
+                let mut values = Vec::with_capacity(struct_literal.fields.len());
                 for (field_name, field_expr) in &struct_literal.fields {
-                    let field_value = self.evaluate_expression(field_expr)?;
-                    fields.insert(field_name.clone(), field_value);
+                    values.push((field_name.clone(), self.evaluate_expression(field_expr)?));
                 }
-                Ok(ChifValue::Struct(struct_literal.struct_name.clone(), fields))
+                // `..base` вычисляется после полей и даёт те поля, которых в литерале нет
+                let base = match &struct_literal.base {
+                    Some(base) => match self.evaluate_expression(base)? {
+                        ChifValue::Struct(name, fields) if name == struct_literal.struct_name => Some(fields),
+                        other => {
+                            return Err(Self::type_error(&struct_literal.struct_name, &other, "base of struct literal"));
+                        }
+                    },
+                    None => None,
+                };
+                self.build_struct_value(&struct_literal.struct_name, values, base)
             }
-            Expression::Reference(expr) => {
+            Expression::Reference(address_of) => {
                 // Create a reference to a variable
-                if let Expression::Identifier(var_name) = &**expr {
+                if let Expression::Identifier(var_name) = &*address_of.target {
                     Ok(ChifValue::Reference(var_name.clone()))
                 } else {
                     // For complex expressions, create a pointer to the value
-                    let value = self.evaluate_expression(expr)?;
+                    let value = self.evaluate_expression(&address_of.target)?;
                     Ok(ChifValue::Pointer(Box::new(value)))
                 }
             }
@@ -671,69 +1281,162 @@ impl Interpreter {
                         // Dereference a variable reference
                         self.get_variable(&var_name)
                     }
-                    _ => Err(ChifError::RuntimeError {
-                        message: "Cannot dereference non-pointer value".to_string(),
-                    })
+                    other => Err(Self::type_error("pointer", &other, "dereference")),
                 }
             }
         }
     }
     
     fn call_method(&mut self, object: &ChifValue, method_name: &str, args: &[Expression]) -> Result<ChifValue> {
+        if self.profiler.is_some() {
+            if let Some(name) = Self::builtin_method_name(object, method_name) {
+                return self.profiled(|| name, |this| this.dispatch_method(object, method_name, args));
+            }
+        }
+        self.dispatch_method(object, method_name, args)
+    }
+    
+    /// Имя встроенного метода в профиле (`con.out`, `list.len`); `None` для
+    /// методов структур программы, которые профиль учитывает как функции
+    fn builtin_method_name(object: &ChifValue, method_name: &str) -> Option<String> {
+        let receiver = match object {
+            ChifValue::Struct(struct_name, _) => match struct_name.as_str() {
+                "Console" => "con",
+                "Memory" => "mem",
+                "Http" => "http",
+                "ProgramArgs" => "args",
+                "Os" => "os",
+                "RonoInfo" => "rono",
+                _ => return None,
+            },
+            // Метод вызывается у значения по ссылке, строку даст этот вызов
+            ChifValue::Reference(_) | ChifValue::Pointer(_) => return None,
+            ChifValue::Int(_) => "int",
+            ChifValue::Float(_) => "float",
+            ChifValue::Str(_) => "str",
+            ChifValue::Bool(_) => "bool",
+            ChifValue::Nil | ChifValue::Void => "nil",
+            ChifValue::Array(_) => "array",
+            ChifValue::List(_) => "list",
+            ChifValue::Map(_) => "map",
+            ChifValue::Tuple(_) => "tuple",
+        };
+        Some(format!("{}.{}", receiver, method_name))
+    }
+    
+    fn dispatch_method(&mut self, object: &ChifValue, method_name: &str, args: &[Expression]) -> Result<ChifValue> {
         match object {
-            ChifValue::Array(_) => {
+            // `xs.len()` для параметра `&xs`/`&mut xs`: метод вызывается у значения
+            ChifValue::Reference(var_name) => {
+                let referenced = self.get_variable(var_name)?;
+                self.call_method(&referenced, method_name, args)
+            }
+            ChifValue::Pointer(inner) => self.call_method(inner, method_name, args),
+            ChifValue::Array(arr) => {
                 match method_name {
-                    "len" => {
-                        if let ChifValue::Array(arr) = object {
-                            Ok(ChifValue::Int(arr.len() as i64))
-                        } else {
-                            unreachable!()
-                        }
+                    "len" => Ok(ChifValue::Int(arr.len() as i64)),
+                    "unique" => {
+                        Self::check_arity("unique", args, 0)?;
+                        // Array literals are immutable, so the result is a new list
+                        Ok(ChifValue::List(self.unique_values(arr)))
                     }
-                    _ => Err(ChifError::RuntimeError {
-                        message: format!("Method '{}' not supported for arrays (immutable)", method_name),
-                    }),
+                    "join" => self.join_strings(arr, args),
+                    _ => Err(Self::method_not_found("immutable array", method_name)),
                 }
             }
-            ChifValue::List(_) => {
+            ChifValue::List(list) => {
                 match method_name {
-                    "len" => {
-                        if let ChifValue::List(list) = object {
-                            Ok(ChifValue::Int(list.len() as i64))
-                        } else {
-                            unreachable!()
-                        }
-                    }
+                    "len" => Ok(ChifValue::Int(list.len() as i64)),
                     "add" => {
-                        if args.len() != 1 {
-                            return Err(ChifError::RuntimeError {
-                                message: "add method expects 1 argument".to_string(),
-                            });
-                        }
+                        Self::check_arity("add", args, 1)?;
                         // Note: This is still a simplified implementation
                         // In a real implementation, we'd need mutable references
-                        Ok(ChifValue::Nil)
+                        Ok(ChifValue::Void)
                     }
                     "addAt" => {
-                        if args.len() != 2 {
-                            return Err(ChifError::RuntimeError {
-                                message: "addAt method expects 2 arguments".to_string(),
-                            });
-                        }
+                        Self::check_arity("addAt", args, 2)?;
                         // Note: This is still a simplified implementation
-                        Ok(ChifValue::Nil)
+                        Ok(ChifValue::Void)
                     }
-                    _ => Err(ChifError::RuntimeError {
-                        message: format!("Unknown method '{}' for list", method_name),
-                    }),
+                    "unique" => {
+                        Self::check_arity("unique", args, 0)?;
+                        Ok(ChifValue::List(self.unique_values(list)))
+                    }
+                    "join" => self.join_strings(list, args),
+                    _ => Err(Self::method_not_found("list", method_name)),
+                }
+            }
+            ChifValue::Map(map) => {
+                match method_name {
+                    "len" => {
+                        Self::check_arity("len", args, 0)?;
+                        Ok(ChifValue::Int(map.len() as i64))
+                    }
+                    "keys" => {
+                        Self::check_arity("keys", args, 0)?;
+                        // Порядок хранения случаен, ключи отдаются по возрастанию
+                        let mut keys: Vec<&str> = map.keys().map(|key| &**key).collect();
+                        keys.sort_unstable();
+                        Ok(ChifValue::List(keys.into_iter().map(|key| ChifValue::Str(key.to_string())).collect()))
+                    }
+                    _ => Err(Self::method_not_found("map", method_name)),
                 }
             }
+            ChifValue::Int(n) => {
+                let radix = match method_name {
+                    "to_hex_str" => format!("{:X}", n.unsigned_abs()),
+                    "to_hex_str_lower" => format!("{:x}", n.unsigned_abs()),
+                    "to_binary_str" => format!("{:b}", n.unsigned_abs()),
+                    _ => return Err(Self::method_not_found("int", method_name)),
+                };
+                Self::check_arity(method_name, args, 0)?;
+                // Без префикса 0x/0b, знак минус перед модулем числа
+                let sign = if *n < 0 { "-" } else { "" };
+                Ok(ChifValue::Str(format!("{}{}", sign, radix)))
+            }
             ChifValue::Str(s) => {
                 match method_name {
-                    "len" => Ok(ChifValue::Int(s.len() as i64)),
-                    _ => Err(ChifError::RuntimeError {
-                        message: format!("Unknown method '{}' for string", method_name),
-                    }),
+                    "len" | "byte_len" => Ok(ChifValue::Int(s.len() as i64)),
+                    "char_count" => Ok(ChifValue::Int(s.chars().count() as i64)),
+                    // Те же ASCII-пробелы, что и rono_str_trim в рантайме
+                    "trim" => {
+                        Self::check_arity("trim", args, 0)?;
+                        Ok(ChifValue::Str(s.trim_matches(|c: char| c.is_ascii_whitespace()).to_string()))
+                    }
+                    "index_of" => {
+                        Self::check_arity("index_of", args, 1)?;
+                        let substr = self.evaluate_expression(&args[0])?;
+                        let substr = Self::expect_str(substr, "index_of() argument")?;
+                        // Позиция в символах, а не в байтах; -1 если подстроки нет
+                        Ok(ChifValue::Int(
+                            s.find(substr.as_str())
+                                .map(|byte_index| s[..byte_index].chars().count() as i64)
+                                .unwrap_or(-1),
+                        ))
+                    }
+                    "chars" | "bytes" => {
+                        Self::check_arity(method_name, args, 0)?;
+                        let items = if method_name == "chars" {
+                            s.chars().map(|c| ChifValue::Str(c.to_string())).collect()
+                        } else {
+                            s.bytes().map(|b| ChifValue::Int(b as i64)).collect()
+                        };
+                        Ok(ChifValue::List(items))
+                    }
+                    "split" => {
+                        Self::check_arity("split", args, 1)?;
+                        let sep = self.evaluate_expression(&args[0])?;
+                        let sep = Self::expect_str(sep, "split() separator")?;
+                        if sep.is_empty() {
+                            return Err(ChifError::RuntimeError {
+                                message: "split() separator must not be empty".to_string(),
+                            });
+                        }
+                        Ok(ChifValue::List(
+                            s.split(sep.as_str()).map(|part| ChifValue::Str(part.to_string())).collect(),
+                        ))
+                    }
+                    _ => Err(Self::method_not_found("str", method_name)),
                 }
             }
             ChifValue::Struct(struct_name, _) if struct_name == "Console" => {
@@ -741,19 +1444,19 @@ impl Interpreter {
                 if method_name == "out" && args.len() == 1 {
                     let arg = self.evaluate_expression(&args[0])?;
                     let output = self.format_output(&arg)?;
-                    println!("{}", output);
-                    Ok(ChifValue::Nil)
+                    self.write_output_line(output)?;
+                    Ok(ChifValue::Void)
+                } else if method_name == "in" && args.is_empty() {
+                    Ok(ChifValue::Str(self.read_console_line()?))
                 } else if method_name == "in" && args.len() == 1 {
                     // Handle console input with pointer
                     if let Expression::Dereference(ref inner) = &args[0] {
                         if let Expression::Identifier(var_name) = &**inner {
-                            let mut input = String::new();
-                            io::stdin().read_line(&mut input).unwrap();
-                            let input = input.trim().to_string();
+                            let input = self.read_console_line()?;
                             
                             // Update the variable
-                            self.set_variable(var_name, ChifValue::Str(input))?;
-                            Ok(ChifValue::Nil)
+                            self.assign_variable(var_name, ChifValue::Str(input))?;
+                            Ok(ChifValue::Void)
                         } else {
                             Err(ChifError::RuntimeError {
                                 message: "con.in expects a pointer to a variable".to_string(),
@@ -765,14 +1468,35 @@ impl Interpreter {
                         })
                     }
                 } else {
-                    Err(ChifError::RuntimeError {
-                        message: format!("Unknown console method '{}'", method_name),
-                    })
+                    Err(Self::method_not_found("con", method_name))
+                }
+            }
+            ChifValue::Struct(struct_name, _) if struct_name == "Http" => self.call_http_method(method_name, args),
+            ChifValue::Struct(struct_name, _) if struct_name == "ProgramArgs" => self.call_args_method(method_name, args),
+            // Интерпретатор работает там же, где программа, поэтому это значения машины
+            ChifValue::Struct(struct_name, _) if struct_name == "Os" || struct_name == "RonoInfo" => {
+                let object = if struct_name == "Os" { "os" } else { "rono" };
+                let value = match (object, method_name) {
+                    ("os", "name") => std::env::consts::OS,
+                    ("os", "arch") => std::env::consts::ARCH,
+                    ("rono", "version") => env!("CARGO_PKG_VERSION"),
+                    _ => return Err(Self::method_not_found(object, method_name)),
+                };
+                Self::check_arity(&format!("{}.{}", object, method_name), args, 0)?;
+                Ok(ChifValue::Str(value.to_string()))
+            }
+            ChifValue::Struct(struct_name, _) if struct_name == "Memory" => {
+                Self::check_arity(&format!("mem.{}", method_name), args, 0)?;
+                match method_name {
+                    "reset" => Ok(ChifValue::Void),
+                    // Интерпретатор не держит память в арене
+                    "used" => Ok(ChifValue::Int(0)),
+                    _ => Err(Self::method_not_found("mem", method_name)),
                 }
             }
             ChifValue::Struct(struct_name, _) => {
                 // Проверяем, является ли вызов метода на переменной
-                if let Expression::MethodCall(method_call) = args[0].clone() {
+                if let Some(Expression::MethodCall(method_call)) = args.first().cloned() {
                     if let Expression::Identifier(var_name) = *method_call.object {
                         // Используем call_mutable_struct_method для вызова метода на переменной
                         return self.call_mutable_struct_method(&var_name, method_name, &args[1..]);
@@ -792,16 +1516,26 @@ impl Interpreter {
                         }
                     }
                 }
-                Err(ChifError::RuntimeError {
-                    message: format!("Unknown method '{}' for struct '{}'", method_name, struct_name),
-                })
+                Err(Self::method_not_found(&format!("struct '{}'", struct_name), method_name))
             }
-            _ => {
-                Err(ChifError::RuntimeError {
-                    message: format!("Unknown method '{}'", method_name),
-                })
+            other => Err(Self::method_not_found(&other.get_type().to_string(), method_name)),
+        }
+    }
+    
+    /// `xs.join(sep)`: every element must be a string
+    fn join_strings(&mut self, items: &[ChifValue], args: &[Expression]) -> Result<ChifValue> {
+        Self::check_arity("join", args, 1)?;
+        let sep = self.evaluate_expression(&args[0])?;
+        let sep = Self::expect_str(sep, "join() separator")?;
+        
+        let mut parts = Vec::with_capacity(items.len());
+        for (index, item) in items.iter().enumerate() {
+            match item {
+                ChifValue::Str(part) => parts.push(part.as_str()),
+                other => return Err(Self::type_error("str", other, format!("join() element at index {}", index))),
             }
         }
+        Ok(ChifValue::Str(parts.join(&sep)))
     }
     
     fn format_output(&mut self, value: &ChifValue) -> Result<String> {
@@ -810,7 +1544,7 @@ impl Interpreter {
                 // Handle string interpolation
                 self.interpolate_string(s)
             }
-            _ => Ok(value.to_string()),
+            _ => Ok(value.render(&|name| self.lookup_variable(name))),
         }
     }
     
@@ -851,7 +1585,7 @@ impl Interpreter {
                     // Evaluate the complex expression
                     match self.evaluate_interpolation_expression(&var_name) {
                         Ok(value) => {
-                            result.push_str(&value.to_string());
+                            result.push_str(&value.render(&|name| self.lookup_variable(name)));
                         }
                         Err(_) => {
                             // If expression evaluation failed, keep the placeholder
@@ -879,7 +1613,12 @@ impl Interpreter {
             let parts: Vec<&str> = method_call.split('.').collect();
             if parts.len() == 2 {
                 let obj = self.get_variable(parts[0])?;
-                return self.call_method(&obj, parts[1], &[]);
+                return match self.call_method(&obj, parts[1], &[])? {
+                    ChifValue::Void => Err(ChifError::RuntimeError {
+                        message: format!("method '{}' has no return value and cannot be interpolated", parts[1]),
+                    }),
+                    value => Ok(value),
+                };
             }
             return Err(ChifError::RuntimeError {
                 message: format!("Invalid method call expression: {}", expr),
@@ -969,53 +1708,134 @@ impl Interpreter {
                 }
             }
         }
-        
-        // Simple variable lookup
-        self.get_variable(expr)
+        
+        // Simple variable lookup
+        self.get_variable(expr)
+    }
+    
+    /// Быстрый путь для `BinaryOp::int_only`: операнды считаются на i64, и
+    /// ChifValue создаётся только для результата. `None`, если операнд во время
+    /// выполнения оказался не int; тогда выражение считается обычным путём.
+    /// Операнды — литералы, переменные и арифметика без побочных эффектов,
+    /// поэтому повторное вычисление ничего не меняет, а ошибки (деление на ноль)
+    /// возникают в том же порядке, что и на обычном пути
+    fn evaluate_int_binary(&self, binary_op: &BinaryOp) -> Result<Option<ChifValue>> {
+        let value = self.int_operand(&binary_op.left).and_then(|left| {
+            let right = self.int_operand(&binary_op.right)?;
+            match Self::int_arithmetic(&binary_op.operator, left, right) {
+                Err(IntFault::NotInt) => Self::int_comparison(&binary_op.operator, left, right)
+                    .map(ChifValue::Bool)
+                    .ok_or(IntFault::NotInt),
+                result => result.map(ChifValue::Int),
+            }
+        });
+        match value {
+            Ok(value) => Ok(Some(value)),
+            Err(fault) => fault.into_error().map_or(Ok(None), Err),
+        }
+    }
+    
+    fn int_operand(&self, expr: &Expression) -> std::result::Result<i64, IntFault> {
+        match expr {
+            Expression::Literal(ChifValue::Int(value)) => Ok(*value),
+            Expression::Identifier(name) => match self.lookup_variable(name) {
+                Some(ChifValue::Int(value)) => Ok(*value),
+                _ => Err(IntFault::NotInt),
+            },
+            Expression::Unary(UnaryOp { operator: UnaryOperator::Minus, operand }) => {
+                self.int_operand(operand).and_then(Self::int_negation)
+            }
+            Expression::Binary(binary_op) => {
+                let left = self.int_operand(&binary_op.left)?;
+                let right = self.int_operand(&binary_op.right)?;
+                Self::int_arithmetic(&binary_op.operator, left, right)
+            }
+            _ => Err(IntFault::NotInt),
+        }
+    }
+    
+    /// `+ - * / %` над int; для остальных операторов `IntFault::NotInt`
+    fn int_arithmetic(op: &BinaryOperator, l: i64, r: i64) -> std::result::Result<i64, IntFault> {
+        match op {
+            BinaryOperator::Add => l.checked_add(r).ok_or_else(|| Self::overflow(l, "+", r)),
+            BinaryOperator::Subtract => l.checked_sub(r).ok_or_else(|| Self::overflow(l, "-", r)),
+            BinaryOperator::Multiply => l.checked_mul(r).ok_or_else(|| Self::overflow(l, "*", r)),
+            // Division truncates toward zero: -7 / 2 == -3
+            BinaryOperator::Divide => {
+                if r == 0 {
+                    Err(IntFault::DivisionByZero)
+                } else {
+                    l.checked_div(r).ok_or_else(|| Self::overflow(l, "/", r))
+                }
+            }
+            BinaryOperator::Modulo => {
+                if r == 0 {
+                    Err(IntFault::DivisionByZero)
+                } else {
+                    // Remainder takes the sign of the dividend, same as srem in compiled code;
+                    // i64::MIN % -1 is 0 there as well
+                    Ok(l.wrapping_rem(r))
+                }
+            }
+            _ => Err(IntFault::NotInt),
+        }
+    }
+    
+    fn overflow(l: i64, operator: &str, r: i64) -> IntFault {
+        IntFault::Overflow(format!("{} {} {}", l, operator, r))
+    }
+    
+    /// `-value`; у `i64::MIN` нет положительной пары
+    fn int_negation(value: i64) -> std::result::Result<i64, IntFault> {
+        value.checked_neg().ok_or_else(|| IntFault::Overflow(format!("-({})", value)))
+    }
+    
+    /// Сравнения int; `None` для остальных операторов
+    fn int_comparison(op: &BinaryOperator, l: i64, r: i64) -> Option<bool> {
+        match op {
+            BinaryOperator::Equal => Some(l == r),
+            BinaryOperator::NotEqual => Some(l != r),
+            BinaryOperator::Less => Some(l < r),
+            BinaryOperator::Greater => Some(l > r),
+            BinaryOperator::LessEqual => Some(l <= r),
+            BinaryOperator::GreaterEqual => Some(l >= r),
+            _ => None,
+        }
     }
     
     fn apply_binary_op(&self, op: &BinaryOperator, left: &ChifValue, right: &ChifValue) -> Result<ChifValue> {
+        if *op == BinaryOperator::In {
+            return self.contains(right, left).map(ChifValue::Bool);
+        }
         match (left, right) {
-            (ChifValue::Int(l), ChifValue::Int(r)) => {
-                match op {
-                    BinaryOperator::Add => Ok(ChifValue::Int(l + r)),
-                    BinaryOperator::Subtract => Ok(ChifValue::Int(l - r)),
-                    BinaryOperator::Multiply => Ok(ChifValue::Int(l * r)),
-                    BinaryOperator::Divide => {
-                        if *r == 0 {
-                            Err(ChifError::RuntimeError {
-                                message: "Division by zero".to_string(),
-                            })
-                        } else {
-                            Ok(ChifValue::Int(l / r))
-                        }
+            (ChifValue::Int(l), ChifValue::Int(r)) => match Self::int_arithmetic(op, *l, *r) {
+                Ok(value) => Ok(ChifValue::Int(value)),
+                Err(IntFault::NotInt) => Self::int_comparison(op, *l, *r).map(ChifValue::Bool).ok_or_else(|| {
+                    ChifError::InvalidOperation {
+                        message: format!("{:?} is not defined for int", op),
                     }
-                    BinaryOperator::Modulo => Ok(ChifValue::Int(l % r)),
-                    BinaryOperator::Equal => Ok(ChifValue::Bool(l == r)),
-                    BinaryOperator::NotEqual => Ok(ChifValue::Bool(l != r)),
-                    BinaryOperator::Less => Ok(ChifValue::Bool(l < r)),
-                    BinaryOperator::Greater => Ok(ChifValue::Bool(l > r)),
-                    BinaryOperator::LessEqual => Ok(ChifValue::Bool(l <= r)),
-                    BinaryOperator::GreaterEqual => Ok(ChifValue::Bool(l >= r)),
-                    _ => Err(ChifError::RuntimeError {
-                        message: format!("Invalid operation for integers: {:?}", op),
-                    }),
-                }
-            }
+                }),
+                Err(fault) => Err(fault.into_error().unwrap_or(ChifError::DivisionByZero)),
+            },
+            // The analyzer types int with float as float: the int operand is promoted
+            (ChifValue::Int(l), ChifValue::Float(_)) => self.apply_binary_op(op, &ChifValue::Float(*l as f64), right),
+            (ChifValue::Float(_), ChifValue::Int(r)) => self.apply_binary_op(op, left, &ChifValue::Float(*r as f64)),
             (ChifValue::Float(l), ChifValue::Float(r)) => {
                 match op {
                     BinaryOperator::Add => Ok(ChifValue::Float(l + r)),
                     BinaryOperator::Subtract => Ok(ChifValue::Float(l - r)),
                     BinaryOperator::Multiply => Ok(ChifValue::Float(l * r)),
                     BinaryOperator::Divide => Ok(ChifValue::Float(l / r)),
+                    // fmod semantics: sign of the dividend, NaN for a zero divisor
+                    BinaryOperator::Modulo => Ok(ChifValue::Float(l % r)),
                     BinaryOperator::Equal => Ok(ChifValue::Bool((l - r).abs() < f64::EPSILON)),
                     BinaryOperator::NotEqual => Ok(ChifValue::Bool((l - r).abs() >= f64::EPSILON)),
                     BinaryOperator::Less => Ok(ChifValue::Bool(l < r)),
                     BinaryOperator::Greater => Ok(ChifValue::Bool(l > r)),
                     BinaryOperator::LessEqual => Ok(ChifValue::Bool(l <= r)),
                     BinaryOperator::GreaterEqual => Ok(ChifValue::Bool(l >= r)),
-                    _ => Err(ChifError::RuntimeError {
-                        message: format!("Invalid operation for floats: {:?}", op),
+                    _ => Err(ChifError::InvalidOperation {
+                        message: format!("{:?} is not defined for float", op),
                     }),
                 }
             }
@@ -1028,8 +1848,8 @@ impl Interpreter {
                     BinaryOperator::Greater => Ok(ChifValue::Bool(l > r)),
                     BinaryOperator::LessEqual => Ok(ChifValue::Bool(l <= r)),
                     BinaryOperator::GreaterEqual => Ok(ChifValue::Bool(l >= r)),
-                    _ => Err(ChifError::RuntimeError {
-                        message: format!("Invalid operation for strings: {:?}", op),
+                    _ => Err(ChifError::InvalidOperation {
+                        message: format!("{:?} is not defined for str", op),
                     }),
                 }
             }
@@ -1039,13 +1859,15 @@ impl Interpreter {
                     BinaryOperator::Or => Ok(ChifValue::Bool(*l || *r)),
                     BinaryOperator::Equal => Ok(ChifValue::Bool(l == r)),
                     BinaryOperator::NotEqual => Ok(ChifValue::Bool(l != r)),
-                    _ => Err(ChifError::RuntimeError {
-                        message: format!("Invalid operation for booleans: {:?}", op),
+                    _ => Err(ChifError::InvalidOperation {
+                        message: format!("{:?} is not defined for bool", op),
                     }),
                 }
             }
-            _ => Err(ChifError::RuntimeError {
-                message: format!("Type mismatch in binary operation: {:?} {:?} {:?}", left, op, right),
+            _ => Err(ChifError::TypeError {
+                expected: left.get_type().to_string(),
+                found: right.get_type().to_string(),
+                context: format!("right operand of {:?}", op),
             }),
         }
     }
@@ -1053,41 +1875,146 @@ impl Interpreter {
     fn apply_unary_op(&self, op: &UnaryOperator, operand: &ChifValue) -> Result<ChifValue> {
         match (op, operand) {
             (UnaryOperator::Not, ChifValue::Bool(b)) => Ok(ChifValue::Bool(!b)),
-            (UnaryOperator::Minus, ChifValue::Int(i)) => Ok(ChifValue::Int(-i)),
+            (UnaryOperator::Minus, ChifValue::Int(i)) => match Self::int_negation(*i) {
+                Ok(value) => Ok(ChifValue::Int(value)),
+                Err(fault) => Err(fault.into_error().unwrap_or(ChifError::DivisionByZero)),
+            },
             (UnaryOperator::Minus, ChifValue::Float(f)) => Ok(ChifValue::Float(-f)),
-            _ => Err(ChifError::RuntimeError {
-                message: format!("Invalid unary operation: {:?} {:?}", op, operand),
-            }),
+            _ => Err(Self::type_error(
+                if *op == UnaryOperator::Not { "bool" } else { "int or float" },
+                operand,
+                format!("operand of {:?}", op),
+            )),
         }
     }
     
     fn get_variable(&self, name: &str) -> Result<ChifValue> {
-        // Check locals first (from innermost to outermost)
-        for scope in self.locals.iter().rev() {
-            if let Some(value) = scope.get(name) {
-                return Ok(value.clone());
+        self.lookup_variable(name).cloned().ok_or_else(|| self.variable_not_found(name))
+    }
+    
+    fn lookup_variable(&self, name: &str) -> Option<&ChifValue> {
+        // Check locals first (from innermost to outermost), then globals
+        self.locals.iter().rev()
+            .find_map(|scope| scope.get(name))
+            .or_else(|| self.globals.get(name))
+    }
+    
+    /// Ошибка с контекстом: текущая функция, ближайшее по написанию видимое имя
+    /// и первые `MAX_LISTED_NAMES` видимых переменных, от внутренней области к внешней
+    fn variable_not_found(&self, name: &str) -> ChifError {
+        let mut seen = HashSet::new();
+        let mut visible = Vec::new();
+        for scope in self.locals.iter().rev().chain(std::iter::once(&self.globals)) {
+            let mut names: Vec<&str> = scope.keys()
+                .map(String::as_str)
+                // Копии для `&mut` и встроенные объекты пользователь не объявлял
+                .filter(|candidate| !candidate.starts_with('&') && !is_builtin_object_name(candidate))
+                .collect();
+            names.sort_unstable();
+            visible.extend(names.into_iter().filter(|candidate| seen.insert(*candidate)));
+        }
+        
+        ChifError::VariableNotFound {
+            name: name.to_string(),
+            function: self.call_stack.last().cloned(),
+            suggestion: suggest_name(name, visible.iter().copied()).map(str::to_string),
+            in_scope: visible.iter().take(MAX_LISTED_NAMES).map(|candidate| candidate.to_string()).collect(),
+            omitted: visible.len().saturating_sub(MAX_LISTED_NAMES),
+        }
+    }
+    
+    /// An int stored where the program declares a float (a variable, a parameter
+    /// or a function result) becomes a float, as the analyzer allows
+    /// Приводит значение к объявленному типу: int становится float, а литералы
+    /// массивов — списками там, где объявлен список, в том числе внутри
+    /// значений словаря и элементов вложенных коллекций
+    fn conform_to_type(declared: &crate::types::ChifType, value: ChifValue) -> ChifValue {
+        use crate::types::ChifType;
+        
+        // Тип элемента: `list[int][][]` состоит из `list[int][]`
+        let element_type = |element: &ChifType, dimensions: &[usize], nested: fn(Box<ChifType>, Vec<usize>) -> ChifType| {
+            if dimensions.len() > 1 {
+                nested(Box::new(element.clone()), dimensions[1..].to_vec())
+            } else {
+                element.clone()
             }
+        };
+        match (declared, value) {
+            (ChifType::Float, ChifValue::Int(n)) => ChifValue::Float(n as f64),
+            (ChifType::List(element, dimensions), ChifValue::Array(items) | ChifValue::List(items)) => {
+                ChifValue::List(Self::conform_items(&element_type(element, dimensions, ChifType::List), items))
+            }
+            (ChifType::Array(element, dimensions), ChifValue::Array(items)) => {
+                ChifValue::Array(Self::conform_items(&element_type(element, dimensions, ChifType::Array), items))
+            }
+            (ChifType::Map(_, value_type), ChifValue::Map(entries)) => ChifValue::Map(
+                entries.into_iter().map(|(key, item)| (key, Self::conform_to_type(value_type, item))).collect()
+            ),
+            (_, value) => value,
         }
+    }
+    
+    /// Приводит элементы коллекции к типу `element`; скалярные элементы,
+    /// кроме float, остаются как есть
+    fn conform_items(element: &crate::types::ChifType, items: Vec<ChifValue>) -> Vec<ChifValue> {
+        use crate::types::ChifType;
         
-        // Check globals
-        if let Some(value) = self.globals.get(name) {
-            Ok(value.clone())
+        if matches!(element, ChifType::Float | ChifType::List(..) | ChifType::Array(..) | ChifType::Map(..)) {
+            items.into_iter().map(|item| Self::conform_to_type(element, item)).collect()
         } else {
-            Err(ChifError::VariableNotFound {
-                name: name.to_string(),
-            })
+            items
         }
     }
     
     fn set_variable(&mut self, name: &str, value: ChifValue) -> Result<()> {
-        if let Some(scope) = self.locals.last_mut() {
+        // Значение, связанное с `_`, просто отбрасывается
+        if name == DISCARD {
+            return Ok(());
+        }
+        let scope = self.locals.last_mut().unwrap_or(&mut self.globals);
+        // Существующая переменная перезаписывается на месте, без новой строки-ключа
+        match scope.get_mut(name) {
+            Some(slot) => *slot = value,
+            None => {
+                scope.insert(name.to_string(), value);
+            }
+        }
+        Ok(())
+    }
+    
+    /// Присваивание: локальная переменная, иначе глобальная переменная с этим
+    /// именем (там же, где её найдёт `lookup_variable`), иначе новая переменная
+    /// текущей области
+    fn assign_variable(&mut self, name: &str, value: ChifValue) -> Result<()> {
+        let local = self.locals.iter().any(|scope| scope.contains_key(name));
+        match self.globals.get_mut(name) {
+            Some(global) if !local => {
+                *global = value;
+                Ok(())
+            }
+            _ => self.set_variable(name, value),
+        }
+    }
+    
+    /// Запись через ссылку меняет переменную в той области, где она объявлена,
+    /// а не заводит новую в области вызванной функции
+    fn set_referenced_variable(&mut self, name: &str, value: ChifValue) -> Result<()> {
+        if let Some(scope) = self.locals.iter_mut().rev().find(|scope| scope.contains_key(name)) {
             scope.insert(name.to_string(), value);
+        } else if let Some(global) = self.globals.get_mut(name) {
+            *global = value;
         } else {
-            self.globals.insert(name.to_string(), value);
+            return Err(self.variable_not_found(name));
         }
         Ok(())
     }
     
+    fn read_only_reference_error() -> ChifError {
+        ChifError::RuntimeError {
+            message: "Cannot write through a read-only reference; declare the parameter as &mut".to_string(),
+        }
+    }
+    
     fn get_index(&self, object: &ChifValue, index: &ChifValue) -> Result<ChifValue> {
         match (object, index) {
             (ChifValue::Array(arr), ChifValue::Int(i)) => {
@@ -1107,26 +2034,64 @@ impl Interpreter {
                 }
             }
             (ChifValue::Map(map), ChifValue::Str(key)) => {
-                if let Some(value) = map.get(key) {
+                if let Some(value) = map.get(key.as_str()) {
                     Ok(value.clone())
                 } else {
                     Ok(ChifValue::Nil)
                 }
             }
-            _ => Err(ChifError::RuntimeError {
-                message: "Invalid index operation".to_string(),
-            }),
+            _ => Err(Self::index_error(object, index)),
+        }
+    }
+    
+    /// Проверяет поля литерала по объявлению структуры: неизвестные, повторные
+    /// и пропущенные поля — ошибка времени выполнения. Поля, которых нет в
+    /// литерале, берутся из `base`, если он задан (`..base`)
+    fn build_struct_value(&self, struct_name: &str, mut values: Vec<(String, ChifValue)>, base: Option<StructFields>) -> Result<ChifValue> {
+        let struct_def = self.structs.get(struct_name).ok_or_else(|| ChifError::RuntimeError {
+            message: format!("Unknown struct '{}'", struct_name),
+        })?;
+        
+        for (index, (field_name, _)) in values.iter().enumerate() {
+            if !struct_def.fields.iter().any(|field| &field.name == field_name) {
+                return Err(ChifError::FieldNotFound {
+                    struct_name: struct_name.to_string(),
+                    field: field_name.clone(),
+                    available: struct_def.fields.iter().map(|field| field.name.clone()).collect(),
+                });
+            }
+            if values[..index].iter().any(|(earlier, _)| earlier == field_name) {
+                return Err(ChifError::RuntimeError {
+                    message: format!("Field '{}' of struct '{}' is specified more than once", field_name, struct_name),
+                });
+            }
+        }
+        
+        let mut fields = StructFields::new();
+        for field in &struct_def.fields {
+            let value = match values.iter().position(|(name, _)| *name == field.name) {
+                Some(position) => values.swap_remove(position).1,
+                None => base.as_ref().and_then(|base| base.get(&field.name).cloned()).ok_or_else(|| {
+                    ChifError::RuntimeError {
+                        message: format!("Missing field '{}' in literal of struct '{}'", field.name, struct_name),
+                    }
+                })?,
+            };
+            fields.insert(field.name.clone(), value);
         }
+        Ok(ChifValue::Struct(struct_name.to_string(), fields))
     }
     
     fn get_field(&self, object: &ChifValue, field: &str) -> Result<ChifValue> {
         match object {
-            ChifValue::Struct(_, fields) => {
+            ChifValue::Struct(struct_name, fields) => {
                 if let Some(value) = fields.get(field) {
                     Ok(value.clone())
                 } else {
-                    Err(ChifError::RuntimeError {
-                        message: format!("Field '{}' not found", field),
+                    Err(ChifError::FieldNotFound {
+                        struct_name: struct_name.clone(),
+                        field: field.to_string(),
+                        available: fields.keys().cloned().collect(),
                     })
                 }
             }
@@ -1135,16 +2100,103 @@ impl Interpreter {
                 let referenced_object = self.get_variable(var_name)?;
                 self.get_field(&referenced_object, field)
             }
+            ChifValue::Pointer(inner) => self.get_field(inner, field),
+            ChifValue::Tuple(values) => {
+                // Позиционный доступ к элементам кортежа: t.0, t.1
+                field.parse::<usize>().ok()
+                    .and_then(|index| values.get(index).cloned())
+                    .ok_or_else(|| ChifError::RuntimeError {
+                        message: format!("Tuple of {} elements has no field '{}'", values.len(), field),
+                    })
+            }
+            other => Err(Self::type_error("struct", other, format!("access to field '{}'", field))),
+        }
+    }
+    
+    /// Записывает значение обратно в место, откуда оно было прочитано:
+    /// переменную, элемент массива/списка или поле структуры
+    fn assign_to_place(&mut self, target: &Expression, value: ChifValue) -> Result<()> {
+        match target {
+            Expression::Identifier(name) => {
+                // A float variable stays float when an int is assigned to it
+                let value = match (self.lookup_variable(name), value) {
+                    (Some(ChifValue::Float(_)), ChifValue::Int(n)) => ChifValue::Float(n as f64),
+                    (_, value) => value,
+                };
+                self.assign_variable(name, value)
+            }
+            Expression::Index(index_access) => self.assign_to_index(index_access, value),
+            Expression::FieldAccess(field_access) => self.assign_to_field(field_access, value),
+            Expression::Dereference(pointer) => match self.evaluate_expression(pointer)? {
+                ChifValue::Reference(var_name) => self.set_referenced_variable(&var_name, value),
+                ChifValue::Pointer(_) => Err(Self::read_only_reference_error()),
+                other => Err(Self::type_error("pointer", &other, "assignment through '*'")),
+            },
             _ => Err(ChifError::RuntimeError {
-                message: "Cannot access field on non-struct value".to_string(),
+                message: "Invalid assignment target".to_string(),
             }),
         }
     }
     
-    fn assign_to_index(&mut self, _index_access: &IndexAccess, _value: ChifValue) -> Result<()> {
-        // This is a simplified implementation
-        // In a real implementation, we'd need to handle mutable references properly
-        Ok(())
+    /// Элементы хранятся по значению, поэтому `pts[1].x = 10` меняет копию
+    /// элемента и записывает весь контейнер обратно
+    fn assign_to_index(&mut self, index_access: &IndexAccess, value: ChifValue) -> Result<()> {
+        let container = self.evaluate_expression(&index_access.object)?;
+        let mut indices = Vec::new();
+        for index_expr in &index_access.indices {
+            indices.push(self.evaluate_expression(index_expr)?);
+        }
+        
+        match container {
+            // `xs[0] = 1` для параметра `&mut xs` меняет копию, которая после вызова
+            // записывается обратно в переменную вызывающего
+            ChifValue::Reference(var_name) => {
+                let referenced = self.get_variable(&var_name)?;
+                let updated = self.set_index(referenced, &indices, value)?;
+                self.set_referenced_variable(&var_name, updated)
+            }
+            ChifValue::Pointer(_) => Err(Self::read_only_reference_error()),
+            container => {
+                let updated = self.set_index(container, &indices, value)?;
+                self.assign_to_place(&index_access.object, updated)
+            }
+        }
+    }
+    
+    fn set_index(&mut self, container: ChifValue, indices: &[ChifValue], value: ChifValue) -> Result<ChifValue> {
+        let Some((index, rest)) = indices.split_first() else {
+            return Ok(value);
+        };
+        
+        match (container, index) {
+            (ChifValue::Array(mut items), ChifValue::Int(i)) => {
+                let idx = *i as usize;
+                let element = items.get(idx).cloned().ok_or(ChifError::IndexOutOfBounds { index: idx })?;
+                items[idx] = self.set_index(element, rest, value)?;
+                Ok(ChifValue::Array(items))
+            }
+            (ChifValue::List(mut items), ChifValue::Int(i)) => {
+                let idx = *i as usize;
+                let element = items.get(idx).cloned().ok_or(ChifError::IndexOutOfBounds { index: idx })?;
+                items[idx] = self.set_index(element, rest, value)?;
+                Ok(ChifValue::List(items))
+            }
+            (ChifValue::Map(mut map), ChifValue::Str(key)) => {
+                // A key that is already in the map keeps its shared string
+                match map.get_mut(key.as_str()) {
+                    Some(slot) => {
+                        let entry = std::mem::replace(slot, ChifValue::Nil);
+                        *slot = self.set_index(entry, rest, value)?;
+                    }
+                    None => {
+                        let entry = self.set_index(ChifValue::Nil, rest, value)?;
+                        map.insert(self.intern(key), entry);
+                    }
+                }
+                Ok(ChifValue::Map(map))
+            }
+            (container, index) => Err(Self::index_error(&container, index)),
+        }
     }
     
     fn assign_to_field(&mut self, field_access: &FieldAccess, value: ChifValue) -> Result<()> {
@@ -1157,17 +2209,23 @@ impl Interpreter {
             
             if let ChifValue::Struct(struct_name, mut fields) = object {
                 fields.insert(field_access.field.clone(), value);
-                self.set_variable(var_name, ChifValue::Struct(struct_name, fields))?;
+                self.assign_variable(var_name, ChifValue::Struct(struct_name, fields))?;
                 return Ok(());
             } else if let ChifValue::Reference(ref_var_name) = object {
                 // Если объект - ссылка, получаем реальный объект
                 let mut ref_object = self.get_variable(&ref_var_name)?;
                 if let ChifValue::Struct(struct_name, mut fields) = ref_object {
                     fields.insert(field_access.field.clone(), value);
-                    self.set_variable(&ref_var_name, ChifValue::Struct(struct_name, fields))?;
+                    self.set_referenced_variable(&ref_var_name, ChifValue::Struct(struct_name, fields))?;
                     return Ok(());
                 }
+            } else if let ChifValue::Pointer(_) = object {
+                return Err(Self::read_only_reference_error());
             }
+        } else if let ChifValue::Struct(struct_name, mut fields) = self.evaluate_expression(object_expr)? {
+            // Поле элемента массива или вложенной структуры: меняем копию и записываем её обратно
+            fields.insert(field_access.field.clone(), value);
+            return self.assign_to_place(object_expr, ChifValue::Struct(struct_name, fields));
         }
         
         Err(ChifError::RuntimeError {
@@ -1175,6 +2233,24 @@ impl Interpreter {
         })
     }
     
+    /// `item in container`: ключ словаря, элемент списка или массива (int
+    /// сравнивается с float как float) или подстрока
+    fn contains(&self, container: &ChifValue, item: &ChifValue) -> Result<bool> {
+        match (container, item) {
+            (ChifValue::Reference(name), _) => self.contains(&self.get_variable(name)?, item),
+            (ChifValue::Pointer(inner), _) => self.contains(inner, item),
+            (ChifValue::Map(map), ChifValue::Str(key)) => Ok(map.contains_key(key.as_str())),
+            (ChifValue::Map(_), _) => Err(Self::type_error("str", item, "map key")),
+            (ChifValue::List(items) | ChifValue::Array(items), _) => Ok(items.iter().any(|element| match (element, item) {
+                (ChifValue::Float(element), ChifValue::Int(item)) => (element - *item as f64).abs() < f64::EPSILON,
+                _ => self.values_equal(element, item),
+            })),
+            (ChifValue::Str(text), ChifValue::Str(part)) => Ok(text.contains(part.as_str())),
+            (ChifValue::Str(_), _) => Err(Self::type_error("str", item, "'in' with a str")),
+            _ => Err(Self::type_error("map, list, array or str", container, "'in'")),
+        }
+    }
+    
     fn is_truthy(&self, value: &ChifValue) -> bool {
         match value {
             ChifValue::Bool(b) => *b,
@@ -1186,29 +2262,83 @@ impl Interpreter {
         }
     }
     
+    /// Функция модуля по внутреннему имени `utils_helper`
+    fn module_function(&self, name: &str) -> Option<Rc<Function>> {
+        self.modules.iter().find_map(|(module_name, module)| {
+            let function = name.strip_prefix(module_name.as_str())?.strip_prefix('_')?;
+            module.functions.get(function).cloned()
+        })
+    }
+    
     fn process_import(&mut self, import: &ImportStatement) -> Result<()> {
-        use std::fs;
-        use crate::{lexer::Lexer, parser::Parser};
+        let file_path = import.file_path();
+        let module_name = import.module_name().map_err(|message| ChifError::RuntimeError { message })?;
         
-        // Add .rono extension if not present
-        let file_path = if import.path.ends_with(".rono") {
-            import.path.clone()
-        } else {
-            format!("{}.rono", import.path)
+        // Read and parse the imported file with the loader shared with the analyzer
+        let from = match self.importing.last() {
+            Some(importer) => importer.parent(),
+            None => self.source_dir.as_deref(),
         };
+        let canonical_path = crate::resolve_import(import, from)
+            .map_err(|message| ChifError::RuntimeError { message })?;
+        let imported_program = crate::load_module(&file_path, &canonical_path)
+            .map_err(|message| ChifError::RuntimeError { message })?;
         
-        // Read the imported file
-        let source = fs::read_to_string(&file_path).map_err(|_| {
-            ChifError::RuntimeError {
-                message: format!("Cannot read file: {}", file_path),
-            }
-        })?;
+        // Модули, которые импортирует сам модуль, нужны его функциям при вызове.
+        // Модуль, который уже загружается выше по цепочке, зарегистрируется там
+        if self.importing.contains(&canonical_path) {
+            return Ok(());
+        }
+        self.importing.push(canonical_path);
+        let nested = imported_program.items.iter()
+            .filter_map(|item| match item {
+                Item::Import(nested) => Some(nested),
+                _ => None,
+            })
+            .try_for_each(|nested| self.process_import(nested));
+        self.importing.pop();
+        nested?;
         
-        // Parse the imported file
-        let mut lexer = Lexer::new(&source);
-        let tokens = lexer.tokenize()?;
-        let mut parser = Parser::new(tokens);
-        let imported_program = parser.parse()?;
+        // `use (...)` берёт только перечисленные функции и структуры, а без него —
+        // экспортированные, если в модуле есть `export`. Функции, которые они
+        // вызывают, и структуры, которые они используют, вместе с методами
+        // доступны им, но не вызывающему коду
+        let exports = imported_program.exported_names();
+        let selection = import.names.clone().or_else(|| exports.clone());
+        let reachable = selection.as_ref().map(|names| imported_program.reachable(names));
+        if let Some(names) = &import.names {
+            let mut available: Vec<&str> = imported_program.items.iter()
+                .filter_map(|item| match item {
+                    Item::Function(func) => Some(func.name.as_str()),
+                    Item::Struct(struct_def) => Some(struct_def.name.as_str()),
+                    _ => None,
+                })
+                .collect();
+            if let Some(exports) = &exports {
+                if let Some(private) = names.iter().find(|name| available.contains(&name.as_str()) && !exports.contains(name)) {
+                    return Err(ChifError::RuntimeError {
+                        message: format!("'{}' exists in module '{}' but is not exported", private, import.path),
+                    });
+                }
+                available.retain(|name| exports.iter().any(|exported| exported == name));
+            }
+            if let Some(missing) = names.iter().find(|name| !available.contains(&name.as_str())) {
+                let suggestion = suggest_name(missing, available.iter().copied())
+                    .map(|closest| format!("; did you mean '{}'?", closest))
+                    .unwrap_or_default();
+                return Err(ChifError::RuntimeError {
+                    message: format!("Module '{}' has no function or struct '{}'{}", import.path, missing, suggestion),
+                });
+            }
+        }
+        let listed = |name: &String| match &selection {
+            Some(names) => names.contains(name),
+            None => true,
+        };
+        let used = |name: &String| match &reachable {
+            Some(reachable) => reachable.structs.contains(name),
+            None => true,
+        };
         
         // Extract functions and structs from imported module
         let mut module_functions = HashMap::new();
@@ -1217,12 +2347,22 @@ impl Interpreter {
         for item in &imported_program.items {
             match item {
                 Item::Function(func) => {
-                    module_functions.insert(func.name.clone(), func.clone());
+                    if matches!(&reachable, Some(reachable) if !reachable.functions.contains(&func.name)) {
+                        continue;
+                    }
+                    let func = Self::prepare_function(func);
+                    if listed(&func.name) {
+                        module_functions.insert(func.name.clone(), Rc::clone(&func));
+                    }
                     // Also add to global functions for recursive calls
-                    self.functions.insert(func.name.clone(), func.clone());
+                    self.functions.insert(func.name.clone(), func);
                 }
+                Item::Struct(struct_def) if !used(&struct_def.name) => {}
+                Item::StructImpl(impl_block) if !used(&impl_block.struct_name) => {}
                 Item::Struct(struct_def) => {
-                    module_structs.insert(struct_def.name.clone(), struct_def.clone());
+                    if listed(&struct_def.name) {
+                        module_structs.insert(struct_def.name.clone(), struct_def.clone());
+                    }
                     // Also add to global structs so they can be used
                     self.structs.insert(struct_def.name.clone(), struct_def.clone());
                 }
@@ -1231,9 +2371,10 @@ impl Interpreter {
                     self.struct_methods
                         .entry(impl_block.struct_name.clone())
                         .or_insert_with(Vec::new)
-                        .extend(impl_block.methods.clone());
+                        .extend(impl_block.methods.iter().map(Self::prepare_function));
                 }
-                _ => {} // Ignore nested imports for now
+                // Анализатор не пропускает глобальные переменные в модулях
+                Item::Import(_) | Item::Global(_) => {}
             }
         }
         
@@ -1243,131 +2384,181 @@ impl Interpreter {
         };
         
         // Store module with alias or filename
-        let module_name = import.alias.clone().unwrap_or_else(|| {
-            // Extract filename without extension
-            std::path::Path::new(&import.path)
-                .file_stem()
-                .unwrap()
-                .to_string_lossy()
-                .to_string()
-        });
-        
         self.modules.insert(module_name, module);
         Ok(())
     }
     
-    fn http_get_request(&self, url: &str) -> Result<ChifValue> {
-        use reqwest::blocking::Client;
-        use std::collections::HashMap;
-        
-        let client = Client::new();
-        match client.get(url).send() {
+    /// Схема http или https и непустой хост; проверяется до любого обращения к сети.
+    /// Те же проверки и сообщения у `rono_http_validate_url` в runtime.c
+    #[cfg(feature = "http")]
+    fn validate_http_url(url: &str) -> Result<()> {
+        let invalid = |problem: &str| ChifError::RuntimeError {
+            message: format!("Invalid URL '{}': {}", url, problem),
+        };
+        let rest = match url.split_once("://") {
+            Some((scheme, rest)) if scheme.eq_ignore_ascii_case("http") || scheme.eq_ignore_ascii_case("https") => rest,
+            _ => return Err(invalid("scheme must be http or https")),
+        };
+        let authority = rest.split(['/', '?', '#']).next().unwrap_or_default();
+        let host = authority.rsplit('@').next().unwrap_or_default();
+        if host.is_empty() || host.starts_with(':') {
+            return Err(invalid("host is empty"));
+        }
+        Ok(())
+    }
+    
+    /// HttpResponse из результата запроса. Сбой соединения (DNS, отказ, таймаут) —
+    /// `ok` false, `status` 0 и текст в `error`; любой HTTP-статус, даже 500, — `ok` true
+    #[cfg(feature = "http")]
+    fn http_response(result: reqwest::Result<reqwest::blocking::Response>) -> ChifValue {
+        let (status, body, content_type, error) = match result {
             Ok(response) => {
                 let status = response.status().as_u16() as i64;
+                let content_type = response.headers()
+                    .get(reqwest::header::CONTENT_TYPE)
+                    .and_then(|value| value.to_str().ok())
+                    .unwrap_or_default()
+                    .to_string();
                 let body = response.text().unwrap_or_else(|_| "Error reading response".to_string());
-                
-                let mut fields = HashMap::new();
-                fields.insert("status".to_string(), ChifValue::Int(status));
-                fields.insert("body".to_string(), ChifValue::Str(body));
-                fields.insert("content_type".to_string(), ChifValue::Str("application/json".to_string()));
-                
-                Ok(ChifValue::Struct("HttpResponse".to_string(), fields))
+                (status, body, content_type, None)
             }
-            Err(e) => {
-                let mut fields = HashMap::new();
-                fields.insert("status".to_string(), ChifValue::Int(0));
-                fields.insert("body".to_string(), ChifValue::Str(format!("Request failed: {}", e)));
-                fields.insert("content_type".to_string(), ChifValue::Str("text/plain".to_string()));
-                
-                Ok(ChifValue::Struct("HttpResponse".to_string(), fields))
+            Err(e) => (0, String::new(), String::new(), Some(e.to_string())),
+        };
+        
+        let mut fields = StructFields::new();
+        fields.insert("status".to_string(), ChifValue::Int(status));
+        fields.insert("body".to_string(), ChifValue::Str(body));
+        fields.insert("content_type".to_string(), ChifValue::Str(content_type));
+        fields.insert("ok".to_string(), ChifValue::Bool(error.is_none()));
+        fields.insert("error".to_string(), ChifValue::Str(error.unwrap_or_default()));
+        ChifValue::Struct("HttpResponse".to_string(), fields)
+    }
+    
+    /// `http.get(url)` и т.д.: до уровня 2 — тело ответа (сбой соединения —
+    /// ошибка выполнения), с уровня 2 — HttpResponse, как у `http_get`
+    #[cfg(feature = "http")]
+    fn call_http_method(&mut self, method_name: &str, args: &[Expression]) -> Result<ChifValue> {
+        let callee = format!("http.{}", method_name);
+        let with_body = matches!(method_name, "post" | "put");
+        if !with_body && !matches!(method_name, "get" | "delete") {
+            return Err(Self::method_not_found("http", method_name));
+        }
+        Self::check_arity(&callee, args, if with_body { 2 } else { 1 })?;
+        let url = self.evaluate_expression(&args[0])?;
+        let url = Self::expect_str(url, &format!("{}() URL", callee))?;
+        let body = match args.get(1) {
+            Some(body) => {
+                let body = self.evaluate_expression(body)?;
+                Self::expect_str(body, &format!("{}() body", callee))?
             }
+            None => String::new(),
+        };
+        let response = match method_name {
+            "get" => self.http_get_request(&url)?,
+            "post" => self.http_post_request(&url, &body)?,
+            "put" => self.http_put_request(&url, &body)?,
+            _ => self.http_delete_request(&url)?,
+        };
+        if self.lang_version >= LangVersion::V2 {
+            return Ok(response);
+        }
+        
+        let field = |name: &str| match &response {
+            ChifValue::Struct(_, fields) => fields.get(name).cloned(),
+            _ => None,
+        };
+        match (field("ok"), field("body"), field("error")) {
+            (Some(ChifValue::Bool(true)), Some(body), _) => Ok(body),
+            (_, _, Some(ChifValue::Str(error))) => Err(ChifError::RuntimeError {
+                message: format!("{} failed: {}", callee, error),
+            }),
+            _ => unreachable!("http_response always builds an HttpResponse"),
+        }
+    }
+    
+    /// `args.count()`, `args.get(i)`, `args.has_flag(name)` (есть ли аргумент
+    /// `--name`) и `args.value_of(name)` (аргумент после `--name`). Чего нет,
+    /// то пустая строка, как в runtime скомпилированной программы
+    fn call_args_method(&mut self, method_name: &str, args: &[Expression]) -> Result<ChifValue> {
+        let arity = match method_name {
+            "count" => 0,
+            "get" | "has_flag" | "value_of" => 1,
+            _ => return Err(Self::method_not_found("args", method_name)),
+        };
+        Self::check_arity(&format!("args.{}", method_name), args, arity)?;
+        if method_name == "count" {
+            return Ok(ChifValue::Int(self.program_args.len() as i64));
         }
+        
+        let arg = self.evaluate_expression(&args[0])?;
+        let position = if method_name == "get" {
+            usize::try_from(Self::expect_int(arg, "args.get() index")?).ok()
+        } else {
+            let name = Self::expect_str(arg, &format!("args.{}() name", method_name))?;
+            let flag = self.program_args.iter().position(|arg| arg.strip_prefix("--") == Some(name.as_str()));
+            if method_name == "has_flag" {
+                return Ok(ChifValue::Bool(flag.is_some()));
+            }
+            flag.map(|flag| flag + 1)
+        };
+        Ok(ChifValue::Str(position.and_then(|position| self.program_args.get(position)).cloned().unwrap_or_default()))
+    }
+    
+    #[cfg(not(feature = "http"))]
+    fn call_http_method(&mut self, method_name: &str, _args: &[Expression]) -> Result<ChifValue> {
+        Err(ChifError::RuntimeError {
+            message: format!("http.{}() is unavailable: rono_lang was built without the `http` feature", method_name),
+        })
+    }
+    
+    #[cfg(feature = "http")]
+    fn http_get_request(&self, url: &str) -> Result<ChifValue> {
+        use reqwest::blocking::Client;
+        
+        Self::validate_http_url(url)?;
+        Ok(Self::http_response(Client::new().get(url).send()))
     }
     
+    #[cfg(feature = "http")]
     fn http_post_request(&self, url: &str, body: &str) -> Result<ChifValue> {
         use reqwest::blocking::Client;
-        use std::collections::HashMap;
         
-        let client = Client::new();
-        match client.post(url).body(body.to_string()).header("Content-Type", "application/json").send() {
-            Ok(response) => {
-                let status = response.status().as_u16() as i64;
-                let response_body = response.text().unwrap_or_else(|_| "Error reading response".to_string());
-                
-                let mut fields = HashMap::new();
-                fields.insert("status".to_string(), ChifValue::Int(status));
-                fields.insert("body".to_string(), ChifValue::Str(response_body));
-                fields.insert("content_type".to_string(), ChifValue::Str("application/json".to_string()));
-                
-                Ok(ChifValue::Struct("HttpResponse".to_string(), fields))
-            }
-            Err(e) => {
-                let mut fields = HashMap::new();
-                fields.insert("status".to_string(), ChifValue::Int(0));
-                fields.insert("body".to_string(), ChifValue::Str(format!("Request failed: {}", e)));
-                fields.insert("content_type".to_string(), ChifValue::Str("text/plain".to_string()));
-                
-                Ok(ChifValue::Struct("HttpResponse".to_string(), fields))
-            }
-        }
+        Self::validate_http_url(url)?;
+        let request = Client::new().post(url).body(body.to_string()).header("Content-Type", "application/json");
+        Ok(Self::http_response(request.send()))
     }
     
+    #[cfg(feature = "http")]
     fn http_put_request(&self, url: &str, body: &str) -> Result<ChifValue> {
         use reqwest::blocking::Client;
-        use std::collections::HashMap;
         
-        let client = Client::new();
-        match client.put(url).body(body.to_string()).header("Content-Type", "application/json").send() {
-            Ok(response) => {
-                let status = response.status().as_u16() as i64;
-                let response_body = response.text().unwrap_or_else(|_| "Error reading response".to_string());
-                
-                let mut fields = HashMap::new();
-                fields.insert("status".to_string(), ChifValue::Int(status));
-                fields.insert("body".to_string(), ChifValue::Str(response_body));
-                fields.insert("content_type".to_string(), ChifValue::Str("application/json".to_string()));
-                
-                Ok(ChifValue::Struct("HttpResponse".to_string(), fields))
-            }
-            Err(e) => {
-                let mut fields = HashMap::new();
-                fields.insert("status".to_string(), ChifValue::Int(0));
-                fields.insert("body".to_string(), ChifValue::Str(format!("Request failed: {}", e)));
-                fields.insert("content_type".to_string(), ChifValue::Str("text/plain".to_string()));
-                
-                Ok(ChifValue::Struct("HttpResponse".to_string(), fields))
-            }
-        }
+        Self::validate_http_url(url)?;
+        let request = Client::new().put(url).body(body.to_string()).header("Content-Type", "application/json");
+        Ok(Self::http_response(request.send()))
     }
     
+    #[cfg(feature = "http")]
     fn http_delete_request(&self, url: &str) -> Result<ChifValue> {
         use reqwest::blocking::Client;
-        use std::collections::HashMap;
         
-        let client = Client::new();
-        match client.delete(url).send() {
-            Ok(response) => {
-                let status = response.status().as_u16() as i64;
-                let response_body = response.text().unwrap_or_else(|_| "Error reading response".to_string());
-                
-                let mut fields = HashMap::new();
-                fields.insert("status".to_string(), ChifValue::Int(status));
-                fields.insert("body".to_string(), ChifValue::Str(response_body));
-                fields.insert("content_type".to_string(), ChifValue::Str("text/plain".to_string()));
-                
-                Ok(ChifValue::Struct("HttpResponse".to_string(), fields))
-            }
-            Err(e) => {
-                let mut fields = HashMap::new();
-                fields.insert("status".to_string(), ChifValue::Int(0));
-                fields.insert("body".to_string(), ChifValue::Str(format!("Request failed: {}", e)));
-                fields.insert("content_type".to_string(), ChifValue::Str("text/plain".to_string()));
-                
-                Ok(ChifValue::Struct("HttpResponse".to_string(), fields))
+        Self::validate_http_url(url)?;
+        Ok(Self::http_response(Client::new().delete(url).send()))
+    }
+    
+    /// Removes duplicates keeping the first occurrence of each value.
+    /// ChifValue is not Hash + Eq (because of f64), so this is O(n²) over values_equal.
+    fn unique_values(&self, values: &[ChifValue]) -> Vec<ChifValue> {
+        let mut result: Vec<ChifValue> = Vec::new();
+        for value in values {
+            if !result.iter().any(|seen| self.values_equal(seen, value)) {
+                result.push(value.clone());
             }
         }
+        result
     }
     
+    /// Ссылки не раскрываются, а значения владеют своим содержимым,
+    /// поэтому рекурсия по кортежам конечна и на циклических структурах
     fn values_equal(&self, left: &ChifValue, right: &ChifValue) -> bool {
         match (left, right) {
             (ChifValue::Int(l), ChifValue::Int(r)) => l == r,
@@ -1375,69 +2566,87 @@ impl Interpreter {
             (ChifValue::Str(l), ChifValue::Str(r)) => l == r,
             (ChifValue::Bool(l), ChifValue::Bool(r)) => l == r,
             (ChifValue::Nil, ChifValue::Nil) => true,
+            (ChifValue::Tuple(l), ChifValue::Tuple(r)) => {
+                l.len() == r.len() && l.iter().zip(r).all(|(a, b)| self.values_equal(a, b))
+            }
             _ => false,
         }
     }
     
-    fn call_function_with_references(&mut self, func: &Function, args: Vec<ChifValue>, arg_exprs: &[Expression]) -> Result<ChifValue> {
+    fn call_function_with_references(&mut self, func: &Function, args: Vec<ChifValue>) -> Result<ChifValue> {
         if args.len() != func.params.len() {
-            return Err(ChifError::RuntimeError {
-                message: format!(
-                    "Function '{}' expects {} arguments, got {}",
-                    func.name,
-                    func.params.len(),
-                    args.len()
-                ),
+            return Err(ChifError::ArityError {
+                callee: func.name.clone(),
+                expected: func.params.len(),
+                got: args.len(),
             });
         }
         
-        // Track which parameters are references to variables
-        let mut var_refs = Vec::new();
-        for (i, arg_expr) in arg_exprs.iter().enumerate() {
-            if let Expression::Reference(ref inner) = arg_expr {
-                if let Expression::Identifier(var_name) = &**inner {
-                    var_refs.push((i, var_name.clone()));
-                }
-            }
-        }
-        
         // Create new scope
         let mut scope = HashMap::new();
+        // (parameter, variable of the caller, copy of the variable in the new scope)
+        let mut var_refs = Vec::new();
         
         // Bind parameters
-        for (param, arg) in func.params.iter().zip(args.iter()) {
-            scope.insert(param.name.clone(), arg.clone());
+        for (param, arg) in func.params.iter().zip(args) {
+            let arg = match arg {
+                // `&mut x`: функция работает с копией x под именем, которое не
+                // совпадёт с её собственными переменными (даже с параметром `x`);
+                // после вызова копия записывается обратно в x
+                ChifValue::Reference(var_name) if param.is_reference => {
+                    let copy = format!("&{}#{}", var_name, self.locals.len());
+                    scope.insert(copy.clone(), self.get_variable(&var_name)?);
+                    var_refs.push((param.name.clone(), var_name, copy.clone()));
+                    ChifValue::Reference(copy)
+                }
+                // Через `&T` функция только читает: она получает копию значения,
+                // и запись через такую ссылку — ошибка
+                ChifValue::Reference(var_name) => ChifValue::Pointer(Box::new(self.get_variable(&var_name)?)),
+                arg => arg,
+            };
+            scope.insert(param.name.clone(), arg);
         }
         
-        self.locals.push(scope);
-        
-        let result = self.execute_block(&func.body);
-        
-        // Update referenced variables after function execution
-        let updates: Vec<(String, ChifValue)> = if let Some(local_scope) = self.locals.last() {
-            var_refs.iter().filter_map(|(param_idx, var_name)| {
-                func.params.get(*param_idx).and_then(|param| {
-                    local_scope.get(&param.name).map(|updated_value| {
-                        (var_name.clone(), updated_value.clone())
-                    })
-                })
-            }).collect()
-        } else {
-            Vec::new()
-        };
-        
-        self.locals.pop();
+        let (result, local_scope) = self.execute_function_body(func, scope);
         
-        // Apply updates after popping the scope
-        for (var_name, updated_value) in updates {
-            self.set_variable(&var_name, updated_value)?;
+        // Параметр `ref`, которому присвоили новое значение, заменяет переменную целиком;
+        // иначе в неё возвращается копия, изменённая через `*p` и `p.field`
+        for (param_name, var_name, copy) in var_refs {
+            let updated_value = match local_scope.get(&param_name) {
+                Some(ChifValue::Reference(target)) if *target == copy => local_scope.get(&copy),
+                reassigned => reassigned,
+            };
+            if let Some(updated_value) = updated_value {
+                self.set_referenced_variable(&var_name, updated_value.clone())?;
+            }
         }
         
         match result {
-            Ok(_) => Ok(ChifValue::Nil),
+            Ok(_) => Ok(ChifValue::Void),
             Err(ChifError::Return(value)) => Ok(value),
-            Err(e) => Err(e),
+            Err(e) => Err(Self::loop_signal_outside_loop(e)),
+        }
+    }
+    
+    fn call_associated_function(&mut self, struct_name: &str, method_name: &str, args: &[Expression]) -> Result<ChifValue> {
+        let method = self.struct_methods.get(struct_name)
+            .and_then(|methods| methods.iter().find(|method| method.name == method_name))
+            .cloned()
+            .ok_or_else(|| ChifError::FunctionNotFound {
+                name: format!("{}.{}", struct_name, method_name),
+            })?;
+        
+        if method.params.iter().any(|param| param.name == "self") {
+            return Err(ChifError::RuntimeError {
+                message: format!("Method '{}' takes self and must be called on a value of type '{}'", method_name, struct_name),
+            });
+        }
+        
+        let mut values = Vec::new();
+        for arg_expr in args {
+            values.push(self.evaluate_expression(arg_expr)?);
         }
+        self.call_function(&method, values)
     }
     
     fn call_mutable_struct_method(&mut self, var_name: &str, method_name: &str, args: &[Expression]) -> Result<ChifValue> {
@@ -1457,7 +2666,8 @@ impl Interpreter {
                         // Передаем первый аргумент (self) как ссылку
                         method_args.push(ChifValue::Reference(var_name.to_string()));
                         
-                        // Добавляем остальные аргументы
+                        // Остальные аргументы — до тела метода: они видят self
+                        // до изменения, как аргументы `xs.add`
                         for arg_expr in args {
                             method_args.push(self.evaluate_expression(arg_expr)?);
                         }
@@ -1470,85 +2680,114 @@ impl Interpreter {
             }
         }
         
-        Err(ChifError::RuntimeError {
-            message: format!("Method '{}' not found for struct", method_name),
-        })
+        Err(Self::method_not_found(&object.get_type().to_string(), method_name))
     }
     
     fn call_mutable_method(&mut self, var_name: &str, method_name: &str, args: &[Expression]) -> Result<ChifValue> {
-        let mut object = self.get_variable(var_name)?;
+        // Сначала все аргументы, потом изменение: аргументы видят список таким,
+        // каким он был до вызова, а изменение применяется к тому, что лежит в
+        // переменной после них. Так `xs.add(xs[0])` добавляет старый первый
+        // элемент, а `xs.add(take(&mut xs))` не теряет изменений из `take`.
+        // В компилируемом режиме этих методов пока нет (ir_gen отклоняет их
+        // с UnsupportedFeature): когда они появятся, порядок должен быть тем же
+        let values = self.mutable_method_arguments(method_name, args)?;
+
+        let mut var_name = var_name.to_string();
+        let mut object = self.get_variable(&var_name)?;
+        // Параметр `&mut xs` меняет копию списка вызывающего, как поля через `p.x`
+        match object {
+            ChifValue::Reference(target) => {
+                object = self.get_variable(&target)?;
+                var_name = target;
+            }
+            ChifValue::Pointer(_) => return Err(Self::read_only_reference_error()),
+            _ => {}
+        }
+        self.count_copy(&object);
+        self.mutate_list(&mut object, method_name, values)?;
+        self.assign_variable(&var_name, object)?;
+        Ok(ChifValue::Void)
+    }
+    
+    /// `m["a"].add(3)`, `xs[0].del(1)`, `p.items.add(x)`: список берётся из
+    /// элемента или поля и после изменения записывается туда же, как при `m["a"] = ...`.
+    /// Как и для переменной, список читается после аргументов
+    fn call_mutable_place_method(&mut self, place: &Expression, method_name: &str, args: &[Expression]) -> Result<ChifValue> {
+        let values = self.mutable_method_arguments(method_name, args)?;
+        let mut object = self.evaluate_expression(place)?;
+        self.mutate_list(&mut object, method_name, values)?;
+        self.assign_to_place(place, object)?;
+        Ok(ChifValue::Void)
+    }
+    
+    fn mutable_method_arguments(&mut self, method_name: &str, args: &[Expression]) -> Result<Vec<ChifValue>> {
+        match method_name {
+            "add" | "del" => Self::check_arity(method_name, args, 1)?,
+            "addAt" => Self::check_arity(method_name, args, 2)?,
+            _ => {}
+        }
+        args.iter().map(|arg| self.evaluate_expression(arg)).collect()
+    }
+    
+    /// Применяет `add`, `addAt` или `del` к списку `object` на месте
+    fn mutate_list(&mut self, object: &mut ChifValue, method_name: &str, values: Vec<ChifValue>) -> Result<()> {
+        let mut values = values.into_iter();
         
-        match &mut object {
+        match object {
             ChifValue::List(list) => {
                 match method_name {
                     "add" => {
-                        if args.len() != 1 {
-                            return Err(ChifError::RuntimeError {
-                                message: "add method expects 1 argument".to_string(),
-                            });
+                        let value = values.next().unwrap();
+                        if let Some(profiler) = &mut self.profiler {
+                            if list.len() == list.capacity() {
+                                profiler.list_grown();
+                            }
                         }
-                        let value = self.evaluate_expression(&args[0])?;
                         list.push(value);
-                        self.set_variable(var_name, object)?;
-                        Ok(ChifValue::Nil)
+                        Ok(())
                     }
                     "addAt" => {
-                        if args.len() != 2 {
-                            return Err(ChifError::RuntimeError {
-                                message: "addAt method expects 2 arguments".to_string(),
-                            });
-                        }
-                        let value = self.evaluate_expression(&args[0])?;
-                        let index = self.evaluate_expression(&args[1])?;
+                        let value = values.next().unwrap();
+                        let index = values.next().unwrap();
                         
                         if let ChifValue::Int(idx) = index {
                             if idx >= 0 && (idx as usize) <= list.len() {
+                                if let Some(profiler) = &mut self.profiler {
+                                    if list.len() == list.capacity() {
+                                        profiler.list_grown();
+                                    }
+                                }
                                 list.insert(idx as usize, value);
-                                self.set_variable(var_name, object)?;
-                                Ok(ChifValue::Nil)
+                                Ok(())
                             } else {
                                 Err(ChifError::RuntimeError {
                                     message: format!("Index {} out of bounds for list of length {}", idx, list.len()),
                                 })
                             }
                         } else {
-                            Err(ChifError::RuntimeError {
-                                message: "addAt index must be an integer".to_string(),
-                            })
+                            Err(Self::type_error("int", &index, "addAt() index"))
                         }
                     }
                     "del" => {
-                        if args.len() != 1 {
-                            return Err(ChifError::RuntimeError {
-                                message: "del method expects 1 argument".to_string(),
-                            });
-                        }
-                        let index = self.evaluate_expression(&args[0])?;
+                        let index = values.next().unwrap();
                         
                         if let ChifValue::Int(idx) = index {
                             if idx >= 0 && (idx as usize) < list.len() {
                                 list.remove(idx as usize);
-                                self.set_variable(var_name, object)?;
-                                Ok(ChifValue::Nil)
+                                Ok(())
                             } else {
                                 Err(ChifError::RuntimeError {
                                     message: format!("Index {} out of bounds for list of length {}", idx, list.len()),
                                 })
                             }
                         } else {
-                            Err(ChifError::RuntimeError {
-                                message: "del index must be an integer".to_string(),
-                            })
+                            Err(Self::type_error("int", &index, "del() index"))
                         }
                     }
-                    _ => Err(ChifError::RuntimeError {
-                        message: format!("Unknown mutable method '{}' for list", method_name),
-                    }),
+                    _ => Err(Self::method_not_found("list", method_name)),
                 }
             }
-            _ => Err(ChifError::RuntimeError {
-                message: format!("Method '{}' not supported for this type", method_name),
-            }),
+            other => Err(Self::method_not_found(&other.get_type().to_string(), method_name)),
         }
     }
     
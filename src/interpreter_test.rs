@@ -0,0 +1,707 @@
+#[cfg(test)]
+mod tests {
+    use crate::ast::{Expression, Program, Statement, VarDecl};
+    use crate::error::ChifError;
+    use crate::interpreter::Interpreter;
+    use crate::lexer::Lexer;
+    use crate::parser::Parser;
+    use crate::types::ChifValue;
+
+    fn parse(source: &str) -> Program {
+        let tokens = Lexer::new(source).tokenize().expect("source should tokenize");
+        Parser::new(tokens).parse().expect("source should parse")
+    }
+
+    /// Runs `make()` from a program declaring `struct Point { x: int, y: int }`
+    fn make_point(literal: &str) -> Result<ChifValue, ChifError> {
+        let source = format!(
+            "struct Point {{ x: int, y: int, }}\nfn make() Point {{ ret {}; }}\nchif main() {{ }}",
+            literal
+        );
+        let mut interpreter = Interpreter::new();
+        interpreter.load(&parse(&source))?;
+        interpreter.call_function_by_name("make")
+    }
+
+    fn runtime_message(result: Result<ChifValue, ChifError>) -> String {
+        match result {
+            Err(ChifError::RuntimeError { message }) => message,
+            other => panic!("expected a runtime error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_struct_literal_fields_follow_declaration_order() {
+        let value = make_point("Point { y = 2, x = 1 }").expect("valid literal should evaluate");
+        assert_eq!(value.to_string(), "Point { x: 1, y: 2 }");
+    }
+
+    #[test]
+    fn test_struct_literal_rejects_unknown_field() {
+        match make_point("Point { x = 1, y = 2, z = 3 }") {
+            Err(ChifError::FieldNotFound { struct_name, field, available }) => {
+                assert_eq!((struct_name.as_str(), field.as_str()), ("Point", "z"));
+                assert_eq!(available, ["x", "y"]);
+            }
+            other => panic!("expected a missing field error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_struct_literal_rejects_missing_field() {
+        let message = runtime_message(make_point("Point { x = 1 }"));
+        assert!(message.contains("Point") && message.contains("'y'"), "{}", message);
+    }
+
+    #[test]
+    fn test_struct_literal_rejects_duplicate_field() {
+        let message = runtime_message(make_point("Point { x = 1, x = 2, y = 3 }"));
+        assert!(message.contains("more than once"), "{}", message);
+    }
+
+    #[test]
+    fn test_struct_literal_base_must_be_the_same_struct() {
+        let message = match make_point("Point { x = 1, ..5 }") {
+            Err(error) => error.to_string(),
+            Ok(value) => panic!("expected a type error, got {:?}", value),
+        };
+        assert!(message.contains("base of struct literal"), "{}", message);
+    }
+
+    #[test]
+    fn test_unknown_field_access_lists_available_fields() {
+        let mut interpreter = Interpreter::new();
+        interpreter.load(&parse(r#"
+            struct Point { x: int, y: int, }
+            fn read() int {
+                let p: Point = Point { x = 1, y = 2 };
+                ret p.z;
+            }
+            chif main() { }
+        "#)).expect("program should load");
+
+        let error = interpreter.call_function_by_name("read").expect_err("p.z should fail");
+        assert!(matches!(&error, ChifError::FieldNotFound { field, .. } if field == "z"), "{:?}", error);
+        assert!(error.to_string().contains("available: x, y"), "{}", error);
+    }
+
+    #[test]
+    fn test_chars_bytes_and_join() {
+        let mut interpreter = Interpreter::new();
+        interpreter.load(&parse(r#"
+            fn spaced() str {
+                list letters: str[] = "añb".chars();
+                ret letters.join("-");
+            }
+            fn byte_count() int {
+                list bytes: int[] = "añb".bytes();
+                ret bytes.len() * 1000 + bytes[1];
+            }
+            fn join_ints() str {
+                list numbers: int[] = [1, 2];
+                ret numbers.join(",");
+            }
+            chif main() { }
+        "#)).expect("program should load");
+
+        assert_eq!(interpreter.call_function_by_name("spaced").unwrap().to_string(), "a-ñ-b");
+        // ñ is two bytes in UTF-8, the first one is 0xC3
+        assert_eq!(interpreter.call_function_by_name("byte_count").unwrap().to_string(), "4195");
+        match interpreter.call_function_by_name("join_ints") {
+            Err(ChifError::TypeError { expected, found, context }) => {
+                assert_eq!((expected.as_str(), found.as_str()), ("str", "int"));
+                assert!(context.contains("index 0"), "{}", context);
+            }
+            other => panic!("expected a type error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_seeded_random_sequence_repeats() {
+        let mut interpreter = Interpreter::new();
+        interpreter.load(&parse(r#"
+            fn sequence(seed: int) str {
+                rand_seed(seed);
+                var out: str = "";
+                for (var i: int = 0; i < 8; i = i + 1) {
+                    out = out + toStr(randi(1, 1000000)) + " " + rands("a", "z") + ",";
+                }
+                ret out;
+            }
+            fn first() str { ret sequence(42); }
+            fn again() str { ret sequence(42); }
+            fn other() str { ret sequence(7); }
+            fn unit_range() bool {
+                rand_seed(1);
+                for (var i: int = 0; i < 10000; i = i + 1) {
+                    var r: float = rando();
+                    if (r < 0.0 || r >= 1.0) {
+                        ret false;
+                    }
+                }
+                ret true;
+            }
+            chif main() { }
+        "#)).expect("program should load");
+
+        let first = interpreter.call_function_by_name("first").unwrap().to_string();
+        assert_eq!(interpreter.call_function_by_name("again").unwrap().to_string(), first);
+        assert_ne!(interpreter.call_function_by_name("other").unwrap().to_string(), first);
+        assert!(matches!(interpreter.call_function_by_name("unit_range"), Ok(ChifValue::Bool(true))));
+    }
+
+    #[test]
+    fn test_unique_keeps_first_occurrences_in_order() {
+        let mut interpreter = Interpreter::new();
+        interpreter.load(&parse(r#"
+            fn distinct() list[int] {
+                ret [3, 1, 2, 1, 3].unique();
+            }
+            fn words() list[str] {
+                list all: str[] = ["b", "a", "b", "c", "a"];
+                ret all.unique();
+            }
+            fn empty() list[int] {
+                list none: int[] = [];
+                ret none.unique();
+            }
+            chif main() { }
+        "#)).expect("program should load");
+
+        assert_eq!(interpreter.call_function_by_name("distinct").unwrap().to_string(), "[3, 1, 2]");
+        assert_eq!(interpreter.call_function_by_name("words").unwrap().to_string(), "[b, a, c]");
+        assert_eq!(interpreter.call_function_by_name("empty").unwrap().to_string(), "[]");
+    }
+
+    #[test]
+    fn test_runtime_errors_have_specific_variants() {
+        let mut interpreter = Interpreter::new();
+        interpreter.load(&parse(r#"
+            fn add(a: int, b: int) int {
+                ret a + b;
+            }
+            fn wrong_arity() int {
+                ret add(1);
+            }
+            fn divide() int {
+                var zero: int = 0;
+                ret 10 % zero;
+            }
+            fn convert() int {
+                ret toInt("12a");
+            }
+            fn unknown_method() str {
+                ret "text".shout();
+            }
+            fn seed() {
+                rand_seed("abc");
+            }
+            chif main() { }
+        "#)).expect("program should load");
+
+        match interpreter.call_function_by_name("wrong_arity") {
+            Err(ChifError::ArityError { callee, expected: 2, got: 1 }) => assert_eq!(callee, "add"),
+            other => panic!("expected an arity error, got {:?}", other),
+        }
+        assert!(matches!(interpreter.call_function_by_name("divide"), Err(ChifError::DivisionByZero)));
+        match interpreter.call_function_by_name("convert") {
+            Err(error @ ChifError::InvalidConversion { .. }) => {
+                assert_eq!(error.to_string(), "Cannot convert str '12a' to int");
+            }
+            other => panic!("expected an invalid conversion, got {:?}", other),
+        }
+        match interpreter.call_function_by_name("unknown_method") {
+            Err(ChifError::MethodNotFound { receiver_type, method }) => {
+                assert_eq!((receiver_type.as_str(), method.as_str()), ("str", "shout"));
+            }
+            other => panic!("expected a missing method error, got {:?}", other),
+        }
+        match interpreter.call_function_by_name("seed") {
+            Err(error @ ChifError::TypeError { .. }) => {
+                assert_eq!(error.to_string(), "Type error in rand_seed() seed: expected int, found str");
+            }
+            other => panic!("expected a type error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_return_from_nested_loops_keeps_scopes_balanced() {
+        let program = parse(r#"
+            fn find(target: int) int {
+                for (var i: int = 0; i < 4; i = i + 1) {
+                    for j in range(0, 4) {
+                        while (true) {
+                            if (i * 4 + j == target) {
+                                ret i * 10 + j;
+                            }
+                            break;
+                        }
+                    }
+                }
+                ret -1;
+            }
+
+            fn divide_down(n: int) int {
+                for (var i: int = 0; i < 3; i = i + 1) {
+                    for k in range(0, 2) {
+                        var q: int = 10 / (n - i);
+                    }
+                }
+                ret n;
+            }
+
+            fn run() int {
+                var i: int = 7;
+                var found: int = find(6);
+                ret i * 100 + found;
+            }
+
+            fn broken() int {
+                ret divide_down(1);
+            }
+
+            chif main() { }
+        "#);
+        let mut interpreter = Interpreter::new();
+        interpreter.load(&program).expect("program should load");
+
+        // Одна сессия интерпретатора: ни ret, ни ошибка внутри циклов не оставляют лишних областей
+        for _ in 0..2 {
+            let value = interpreter.call_function_by_name("run").expect("run should return");
+            assert_eq!(value.to_string(), "712");
+        }
+        assert!(matches!(interpreter.call_function_by_name("broken"), Err(ChifError::DivisionByZero)));
+        let value = interpreter.call_function_by_name("run").expect("run should return after an error");
+        assert_eq!(value.to_string(), "712");
+    }
+
+    #[test]
+    fn test_unknown_variable_suggests_a_visible_name() {
+        let declarations: String = (0..20).map(|n| format!("var v{}: int = {};\n", n, n)).collect();
+        let mut interpreter = Interpreter::new();
+        interpreter.load(&parse(&format!(r#"
+            fn total_of() int {{
+                let count: int = 3;
+                var total: int = 0;
+                for i in range(0, count) {{
+                    total = totl + i;
+                }}
+                ret total;
+            }}
+
+            fn crowded() int {{
+                {}
+                ret missing;
+            }}
+
+            chif main() {{ }}
+        "#, declarations))).expect("program should load");
+
+        let error = interpreter.call_function_by_name("total_of").expect_err("totl should fail");
+        match &error {
+            ChifError::VariableNotFound { name, function, suggestion, in_scope, omitted } => {
+                assert_eq!(name, "totl");
+                assert_eq!(function.as_deref(), Some("total_of"));
+                assert_eq!(suggestion.as_deref(), Some("total"));
+                // Innermost scope first: the loop variable, then the function's variables
+                assert_eq!(in_scope, &["i", "count", "total"]);
+                assert_eq!(*omitted, 0);
+            }
+            other => panic!("expected VariableNotFound, got {:?}", other),
+        }
+        assert_eq!(
+            error.to_string(),
+            "Variable 'totl' not found in function 'total_of'; did you mean 'total'? (in scope: i, count, total)"
+        );
+
+        // A crowded scope is listed only up to a fixed number of names
+        let error = interpreter.call_function_by_name("crowded").expect_err("missing should fail");
+        match &error {
+            ChifError::VariableNotFound { suggestion, in_scope, omitted, .. } => {
+                assert_eq!(*suggestion, None);
+                assert_eq!(in_scope.len(), 8);
+                assert_eq!(*omitted, 12);
+            }
+            other => panic!("expected VariableNotFound, got {:?}", other),
+        }
+        assert!(error.to_string().ends_with(" and 12 more)"), "{}", error);
+    }
+
+    #[test]
+    fn test_interned_and_plain_keys_address_the_same_map_entry() {
+        let program = parse(r#"
+            fn tally() int {
+                var counts: map[str:int] = {"apple": 1, "pear": 2};
+                var key: str = intern("apple");
+                counts[key] = counts[key] + 10;
+                counts["app" + "le"] = counts["apple"] + 100;
+                counts[intern("plum")] = counts["pear"] * 2;
+                if (intern("plum") != "plum") {
+                    ret 0;
+                }
+                ret counts["apple"] + counts[intern("pear")] * 1000 + counts["plum"] * 10000;
+            }
+
+            chif main() { }
+        "#);
+        let mut interpreter = Interpreter::new();
+        interpreter.load(&program).expect("program should load");
+        let value = interpreter.call_function_by_name("tally").expect("tally should run");
+        assert!(matches!(value, ChifValue::Int(42111)), "{:?}", value);
+    }
+
+    /// Runs `main` with con.out captured and returns the printed lines
+    fn run_captured(source: &str) -> Vec<String> {
+        let mut interpreter = Interpreter::new();
+        interpreter.capture_output();
+        interpreter.execute(&parse(source)).expect("program should run");
+        interpreter.take_output()
+    }
+
+    #[test]
+    fn test_tuple_returns_destructure_in_order() {
+        let output = run_captured(r#"
+            fn divmod(a: int, b: int) (int, int) {
+                ret (a / b, a % b);
+            }
+            fn min_max(a: int, b: int) (int, int) {
+                if (a < b) {
+                    ret (a, b);
+                }
+                ret (b, a);
+            }
+            chif main() {
+                var (q, r) = divmod(7, 2);
+                let (low, high): (int, int) = min_max(9, 4);
+                let pair: (int, int) = min_max(1, 5);
+                con.out(q);
+                con.out(r);
+                con.out(low);
+                con.out(high);
+                con.out(pair.0);
+                con.out(pair.1);
+            }
+        "#);
+        assert_eq!(output, ["3", "1", "4", "9", "1", "5"]);
+    }
+
+    #[test]
+    fn test_string_index_of_counts_characters() {
+        // "é" takes two bytes, so byte and character positions differ after it
+        let output = run_captured(r#"
+            chif main() {
+                let word: str = "héllo";
+                con.out(word.index_of("l"));
+                con.out(word.index_of("z"));
+                con.out(word.char_count());
+                con.out(word.byte_len());
+            }
+        "#);
+        assert_eq!(output, ["2", "-1", "5", "6"]);
+    }
+
+    #[test]
+    fn test_int_radix_string_methods_format_without_prefix() {
+        let output = run_captured(r#"
+            chif main() {
+                let negative: int = 0 - 1;
+                con.out(255.to_hex_str());
+                con.out(48879.to_hex_str_lower());
+                con.out(255.to_binary_str());
+                con.out(negative.to_hex_str());
+            }
+        "#);
+        assert_eq!(output, ["FF", "beef", "11111111", "-1"]);
+    }
+
+    #[test]
+    fn test_list_add_then_index() {
+        for decl in ["var xs: list[int] = [];", "list xs: int[] = [];", "var xs: list = [1];"] {
+            let source = format!("chif main() {{\n    {}\n    xs.add(5);\n    xs.add(7);\n    con.out(xs[xs.len() - 1]);\n    con.out(xs.len());\n}}\n", decl);
+            let expected_len = if decl.contains("[1]") { "3" } else { "2" };
+            assert_eq!(run_captured(&source), ["7", expected_len], "{}", decl);
+        }
+    }
+
+    #[test]
+    fn test_printing_a_reference_cycle_terminates_with_a_marker() {
+        let output = run_captured(r#"
+            struct Node { name: str, next: pointer[Node], }
+
+            chif main() {
+                var a: Node = Node { name = "a", next = nil };
+                var b: Node = Node { name = "b", next = nil };
+                b.next = &a;
+                a.next = &b;
+                con.out(a);
+                con.out("b = {b}");
+            }
+        "#);
+        assert_eq!(output, vec![
+            "Node { name: a, next: &Node { name: b, next: &Node { name: a, next: <cycle> } } }".to_string(),
+            "b = Node { name: b, next: &Node { name: a, next: &Node { name: b, next: <cycle> } } }".to_string(),
+        ]);
+    }
+
+    #[test]
+    fn test_printing_limits_depth_and_length() {
+        let output = run_captured(r#"
+            chif main() {
+                list nested: int[] = [[[[[[[[[[1]]]]]]]]]];
+                con.out(nested);
+                list big: int[] = [];
+                for (var i: int = 0; i < 5000; i = i + 1) {
+                    big.add(i);
+                }
+                con.out(big);
+            }
+        "#);
+        assert_eq!(output[0], "[[[[[[[[...]]]]]]]]");
+        assert!(output[1].starts_with("[0, 1, 2, "), "{}", &output[1][..20]);
+        assert!(output[1].ends_with("..."));
+        assert_eq!(output[1].chars().count(), crate::types::MAX_DISPLAY_CHARS + 3);
+    }
+    
+    #[test]
+    fn test_definitions_after_their_uses_resolve() {
+        let output = run_captured(r#"
+            chif main() {
+                var c: Counter = Counter { n = 5 };
+                con.out(later(1));
+                con.out(c.twice());
+                con.out(c.shifted());
+            }
+            
+            fn_for Counter {
+                fn twice(self) int {
+                    ret self.plus(self.n);
+                }
+                fn shifted(self) int {
+                    ret offset() + self.n;
+                }
+                fn plus(self, k: int) int {
+                    ret self.n + k;
+                }
+            }
+            
+            fn later(x: int) int {
+                ret x + even_later();
+            }
+            
+            fn even_later() int {
+                ret 10;
+            }
+            
+            fn offset() int {
+                ret 100;
+            }
+            
+            struct Counter { n: int, }
+        "#);
+        assert_eq!(output, vec!["11", "10", "105"]);
+    }
+    
+    #[test]
+    fn test_import_order_does_not_matter() {
+        let dir = tempfile::tempdir().expect("temp dir should be created");
+        // Модуль использует свой импорт и свои функции до их определения
+        std::fs::write(dir.path().join("shapes.rono"), r#"
+            export fn area(w: int, h: int) int {
+                ret scale(w) * h;
+            }
+            
+            fn scale(x: int) int {
+                ret x * units.factor();
+            }
+        "#.to_string() + &format!("import \"{}/units\";", dir.path().display())).expect("module should be written");
+        std::fs::write(dir.path().join("units.rono"), r#"
+            fn factor() int {
+                ret one() + 1;
+            }
+            
+            fn one() int {
+                ret 1;
+            }
+        "#).expect("module should be written");
+        
+        // Импорт после функций: своя area файла не заменяется одноимённой из модуля
+        let output = run_captured(&format!(r#"
+            chif main() {{
+                con.out(shapes.area(3, 2));
+                con.out(area(3, 2));
+            }}
+            
+            fn area(w: int, h: int) int {{
+                ret -w * h;
+            }}
+            
+            import "{}/shapes";
+        "#, dir.path().display()));
+        assert_eq!(output, vec!["12", "-6"]);
+    }
+
+    #[test]
+    fn test_int_expressions_are_marked_for_the_fast_path() {
+        let program = parse(r#"
+            fn f(n: int, scale: float, ref out: int) int {
+                var k: int = n * 2 + 1;
+                var ok: bool = k < n && n > 0;
+                var s: float = scale * 2.0;
+                var m: int = out + 1;
+                ret -k % 3;
+            }
+        "#);
+        let mut func = match &program.items[0] {
+            crate::ast::Item::Function(func) => func.clone(),
+            other => panic!("expected a function, got {:?}", other),
+        };
+        func.mark_int_expressions();
+        
+        let marks: Vec<bool> = func.body.statements.iter().map(|statement| match statement {
+            Statement::VarDecl(VarDecl { value: Some(Expression::Binary(binary_op)), .. })
+            | Statement::Return(Some(Expression::Binary(binary_op))) => binary_op.int_only,
+            other => panic!("unexpected statement {:?}", other),
+        }).collect();
+        // `&&` не считается на i64, но его операнды-сравнения — да; `out` — ссылка
+        assert_eq!(marks, vec![true, false, false, false, true]);
+        match &func.body.statements[1] {
+            Statement::VarDecl(VarDecl { value: Some(Expression::Binary(and)), .. }) => {
+                assert!(matches!(&*and.left, Expression::Binary(left) if left.int_only));
+                assert!(matches!(&*and.right, Expression::Binary(right) if right.int_only));
+            }
+            other => panic!("unexpected statement {:?}", other),
+        }
+    }
+    
+    #[test]
+    fn test_int_fast_path_falls_back_when_a_value_is_not_an_int() {
+        // Без анализатора `int` в объявлении не проверяется, и x хранит float;
+        // отмеченное выражение тогда считается обычным путём
+        let program = parse("fn f() float { var x: int = 1.5; ret x + x; }");
+        let mut interpreter = Interpreter::new();
+        interpreter.load(&program).expect("program should load");
+        assert_eq!(interpreter.call_function_by_name("f").expect("f should run").to_string(), "3.0");
+    }
+    
+    #[test]
+    fn test_int_overflow_is_a_runtime_error_on_both_paths() {
+        // `x + 1` и `-x + 0` идут быстрым путём, `top() + 1` и `-(...)` — обычным
+        let program = parse(r#"
+            fn top() int { ret 9223372036854775807; }
+            fn fast_add() int { var x: int = 9223372036854775807; ret x + 1; }
+            fn slow_add() int { ret top() + 1; }
+            fn fast_sub() int { var x: int = -9223372036854775807; ret x - 2; }
+            fn slow_mul() int { ret top() * 2; }
+            fn fast_neg() int { var x: int = -9223372036854775807 - 1; ret -x + 0; }
+            fn slow_neg() int { ret -(0 - top() - 1); }
+            fn fits() int { ret 9223372036854775806 + 1; }
+        "#);
+        let mut interpreter = Interpreter::new();
+        interpreter.load(&program).expect("program should load");
+        let cases = [
+            ("fast_add", "9223372036854775807 + 1"),
+            ("slow_add", "9223372036854775807 + 1"),
+            ("fast_sub", "-9223372036854775807 - 2"),
+            ("slow_mul", "9223372036854775807 * 2"),
+            ("fast_neg", "-(-9223372036854775808)"),
+            ("slow_neg", "-(-9223372036854775808)"),
+        ];
+        for (function, operation) in cases {
+            match interpreter.call_function_by_name(function) {
+                Err(error) => assert_eq!(error.to_string(), format!("Integer overflow in {}", operation), "{}", function),
+                Ok(value) => panic!("{} should overflow, got {}", function, value),
+            }
+        }
+        assert_eq!(interpreter.call_function_by_name("fits").expect("fits should run").to_string(), "9223372036854775807");
+    }
+    
+    #[test]
+    fn test_prelude_is_loaded_unless_disabled() {
+        let program = parse("fn width() int { ret clamp(42, 0, 10); }");
+        let mut interpreter = Interpreter::new();
+        interpreter.load(&program).expect("program should load");
+        assert_eq!(interpreter.call_function_by_name("width").expect("width should run").to_string(), "10");
+        
+        let mut interpreter = Interpreter::new();
+        interpreter.set_prelude(false);
+        interpreter.load(&program).expect("program should load");
+        assert!(interpreter.call_function_by_name("width").is_err(), "clamp should be undefined without the prelude");
+    }
+
+    #[test]
+    fn test_platform_builtins_report_the_host() {
+        let source = r#"
+            fn platform() str {
+                ret os.name() + "/" + os.arch() + " " + rono.version();
+            }
+        "#;
+        let mut interpreter = Interpreter::new();
+        interpreter.load(&parse(source)).expect("program should load");
+        let expected = format!("{}/{} {}", std::env::consts::OS, std::env::consts::ARCH, env!("CARGO_PKG_VERSION"));
+        assert!(matches!(interpreter.call_function_by_name("platform"), Ok(ChifValue::Str(platform)) if platform == expected));
+
+        let error = crate::analyze_source("chif main() {\n    con.out(os.kernel());\n}\n").expect_err("os has no kernel()");
+        assert!(error.to_string().contains("os has no method 'kernel'; available: name, arch"), "{}", error);
+    }
+
+    #[test]
+    fn test_calls_without_a_value_cannot_be_used_as_values() {
+        let source = r#"
+            fn log(message: str) {
+                con.out(message);
+            }
+            fn early(flag: bool) {
+                if (flag) {
+                    ret;
+                }
+                con.out("late");
+            }
+            fn statements() int {
+                log("a");
+                early(true);
+                con.out("b");
+                ret 1;
+            }
+            fn stored() { var x: int = log("a"); }
+            fn operand() int { ret 1 + log("a"); }
+            fn argument() { log(early(true)); }
+            fn printed() { con.out(log("a")); }
+            fn returned() int { ret early(false); }
+        "#;
+        let program = parse(source);
+        let mut interpreter = Interpreter::new();
+        interpreter.capture_output();
+        interpreter.load(&program).expect("program should load");
+
+        // Without the analyzer: a missing value is discarded in statement position
+        assert!(matches!(interpreter.call_function_by_name("statements"), Ok(ChifValue::Int(1))));
+        assert_eq!(interpreter.take_output(), vec!["a", "b"]);
+        // Callers outside the interpreter see nil
+        interpreter.load(&parse("fn quiet() { }")).expect("program should load");
+        assert!(matches!(interpreter.call_function_by_name("quiet"), Ok(ChifValue::Nil)));
+
+        for (function, callee) in [("stored", "log"), ("operand", "log"), ("argument", "early"), ("printed", "log"), ("returned", "early")] {
+            let message = runtime_message(interpreter.call_function_by_name(function));
+            assert_eq!(message, format!("function '{}' has no return value and cannot be used in an expression", callee), "{}", function);
+        }
+
+        // A correct analyzed program behaves as before
+        let analyzed = r#"
+            fn log(message: str) {
+                con.out(message);
+            }
+            chif main() {
+                var items: list[int] = [1];
+                items.add(2);
+                log("{items}");
+                var missing: map[str:int] = {"a": 1};
+                con.out(missing["b"]);
+            }
+        "#;
+        crate::analyze_source(analyzed).expect("program should type-check");
+        let mut interpreter = Interpreter::new();
+        interpreter.capture_output();
+        interpreter.execute(&parse(analyzed)).expect("program should run");
+        assert_eq!(interpreter.take_output(), vec!["[1, 2]", "nil"]);
+    }
+}
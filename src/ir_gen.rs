@@ -1,11 +1,20 @@
 use crate::ast::*;
 use crate::semantic::AnalyzedProgram;
+use crate::lang::LangVersion;
+use crate::logger;
 use crate::types::{ChifType, ChifValue};
 
+use cranelift::codegen::control::ControlPlane;
+use cranelift::codegen::ir::{self, ExternalName, InstructionData, Opcode, UserExternalName, UserExternalNameRef};
+use cranelift::codegen::isa::TargetIsa;
 use cranelift::prelude::*;
-use cranelift_module::{Linkage, Module};
+use cranelift_module::{DataDescription, DataId, Linkage, Module, ModuleDeclarations};
 use cranelift_object::ObjectModule;
-use std::collections::HashMap;
+use std::borrow::Cow;
+use std::collections::{HashMap, HashSet};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
 use thiserror::Error;
 
 #[derive(Debug, Error)]
@@ -19,8 +28,19 @@ pub enum IRError {
     #[error("Unsupported feature: {0}")]
     UnsupportedFeature(String),
     
+    #[error("Signature mismatch: {0}")]
+    SignatureMismatch(String),
+    
+    // Boxed: `ModuleError` is several times larger than the other variants,
+    // and every generator returns this error
     #[error("Module error: {0}")]
-    Module(#[from] cranelift_module::ModuleError),
+    Module(Box<cranelift_module::ModuleError>),
+}
+
+impl From<cranelift_module::ModuleError> for IRError {
+    fn from(error: cranelift_module::ModuleError) -> Self {
+        IRError::Module(Box::new(error))
+    }
 }
 
 pub struct IRGenerator {
@@ -30,15 +50,232 @@ pub struct IRGenerator {
     
     // Symbol tables for IR generation
     pub functions: HashMap<String, cranelift_module::FuncId>,
+    pub function_return_types: HashMap<String, ChifType>,
     pub variables: HashMap<String, Variable>,
     pub current_function: Option<cranelift_module::FuncId>,
     pub string_constants: HashMap<String, cranelift_module::DataId>,
     
-    // Struct definitions for layout information
-    pub structs: HashMap<String, StructLayout>,
-    
     // Loop context for break/continue
     pub loop_stack: Vec<LoopContext>,
+    
+    // Size context, struct layouts and the state of the function being generated
+    pub context: CodegenContext,
+    
+    // Textual IR of each function, collected when capture_ir is set (debug builds)
+    pub capture_ir: bool,
+    pub function_ir: HashMap<String, String>,
+    
+    // Parsed imported modules by canonical path, and the (path, module name)
+    // pairs whose functions are already generated
+    pub module_cache: HashMap<PathBuf, Program>,
+    pub generated_modules: HashSet<(PathBuf, String)>,
+    // Functions of a module are generated once, prefixed with the name of its
+    // first import; other names of the same file refer to those functions.
+    // Every module name stands for one file, since all modules share the
+    // object's symbols, and `importing` holds the modules being imported
+    module_prefixes: HashMap<PathBuf, String>,
+    module_names: HashMap<String, PathBuf>,
+    importing: Vec<PathBuf>,
+    // Directory of the program's file, which its `./` and bare imports are
+    // relative to; a module's imports are relative to its own directory
+    pub source_dir: Option<PathBuf>,
+    // File of each generated user function and method, `None` for the main file.
+    // A second definition is reported by name here instead of reaching cranelift
+    // as a DuplicateDefinition of the mangled symbol
+    defined_in: HashMap<String, Option<PathBuf>>,
+    
+    // Entry point of the executable. Unless it is this file's `chif main`,
+    // `main` is generated as a wrapper that calls it
+    pub entry: Option<EntryPoint>,
+    
+    // Some function reads `args`, so main passes argc and argv to the runtime
+    uses_program_args: bool,
+    
+    // Threads generating function bodies and compiling them to machine code.
+    // Bodies are generated once every function is declared; imported ones
+    // wait in `imported_bodies` until then
+    pub jobs: usize,
+    imported_bodies: Vec<PendingBody<'static>>,
+}
+
+/// A function whose body is generated once every function is declared. Bodies
+/// are generated on `jobs` threads but added to the module in this order, so
+/// the object does not depend on which thread finished first
+struct PendingBody<'p> {
+    // An imported function is a copy: its calls to the module's own functions are renamed
+    func: Cow<'p, Function>,
+    name: String,
+    // Struct of a method: its `self: Self` and `Self` return get this type
+    current_struct: Option<String>,
+}
+
+/// A body generated and compiled to machine code, waiting to be added to the module
+struct GeneratedBody {
+    id: cranelift_module::FuncId,
+    name: String,
+    ctx: codegen::Context,
+    // The body's data object, reserved before the body was generated, and its bytes
+    data_id: DataId,
+    data: Vec<u8>,
+    warnings: Vec<String>,
+    // IR as generated, before optimization, when `capture_ir` is set
+    ir: Option<ir::Function>,
+}
+
+/// The module as the generators of one function body see it: the declared
+/// functions and the target. It only borrows the module, so several bodies are
+/// generated at once. Every body gets one read-only data object of its own,
+/// declared before the bodies are generated; the data the body defines (string
+/// constants, templates of large array literals) is laid out in it at offsets,
+/// and `define_bodies` defines the object when it adds the body
+pub struct BodyModule<'m> {
+    declarations: &'m ModuleDeclarations,
+    isa: &'m dyn TargetIsa,
+    data_id: DataId,
+    data: Vec<u8>,
+    // Name of the data object in the function, declared with the first data
+    data_name: Option<UserExternalNameRef>,
+}
+
+impl<'m> BodyModule<'m> {
+    fn new(declarations: &'m ModuleDeclarations, isa: &'m dyn TargetIsa, data_id: DataId) -> Self {
+        Self { declarations, isa, data_id, data: Vec::new(), data_name: None }
+    }
+    
+    pub fn isa(&self) -> &'m dyn TargetIsa {
+        self.isa
+    }
+    
+    pub fn declarations(&self) -> &'m ModuleDeclarations {
+        self.declarations
+    }
+    
+    /// `Module::declare_func_in_func` over the borrowed declarations
+    pub fn declare_func_in_func(&self, func_id: cranelift_module::FuncId, func: &mut ir::Function) -> ir::FuncRef {
+        let decl = self.declarations.get_function_decl(func_id);
+        let signature = func.import_signature(decl.signature.clone());
+        let user_name_ref = func.declare_imported_user_function(UserExternalName {
+            namespace: 0,
+            index: func_id.as_u32(),
+        });
+        func.import_function(ir::ExtFuncData {
+            name: ExternalName::user(user_name_ref),
+            signature,
+            colocated: decl.linkage.is_final(),
+        })
+    }
+    
+    /// Read-only `bytes` for the body, aligned to `align` within its data object:
+    /// their address as a global value of `func`
+    pub fn define_data_in_func(&mut self, bytes: &[u8], align: usize, func: &mut ir::Function) -> ir::GlobalValue {
+        let offset = self.data.len().next_multiple_of(align);
+        self.data.resize(offset, 0);
+        self.data.extend_from_slice(bytes);
+        // Namespace 1 is the module's data objects, as in `Module::declare_data_in_func`
+        let data_id = self.data_id;
+        let name = *self.data_name.get_or_insert_with(|| func.declare_imported_user_function(UserExternalName {
+            namespace: 1,
+            index: data_id.as_u32(),
+        }));
+        // Anonymous data is local to the object, like `declare_anonymous_data` makes it
+        func.create_global_value(ir::GlobalValueData::Symbol {
+            name: ExternalName::user(name),
+            offset: ir::immediates::Imm64::new(offset as i64),
+            colocated: true,
+            tls: false,
+        })
+    }
+}
+
+/// What every function body is generated against: the module, and the functions
+/// and layouts collected by the passes that declare them. The threads generating
+/// bodies share it and only read it
+struct BodyEnv<'g> {
+    declarations: &'g ModuleDeclarations,
+    isa: &'g dyn TargetIsa,
+    functions: &'g HashMap<String, cranelift_module::FuncId>,
+    function_return_types: &'g HashMap<String, ChifType>,
+    // Names of user functions by function id, for `verify_signatures`
+    user_functions: HashMap<u32, &'g str>,
+    context: &'g CodegenContext,
+    uses_program_args: bool,
+    capture_ir: bool,
+}
+
+/// What one thread reuses from body to body: the builder context, and its own
+/// copy of the context, whose per-function parts are reset for every body
+struct BodyWorker<'g> {
+    env: &'g BodyEnv<'g>,
+    builder_context: FunctionBuilderContext,
+    context: CodegenContext,
+}
+
+/// What the static generators know besides the builder and the module. They
+/// receive only this struct by `&mut`, so it carries:
+///
+/// - the size limits: array literals larger than `max_stack_literal_bytes` whose
+///   elements are all constants are emitted as read-only data instead of a stack
+///   slot, and functions whose stack slots add up to more than
+///   `stack_warning_bytes` get a warning;
+/// - what the declaring passes collected: struct layouts, the parameter lists of
+///   user functions (calls with named arguments are put into positional order as
+///   they are generated) and the language level;
+/// - the state of the function being generated: the Rono types of its variables
+///   (field access and arrays of structs need them to compute offsets, string
+///   operations to tell a str from an int), its struct return and the arrays and
+///   lists it treats specially. `BodyWorker::generate` resets it for every body.
+#[derive(Debug, Clone)]
+pub struct CodegenContext {
+    pub max_stack_literal_bytes: u32,
+    pub stack_warning_bytes: u32,
+    pub warnings: Vec<String>,
+    pub structs: HashMap<String, StructLayout>,
+    // Parameters of user functions, for resolving named arguments at each call
+    pub function_params: HashMap<String, Vec<Parameter>>,
+    // Declared Rono types of the current function's variables (Cranelift types
+    // alone cannot tell a str pointer from an int)
+    pub variable_types: HashMap<String, ChifType>,
+    // Variables of the current function declared as a struct or an array of structs
+    pub struct_variables: HashMap<String, ChifType>,
+    // Cranelift variables declared in the current function. A name can be declared
+    // again in another scope, so the size of the name map is not a fresh index
+    pub variable_count: usize,
+    // Language level of the program: from level 2 `http.*` returns HttpResponse
+    pub lang_version: LangVersion,
+    // Struct the current function returns: `ret` copies it out of the dying frame
+    pub return_struct: Option<String>,
+    // Array variables of the current function that are only indexed or measured:
+    // a large constant initializer of one of them can stay in read-only data
+    pub read_only_arrays: HashSet<String>,
+    // List variables of the current function holding a list the runtime built,
+    // whose length `rono_list_len` reads
+    pub runtime_lists: HashSet<String>,
+}
+
+impl CodegenContext {
+    fn next_variable(&mut self) -> Variable {
+        self.variable_count += 1;
+        Variable::new(self.variable_count - 1)
+    }
+}
+
+impl Default for CodegenContext {
+    fn default() -> Self {
+        Self {
+            max_stack_literal_bytes: 4 * 1024,
+            stack_warning_bytes: 64 * 1024,
+            warnings: Vec::new(),
+            structs: HashMap::new(),
+            function_params: HashMap::new(),
+            variable_types: HashMap::new(),
+            struct_variables: HashMap::new(),
+            variable_count: 0,
+            lang_version: LangVersion::default(),
+            return_struct: None,
+            read_only_arrays: HashSet::new(),
+            runtime_lists: HashSet::new(),
+        }
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -47,12 +284,76 @@ pub struct LoopContext {
     pub continue_block: cranelift::prelude::Block,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Clone)]
 pub struct StructLayout {
     pub name: String,
     pub fields: Vec<StructFieldLayout>,
     pub size: u32,
     pub alignment: u32,
+    // Position of each field in `fields`, so lookups by name don't scan wide structs
+    field_index: HashMap<String, usize>,
+}
+
+// The index repeats `fields`, so it is left out of `--dump` output
+impl std::fmt::Debug for StructLayout {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("StructLayout")
+            .field("name", &self.name)
+            .field("fields", &self.fields)
+            .field("size", &self.size)
+            .field("alignment", &self.alignment)
+            .finish()
+    }
+}
+
+impl StructLayout {
+    /// Calculates field offsets the same way compiled code lays out structs
+    pub fn compute(struct_def: &StructDef) -> Result<Self, IRError> {
+        let mut fields = Vec::new();
+        let mut field_index = HashMap::new();
+        let mut current_offset = 0u32;
+        let mut max_alignment = 1u32;
+        
+        for field in &struct_def.fields {
+            let field_size = IRGenerator::get_type_size(&field.field_type)?;
+            let field_alignment = IRGenerator::get_type_alignment(&field.field_type)?;
+            
+            // Update maximum alignment
+            max_alignment = max_alignment.max(field_alignment);
+            
+            // Align current offset to field alignment
+            current_offset = IRGenerator::align_to(current_offset, field_alignment);
+            
+            if field_index.insert(field.name.clone(), fields.len()).is_some() {
+                return Err(IRError::Generation(format!(
+                    "Struct '{}' declares field '{}' more than once", struct_def.name, field.name
+                )));
+            }
+            fields.push(StructFieldLayout {
+                name: field.name.clone(),
+                field_type: field.field_type.clone(),
+                offset: current_offset,
+                size: field_size,
+            });
+            
+            current_offset += field_size;
+        }
+        
+        // Align total size to struct alignment
+        let total_size = IRGenerator::align_to(current_offset, max_alignment);
+        
+        Ok(Self {
+            name: struct_def.name.clone(),
+            fields,
+            size: total_size,
+            alignment: max_alignment,
+            field_index,
+        })
+    }
+    
+    pub fn field(&self, name: &str) -> Option<&StructFieldLayout> {
+        self.field_index.get(name).map(|&index| &self.fields[index])
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -63,6 +364,78 @@ pub struct StructFieldLayout {
     pub size: u32,
 }
 
+/// Array variables of `func` that are only indexed (`a[i]`) or measured
+/// (`a.len()`): never assigned through, passed, returned or referenced, so no
+/// code writes to their memory. Names declared more than once are left out
+fn read_only_arrays(func: &Function) -> HashSet<String> {
+    let mut uses = ArrayUses::default();
+    for param in &func.params {
+        *uses.declared.entry(&param.name).or_default() += 1;
+    }
+    func.body.walk(&mut uses);
+    uses.declared.iter()
+        .filter(|&(name, &count)| {
+            count == 1
+                && !uses.written.contains(name)
+                && uses.uses.get(name).copied().unwrap_or_default() == uses.reads.get(name).copied().unwrap_or_default()
+        })
+        .map(|(name, _)| name.to_string())
+        .collect()
+}
+
+#[derive(Default)]
+struct ArrayUses<'a> {
+    declared: HashMap<&'a str, usize>,
+    // Every mention of a name, and those of them that only read an element or the length
+    uses: HashMap<&'a str, usize>,
+    reads: HashMap<&'a str, usize>,
+    // Roots of assignment targets: `a = ...`, `a[i] = ...`, `a[i].x = ...`
+    written: HashSet<&'a str>,
+}
+
+impl<'a> Visitor<'a> for ArrayUses<'a> {
+    fn statement(&mut self, statement: &'a Statement) {
+        match statement {
+            Statement::VarDecl(var_decl) => *self.declared.entry(&var_decl.name).or_default() += 1,
+            Statement::Destructure(destructure) => {
+                for name in &destructure.names {
+                    *self.declared.entry(name).or_default() += 1;
+                }
+            }
+            Statement::ForIn(for_in) => *self.declared.entry(&for_in.variable).or_default() += 1,
+            Statement::Assignment(assignment) => {
+                let mut target = &assignment.target;
+                loop {
+                    match target {
+                        Expression::Index(IndexAccess { object, .. }) | Expression::FieldAccess(FieldAccess { object, .. }) => target = object,
+                        Expression::Identifier(name) => {
+                            self.written.insert(name);
+                            break;
+                        }
+                        _ => break,
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+    
+    fn expression(&mut self, expression: &'a Expression) {
+        let read = match expression {
+            Expression::Identifier(name) => {
+                *self.uses.entry(name).or_default() += 1;
+                return;
+            }
+            Expression::Index(index_access) => &index_access.object,
+            Expression::MethodCall(method_call) if method_call.method == "len" && method_call.args.is_empty() => &method_call.object,
+            _ => return,
+        };
+        if let Expression::Identifier(name) = read.as_ref() {
+            *self.reads.entry(name).or_default() += 1;
+        }
+    }
+}
+
 impl IRGenerator {
     pub fn new(module: ObjectModule) -> Self {
         Self {
@@ -70,15 +443,36 @@ impl IRGenerator {
             builder_context: FunctionBuilderContext::new(),
             ctx: codegen::Context::new(),
             functions: HashMap::new(),
+            function_return_types: HashMap::new(),
             variables: HashMap::new(),
             current_function: None,
             string_constants: HashMap::new(),
-            structs: HashMap::new(),
             loop_stack: Vec::new(),
+            context: CodegenContext::default(),
+            capture_ir: false,
+            function_ir: HashMap::new(),
+            module_cache: HashMap::new(),
+            generated_modules: HashSet::new(),
+            module_prefixes: HashMap::new(),
+            module_names: HashMap::new(),
+            importing: Vec::new(),
+            source_dir: None,
+            defined_in: HashMap::new(),
+            entry: None,
+            uses_program_args: false,
+            jobs: 1,
+            imported_bodies: Vec::new(),
         }
     }
     
     pub fn generate(&mut self, program: &AnalyzedProgram) -> Result<(), IRError> {
+        if let Some(Item::Global(var_decl)) = program.items.iter().find(|item| matches!(item, Item::Global(_))) {
+            return Err(IRError::UnsupportedFeature(format!(
+                "Global variable '{}': global variables are supported only by the interpreter (rono run, rono test)",
+                var_decl.name
+            )));
+        }
+        
         // First pass: declare runtime functions
         self.declare_runtime_functions()?;
         
@@ -89,6 +483,12 @@ impl IRGenerator {
             }
         }
         
+        // main hands argc and argv to the runtime only when something reads `args`,
+        // so programs that do not use it link without the runtime
+        self.uses_program_args = program.items.iter()
+            .chain(self.module_cache.values().flat_map(|module| &module.items))
+            .any(|item| item.calls_methods_of("args"));
+        
         // Third pass: process struct definitions
         for item in &program.items {
             if let Item::Struct(struct_def) = item {
@@ -99,44 +499,148 @@ impl IRGenerator {
         // Fourth pass: declare all user functions and struct methods
         for item in &program.items {
             if let Item::Function(func) = item {
-                self.declare_function(func)?;
+                self.claim_definition(&func.name, None, None)?;
+                self.declare_function(func, &func.name)?;
             } else if let Item::StructImpl(impl_block) = item {
                 // Declare methods with struct prefix
                 for method in &impl_block.methods {
                     let method_name = format!("{}_{}", impl_block.struct_name, method.name);
-                    let mut method_with_new_name = method.clone();
-                    method_with_new_name.name = method_name;
-                    self.declare_function(&method_with_new_name)?;
+                    self.claim_definition(&method_name, Some(&impl_block.struct_name), None)?;
+                    self.declare_function(method, &method_name)?;
                 }
             }
         }
         
-        // Fifth pass: generate function bodies and struct methods
+        // Fifth pass: generate the bodies of imported functions, then of this
+        // file's functions and struct methods
+        let mut bodies: Vec<PendingBody> = std::mem::take(&mut self.imported_bodies);
         for item in &program.items {
             if let Item::Function(func) = item {
-                self.generate_function(func)?;
+                bodies.push(PendingBody { func: Cow::Borrowed(func), name: func.name.clone(), current_struct: None });
             } else if let Item::StructImpl(impl_block) = item {
                 // Generate method bodies with struct prefix
                 for method in &impl_block.methods {
-                    let method_name = format!("{}_{}", impl_block.struct_name, method.name);
-                    let mut method_with_new_name = method.clone();
-                    method_with_new_name.name = method_name;
-                    self.generate_function(&method_with_new_name)?;
+                    bodies.push(PendingBody {
+                        func: Cow::Borrowed(method),
+                        name: format!("{}_{}", impl_block.struct_name, method.name),
+                        current_struct: Some(impl_block.struct_name.clone()),
+                    });
                 }
             }
         }
+        let generated = self.generate_bodies(&bodies)?;
+        self.define_bodies(generated)?;
+        
+        match self.entry.clone() {
+            Some(entry) if entry.module.is_some() || !self.is_main_function(program, &entry.function) => {
+                self.generate_entry_wrapper(&entry)?;
+            }
+            _ => {}
+        }
+        
+        Ok(())
+    }
+    
+    /// Hands main's argc and argv to the runtime for `args` through `init`,
+    /// `rono_args_init` declared in the function
+    fn store_program_args(
+        builder: &mut FunctionBuilder,
+        entry_block: cranelift::codegen::ir::Block,
+        init: ir::FuncRef,
+    ) {
+        let argv = builder.block_params(entry_block).to_vec();
+        builder.ins().call(init, &argv);
+    }
+    
+    fn is_main_function(&self, program: &AnalyzedProgram, name: &str) -> bool {
+        program.items.iter().any(|item| matches!(item, Item::Function(func) if func.is_main && func.name == name))
+    }
+    
+    /// Generates the C `main` of an executable whose entry point is an imported
+    /// `main` or a function chosen with `--entry`: it calls the entry function
+    /// and exits with its int result, or 0 when it returns nothing
+    fn generate_entry_wrapper(&mut self, entry: &EntryPoint) -> Result<(), IRError> {
+        let target_name = match &entry.module {
+            Some(module) => format!("{}_{}", module, entry.function),
+            None => entry.function.clone(),
+        };
+        let target_id = *self.functions.get(&target_name)
+            .ok_or_else(|| IRError::Generation(format!("entry function '{}' is not generated", entry)))?;
+        let target_returns = self.module.declarations().get_function_decl(target_id).signature.returns.clone();
+        
+        let mut sig = self.module.make_signature();
+        sig.call_conv = self.module.target_config().default_call_conv;
+        sig.params.push(AbiParam::new(types::I32));
+        sig.params.push(AbiParam::new(types::I64));
+        sig.returns.push(AbiParam::new(types::I32));
+        let main_id = self.module.declare_function("main", Linkage::Export, &sig)
+            .map_err(IRError::from)?;
+        
+        self.ctx.clear();
+        self.ctx.func.signature = sig;
+        let mut builder = FunctionBuilder::new(&mut self.ctx.func, &mut self.builder_context);
+        let entry_block = builder.create_block();
+        builder.append_block_params_for_function_params(entry_block);
+        builder.switch_to_block(entry_block);
+        builder.seal_block(entry_block);
+        if self.uses_program_args {
+            let init_id = *self.functions.get("rono_args_init")
+                .ok_or_else(|| IRError::Generation("Runtime function rono_args_init not found".to_string()))?;
+            let init = self.module.declare_func_in_func(init_id, builder.func);
+            Self::store_program_args(&mut builder, entry_block, init);
+        }
+        
+        let target = self.module.declare_func_in_func(target_id, builder.func);
+        let call = builder.ins().call(target, &[]);
+        let result = builder.inst_results(call).first().copied();
+        let exit_code = match (result, target_returns.first().map(|param| param.value_type)) {
+            (Some(value), Some(types::I32)) => value,
+            (Some(value), Some(_)) => builder.ins().ireduce(types::I32, value),
+            _ => builder.ins().iconst(types::I32, 0),
+        };
+        builder.ins().return_(&[exit_code]);
+        builder.finalize();
         
+        if self.capture_ir {
+            self.function_ir.insert("main".to_string(), self.ctx.func.display().to_string());
+        }
+        self.module.define_function(main_id, &mut self.ctx).map_err(IRError::from)?;
+        Ok(())
+    }
+    
+    /// Records that `file` (`None` for the main file) defines the function or
+    /// method `name`, failing if another definition already did
+    fn claim_definition(&mut self, name: &str, struct_name: Option<&str>, file: Option<&PathBuf>) -> Result<(), IRError> {
+        if let Some(first) = self.defined_in.get(name) {
+            let message = match struct_name {
+                Some(struct_name) => crate::semantic::duplicate_method_message(
+                    struct_name,
+                    &name[struct_name.len() + 1..],
+                    first.as_deref(),
+                    file.map(PathBuf::as_path),
+                ),
+                None => format!("Function '{}' is defined more than once", name),
+            };
+            return Err(IRError::Generation(message));
+        }
+        self.defined_in.insert(name.to_string(), file.cloned());
         Ok(())
     }
     
-    fn declare_function(&mut self, func: &Function) -> Result<(), IRError> {
+    /// Declares `func` under `name`, which differs from `func.name` for methods
+    /// and imported functions
+    fn declare_function(&mut self, func: &Function, name: &str) -> Result<(), IRError> {
         let mut sig = self.module.make_signature();
         
         // Use system calling convention for main function
         if func.is_main {
             sig.call_conv = self.module.target_config().default_call_conv;
-            // Main function should have standard C signature: int main(int argc, char** argv)
-            // For now, we'll make it int main(void) and ignore parameters
+            // The executable's own main has the C signature int main(int argc, char** argv);
+            // the main of an imported module is called like a function without parameters
+            if name == "main" {
+                sig.params.push(AbiParam::new(types::I32));
+                sig.params.push(AbiParam::new(types::I64));
+            }
             sig.returns.push(AbiParam::new(types::I32)); // Return int
         } else {
             // Add parameters for regular functions
@@ -148,126 +652,179 @@ impl IRGenerator {
         
         // Add return type
         if let Some(return_type) = &func.return_type {
-            if *return_type != ChifType::Nil {
+            if let ChifType::Tuple(element_types) = return_type {
+                // Tuples are returned as multiple values
+                for element_type in element_types {
+                    let cranelift_type = Self::chif_type_to_cranelift(element_type)?;
+                    sig.returns.push(AbiParam::new(cranelift_type));
+                }
+            } else if *return_type != ChifType::Nil {
                 let cranelift_type = Self::chif_type_to_cranelift(return_type)?;
                 sig.returns.push(AbiParam::new(cranelift_type));
             }
         }
         
-        let func_id = self.module.declare_function(&func.name, Linkage::Export, &sig)
-            .map_err(|e| IRError::Module(e))?;
+        let func_id = self.module.declare_function(name, Linkage::Export, &sig)
+            .map_err(IRError::from)?;
         
-        self.functions.insert(func.name.clone(), func_id);
+        self.functions.insert(name.to_string(), func_id);
+        self.function_return_types.insert(
+            name.to_string(),
+            func.return_type.clone().unwrap_or(ChifType::Nil),
+        );
+        self.context.function_params.insert(name.to_string(), func.params.clone());
         
         Ok(())
     }
     
-    fn generate_function(&mut self, func: &Function) -> Result<(), IRError> {
-        let func_id = self.functions[&func.name];
-        self.current_function = Some(func_id);
-        
-        // Clear context for new function
-        self.ctx.clear();
-        self.variables.clear();
-        
-        // Get function signature
-        let sig = self.module.declarations().get_function_decl(func_id).signature.clone();
-        
-        // Set the function signature in the context
-        self.ctx.func.signature = sig.clone();
-        
-        // Create function builder
-        let mut builder = FunctionBuilder::new(&mut self.ctx.func, &mut self.builder_context);
-        
-        // Create entry block
-        let entry_block = builder.create_block();
-        
-        // Add block params for functions with parameters
-        if !func.params.is_empty() {
-            // Manually add block parameters based on function signature
-            for param_abi in &sig.params {
-                builder.append_block_param(entry_block, param_abi.value_type);
-            }
+    /// Type of a parameter inside the body: `self: Self` of a method is its struct
+    fn parameter_type(param: &Parameter, current_struct: Option<&str>) -> ChifType {
+        match (&param.param_type, current_struct) {
+            (ChifType::Struct(name), Some(struct_name)) if name == "Self" => ChifType::Struct(struct_name.to_string()),
+            (param_type, _) => param_type.clone(),
         }
-        
-        builder.switch_to_block(entry_block);
-        builder.seal_block(entry_block);
-        
-        // Create variables for parameters
-        if !func.params.is_empty() {
-            let block_params: Vec<Value> = builder.block_params(entry_block).to_vec();
-            for (i, param) in func.params.iter().enumerate() {
-                if i < block_params.len() && i < sig.params.len() {
-                    let param_value = block_params[i];
-                    let var = Variable::new(self.variables.len());
-                    let param_type = sig.params[i].value_type;
-                    builder.declare_var(var, param_type);
-                    builder.def_var(var, param_value);
-                    self.variables.insert(param.name.clone(), var);
-                }
-            }
+    }
+    
+    /// Generates the IR of `bodies` and compiles it to machine code on `jobs`
+    /// threads. The bodies keep their order, and the error is the one of the
+    /// first body that failed, as if they were generated one by one. The data
+    /// objects of the bodies are declared first, in the same order, so the
+    /// object file does not depend on the number of threads
+    fn generate_bodies(&mut self, bodies: &[PendingBody]) -> Result<Vec<GeneratedBody>, IRError> {
+        let mut data_ids = Vec::with_capacity(bodies.len());
+        for _ in bodies {
+            data_ids.push(self.module.declare_anonymous_data(false, false)?);
         }
-        
-        // Generate function body
-        let has_return = Self::block_ends_with_return(&func.body);
-        
-        // Generate statements
-        let statements = func.body.statements.clone();
-        let variables = &mut self.variables;
-        let is_main = func.is_main;
-        
-        for statement in statements {
-            Self::generate_statement_static(&mut builder, &statement, variables, is_main, &self.functions, &mut self.module)?;
+        let env = BodyEnv {
+            declarations: self.module.declarations(),
+            isa: self.module.isa(),
+            functions: &self.functions,
+            function_return_types: &self.function_return_types,
+            user_functions: self.functions.iter()
+                .filter(|(name, _)| self.context.function_params.contains_key(*name))
+                .map(|(name, id)| (id.as_u32(), name.as_str()))
+                .collect(),
+            context: &self.context,
+            uses_program_args: self.uses_program_args,
+            capture_ir: self.capture_ir,
+        };
+        let threads = self.jobs.min(bodies.len());
+        if threads <= 1 {
+            let mut worker = BodyWorker::new(&env);
+            return bodies.iter().zip(data_ids).map(|(body, data_id)| worker.generate(body, data_id)).collect();
         }
         
-        // Add implicit return if needed
-        if !has_return {
-            if func.is_main {
-                // Main function should return 0 (success) by default
-                let zero = builder.ins().iconst(types::I32, 0);
-                builder.ins().return_(&[zero]);
-            } else if func.return_type.is_none() || func.return_type == Some(ChifType::Nil) {
-                builder.ins().return_(&[]);
-            } else {
-                // This should be caught by semantic analysis
-                return Err(IRError::Generation("Function missing return statement".to_string()));
+        // Bodies are taken in order, so every body before the first failed one
+        // is generated; the ones after it are skipped
+        let queue = Mutex::new(bodies.iter().zip(data_ids).enumerate());
+        let failed_at = AtomicUsize::new(usize::MAX);
+        let mut generated: Vec<(usize, Result<GeneratedBody, IRError>)> = std::thread::scope(|scope| {
+            let workers: Vec<_> = (0..threads)
+                .map(|_| scope.spawn(|| {
+                    let mut worker = BodyWorker::new(&env);
+                    let mut generated = Vec::new();
+                    loop {
+                        let Some((index, (body, data_id))) = queue.lock().expect("a generating thread panicked").next() else { break };
+                        if index > failed_at.load(Ordering::Relaxed) {
+                            break;
+                        }
+                        let body = worker.generate(body, data_id);
+                        if body.is_err() {
+                            failed_at.fetch_min(index, Ordering::Relaxed);
+                        }
+                        generated.push((index, body));
+                    }
+                    generated
+                }))
+                .collect();
+            workers.into_iter()
+                .flat_map(|worker| worker.join().unwrap_or_else(|panic| std::panic::resume_unwind(panic)))
+                .collect()
+        });
+        generated.sort_by_key(|(index, _)| *index);
+        generated.into_iter().map(|(_, body)| body).collect()
+    }
+    
+    /// Adds the generated bodies to the module in their order: defines the data
+    /// object of each one and its machine code
+    fn define_bodies(&mut self, generated: Vec<GeneratedBody>) -> Result<(), IRError> {
+        for body in generated {
+            let GeneratedBody { id, name, ctx, data_id, data, warnings, ir } = body;
+            let mut description = DataDescription::new();
+            description.define(data.into_boxed_slice());
+            description.set_align(8);
+            self.module.define_data(data_id, &description)?;
+            self.context.warnings.extend(warnings);
+            if let Some(ir) = ir {
+                self.function_ir.insert(name, ir.display().to_string());
             }
+            let compiled = ctx.compiled_code().expect("the body was compiled");
+            self.module.define_function_bytes(
+                id,
+                &ctx.func,
+                compiled.alignment as u64,
+                compiled.code_buffer(),
+                compiled.buffer.relocs(),
+            )?;
         }
-        
-        // Finalize function
-        builder.finalize();
-        
-        // Print IR for debugging (commented out for now)
-        // println!("Generated IR for function '{}':", func.name);
-        // println!("{}", self.ctx.func.display());
-        
-        // Define the function in the module
-        self.module.define_function(func_id, &mut self.ctx)
-            .map_err(|e| {
-                println!("Function '{}' IR:", func.name);
-                println!("{}", self.ctx.func.display());
-                IRError::Module(e)
-            })?;
-        
         Ok(())
     }
     
+    /// Returns `true` when the statement ends the current block (return, break,
+    /// continue, or an if whose branches all do), so nothing may follow it
     fn generate_statement_static(
         builder: &mut FunctionBuilder, 
         statement: &Statement, 
         variables: &mut HashMap<String, Variable>,
         is_main: bool,
+        loop_stack: &mut Vec<LoopContext>,
         functions: &HashMap<String, cranelift_module::FuncId>,
-        module: &mut ObjectModule
-    ) -> Result<(), IRError> {
+        return_types: &HashMap<String, ChifType>,
+        context: &mut CodegenContext,
+        module: &mut BodyModule
+    ) -> Result<bool, IRError> {
         match statement {
+            // `_` gets no variable: only the side effects of the value remain
+            Statement::VarDecl(var_decl) if var_decl.name == DISCARD => {
+                if let Some(value) = &var_decl.value {
+                    Self::generate_expression_static(builder, value, variables, functions, return_types, context, module)?;
+                }
+            }
+            Statement::Assignment(Assignment { target: Expression::Identifier(name), value }) if name == DISCARD => {
+                Self::generate_expression_static(builder, value, variables, functions, return_types, context, module)?;
+            }
             Statement::VarDecl(var_decl) => {
                 let cranelift_type = Self::chif_type_to_cranelift(&var_decl.var_type)?;
-                let var = Variable::new(variables.len());
+                let var = context.next_variable();
                 builder.declare_var(var, cranelift_type);
                 
                 let init_value = if let Some(init_expr) = &var_decl.value {
-                    Self::generate_expression_static(builder, init_expr, variables, functions, module)?
+                    let value = match init_expr {
+                        Expression::ArrayLiteral(elements) => {
+                            let read_only = context.read_only_arrays.contains(&var_decl.name);
+                            Self::generate_array_literal(builder, elements, read_only, variables, functions, return_types, context, module)?
+                        }
+                        _ => Self::generate_expression_static(builder, init_expr, variables, functions, return_types, context, module)?,
+                    };
+                    let copied_layout = match (&var_decl.var_type, init_expr) {
+                        (ChifType::Struct(name), Expression::Identifier(_) | Expression::Index(_)) => context.structs.get(name),
+                        _ => None,
+                    };
+                    if let Some(layout) = copied_layout {
+                        // Structs are values: `var p: Point = pts[1];` gets its own copy
+                        let slot = builder.create_sized_stack_slot(StackSlotData::new(
+                            StackSlotKind::ExplicitSlot,
+                            layout.size,
+                        ));
+                        let copy = builder.ins().stack_addr(types::I64, slot, 0);
+                        Self::copy_struct_fields(builder, layout, value, copy, 0);
+                        copy
+                    } else if cranelift_type == types::F64 {
+                        // `var f: float = 2;` is allowed by the analyzer
+                        Self::promote_to_float(builder, init_expr, value)
+                    } else {
+                        value
+                    }
                 } else {
                     // Initialize with default value
                     Self::get_default_value(builder, cranelift_type)
@@ -275,16 +832,58 @@ impl IRGenerator {
                 
                 builder.def_var(var, init_value);
                 variables.insert(var_decl.name.clone(), var);
+                // `array[int]` leaves the size to the initializer; knowing it lets `in` scan the array
+                let var_type = match (&var_decl.var_type, &var_decl.value) {
+                    (ChifType::Array(element, dimensions), Some(Expression::ArrayLiteral(elements))) if dimensions[..] == [0] => {
+                        ChifType::Array(element.clone(), vec![elements.len()])
+                    }
+                    (var_type, _) => var_type.clone(),
+                };
+                context.variable_types.insert(var_decl.name.clone(), var_type);
+                Self::track_runtime_list(context, &var_decl.name, var_decl.value.as_ref());
+                if Self::struct_name_of(&var_decl.var_type).is_some() {
+                    context.struct_variables.insert(var_decl.name.clone(), var_decl.var_type.clone());
+                }
             }
             Statement::Assignment(assignment) => {
                 // For now, only handle simple variable assignments
                 if let Expression::Identifier(var_name) = &assignment.target {
-                    let value = Self::generate_expression_static(builder, &assignment.value, variables, functions, module)?;
+                    let mut value = Self::generate_expression_static(builder, &assignment.value, variables, functions, return_types, context, module)?;
+                    if context.variable_types.get(var_name) == Some(&ChifType::Float) && builder.func.dfg.value_type(value) == types::I64 {
+                        value = Self::promote_to_float(builder, &assignment.value, value);
+                    }
+                    Self::track_runtime_list(context, var_name, Some(&assignment.value));
                     if let Some(&var) = variables.get(var_name) {
                         builder.def_var(var, value);
                     } else {
                         return Err(IRError::Generation(format!("Undefined variable: {}", var_name)));
                     }
+                } else if let Expression::FieldAccess(field_access) = &assignment.target {
+                    // `p.x = 1` and `pts[1].x = 1` store straight into the struct's memory
+                    let (offset, field_type) = Self::struct_field_slot(&field_access.object, &field_access.field, context)
+                        .ok_or_else(|| IRError::UnsupportedFeature(format!(
+                            "Assignment to field '{}' needs a local struct variable or an element of an array of structs",
+                            field_access.field
+                        )))?;
+                    let struct_ptr = Self::generate_struct_address(builder, &field_access.object, variables, functions, return_types, context, module)?;
+                    let mut value = Self::generate_expression_static(builder, &assignment.value, variables, functions, return_types, context, module)?;
+                    if field_type == types::F64 {
+                        value = Self::promote_to_float(builder, &assignment.value, value);
+                    }
+                    builder.ins().store(MemFlags::new(), value, struct_ptr, offset);
+                } else if let Some(layout) = match &assignment.target {
+                    Expression::Index(index_access) => Self::struct_element_layout(index_access, context).cloned(),
+                    _ => None,
+                } {
+                    // `pts[1] = p;` copies the struct into the array element
+                    let element_ptr = Self::generate_expression_static(builder, &assignment.target, variables, functions, return_types, context, module)?;
+                    let struct_ptr = Self::generate_expression_static(builder, &assignment.value, variables, functions, return_types, context, module)?;
+                    Self::copy_struct_fields(builder, &layout, struct_ptr, element_ptr, 0);
+                } else if let Expression::Dereference(pointer) = &assignment.target {
+                    // `*p = v` stores into the memory `p` points at
+                    let address = Self::generate_expression_static(builder, pointer, variables, functions, return_types, context, module)?;
+                    let value = Self::generate_expression_static(builder, &assignment.value, variables, functions, return_types, context, module)?;
+                    builder.ins().store(MemFlags::new(), value, address, 0);
                 } else {
                     return Err(IRError::UnsupportedFeature("Complex assignment targets not yet supported".to_string()));
                 }
@@ -293,12 +892,33 @@ impl IRGenerator {
                 if let Some(expr) = expr {
                     if is_main {
                         // Main function should return int32
-                        let return_value = Self::generate_expression_static(builder, expr, variables, functions, module)?;
+                        let return_value = Self::generate_expression_static(builder, expr, variables, functions, return_types, context, module)?;
                         // Convert to i32 if needed
                         let return_i32 = builder.ins().ireduce(types::I32, return_value);
                         builder.ins().return_(&[return_i32]);
+                    } else if Self::is_tuple_expression(expr, return_types) {
+                        let return_values = Self::generate_tuple_values(builder, expr, variables, functions, return_types, context, module)?;
+                        builder.ins().return_(&return_values);
+                    } else if let Some(layout) = context.return_struct.as_ref().and_then(|name| context.structs.get(name)).cloned() {
+                        // The stack frame is gone after return, so a returned struct (a literal, a
+                        // local variable or a parameter's copy) is copied to the runtime arena
+                        let struct_ptr = Self::generate_expression_static(builder, expr, variables, functions, return_types, context, module)?;
+                        let alloc_id = *functions.get("rono_alloc_struct")
+                            .ok_or_else(|| IRError::Generation("Runtime function rono_alloc_struct not found".to_string()))?;
+                        let alloc_ref = module.declare_func_in_func(alloc_id, builder.func);
+                        let size = builder.ins().iconst(types::I64, layout.size as i64);
+                        let call = builder.ins().call(alloc_ref, &[size]);
+                        let heap_ptr = builder.inst_results(call)[0];
+                        Self::copy_struct_fields(builder, &layout, struct_ptr, heap_ptr, 0);
+                        builder.ins().return_(&[heap_ptr]);
                     } else {
-                        let return_value = Self::generate_expression_static(builder, expr, variables, functions, module)?;
+                        let mut return_value = Self::generate_expression_static(builder, expr, variables, functions, return_types, context, module)?;
+                        // `ret 1;` in a function returning float
+                        if builder.func.signature.returns.first().is_some_and(|result| result.value_type == types::F64)
+                            && builder.func.dfg.value_type(return_value) == types::I64
+                        {
+                            return_value = Self::promote_to_float(builder, expr, return_value);
+                        }
                         builder.ins().return_(&[return_value]);
                     }
                 } else {
@@ -310,14 +930,36 @@ impl IRGenerator {
                         builder.ins().return_(&[]);
                     }
                 }
+                return Ok(true);
+            }
+            Statement::Destructure(destructure) => {
+                let values = Self::generate_tuple_values(builder, &destructure.value, variables, functions, return_types, context, module)?;
+                if values.len() != destructure.names.len() {
+                    return Err(IRError::Generation(format!(
+                        "Cannot destructure a tuple of {} elements into {} variables",
+                        values.len(),
+                        destructure.names.len()
+                    )));
+                }
+                
+                for (name, value) in destructure.names.iter().zip(values) {
+                    if name == DISCARD {
+                        continue;
+                    }
+                    let var = context.next_variable();
+                    builder.declare_var(var, builder.func.dfg.value_type(value));
+                    builder.def_var(var, value);
+                    variables.insert(name.clone(), var);
+                    context.variable_types.remove(name);
+                }
             }
             Statement::Expression(expr) => {
                 // Generate expression but ignore result
-                Self::generate_expression_static(builder, expr, variables, functions, module)?;
+                Self::generate_expression_static(builder, expr, variables, functions, return_types, context, module)?;
             }
             Statement::If(if_stmt) => {
                 // Generate condition
-                let condition = Self::generate_expression_static(builder, &if_stmt.condition, variables, functions, module)?;
+                let condition = Self::generate_expression_static(builder, &if_stmt.condition, variables, functions, return_types, context, module)?;
                 
                 // Create blocks for then, else (optional), and merge
                 let then_block = builder.create_block();
@@ -337,32 +979,110 @@ impl IRGenerator {
                 
                 // Generate then block
                 builder.switch_to_block(then_block);
+                let mut then_terminated = false;
                 for stmt in &if_stmt.then_block.statements {
-                    Self::generate_statement_static(builder, stmt, variables, is_main, functions, module)?;
+                    then_terminated = Self::generate_statement_static(builder, stmt, variables, is_main, loop_stack, functions, return_types, context, module)?;
+                    if then_terminated {
+                        break;
+                    }
                 }
-                // Jump to merge block if no return statement
-                if !Self::block_ends_with_return(&if_stmt.then_block) {
+                // Jump to merge block only if control reaches the end of the branch
+                if !then_terminated {
                     builder.ins().jump(merge_block, &[]);
                 }
                 builder.seal_block(then_block);
                 
-                // Generate else block if present
+                // Without an else branch the condition falls through to merge
+                let mut else_terminated = false;
                 if let (Some(else_block), Some(else_body)) = (else_block, &if_stmt.else_block) {
                     builder.switch_to_block(else_block);
                     for stmt in &else_body.statements {
-                        Self::generate_statement_static(builder, stmt, variables, is_main, functions, module)?;
+                        else_terminated = Self::generate_statement_static(builder, stmt, variables, is_main, loop_stack, functions, return_types, context, module)?;
+                        if else_terminated {
+                            break;
+                        }
                     }
-                    // Jump to merge block if no return statement
-                    if !Self::block_ends_with_return(else_body) {
+                    if !else_terminated {
                         builder.ins().jump(merge_block, &[]);
                     }
                     builder.seal_block(else_block);
                 }
                 
+                // Both branches left the block: merge has no predecessors and is never emitted
+                if then_terminated && else_terminated {
+                    return Ok(true);
+                }
+                
                 // Continue with merge block
                 builder.switch_to_block(merge_block);
                 builder.seal_block(merge_block);
             }
+            Statement::Switch(switch_stmt) => {
+                // The scrutinee is evaluated once and every case compares against that value.
+                // Strings compare by contents, like values_equal in the interpreter
+                let scrutinee = Self::generate_expression_static(builder, &switch_stmt.expr, variables, functions, return_types, context, module)?;
+                let compare_strings = std::iter::once(&switch_stmt.expr)
+                    .chain(switch_stmt.cases.iter().map(|case| &case.value))
+                    .any(|expr| Self::is_str_expression(expr, &context.variable_types, return_types));
+                let merge_block = builder.create_block();
+                let mut all_terminated = true;
+                
+                for case in &switch_stmt.cases {
+                    let case_value = Self::generate_expression_static(builder, &case.value, variables, functions, return_types, context, module)?;
+                    let matches = if compare_strings {
+                        let func_id = *functions.get("rono_str_equal")
+                            .ok_or_else(|| IRError::Generation("Runtime function rono_str_equal not found".to_string()))?;
+                        let func_ref = module.declare_func_in_func(func_id, builder.func);
+                        let call = builder.ins().call(func_ref, &[scrutinee, case_value]);
+                        builder.inst_results(call)[0]
+                    } else if builder.func.dfg.value_type(scrutinee).is_float() {
+                        builder.ins().fcmp(FloatCC::Equal, scrutinee, case_value)
+                    } else {
+                        builder.ins().icmp(IntCC::Equal, scrutinee, case_value)
+                    };
+                    
+                    let body_block = builder.create_block();
+                    let next_block = builder.create_block();
+                    builder.ins().brif(matches, body_block, &[], next_block, &[]);
+                    
+                    builder.switch_to_block(body_block);
+                    builder.seal_block(body_block);
+                    let mut case_terminated = false;
+                    for stmt in &case.body.statements {
+                        case_terminated = Self::generate_statement_static(builder, stmt, variables, is_main, loop_stack, functions, return_types, context, module)?;
+                        if case_terminated {
+                            break;
+                        }
+                    }
+                    if !case_terminated {
+                        builder.ins().jump(merge_block, &[]);
+                    }
+                    all_terminated &= case_terminated;
+                    
+                    builder.switch_to_block(next_block);
+                    builder.seal_block(next_block);
+                }
+                
+                // No case matched: run the default arm or fall through to merge
+                let mut default_terminated = false;
+                if let Some(default_case) = &switch_stmt.default_case {
+                    for stmt in &default_case.statements {
+                        default_terminated = Self::generate_statement_static(builder, stmt, variables, is_main, loop_stack, functions, return_types, context, module)?;
+                        if default_terminated {
+                            break;
+                        }
+                    }
+                }
+                if !default_terminated {
+                    builder.ins().jump(merge_block, &[]);
+                }
+                
+                if all_terminated && default_terminated {
+                    return Ok(true);
+                }
+                builder.switch_to_block(merge_block);
+                builder.seal_block(merge_block);
+            }
             Statement::While(while_stmt) => {
                 // Create blocks for loop header, body, and exit
                 let header_block = builder.create_block();
@@ -374,23 +1094,29 @@ impl IRGenerator {
                 
                 // Generate header block (condition check)
                 builder.switch_to_block(header_block);
-                let condition = Self::generate_expression_static(builder, &while_stmt.condition, variables, functions, module)?;
+                let condition = Self::generate_expression_static(builder, &while_stmt.condition, variables, functions, return_types, context, module)?;
                 builder.ins().brif(condition, body_block, &[], exit_block, &[]);
                 
                 // Push loop context for break/continue
-                let loop_context = LoopContext {
+                loop_stack.push(LoopContext {
                     break_block: exit_block,
                     continue_block: header_block,
-                };
-                // Note: We can't access self here, so we'll need to refactor this
+                });
                 
                 // Generate body block
                 builder.switch_to_block(body_block);
+                let mut body_terminated = false;
                 for stmt in &while_stmt.body.statements {
-                    Self::generate_statement_static(builder, stmt, variables, is_main, functions, module)?;
+                    body_terminated = Self::generate_statement_static(builder, stmt, variables, is_main, loop_stack, functions, return_types, context, module)?;
+                    if body_terminated {
+                        break;
+                    }
                 }
+                loop_stack.pop();
                 // Jump back to header for next iteration
-                builder.ins().jump(header_block, &[]);
+                if !body_terminated {
+                    builder.ins().jump(header_block, &[]);
+                }
                 
                 // Seal blocks after all jumps are created
                 builder.seal_block(header_block);
@@ -409,7 +1135,7 @@ impl IRGenerator {
                 
                 // Generate initialization if present
                 if let Some(init_stmt) = &for_stmt.init {
-                    Self::generate_statement_static(builder, init_stmt, variables, is_main, functions, module)?;
+                    Self::generate_statement_static(builder, init_stmt, variables, is_main, loop_stack, functions, return_types, context, module)?;
                 }
                 
                 // Jump to header block
@@ -418,25 +1144,39 @@ impl IRGenerator {
                 // Generate header block (condition check)
                 builder.switch_to_block(header_block);
                 if let Some(condition_expr) = &for_stmt.condition {
-                    let condition = Self::generate_expression_static(builder, condition_expr, variables, functions, module)?;
+                    let condition = Self::generate_expression_static(builder, condition_expr, variables, functions, return_types, context, module)?;
                     builder.ins().brif(condition, body_block, &[], exit_block, &[]);
                 } else {
                     // No condition means infinite loop (until break)
                     builder.ins().jump(body_block, &[]);
                 }
                 
-                // Generate body block
+                // `continue` jumps to the update block, so the update clause runs
+                // exactly once per iteration however the body finishes
+                loop_stack.push(LoopContext {
+                    break_block: exit_block,
+                    continue_block: update_block,
+                });
+                
+                // Generate body block
                 builder.switch_to_block(body_block);
+                let mut body_terminated = false;
                 for stmt in &for_stmt.body.statements {
-                    Self::generate_statement_static(builder, stmt, variables, is_main, functions, module)?;
+                    body_terminated = Self::generate_statement_static(builder, stmt, variables, is_main, loop_stack, functions, return_types, context, module)?;
+                    if body_terminated {
+                        break;
+                    }
                 }
+                loop_stack.pop();
                 // Jump to update block
-                builder.ins().jump(update_block, &[]);
+                if !body_terminated {
+                    builder.ins().jump(update_block, &[]);
+                }
                 
                 // Generate update block
                 builder.switch_to_block(update_block);
                 if let Some(update_stmt) = &for_stmt.update {
-                    Self::generate_statement_static(builder, update_stmt, variables, is_main, functions, module)?;
+                    Self::generate_statement_static(builder, update_stmt, variables, is_main, loop_stack, functions, return_types, context, module)?;
                 }
                 // Jump back to header for next iteration
                 builder.ins().jump(header_block, &[]);
@@ -450,46 +1190,320 @@ impl IRGenerator {
                 builder.switch_to_block(exit_block);
                 builder.seal_block(exit_block);
             }
-            Statement::Break => {
-                // For now, we'll implement a simple version without loop context
-                // In a real implementation, we would jump to the loop's exit block
-                // For now, just ignore break statements in compilation
-                // TODO: Implement proper loop context tracking
+            Statement::ForIn(for_in) => {
+                let args = for_in.range_args().ok_or_else(|| {
+                    IRError::Generation("for-in can only iterate over range(start, end[, step])".to_string())
+                })?;
+                // The bounds are evaluated once, before the loop
+                let mut bounds = Vec::with_capacity(args.len());
+                for arg in args {
+                    bounds.push(Self::generate_expression_static(builder, arg, variables, functions, return_types, context, module)?);
+                }
+                let (start, end, step) = match bounds[..] {
+                    [start, end] => (start, end, builder.ins().iconst(types::I64, 1)),
+                    [start, end, step] => {
+                        let func_id = *functions.get("rono_range_check_step")
+                            .ok_or_else(|| IRError::Generation("Runtime function rono_range_check_step not found".to_string()))?;
+                        let func_ref = module.declare_func_in_func(func_id, builder.func);
+                        builder.ins().call(func_ref, &[step]);
+                        (start, end, step)
+                    }
+                    _ => return Err(IRError::Generation(format!("range() expects 2 or 3 arguments, got {}", bounds.len()))),
+                };
+                let ascending = builder.ins().icmp_imm(IntCC::SignedGreaterThan, step, 0);
+                // |step| compared unsigned, so i64::MIN still has the right magnitude
+                let negated_step = builder.ins().ineg(step);
+                let stride = builder.ins().select(ascending, step, negated_step);
+                
+                let var = context.next_variable();
+                builder.declare_var(var, types::I64);
+                builder.def_var(var, start);
+                let shadowed = variables.insert(for_in.variable.clone(), var);
+                let shadowed_type = context.variable_types.insert(for_in.variable.clone(), ChifType::Int);
+                
+                let header_block = builder.create_block();
+                let body_block = builder.create_block();
+                let update_block = builder.create_block();
+                let exit_block = builder.create_block();
+                builder.ins().jump(header_block, &[]);
+                
+                builder.switch_to_block(header_block);
+                let current = builder.use_var(var);
+                let below = builder.ins().icmp(IntCC::SignedLessThan, current, end);
+                let above = builder.ins().icmp(IntCC::SignedGreaterThan, current, end);
+                let condition = builder.ins().select(ascending, below, above);
+                builder.ins().brif(condition, body_block, &[], exit_block, &[]);
+                
+                loop_stack.push(LoopContext {
+                    break_block: exit_block,
+                    continue_block: update_block,
+                });
+                builder.switch_to_block(body_block);
+                let mut body_terminated = false;
+                for stmt in &for_in.body.statements {
+                    body_terminated = Self::generate_statement_static(builder, stmt, variables, is_main, loop_stack, functions, return_types, context, module)?;
+                    if body_terminated {
+                        break;
+                    }
+                }
+                loop_stack.pop();
+                if !body_terminated {
+                    builder.ins().jump(update_block, &[]);
+                }
+                
+                // The loop ends before the step would pass `end`, so the
+                // increment never overflows near the i64 limits
+                builder.switch_to_block(update_block);
+                let current = builder.use_var(var);
+                let distance_up = builder.ins().isub(end, current);
+                let distance_down = builder.ins().isub(current, end);
+                let distance = builder.ins().select(ascending, distance_up, distance_down);
+                let last = builder.ins().icmp(IntCC::UnsignedLessThanOrEqual, distance, stride);
+                let next = builder.ins().iadd(current, step);
+                builder.def_var(var, next);
+                builder.ins().brif(last, exit_block, &[], header_block, &[]);
+                
+                builder.seal_block(header_block);
+                builder.seal_block(body_block);
+                builder.seal_block(update_block);
+                builder.switch_to_block(exit_block);
+                builder.seal_block(exit_block);
+                
+                if let Some(outer) = shadowed {
+                    variables.insert(for_in.variable.clone(), outer);
+                }
+                if let Some(outer_type) = shadowed_type {
+                    context.variable_types.insert(for_in.variable.clone(), outer_type);
+                }
             }
-            Statement::Continue => {
-                // For now, we'll implement a simple version without loop context
-                // In a real implementation, we would jump to the loop's continue block
-                // For now, just ignore continue statements in compilation
-                // TODO: Implement proper loop context tracking
+            Statement::Break | Statement::Continue => {
+                let loop_context = loop_stack.last().ok_or_else(|| {
+                    IRError::Generation("break/continue outside of a loop".to_string())
+                })?;
+                let target = if matches!(statement, Statement::Break) {
+                    loop_context.break_block
+                } else {
+                    loop_context.continue_block
+                };
+                builder.ins().jump(target, &[]);
+                return Ok(true);
             }
-            _ => {
-                return Err(IRError::UnsupportedFeature(format!("Statement type not yet supported: {:?}", statement)));
+        }
+        
+        Ok(false)
+    }
+    
+    /// Whether the expression is statically known to be a str
+    fn is_str_expression(
+        expression: &Expression,
+        variable_types: &HashMap<String, ChifType>,
+        return_types: &HashMap<String, ChifType>,
+    ) -> bool {
+        let known_type = match expression {
+            Expression::Literal(value) => Some(value.get_type()),
+            Expression::Identifier(name) => variable_types.get(name).cloned(),
+            Expression::Call(func_call) => return_types.get(&func_call.name).cloned(),
+            // `a + b` is a str when either operand is
+            Expression::Binary(binary_op) if binary_op.operator == BinaryOperator::Add => {
+                return [&binary_op.left, &binary_op.right].iter()
+                    .any(|side| Self::is_str_expression(side, variable_types, return_types));
             }
+            // A variable named `args`, `os` or `rono` shadows the builtin object
+            Expression::MethodCall(method_call) => Self::builtin_object_method_type(method_call)
+                .filter(|_| !matches!(&*method_call.object, Expression::Identifier(name) if variable_types.contains_key(name)))
+                .or_else(|| Self::builtin_method_return_type(&method_call.method)),
+            _ => None,
+        };
+        known_type == Some(ChifType::Str)
+    }
+    
+    fn is_tuple_expression(expression: &Expression, return_types: &HashMap<String, ChifType>) -> bool {
+        match expression {
+            Expression::TupleLiteral(_) => true,
+            Expression::Call(func_call) => {
+                matches!(return_types.get(&func_call.name), Some(ChifType::Tuple(_)))
+            }
+            _ => false,
+        }
+    }
+    
+    fn is_overflow_builtin(name: &str) -> bool {
+        matches!(name, "wrapping_add" | "wrapping_sub" | "wrapping_mul" | "saturating_add" | "saturating_sub" | "checked_add")
+    }
+    
+    /// Integer arithmetic with explicit overflow behaviour. iadd/isub/imul already
+    /// wrap; overflow of a signed add or sub is detected from the sign bits, so
+    /// the saturating variants are a compare-and-select and checked_add returns
+    /// the wrapped sum together with an ok flag.
+    fn generate_overflow_arithmetic(builder: &mut FunctionBuilder, name: &str, a: Value, b: Value) -> Vec<Value> {
+        let result = match name {
+            "wrapping_mul" => return vec![builder.ins().imul(a, b)],
+            "wrapping_sub" | "saturating_sub" => builder.ins().isub(a, b),
+            _ => builder.ins().iadd(a, b),
+        };
+        if name.starts_with("wrapping_") {
+            return vec![result];
         }
         
-        Ok(())
+        // a + b overflows when both operands differ in sign from the result,
+        // a - b when the operands differ in sign and the result differs from a
+        let result_sign = builder.ins().bxor(a, result);
+        let operand_sign = if name == "saturating_sub" {
+            builder.ins().bxor(a, b)
+        } else {
+            builder.ins().bxor(b, result)
+        };
+        let sign_mask = builder.ins().band(result_sign, operand_sign);
+        if name == "checked_add" {
+            let ok = builder.ins().icmp_imm(IntCC::SignedGreaterThanOrEqual, sign_mask, 0);
+            return vec![result, ok];
+        }
+        let overflowed = builder.ins().icmp_imm(IntCC::SignedLessThan, sign_mask, 0);
+        // On overflow the true result is past the limit on the side of a: MIN for
+        // negative a, MAX otherwise
+        let a_sign = builder.ins().sshr_imm(a, 63);
+        let limit = builder.ins().bxor_imm(a_sign, i64::MAX);
+        vec![builder.ins().select(overflowed, limit, result)]
+    }
+    
+    /// Generates the element values of a tuple. Tuples never live in a single
+    /// Cranelift value: they are either literals or calls with multiple returns.
+    fn generate_tuple_values(
+        builder: &mut FunctionBuilder,
+        expression: &Expression,
+        variables: &HashMap<String, Variable>,
+        functions: &HashMap<String, cranelift_module::FuncId>,
+        return_types: &HashMap<String, ChifType>,
+        context: &mut CodegenContext,
+        module: &mut BodyModule
+    ) -> Result<Vec<Value>, IRError> {
+        match expression {
+            Expression::TupleLiteral(elements) => {
+                let mut values = Vec::new();
+                for element in elements {
+                    values.push(Self::generate_expression_static(builder, element, variables, functions, return_types, context, module)?);
+                }
+                Ok(values)
+            }
+            Expression::Call(func_call) if func_call.name == "checked_add" => {
+                if func_call.args.len() != 2 {
+                    return Err(IRError::Generation("checked_add expects 2 arguments (a, b)".to_string()));
+                }
+                
+                let a = Self::generate_expression_static(builder, &func_call.args[0], variables, functions, return_types, context, module)?;
+                let b = Self::generate_expression_static(builder, &func_call.args[1], variables, functions, return_types, context, module)?;
+                Ok(Self::generate_overflow_arithmetic(builder, "checked_add", a, b))
+            }
+            Expression::Call(func_call) if Self::is_tuple_expression(expression, return_types) => {
+                let func_id = *functions.get(&func_call.name)
+                    .ok_or_else(|| IRError::Generation(format!("Undefined function: {}", func_call.name)))?;
+                
+                let positions = Self::parameter_positions(func_call, context).map_err(IRError::Generation)?;
+                let mut args = Vec::new();
+                for arg in func_call.source_args() {
+                    args.push(Self::generate_expression_static(builder, arg, variables, functions, return_types, context, module)?);
+                }
+                if let Some(positions) = &positions {
+                    args = in_parameter_order(args, positions);
+                }
+                
+                let func_ref = module.declare_func_in_func(func_id, builder.func);
+                let call_result = builder.ins().call(func_ref, &args);
+                Ok(builder.inst_results(call_result).to_vec())
+            }
+            _ => Err(IRError::UnsupportedFeature(
+                "Only tuple literals and calls returning tuples can be destructured".to_string(),
+            )),
+        }
     }
     
-    fn is_float_expression(expression: &Expression) -> bool {
+    /// `FunctionCall::parameter_positions` of a call with named arguments; `None`
+    /// for a positional call, whose arguments already are in parameter order
+    fn parameter_positions(func_call: &FunctionCall, context: &CodegenContext) -> Result<Option<Vec<usize>>, String> {
+        if func_call.named_args.is_empty() {
+            return Ok(None);
+        }
+        let params = context.function_params.get(&func_call.name).ok_or_else(|| format!(
+            "Named arguments are only supported for user-defined functions, not '{}'",
+            func_call.name
+        ))?;
+        func_call.parameter_positions(params).map(Some)
+    }
+    
+    fn is_float_expression(expression: &Expression, return_types: &HashMap<String, ChifType>) -> bool {
         match expression {
             Expression::Literal(ChifValue::Float(_)) => true,
-            Expression::Binary(binary_op) => {
-                Self::is_float_expression(&binary_op.left) || Self::is_float_expression(&binary_op.right)
+            // Comparisons of floats produce a bool
+            Expression::Binary(binary_op) if matches!(
+                binary_op.operator,
+                BinaryOperator::Add | BinaryOperator::Subtract | BinaryOperator::Multiply | BinaryOperator::Divide | BinaryOperator::Modulo
+            ) => {
+                Self::is_float_expression(&binary_op.left, return_types) || Self::is_float_expression(&binary_op.right, return_types)
+            }
+            Expression::Call(func_call) => {
+                matches!(return_types.get(&func_call.name), Some(ChifType::Float))
             }
             _ => false,
         }
     }
+    
+    /// Converts an integer operand of a float operation to f64. An int literal
+    /// becomes an f64 constant directly instead of a runtime conversion.
+    fn promote_to_float(builder: &mut FunctionBuilder, expression: &Expression, value: Value) -> Value {
+        if builder.func.dfg.value_type(value).is_float() {
+            return value;
+        }
+        match expression {
+            Expression::Literal(ChifValue::Int(n)) => builder.ins().f64const(*n as f64),
+            _ => builder.ins().fcvt_from_sint(types::F64, value),
+        }
+    }
+    
+    /// Picks the runtime print function for a con.out argument.
+    /// Literals and calls with a known return type decide directly; anything else
+    /// falls back to the Cranelift type of the generated value.
+    pub fn select_print_function(
+        expression: &Expression,
+        value_type: Type,
+        return_types: &HashMap<String, ChifType>,
+    ) -> &'static str {
+        let known_type = match expression {
+            Expression::Literal(ChifValue::Int(_)) => Some(ChifType::Int),
+            Expression::Literal(ChifValue::Float(_)) => Some(ChifType::Float),
+            Expression::Literal(ChifValue::Bool(_)) => Some(ChifType::Bool),
+            Expression::Literal(ChifValue::Str(_)) => Some(ChifType::Str),
+            Expression::Call(func_call) => return_types.get(&func_call.name).cloned(),
+            Expression::MethodCall(method_call) => Self::builtin_object_method_type(method_call)
+                .or_else(|| Self::builtin_method_return_type(&method_call.method)),
+            _ if Self::is_float_expression(expression, return_types) => Some(ChifType::Float),
+            _ => None,
+        };
+        
+        match known_type {
+            Some(ChifType::Float) => "rono_print_float",
+            Some(ChifType::Bool) => "rono_print_bool",
+            Some(ChifType::Str) => "rono_print_string",
+            Some(ChifType::Int) => "rono_print_int",
+            _ => match value_type {
+                types::F64 => "rono_print_float",
+                types::I8 => "rono_print_bool",
+                _ => "rono_print_int",
+            },
+        }
+    }
 
     fn generate_expression_static(
         builder: &mut FunctionBuilder, 
         expression: &Expression, 
         variables: &HashMap<String, Variable>,
         functions: &HashMap<String, cranelift_module::FuncId>,
-        module: &mut ObjectModule
+        return_types: &HashMap<String, ChifType>,
+        context: &mut CodegenContext,
+        module: &mut BodyModule
     ) -> Result<Value, IRError> {
         match expression {
             Expression::Literal(value) => {
-                Self::generate_literal(builder, value)
+                Self::generate_literal(builder, module, value)
             }
             Expression::Identifier(name) => {
                 if let Some(&var) = variables.get(name) {
@@ -498,20 +1512,58 @@ impl IRGenerator {
                     Err(IRError::Generation(format!("Undefined variable: {}", name)))
                 }
             }
+            Expression::Binary(binary_op) if binary_op.operator == BinaryOperator::In => {
+                Self::generate_membership(builder, binary_op, variables, functions, return_types, context, module)
+            }
             Expression::Binary(binary_op) => {
-                // Check for constant folding opportunities
-                if let (Expression::Literal(left_val), Expression::Literal(right_val)) = 
-                    (&*binary_op.left, &*binary_op.right) {
-                    if let Some(folded) = Self::fold_constants(left_val, &binary_op.operator, right_val) {
-                        return Self::generate_literal(builder, &folded);
+                // Check for constant folding opportunities; `os.name() == "windows"`
+                // is a constant too, so the branch it guards can be pruned
+                let constant = |side: &Expression| match side {
+                    Expression::Literal(value) => Some(value.clone()),
+                    Expression::MethodCall(method_call) => Self::platform_constant(method_call, module.isa().triple()).map(ChifValue::Str),
+                    _ => None,
+                };
+                if let (Some(left_val), Some(right_val)) = (constant(&binary_op.left), constant(&binary_op.right)) {
+                    if let Some(folded) = Self::fold_constants(&left_val, &binary_op.operator, &right_val) {
+                        return Self::generate_literal(builder, module, &folded);
                     }
                 }
                 
-                let left = Self::generate_expression_static(builder, &binary_op.left, variables, functions, module)?;
-                let right = Self::generate_expression_static(builder, &binary_op.right, variables, functions, module)?;
+                let left = Self::generate_expression_static(builder, &binary_op.left, variables, functions, return_types, context, module)?;
+                let right = Self::generate_expression_static(builder, &binary_op.right, variables, functions, return_types, context, module)?;
+                
+                // The generated values decide: a float on either side makes it a float
+                // operation, and the integer side is converted first
+                let is_float = builder.func.dfg.value_type(left).is_float() || builder.func.dfg.value_type(right).is_float();
+                let (left, right) = if is_float {
+                    (Self::promote_to_float(builder, &binary_op.left, left), Self::promote_to_float(builder, &binary_op.right, right))
+                } else {
+                    (left, right)
+                };
                 
-                // Determine if this is a float operation
-                let is_float = Self::is_float_expression(&binary_op.left) || Self::is_float_expression(&binary_op.right);
+                // Strings compare by contents and `+` concatenates them into a new
+                // runtime string when either side is known to be a str
+                let is_str = [&binary_op.left, &binary_op.right].iter()
+                    .any(|side| Self::is_str_expression(side, &context.variable_types, return_types));
+                if is_str && binary_op.operator == BinaryOperator::Add {
+                    let func_id = *functions.get("rono_string_concat")
+                        .ok_or_else(|| IRError::Generation("Runtime function rono_string_concat not found".to_string()))?;
+                    let func_ref = module.declare_func_in_func(func_id, builder.func);
+                    let call = builder.ins().call(func_ref, &[left, right]);
+                    return Ok(builder.inst_results(call)[0]);
+                }
+                if is_str && matches!(binary_op.operator, BinaryOperator::Equal | BinaryOperator::NotEqual) {
+                    let func_id = *functions.get("rono_str_equal")
+                        .ok_or_else(|| IRError::Generation("Runtime function rono_str_equal not found".to_string()))?;
+                    let func_ref = module.declare_func_in_func(func_id, builder.func);
+                    let call = builder.ins().call(func_ref, &[left, right]);
+                    let equal = builder.inst_results(call)[0];
+                    return Ok(if binary_op.operator == BinaryOperator::Equal {
+                        equal
+                    } else {
+                        builder.ins().icmp_imm(IntCC::Equal, equal, 0)
+                    });
+                }
                 
                 match binary_op.operator {
                     BinaryOperator::Add => {
@@ -539,7 +1591,25 @@ impl IRGenerator {
                         if is_float {
                             Ok(builder.ins().fdiv(left, right))
                         } else {
-                            Ok(builder.ins().sdiv(left, right))
+                            // sdiv traps on a zero divisor and on i64::MIN / -1;
+                            // the runtime checks both and exits with an error message
+                            let func_id = *functions.get("rono_int_div")
+                                .ok_or_else(|| IRError::Generation("Runtime function rono_int_div not found".to_string()))?;
+                            let func_ref = module.declare_func_in_func(func_id, builder.func);
+                            let call = builder.ins().call(func_ref, &[left, right]);
+                            Ok(builder.inst_results(call)[0])
+                        }
+                    }
+                    BinaryOperator::Modulo => {
+                        if is_float {
+                            // Cranelift has no frem, the runtime wraps C fmod
+                            let func_id = *functions.get("rono_fmod")
+                                .ok_or_else(|| IRError::Generation("Runtime function rono_fmod not found".to_string()))?;
+                            let func_ref = module.declare_func_in_func(func_id, builder.func);
+                            let call = builder.ins().call(func_ref, &[left, right]);
+                            Ok(builder.inst_results(call)[0])
+                        } else {
+                            Ok(builder.ins().srem(left, right))
                         }
                     }
                     BinaryOperator::Equal => {
@@ -588,12 +1658,16 @@ impl IRGenerator {
                 }
             }
             Expression::Unary(unary_op) => {
-                let operand = Self::generate_expression_static(builder, &unary_op.operand, variables, functions, module)?;
+                let operand = Self::generate_expression_static(builder, &unary_op.operand, variables, functions, return_types, context, module)?;
                 
                 match unary_op.operator {
                     UnaryOperator::Minus => {
-                        let zero = builder.ins().iconst(types::I64, 0);
-                        Ok(builder.ins().isub(zero, operand))
+                        if builder.func.dfg.value_type(operand).is_float() {
+                            Ok(builder.ins().fneg(operand))
+                        } else {
+                            let zero = builder.ins().iconst(types::I64, 0);
+                            Ok(builder.ins().isub(zero, operand))
+                        }
                     }
                     UnaryOperator::Not => {
                         // For boolean not, we assume the value is 0 or 1
@@ -603,39 +1677,27 @@ impl IRGenerator {
                 }
             }
             Expression::Call(func_call) => {
+                let positions = Self::parameter_positions(func_call, context).map_err(IRError::Generation)?;
+                
                 // Special handling for console output
                 if func_call.name == "con.out" {
                     if func_call.args.len() != 1 {
                         return Err(IRError::Generation("con.out expects exactly one argument".to_string()));
                     }
                     
-                    let arg_value = Self::generate_expression_static(builder, &func_call.args[0], variables, functions, module)?;
+                    let arg_value = Self::generate_expression_static(builder, &func_call.args[0], variables, functions, return_types, context, module)?;
                     
                     // Determine the type of the argument and call appropriate runtime function
-                    let (func_name, converted_arg) = match &func_call.args[0] {
-                        Expression::Literal(ChifValue::Int(_)) => ("rono_print_int", arg_value),
-                        Expression::Literal(ChifValue::Float(_)) => ("rono_print_float", arg_value),
-                        Expression::Literal(ChifValue::Bool(_)) => ("rono_print_bool", arg_value),
-                        Expression::Literal(ChifValue::Str(_)) => ("rono_print_string", arg_value),
-                        _ => {
-                            // For variables and complex expressions, we need to infer the type
-                            // This is a simplified approach - check if it's a float expression
-                            if Self::is_float_expression(&func_call.args[0]) {
-                                ("rono_print_float", arg_value)
-                            } else {
-                                // Default to int for now
-                                ("rono_print_int", arg_value)
-                            }
-                        }
-                    };
+                    let value_type = builder.func.dfg.value_type(arg_value);
+                    let func_name = Self::select_print_function(&func_call.args[0], value_type, return_types);
                     
                     if let Some(&print_func_id) = functions.get(func_name) {
                         let func_ref = module.declare_func_in_func(print_func_id, builder.func);
-                        builder.ins().call(func_ref, &[converted_arg]);
+                        builder.ins().call(func_ref, &[arg_value]);
                         // Return dummy value since con.out returns void
                         Ok(builder.ins().iconst(types::I64, 0))
                     } else {
-                        Err(IRError::Generation("Runtime function rono_print_int not found".to_string()))
+                        Err(IRError::Generation(format!("Runtime function {} not found", func_name)))
                     }
                 } else if func_call.name == "randi" {
                     // Handle randi(min, max) function call
@@ -643,8 +1705,8 @@ impl IRGenerator {
                         return Err(IRError::Generation("randi expects 2 arguments (min, max)".to_string()));
                     }
                     
-                    let min_value = Self::generate_expression_static(builder, &func_call.args[0], variables, functions, module)?;
-                    let max_value = Self::generate_expression_static(builder, &func_call.args[1], variables, functions, module)?;
+                    let min_value = Self::generate_expression_static(builder, &func_call.args[0], variables, functions, return_types, context, module)?;
+                    let max_value = Self::generate_expression_static(builder, &func_call.args[1], variables, functions, return_types, context, module)?;
                     
                     if let Some(&rand_func_id) = functions.get("rono_rand_int") {
                         let func_ref = module.declare_func_in_func(rand_func_id, builder.func);
@@ -659,8 +1721,8 @@ impl IRGenerator {
                         return Err(IRError::Generation("randf expects 2 arguments (min, max)".to_string()));
                     }
                     
-                    let min_value = Self::generate_expression_static(builder, &func_call.args[0], variables, functions, module)?;
-                    let max_value = Self::generate_expression_static(builder, &func_call.args[1], variables, functions, module)?;
+                    let min_value = Self::generate_expression_static(builder, &func_call.args[0], variables, functions, return_types, context, module)?;
+                    let max_value = Self::generate_expression_static(builder, &func_call.args[1], variables, functions, return_types, context, module)?;
                     
                     if let Some(&rand_func_id) = functions.get("rono_rand_float") {
                         let func_ref = module.declare_func_in_func(rand_func_id, builder.func);
@@ -669,14 +1731,43 @@ impl IRGenerator {
                     } else {
                         Err(IRError::Generation("Runtime function rono_rand_float not found".to_string()))
                     }
+                } else if func_call.name == "rando" {
+                    // Handle rando() function call
+                    if !func_call.args.is_empty() {
+                        return Err(IRError::Generation("rando expects no arguments".to_string()));
+                    }
+                    
+                    if let Some(&rand_func_id) = functions.get("rono_rand_unit") {
+                        let func_ref = module.declare_func_in_func(rand_func_id, builder.func);
+                        let result = builder.ins().call(func_ref, &[]);
+                        Ok(builder.inst_results(result)[0])
+                    } else {
+                        Err(IRError::Generation("Runtime function rono_rand_unit not found".to_string()))
+                    }
+                } else if func_call.name == "rand_seed" {
+                    // Handle rand_seed(seed) function call
+                    if func_call.args.len() != 1 {
+                        return Err(IRError::Generation("rand_seed expects 1 argument (seed)".to_string()));
+                    }
+                    
+                    let seed_value = Self::generate_expression_static(builder, &func_call.args[0], variables, functions, return_types, context, module)?;
+                    
+                    if let Some(&seed_func_id) = functions.get("rono_rand_seed") {
+                        let func_ref = module.declare_func_in_func(seed_func_id, builder.func);
+                        builder.ins().call(func_ref, &[seed_value]);
+                        // Return dummy value since rand_seed returns void
+                        Ok(builder.ins().iconst(types::I64, 0))
+                    } else {
+                        Err(IRError::Generation("Runtime function rono_rand_seed not found".to_string()))
+                    }
                 } else if func_call.name == "rands" {
                     // Handle rands(from, to) function call
                     if func_call.args.len() != 2 {
                         return Err(IRError::Generation("rands expects 2 arguments (from, to)".to_string()));
                     }
                     
-                    let from_value = Self::generate_expression_static(builder, &func_call.args[0], variables, functions, module)?;
-                    let to_value = Self::generate_expression_static(builder, &func_call.args[1], variables, functions, module)?;
+                    let from_value = Self::generate_expression_static(builder, &func_call.args[0], variables, functions, return_types, context, module)?;
+                    let to_value = Self::generate_expression_static(builder, &func_call.args[1], variables, functions, return_types, context, module)?;
                     
                     if let Some(&rand_func_id) = functions.get("rono_rand_char_range") {
                         let func_ref = module.declare_func_in_func(rand_func_id, builder.func);
@@ -685,14 +1776,92 @@ impl IRGenerator {
                     } else {
                         Err(IRError::Generation("Runtime function rono_rand_char_range not found".to_string()))
                     }
+                } else if func_call.name == "fdiv" {
+                    if func_call.args.len() != 2 {
+                        return Err(IRError::Generation("fdiv expects 2 arguments (a, b)".to_string()));
+                    }
+                    
+                    let a = Self::generate_expression_static(builder, &func_call.args[0], variables, functions, return_types, context, module)?;
+                    let b = Self::generate_expression_static(builder, &func_call.args[1], variables, functions, return_types, context, module)?;
+                    let a = builder.ins().fcvt_from_sint(types::F64, a);
+                    let b = builder.ins().fcvt_from_sint(types::F64, b);
+                    Ok(builder.ins().fdiv(a, b))
+                } else if Self::is_overflow_builtin(&func_call.name) {
+                    if func_call.name == "checked_add" {
+                        return Err(IRError::UnsupportedFeature("Tuple values can only be returned or destructured".to_string()));
+                    }
+                    if func_call.args.len() != 2 {
+                        return Err(IRError::Generation(format!("{} expects 2 arguments (a, b)", func_call.name)));
+                    }
+                    
+                    let a = Self::generate_expression_static(builder, &func_call.args[0], variables, functions, return_types, context, module)?;
+                    let b = Self::generate_expression_static(builder, &func_call.args[1], variables, functions, return_types, context, module)?;
+                    Ok(Self::generate_overflow_arithmetic(builder, &func_call.name, a, b)[0])
+                } else if let Some(method) = Self::http_request_method(&func_call.name) {
+                    Self::generate_http_request(builder, &func_call.name, method, &func_call.args, variables, functions, return_types, context, module)
+                } else if func_call.name == "toStr" {
+                    if func_call.args.len() != 1 {
+                        return Err(IRError::Generation("toStr expects 1 argument".to_string()));
+                    }
+                    
+                    let argument = &func_call.args[0];
+                    let value = Self::generate_expression_static(builder, argument, variables, functions, return_types, context, module)?;
+                    if matches!(argument, Expression::Identifier(name) if context.variable_types.get(name) == Some(&ChifType::Str)) {
+                        return Ok(value);
+                    }
+                    // The same choice of type as con.out, whose text toStr returns
+                    let to_str = match Self::select_print_function(argument, builder.func.dfg.value_type(value), return_types) {
+                        "rono_print_string" => return Ok(value),
+                        "rono_print_float" => "rono_float_to_str",
+                        "rono_print_bool" => "rono_bool_to_str",
+                        _ => "rono_int_to_str",
+                    };
+                    let func_id = *functions.get(to_str)
+                        .ok_or_else(|| IRError::Generation(format!("Runtime function {} not found", to_str)))?;
+                    let func_ref = module.declare_func_in_func(func_id, builder.func);
+                    let call = builder.ins().call(func_ref, &[value]);
+                    Ok(builder.inst_results(call)[0])
+                } else if func_call.name == "intern" {
+                    if func_call.args.len() != 1 {
+                        return Err(IRError::Generation("intern expects 1 argument (s)".to_string()));
+                    }
+                    
+                    let value = Self::generate_expression_static(builder, &func_call.args[0], variables, functions, return_types, context, module)?;
+                    let func_id = *functions.get("rono_str_intern")
+                        .ok_or_else(|| IRError::Generation("Runtime function rono_str_intern not found".to_string()))?;
+                    let func_ref = module.declare_func_in_func(func_id, builder.func);
+                    let call = builder.ins().call(func_ref, &[value]);
+                    Ok(builder.inst_results(call)[0])
                 } else {
                     // Look up the function
                     if let Some(&func_id) = functions.get(&func_call.name) {
-                        // Generate arguments
+                        // Generate arguments in source order, then place them by parameter;
+                        // an int passed for a float parameter is converted
+                        let param_types: Vec<Type> = module.declarations().get_function_decl(func_id).signature.params.iter()
+                            .map(|param| param.value_type)
+                            .collect();
                         let mut args = Vec::new();
-                        for arg in &func_call.args {
-                            let arg_value = Self::generate_expression_static(builder, arg, variables, functions, module)?;
+                        let mut written_back = Vec::new();
+                        for (index, arg) in func_call.source_args().enumerate() {
+                            let index = positions.as_ref().map_or(index, |positions| positions[index]);
+                            let mut arg_value = Self::generate_expression_static(builder, arg, variables, functions, return_types, context, module)?;
+                            if param_types.get(index) == Some(&types::F64) && builder.func.dfg.value_type(arg_value) == types::I64 {
+                                arg_value = Self::promote_to_float(builder, arg, arg_value);
+                            }
                             args.push(arg_value);
+                            // `&x` points at a copy of the variable, so after the call
+                            // `&mut x` copies what the callee wrote back into `x`
+                            if let Expression::Reference(AddressOf { target, mutable: true }) = arg {
+                                if let Expression::Identifier(name) = &**target {
+                                    if let Some(&var) = variables.get(name) {
+                                        written_back.push((var, arg_value));
+                                    }
+                                }
+                            }
+                        }
+                        
+                        if let Some(positions) = &positions {
+                            args = in_parameter_order(args, positions);
                         }
                         
                         // Get function reference
@@ -700,6 +1869,12 @@ impl IRGenerator {
                         
                         // Make the call
                         let call_result = builder.ins().call(func_ref, &args);
+                        for (var, address) in written_back {
+                            let current = builder.use_var(var);
+                            let var_type = builder.func.dfg.value_type(current);
+                            let updated = builder.ins().load(var_type, MemFlags::new(), address, 0);
+                            builder.def_var(var, updated);
+                        }
                         
                         // Return the first result (if any)
                         let results = builder.inst_results(call_result);
@@ -715,6 +1890,19 @@ impl IRGenerator {
                 }
             }
             Expression::MethodCall(method_call) => {
+                // String methods are lowered to runtime calls by name
+                if Self::builtin_method_runtime(&method_call.method).is_some() {
+                    return Self::generate_builtin_method(builder, method_call, variables, functions, return_types, context, module);
+                }
+                
+                // These build or consume lists, which compiled code cannot represent yet
+                if matches!(method_call.method.as_str(), "chars" | "bytes" | "split" | "join") {
+                    return Err(IRError::UnsupportedFeature(format!(
+                        "Method '{}' needs list support, which is not compiled yet",
+                        method_call.method
+                    )));
+                }
+                
                 // Special handling for console output
                 if let Expression::Identifier(object_name) = &*method_call.object {
                     if object_name == "con" && method_call.method == "out" {
@@ -724,21 +1912,24 @@ impl IRGenerator {
                         
                         if method_call.args.len() == 1 {
                             // Simple output: con.out(value)
-                            let arg_value = Self::generate_expression_static(builder, &method_call.args[0], variables, functions, module)?;
+                            let arg_value = Self::generate_expression_static(builder, &method_call.args[0], variables, functions, return_types, context, module)?;
+                            
+                            // Call the runtime print function matching the argument type
+                            let value_type = builder.func.dfg.value_type(arg_value);
+                            let func_name = Self::select_print_function(&method_call.args[0], value_type, return_types);
                             
-                            // Call runtime print function
-                            if let Some(&print_func_id) = functions.get("rono_print_int") {
+                            if let Some(&print_func_id) = functions.get(func_name) {
                                 let func_ref = module.declare_func_in_func(print_func_id, builder.func);
                                 builder.ins().call(func_ref, &[arg_value]);
                                 // Return dummy value since con.out returns void
                                 Ok(builder.ins().iconst(types::I64, 0))
                             } else {
-                                Err(IRError::Generation("Runtime function rono_print_int not found".to_string()))
+                                Err(IRError::Generation(format!("Runtime function {} not found", func_name)))
                             }
                         } else if method_call.args.len() == 2 {
                             // Formatted output: con.out("Value: {}", value)
                             // For now, we'll ignore the format string and just use a default format
-                            let arg_value = Self::generate_expression_static(builder, &method_call.args[1], variables, functions, module)?;
+                            let arg_value = Self::generate_expression_static(builder, &method_call.args[1], variables, functions, return_types, context, module)?;
                             
                             // Call runtime format function with null format (uses default)
                             if let Some(&format_func_id) = functions.get("rono_print_format_int") {
@@ -758,21 +1949,27 @@ impl IRGenerator {
                             return Err(IRError::Generation("con.in expects no arguments".to_string()));
                         }
                         
-                        // Call runtime input function - for now assume integer input
-                        if let Some(&input_func_id) = functions.get("rono_input_int") {
+                        // The line comes back without its "\n" or "\r\n" terminator
+                        if let Some(&input_func_id) = functions.get("rono_input_string") {
                             let func_ref = module.declare_func_in_func(input_func_id, builder.func);
                             let result = builder.ins().call(func_ref, &[]);
                             Ok(builder.inst_results(result)[0])
                         } else {
-                            Err(IRError::Generation("Runtime function rono_input_int not found".to_string()))
+                            Err(IRError::Generation("Runtime function rono_input_string not found".to_string()))
                         }
 
+                    } else if let Some(method) = Self::http_request_method(&format!("http_{}", method_call.method))
+                        .filter(|_| object_name == "http" && context.lang_version >= LangVersion::V2)
+                    {
+                        // From language level 2 `http.get` and friends return HttpResponse like `http_get`
+                        let name = format!("http.{}", method_call.method);
+                        Self::generate_http_request(builder, &name, method, &method_call.args, variables, functions, return_types, context, module)
                     } else if object_name == "http" && method_call.method == "get" {
                         if method_call.args.len() != 1 {
                             return Err(IRError::Generation("http.get expects 1 argument (url)".to_string()));
                         }
                         
-                        let url_value = Self::generate_expression_static(builder, &method_call.args[0], variables, functions, module)?;
+                        let url_value = Self::generate_expression_static(builder, &method_call.args[0], variables, functions, return_types, context, module)?;
                         
                         if let Some(&http_func_id) = functions.get("rono_http_get") {
                             let func_ref = module.declare_func_in_func(http_func_id, builder.func);
@@ -786,8 +1983,8 @@ impl IRGenerator {
                             return Err(IRError::Generation("http.post expects 2 arguments (url, data)".to_string()));
                         }
                         
-                        let url_value = Self::generate_expression_static(builder, &method_call.args[0], variables, functions, module)?;
-                        let data_value = Self::generate_expression_static(builder, &method_call.args[1], variables, functions, module)?;
+                        let url_value = Self::generate_expression_static(builder, &method_call.args[0], variables, functions, return_types, context, module)?;
+                        let data_value = Self::generate_expression_static(builder, &method_call.args[1], variables, functions, return_types, context, module)?;
                         
                         if let Some(&http_func_id) = functions.get("rono_http_post") {
                             let func_ref = module.declare_func_in_func(http_func_id, builder.func);
@@ -801,8 +1998,8 @@ impl IRGenerator {
                             return Err(IRError::Generation("http.put expects 2 arguments (url, data)".to_string()));
                         }
                         
-                        let url_value = Self::generate_expression_static(builder, &method_call.args[0], variables, functions, module)?;
-                        let data_value = Self::generate_expression_static(builder, &method_call.args[1], variables, functions, module)?;
+                        let url_value = Self::generate_expression_static(builder, &method_call.args[0], variables, functions, return_types, context, module)?;
+                        let data_value = Self::generate_expression_static(builder, &method_call.args[1], variables, functions, return_types, context, module)?;
                         
                         if let Some(&http_func_id) = functions.get("rono_http_put") {
                             let func_ref = module.declare_func_in_func(http_func_id, builder.func);
@@ -816,7 +2013,7 @@ impl IRGenerator {
                             return Err(IRError::Generation("http.delete expects 1 argument (url)".to_string()));
                         }
                         
-                        let url_value = Self::generate_expression_static(builder, &method_call.args[0], variables, functions, module)?;
+                        let url_value = Self::generate_expression_static(builder, &method_call.args[0], variables, functions, return_types, context, module)?;
                         
                         if let Some(&http_func_id) = functions.get("rono_http_delete") {
                             let func_ref = module.declare_func_in_func(http_func_id, builder.func);
@@ -825,38 +2022,118 @@ impl IRGenerator {
                         } else {
                             Err(IRError::Generation("Runtime function rono_http_delete not found".to_string()))
                         }
+                    } else if (object_name == "os" || object_name == "rono") && !variables.contains_key(object_name) {
+                        let value = Self::platform_constant(method_call, module.isa().triple())
+                            .ok_or_else(|| IRError::Generation(format!("{}.{} is not a builtin without arguments", object_name, method_call.method)))?;
+                        Self::generate_string_constant(builder, module, &value)
+                    } else if object_name == "args" && !variables.contains_key(object_name) {
+                        let (runtime_name, arity) = match method_call.method.as_str() {
+                            "count" => ("rono_args_count", 0),
+                            "get" => ("rono_args_get", 1),
+                            "has_flag" => ("rono_args_has_flag", 1),
+                            "value_of" => ("rono_args_value_of", 1),
+                            method => return Err(IRError::Generation(format!("args has no method '{}'", method))),
+                        };
+                        if method_call.args.len() != arity {
+                            return Err(IRError::Generation(format!("args.{} expects {} argument(s)", method_call.method, arity)));
+                        }
+                        
+                        let mut args = Vec::new();
+                        for arg in &method_call.args {
+                            args.push(Self::generate_expression_static(builder, arg, variables, functions, return_types, context, module)?);
+                        }
+                        let func_id = *functions.get(runtime_name)
+                            .ok_or_else(|| IRError::Generation(format!("Runtime function {} not found", runtime_name)))?;
+                        let func_ref = module.declare_func_in_func(func_id, builder.func);
+                        let call = builder.ins().call(func_ref, &args);
+                        Ok(builder.inst_results(call)[0])
+                    } else if object_name == "mem" && (method_call.method == "reset" || method_call.method == "used") {
+                        if !method_call.args.is_empty() {
+                            return Err(IRError::Generation(format!("mem.{} expects no arguments", method_call.method)));
+                        }
+                        
+                        let runtime_name = if method_call.method == "reset" { "rono_mem_reset" } else { "rono_mem_used" };
+                        let func_id = *functions.get(runtime_name)
+                            .ok_or_else(|| IRError::Generation(format!("Runtime function {} not found", runtime_name)))?;
+                        let func_ref = module.declare_func_in_func(func_id, builder.func);
+                        let call = builder.ins().call(func_ref, &[]);
+                        // mem.reset returns nothing; like con.out, yield a dummy value
+                        match builder.inst_results(call).first().copied() {
+                            Some(used) => Ok(used),
+                            None => Ok(builder.ins().iconst(types::I64, 0)),
+                        }
+                    } else if let (false, Some(&func_id)) = (
+                        variables.contains_key(object_name),
+                        functions.get(&format!("{}_{}", object_name, method_call.method)),
+                    ) {
+                        // Associated function `Point.new(...)`: call the mangled function without self
+                        let mut args = Vec::new();
+                        for arg in &method_call.args {
+                            args.push(Self::generate_expression_static(builder, arg, variables, functions, return_types, context, module)?);
+                        }
+                        let func_ref = module.declare_func_in_func(func_id, builder.func);
+                        let call = builder.ins().call(func_ref, &args);
+                        match builder.inst_results(call).first().copied() {
+                            Some(result) => Ok(result),
+                            None => Ok(builder.ins().iconst(types::I64, 0)),
+                        }
+                    } else if method_call.method == "len" && method_call.args.is_empty()
+                        && matches!(context.variable_types.get(object_name), Some(ChifType::Array(..) | ChifType::List(..)))
+                    {
+                        Self::generate_list_len(builder, &method_call.object, variables, functions, return_types, context, module)
+                    } else if method_call.method == "unique"
+                        && matches!(context.variable_types.get(object_name), Some(ChifType::Array(..) | ChifType::List(..)))
+                    {
+                        Self::generate_list_unique(builder, &method_call.object, variables, functions, return_types, context, module)
+                    } else if matches!(method_call.method.as_str(), "add" | "addAt" | "del")
+                        && matches!(context.variable_types.get(object_name), Some(ChifType::Array(..) | ChifType::List(..)))
+                    {
+                        // Lists cannot grow here yet. Once they can, these must follow the
+                        // interpreter's call_mutable_method: every argument is evaluated
+                        // before the list changes, and the change applies to the list as
+                        // the arguments left it
+                        Err(IRError::UnsupportedFeature(format!(
+                            "List method '{}' is not supported in compiled code yet", method_call.method
+                        )))
                     } else {
                         // Handle struct method calls
-                        Self::generate_struct_method_call(builder, method_call, variables, functions, module)
+                        Self::generate_struct_method_call(builder, method_call, variables, functions, return_types, context, module)
                     }
+                } else if method_call.method == "unique" && method_call.args.is_empty() {
+                    Self::generate_list_unique(builder, &method_call.object, variables, functions, return_types, context, module)
+                } else if method_call.method == "len" && method_call.args.is_empty() && Self::is_unique_call(&method_call.object) {
+                    Self::generate_list_len(builder, &method_call.object, variables, functions, return_types, context, module)
                 } else {
                     // Handle struct method calls on complex expressions
-                    Self::generate_struct_method_call(builder, method_call, variables, functions, module)
+                    Self::generate_struct_method_call(builder, method_call, variables, functions, return_types, context, module)
                 }
             }
             Expression::StructLiteral(struct_literal) => {
                 // Allocate memory for the struct
-                Self::generate_struct_instantiation(builder, struct_literal, variables, functions, module)
+                Self::generate_struct_instantiation(builder, struct_literal, variables, functions, return_types, context, module)
             }
             Expression::FieldAccess(field_access) => {
                 // Generate field access
-                Self::generate_field_access(builder, field_access, variables, functions, module)
+                Self::generate_field_access(builder, field_access, variables, functions, return_types, context, module)
             }
             Expression::ArrayLiteral(elements) => {
                 // Generate array literal
-                Self::generate_array_literal(builder, elements, variables, functions, module)
+                Self::generate_array_literal(builder, elements, false, variables, functions, return_types, context, module)
+            }
+            Expression::TupleLiteral(_) => {
+                Err(IRError::UnsupportedFeature("Tuple values can only be returned or destructured".to_string()))
             }
             Expression::Index(index_access) => {
                 // Generate array indexing
-                Self::generate_array_index(builder, index_access, variables, functions, module)
+                Self::generate_array_index(builder, index_access, variables, functions, return_types, context, module)
             }
-            Expression::Reference(expr) => {
+            Expression::Reference(address_of) => {
                 // Generate address-of operation (&expr)
-                Self::generate_address_of(builder, expr, variables, functions, module)
+                Self::generate_address_of(builder, &address_of.target, variables, functions, return_types, context, module)
             }
             Expression::Dereference(expr) => {
                 // Generate dereference operation (*expr)
-                Self::generate_dereference(builder, expr, variables, functions, module)
+                Self::generate_dereference(builder, expr, variables, functions, return_types, context, module)
             }
             _ => {
                 Err(IRError::UnsupportedFeature(format!("Expression type not yet supported: {:?}", expression)))
@@ -864,18 +2141,15 @@ impl IRGenerator {
         }
     }
     
-    fn generate_literal(builder: &mut FunctionBuilder, value: &ChifValue) -> Result<Value, IRError> {
+    fn generate_literal(builder: &mut FunctionBuilder, module: &mut BodyModule, value: &ChifValue) -> Result<Value, IRError> {
         match value {
             ChifValue::Int(i) => Ok(builder.ins().iconst(types::I64, *i)),
             ChifValue::Float(f) => Ok(builder.ins().f64const(*f)),
             ChifValue::Bool(b) => Ok(builder.ins().iconst(types::I8, if *b { 1 } else { 0 })),
-            ChifValue::Nil => Ok(builder.ins().iconst(types::I64, 0)), // Represent nil as 0
-            ChifValue::Str(s) => {
-                // Create string constant in memory
-                // For now, we need to handle this differently since we can't access self.module here
-                // Let's use a simpler approach - create string on stack
-                Self::generate_string_on_stack(builder, s)
-            }
+            ChifValue::Nil | ChifValue::Void => Ok(builder.ins().iconst(types::I64, 0)), // Represent nil as 0
+            // Literals live in read-only data, so a string returned from the
+            // function that created it stays valid
+            ChifValue::Str(s) => Self::generate_string_constant(builder, module, s),
             ChifValue::Array(_) => {
                 // TODO: Implement array literal support
                 Err(IRError::UnsupportedFeature("Array literals not yet supported".to_string()))
@@ -900,6 +2174,9 @@ impl IRGenerator {
                 // TODO: Implement reference literal support
                 Err(IRError::UnsupportedFeature("Reference literals not yet supported".to_string()))
             }
+            ChifValue::Tuple(_) => {
+                Err(IRError::UnsupportedFeature("Tuple values can only be returned or destructured".to_string()))
+            }
         }
     }
     
@@ -915,7 +2192,7 @@ impl IRGenerator {
             ChifType::Array(_element_type, _dimensions) => Ok(types::I64), // Array as pointer for now
             ChifType::List(_element_type, _dimensions) => Ok(types::I64), // List as pointer for now
             ChifType::Map(_key_type, _value_type) => Ok(types::I64), // Map as pointer for now
-            _ => Err(IRError::TypeConversion(format!("Type conversion not yet supported: {:?}", chif_type))),
+            ChifType::Tuple(_) => Err(IRError::TypeConversion(format!("Type conversion not yet supported: {:?}", chif_type))),
         }
     }
     
@@ -934,8 +2211,9 @@ impl IRGenerator {
             (ChifValue::Int(a), BinaryOperator::Add, ChifValue::Int(b)) => Some(ChifValue::Int(a + b)),
             (ChifValue::Int(a), BinaryOperator::Subtract, ChifValue::Int(b)) => Some(ChifValue::Int(a - b)),
             (ChifValue::Int(a), BinaryOperator::Multiply, ChifValue::Int(b)) => Some(ChifValue::Int(a * b)),
-            (ChifValue::Int(a), BinaryOperator::Divide, ChifValue::Int(b)) if *b != 0 => Some(ChifValue::Int(a / b)),
-            (ChifValue::Int(a), BinaryOperator::Modulo, ChifValue::Int(b)) if *b != 0 => Some(ChifValue::Int(a % b)),
+            // Division by zero and i64::MIN / -1 are left to the runtime, which reports them
+            (ChifValue::Int(a), BinaryOperator::Divide, ChifValue::Int(b)) => a.checked_div(*b).map(ChifValue::Int),
+            (ChifValue::Int(a), BinaryOperator::Modulo, ChifValue::Int(b)) => a.checked_rem(*b).map(ChifValue::Int),
             
             // Integer comparisons
             (ChifValue::Int(a), BinaryOperator::Equal, ChifValue::Int(b)) => Some(ChifValue::Bool(a == b)),
@@ -950,6 +2228,7 @@ impl IRGenerator {
             (ChifValue::Float(a), BinaryOperator::Subtract, ChifValue::Float(b)) => Some(ChifValue::Float(a - b)),
             (ChifValue::Float(a), BinaryOperator::Multiply, ChifValue::Float(b)) => Some(ChifValue::Float(a * b)),
             (ChifValue::Float(a), BinaryOperator::Divide, ChifValue::Float(b)) if *b != 0.0 => Some(ChifValue::Float(a / b)),
+            (ChifValue::Float(a), BinaryOperator::Modulo, ChifValue::Float(b)) if *b != 0.0 => Some(ChifValue::Float(a % b)),
             
             // Float comparisons
             (ChifValue::Float(a), BinaryOperator::Equal, ChifValue::Float(b)) => Some(ChifValue::Bool((a - b).abs() < f64::EPSILON)),
@@ -975,60 +2254,42 @@ impl IRGenerator {
             (ChifValue::Bool(a), BinaryOperator::Equal, ChifValue::Bool(b)) => Some(ChifValue::Bool(a == b)),
             (ChifValue::Bool(a), BinaryOperator::NotEqual, ChifValue::Bool(b)) => Some(ChifValue::Bool(a != b)),
             
-            // String concatenation
+            // String concatenation and comparison
             (ChifValue::Str(a), BinaryOperator::Add, ChifValue::Str(b)) => Some(ChifValue::Str(format!("{}{}", a, b))),
+            (ChifValue::Str(a), BinaryOperator::Equal, ChifValue::Str(b)) => Some(ChifValue::Bool(a == b)),
+            (ChifValue::Str(a), BinaryOperator::NotEqual, ChifValue::Str(b)) => Some(ChifValue::Bool(a != b)),
             
             _ => None, // No folding possible
         }
     }
     
-    fn block_ends_with_return(block: &crate::ast::Block) -> bool {
-        for stmt in &block.statements {
-            match stmt {
-                Statement::Return(_) => return true,
-                Statement::If(if_stmt) => {
-                    // If both branches return, then the if statement returns
-                    if Self::block_ends_with_return(&if_stmt.then_block) {
-                        if let Some(else_block) = &if_stmt.else_block {
-                            if Self::block_ends_with_return(else_block) {
-                                return true;
-                            }
-                        }
-                    }
-                }
-                _ => {}
-            }
-        }
-        false
-    }
-    
     fn declare_runtime_functions(&mut self) -> Result<(), IRError> {
         // Declare rono_print_int(i64) -> void
         let mut print_int_sig = self.module.make_signature();
         print_int_sig.params.push(AbiParam::new(types::I64));
         let print_int_id = self.module.declare_function("rono_print_int", Linkage::Import, &print_int_sig)
-            .map_err(|e| IRError::Module(e))?;
+            .map_err(IRError::from)?;
         self.functions.insert("rono_print_int".to_string(), print_int_id);
         
         // Declare rono_print_float(f64) -> void
         let mut print_float_sig = self.module.make_signature();
         print_float_sig.params.push(AbiParam::new(types::F64));
         let print_float_id = self.module.declare_function("rono_print_float", Linkage::Import, &print_float_sig)
-            .map_err(|e| IRError::Module(e))?;
+            .map_err(IRError::from)?;
         self.functions.insert("rono_print_float".to_string(), print_float_id);
         
         // Declare rono_print_bool(i8) -> void
         let mut print_bool_sig = self.module.make_signature();
         print_bool_sig.params.push(AbiParam::new(types::I8));
         let print_bool_id = self.module.declare_function("rono_print_bool", Linkage::Import, &print_bool_sig)
-            .map_err(|e| IRError::Module(e))?;
+            .map_err(IRError::from)?;
         self.functions.insert("rono_print_bool".to_string(), print_bool_id);
         
         // Declare rono_print_string(const char*) -> void
         let mut print_string_sig = self.module.make_signature();
         print_string_sig.params.push(AbiParam::new(types::I64)); // String as pointer
         let print_string_id = self.module.declare_function("rono_print_string", Linkage::Import, &print_string_sig)
-            .map_err(|e| IRError::Module(e))?;
+            .map_err(IRError::from)?;
         self.functions.insert("rono_print_string".to_string(), print_string_id);
         
         // Declare rono_print_format_int(const char*, i64) -> void for interpolation
@@ -1036,7 +2297,7 @@ impl IRGenerator {
         print_format_sig.params.push(AbiParam::new(types::I64)); // Format string as pointer
         print_format_sig.params.push(AbiParam::new(types::I64)); // Value
         let print_format_id = self.module.declare_function("rono_print_format_int", Linkage::Import, &print_format_sig)
-            .map_err(|e| IRError::Module(e))?;
+            .map_err(IRError::from)?;
         self.functions.insert("rono_print_format_int".to_string(), print_format_id);
         
         // Declare console input functions
@@ -1044,28 +2305,28 @@ impl IRGenerator {
         let mut input_string_sig = self.module.make_signature();
         input_string_sig.returns.push(AbiParam::new(types::I64)); // String as pointer
         let input_string_id = self.module.declare_function("rono_input_string", Linkage::Import, &input_string_sig)
-            .map_err(|e| IRError::Module(e))?;
+            .map_err(IRError::from)?;
         self.functions.insert("rono_input_string".to_string(), input_string_id);
         
         // rono_input_int() -> i64
         let mut input_int_sig = self.module.make_signature();
         input_int_sig.returns.push(AbiParam::new(types::I64));
         let input_int_id = self.module.declare_function("rono_input_int", Linkage::Import, &input_int_sig)
-            .map_err(|e| IRError::Module(e))?;
+            .map_err(IRError::from)?;
         self.functions.insert("rono_input_int".to_string(), input_int_id);
         
         // rono_input_float() -> f64
         let mut input_float_sig = self.module.make_signature();
         input_float_sig.returns.push(AbiParam::new(types::F64));
         let input_float_id = self.module.declare_function("rono_input_float", Linkage::Import, &input_float_sig)
-            .map_err(|e| IRError::Module(e))?;
+            .map_err(IRError::from)?;
         self.functions.insert("rono_input_float".to_string(), input_float_id);
         
         // rono_input_bool() -> i8
         let mut input_bool_sig = self.module.make_signature();
         input_bool_sig.returns.push(AbiParam::new(types::I8));
         let input_bool_id = self.module.declare_function("rono_input_bool", Linkage::Import, &input_bool_sig)
-            .map_err(|e| IRError::Module(e))?;
+            .map_err(IRError::from)?;
         self.functions.insert("rono_input_bool".to_string(), input_bool_id);
         
         // Declare random number generation functions
@@ -1075,7 +2336,7 @@ impl IRGenerator {
         rand_int_sig.params.push(AbiParam::new(types::I64)); // max
         rand_int_sig.returns.push(AbiParam::new(types::I64));
         let rand_int_id = self.module.declare_function("rono_rand_int", Linkage::Import, &rand_int_sig)
-            .map_err(|e| IRError::Module(e))?;
+            .map_err(IRError::from)?;
         self.functions.insert("rono_rand_int".to_string(), rand_int_id);
         
         // rono_rand_float(f64, f64) -> f64
@@ -1084,7 +2345,7 @@ impl IRGenerator {
         rand_float_sig.params.push(AbiParam::new(types::F64)); // max
         rand_float_sig.returns.push(AbiParam::new(types::F64));
         let rand_float_id = self.module.declare_function("rono_rand_float", Linkage::Import, &rand_float_sig)
-            .map_err(|e| IRError::Module(e))?;
+            .map_err(IRError::from)?;
         self.functions.insert("rono_rand_float".to_string(), rand_float_id);
         
         // rono_rand_string(i64) -> char*
@@ -1092,7 +2353,7 @@ impl IRGenerator {
         rand_string_sig.params.push(AbiParam::new(types::I64)); // length
         rand_string_sig.returns.push(AbiParam::new(types::I64)); // String as pointer
         let rand_string_id = self.module.declare_function("rono_rand_string", Linkage::Import, &rand_string_sig)
-            .map_err(|e| IRError::Module(e))?;
+            .map_err(IRError::from)?;
         self.functions.insert("rono_rand_string".to_string(), rand_string_id);
         
         // rono_rand_char_range(const char*, const char*) -> char*
@@ -1101,16 +2362,201 @@ impl IRGenerator {
         rand_char_range_sig.params.push(AbiParam::new(types::I64)); // to as pointer
         rand_char_range_sig.returns.push(AbiParam::new(types::I64)); // String as pointer
         let rand_char_range_id = self.module.declare_function("rono_rand_char_range", Linkage::Import, &rand_char_range_sig)
-            .map_err(|e| IRError::Module(e))?;
+            .map_err(IRError::from)?;
         self.functions.insert("rono_rand_char_range".to_string(), rand_char_range_id);
         
+        // rono_rand_unit() -> f64 in [0.0, 1.0)
+        let mut rand_unit_sig = self.module.make_signature();
+        rand_unit_sig.returns.push(AbiParam::new(types::F64));
+        let rand_unit_id = self.module.declare_function("rono_rand_unit", Linkage::Import, &rand_unit_sig)
+            .map_err(IRError::from)?;
+        self.functions.insert("rono_rand_unit".to_string(), rand_unit_id);
+        
+        // rono_rand_seed(i64) -> void
+        let mut rand_seed_sig = self.module.make_signature();
+        rand_seed_sig.params.push(AbiParam::new(types::I64)); // seed
+        let rand_seed_id = self.module.declare_function("rono_rand_seed", Linkage::Import, &rand_seed_sig)
+            .map_err(IRError::from)?;
+        self.functions.insert("rono_rand_seed".to_string(), rand_seed_id);
+        
+        // rono_list_unique(i64 ptr, i64 len, i64 elem_type) -> i64 (new buffer, its count stored just before it)
+        let mut list_unique_sig = self.module.make_signature();
+        list_unique_sig.params.push(AbiParam::new(types::I64)); // ptr
+        list_unique_sig.params.push(AbiParam::new(types::I64)); // len
+        list_unique_sig.params.push(AbiParam::new(types::I64)); // elem_type
+        list_unique_sig.returns.push(AbiParam::new(types::I64));
+        let list_unique_id = self.module.declare_function("rono_list_unique", Linkage::Import, &list_unique_sig)
+            .map_err(IRError::from)?;
+        self.functions.insert("rono_list_unique".to_string(), list_unique_id);
+        
+        // rono_list_len(i64 ptr) -> i64, the length of a list rono_list_unique built
+        let mut list_len_sig = self.module.make_signature();
+        list_len_sig.params.push(AbiParam::new(types::I64)); // ptr
+        list_len_sig.returns.push(AbiParam::new(types::I64));
+        let list_len_id = self.module.declare_function("rono_list_len", Linkage::Import, &list_len_sig)
+            .map_err(IRError::from)?;
+        self.functions.insert("rono_list_len".to_string(), list_len_id);
+        
+        // `in`: rono_list_contains(i64 ptr, i64 len, i64 value, i64 elem_type) -> i8,
+        // rono_str_contains(ptr, ptr) -> i8
+        let membership_functions: [(&str, &[Type]); 2] = [
+            ("rono_list_contains", &[types::I64, types::I64, types::I64, types::I64]),
+            ("rono_str_contains", &[types::I64, types::I64]),
+        ];
+        for (name, params) in membership_functions {
+            let mut sig = self.module.make_signature();
+            sig.params.extend(params.iter().map(|&param| AbiParam::new(param)));
+            sig.returns.push(AbiParam::new(types::I8));
+            let func_id = self.module.declare_function(name, Linkage::Import, &sig)
+                .map_err(IRError::from)?;
+            self.functions.insert(name.to_string(), func_id);
+        }
+        
+        // rono_fmod(f64, f64) -> f64 for float remainder
+        let mut fmod_sig = self.module.make_signature();
+        fmod_sig.params.push(AbiParam::new(types::F64));
+        fmod_sig.params.push(AbiParam::new(types::F64));
+        fmod_sig.returns.push(AbiParam::new(types::F64));
+        let fmod_id = self.module.declare_function("rono_fmod", Linkage::Import, &fmod_sig)
+            .map_err(IRError::from)?;
+        self.functions.insert("rono_fmod".to_string(), fmod_id);
+        
+        // rono_int_div(i64, i64) -> i64, truncating division that reports a zero divisor
+        // and i64::MIN / -1 instead of trapping
+        let mut int_div_sig = self.module.make_signature();
+        int_div_sig.params.push(AbiParam::new(types::I64));
+        int_div_sig.params.push(AbiParam::new(types::I64));
+        int_div_sig.returns.push(AbiParam::new(types::I64));
+        let int_div_id = self.module.declare_function("rono_int_div", Linkage::Import, &int_div_sig)
+            .map_err(IRError::from)?;
+        self.functions.insert("rono_int_div".to_string(), int_div_id);
+        
+        // rono_string_concat(ptr, ptr) -> ptr, a new string allocated by the runtime
+        let mut string_concat_sig = self.module.make_signature();
+        string_concat_sig.params.push(AbiParam::new(types::I64));
+        string_concat_sig.params.push(AbiParam::new(types::I64));
+        string_concat_sig.returns.push(AbiParam::new(types::I64));
+        let string_concat_id = self.module.declare_function("rono_string_concat", Linkage::Import, &string_concat_sig)
+            .map_err(IRError::from)?;
+        self.functions.insert("rono_string_concat".to_string(), string_concat_id);
+        
+        // rono_range_check_step(i64) stops the program on a zero range() step
+        let mut range_check_sig = self.module.make_signature();
+        range_check_sig.params.push(AbiParam::new(types::I64));
+        let range_check_id = self.module.declare_function("rono_range_check_step", Linkage::Import, &range_check_sig)
+            .map_err(IRError::from)?;
+        self.functions.insert("rono_range_check_step".to_string(), range_check_id);
+        
+        // rono_str_equal(ptr, ptr) -> i8 compares string contents (== and switch over str)
+        let mut str_equal_sig = self.module.make_signature();
+        str_equal_sig.params.push(AbiParam::new(types::I64));
+        str_equal_sig.params.push(AbiParam::new(types::I64));
+        str_equal_sig.returns.push(AbiParam::new(types::I8));
+        let str_equal_id = self.module.declare_function("rono_str_equal", Linkage::Import, &str_equal_sig)
+            .map_err(IRError::from)?;
+        self.functions.insert("rono_str_equal".to_string(), str_equal_id);
+        
+        // rono_copy_data(ptr, size) -> ptr, an arena copy of a large array literal's template
+        let mut copy_data_sig = self.module.make_signature();
+        copy_data_sig.params.push(AbiParam::new(types::I64));
+        copy_data_sig.params.push(AbiParam::new(types::I64));
+        copy_data_sig.returns.push(AbiParam::new(types::I64));
+        let copy_data_id = self.module.declare_function("rono_copy_data", Linkage::Import, &copy_data_sig)
+            .map_err(IRError::from)?;
+        self.functions.insert("rono_copy_data".to_string(), copy_data_id);
+        
+        // rono_alloc_struct(size) -> ptr in the runtime arena, for structs returned from functions
+        let mut alloc_struct_sig = self.module.make_signature();
+        alloc_struct_sig.params.push(AbiParam::new(types::I64));
+        alloc_struct_sig.returns.push(AbiParam::new(types::I64));
+        let alloc_struct_id = self.module.declare_function("rono_alloc_struct", Linkage::Import, &alloc_struct_sig)
+            .map_err(IRError::from)?;
+        self.functions.insert("rono_alloc_struct".to_string(), alloc_struct_id);
+        
+        // Runtime arena: rono_mem_reset() frees every string and list returned so far,
+        // rono_mem_used() -> i64 reports the bytes it holds
+        let mem_reset_sig = self.module.make_signature();
+        let mem_reset_id = self.module.declare_function("rono_mem_reset", Linkage::Import, &mem_reset_sig)
+            .map_err(IRError::from)?;
+        self.functions.insert("rono_mem_reset".to_string(), mem_reset_id);
+        
+        let mut mem_used_sig = self.module.make_signature();
+        mem_used_sig.returns.push(AbiParam::new(types::I64));
+        let mem_used_id = self.module.declare_function("rono_mem_used", Linkage::Import, &mem_used_sig)
+            .map_err(IRError::from)?;
+        self.functions.insert("rono_mem_used".to_string(), mem_used_id);
+        
+        // String methods: rono_str_char_count(ptr) -> i64, rono_str_byte_len(ptr) -> i64,
+        // rono_str_index_of(ptr, ptr) -> i64 (character position or -1)
+        // Int formatting: rono_int_to_hex(i64) -> ptr, rono_int_to_hex_lower(i64) -> ptr,
+        // rono_int_to_binary(i64) -> ptr
+        // intern(s): rono_str_intern(ptr) -> ptr of the canonical copy
+        // toStr(x): rono_int_to_str(i64), rono_float_to_str(f64), rono_bool_to_str(i8) -> ptr
+        let runtime_methods = [
+            ("rono_str_char_count", 1), ("rono_str_byte_len", 1), ("rono_str_index_of", 2), ("rono_str_trim", 1),
+            ("rono_str_intern", 1),
+            ("rono_int_to_hex", 1), ("rono_int_to_hex_lower", 1), ("rono_int_to_binary", 1),
+            ("rono_int_to_str", 1),
+        ];
+        for (name, param_count) in runtime_methods {
+            let mut sig = self.module.make_signature();
+            for _ in 0..param_count {
+                sig.params.push(AbiParam::new(types::I64));
+            }
+            sig.returns.push(AbiParam::new(types::I64));
+            let func_id = self.module.declare_function(name, Linkage::Import, &sig)
+                .map_err(IRError::from)?;
+            self.functions.insert(name.to_string(), func_id);
+        }
+        for (name, param_type) in [("rono_float_to_str", types::F64), ("rono_bool_to_str", types::I8)] {
+            let mut sig = self.module.make_signature();
+            sig.params.push(AbiParam::new(param_type));
+            sig.returns.push(AbiParam::new(types::I64));
+            let func_id = self.module.declare_function(name, Linkage::Import, &sig)
+                .map_err(IRError::from)?;
+            self.functions.insert(name.to_string(), func_id);
+        }
+        
+        // Command-line arguments: main passes argc and argv to rono_args_init(i32, ptr);
+        // rono_args_count() -> i64, rono_args_get(i64) -> ptr,
+        // rono_args_has_flag(ptr) -> i8, rono_args_value_of(ptr) -> ptr
+        let args_functions: [(&str, &[Type], Option<Type>); 5] = [
+            ("rono_args_init", &[types::I32, types::I64], None),
+            ("rono_args_count", &[], Some(types::I64)),
+            ("rono_args_get", &[types::I64], Some(types::I64)),
+            ("rono_args_has_flag", &[types::I64], Some(types::I8)),
+            ("rono_args_value_of", &[types::I64], Some(types::I64)),
+        ];
+        for (name, params, returns) in args_functions {
+            let mut sig = self.module.make_signature();
+            sig.params.extend(params.iter().map(|&param| AbiParam::new(param)));
+            sig.returns.extend(returns.map(AbiParam::new));
+            let func_id = self.module.declare_function(name, Linkage::Import, &sig)
+                .map_err(IRError::from)?;
+            self.functions.insert(name.to_string(), func_id);
+        }
+        
+        // Return types of the random builtins as seen from Rono code
+        self.function_return_types.insert("randi".to_string(), ChifType::Int);
+        self.function_return_types.insert("randf".to_string(), ChifType::Float);
+        self.function_return_types.insert("rands".to_string(), ChifType::Str);
+        self.function_return_types.insert("rando".to_string(), ChifType::Float);
+        self.function_return_types.insert("rand_seed".to_string(), ChifType::Nil);
+        self.function_return_types.insert("intern".to_string(), ChifType::Str);
+        self.function_return_types.insert("fdiv".to_string(), ChifType::Float);
+        for name in ["wrapping_add", "wrapping_sub", "wrapping_mul", "saturating_add", "saturating_sub"] {
+            self.function_return_types.insert(name.to_string(), ChifType::Int);
+        }
+        self.function_return_types.insert("checked_add".to_string(), ChifType::Tuple(vec![ChifType::Int, ChifType::Bool]));
+        self.function_return_types.insert("toStr".to_string(), ChifType::Str);
+        
         // Declare HTTP functions
         // rono_http_get(const char*) -> char*
         let mut http_get_sig = self.module.make_signature();
         http_get_sig.params.push(AbiParam::new(types::I64)); // URL as pointer
         http_get_sig.returns.push(AbiParam::new(types::I64)); // Response as pointer
         let http_get_id = self.module.declare_function("rono_http_get", Linkage::Import, &http_get_sig)
-            .map_err(|e| IRError::Module(e))?;
+            .map_err(IRError::from)?;
         self.functions.insert("rono_http_get".to_string(), http_get_id);
         
         // rono_http_post(const char*, const char*) -> char*
@@ -1119,7 +2565,7 @@ impl IRGenerator {
         http_post_sig.params.push(AbiParam::new(types::I64)); // Data as pointer
         http_post_sig.returns.push(AbiParam::new(types::I64)); // Response as pointer
         let http_post_id = self.module.declare_function("rono_http_post", Linkage::Import, &http_post_sig)
-            .map_err(|e| IRError::Module(e))?;
+            .map_err(IRError::from)?;
         self.functions.insert("rono_http_post".to_string(), http_post_id);
         
         // rono_http_put(const char*, const char*) -> char*
@@ -1128,7 +2574,7 @@ impl IRGenerator {
         http_put_sig.params.push(AbiParam::new(types::I64)); // Data as pointer
         http_put_sig.returns.push(AbiParam::new(types::I64)); // Response as pointer
         let http_put_id = self.module.declare_function("rono_http_put", Linkage::Import, &http_put_sig)
-            .map_err(|e| IRError::Module(e))?;
+            .map_err(IRError::from)?;
         self.functions.insert("rono_http_put".to_string(), http_put_id);
         
         // rono_http_delete(const char*) -> char*
@@ -1136,68 +2582,50 @@ impl IRGenerator {
         http_delete_sig.params.push(AbiParam::new(types::I64)); // URL as pointer
         http_delete_sig.returns.push(AbiParam::new(types::I64)); // Response as pointer
         let http_delete_id = self.module.declare_function("rono_http_delete", Linkage::Import, &http_delete_sig)
-            .map_err(|e| IRError::Module(e))?;
+            .map_err(IRError::from)?;
         self.functions.insert("rono_http_delete".to_string(), http_delete_id);
-
+        
+        // rono_http_request(method, const char* url, const char* body) -> RonoHttpResponse*
+        let mut http_request_sig = self.module.make_signature();
+        http_request_sig.params.push(AbiParam::new(types::I64)); // Method code
+        http_request_sig.params.push(AbiParam::new(types::I64)); // URL as pointer
+        http_request_sig.params.push(AbiParam::new(types::I64)); // Body as pointer, 0 for none
+        http_request_sig.returns.push(AbiParam::new(types::I64)); // HttpResponse as pointer
+        let http_request_id = self.module.declare_function("rono_http_request", Linkage::Import, &http_request_sig)
+            .map_err(IRError::from)?;
+        self.functions.insert("rono_http_request".to_string(), http_request_id);
+        let http_response = crate::semantic::http_response_struct();
+        for name in ["http_get", "http_post", "http_put", "http_delete"] {
+            self.function_return_types.insert(name.to_string(), ChifType::Struct(http_response.name.clone()));
+        }
+        self.context.structs.insert(http_response.name.clone(), StructLayout::compute(&http_response)?);
         
         Ok(())
     }
 
     fn process_struct_definition(&mut self, struct_def: &StructDef) -> Result<(), IRError> {
-        // Calculate struct layout and field offsets
-        let mut fields = Vec::new();
-        let mut current_offset = 0u32;
-        let mut max_alignment = 1u32;
-        
-        for field in &struct_def.fields {
-            let field_size = Self::get_type_size(&field.field_type)?;
-            let field_alignment = Self::get_type_alignment(&field.field_type)?;
-            
-            // Update maximum alignment
-            max_alignment = max_alignment.max(field_alignment);
-            
-            // Align current offset to field alignment
-            current_offset = Self::align_to(current_offset, field_alignment);
-            
-            fields.push(StructFieldLayout {
-                name: field.name.clone(),
-                field_type: field.field_type.clone(),
-                offset: current_offset,
-                size: field_size,
-            });
-            
-            current_offset += field_size;
-        }
-        
-        // Align total size to struct alignment
-        let total_size = Self::align_to(current_offset, max_alignment);
-        
-        let layout = StructLayout {
-            name: struct_def.name.clone(),
-            fields,
-            size: total_size,
-            alignment: max_alignment,
-        };
-        
-        self.structs.insert(struct_def.name.clone(), layout);
+        let layout = StructLayout::compute(struct_def)?;
+        self.context.structs.insert(struct_def.name.clone(), layout);
         
         Ok(())
     }
-
+    
     fn get_type_size(chif_type: &ChifType) -> Result<u32, IRError> {
         match chif_type {
             ChifType::Int => Ok(8),      // i64
             ChifType::Float => Ok(8),    // f64
             ChifType::Bool => Ok(1),     // i8
             ChifType::Str => Ok(8),      // pointer
-            ChifType::Nil => Ok(0),
             ChifType::Pointer(_) => Ok(8), // pointer size
             ChifType::Struct(name) => {
                 // For now, return a placeholder size
                 // In a full implementation, we would look up the struct size
                 Ok(16) // placeholder
             }
-            _ => Err(IRError::UnsupportedFeature(format!("Type size calculation not implemented for: {:?}", chif_type))),
+            // Collections are held by pointer, whatever their element types
+            ChifType::Array(..) | ChifType::List(..) | ChifType::Map(..) => Ok(8),
+            // The analyzer rejects nil fields
+            ChifType::Nil | ChifType::Tuple(_) => Err(IRError::UnsupportedFeature(format!("Type size calculation not implemented for: {}", chif_type))),
         }
     }
     
@@ -1207,10 +2635,10 @@ impl IRGenerator {
             ChifType::Float => Ok(8),    // f64 alignment
             ChifType::Bool => Ok(1),     // i8 alignment
             ChifType::Str => Ok(8),      // pointer alignment
-            ChifType::Nil => Ok(1),
             ChifType::Pointer(_) => Ok(8), // pointer alignment
             ChifType::Struct(_) => Ok(8),  // struct alignment (max field alignment)
-            _ => Err(IRError::UnsupportedFeature(format!("Type alignment calculation not implemented for: {:?}", chif_type))),
+            ChifType::Array(..) | ChifType::List(..) | ChifType::Map(..) => Ok(8), // pointer alignment
+            ChifType::Nil | ChifType::Tuple(_) => Err(IRError::UnsupportedFeature(format!("Type alignment calculation not implemented for: {}", chif_type))),
         }
     }
     
@@ -1223,36 +2651,42 @@ impl IRGenerator {
         struct_literal: &StructLiteral,
         variables: &HashMap<String, Variable>,
         functions: &HashMap<String, cranelift_module::FuncId>,
-        module: &mut ObjectModule
+        return_types: &HashMap<String, ChifType>,
+        context: &mut CodegenContext,
+        module: &mut BodyModule
     ) -> Result<Value, IRError> {
-        // For now, we'll implement a simple version that allocates memory on the stack
-        // In a full implementation, we would:
-        // 1. Look up the struct layout
-        // 2. Allocate memory (stack or heap)
-        // 3. Initialize fields with provided values
-        // 4. Return pointer to the struct
+        // Fields go to the offsets of the struct layout, whatever order the literal lists them in
+        let layout = context.structs.get(&struct_literal.struct_name).cloned()
+            .ok_or_else(|| IRError::Generation(format!("Unknown struct '{}'", struct_literal.struct_name)))?;
         
-        // For this implementation, we'll create a simple struct representation
-        // We'll allocate space for each field and store them sequentially
-        
-        // Calculate total size needed (simplified - assume each field is 8 bytes)
-        let field_count = struct_literal.fields.len() as i64;
-        let total_size = field_count * 8; // 8 bytes per field
-        
-        // Allocate stack space (simplified approach)
+        // Allocate stack space
         let stack_slot = builder.create_sized_stack_slot(StackSlotData::new(
             StackSlotKind::ExplicitSlot,
-            total_size as u32,
+            layout.size,
         ));
         
         // Get pointer to the allocated memory
         let struct_ptr = builder.ins().stack_addr(types::I64, stack_slot, 0);
         
-        // Initialize fields
-        for (i, (field_name, field_expr)) in struct_literal.fields.iter().enumerate() {
-            let field_value = Self::generate_expression_static(builder, field_expr, variables, functions, module)?;
-            let offset = (i * 8) as i32; // 8 bytes per field
-            builder.ins().store(MemFlags::new(), field_value, struct_ptr, offset);
+        // Evaluate the listed fields first, as the interpreter does
+        let mut values = Vec::with_capacity(struct_literal.fields.len());
+        for (field_name, field_expr) in &struct_literal.fields {
+            let field = layout.field(field_name)
+                .ok_or_else(|| IRError::Generation(format!("Struct '{}' has no field '{}'", layout.name, field_name)))?;
+            let mut field_value = Self::generate_expression_static(builder, field_expr, variables, functions, return_types, context, module)?;
+            if field.field_type == ChifType::Float {
+                field_value = Self::promote_to_float(builder, field_expr, field_value);
+            }
+            values.push((field.offset as i32, field_value));
+        }
+        
+        // `..base` fills the whole slot, then the listed fields override it
+        if let Some(base) = &struct_literal.base {
+            let base_ptr = Self::generate_struct_address(builder, base, variables, functions, return_types, context, module)?;
+            Self::copy_struct_fields(builder, &layout, base_ptr, struct_ptr, 0);
+        }
+        for (offset, value) in values {
+            builder.ins().store(MemFlags::new(), value, struct_ptr, offset);
         }
         
         // Return pointer to the struct
@@ -1264,95 +2698,345 @@ impl IRGenerator {
         field_access: &FieldAccess,
         variables: &HashMap<String, Variable>,
         functions: &HashMap<String, cranelift_module::FuncId>,
-        module: &mut ObjectModule
+        return_types: &HashMap<String, ChifType>,
+        context: &mut CodegenContext,
+        module: &mut BodyModule
     ) -> Result<Value, IRError> {
-        // Generate the object expression (should be a struct pointer)
-        let struct_ptr = Self::generate_expression_static(builder, &field_access.object, variables, functions, module)?;
-        
-        // For now, we'll use a simple field offset calculation
-        // In a full implementation, we would:
-        // 1. Look up the struct type from the object expression
-        // 2. Find the field offset from the struct layout
-        // 3. Load the value from memory at struct_ptr + offset
-        
-        // For this simplified implementation, we'll assume fields are stored sequentially
-        // and each field is 8 bytes. We'll need to know the field index.
-        
-        // This is a simplified approach - in reality we'd need struct layout information
-        let field_offset = match field_access.field.as_str() {
-            "x" => 0,  // First field
-            "y" => 8,  // Second field  
-            "width" => 0,  // First field for Rectangle
-            "height" => 8, // Second field for Rectangle
-            _ => return Err(IRError::Generation(format!("Unknown field: {}", field_access.field))),
+        // Struct variables and elements of arrays of structs have a known layout
+        let (field_offset, field_type) = match Self::struct_field_slot(&field_access.object, &field_access.field, context) {
+            Some(slot) => slot,
+            // Otherwise (parameters, call results) assume the usual first and second fields
+            None => match field_access.field.as_str() {
+                "x" | "width" => (0, types::I64),
+                "y" | "height" => (8, types::I64),
+                _ => return Err(IRError::Generation(format!("Unknown field: {}", field_access.field))),
+            },
         };
         
+        // Generate the object expression (a struct pointer or an element address)
+        let struct_ptr = Self::generate_struct_address(builder, &field_access.object, variables, functions, return_types, context, module)?;
+        
         // Load the field value from memory
-        let field_value = builder.ins().load(types::I64, MemFlags::new(), struct_ptr, field_offset);
+        let field_value = builder.ins().load(field_type, MemFlags::new(), struct_ptr, field_offset);
         Ok(field_value)
     }
     
-    fn generate_struct_method_call(
+    /// Address of the struct `object` stands for. A `pointer[Point]` parameter
+    /// points at the caller's variable, which itself holds the struct's address
+    fn generate_struct_address(
+        builder: &mut FunctionBuilder,
+        object: &Expression,
+        variables: &HashMap<String, Variable>,
+        functions: &HashMap<String, cranelift_module::FuncId>,
+        return_types: &HashMap<String, ChifType>,
+        context: &mut CodegenContext,
+        module: &mut BodyModule
+    ) -> Result<Value, IRError> {
+        let address = Self::generate_expression_static(builder, object, variables, functions, return_types, context, module)?;
+        let through_pointer = match object {
+            Expression::Identifier(name) => matches!(context.struct_variables.get(name), Some(chif_type) if Self::is_pointer_to_struct(chif_type)),
+            _ => false,
+        };
+        Ok(if through_pointer {
+            builder.ins().load(types::I64, MemFlags::new(), address, 0)
+        } else {
+            address
+        })
+    }
+    
+    fn is_pointer_to_struct(chif_type: &ChifType) -> bool {
+        matches!(chif_type, ChifType::Pointer(target) if matches!(**target, ChifType::Struct(_)))
+    }
+    
+    fn struct_name_of(chif_type: &ChifType) -> Option<&str> {
+        match chif_type {
+            ChifType::Struct(name) => Some(name),
+            ChifType::Array(element, _) => Self::struct_name_of(element),
+            _ => None,
+        }
+    }
+    
+    /// Layout of the elements of `pts[i]` when `pts` is a local array of structs.
+    /// Such arrays store their elements inline, `layout.size` bytes each
+    fn struct_element_layout<'a>(index_access: &IndexAccess, context: &'a CodegenContext) -> Option<&'a StructLayout> {
+        let Expression::Identifier(name) = &*index_access.object else {
+            return None;
+        };
+        match context.struct_variables.get(name)? {
+            ChifType::Array(element, dimensions) if dimensions.len() == index_access.indices.len() => {
+                context.structs.get(Self::struct_name_of(element)?)
+            }
+            _ => None,
+        }
+    }
+    
+    /// Offset and Cranelift type of `object.field` when the struct type of `object` is known
+    fn struct_field_slot(object: &Expression, field_name: &str, context: &CodegenContext) -> Option<(i32, Type)> {
+        let layout = match object {
+            Expression::Identifier(name) => match context.struct_variables.get(name)? {
+                ChifType::Struct(struct_name) => context.structs.get(struct_name)?,
+                ChifType::Pointer(target) => context.structs.get(Self::struct_name_of(target)?)?,
+                _ => return None,
+            },
+            Expression::Index(index_access) => Self::struct_element_layout(index_access, context)?,
+            _ => return None,
+        };
+        let field = layout.field(field_name)?;
+        Some((field.offset as i32, Self::chif_type_to_cranelift(&field.field_type).ok()?))
+    }
+    
+    /// Copies a struct field by field, so padding bytes are never read
+    fn copy_struct_fields(builder: &mut FunctionBuilder, layout: &StructLayout, from: Value, to: Value, to_offset: i32) {
+        for field in &layout.fields {
+            let field_type = Self::chif_type_to_cranelift(&field.field_type).unwrap_or(types::I64);
+            let value = builder.ins().load(field_type, MemFlags::new(), from, field.offset as i32);
+            builder.ins().store(MemFlags::new(), value, to, to_offset + field.offset as i32);
+        }
+    }
+    
+    /// `rono_http_request(method, url, body)`: a pointer to the runtime's RonoHttpResponse.
+    /// GET and DELETE take no body and pass 0 for it
+    #[allow(clippy::too_many_arguments)]
+    fn generate_http_request(
+        builder: &mut FunctionBuilder,
+        name: &str,
+        method: i64,
+        args: &[Expression],
+        variables: &HashMap<String, Variable>,
+        functions: &HashMap<String, cranelift_module::FuncId>,
+        return_types: &HashMap<String, ChifType>,
+        context: &mut CodegenContext,
+        module: &mut BodyModule,
+    ) -> Result<Value, IRError> {
+        let expected = if method == 1 || method == 2 { 2 } else { 1 };
+        if args.len() != expected {
+            return Err(IRError::Generation(format!("{} expects {} argument(s)", name, expected)));
+        }
+        
+        let url = Self::generate_expression_static(builder, &args[0], variables, functions, return_types, context, module)?;
+        let body = match args.get(1) {
+            Some(body) => Self::generate_expression_static(builder, body, variables, functions, return_types, context, module)?,
+            None => builder.ins().iconst(types::I64, 0),
+        };
+        let method = builder.ins().iconst(types::I64, method);
+        let func_id = *functions.get("rono_http_request")
+            .ok_or_else(|| IRError::Generation("Runtime function rono_http_request not found".to_string()))?;
+        let func_ref = module.declare_func_in_func(func_id, builder.func);
+        let call = builder.ins().call(func_ref, &[method, url, body]);
+        Ok(builder.inst_results(call)[0])
+    }
+    
+    /// Method code `rono_http_request` expects for an HTTP builtin
+    fn http_request_method(name: &str) -> Option<i64> {
+        match name {
+            "http_get" => Some(0),
+            "http_post" => Some(1),
+            "http_put" => Some(2),
+            "http_delete" => Some(3),
+            _ => None,
+        }
+    }
+    
+    fn builtin_method_runtime(method: &str) -> Option<&'static str> {
+        match method {
+            "char_count" => Some("rono_str_char_count"),
+            "byte_len" => Some("rono_str_byte_len"),
+            "index_of" => Some("rono_str_index_of"),
+            "trim" => Some("rono_str_trim"),
+            "to_hex_str" => Some("rono_int_to_hex"),
+            "to_hex_str_lower" => Some("rono_int_to_hex_lower"),
+            "to_binary_str" => Some("rono_int_to_binary"),
+            _ => None,
+        }
+    }
+    
+    /// Type of a method of a builtin object: `con.in()` and the `args` methods
+    fn builtin_object_method_type(method_call: &MethodCall) -> Option<ChifType> {
+        let Expression::Identifier(object) = &*method_call.object else {
+            return None;
+        };
+        match (object.as_str(), method_call.method.as_str()) {
+            ("con", "in") | ("args", "get" | "value_of") | ("os", "name" | "arch") | ("rono", "version") => Some(ChifType::Str),
+            ("args", "count") => Some(ChifType::Int),
+            ("args", "has_flag") => Some(ChifType::Bool),
+            _ => None,
+        }
+    }
+    
+    fn builtin_method_return_type(method: &str) -> Option<ChifType> {
+        match method {
+            "char_count" | "byte_len" | "index_of" => Some(ChifType::Int),
+            "trim" | "to_hex_str" | "to_hex_str_lower" | "to_binary_str" => Some(ChifType::Str),
+            _ => None,
+        }
+    }
+    
+    fn generate_builtin_method(
         builder: &mut FunctionBuilder,
         method_call: &MethodCall,
         variables: &HashMap<String, Variable>,
         functions: &HashMap<String, cranelift_module::FuncId>,
-        module: &mut ObjectModule
+        return_types: &HashMap<String, ChifType>,
+        context: &mut CodegenContext,
+        module: &mut BodyModule
     ) -> Result<Value, IRError> {
-        // Generate the object (self parameter)
-        let self_value = Self::generate_expression_static(builder, &method_call.object, variables, functions, module)?;
-        
-        // For now, we'll assume the method name follows the pattern StructName_methodName
-        // In a real implementation, we would need to determine the struct type from the object
-        // For this simplified version, we'll try common struct names
-        let possible_method_names = vec![
-            format!("Point_{}", method_call.method),
-            format!("Rectangle_{}", method_call.method),
-            // Add more struct names as needed
-        ];
+        let mut args = vec![Self::generate_expression_static(builder, &method_call.object, variables, functions, return_types, context, module)?];
+        for arg in &method_call.args {
+            args.push(Self::generate_expression_static(builder, arg, variables, functions, return_types, context, module)?);
+        }
         
-        for method_name in possible_method_names {
-            if let Some(&func_id) = functions.get(&method_name) {
-                // Generate arguments (self + other arguments)
-                let mut args = vec![self_value];
-                for arg in &method_call.args {
-                    let arg_value = Self::generate_expression_static(builder, arg, variables, functions, module)?;
-                    args.push(arg_value);
-                }
-                
-                // Get function reference and make the call
-                let func_ref = module.declare_func_in_func(func_id, builder.func);
-                let call_result = builder.ins().call(func_ref, &args);
-                
-                // Return the first result (if any)
-                let results = builder.inst_results(call_result);
-                if results.is_empty() {
-                    // Method returns void, return a dummy value
-                    return Ok(builder.ins().iconst(types::I64, 0));
-                } else {
-                    return Ok(results[0]);
+        let runtime_name = Self::builtin_method_runtime(&method_call.method)
+            .ok_or_else(|| IRError::Generation(format!("Unknown builtin method '{}'", method_call.method)))?;
+        let func_id = *functions.get(runtime_name)
+            .ok_or_else(|| IRError::Generation(format!("Runtime function {} not found", runtime_name)))?;
+        let func_ref = module.declare_func_in_func(func_id, builder.func);
+        let call = builder.ins().call(func_ref, &args);
+        Ok(builder.inst_results(call)[0])
+    }
+    
+    fn generate_struct_method_call(
+        builder: &mut FunctionBuilder,
+        method_call: &MethodCall,
+        variables: &HashMap<String, Variable>,
+        functions: &HashMap<String, cranelift_module::FuncId>,
+        return_types: &HashMap<String, ChifType>,
+        context: &mut CodegenContext,
+        module: &mut BodyModule
+    ) -> Result<Value, IRError> {
+        // Methods of local and imported structs alike are named `Struct_method`.
+        // When the receiver's type cannot be told, the only struct with such a
+        // method is meant
+        let struct_name = Self::receiver_struct(&method_call.object, variables, return_types, context)
+            .or_else(|| {
+                let mut owners = context.structs.keys()
+                    .filter(|name| functions.contains_key(&format!("{}_{}", name, method_call.method)));
+                match (owners.next(), owners.next()) {
+                    (Some(name), None) => Some(name.clone()),
+                    _ => None,
                 }
-            }
+            })
+            .ok_or_else(|| IRError::Generation(format!("Method '{}' not found", method_call.method)))?;
+        let method_name = format!("{}_{}", struct_name, method_call.method);
+        let func_id = *functions.get(&method_name)
+            .ok_or_else(|| IRError::Generation(format!("Struct '{}' has no method '{}'", struct_name, method_call.method)))?;
+        
+        // Generate the object (self parameter), then the other arguments
+        let self_value = Self::generate_expression_static(builder, &method_call.object, variables, functions, return_types, context, module)?;
+        let mut args = vec![self_value];
+        for arg in &method_call.args {
+            let arg_value = Self::generate_expression_static(builder, arg, variables, functions, return_types, context, module)?;
+            args.push(arg_value);
         }
         
-        Err(IRError::Generation(format!("Method '{}' not found", method_call.method)))
+        let func_ref = module.declare_func_in_func(func_id, builder.func);
+        let call_result = builder.ins().call(func_ref, &args);
+        
+        // Return the first result (if any)
+        let results = builder.inst_results(call_result);
+        if results.is_empty() {
+            // Method returns void, return a dummy value
+            Ok(builder.ins().iconst(types::I64, 0))
+        } else {
+            Ok(results[0])
+        }
     }
     
+    /// Struct type of a method call receiver, when the expression tells it:
+    /// struct variables and parameters, literals, calls of functions and
+    /// methods returning a struct, struct fields and elements of struct arrays
+    fn receiver_struct(
+        object: &Expression,
+        variables: &HashMap<String, Variable>,
+        return_types: &HashMap<String, ChifType>,
+        context: &CodegenContext,
+    ) -> Option<String> {
+        let receiver_type = match object {
+            Expression::Identifier(name) => context.struct_variables.get(name)?.clone(),
+            Expression::StructLiteral(literal) => return Some(literal.struct_name.clone()),
+            Expression::Call(call) => return_types.get(&call.name)?.clone(),
+            Expression::MethodCall(call) => {
+                let owner = match &*call.object {
+                    // `geometry.make_rect(...)` and `Rect.new(...)` are named after the module or struct
+                    Expression::Identifier(name) if !variables.contains_key(name) => name.clone(),
+                    inner => Self::receiver_struct(inner, variables, return_types, context)?,
+                };
+                return_types.get(&format!("{}_{}", owner, call.method))?.clone()
+            }
+            Expression::FieldAccess(access) => {
+                let owner = Self::receiver_struct(&access.object, variables, return_types, context)?;
+                let layout = context.structs.get(&owner)?;
+                layout.field(&access.field)?.field_type.clone()
+            }
+            Expression::Index(index_access) => return Some(Self::struct_element_layout(index_access, context)?.name.clone()),
+            _ => return None,
+        };
+        match receiver_type {
+            ChifType::Struct(name) => Some(name),
+            ChifType::Pointer(target) => Self::struct_name_of(&target).map(str::to_string),
+            _ => None,
+        }
+    }
+    
+    /// `read_only`: nothing writes through the array, so a large constant literal
+    /// can be used in place. Otherwise every evaluation copies it out of its template
+    #[allow(clippy::too_many_arguments)]
     fn generate_array_literal(
         builder: &mut FunctionBuilder,
         elements: &[Expression],
+        read_only: bool,
         variables: &HashMap<String, Variable>,
         functions: &HashMap<String, cranelift_module::FuncId>,
-        module: &mut ObjectModule
+        return_types: &HashMap<String, ChifType>,
+        context: &mut CodegenContext,
+        module: &mut BodyModule
     ) -> Result<Value, IRError> {
         if elements.is_empty() {
             // Empty array - return null pointer
             return Ok(builder.ins().iconst(types::I64, 0));
         }
         
-        // Calculate total size needed (assume each element is 8 bytes for now)
+        // Structs of one type are stored inline, everything else takes 8 bytes per element
+        let struct_layout = match elements.first() {
+            Some(Expression::StructLiteral(first)) if elements.iter().all(|element| {
+                matches!(element, Expression::StructLiteral(literal) if literal.struct_name == first.struct_name)
+            }) => context.structs.get(&first.struct_name).cloned(),
+            _ => None,
+        };
+        let element_size = struct_layout.as_ref().map_or(8, |layout| layout.size as i64);
         let element_count = elements.len() as i64;
-        let total_size = element_count * 8; // 8 bytes per element
+        let total_size = element_count * element_size;
+        
+        // Large literals don't belong on the stack
+        if total_size > context.max_stack_literal_bytes as i64 {
+            if let Some(bytes) = Self::constant_array_bytes(elements, module.isa().endianness()) {
+                let global = module.define_data_in_func(&bytes, 8, builder.func);
+                let template = builder.ins().global_value(types::I64, global);
+                if read_only {
+                    context.warnings.push(format!(
+                        "array literal of {} elements ({} bytes) exceeds {} bytes, emitted as static read-only data",
+                        element_count, total_size, context.max_stack_literal_bytes
+                    ));
+                    return Ok(template);
+                }
+                
+                // The array may be written to, so each evaluation gets its own copy
+                context.warnings.push(format!(
+                    "array literal of {} elements ({} bytes) exceeds {} bytes, copied from static data into the runtime arena",
+                    element_count, total_size, context.max_stack_literal_bytes
+                ));
+                let copy_id = *functions.get("rono_copy_data")
+                    .ok_or_else(|| IRError::Generation("Runtime function rono_copy_data not found".to_string()))?;
+                let copy_ref = module.declare_func_in_func(copy_id, builder.func);
+                let size = builder.ins().iconst(types::I64, total_size);
+                let call = builder.ins().call(copy_ref, &[template, size]);
+                return Ok(builder.inst_results(call)[0]);
+            }
+            
+            context.warnings.push(format!(
+                "array literal of {} elements ({} bytes) exceeds {} bytes but has non-constant elements, kept on the stack",
+                element_count, total_size, context.max_stack_literal_bytes
+            ));
+        }
         
         // Allocate stack space
         let stack_slot = builder.create_sized_stack_slot(StackSlotData::new(
@@ -1365,39 +3049,235 @@ impl IRGenerator {
         
         // Initialize elements
         for (i, element_expr) in elements.iter().enumerate() {
-            let element_value = Self::generate_expression_static(builder, element_expr, variables, functions, module)?;
-            let offset = (i * 8) as i32; // 8 bytes per element
-            builder.ins().store(MemFlags::new(), element_value, array_ptr, offset);
+            let element_value = Self::generate_expression_static(builder, element_expr, variables, functions, return_types, context, module)?;
+            let offset = i as i32 * element_size as i32;
+            if let Some(layout) = &struct_layout {
+                Self::copy_struct_fields(builder, layout, element_value, array_ptr, offset);
+            } else {
+                builder.ins().store(MemFlags::new(), element_value, array_ptr, offset);
+            }
         }
         
         // Return pointer to the array
         Ok(array_ptr)
     }
     
+    /// Encodes an array literal whose elements are all constants into its
+    /// in-memory layout (8 bytes per element). Returns None for anything else.
+    fn constant_array_bytes(elements: &[Expression], endianness: codegen::ir::Endianness) -> Option<Vec<u8>> {
+        let mut bytes = Vec::with_capacity(elements.len() * 8);
+        for element in elements {
+            let bits: u64 = match element {
+                Expression::Literal(ChifValue::Int(i)) => *i as u64,
+                Expression::Literal(ChifValue::Float(f)) => f.to_bits(),
+                Expression::Literal(ChifValue::Bool(b)) => *b as u64,
+                Expression::Unary(unary_op) if unary_op.operator == UnaryOperator::Minus => {
+                    match &*unary_op.operand {
+                        Expression::Literal(ChifValue::Int(i)) => i.wrapping_neg() as u64,
+                        Expression::Literal(ChifValue::Float(f)) => (-f).to_bits(),
+                        _ => return None,
+                    }
+                }
+                _ => return None,
+            };
+            match endianness {
+                codegen::ir::Endianness::Little => bytes.extend_from_slice(&bits.to_le_bytes()),
+                codegen::ir::Endianness::Big => bytes.extend_from_slice(&bits.to_be_bytes()),
+            }
+        }
+        Some(bytes)
+    }
+    
+    /// `item in container` for a str, or for an array whose length is known
+    /// here: a literal or a one-dimensional array variable initialized with one
+    /// or declared with a size. Lists and maps have no length or lookup in
+    /// compiled code yet
+    fn generate_membership(
+        builder: &mut FunctionBuilder,
+        binary_op: &BinaryOp,
+        variables: &HashMap<String, Variable>,
+        functions: &HashMap<String, cranelift_module::FuncId>,
+        return_types: &HashMap<String, ChifType>,
+        context: &mut CodegenContext,
+        module: &mut BodyModule
+    ) -> Result<Value, IRError> {
+        let (item, container) = (&*binary_op.left, &*binary_op.right);
+        let runtime = |name: &str| functions.get(name).copied()
+            .ok_or_else(|| IRError::Generation(format!("Runtime function {} not found", name)));
+        
+        if Self::is_str_expression(container, &context.variable_types, return_types) {
+            let func_id = runtime("rono_str_contains")?;
+            let text = Self::generate_expression_static(builder, container, variables, functions, return_types, context, module)?;
+            let part = Self::generate_expression_static(builder, item, variables, functions, return_types, context, module)?;
+            let func_ref = module.declare_func_in_func(func_id, builder.func);
+            let call = builder.ins().call(func_ref, &[text, part]);
+            return Ok(builder.inst_results(call)[0]);
+        }
+        
+        let (elem_tag, len) = Self::sized_list_operand(container, "'in'", context, return_types)?;
+        
+        let func_id = runtime("rono_list_contains")?;
+        let items = Self::generate_expression_static(builder, container, variables, functions, return_types, context, module)?;
+        let mut value = Self::generate_expression_static(builder, item, variables, functions, return_types, context, module)?;
+        if elem_tag == 1 {
+            // Elements are passed as their 8 bytes, so the float goes by its bits
+            value = Self::promote_to_float(builder, item, value);
+            value = builder.ins().bitcast(types::I64, MemFlags::new(), value);
+        }
+        let len = builder.ins().iconst(types::I64, len as i64);
+        let elem_type = builder.ins().iconst(types::I64, elem_tag);
+        let func_ref = module.declare_func_in_func(func_id, builder.func);
+        let call = builder.ins().call(func_ref, &[items, len, value, elem_type]);
+        Ok(builder.inst_results(call)[0])
+    }
+    
+    /// Element type tag and length of a list operand whose length is known
+    /// here: an array literal or a one-dimensional array variable initialized
+    /// with one or declared with a size. The tag is what the runtime list
+    /// functions expect: 0 = int, 1 = float, 2 = string
+    fn sized_list_operand(
+        container: &Expression,
+        operation: &str,
+        context: &CodegenContext,
+        return_types: &HashMap<String, ChifType>,
+    ) -> Result<(i64, usize), IRError> {
+        let (element_type, len) = match container {
+            Expression::ArrayLiteral(elements) => {
+                let element_type = match elements.first() {
+                    Some(first) if Self::is_str_expression(first, &context.variable_types, return_types) => ChifType::Str,
+                    Some(first) if Self::is_float_expression(first, return_types) => ChifType::Float,
+                    _ => ChifType::Int,
+                };
+                (element_type, elements.len())
+            }
+            Expression::Identifier(name) => match context.variable_types.get(name) {
+                Some(ChifType::Array(element_type, dimensions)) if dimensions.len() == 1 && dimensions[0] > 0 => {
+                    ((**element_type).clone(), dimensions[0])
+                }
+                Some(other) => return Err(IRError::UnsupportedFeature(format!(
+                    "{} over {} is not supported in compiled code yet; use an array of known size or a str", operation, other
+                ))),
+                None => return Err(IRError::Generation(format!("Undefined variable: {}", name))),
+            },
+            _ => return Err(IRError::UnsupportedFeature(format!(
+                "{} needs a str, an array literal or an array variable on the right in compiled code", operation
+            ))),
+        };
+        let elem_tag = match element_type {
+            ChifType::Int => 0,
+            ChifType::Float => 1,
+            ChifType::Str => 2,
+            other => return Err(IRError::UnsupportedFeature(format!(
+                "{} over an array of {} is not supported in compiled code yet", operation, other
+            ))),
+        };
+        Ok((elem_tag, len))
+    }
+    
+    fn is_unique_call(expression: &Expression) -> bool {
+        matches!(expression, Expression::MethodCall(method_call) if method_call.method == "unique" && method_call.args.is_empty())
+    }
+    
+    /// Remembers whether `name` now holds a list built by `unique()`, so its
+    /// `len()` is read from the runtime rather than from the declared size
+    fn track_runtime_list(context: &mut CodegenContext, name: &str, value: Option<&Expression>) {
+        if value.is_some_and(Self::is_unique_call) {
+            context.runtime_lists.insert(name.to_string());
+        } else {
+            context.runtime_lists.remove(name);
+        }
+    }
+    
+    /// `len()` of a list: the runtime knows the length of a list it built, an
+    /// array variable of known size has it as a constant
+    fn generate_list_len(
+        builder: &mut FunctionBuilder,
+        list: &Expression,
+        variables: &HashMap<String, Variable>,
+        functions: &HashMap<String, cranelift_module::FuncId>,
+        return_types: &HashMap<String, ChifType>,
+        context: &mut CodegenContext,
+        module: &mut BodyModule
+    ) -> Result<Value, IRError> {
+        let built_by_runtime = match list {
+            Expression::Identifier(name) => context.runtime_lists.contains(name),
+            _ => Self::is_unique_call(list),
+        };
+        if !built_by_runtime {
+            let (_, len) = Self::sized_list_operand(list, "len()", context, return_types)?;
+            return Ok(builder.ins().iconst(types::I64, len as i64));
+        }
+        let list_ptr = Self::generate_expression_static(builder, list, variables, functions, return_types, context, module)?;
+        let len_func_id = *functions.get("rono_list_len")
+            .ok_or_else(|| IRError::Generation("Runtime function rono_list_len not found".to_string()))?;
+        let func_ref = module.declare_func_in_func(len_func_id, builder.func);
+        let result = builder.ins().call(func_ref, &[list_ptr]);
+        Ok(builder.inst_results(result)[0])
+    }
+    
+    /// `list.unique()` on a literal or an array variable of known length. The
+    /// runtime returns a new buffer of the distinct elements; `rono_list_len`
+    /// gives their count
+    fn generate_list_unique(
+        builder: &mut FunctionBuilder,
+        list: &Expression,
+        variables: &HashMap<String, Variable>,
+        functions: &HashMap<String, cranelift_module::FuncId>,
+        return_types: &HashMap<String, ChifType>,
+        context: &mut CodegenContext,
+        module: &mut BodyModule
+    ) -> Result<Value, IRError> {
+        let (elem_tag, len) = Self::sized_list_operand(list, "unique()", context, return_types)?;
+        let unique_func_id = *functions.get("rono_list_unique")
+            .ok_or_else(|| IRError::Generation("Runtime function rono_list_unique not found".to_string()))?;
+        
+        // The runtime only reads the elements
+        let list_ptr = match list {
+            Expression::ArrayLiteral(elements) => Self::generate_array_literal(builder, elements, true, variables, functions, return_types, context, module)?,
+            _ => Self::generate_expression_static(builder, list, variables, functions, return_types, context, module)?,
+        };
+        let len = builder.ins().iconst(types::I64, len as i64);
+        let elem_type = builder.ins().iconst(types::I64, elem_tag);
+        let func_ref = module.declare_func_in_func(unique_func_id, builder.func);
+        let result = builder.ins().call(func_ref, &[list_ptr, len, elem_type]);
+        Ok(builder.inst_results(result)[0])
+    }
+    
     fn generate_array_index(
         builder: &mut FunctionBuilder,
         index_access: &IndexAccess,
         variables: &HashMap<String, Variable>,
         functions: &HashMap<String, cranelift_module::FuncId>,
-        module: &mut ObjectModule
+        return_types: &HashMap<String, ChifType>,
+        context: &mut CodegenContext,
+        module: &mut BodyModule
     ) -> Result<Value, IRError> {
+        // The last index into an array of structs yields the element's address
+        let struct_size = Self::struct_element_layout(index_access, context).map(|layout| layout.size);
+        
         // Generate the array pointer
-        let mut current_ptr = Self::generate_expression_static(builder, &index_access.object, variables, functions, module)?;
+        let mut current_ptr = Self::generate_expression_static(builder, &index_access.object, variables, functions, return_types, context, module)?;
         
         // Handle multiple indices for multidimensional arrays
-        for index_expr in &index_access.indices {
+        for (i, index_expr) in index_access.indices.iter().enumerate() {
+            let inline_size = struct_size.filter(|_| i + 1 == index_access.indices.len());
+            
             // Generate the index
-            let index_value = Self::generate_expression_static(builder, index_expr, variables, functions, module)?;
+            let index_value = Self::generate_expression_static(builder, index_expr, variables, functions, return_types, context, module)?;
             
-            // Calculate offset: index * element_size (8 bytes)
-            let element_size = builder.ins().iconst(types::I64, 8);
+            // Calculate offset: index * element_size (8 bytes, or the struct size)
+            let element_size = builder.ins().iconst(types::I64, inline_size.unwrap_or(8) as i64);
             let offset = builder.ins().imul(index_value, element_size);
             
             // Calculate final address: current_ptr + offset
             let element_ptr = builder.ins().iadd(current_ptr, offset);
             
             // Load the element value (which might be another array pointer)
-            current_ptr = builder.ins().load(types::I64, MemFlags::new(), element_ptr, 0);
+            current_ptr = if inline_size.is_some() {
+                element_ptr
+            } else {
+                builder.ins().load(types::I64, MemFlags::new(), element_ptr, 0)
+            };
         }
         
         Ok(current_ptr)
@@ -1410,92 +3290,218 @@ impl IRGenerator {
     }
     
     fn process_import(&mut self, import: &ImportStatement) -> Result<(), IRError> {
-        // Add .rono extension if not present
-        let file_path = if import.path.ends_with(".rono") {
-            import.path.clone()
-        } else {
-            format!("{}.rono", import.path)
+        let file_path = import.file_path();
+        
+        let from = match self.importing.last() {
+            Some(importer) => importer.parent(),
+            None => self.source_dir.as_deref(),
         };
+        let canonical_path = crate::resolve_import(import, from).map_err(IRError::Generation)?;
         
-        // Read the imported file
-        let source = std::fs::read_to_string(&file_path).map_err(|_| {
-            IRError::Generation(format!("Could not read module file: {}", file_path))
-        })?;
+        // Get module name for prefixing
+        let module_name = import.module_name().map_err(IRError::Generation)?;
+        match self.module_names.get(&module_name) {
+            Some(path) if *path != canonical_path => {
+                return Err(IRError::Generation(format!(
+                    "Module name '{}' refers to both {} and {}; import one of them with 'as'",
+                    module_name, path.display(), canonical_path.display()
+                )));
+            }
+            Some(_) => {}
+            None => {
+                self.module_names.insert(module_name.clone(), canonical_path.clone());
+            }
+        }
         
-        // Parse the imported file
-        use crate::{lexer::Lexer, parser::Parser};
-        let mut lexer = Lexer::new(&source);
-        let tokens = lexer.tokenize().map_err(|e| {
-            IRError::Generation(format!("Failed to tokenize module {}: {}", file_path, e))
-        })?;
+        // Functions of this file under this prefix are already defined. A `use (...)`
+        // import defines only part of them, so later imports may still add more.
+        // A module importing itself back is completed by its first import
+        if self.generated_modules.contains(&(canonical_path.clone(), module_name.clone()))
+            || self.importing.contains(&canonical_path)
+        {
+            return Ok(());
+        }
+        if import.names.is_none() {
+            self.generated_modules.insert((canonical_path.clone(), module_name.clone()));
+        }
         
-        let mut parser = Parser::new(tokens);
-        let imported_program = parser.parse().map_err(|e| {
-            IRError::Generation(format!("Failed to parse module {}: {}", file_path, e))
-        })?;
+        if !self.module_cache.contains_key(&canonical_path) {
+            let program = crate::load_module(&file_path, &canonical_path).map_err(IRError::Generation)?;
+            self.module_cache.insert(canonical_path.clone(), program);
+        }
         
-        // Get module name for prefixing
-        let module_name = import.alias.clone().unwrap_or_else(|| {
-            std::path::Path::new(&import.path)
-                .file_stem()
-                .unwrap()
-                .to_string_lossy()
-                .to_string()
-        });
+        // The module's bodies call the modules it imports itself
+        let nested: Vec<ImportStatement> = self.module_cache[&canonical_path].items.iter()
+            .filter_map(|item| match item {
+                Item::Import(nested) => Some(nested.clone()),
+                _ => None,
+            })
+            .collect();
+        self.importing.push(canonical_path.clone());
+        let mut imported = Ok(());
+        for nested in &nested {
+            imported = self.process_import(nested);
+            if imported.is_err() {
+                break;
+            }
+        }
+        self.importing.pop();
+        imported?;
+        
+        let prefix = self.module_prefixes.entry(canonical_path.clone())
+            .or_insert_with(|| module_name.clone())
+            .clone();
+        let cached = &self.module_cache[&canonical_path];
+        
+        // Structs keep their own names, so values of an imported struct and methods
+        // added to it by `fn_for` in another file all refer to the same layout
+        let layouts = cached.items.iter()
+            .filter_map(|item| match item {
+                Item::Struct(struct_def) if !self.context.structs.contains_key(&struct_def.name) => {
+                    Some(StructLayout::compute(struct_def))
+                }
+                _ => None,
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        for layout in layouts {
+            self.context.structs.insert(layout.name.clone(), layout);
+        }
+        
+        // With `use (...)`, or without it in a module that marks items with `export`,
+        // only the selected functions, their transitive callees within the module and
+        // the methods of the structs any of them use are generated
+        let selection = import.names.clone().or_else(|| cached.exported_names());
+        let reachable = selection.as_ref().map(|names| cached.reachable(names));
+        
+        // Skip what is not selected or was declared by an earlier import of this
+        // file. Only the rest is copied out of the cache, because its calls get
+        // renamed below; a method another file defined too is reported when declared
+        let defined_here = |name: String| {
+            self.defined_in.get(&name).is_some_and(|file| file.as_ref() == Some(&canonical_path))
+        };
+        let selected = |name: &String| match &reachable {
+            Some(reachable) => reachable.functions.contains(name),
+            None => true,
+        };
+        let used = |name: &String| match &reachable {
+            Some(reachable) => reachable.structs.contains(name),
+            None => true,
+        };
+        let selected_functions: Vec<String> = cached.items.iter()
+            .filter_map(|item| match item {
+                Item::Function(func) if selected(&func.name) => Some(func.name.clone()),
+                _ => None,
+            })
+            .collect();
+        let mut items: Vec<Item> = cached.items.iter()
+            .filter(|item| match item {
+                Item::Function(func) => {
+                    selected(&func.name) && !defined_here(format!("{}_{}", prefix, func.name))
+                }
+                Item::StructImpl(impl_block) => {
+                    used(&impl_block.struct_name) && !impl_block.methods.iter().any(|method| {
+                        defined_here(format!("{}_{}", impl_block.struct_name, method.name))
+                    })
+                }
+                _ => false,
+            })
+            .cloned()
+            .collect();
+        
+        // Calls between functions of the module use their prefixed names
+        let module_functions: HashSet<String> = cached.items.iter()
+            .filter_map(|item| match item {
+                Item::Function(func) => Some(func.name.clone()),
+                _ => None,
+            })
+            .collect();
+        let mut qualify_calls = |call: &mut FunctionCall| {
+            if module_functions.contains(&call.name) {
+                call.name = format!("{}_{}", prefix, call.name);
+            }
+            Ok::<(), ()>(())
+        };
+        for item in &mut items {
+            match item {
+                Item::Function(func) => {
+                    let _ = func.body.visit_calls_mut(&mut qualify_calls);
+                }
+                Item::StructImpl(impl_block) => {
+                    for method in &mut impl_block.methods {
+                        let _ = method.body.visit_calls_mut(&mut qualify_calls);
+                    }
+                }
+                _ => {}
+            }
+        }
         
         // Declare imported functions with module prefix
-        for item in &imported_program.items {
+        for item in &items {
             match item {
                 Item::Function(func) => {
-                    let qualified_name = format!("{}_{}", module_name, func.name);
-                    let mut qualified_func = func.clone();
-                    qualified_func.name = qualified_name;
-                    self.declare_function(&qualified_func)?;
+                    let function_name = format!("{}_{}", prefix, func.name);
+                    self.claim_definition(&function_name, None, Some(&canonical_path))?;
+                    self.declare_function(func, &function_name)?;
                 }
                 Item::StructImpl(impl_block) => {
-                    // Declare methods with module and struct prefix
+                    // Methods are named after the struct alone, as in the main file
                     for method in &impl_block.methods {
-                        let method_name = format!("{}_{}_{}", module_name, impl_block.struct_name, method.name);
-                        let mut method_with_new_name = method.clone();
-                        method_with_new_name.name = method_name;
-                        self.declare_function(&method_with_new_name)?;
+                        let method_name = format!("{}_{}", impl_block.struct_name, method.name);
+                        self.claim_definition(&method_name, Some(&impl_block.struct_name), Some(&canonical_path))?;
+                        self.declare_function(method, &method_name)?;
                     }
                 }
                 _ => {} // Other items handled elsewhere
             }
         }
         
-        // Generate imported function bodies
-        for item in &imported_program.items {
+        // Their bodies are generated with those of the importing file
+        for item in items {
             match item {
                 Item::Function(func) => {
-                    let qualified_name = format!("{}_{}", module_name, func.name);
-                    let mut qualified_func = func.clone();
-                    qualified_func.name = qualified_name;
-                    self.generate_function(&qualified_func)?;
+                    let name = format!("{}_{}", prefix, func.name);
+                    self.imported_bodies.push(PendingBody { func: Cow::Owned(func), name, current_struct: None });
                 }
                 Item::StructImpl(impl_block) => {
-                    // Generate method bodies with module and struct prefix
-                    for method in &impl_block.methods {
-                        let method_name = format!("{}_{}_{}", module_name, impl_block.struct_name, method.name);
-                        let mut method_with_new_name = method.clone();
-                        method_with_new_name.name = method_name;
-                        self.generate_function(&method_with_new_name)?;
+                    for method in impl_block.methods {
+                        self.imported_bodies.push(PendingBody {
+                            name: format!("{}_{}", impl_block.struct_name, method.name),
+                            func: Cow::Owned(method),
+                            current_struct: Some(impl_block.struct_name.clone()),
+                        });
                     }
                 }
                 _ => {} // Other items handled elsewhere
             }
         }
         
+        // Under another name of the same file, calls go to the functions generated above
+        if prefix != module_name {
+            for name in selected_functions {
+                self.alias_function(&format!("{}_{}", module_name, name), &format!("{}_{}", prefix, name));
+            }
+        }
+        
         Ok(())
     }
     
+    /// Makes `alias` another name of the declared function `name`
+    fn alias_function(&mut self, alias: &str, name: &str) {
+        if let Some(&func_id) = self.functions.get(name) {
+            self.functions.insert(alias.to_string(), func_id);
+            self.function_return_types.insert(alias.to_string(), self.function_return_types[name].clone());
+            self.context.function_params.insert(alias.to_string(), self.context.function_params[name].clone());
+        }
+    }
+    
     fn generate_address_of(
         builder: &mut FunctionBuilder,
         expr: &Expression,
         variables: &HashMap<String, Variable>,
         functions: &HashMap<String, cranelift_module::FuncId>,
-        module: &mut ObjectModule
+        return_types: &HashMap<String, ChifType>,
+        context: &mut CodegenContext,
+        module: &mut BodyModule
     ) -> Result<Value, IRError> {
         match expr {
             Expression::Identifier(var_name) => {
@@ -1524,7 +3530,7 @@ impl IRGenerator {
             }
             _ => {
                 // For other expressions, we need to evaluate them and create a temporary
-                let value = Self::generate_expression_static(builder, expr, variables, functions, module)?;
+                let value = Self::generate_expression_static(builder, expr, variables, functions, return_types, context, module)?;
                 
                 // Create a stack slot to store the temporary value
                 let stack_slot = builder.create_sized_stack_slot(cranelift::prelude::StackSlotData::new(
@@ -1546,10 +3552,12 @@ impl IRGenerator {
         expr: &Expression,
         variables: &HashMap<String, Variable>,
         functions: &HashMap<String, cranelift_module::FuncId>,
-        module: &mut ObjectModule
+        return_types: &HashMap<String, ChifType>,
+        context: &mut CodegenContext,
+        module: &mut BodyModule
     ) -> Result<Value, IRError> {
         // Generate the pointer expression
-        let pointer = Self::generate_expression_static(builder, expr, variables, functions, module)?;
+        let pointer = Self::generate_expression_static(builder, expr, variables, functions, return_types, context, module)?;
         
         // For now, we need to determine what type to load
         // This is a simplified approach - we'll try to infer from context
@@ -1562,33 +3570,251 @@ impl IRGenerator {
         Ok(builder.ins().load(types::I64, cranelift::prelude::MemFlags::new(), pointer, 0))
     }
     
-    fn generate_string_on_stack(
-        builder: &mut FunctionBuilder,
-        s: &str,
-    ) -> Result<Value, IRError> {
-        // Create string on stack (simplified approach)
-        let string_bytes = s.as_bytes();
-        let string_len = string_bytes.len() + 1; // +1 for null terminator
+    /// `os.name()`, `os.arch()` and `rono.version()` of the target being compiled for,
+    /// which is not the machine running the compiler when cross-compiling
+    fn platform_constant(method_call: &MethodCall, triple: &target_lexicon::Triple) -> Option<String> {
+        let Expression::Identifier(object) = &*method_call.object else {
+            return None;
+        };
+        if !method_call.args.is_empty() {
+            return None;
+        }
+        match (object.as_str(), method_call.method.as_str()) {
+            // Named like `std::env::consts::OS`, which the interpreter reports
+            ("os", "name") => Some(match triple.operating_system.to_string() {
+                os if os.starts_with("darwin") || os.starts_with("macos") => "macos".to_string(),
+                os => os,
+            }),
+            ("os", "arch") => Some(triple.architecture.to_string()),
+            ("rono", "version") => Some(env!("CARGO_PKG_VERSION").to_string()),
+            _ => None,
+        }
+    }
+    
+    /// A NUL-terminated string in read-only data of the object file
+    fn generate_string_constant(builder: &mut FunctionBuilder, module: &mut BodyModule, s: &str) -> Result<Value, IRError> {
+        let global = module.define_data_in_func(&[s.as_bytes(), &[0]].concat(), 1, builder.func);
+        Ok(builder.ins().global_value(types::I64, global))
+    }
+}
+
+impl<'g> BodyWorker<'g> {
+    fn new(env: &'g BodyEnv<'g>) -> Self {
+        Self {
+            env,
+            builder_context: FunctionBuilderContext::new(),
+            context: env.context.clone(),
+        }
+    }
+    
+    /// Generates the body of `body.func`, declared earlier under `body.name`,
+    /// with its data in `data_id`, and compiles it to machine code
+    fn generate(&mut self, body: &PendingBody, data_id: DataId) -> Result<GeneratedBody, IRError> {
+        let env = self.env;
+        let (func, name) = (body.func.as_ref(), body.name.as_str());
+        let func_id = env.functions[name];
+        let mut ctx = codegen::Context::new();
+        let mut module = BodyModule::new(env.declarations, env.isa, data_id);
+        let mut variables = HashMap::new();
+        let mut loop_stack = Vec::new();
+        
+        // Per-function parts of the context
+        let context = &mut self.context;
+        context.variable_types.clear();
+        context.struct_variables.clear();
+        context.runtime_lists.clear();
+        context.variable_count = 0;
+        context.return_struct = match &func.return_type {
+            Some(ChifType::Struct(struct_name)) if struct_name == "Self" => body.current_struct.clone(),
+            Some(ChifType::Struct(struct_name)) => Some(struct_name.clone()),
+            _ => None,
+        };
+        context.read_only_arrays = read_only_arrays(func);
         
-        // Create stack slot for the string
-        let stack_slot = builder.create_sized_stack_slot(StackSlotData::new(
-            StackSlotKind::ExplicitSlot,
-            string_len as u32,
-        ));
+        // Get function signature
+        let sig = env.declarations.get_function_decl(func_id).signature.clone();
         
-        // Get pointer to stack slot
-        let string_ptr = builder.ins().stack_addr(types::I64, stack_slot, 0);
+        // Set the function signature in `ctx`
+        ctx.func.signature = sig.clone();
         
-        // Store each byte of the string
-        for (i, &byte) in string_bytes.iter().enumerate() {
-            let byte_val = builder.ins().iconst(types::I8, byte as i64);
-            builder.ins().store(MemFlags::new(), byte_val, string_ptr, i as i32);
+        // Create function builder
+        let mut builder = FunctionBuilder::new(&mut ctx.func, &mut self.builder_context);
+        
+        // Create entry block
+        let entry_block = builder.create_block();
+        
+        // Add block params for functions with parameters
+        if !func.params.is_empty() {
+            // Manually add block parameters based on function signature
+            for param_abi in &sig.params {
+                builder.append_block_param(entry_block, param_abi.value_type);
+            }
         }
         
-        // Store null terminator
-        let null_byte = builder.ins().iconst(types::I8, 0);
-        builder.ins().store(MemFlags::new(), null_byte, string_ptr, string_bytes.len() as i32);
+        let is_c_main = func.is_main && name == "main";
+        if is_c_main {
+            builder.append_block_param(entry_block, types::I32);
+            builder.append_block_param(entry_block, types::I64);
+        }
+        
+        builder.switch_to_block(entry_block);
+        builder.seal_block(entry_block);
+        
+        if is_c_main && env.uses_program_args {
+            let init_id = *env.functions.get("rono_args_init")
+                .ok_or_else(|| IRError::Generation("Runtime function rono_args_init not found".to_string()))?;
+            let init = module.declare_func_in_func(init_id, builder.func);
+            IRGenerator::store_program_args(&mut builder, entry_block, init);
+        }
+        
+        // Create variables for parameters
+        if !func.params.is_empty() {
+            let block_params: Vec<Value> = builder.block_params(entry_block).to_vec();
+            for (i, param) in func.params.iter().enumerate() {
+                if i < block_params.len() && i < sig.params.len() {
+                    let mut param_value = block_params[i];
+                    let var = context.next_variable();
+                    let param_type = IRGenerator::parameter_type(param, body.current_struct.as_deref());
+                    if let ChifType::Struct(struct_name) = &param_type {
+                        // Struct arguments are passed by value: everything except
+                        // the receiver works on the callee's own copy
+                        if let Some(layout) = context.structs.get(struct_name).filter(|_| param.name != "self") {
+                            let slot = builder.create_sized_stack_slot(StackSlotData::new(
+                                StackSlotKind::ExplicitSlot,
+                                layout.size,
+                            ));
+                            let copy = builder.ins().stack_addr(types::I64, slot, 0);
+                            IRGenerator::copy_struct_fields(&mut builder, layout, param_value, copy, 0);
+                            param_value = copy;
+                        }
+                    }
+                    builder.declare_var(var, sig.params[i].value_type);
+                    builder.def_var(var, param_value);
+                    variables.insert(param.name.clone(), var);
+                    // Struct parameters are pointers (to the copy above, or to the
+                    // caller's struct for &mut), so fields are read through the layout
+                    if IRGenerator::is_pointer_to_struct(&param_type) || matches!(param_type, ChifType::Struct(_)) {
+                        context.struct_variables.insert(param.name.clone(), param_type.clone());
+                    }
+                    context.variable_types.insert(param.name.clone(), param_type);
+                }
+            }
+        }
+        
+        // Generate statements
+        let is_main = func.is_main;
+        
+        let mut terminated = false;
+        for statement in &func.body.statements {
+            terminated = IRGenerator::generate_statement_static(&mut builder, statement, &mut variables, is_main, &mut loop_stack, env.functions, env.function_return_types, context, &mut module)?;
+            if terminated {
+                break;
+            }
+        }
+        
+        // Add implicit return if control can reach the end of the body
+        if !terminated {
+            if func.is_main {
+                // Main function should return 0 (success) by default
+                let zero = builder.ins().iconst(types::I32, 0);
+                builder.ins().return_(&[zero]);
+            } else if func.return_type.is_none() || func.return_type == Some(ChifType::Nil) {
+                builder.ins().return_(&[]);
+            } else {
+                // This should be caught by semantic analysis
+                return Err(IRError::Generation("Function missing return statement".to_string()));
+            }
+        }
+        
+        // Finalize function
+        builder.finalize();
+        env.verify_signatures(&ctx.func, func, name).map_err(IRError::SignatureMismatch)?;
+        
+        // Warn about functions with large stack frames
+        let stack_bytes: u32 = ctx.func.sized_stack_slots.values().map(|slot| slot.size).sum();
+        if stack_bytes > context.stack_warning_bytes {
+            context.warnings.push(format!(
+                "function '{}' uses {} bytes of stack (threshold {} bytes)",
+                name, stack_bytes, context.stack_warning_bytes
+            ));
+        }
+        
+        let ir = env.capture_ir.then(|| ctx.func.clone());
+        ctx.compile(env.isa, &mut ControlPlane::default())
+            .map_err(|error| {
+                logger::verbose(format!("Function '{}' IR:\n{}", name, error.func.display()));
+                IRError::from(cranelift_module::ModuleError::Compilation(error.inner))
+            })?;
         
-        Ok(string_ptr)
+        Ok(GeneratedBody {
+            id: func_id,
+            name: name.to_string(),
+            ctx,
+            data_id,
+            data: module.data,
+            warnings: std::mem::take(&mut context.warnings),
+            ir,
+        })
     }
-}
\ No newline at end of file
+}
+
+impl BodyEnv<'_> {
+    /// Checks the calls to user functions and the returns of the function just
+    /// generated against the declared Rono signatures. Cranelift's verifier rejects
+    /// the same mismatches too, but only in terms of SSA values.
+    fn verify_signatures(&self, ir: &ir::Function, func: &Function, name: &str) -> Result<(), String> {
+        let type_list = |types: &[Type]| if types.is_empty() {
+            "nothing".to_string()
+        } else {
+            types.iter().map(Type::to_string).collect::<Vec<_>>().join(", ")
+        };
+        
+        for block in ir.layout.blocks() {
+            for inst in ir.layout.block_insts(block) {
+                let values = ir.dfg.inst_args(inst);
+                
+                if let InstructionData::Call { func_ref, .. } = ir.dfg.insts[inst] {
+                    let ExternalName::User(name_ref) = ir.dfg.ext_funcs[func_ref].name else {
+                        continue;
+                    };
+                    let Some(&callee) = self.user_functions.get(&ir.params.user_named_funcs()[name_ref].index) else {
+                        continue;
+                    };
+                    let params = &self.context.function_params[callee];
+                    if values.len() != params.len() {
+                        return Err(format!(
+                            "call to '{}' passes {} arguments, expected {}",
+                            callee, values.len(), params.len()
+                        ));
+                    }
+                    for (i, (&value, param)) in values.iter().zip(params).enumerate() {
+                        let expected = IRGenerator::chif_type_to_cranelift(&param.param_type).map_err(|e| e.to_string())?;
+                        let provided = ir.dfg.value_type(value);
+                        if provided != expected {
+                            return Err(format!(
+                                "call to '{}' passes {} as parameter {} ('{}'), expected {} ({})",
+                                callee, provided, i + 1, param.name, param.param_type, expected
+                            ));
+                        }
+                    }
+                } else if ir.dfg.insts[inst].opcode() == Opcode::Return {
+                    let expected: Vec<Type> = ir.signature.returns.iter().map(|param| param.value_type).collect();
+                    let provided: Vec<Type> = values.iter().map(|&value| ir.dfg.value_type(value)).collect();
+                    if provided != expected {
+                        let declared = match &func.return_type {
+                            _ if func.is_main => "int".to_string(),
+                            Some(return_type) => return_type.to_string(),
+                            None => "nil".to_string(),
+                        };
+                        return Err(format!(
+                            "function '{}' returns {}, but its declared return type {} needs {}",
+                            name, type_list(&provided), declared, type_list(&expected)
+                        ));
+                    }
+                }
+            }
+        }
+        
+        Ok(())
+    }
+}
@@ -0,0 +1,335 @@
+#[cfg(test)]
+mod tests {
+    use crate::ast::*;
+    use crate::ir_gen::{IRError, IRGenerator};
+    use crate::semantic::AnalyzedProgram;
+    use crate::types::{ChifType, ChifValue};
+
+    use cranelift::prelude::settings;
+    use cranelift::prelude::types;
+    use cranelift_object::{ObjectBuilder, ObjectModule};
+    use std::collections::HashMap;
+    use target_lexicon::Triple;
+
+    fn host_module() -> ObjectModule {
+        let flags = settings::Flags::new(settings::builder());
+        let isa = cranelift::codegen::isa::lookup(Triple::host())
+            .expect("host ISA should be available")
+            .finish(flags)
+            .expect("host ISA should build");
+        let builder = ObjectBuilder::new(
+            isa,
+            "rono_test".to_string(),
+            cranelift_module::default_libcall_names(),
+        ).expect("object builder should be created");
+        ObjectModule::new(builder)
+    }
+
+    fn call(name: &str, args: Vec<Expression>) -> Expression {
+        Expression::Call(FunctionCall { name: name.to_string(), args, named_args: Vec::new() })
+    }
+
+    fn con_out(arg: Expression) -> Statement {
+        Statement::Expression(Expression::MethodCall(MethodCall {
+            object: Box::new(Expression::Identifier("con".to_string())),
+            method: "out".to_string(),
+            args: vec![arg],
+        }))
+    }
+
+    fn returning(name: &str, return_type: ChifType, value: ChifValue) -> Item {
+        Item::Function(Function {
+            name: name.to_string(),
+            params: vec![],
+            return_type: Some(return_type),
+            body: Block {
+                statements: vec![Statement::Return(Some(Expression::Literal(value)))],
+            },
+            is_main: false,
+            deprecated: None,
+            is_exported: false,
+        })
+    }
+
+    #[test]
+    fn test_print_dispatch_uses_call_return_types() {
+        let program = AnalyzedProgram::new(vec![
+            returning("half", ChifType::Float, ChifValue::Float(0.5)),
+            returning("greeting", ChifType::Str, ChifValue::Str("hi".to_string())),
+            Item::Function(Function {
+                name: "main".to_string(),
+                params: vec![],
+                return_type: None,
+                body: Block {
+                    statements: vec![
+                        con_out(call("randf", vec![
+                            Expression::Literal(ChifValue::Float(0.0)),
+                            Expression::Literal(ChifValue::Float(1.0)),
+                        ])),
+                        con_out(call("half", vec![])),
+                        con_out(call("greeting", vec![])),
+                    ],
+                },
+                is_main: true,
+                deprecated: None,
+                is_exported: false,
+            }),
+        ]);
+
+        let mut generator = IRGenerator::new(host_module());
+        let result = generator.generate(&program);
+        assert!(result.is_ok(), "Printing typed call results should compile: {:?}", result.err());
+
+        let return_types = &generator.function_return_types;
+        assert_eq!(
+            IRGenerator::select_print_function(&call("randf", vec![]), types::F64, return_types),
+            "rono_print_float"
+        );
+        assert_eq!(
+            IRGenerator::select_print_function(&call("half", vec![]), types::F64, return_types),
+            "rono_print_float"
+        );
+        assert_eq!(
+            IRGenerator::select_print_function(&call("greeting", vec![]), types::I64, return_types),
+            "rono_print_string"
+        );
+    }
+
+    #[test]
+    fn test_print_dispatch_falls_back_to_value_type() {
+        let return_types = HashMap::new();
+        let variable = Expression::Identifier("x".to_string());
+
+        assert_eq!(IRGenerator::select_print_function(&variable, types::F64, &return_types), "rono_print_float");
+        assert_eq!(IRGenerator::select_print_function(&variable, types::I8, &return_types), "rono_print_bool");
+        assert_eq!(IRGenerator::select_print_function(&variable, types::I64, &return_types), "rono_print_int");
+    }
+
+    #[test]
+    fn test_list_unique_on_literal_compiles() {
+        let numbers = vec![3, 1, 2, 1, 3].into_iter()
+            .map(|n| Expression::Literal(ChifValue::Int(n)))
+            .collect();
+        let program = AnalyzedProgram::new(vec![Item::Function(Function {
+            name: "main".to_string(),
+            params: vec![],
+            return_type: None,
+            body: Block {
+                statements: vec![Statement::Expression(Expression::MethodCall(MethodCall {
+                    object: Box::new(Expression::ArrayLiteral(numbers)),
+                    method: "unique".to_string(),
+                    args: vec![],
+                }))],
+            },
+            is_main: true,
+            deprecated: None,
+            is_exported: false,
+        })]);
+
+        let mut generator = IRGenerator::new(host_module());
+        let result = generator.generate(&program);
+        assert!(result.is_ok(), "unique() on a list literal should compile: {:?}", result.err());
+    }
+
+    #[test]
+    fn test_tuple_return_and_destructure_compile() {
+        let tokens = crate::lexer::Lexer::new(r#"
+            fn divmod(a: int, b: int) (int, int) {
+                ret (a / b, a - (a / b) * b);
+            }
+
+            chif main() {
+                var (q, r) = divmod(7, 2);
+                con.out(q);
+                con.out(r);
+            }
+        "#).tokenize().expect("source should tokenize");
+        let program = crate::parser::Parser::new(tokens).parse().expect("source should parse");
+
+        let mut generator = IRGenerator::new(host_module());
+        let result = generator.generate(&AnalyzedProgram::new(program.items));
+        assert!(result.is_ok(), "Tuple returns should compile to multiple return values: {:?}", result.err());
+    }
+
+    #[test]
+    fn test_global_variables_are_rejected_by_the_compiler() {
+        let tokens = crate::lexer::Lexer::new("var calls: int = 0;\nchif main() { calls = 1; }").tokenize().expect("source should tokenize");
+        let program = crate::parser::Parser::new(tokens).parse().expect("source should parse");
+        match IRGenerator::new(host_module()).generate(&AnalyzedProgram::new(program.items)) {
+            Err(IRError::UnsupportedFeature(message)) => assert!(message.starts_with("Global variable 'calls'"), "{}", message),
+            other => panic!("globals should be unsupported in compiled code, got {:?}", other),
+        }
+    }
+
+    /// Generates unanalyzed source, so type mismatches reach the IR generator
+    fn signature_mismatch(source: &str) -> String {
+        let tokens = crate::lexer::Lexer::new(source).tokenize().expect("source should tokenize");
+        let program = crate::parser::Parser::new(tokens).parse().expect("source should parse");
+        match IRGenerator::new(host_module()).generate(&AnalyzedProgram::new(program.items)) {
+            Err(IRError::SignatureMismatch(message)) => message,
+            other => panic!("expected a signature mismatch, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_call_argument_types_are_verified() {
+        let message = signature_mismatch(r#"
+            fn scale(count: int, factor: float) float {
+                ret factor;
+            }
+
+            chif main() {
+                con.out(scale(2, true));
+            }
+        "#);
+        assert_eq!(message, "call to 'scale' passes i8 as parameter 2 ('factor'), expected float (f64)");
+    }
+
+    #[test]
+    fn test_return_types_are_verified() {
+        let message = signature_mismatch(r#"
+            fn ratio() float {
+                ret true;
+            }
+
+            chif main() {
+                con.out(ratio());
+            }
+        "#);
+        assert_eq!(message, "function 'ratio' returns i8, but its declared return type float needs f64");
+    }
+
+    #[test]
+    fn test_large_array_literal_moves_to_static_data() {
+        // var big: array[int] = [0, 3, 6, ...]; con.out(big[9999]);
+        let elements: Vec<Expression> = (0..10_000)
+            .map(|i| Expression::Literal(ChifValue::Int(i * 3)))
+            .collect();
+        let program = AnalyzedProgram::new(vec![Item::Function(Function {
+            name: "main".to_string(),
+            params: vec![],
+            return_type: None,
+            body: Block {
+                statements: vec![
+                    Statement::VarDecl(VarDecl {
+                        name: "big".to_string(),
+                        var_type: ChifType::Array(Box::new(ChifType::Int), vec![10_000]),
+                        value: Some(Expression::ArrayLiteral(elements)),
+                        is_mutable: false,
+                    }),
+                    con_out(Expression::Index(IndexAccess {
+                        object: Box::new(Expression::Identifier("big".to_string())),
+                        indices: vec![Expression::Literal(ChifValue::Int(9_999))],
+                    })),
+                ],
+            },
+            is_main: true,
+            deprecated: None,
+            is_exported: false,
+        })]);
+
+        let mut generator = IRGenerator::new(host_module());
+        let result = generator.generate(&program);
+        assert!(result.is_ok(), "A 10k-element literal should compile: {:?}", result.err());
+
+        let warnings = generator.context.warnings.clone();
+        assert_eq!(warnings.len(), 1, "Expected exactly one warning, got {:?}", warnings);
+        // big is only indexed, so the literal is used in place
+        assert!(warnings[0].contains("80000 bytes") && warnings[0].contains("static read-only data"));
+    }
+
+    #[test]
+    fn test_small_array_literal_stays_on_stack() {
+        let program = AnalyzedProgram::new(vec![Item::Function(Function {
+            name: "main".to_string(),
+            params: vec![],
+            return_type: None,
+            body: Block {
+                statements: vec![Statement::VarDecl(VarDecl {
+                    name: "small".to_string(),
+                    var_type: ChifType::Array(Box::new(ChifType::Int), vec![3]),
+                    value: Some(Expression::ArrayLiteral(vec![
+                        Expression::Literal(ChifValue::Int(1)),
+                        Expression::Literal(ChifValue::Int(2)),
+                        Expression::Literal(ChifValue::Int(3)),
+                    ])),
+                    is_mutable: false,
+                })],
+            },
+            is_main: true,
+            deprecated: None,
+            is_exported: false,
+        })]);
+
+        let mut generator = IRGenerator::new(host_module());
+        assert!(generator.generate(&program).is_ok());
+        assert!(generator.context.warnings.is_empty(), "Small literals should not warn: {:?}", generator.context.warnings);
+    }
+
+    #[test]
+    fn test_struct_layout_holds_nested_collections_by_pointer() {
+        use crate::ir_gen::StructLayout;
+
+        let field = |name: &str, field_type: ChifType| StructField { name: name.to_string(), field_type, is_private: false };
+        let nested = StructDef {
+            name: "Registry".to_string(),
+            fields: vec![
+                field("flag", ChifType::Bool),
+                field("grid", ChifType::Array(Box::new(ChifType::Int), vec![3, 4])),
+                field("rows", ChifType::List(Box::new(ChifType::Map(Box::new(ChifType::Str), Box::new(ChifType::Int))), vec![0])),
+                field("index", ChifType::Map(Box::new(ChifType::Str), Box::new(ChifType::Struct("Point".to_string())))),
+                field("cells", ChifType::Pointer(Box::new(ChifType::Array(Box::new(ChifType::Int), vec![0])))),
+            ],
+            is_exported: false,
+        };
+        let layout = StructLayout::compute(&nested).expect("every nesting the parser accepts should lay out");
+        let offsets: Vec<u32> = layout.fields.iter().map(|field| field.offset).collect();
+        assert_eq!(offsets, vec![0, 8, 16, 24, 32]);
+        assert_eq!(layout.size, 40);
+
+        let with_tuple = StructDef {
+            name: "Pair".to_string(),
+            fields: vec![field("both", ChifType::Tuple(vec![ChifType::Int, ChifType::Int]))],
+            is_exported: false,
+        };
+        assert!(matches!(StructLayout::compute(&with_tuple), Err(IRError::UnsupportedFeature(_))));
+    }
+
+    #[test]
+    fn test_struct_layout_looks_up_fields_of_wide_structs_by_name() {
+        use crate::ir_gen::StructLayout;
+
+        let field = |name: String, field_type: ChifType| StructField { name, field_type, is_private: false };
+        let types = [ChifType::Int, ChifType::Bool, ChifType::Float, ChifType::Str];
+        let wide = StructDef {
+            name: "Config".to_string(),
+            fields: (0..64).map(|i| field(format!("f{}", i), types[i % 4].clone())).collect(),
+            is_exported: false,
+        };
+        let layout = StructLayout::compute(&wide).expect("a wide struct should lay out");
+        // int, bool (padded to 8), float, str: every field takes 8 bytes
+        for (i, expected) in layout.fields.iter().enumerate() {
+            let found = layout.field(&format!("f{}", i)).expect("every field should be found");
+            assert_eq!((found.offset, &found.field_type), (i as u32 * 8, &types[i % 4]));
+            assert_eq!(found.offset, expected.offset);
+        }
+        assert!(layout.field("f64").is_none());
+        assert_eq!(layout.size, 512);
+
+        let dump = format!("{:?}", layout);
+        assert!(dump.starts_with("StructLayout { name: \"Config\", fields: [StructFieldLayout { name: \"f0\""), "{}", dump);
+        assert!(!dump.contains("field_index"), "{}", dump);
+
+        let twice = StructDef {
+            name: "Point".to_string(),
+            fields: vec![field("x".to_string(), ChifType::Int), field("x".to_string(), ChifType::Float)],
+            is_exported: false,
+        };
+        match StructLayout::compute(&twice) {
+            Err(IRError::Generation(message)) => assert_eq!(message, "Struct 'Point' declares field 'x' more than once"),
+            other => panic!("duplicate fields should be rejected, got {:?}", other.map(|layout| layout.size)),
+        }
+        let error = crate::analyze_source("struct Point {\n    x: int,\n    x: float,\n}\n").expect_err("the analyzer should reject it too");
+        assert!(error.to_string().contains("Struct 'Point' declares field 'x' more than once"), "{}", error);
+    }
+}
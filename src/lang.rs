@@ -0,0 +1,134 @@
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Уровень языка (`--lang-version`, ключ `lang-version` в rono.toml).
+/// Определяет форму встроенных функций, которые менялись между уровнями:
+/// программа без уровня сохраняет поведение, для которого её писали
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct LangVersion(u32);
+
+impl LangVersion {
+    /// Исходное поведение: `http.get` и остальные методы `http` возвращают тело ответа (str)
+    pub const V1: LangVersion = LangVersion(1);
+    /// Методы `http` возвращают HttpResponse, как `http_get` и т.д.
+    pub const V2: LangVersion = LangVersion(2);
+    pub const LATEST: LangVersion = LangVersion::V2;
+
+    pub fn new(level: u32) -> Result<Self, String> {
+        if (1..=Self::LATEST.0).contains(&level) {
+            Ok(LangVersion(level))
+        } else {
+            Err(format!("unknown language level {} (supported: 1..={})", level, Self::LATEST.0))
+        }
+    }
+
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let level = text.trim().parse::<u32>()
+            .map_err(|_| format!("language level must be a number, found '{}'", text.trim()))?;
+        Self::new(level)
+    }
+
+    pub fn level(self) -> u32 {
+        self.0
+    }
+}
+
+impl Default for LangVersion {
+    fn default() -> Self {
+        LangVersion::V1
+    }
+}
+
+impl fmt::Display for LangVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+/// Запись реестра встроенных функций: с какого уровня форма есть и с какого
+/// она устарела (будет изменена или удалена на следующем уровне)
+#[derive(Debug, Clone, Copy)]
+pub struct BuiltinInfo {
+    pub name: &'static str,
+    pub introduced: LangVersion,
+    pub deprecated: Option<LangVersion>,
+    /// Что использовать вместо устаревшей формы
+    pub note: &'static str,
+}
+
+const HTTP_METHOD_NOTE: &str = "it returns the response body as str; from language level 2 it returns HttpResponse (--lang-version 2)";
+
+pub const BUILTINS: &[BuiltinInfo] = &[
+    BuiltinInfo { name: "con.out", introduced: LangVersion::V1, deprecated: None, note: "" },
+    BuiltinInfo { name: "con.in", introduced: LangVersion::V1, deprecated: None, note: "" },
+    BuiltinInfo { name: "mem.reset", introduced: LangVersion::V1, deprecated: None, note: "" },
+    BuiltinInfo { name: "mem.used", introduced: LangVersion::V1, deprecated: None, note: "" },
+    BuiltinInfo { name: "http.get", introduced: LangVersion::V1, deprecated: Some(LangVersion::V1), note: HTTP_METHOD_NOTE },
+    BuiltinInfo { name: "http.post", introduced: LangVersion::V1, deprecated: Some(LangVersion::V1), note: HTTP_METHOD_NOTE },
+    BuiltinInfo { name: "http.put", introduced: LangVersion::V1, deprecated: Some(LangVersion::V1), note: HTTP_METHOD_NOTE },
+    BuiltinInfo { name: "http.delete", introduced: LangVersion::V1, deprecated: Some(LangVersion::V1), note: HTTP_METHOD_NOTE },
+    BuiltinInfo { name: "http_get", introduced: LangVersion::V1, deprecated: None, note: "" },
+    BuiltinInfo { name: "http_post", introduced: LangVersion::V1, deprecated: None, note: "" },
+    BuiltinInfo { name: "http_put", introduced: LangVersion::V1, deprecated: None, note: "" },
+    BuiltinInfo { name: "http_delete", introduced: LangVersion::V1, deprecated: None, note: "" },
+];
+
+pub fn builtin_info(name: &str) -> Option<&'static BuiltinInfo> {
+    BUILTINS.iter().find(|info| info.name == name)
+}
+
+impl BuiltinInfo {
+    pub fn is_available(&self, version: LangVersion) -> bool {
+        version >= self.introduced
+    }
+
+    /// Устарела ли форма на уровне `version`. Форма, устаревшая на уровне N,
+    /// меняется на N + 1, поэтому предупреждение только на самом уровне N
+    pub fn is_deprecated(&self, version: LangVersion) -> bool {
+        self.deprecated == Some(version)
+    }
+}
+
+/// Файл настроек проекта: ищется в каталоге программы и выше
+pub const PROJECT_FILE: &str = "rono.toml";
+
+/// Ближайший rono.toml для файла программы
+pub fn find_project_file(program: &Path) -> Option<PathBuf> {
+    let start = fs::canonicalize(program).ok()?;
+    start.ancestors()
+        .skip(1)
+        .map(|dir| dir.join(PROJECT_FILE))
+        .find(|candidate| candidate.is_file())
+}
+
+/// `lang-version` из текста rono.toml. Читаются только строки `ключ = значение`
+/// верхнего уровня; остальные ключи и таблицы пропускаются
+pub fn parse_project_lang_version(text: &str) -> Result<Option<LangVersion>, String> {
+    for (index, line) in text.lines().enumerate() {
+        let line = line.split('#').next().unwrap_or_default().trim();
+        if line.starts_with('[') {
+            break;
+        }
+        let Some((key, value)) = line.split_once('=') else {
+            continue;
+        };
+        if key.trim() == "lang-version" {
+            let value = value.trim().trim_matches('"');
+            return LangVersion::parse(value)
+                .map(Some)
+                .map_err(|message| format!("{} line {}: {}", PROJECT_FILE, index + 1, message));
+        }
+    }
+    Ok(None)
+}
+
+/// Уровень из rono.toml рядом с программой; без файла или ключа — `None`
+pub fn project_lang_version(program: &Path) -> Result<Option<LangVersion>, String> {
+    let Some(path) = find_project_file(program) else {
+        return Ok(None);
+    };
+    let text = fs::read_to_string(&path)
+        .map_err(|e| format!("cannot read '{}': {}", path.display(), e))?;
+    parse_project_lang_version(&text)
+}
@@ -0,0 +1,36 @@
+#[cfg(test)]
+mod tests {
+    use crate::lang::{builtin_info, parse_project_lang_version, project_lang_version, LangVersion, PROJECT_FILE};
+
+    #[test]
+    fn test_lang_version_parsing_and_registry() {
+        assert_eq!(LangVersion::parse("2"), Ok(LangVersion::V2));
+        assert_eq!(LangVersion::default(), LangVersion::V1);
+        assert!(LangVersion::parse("0").is_err());
+        assert!(LangVersion::parse("3").unwrap_err().contains("supported: 1..=2"));
+        assert!(LangVersion::parse("two").is_err());
+
+        let get = builtin_info("http.get").expect("http.get should be registered");
+        assert!(get.is_deprecated(LangVersion::V1));
+        assert!(!get.is_deprecated(LangVersion::V2));
+        assert!(builtin_info("http_get").is_some_and(|info| info.is_available(LangVersion::V1)));
+    }
+
+    #[test]
+    fn test_project_file_sets_lang_version() {
+        let text = "# проект\nname = \"demo\"\nlang-version = 2 # новый уровень\n\n[build]\nlang-version = 1\n";
+        assert_eq!(parse_project_lang_version(text), Ok(Some(LangVersion::V2)));
+        assert_eq!(parse_project_lang_version("name = \"demo\""), Ok(None));
+        assert!(parse_project_lang_version("lang-version = \"9\"").unwrap_err().contains("line 1"));
+
+        // Ищется в каталоге программы и выше
+        let dir = tempfile::tempdir().expect("temp dir should be created");
+        let nested = dir.path().join("src");
+        std::fs::create_dir(&nested).expect("dir should be created");
+        let program = nested.join("main.rono");
+        std::fs::write(&program, "chif main() { }").expect("program should be written");
+        assert_eq!(project_lang_version(&program), Ok(None));
+        std::fs::write(dir.path().join(PROJECT_FILE), "lang-version = 2\n").expect("project file should be written");
+        assert_eq!(project_lang_version(&program), Ok(Some(LangVersion::V2)));
+    }
+}
@@ -23,8 +23,10 @@ pub enum Token {
     Break,
     Continue,
     Import,
+    Export,
     As,
     Ref,
+    Priv,
     
     // Types
     Int,
@@ -71,16 +73,167 @@ pub enum Token {
     Colon,
     Comma,
     Dot,
+    At,
+    
+    // Trivia, only produced by `Lexer::with_trivia(true)`
+    Comment(String),
+    Whitespace(String),
     
     // Special
     Eof,
 }
 
+/// Класс токена для подсветки синтаксиса
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TokenCategory {
+    /// Ключевые слова, имена типов и `true`/`false`
+    Keyword,
+    Identifier,
+    Number,
+    String,
+    Operator,
+    Punctuation,
+    Comment,
+    /// Пробелы и `Eof`
+    Whitespace,
+}
+
+impl Token {
+    pub fn category(&self) -> TokenCategory {
+        match self {
+            Token::Identifier(_) => TokenCategory::Identifier,
+            Token::IntLiteral(_) | Token::FloatLiteral(_) => TokenCategory::Number,
+            Token::StringLiteral(_) => TokenCategory::String,
+            Token::Plus | Token::Minus | Token::Multiply | Token::Divide | Token::Modulo
+            | Token::Assign | Token::Equal | Token::NotEqual | Token::Less | Token::Greater
+            | Token::LessEqual | Token::GreaterEqual | Token::And | Token::Or | Token::Not
+            | Token::Reference | Token::Dereference => TokenCategory::Operator,
+            Token::LeftParen | Token::RightParen | Token::LeftBrace | Token::RightBrace
+            | Token::LeftBracket | Token::RightBracket | Token::Semicolon | Token::Colon
+            | Token::Comma | Token::Dot | Token::At => TokenCategory::Punctuation,
+            Token::Comment(_) => TokenCategory::Comment,
+            Token::Whitespace(_) | Token::Eof => TokenCategory::Whitespace,
+            _ => TokenCategory::Keyword,
+        }
+    }
+
+    pub fn is_trivia(&self) -> bool {
+        matches!(self, Token::Comment(_) | Token::Whitespace(_))
+    }
+
+    /// Написание в исходном тексте у ключевых слов, типов, операторов и
+    /// разделителей; у имён, литералов и служебных токенов его нет
+    pub fn text(&self) -> Option<&'static str> {
+        let text = match self {
+            Token::Chif => "chif",
+            Token::Let => "let",
+            Token::Var => "var",
+            Token::Array => "array",
+            Token::List => "list",
+            Token::Map => "map",
+            Token::Fn => "fn",
+            Token::FnFor => "fn_for",
+            Token::Struct => "struct",
+            Token::If => "if",
+            Token::Else => "else",
+            Token::For => "for",
+            Token::While => "while",
+            Token::Switch => "switch",
+            Token::Case => "case",
+            Token::Default => "default",
+            Token::Ret => "ret",
+            Token::Break => "break",
+            Token::Continue => "continue",
+            Token::Import => "import",
+            Token::Export => "export",
+            Token::As => "as",
+            Token::Ref => "ref",
+            Token::Priv => "priv",
+            Token::Int => "int",
+            Token::Float => "float",
+            Token::Str => "str",
+            Token::Bool => "bool",
+            Token::Nil => "nil",
+            Token::Pointer => "pointer",
+            Token::BoolLiteral(true) => "true",
+            Token::BoolLiteral(false) => "false",
+            Token::Plus => "+",
+            Token::Minus => "-",
+            Token::Multiply | Token::Dereference => "*",
+            Token::Divide => "/",
+            Token::Modulo => "%",
+            Token::Assign => "=",
+            Token::Equal => "==",
+            Token::NotEqual => "!=",
+            Token::Less => "<",
+            Token::Greater => ">",
+            Token::LessEqual => "<=",
+            Token::GreaterEqual => ">=",
+            Token::And => "&&",
+            Token::Or => "||",
+            Token::Not => "!",
+            Token::Reference => "&",
+            Token::LeftParen => "(",
+            Token::RightParen => ")",
+            Token::LeftBrace => "{",
+            Token::RightBrace => "}",
+            Token::LeftBracket => "[",
+            Token::RightBracket => "]",
+            Token::Semicolon => ";",
+            Token::Colon => ":",
+            Token::Comma => ",",
+            Token::Dot => ".",
+            Token::At => "@",
+            Token::Identifier(_) | Token::IntLiteral(_) | Token::FloatLiteral(_) | Token::StringLiteral(_)
+            | Token::Comment(_) | Token::Whitespace(_) | Token::Eof => return None,
+        };
+        Some(text)
+    }
+
+    /// Токен для сообщений об ошибках: `'}'`, `keyword 'fn'`, `identifier 'foo'`, `string literal`
+    pub fn describe(&self) -> String {
+        match self {
+            Token::Identifier(name) => format!("identifier '{}'", name),
+            Token::IntLiteral(value) => format!("number {}", value),
+            Token::FloatLiteral(value) => format!("number {}", crate::types::format_float(*value)),
+            Token::StringLiteral(_) => "string literal".to_string(),
+            Token::Comment(_) => "comment".to_string(),
+            Token::Whitespace(_) => "whitespace".to_string(),
+            Token::Eof => "end of file".to_string(),
+            token => {
+                let text = token.text().unwrap_or_default();
+                match token.category() {
+                    TokenCategory::Keyword => format!("keyword '{}'", text),
+                    _ => format!("'{}'", text),
+                }
+            }
+        }
+    }
+}
+
+/// Токен с позицией в исходном тексте: `start..end` — смещения в байтах,
+/// `line` и `col` (в символах) считаются с единицы и указывают на начало токена
+#[derive(Debug, Clone, PartialEq)]
+pub struct SpannedToken {
+    pub token: Token,
+    pub start: usize,
+    pub end: usize,
+    pub line: u32,
+    pub col: u32,
+}
+
+/// Наибольшая длина строкового литерала в байтах по умолчанию. Длиннее —
+/// ошибка разбора, а не неограниченный рост одного токена
+pub const MAX_STRING_LITERAL_BYTES: usize = 1 << 20;
+
 pub struct Lexer {
     input: Vec<char>,
     position: usize,
+    byte_offset: usize,
     line: usize,
     column: usize,
+    include_trivia: bool,
+    max_string_literal: usize,
 }
 
 impl Lexer {
@@ -88,25 +241,62 @@ impl Lexer {
         Self {
             input: input.chars().collect(),
             position: 0,
+            byte_offset: 0,
             line: 1,
             column: 1,
+            include_trivia: false,
+            max_string_literal: MAX_STRING_LITERAL_BYTES,
         }
     }
     
+    /// Включает в `tokenize_spanned` комментарии и пробелы
+    pub fn with_trivia(mut self, include: bool) -> Self {
+        self.include_trivia = include;
+        self
+    }
+    
+    /// Предел длины строкового литерала в байтах (после разбора escape-последовательностей)
+    pub fn with_max_string_literal(mut self, bytes: usize) -> Self {
+        self.max_string_literal = bytes;
+        self
+    }
+    
     pub fn tokenize(&mut self) -> Result<Vec<Token>> {
+        Ok(self.tokenize_spanned()?
+            .into_iter()
+            .map(|spanned| spanned.token)
+            .filter(|token| !token.is_trivia())
+            .collect())
+    }
+    
+    pub fn tokenize_spanned(&mut self) -> Result<Vec<SpannedToken>> {
         let mut tokens = Vec::new();
         
         while !self.is_at_end() {
-            self.skip_whitespace();
-            if self.is_at_end() {
-                break;
+            let (start, line, col) = (self.byte_offset, self.line, self.column);
+            let token = match self.peek() {
+                Some(ch) if ch.is_whitespace() => self.whitespace(),
+                Some('/') if self.peek_next() == Some('/') => self.line_comment(),
+                _ => self.next_token()?,
+            };
+            if self.include_trivia || !token.is_trivia() {
+                tokens.push(SpannedToken {
+                    token,
+                    start,
+                    end: self.byte_offset,
+                    line: line as u32,
+                    col: col as u32,
+                });
             }
-            
-            let token = self.next_token()?;
-            tokens.push(token);
         }
         
-        tokens.push(Token::Eof);
+        tokens.push(SpannedToken {
+            token: Token::Eof,
+            start: self.byte_offset,
+            end: self.byte_offset,
+            line: self.line as u32,
+            col: self.column as u32,
+        });
         Ok(tokens)
     }
     
@@ -124,6 +314,7 @@ impl Lexer {
             ':' => Ok(Token::Colon),
             ',' => Ok(Token::Comma),
             '.' => Ok(Token::Dot),
+            '@' => Ok(Token::At),
             '+' => Ok(Token::Plus),
             '-' => Ok(Token::Minus),
             '*' => {
@@ -198,8 +389,18 @@ impl Lexer {
     
     fn string_literal(&mut self) -> Result<Token> {
         let mut value = String::new();
+        // Открывающая кавычка уже прочитана
+        let (line, column) = (self.line, self.column.saturating_sub(1));
         
         while let Some(ch) = self.peek() {
+            if value.len() > self.max_string_literal {
+                return Err(ChifError::LexerError {
+                    line,
+                    column,
+                    message: format!("String literal is longer than {} bytes", self.max_string_literal),
+                });
+            }
+            
             if ch == '"' {
                 self.advance(); // consume closing quote
                 return Ok(Token::StringLiteral(value));
@@ -323,8 +524,10 @@ impl Lexer {
             "break" => Token::Break,
             "continue" => Token::Continue,
             "import" => Token::Import,
+            "export" => Token::Export,
             "as" => Token::As,
             "ref" => Token::Ref,
+            "priv" => Token::Priv,
             "int" => Token::Int,
             "float" => Token::Float,
             "str" => Token::Str,
@@ -339,43 +542,39 @@ impl Lexer {
         Ok(token)
     }
     
-    fn skip_whitespace(&mut self) {
+    fn whitespace(&mut self) -> Token {
+        let mut value = String::new();
         while let Some(ch) = self.peek() {
-            if ch.is_whitespace() {
-                if ch == '\n' {
-                    self.line += 1;
-                    self.column = 1;
-                } else {
-                    self.column += 1;
-                }
-                self.advance();
-            } else if ch == '/' && self.peek_next() == Some('/') {
-                // Skip line comment
-                self.skip_line_comment();
-            } else {
+            if !ch.is_whitespace() {
                 break;
             }
+            value.push(self.advance());
         }
+        Token::Whitespace(value)
     }
     
-    fn skip_line_comment(&mut self) {
-        // Skip the '//' characters
-        self.advance(); // first '/'
-        self.advance(); // second '/'
-        
-        // Skip until end of line or end of file
+    fn line_comment(&mut self) -> Token {
+        // The comment runs until end of line or end of file, '\n' is not included
+        let mut value = String::new();
         while let Some(ch) = self.peek() {
             if ch == '\n' {
                 break;
             }
-            self.advance();
+            value.push(self.advance());
         }
+        Token::Comment(value)
     }
     
     fn advance(&mut self) -> char {
         let ch = self.input[self.position];
         self.position += 1;
-        self.column += 1;
+        self.byte_offset += ch.len_utf8();
+        if ch == '\n' {
+            self.line += 1;
+            self.column = 1;
+        } else {
+            self.column += 1;
+        }
         ch
     }
     
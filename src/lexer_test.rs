@@ -0,0 +1,112 @@
+#[cfg(test)]
+mod tests {
+    use crate::error::ChifError;
+    use crate::lexer::{Lexer, SpannedToken, Token, TokenCategory, MAX_STRING_LITERAL_BYTES};
+    use crate::parser::Parser;
+
+    const SNIPPET: &str = "let s = \"añ\"; // ü\nx+1";
+
+    fn spanned(token: Token, start: usize, end: usize, line: u32, col: u32) -> SpannedToken {
+        SpannedToken { token, start, end, line, col }
+    }
+
+    #[test]
+    fn test_spans_count_bytes_and_characters() {
+        let tokens = Lexer::new(SNIPPET).tokenize_spanned().expect("snippet should tokenize");
+
+        // ñ and ü take two bytes each but one column
+        assert_eq!(tokens, vec![
+            spanned(Token::Let, 0, 3, 1, 1),
+            spanned(Token::Identifier("s".to_string()), 4, 5, 1, 5),
+            spanned(Token::Assign, 6, 7, 1, 7),
+            spanned(Token::StringLiteral("añ".to_string()), 8, 13, 1, 9),
+            spanned(Token::Semicolon, 13, 14, 1, 13),
+            spanned(Token::Identifier("x".to_string()), 21, 22, 2, 1),
+            spanned(Token::Plus, 22, 23, 2, 2),
+            spanned(Token::IntLiteral(1), 23, 24, 2, 3),
+            spanned(Token::Eof, 24, 24, 2, 4),
+        ]);
+        assert_eq!(&SNIPPET[8..13], "\"añ\"");
+    }
+
+    #[test]
+    fn test_trivia_tokens_cover_the_whole_source() {
+        let tokens = Lexer::new(SNIPPET).with_trivia(true).tokenize_spanned()
+            .expect("snippet should tokenize");
+
+        assert!(tokens.contains(&spanned(Token::Comment("// ü".to_string()), 15, 20, 1, 15)));
+        assert!(tokens.contains(&spanned(Token::Whitespace("\n".to_string()), 20, 21, 1, 19)));
+
+        // Spans are contiguous, so the source can be rebuilt from them
+        let mut offset = 0;
+        for token in &tokens {
+            assert_eq!(token.start, offset, "{:?}", token);
+            offset = token.end;
+        }
+        assert_eq!(offset, SNIPPET.len());
+    }
+
+    #[test]
+    fn test_tokenize_drops_spans_and_trivia() {
+        let tokens = Lexer::new(SNIPPET).with_trivia(true).tokenize().expect("snippet should tokenize");
+        assert_eq!(tokens.len(), 9);
+        assert!(!tokens.iter().any(Token::is_trivia));
+    }
+
+    #[test]
+    fn test_token_categories() {
+        let tokens = Lexer::new("fn f() int { ret 1.5 * \"s\"; } // done").with_trivia(true)
+            .tokenize_spanned()
+            .expect("source should tokenize");
+        let categories: Vec<TokenCategory> = tokens.iter()
+            .map(|spanned| spanned.token.category())
+            .filter(|category| *category != TokenCategory::Whitespace)
+            .collect();
+
+        assert_eq!(categories, vec![
+            TokenCategory::Keyword,
+            TokenCategory::Identifier,
+            TokenCategory::Punctuation,
+            TokenCategory::Punctuation,
+            TokenCategory::Keyword,
+            TokenCategory::Punctuation,
+            TokenCategory::Keyword,
+            TokenCategory::Number,
+            TokenCategory::Operator,
+            TokenCategory::String,
+            TokenCategory::Punctuation,
+            TokenCategory::Punctuation,
+            TokenCategory::Comment,
+        ]);
+    }
+
+    #[test]
+    fn test_parser_consumes_spanned_tokens() {
+        let source = "// header\nfn add(a: int, b: int) int {\n    ret a + b; // sum\n}\nchif main() { }";
+        let spanned = Lexer::new(source).with_trivia(true).tokenize_spanned().expect("source should tokenize");
+        let plain = Lexer::new(source).tokenize().expect("source should tokenize");
+
+        let mut parser = Parser::from_spanned(spanned);
+        assert_eq!(parser.current_location(), Some((2, 1)));
+        let from_spanned = parser.parse().expect("spanned tokens should parse");
+        let from_plain = Parser::new(plain).parse().expect("plain tokens should parse");
+        assert_eq!(format!("{:?}", from_spanned), format!("{:?}", from_plain));
+    }
+
+    #[test]
+    fn test_string_literal_length_is_capped() {
+        let source = "let s = \"0123456789abcdef\";";
+        assert!(Lexer::new(source).with_max_string_literal(16).tokenize().is_ok());
+        match Lexer::new(source).with_max_string_literal(15).tokenize() {
+            Err(ChifError::LexerError { line, column, message }) => {
+                assert_eq!((line, column), (1, 9), "the error points at the opening quote");
+                assert_eq!(message, "String literal is longer than 15 bytes");
+            }
+            other => panic!("expected a lexer error, got {:?}", other),
+        }
+
+        // Без настройки предел — MAX_STRING_LITERAL_BYTES
+        let huge = format!("\"{}\"", "x".repeat(MAX_STRING_LITERAL_BYTES + 1));
+        assert!(matches!(Lexer::new(&huge).tokenize(), Err(ChifError::LexerError { .. })));
+    }
+}
@@ -1,22 +1,199 @@
 pub mod lexer;
 pub mod parser;
 pub mod ast;
+pub mod interner;
 pub mod interpreter;
 pub mod types;
 pub mod error;
+#[cfg(feature = "backend-cranelift")]
 pub mod compiler;
 pub mod semantic;
+#[cfg(feature = "backend-cranelift")]
 pub mod ir_gen;
+pub mod bench;
+pub mod testing;
+#[cfg(feature = "backend-cranelift")]
+pub mod header;
+pub mod examples;
+pub mod logger;
+pub mod style;
+pub mod metrics;
+pub mod workspace;
+pub mod lang;
+pub mod watch;
+pub mod progress;
+pub mod prelude;
+pub mod diagnostics;
+pub mod profile;
 
 #[cfg(test)]
 mod semantic_test;
+#[cfg(all(test, feature = "backend-cranelift"))]
+mod ir_gen_test;
+#[cfg(all(test, feature = "backend-cranelift"))]
+mod compiler_test;
+#[cfg(test)]
+mod bench_test;
+#[cfg(test)]
+mod testing_test;
+#[cfg(all(test, feature = "backend-cranelift"))]
+mod conformance_test;
+#[cfg(test)]
+mod error_test;
+#[cfg(test)]
+mod diagnostics_test;
+#[cfg(all(test, feature = "backend-cranelift"))]
+mod header_test;
+#[cfg(test)]
+mod interpreter_test;
+#[cfg(test)]
+mod examples_test;
+#[cfg(test)]
+mod lexer_test;
+#[cfg(test)]
+mod parser_test;
+#[cfg(test)]
+mod metrics_test;
+#[cfg(test)]
+mod workspace_test;
+#[cfg(test)]
+mod lang_test;
+#[cfg(test)]
+mod watch_test;
+#[cfg(test)]
+mod method_body_test;
+#[cfg(test)]
+mod profile_test;
+#[cfg(test)]
+mod interner_test;
 
-pub use error::{ChifError, Result};
-pub use lexer::Lexer;
+pub use error::{ChifError, ErrorFormat, ErrorReport, Result, RonoError, SourceLocation};
+pub use lexer::{Lexer, SpannedToken, Token, TokenCategory};
 pub use parser::Parser;
 pub use interpreter::Interpreter;
-pub use ast::Program;
-pub use types::{ChifType, ChifValue};
-pub use compiler::{Compiler, CompilerError, Target, OptLevel, detect_host_target};
-pub use semantic::{SemanticAnalyzer, SemanticError, AnalyzedProgram};
-pub use ir_gen::{IRGenerator, IRError};
\ No newline at end of file
+pub use ast::{EntryPoint, ImportScheme, ImportStatement, Program};
+pub use types::{ChifType, ChifValue, StructFields};
+#[cfg(feature = "backend-cranelift")]
+pub use compiler::{Compiler, CompilerError, CompiledArtifact, Target, OptLevel, detect_host_target};
+pub use semantic::{SemanticAnalyzer, SemanticError, AnalyzedProgram, SymbolInfo, SymbolKind, SymbolOrigin};
+#[cfg(feature = "backend-cranelift")]
+pub use ir_gen::{IRGenerator, IRError};
+pub use bench::{BenchOptions, BenchStats, BenchReport, run_benchmark};
+pub use testing::{TestCase, TestOptions, TestReport, TestResult, TestStatus, run_tests};
+pub use examples::{ExampleHeader, ExamplesReport, verify_examples};
+pub use metrics::{FileMetrics, FunctionMetrics, MetricLimit, MetricsReport};
+pub use workspace::{FileDiagnostics, WorkspaceAnalysis};
+pub use lang::LangVersion;
+pub use watch::{WatchRun, WatchSet, Watcher};
+pub use progress::{CompileSummary, ProgressEvent, ProgressSink, Stage};
+pub use profile::{CallKind, FunctionProfile, Profile, Profiler};
+
+/// Лексический и синтаксический разбор исходного текста
+pub fn parse_source(source: &str) -> std::result::Result<Program, RonoError> {
+    let tokens = Lexer::new(source).tokenize_spanned().map_err(RonoError::Lex)?;
+    Parser::from_spanned(tokens).parse().map_err(RonoError::Parse)
+}
+
+thread_local! {
+    /// Файлы, которые `load_module` читал в этом потоке, — зависимости для `--watch`
+    static LOADED_MODULES: std::cell::RefCell<Vec<std::path::PathBuf>> = const { std::cell::RefCell::new(Vec::new()) };
+}
+
+/// Чтение и разбор импортируемого модуля. Общий для анализатора, генератора IR
+/// и интерпретатора: компилятор передаёт генератору модули, уже разобранные анализатором.
+/// О каждом модуле узнаёт подписчик `progress::set_sink`
+pub fn load_module(file_path: &str, canonical_path: &std::path::Path) -> std::result::Result<Program, String> {
+    if let Some(source) = bundled_source(canonical_path) {
+        progress::parsing_module(canonical_path);
+        return load_module_source(file_path, source);
+    }
+    // Модуль с ошибкой тоже зависимость: его исправление должно перезапустить прогон
+    LOADED_MODULES.with(|modules| modules.borrow_mut().push(canonical_path.to_path_buf()));
+    progress::parsing_module(canonical_path);
+    let source = std::fs::read_to_string(canonical_path)
+        .map_err(|_| format!("Could not read module file: {}", file_path))?;
+    load_module_source(file_path, &source)
+}
+
+/// Модули, встроенные в крейт: `import "pkg:<имя>"`
+const BUNDLED_MODULES: &[(&str, &str)] = &[("prelude", prelude::SOURCE)];
+
+/// Текст встроенного модуля по его пути `pkg:<имя>`
+fn bundled_source(path: &std::path::Path) -> Option<&'static str> {
+    let name = path.to_str()?.strip_prefix("pkg:")?;
+    BUNDLED_MODULES.iter().find(|(bundled, _)| *bundled == name).map(|(_, source)| *source)
+}
+
+/// Путь, на который указывает импорт, без проверки, что файл есть. `./utils`,
+/// `../lib/utils` и прежняя форма `utils` отсчитываются от `from` — каталога
+/// импортирующего файла (`None`: файл неизвестен, от текущего каталога),
+/// `/abs/utils` берётся как есть, встроенный модуль получает путь `pkg:<имя>`
+pub fn import_candidate(import: &ImportStatement, from: Option<&std::path::Path>) -> std::result::Result<std::path::PathBuf, String> {
+    let file_path = import.file_path();
+    match import.scheme() {
+        ImportScheme::Package => {
+            let name = import.path.trim_start_matches("pkg:").trim_end_matches(".rono");
+            if BUNDLED_MODULES.iter().any(|(bundled, _)| *bundled == name) {
+                return Ok(std::path::PathBuf::from(format!("pkg:{}", name)));
+            }
+            let bundled: Vec<String> = BUNDLED_MODULES.iter().map(|(bundled, _)| format!("pkg:{}", bundled)).collect();
+            Err(format!(
+                "Could not find module '{}': searched the bundled modules ({})",
+                import.path, bundled.join(", ")
+            ))
+        }
+        ImportScheme::Absolute => Ok(std::path::PathBuf::from(file_path)),
+        ImportScheme::Relative | ImportScheme::Bare => Ok(match from {
+            Some(dir) => dir.join(file_path),
+            None => std::path::PathBuf::from(file_path),
+        }),
+    }
+}
+
+/// Канонический путь модуля — ключ кэшей модулей у анализатора, генератора IR
+/// и интерпретатора. Если файла нет, ошибка говорит, где его искали
+pub fn resolve_import(import: &ImportStatement, from: Option<&std::path::Path>) -> std::result::Result<std::path::PathBuf, String> {
+    let candidate = import_candidate(import, from)?;
+    if bundled_source(&candidate).is_some() {
+        return Ok(candidate);
+    }
+    std::fs::canonicalize(&candidate).map_err(|_| {
+        let searched = match (import.scheme(), from) {
+            (ImportScheme::Absolute, _) => "as an absolute path".to_string(),
+            (_, Some(dir)) => format!("relative to the importing file's directory {}", dir.display()),
+            (_, None) => "relative to the working directory".to_string(),
+        };
+        let legacy = if import.scheme() == ImportScheme::Bare {
+            format!("; a path without './' is resolved like './{}'", import.path)
+        } else {
+            String::new()
+        };
+        format!("Could not find module '{}': looked for {} ({}){}", import.path, candidate.display(), searched, legacy)
+    })
+}
+
+/// Разбор модуля, исходный текст которого уже в памяти (прелюдия, модули
+/// встраивающего приложения); `name` попадает в сообщения об ошибках
+pub fn load_module_source(name: &str, source: &str) -> std::result::Result<Program, String> {
+    let tokens = Lexer::new(source).tokenize_spanned()
+        .map_err(|e| format!("Failed to tokenize module {}: {}", name, e))?;
+    Parser::from_spanned(tokens).parse()
+        .map_err(|e| format!("Failed to parse module {}: {}", name, e))
+}
+
+/// Модули, прочитанные `load_module` в этом потоке с прошлого вызова
+pub fn take_loaded_modules() -> Vec<std::path::PathBuf> {
+    LOADED_MODULES.with(|modules| std::mem::take(&mut *modules.borrow_mut()))
+}
+
+/// Разбор и семантический анализ
+pub fn analyze_source(source: &str) -> std::result::Result<AnalyzedProgram, RonoError> {
+    let program = parse_source(source)?;
+    Ok(SemanticAnalyzer::new().analyze_owned(program)?)
+}
+
+/// Разбор и выполнение программы в интерпретаторе
+pub fn run_source(source: &str) -> std::result::Result<(), RonoError> {
+    let program = parse_source(source)?;
+    Interpreter::new().execute(&program).map_err(RonoError::runtime)
+}
@@ -0,0 +1,75 @@
+use std::fmt::Display;
+use std::sync::atomic::{AtomicBool, AtomicU8, Ordering};
+
+/// Уровень подробности сообщений CLI (`-q` / `-v`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Verbosity {
+    /// Только ошибки
+    Quiet,
+    Normal,
+    /// Этапы компиляции и их время
+    Verbose,
+}
+
+static VERBOSITY: AtomicU8 = AtomicU8::new(Verbosity::Normal as u8);
+static MESSAGES_TO_STDERR: AtomicBool = AtomicBool::new(false);
+
+pub fn set_verbosity(verbosity: Verbosity) {
+    VERBOSITY.store(verbosity as u8, Ordering::Relaxed);
+}
+
+pub fn verbosity() -> Verbosity {
+    match VERBOSITY.load(Ordering::Relaxed) {
+        0 => Verbosity::Quiet,
+        1 => Verbosity::Normal,
+        _ => Verbosity::Verbose,
+    }
+}
+
+/// Переносит обычные сообщения в stderr, чтобы stdout оставался машиночитаемым
+/// (`--status-json`)
+pub fn set_messages_to_stderr(to_stderr: bool) {
+    MESSAGES_TO_STDERR.store(to_stderr, Ordering::Relaxed);
+}
+
+fn message(text: impl Display) {
+    if MESSAGES_TO_STDERR.load(Ordering::Relaxed) {
+        eprintln!("{}", text);
+    } else {
+        println!("{}", text);
+    }
+}
+
+/// Итог команды, например "Compilation successful! Output: app"
+pub fn info(text: impl Display) {
+    if verbosity() >= Verbosity::Normal {
+        message(text);
+    }
+}
+
+/// Ход компиляции и время этапов, только с `-v`
+pub fn verbose(text: impl Display) {
+    if verbosity() >= Verbosity::Verbose {
+        message(text);
+    }
+}
+
+/// Предупреждения и прочая диагностика, кроме ошибок
+pub fn warn(text: impl Display) {
+    if verbosity() >= Verbosity::Normal {
+        eprintln!("{}", text);
+    }
+}
+
+/// Ход загрузки модулей и компиляции, как у cargo: всегда в stderr, чтобы не
+/// смешиваться с выводом программы
+pub fn progress(text: impl Display, level: Verbosity) {
+    if verbosity() >= level {
+        eprintln!("{}", text);
+    }
+}
+
+/// Ошибки выводятся на любом уровне
+pub fn error(text: impl Display) {
+    eprintln!("{}", text);
+}
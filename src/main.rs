@@ -1,7 +1,20 @@
 use rono_lang::*;
-use clap::{Arg, Command};
+use rono_lang::compiler::DiagnosticLevel;
+use rono_lang::diagnostics::{self, Code, WarningAction, WarningPolicy};
+use rono_lang::logger::{self, Verbosity};
+use rono_lang::lang;
+use rono_lang::progress;
+use rono_lang::style;
+use clap::{Arg, ArgMatches, Command};
+use serde_json::{json, Value};
+use std::any::Any;
 use std::fs;
+use std::io::IsTerminal;
+use std::panic::{self, AssertUnwindSafe};
+use std::path::Path;
 use std::process;
+use std::thread;
+use std::time::{Duration, Instant, SystemTime};
 
 fn main() {
     let matches = Command::new("rono")
@@ -18,6 +31,112 @@ fn main() {
                         .required(true)
                         .index(1),
                 )
+                .arg(
+                    Arg::new("error-format")
+                        .long("error-format")
+                        .help("How errors are printed: pretty when stderr is a terminal, plain otherwise")
+                        .value_name("FORMAT")
+                        .value_parser(["pretty", "plain", "json"]),
+                )
+                .arg(
+                    Arg::new("entry")
+                        .long("entry")
+                        .help("Start at this function instead of 'chif main' (FUNCTION or MODULE.FUNCTION)")
+                        .value_name("FUNCTION")
+                        .value_parser(EntryPoint::parse),
+                )
+                .arg(
+                    Arg::new("no-prelude")
+                        .long("no-prelude")
+                        .help("Do not make the prelude functions (clamp, pad_left, ...) available to the program")
+                        .action(clap::ArgAction::SetTrue),
+                )
+                .arg(
+                    Arg::new("watch")
+                        .long("watch")
+                        .help("Run again whenever the program or a module it imports changes")
+                        .action(clap::ArgAction::SetTrue),
+                )
+                .arg(
+                    Arg::new("clear")
+                        .long("clear")
+                        .help("Clear the screen before every run in --watch mode")
+                        .requires("watch")
+                        .action(clap::ArgAction::SetTrue),
+                )
+                .arg(
+                    Arg::new("profile")
+                        .long("profile")
+                        .help("Print calls and time of every function to stderr when the program ends")
+                        .action(clap::ArgAction::SetTrue),
+                )
+                .arg(
+                    Arg::new("profile-out")
+                        .long("profile-out")
+                        .help("Also write the profile as JSON to this file (implies --profile)")
+                        .value_name("PATH"),
+                )
+                .arg(
+                    Arg::new("args")
+                        .help("Arguments passed to the program, available through the 'args' object")
+                        .value_name("ARGS")
+                        .index(2)
+                        .num_args(0..)
+                        .last(true),
+                )
+        )
+        .subcommand(
+            Command::new("check")
+                .about("Parse and analyze a Rono program without running it")
+                .arg(
+                    Arg::new("file")
+                        .help("The input file to check")
+                        .required(true)
+                        .index(1),
+                )
+                .arg(
+                    Arg::new("dump-symbols")
+                        .long("dump-symbols")
+                        .help("Print every global symbol with its kind, signature or type and origin (--dump-symbols=json for JSON)")
+                        .value_name("FORMAT")
+                        .num_args(0..=1)
+                        .require_equals(true)
+                        .default_missing_value("text")
+                        .value_parser(["text", "json"]),
+                )
+                .arg(
+                    Arg::new("error-format")
+                        .long("error-format")
+                        .help("How errors are printed: pretty when stderr is a terminal, plain otherwise")
+                        .value_name("FORMAT")
+                        .value_parser(["pretty", "plain", "json"]),
+                )
+                .arg(
+                    Arg::new("entry")
+                        .long("entry")
+                        .help("Start at this function instead of 'chif main' (FUNCTION or MODULE.FUNCTION)")
+                        .value_name("FUNCTION")
+                        .value_parser(EntryPoint::parse),
+                )
+                .arg(
+                    Arg::new("no-prelude")
+                        .long("no-prelude")
+                        .help("Do not make the prelude functions (clamp, pad_left, ...) available to the program")
+                        .action(clap::ArgAction::SetTrue),
+                )
+                .arg(
+                    Arg::new("watch")
+                        .long("watch")
+                        .help("Run again whenever the program or a module it imports changes")
+                        .action(clap::ArgAction::SetTrue),
+                )
+                .arg(
+                    Arg::new("clear")
+                        .long("clear")
+                        .help("Clear the screen before every run in --watch mode")
+                        .requires("watch")
+                        .action(clap::ArgAction::SetTrue),
+                )
         )
         .subcommand(
             Command::new("compile")
@@ -59,6 +178,241 @@ fn main() {
                         .help("Include debug information")
                         .action(clap::ArgAction::SetTrue),
                 )
+                .arg(
+                    Arg::new("emit-header")
+                        .long("emit-header")
+                        .help("Also write a C header declaring the compiled functions")
+                        .action(clap::ArgAction::SetTrue),
+                )
+                .arg(
+                    Arg::new("entry")
+                        .long("entry")
+                        .help("Start at this function instead of 'chif main' (FUNCTION or MODULE.FUNCTION)")
+                        .value_name("FUNCTION")
+                        .value_parser(EntryPoint::parse),
+                )
+                .arg(
+                    Arg::new("no-prelude")
+                        .long("no-prelude")
+                        .help("Do not make the prelude functions (clamp, pad_left, ...) available to the program")
+                        .action(clap::ArgAction::SetTrue),
+                )
+                .arg(
+                    Arg::new("crate-type")
+                        .long("crate-type")
+                        .help("Build an executable, or a library object without 'chif main'")
+                        .value_name("TYPE")
+                        .value_parser(["bin", "lib"])
+                        .default_value("bin"),
+                )
+                .arg(
+                    Arg::new("runtime")
+                        .long("runtime")
+                        .help("Link this runtime object or static library instead of the built-in runtime")
+                        .value_name("PATH"),
+                )
+                .arg(
+                    Arg::new("linker")
+                        .long("linker")
+                        .help("Link with this command instead of cc; it is called like cc")
+                        .value_name("COMMAND"),
+                )
+                .arg(
+                    Arg::new("jobs")
+                        .short('j')
+                        .long("jobs")
+                        .help("Number of threads compiling functions (default: number of CPUs); the output does not depend on it")
+                        .value_name("N")
+                        .value_parser(clap::value_parser!(usize)),
+                )
+        )
+        .subcommand(
+            Command::new("bench")
+                .about("Benchmark a function of a Rono program in interpreted mode")
+                .arg(
+                    Arg::new("file")
+                        .help("The input file")
+                        .required(true)
+                        .index(1),
+                )
+                .arg(
+                    Arg::new("fn")
+                        .long("fn")
+                        .help("Name of the function to benchmark")
+                        .value_name("NAME")
+                        .required(true),
+                )
+                .arg(
+                    Arg::new("iterations")
+                        .short('n')
+                        .long("iterations")
+                        .help("Number of timed iterations")
+                        .value_name("N")
+                        .value_parser(clap::value_parser!(usize))
+                        .default_value("1000"),
+                )
+                .arg(
+                    Arg::new("warmup")
+                        .long("warmup")
+                        .help("Number of untimed warm-up iterations")
+                        .value_name("N")
+                        .value_parser(clap::value_parser!(usize))
+                        .default_value("100"),
+                )
+                .arg(
+                    Arg::new("setup-fn")
+                        .long("setup-fn")
+                        .help("Function called once before benchmarking, e.g. to fill global variables")
+                        .value_name("NAME"),
+                )
+                .arg(
+                    Arg::new("remove-outliers")
+                        .long("remove-outliers")
+                        .help("Drop the slowest 1% of samples")
+                        .action(clap::ArgAction::SetTrue),
+                )
+        )
+        .subcommand(
+            Command::new("test")
+                .about("Run the test_* functions of a Rono program in parallel in interpreted mode")
+                .arg(
+                    Arg::new("file")
+                        .help("The input file")
+                        .required(true)
+                        .index(1),
+                )
+                .arg(
+                    Arg::new("timeout")
+                        .long("timeout")
+                        .help("Time limit of a test without a '// timeout: ...' comment, like 30s or 500ms")
+                        .value_name("DURATION")
+                        .value_parser(testing::parse_duration)
+                        .default_value("30s"),
+                )
+                .arg(
+                    Arg::new("jobs")
+                        .short('j')
+                        .long("jobs")
+                        .help("Number of tests run at the same time (default: number of CPUs)")
+                        .value_name("N")
+                        .value_parser(clap::value_parser!(usize)),
+                )
+        )
+        .subcommand(
+            Command::new("inspect")
+                .about("Report size and complexity metrics of every function")
+                .arg(
+                    Arg::new("files")
+                        .help("The input files")
+                        .required(true)
+                        .num_args(1..)
+                        .index(1),
+                )
+                .arg(
+                    Arg::new("metrics")
+                        .long("metrics")
+                        .help("Statements, expressions, nesting, cyclomatic complexity and parameters per function")
+                        .required(true)
+                        .action(clap::ArgAction::SetTrue),
+                )
+                .arg(
+                    Arg::new("format")
+                        .long("format")
+                        .help("Output format")
+                        .value_name("FORMAT")
+                        .value_parser(["table", "json"])
+                        .default_value("table"),
+                )
+                .arg(
+                    Arg::new("fail-over")
+                        .long("fail-over")
+                        .help("Exit with an error if a function exceeds the limit, e.g. complexity=15 (repeatable)")
+                        .value_name("METRIC=MAX")
+                        .value_parser(MetricLimit::parse)
+                        .action(clap::ArgAction::Append),
+                )
+        )
+        .subcommand(
+            Command::new("examples")
+                .about("Check, run and compile every .rono file of a directory")
+                .hide(true)
+                .arg(
+                    Arg::new("dir")
+                        .help("Directory with examples")
+                        .index(1)
+                        .default_value("examples"),
+                )
+                .arg(
+                    Arg::new("verify")
+                        .long("verify")
+                        .help("Verify the examples instead of listing them")
+                        .action(clap::ArgAction::SetTrue),
+                )
+        )
+        .arg(
+            Arg::new("quiet")
+                .short('q')
+                .long("quiet")
+                .help("Print errors only")
+                .global(true)
+                .conflicts_with("verbose")
+                .action(clap::ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("verbose")
+                .short('v')
+                .long("verbose")
+                .help("Print compilation stages and their timings")
+                .global(true)
+                .action(clap::ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("color")
+                .long("color")
+                .help("Color diagnostics: auto (only on a terminal and without NO_COLOR), always or never")
+                .value_name("WHEN")
+                .value_parser(["auto", "always", "never"])
+                .default_value("auto")
+                .global(true),
+        )
+        .arg(
+            Arg::new("lang-version")
+                .long("lang-version")
+                .help("Language level of the program; overrides lang-version in rono.toml (default 1)")
+                .value_name("N")
+                .value_parser(LangVersion::parse)
+                .global(true),
+        )
+        .arg(
+            Arg::new("status-json")
+                .long("status-json")
+                .help("Finish with one JSON status line on stdout")
+                .global(true)
+                .action(clap::ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("allow")
+                .long("allow")
+                .help("Do not report the warning with this code, e.g. W0003 (repeatable)")
+                .value_name("CODE")
+                .value_parser(diagnostics::parse_warning_code)
+                .global(true)
+                .action(clap::ArgAction::Append),
+        )
+        .arg(
+            Arg::new("deny")
+                .long("deny")
+                .help("Report the warning with this code as an error (repeatable)")
+                .value_name("CODE")
+                .value_parser(diagnostics::parse_warning_code)
+                .global(true)
+                .action(clap::ArgAction::Append),
+        )
+        .arg(
+            Arg::new("explain")
+                .long("explain")
+                .help("Explain a diagnostic code, e.g. E0002, with an example")
+                .value_name("CODE"),
         )
         // Legacy support for old CLI
         .arg(
@@ -75,103 +429,506 @@ fn main() {
         )
         .get_matches();
 
+    // Global flags are propagated down to the subcommand matches
+    let global = matches.subcommand().map_or(&matches, |(_, sub_matches)| sub_matches);
+    let status = Status::from_matches(global);
+    let policy = warning_policy(global);
+
+    if let Some(code) = matches.get_one::<String>("explain") {
+        explain_code(code, &status);
+    }
+
     match matches.subcommand() {
-        Some(("run", sub_matches)) => {
+        Some((command @ ("run" | "check"), sub_matches)) => {
             let filename = sub_matches.get_one::<String>("file").unwrap();
-            run_program(filename);
+            let error_format = sub_matches.get_one::<String>("error-format")
+                .and_then(|name| ErrorFormat::parse(name))
+                .unwrap_or_else(default_error_format);
+            let version = lang_version(sub_matches, filename, &status);
+            let entry = sub_matches.get_one::<EntryPoint>("entry");
+            let prelude = !sub_matches.get_flag("no-prelude");
+            let program_args: Vec<String> = match command {
+                "run" => sub_matches.get_many::<String>("args").map(|args| args.cloned().collect()).unwrap_or_default(),
+                _ => Vec::new(),
+            };
+            let dump_symbols = match command {
+                "check" => sub_matches.get_one::<String>("dump-symbols").map(String::as_str),
+                _ => None,
+            };
+            let profile_out = match command {
+                "run" => sub_matches.get_one::<String>("profile-out").map(String::as_str),
+                _ => None,
+            };
+            let profile = (command == "run" && sub_matches.get_flag("profile") || profile_out.is_some())
+                .then_some(ProfileOutput { json: profile_out });
+            let once = || match command {
+                "run" => execute_program(filename, error_format, version, entry, prelude, &program_args, profile.as_ref())
+                    .map(|()| 0)
+                    .map_err(|error| vec![error]),
+                _ => check_program(filename, error_format, version, entry, prelude, &policy, dump_symbols),
+            };
+            if sub_matches.get_flag("watch") {
+                watch_program(filename, sub_matches.get_flag("clear"), || once().is_ok());
+            }
+            match once() {
+                Ok(warnings) => {
+                    if command == "check" {
+                        logger::info(format!("No errors found in {}", filename));
+                    }
+                    status.succeed(None, warnings);
+                }
+                Err(errors) => status.fail(errors),
+            }
         }
         Some(("compile", sub_matches)) => {
             let filename = sub_matches.get_one::<String>("file").unwrap();
-            let output = sub_matches.get_one::<String>("output");
-            let target_str = sub_matches.get_one::<String>("target");
-            let optimize_str = sub_matches.get_one::<String>("optimize").unwrap();
-            let debug = sub_matches.get_flag("debug");
-            
-            compile_program(filename, output, target_str, optimize_str, debug);
+            compile_program(filename, sub_matches, &policy, &status);
+        }
+        Some(("bench", sub_matches)) => {
+            let filename = sub_matches.get_one::<String>("file").unwrap();
+            let mut options = BenchOptions::new(sub_matches.get_one::<String>("fn").unwrap());
+            options.iterations = *sub_matches.get_one::<usize>("iterations").unwrap();
+            options.warmup = *sub_matches.get_one::<usize>("warmup").unwrap();
+            options.setup_function = sub_matches.get_one::<String>("setup-fn").cloned();
+            options.remove_outliers = sub_matches.get_flag("remove-outliers");
+
+            bench_program(filename, &options, &status);
+        }
+        Some(("test", sub_matches)) => {
+            let filename = sub_matches.get_one::<String>("file").unwrap();
+            let mut options = TestOptions {
+                timeout: *sub_matches.get_one::<Duration>("timeout").unwrap(),
+                ..TestOptions::default()
+            };
+            if let Some(jobs) = sub_matches.get_one::<usize>("jobs") {
+                options.jobs = *jobs;
+            }
+
+            test_program(filename, &options, &status);
+        }
+        Some(("inspect", sub_matches)) => {
+            let files: Vec<&String> = sub_matches.get_many::<String>("files").unwrap().collect();
+            let json = sub_matches.get_one::<String>("format").map(|s| s.as_str()) == Some("json");
+            let limits: Vec<MetricLimit> = sub_matches.get_many::<MetricLimit>("fail-over")
+                .map(|limits| limits.copied().collect())
+                .unwrap_or_default();
+            inspect_metrics(&files, json, &limits, &status);
+        }
+        Some(("examples", sub_matches)) => {
+            let dir = sub_matches.get_one::<String>("dir").unwrap();
+            examples_command(dir, sub_matches.get_flag("verify"), &status);
         }
         _ => {
             // Legacy mode support
             if let Some(filename) = matches.get_one::<String>("file") {
                 let run_mode = matches.get_flag("run");
+                let version = lang_version(&matches, filename, &status);
                 if run_mode {
-                    run_program(filename);
+                    run_program(filename, default_error_format(), version, &status);
                 } else {
                     // Default to interpretation for legacy mode
-                    run_program(filename);
+                    run_program(filename, default_error_format(), version, &status);
                 }
             } else {
-                eprintln!("No input file specified. Use 'rono --help' for usage information.");
-                process::exit(1);
+                logger::error("No input file specified. Use 'rono --help' for usage information.");
+                status.fail(vec![json!({"stage": "cli", "location": null, "message": "No input file specified"})]);
             }
         }
     }
 }
 
-fn run_program(filename: &str) {
-    let source = match fs::read_to_string(filename) {
-        Ok(content) => content,
-        Err(e) => {
-            eprintln!("Error reading file '{}': {}", filename, e);
-            process::exit(1);
-        }
-    };
+/// Уровень сообщений и итоговая строка `--status-json` для `run` и `compile`
+struct Status {
+    json: bool,
+    started: Instant,
+}
 
-    // Lexical analysis
-    let mut lexer = lexer::Lexer::new(&source);
-    let tokens = match lexer.tokenize() {
-        Ok(tokens) => tokens,
-        Err(e) => {
-            eprintln!("Lexer error: {}", e);
-            process::exit(1);
+impl Status {
+    fn from_matches(matches: &ArgMatches) -> Self {
+        if matches.get_flag("quiet") {
+            logger::set_verbosity(Verbosity::Quiet);
+        } else if matches.get_flag("verbose") {
+            logger::set_verbosity(Verbosity::Verbose);
         }
-    };
+        let color = matches.get_one::<String>("color").and_then(|name| style::ColorChoice::parse(name));
+        style::set_color_choice(color.unwrap_or(style::ColorChoice::Auto));
+        let json = matches.get_flag("status-json");
+        // stdout keeps only the program's own output and the final JSON line
+        logger::set_messages_to_stderr(json);
+        progress::set_sink(Some(Box::new(CliProgress)));
+        Self { json, started: Instant::now() }
+    }
 
-    // Parsing
-    let mut parser = parser::Parser::new(tokens);
-    let ast = match parser.parse() {
-        Ok(ast) => ast,
-        Err(e) => {
-            eprintln!("Parser error: {}", e);
-            process::exit(1);
+    fn succeed(&self, output: Option<&str>, warnings: usize) {
+        logger::verbose(format!("Finished in {} ms", self.started.elapsed().as_millis()));
+        if self.json {
+            let mut line = json!({
+                "status": "ok",
+                "warnings": warnings,
+                "duration_ms": self.started.elapsed().as_millis() as u64,
+            });
+            if let Some(output) = output {
+                line["output"] = json!(output);
+            }
+            println!("{}", line);
         }
-    };
+    }
 
-    // Interpretation
-    let mut interpreter = interpreter::Interpreter::new();
-    if let Err(e) = interpreter.execute(&ast) {
-        eprintln!("Runtime error: {}", e);
+    /// Ошибки уже выведены в stderr; завершает процесс с кодом 1
+    fn fail(&self, errors: Vec<Value>) -> ! {
+        if self.json {
+            println!("{}", json!({ "status": "error", "errors": errors }));
+        }
         process::exit(1);
     }
+
+    fn fail_with(&self, error: RonoError) -> ! {
+        report_error(&error);
+        self.fail(vec![error_json(&error)])
+    }
 }
 
-fn compile_program(filename: &str, output: Option<&String>, target_str: Option<&String>, optimize_str: &str, debug: bool) {
-    let source = match fs::read_to_string(filename) {
-        Ok(content) => content,
-        Err(e) => {
-            eprintln!("Error reading file '{}': {}", filename, e);
-            process::exit(1);
+/// Ход работы в stderr: разбираемые модули и итог компиляции на обычном
+/// уровне, время этапов — с `-v`
+struct CliProgress;
+
+impl ProgressSink for CliProgress {
+    fn event(&mut self, event: &ProgressEvent) {
+        let level = match event {
+            ProgressEvent::StageFinished { .. } => Verbosity::Verbose,
+            ProgressEvent::ParsingModule { .. } | ProgressEvent::Finished(_) => Verbosity::Normal,
+        };
+        logger::progress(event, level);
+    }
+}
+
+fn default_error_format() -> ErrorFormat {
+    if std::io::stderr().is_terminal() {
+        ErrorFormat::Pretty
+    } else {
+        ErrorFormat::Plain
+    }
+}
+
+fn error_json(error: &RonoError) -> Value {
+    json!({
+        "stage": error.stage(),
+        "code": error.code().map(Code::as_str),
+        "location": error.location(),
+        "message": error.message(),
+    })
+}
+
+/// Ошибка одной строкой и, если у неё есть код, строка `Code:` как у предупреждений
+fn report_error(error: &RonoError) {
+    match error.code() {
+        Some(code) => logger::error(format!("{}\n  Code: {}", error, code)),
+        None => logger::error(error),
+    }
+}
+
+/// `--explain`: описание кода в stdout
+fn explain_code(code: &str, status: &Status) -> ! {
+    match Code::parse(code) {
+        Some(code) => {
+            print!("{}", code.explanation());
+            process::exit(0);
         }
-    };
+        None => {
+            let message = format!(
+                "unknown diagnostic code '{}'; known codes: {}",
+                code,
+                Code::ALL.map(Code::as_str).join(", ")
+            );
+            logger::error(&message);
+            status.fail(vec![json!({"stage": "cli", "location": null, "message": message})]);
+        }
+    }
+}
 
-    // Lexical analysis
-    let mut lexer = lexer::Lexer::new(&source);
-    let tokens = match lexer.tokenize() {
-        Ok(tokens) => tokens,
-        Err(e) => {
-            eprintln!("Lexer error: {}", e);
-            process::exit(1);
+/// Коды из `--allow` и `--deny`
+fn warning_policy(matches: &ArgMatches) -> WarningPolicy {
+    let codes = |name: &str| matches.get_many::<Code>(name).map(|codes| codes.copied().collect()).unwrap_or_default();
+    WarningPolicy { allowed: codes("allow"), denied: codes("deny") }
+}
+
+/// `--lang-version`, иначе `lang-version` из ближайшего rono.toml, иначе уровень 1
+fn lang_version(matches: &ArgMatches, filename: &str, status: &Status) -> LangVersion {
+    if let Some(version) = matches.get_one::<LangVersion>("lang-version") {
+        return *version;
+    }
+    match lang::project_lang_version(Path::new(filename)) {
+        Ok(version) => version.unwrap_or_default(),
+        Err(message) => {
+            logger::error(&message);
+            status.fail(vec![json!({"stage": "cli", "location": null, "message": message})]);
         }
+    }
+}
+
+fn run_program(filename: &str, error_format: ErrorFormat, lang_version: LangVersion, status: &Status) {
+    match execute_program(filename, error_format, lang_version, None, true, &[], None) {
+        Ok(()) => status.succeed(None, 0),
+        Err(error) => status.fail(vec![error]),
+    }
+}
+
+/// Куда выводится профиль `run --profile`: таблица всегда в stderr,
+/// с `--profile-out` ещё и JSON в файл
+struct ProfileOutput<'a> {
+    json: Option<&'a str>,
+}
+
+/// Один прогон программы. Ошибка уже выведена в формате `error_format`,
+/// а завершать ли процесс, решает вызывающий (`--watch` продолжает следить)
+fn execute_program(
+    filename: &str,
+    error_format: ErrorFormat,
+    lang_version: LangVersion,
+    entry: Option<&EntryPoint>,
+    prelude: bool,
+    program_args: &[String],
+    profile: Option<&ProfileOutput>,
+) -> std::result::Result<(), Value> {
+    let report = |report: ErrorReport, error: Value| {
+        logger::error(report.render(error_format));
+        error
     };
+    let source = read_file(filename)
+        .map_err(|error| report(ErrorReport::new(&error, filename, None, &[]), error_json(&error)))?;
+    let started = Instant::now();
+    let ast = parse_source(&source)
+        .map_err(|error| report(ErrorReport::new(&error, filename, Some(&source), &[]), error_json(&error)))?;
+    progress::stage_finished(Stage::Parse, started);
+    progress::start(&ast);
 
-    // Parsing
-    let mut parser = parser::Parser::new(tokens);
-    let ast = match parser.parse() {
-        Ok(ast) => ast,
-        Err(e) => {
-            eprintln!("Parser error: {}", e);
-            process::exit(1);
+    // A panic inside the interpreter is reported like any other error
+    let mut interpreter = Interpreter::new();
+    interpreter.set_lang_version(lang_version);
+    interpreter.set_entry(entry.cloned());
+    interpreter.set_prelude(prelude);
+    interpreter.set_source_dir(Path::new(filename).parent().map(Path::to_path_buf));
+    interpreter.set_program_args(program_args.to_vec());
+    if profile.is_some() {
+        interpreter.enable_profile();
+    }
+    panic::set_hook(Box::new(|_| {}));
+    let outcome = panic::catch_unwind(AssertUnwindSafe(|| interpreter.execute(&ast)));
+    drop(panic::take_hook());
+    // Профиль выводится и после ошибки: видно, где программа провела время до неё
+    if let (Some(output), Some(report)) = (profile, interpreter.take_profile()) {
+        eprint!("{}", report);
+        if let Some(path) = output.json {
+            if let Err(error) = fs::write(path, format!("{:#}\n", report.to_json())) {
+                logger::error(format!("Could not write the profile to {}: {}", path, error));
+            }
+        }
+    }
+    match outcome {
+        Ok(Ok(())) => Ok(()),
+        // `rono run app.rono | head`: the reader has what it wanted, nothing to report
+        Ok(Err(ChifError::OutputClosed)) => process::exit(interpreter::EXIT_OUTPUT_CLOSED),
+        Ok(Err(e)) => {
+            let error = RonoError::runtime(e);
+            Err(report(ErrorReport::new(&error, filename, Some(&source), interpreter.error_trace()), error_json(&error)))
         }
+        Err(payload) => {
+            let message = panic_message(payload.as_ref());
+            let trace: Vec<String> = interpreter.call_stack().iter().rev().cloned().collect();
+            let error = json!({"stage": "panic", "location": null, "message": message});
+            Err(report(ErrorReport::panic(message, filename, &trace), error))
+        }
+    }
+}
+
+/// `check`: разбор и семантический анализ без выполнения. Возвращает число
+/// предупреждений; предупреждения из `--deny` выводятся как ошибки. С
+/// `--dump-symbols` печатает в stdout символы глобальной области
+fn check_program(
+    filename: &str,
+    error_format: ErrorFormat,
+    lang_version: LangVersion,
+    entry: Option<&EntryPoint>,
+    prelude: bool,
+    policy: &WarningPolicy,
+    dump_symbols: Option<&str>,
+) -> std::result::Result<usize, Vec<Value>> {
+    let report = |error: RonoError, source: Option<&str>| {
+        logger::error(ErrorReport::new(&error, filename, source, &[]).render(error_format));
+        vec![error_json(&error)]
     };
+    let source = read_file(filename).map_err(|error| report(error, None))?;
+    let ast = parse_source(&source).map_err(|error| report(error, Some(&source)))?;
+    progress::start(&ast);
+
+    let mut analyzer = SemanticAnalyzer::new();
+    analyzer.lang_version = lang_version;
+    analyzer.entry = entry.cloned();
+    analyzer.prelude = prelude;
+    analyzer.source_dir = Path::new(filename).parent().map(Path::to_path_buf);
+    analyzer.analyze(&ast).map_err(|error| report(RonoError::from(error), Some(&source)))?;
+    match dump_symbols {
+        Some("json") => println!("{}", Value::Array(analyzer.symbols().iter().map(SymbolInfo::to_json).collect())),
+        Some(_) => {
+            for symbol in analyzer.symbols() {
+                println!("{}", symbol);
+            }
+        }
+        None => {}
+    }
+    let mut warnings = 0;
+    let mut denied = Vec::new();
+    for warning in &analyzer.warnings {
+        let code = warning.code();
+        let action = policy.action(Some(code.as_str()));
+        if action == WarningAction::Allow {
+            continue;
+        }
+        let mut location = warning.location().clone();
+        if location.file == SourceLocation::unknown().file {
+            location.file = filename.to_string();
+        }
+        let text = format!("{}: {}\n  Code: {}", location, warning, code);
+        if action == WarningAction::Deny {
+            logger::error(format!("{}: {}", style::paint("error", style::Style::Error, style::Stream::Stderr), text));
+            denied.push(json!({
+                "stage": "semantic",
+                "code": code.as_str(),
+                "location": location.to_string(),
+                "message": warning.to_string(),
+            }));
+        } else {
+            logger::warn(format!("{}: {}", style::paint("warning", style::Style::Warning, style::Stream::Stderr), text));
+            warnings += 1;
+        }
+    }
+    if denied.is_empty() {
+        Ok(warnings)
+    } else {
+        Err(denied)
+    }
+}
+
+/// `--watch`: прогон сейчас и после каждого изменения программы или модулей,
+/// которые она импортирует. Ошибки прогона не останавливают наблюдение;
+/// выход — Ctrl-C
+fn watch_program(filename: &str, clear: bool, mut run: impl FnMut() -> bool) -> ! {
+    let mut watcher = Watcher::new(filename, || {
+        if clear {
+            // Очистка экрана и курсор в начало
+            print!("\x1b[2J\x1b[H");
+        }
+        run()
+    });
+    let mut outcome = watcher.rerun(Vec::new());
+    loop {
+        let line = outcome.status_line(SystemTime::now());
+        logger::info(style::paint(line, if outcome.ok { style::Style::Success } else { style::Style::Error }, style::Stream::Stdout));
+        outcome = loop {
+            thread::sleep(WATCH_POLL_INTERVAL);
+            if let Some(outcome) = watcher.poll() {
+                break outcome;
+            }
+        };
+    }
+}
+
+/// Как часто `--watch` проверяет время изменения файлов
+const WATCH_POLL_INTERVAL: Duration = Duration::from_millis(200);
+
+fn panic_message(payload: &(dyn Any + Send)) -> String {
+    payload.downcast_ref::<String>().cloned()
+        .or_else(|| payload.downcast_ref::<&str>().map(|message| message.to_string()))
+        .unwrap_or_else(|| "panic without a message".to_string())
+}
+
+fn read_file(filename: &str) -> std::result::Result<String, RonoError> {
+    fs::read_to_string(filename).map_err(|e| {
+        RonoError::from(std::io::Error::new(e.kind(), format!("cannot read '{}': {}", filename, e)))
+    })
+}
+
+fn read_source(filename: &str, status: &Status) -> String {
+    read_file(filename).unwrap_or_else(|error| status.fail_with(error))
+}
+
+fn parse_or_exit(source: &str, status: &Status) -> Program {
+    let started = Instant::now();
+    match parse_source(source) {
+        Ok(ast) => {
+            progress::stage_finished(Stage::Parse, started);
+            ast
+        }
+        Err(e) => status.fail_with(e),
+    }
+}
+
+fn bench_program(filename: &str, options: &BenchOptions, status: &Status) {
+    let source = read_source(filename, status);
+    let ast = parse_or_exit(&source, status);
+
+    match run_benchmark(&ast, options) {
+        Ok(report) => print!("{}", report),
+        Err(e) => status.fail_with(RonoError::from(e)),
+    }
+}
+
+fn test_program(filename: &str, options: &TestOptions, status: &Status) {
+    let source = read_source(filename, status);
+
+    match run_tests(&source, options) {
+        Ok(report) => {
+            print!("{}", report);
+            if report.success() {
+                status.succeed(None, 0);
+            } else {
+                status.fail(report.results.iter()
+                    .filter(|result| result.status != TestStatus::Passed)
+                    .map(|result| json!({"stage": "test", "location": result.name, "message": result.status.to_string()}))
+                    .collect())
+            }
+        }
+        Err(e) => status.fail_with(e),
+    }
+}
+
+fn inspect_metrics(files: &[&String], json: bool, limits: &[MetricLimit], status: &Status) {
+    let mut report = MetricsReport::default();
+    for filename in files {
+        let source = read_source(filename, status);
+        let ast = parse_or_exit(&source, status);
+        report.files.push(FileMetrics::new(filename, &ast));
+    }
+
+    if json {
+        println!("{}", report.to_json());
+    } else {
+        println!("{}", report);
+    }
+
+    let violations = report.violations(limits);
+    if violations.is_empty() {
+        status.succeed(None, 0);
+        return;
+    }
+    for violation in &violations {
+        logger::error(violation);
+    }
+    status.fail(violations.iter()
+        .map(|violation| json!({"stage": "inspect", "location": violation.file, "message": violation.to_string()}))
+        .collect())
+}
+
+fn compile_program(filename: &str, options: &ArgMatches, policy: &WarningPolicy, status: &Status) {
+    let output = options.get_one::<String>("output");
+    let target_str = options.get_one::<String>("target");
+    let optimize_str = options.get_one::<String>("optimize").unwrap().as_str();
+    let debug = options.get_flag("debug");
+    let emit_header = options.get_flag("emit-header");
+    let library = options.get_one::<String>("crate-type").map(|s| s.as_str()) == Some("lib");
+
+    let source = read_source(filename, status);
+    let ast = parse_or_exit(&source, status);
 
     // Determine target
     let target = match target_str.map(|s| s.as_str()) {
@@ -182,8 +939,8 @@ fn compile_program(filename: &str, output: Option<&String>, target_str: Option<&
         Some("aarch64-macos") => Target::Aarch64MacOS,
         None => detect_host_target(),
         Some(unknown) => {
-            eprintln!("Unknown target: {}", unknown);
-            process::exit(1);
+            logger::error(format!("Unknown target: {}", unknown));
+            status.fail(vec![json!({"stage": "cli", "location": null, "message": format!("Unknown target: {}", unknown)})]);
         }
     };
 
@@ -193,8 +950,8 @@ fn compile_program(filename: &str, output: Option<&String>, target_str: Option<&
         "speed" => OptLevel::Speed,
         "size" => OptLevel::Size,
         _ => {
-            eprintln!("Unknown optimization level: {}", optimize_str);
-            process::exit(1);
+            logger::error(format!("Unknown optimization level: {}", optimize_str));
+            status.fail(vec![json!({"stage": "cli", "location": null, "message": format!("Unknown optimization level: {}", optimize_str)})]);
         }
     };
 
@@ -217,27 +974,99 @@ fn compile_program(filename: &str, output: Option<&String>, target_str: Option<&
     // Create compiler and compile
     let mut compiler = match Compiler::new(target, opt_level, debug) {
         Ok(compiler) => compiler,
-        Err(e) => {
-            eprintln!("Failed to create compiler: {}", e);
-            process::exit(1);
-        }
+        Err(e) => status.fail_with(RonoError::from(e)),
     };
 
-    match compiler.compile(&ast, &output_path) {
+    compiler.set_source(filename, &source);
+    compiler.set_lang_version(lang_version(options, filename, status));
+    compiler.set_entry(options.get_one::<EntryPoint>("entry").cloned());
+    compiler.set_prelude(!options.get_flag("no-prelude"));
+    compiler.set_warning_policy(policy.clone());
+    if let Some(runtime) = options.get_one::<String>("runtime") {
+        compiler.set_runtime(runtime);
+    }
+    if let Some(linker) = options.get_one::<String>("linker") {
+        compiler.set_linker(linker);
+    }
+    if let Some(jobs) = options.get_one::<usize>("jobs") {
+        compiler.set_jobs(*jobs);
+    }
+    
+    let result = if library {
+        compiler.compile_library(&ast, &output_path).map(|object_path| {
+            logger::info(format!(
+                "Library object written to {} (link it with {})",
+                object_path,
+                compiler::library_runtime_path(&object_path).display()
+            ));
+        })
+    } else {
+        compiler.compile(&ast, &output_path)
+    };
+    
+    match result {
         Ok(()) => {
+            compiler.print_diagnostics(); // Print warnings and info
             if compiler.has_errors() {
-                compiler.print_diagnostics();
-                eprintln!("Compilation failed due to errors.");
-                process::exit(1);
-            } else {
-                compiler.print_diagnostics(); // Print warnings and info
-                println!("Compilation successful! Output: {}", output_path);
+                logger::error("Compilation failed due to errors.");
+                status.fail(diagnostic_errors_json(&compiler));
+            }
+            logger::info(format!("Compilation successful! Output: {}", output_path));
+            if emit_header {
+                match compiler.emit_header(&ast, &output_path) {
+                    Ok(header_path) => logger::info(format!("Header written to {}", header_path)),
+                    Err(e) => status.fail_with(RonoError::from(e)),
+                }
             }
+            let warnings = compiler.diagnostics().iter()
+                .filter(|diagnostic| matches!(diagnostic.level, DiagnosticLevel::Warning))
+                .count();
+            status.succeed(Some(&output_path), warnings);
         }
         Err(e) => {
             compiler.print_diagnostics();
-            eprintln!("Compilation failed: {}", e);
-            process::exit(1);
+            let error = RonoError::from(e);
+            report_error(&error);
+            let mut errors = diagnostic_errors_json(&compiler);
+            errors.push(error_json(&error));
+            status.fail(errors);
+        }
+    }
+}
+
+fn diagnostic_errors_json(compiler: &Compiler) -> Vec<Value> {
+    compiler.diagnostics().iter()
+        .filter(|diagnostic| matches!(diagnostic.level, DiagnosticLevel::Error))
+        .map(|diagnostic| json!({
+            "stage": "compile",
+            "code": diagnostic.code,
+            "location": diagnostic.location.to_string(),
+            "message": diagnostic.message,
+        }))
+        .collect()
+}
+
+fn examples_command(dir: &str, verify: bool, status: &Status) {
+    let dir = std::path::Path::new(dir);
+    if !verify {
+        match examples::collect_examples(dir) {
+            Ok(files) => {
+                for path in files {
+                    println!("{}", path.display());
+                }
+            }
+            Err(e) => status.fail_with(RonoError::from(e)),
+        }
+        return;
+    }
+    
+    match verify_examples(dir) {
+        Ok(report) => {
+            println!("{}", report);
+            if !report.is_success() {
+                process::exit(1);
+            }
         }
+        Err(e) => status.fail_with(RonoError::from(e)),
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,204 @@
+#[cfg(test)]
+mod tests {
+    use crate::ast::{Item, Program};
+    use crate::types::ChifType;
+    use crate::interpreter::Interpreter;
+    use crate::lexer::Lexer;
+    use crate::parser::Parser;
+    use crate::semantic::SemanticAnalyzer;
+
+    const SHAPE: &str = r#"
+        struct Shape {
+            width: int,
+            height: int,
+            scale: float,
+            name: str,
+            sides: list[int],
+        }
+
+        fn describe(name: str, area: int) str {
+            ret name;
+        }
+    "#;
+
+    fn parse(source: &str) -> Program {
+        let tokens = Lexer::new(source).tokenize().expect("source should tokenize");
+        Parser::new(tokens).parse().expect("source should parse")
+    }
+
+    /// Анализирует `Shape` с методами `methods` и возвращает текст ошибки
+    fn analyze_methods(methods: &str) -> Option<String> {
+        let source = format!("{}\nfn_for Shape {{\n{}\n}}\nchif main() {{ }}", SHAPE, methods);
+        SemanticAnalyzer::new().analyze(&parse(&source)).err().map(|error| error.to_string())
+    }
+
+    fn assert_type_checks(methods: &str) {
+        if let Some(message) = analyze_methods(methods) {
+            panic!("methods should type-check, got: {}\n{}", message, methods);
+        }
+    }
+
+    fn assert_rejected(methods: &str, expected: &str) {
+        let message = analyze_methods(methods).unwrap_or_else(|| panic!("methods should be rejected:\n{}", methods));
+        assert!(message.contains(expected), "expected '{}', got: {}", expected, message);
+    }
+
+    /// Вызывает `fn check() <return_type>` с телом `check_body`, в котором
+    /// объявлена `shape: Shape { width = 3, height = 4, scale = 1.5, name = "box", sides = [3, 4, 5] }`
+    fn run_method(methods: &str, return_type: &str, check_body: &str) -> String {
+        let source = format!(
+            "{}\nfn_for Shape {{\n{}\n}}\nfn check() {} {{\n var shape: Shape = Shape {{ width = 3, height = 4, scale = 1.5, name = \"box\", sides = [3, 4, 5] }};\n{}\n}}\nchif main() {{ }}",
+            SHAPE, methods, return_type, check_body
+        );
+        let program = parse(&source);
+        SemanticAnalyzer::new().analyze(&program).expect("program should type-check");
+        let mut interpreter = Interpreter::new();
+        interpreter.load(&program).expect("program should load");
+        interpreter.call_function_by_name("check").expect("check should run").to_string()
+    }
+
+    #[test]
+    fn test_self_fields_have_their_declared_types() {
+        assert_type_checks(r#"
+            fn fields(self) int {
+                var width: int = self.width;
+                var scale: float = self.scale;
+                var name: str = self.name;
+                var sides: list[int] = self.sides;
+                ret width;
+            }
+        "#);
+        assert_rejected("fn wrong(self) int { var name: int = self.name; ret 0; }", "expected int, found str");
+        assert_rejected("fn wrong(self) int { var scale: int = self.scale; ret 0; }", "expected int, found float");
+        assert_rejected("fn wrong(self) int { ret self.depth; }", "Field 'depth' not found in struct 'Shape'");
+    }
+
+    #[test]
+    fn test_self_fields_mix_with_parameters_in_arithmetic() {
+        assert_type_checks(r#"
+            fn area_scaled(self, k: int) int {
+                ret self.width * self.height * k + 1;
+            }
+
+            fn scaled_width(self, factor: float) float {
+                ret self.scale * factor;
+            }
+        "#);
+        assert_rejected("fn wrong(self, k: int) str { ret self.width * k; }", "expected str, found int");
+        assert_rejected("fn wrong(self) bool { var total: str = self.width + self.height; ret true; }", "expected str, found int");
+    }
+
+    #[test]
+    fn test_self_fields_are_returned_and_passed_to_functions() {
+        assert_type_checks(r#"
+            fn label(self) str {
+                ret describe(self.name, self.width * self.height);
+            }
+
+            fn width_of(self) int {
+                ret self.width;
+            }
+        "#);
+        assert_rejected("fn wrong(self) str { ret self.width; }", "expected str, found int");
+        assert_rejected("fn wrong(self) str { ret describe(self.width, 1); }", "expected str, found int");
+    }
+
+    #[test]
+    fn test_assignments_to_self_fields_are_checked() {
+        assert_type_checks(r#"
+            fn grow(self, by: int) {
+                self.width = self.width + by;
+                self.scale = self.scale * 2.0;
+                self.sides[0] = self.sides[0] + by;
+            }
+        "#);
+        assert_rejected("fn wrong(self) { self.width = \"wide\"; }", "expected int, found str");
+        assert_rejected("fn wrong(self) { self.sides[0] = \"side\"; }", "expected int, found str");
+    }
+
+    #[test]
+    fn test_methods_called_on_self_return_their_types() {
+        assert_type_checks(r#"
+            fn area(self) int {
+                ret self.width * self.height;
+            }
+
+            fn double_area(self) int {
+                ret self.area() * 2;
+            }
+        "#);
+        assert_rejected(
+            "fn area(self) int { ret self.width * self.height; }\nfn wrong(self) str { ret self.area(); }",
+            "expected str, found int",
+        );
+    }
+
+    #[test]
+    fn test_self_list_fields_are_indexed_with_element_type() {
+        assert_type_checks(r#"
+            fn longest_side(self) int {
+                var longest: int = self.sides[0];
+                for (var i: int = 1; i < self.sides.len(); i = i + 1) {
+                    if (self.sides[i] > longest) {
+                        longest = self.sides[i];
+                    }
+                }
+                ret longest;
+            }
+        "#);
+        assert_rejected("fn wrong(self) str { ret self.sides[0]; }", "expected str, found int");
+    }
+
+    #[test]
+    fn test_self_typed_as_self_resolves_to_the_struct() {
+        // Парсер подставляет имя структуры сам; AST, собранный без него,
+        // оставляет у self тип `Self`
+        let source = format!("{}\nfn_for Shape {{\nfn label(self) str {{ ret describe(self.name, self.width); }}\n}}\nchif main() {{ }}", SHAPE);
+        let mut program = parse(&source);
+        for item in &mut program.items {
+            if let Item::StructImpl(impl_block) = item {
+                impl_block.methods[0].params[0].param_type = ChifType::Struct("Self".to_string());
+            }
+        }
+        SemanticAnalyzer::new().analyze(&program).expect("self should have the type of Shape");
+    }
+
+    #[test]
+    fn test_method_bodies_compute_values_from_self() {
+        let methods = r#"
+            fn area(self) int {
+                ret self.width * self.height;
+            }
+
+            fn area_scaled(self, k: int) int {
+                ret self.area() * k + self.width;
+            }
+
+            fn scaled_width(self, factor: float) float {
+                ret self.scale * factor;
+            }
+
+            fn label(self) str {
+                ret describe(self.name, self.area());
+            }
+
+            fn perimeter(self) int {
+                var total: int = 0;
+                for (var i: int = 0; i < self.sides.len(); i = i + 1) {
+                    total = total + self.sides[i];
+                }
+                ret total;
+            }
+
+            fn grow(self, by: int) {
+                self.width = self.width + by;
+                self.sides[0] = self.sides[0] + by;
+            }
+        "#;
+        assert_eq!(run_method(methods, "int", "ret shape.area_scaled(2);"), "27");
+        assert_eq!(run_method(methods, "float", "ret shape.scaled_width(3.0);"), "4.5");
+        assert_eq!(run_method(methods, "str", "ret shape.label();"), "box");
+        assert_eq!(run_method(methods, "int", "ret shape.perimeter();"), "12");
+        assert_eq!(run_method(methods, "int", "shape.grow(2);\nret shape.area() + shape.perimeter();"), "34");
+    }
+}
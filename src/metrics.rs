@@ -0,0 +1,403 @@
+use crate::ast::*;
+use serde_json::{json, Value};
+use std::fmt;
+
+/// Метрики одной функции или метода для `rono inspect --metrics`
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FunctionMetrics {
+    /// Имя функции; методы записываются как `Struct.method`
+    pub name: String,
+    /// Инструкции блоков; `init` и `update` в заголовке `for` не считаются
+    pub statements: usize,
+    /// Все выражения, включая вложенные
+    pub expressions: usize,
+    /// Наибольшая вложенность блоков if/else, циклов и case; тело функции — 0
+    pub max_nesting: usize,
+    /// 1 + if, циклы, ветки case и операторы `&&`/`||`
+    pub complexity: usize,
+    pub params: usize,
+}
+
+/// Метрика, по которой `--fail-over` задаёт порог
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Metric {
+    Statements,
+    Expressions,
+    Nesting,
+    Complexity,
+    Params,
+}
+
+impl Metric {
+    pub const ALL: [Metric; 5] = [Metric::Statements, Metric::Expressions, Metric::Nesting, Metric::Complexity, Metric::Params];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Metric::Statements => "statements",
+            Metric::Expressions => "expressions",
+            Metric::Nesting => "nesting",
+            Metric::Complexity => "complexity",
+            Metric::Params => "params",
+        }
+    }
+
+    pub fn value(self, metrics: &FunctionMetrics) -> usize {
+        match self {
+            Metric::Statements => metrics.statements,
+            Metric::Expressions => metrics.expressions,
+            Metric::Nesting => metrics.max_nesting,
+            Metric::Complexity => metrics.complexity,
+            Metric::Params => metrics.params,
+        }
+    }
+}
+
+/// Порог `metric=max`: функция с большим значением нарушает его
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MetricLimit {
+    pub metric: Metric,
+    pub max: usize,
+}
+
+impl MetricLimit {
+    /// Разбирает `complexity=15`
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let (name, max) = text.split_once('=')
+            .ok_or_else(|| format!("expected <metric>=<max>, got '{}'", text))?;
+        let metric = Metric::ALL.into_iter()
+            .find(|metric| metric.name() == name.trim())
+            .ok_or_else(|| {
+                let names: Vec<&str> = Metric::ALL.iter().map(|metric| metric.name()).collect();
+                format!("unknown metric '{}'; expected one of: {}", name.trim(), names.join(", "))
+            })?;
+        let max = max.trim().parse()
+            .map_err(|_| format!("limit of '{}' must be a non-negative integer, got '{}'", metric.name(), max.trim()))?;
+        Ok(Self { metric, max })
+    }
+}
+
+/// Функция, превысившая порог
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LimitViolation {
+    pub file: String,
+    pub function: String,
+    pub metric: Metric,
+    pub value: usize,
+    pub max: usize,
+}
+
+impl fmt::Display for LimitViolation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f, "{}: function '{}' has {} {}, over the limit of {}",
+            self.file, self.function, self.metric.name(), self.value, self.max
+        )
+    }
+}
+
+/// Сумма метрик по файлу или программе; вложенность — максимум
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MetricsTotals {
+    pub functions: usize,
+    pub statements: usize,
+    pub expressions: usize,
+    pub max_nesting: usize,
+    pub complexity: usize,
+    pub max_complexity: usize,
+}
+
+impl MetricsTotals {
+    fn add(&mut self, metrics: &FunctionMetrics) {
+        self.functions += 1;
+        self.statements += metrics.statements;
+        self.expressions += metrics.expressions;
+        self.max_nesting = self.max_nesting.max(metrics.max_nesting);
+        self.complexity += metrics.complexity;
+        self.max_complexity = self.max_complexity.max(metrics.complexity);
+    }
+
+    fn to_json(&self) -> Value {
+        json!({
+            "functions": self.functions,
+            "statements": self.statements,
+            "expressions": self.expressions,
+            "max_nesting": self.max_nesting,
+            "complexity": self.complexity,
+            "max_complexity": self.max_complexity,
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileMetrics {
+    pub file: String,
+    pub functions: Vec<FunctionMetrics>,
+}
+
+impl FileMetrics {
+    pub fn new(file: &str, program: &Program) -> Self {
+        Self { file: file.to_string(), functions: program_metrics(program) }
+    }
+
+    pub fn totals(&self) -> MetricsTotals {
+        let mut totals = MetricsTotals::default();
+        for metrics in &self.functions {
+            totals.add(metrics);
+        }
+        totals
+    }
+}
+
+/// Итог `rono inspect --metrics` по всем файлам
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MetricsReport {
+    pub files: Vec<FileMetrics>,
+}
+
+impl MetricsReport {
+    pub fn totals(&self) -> MetricsTotals {
+        let mut totals = MetricsTotals::default();
+        for metrics in self.files.iter().flat_map(|file| &file.functions) {
+            totals.add(metrics);
+        }
+        totals
+    }
+
+    /// Нарушения порогов в порядке файлов и функций
+    pub fn violations(&self, limits: &[MetricLimit]) -> Vec<LimitViolation> {
+        let mut violations = Vec::new();
+        for file in &self.files {
+            for metrics in &file.functions {
+                for limit in limits {
+                    let value = limit.metric.value(metrics);
+                    if value > limit.max {
+                        violations.push(LimitViolation {
+                            file: file.file.clone(),
+                            function: metrics.name.clone(),
+                            metric: limit.metric,
+                            value,
+                            max: limit.max,
+                        });
+                    }
+                }
+            }
+        }
+        violations
+    }
+
+    pub fn to_json(&self) -> Value {
+        let files: Vec<Value> = self.files.iter().map(|file| json!({
+            "file": file.file,
+            "functions": file.functions.iter().map(|metrics| json!({
+                "name": metrics.name,
+                "statements": metrics.statements,
+                "expressions": metrics.expressions,
+                "max_nesting": metrics.max_nesting,
+                "complexity": metrics.complexity,
+                "params": metrics.params,
+            })).collect::<Vec<_>>(),
+            "totals": file.totals().to_json(),
+        })).collect();
+        json!({ "files": files, "totals": self.totals().to_json() })
+    }
+}
+
+impl fmt::Display for MetricsReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let width = self.files.iter()
+            .flat_map(|file| &file.functions)
+            .map(|metrics| metrics.name.chars().count())
+            .chain(std::iter::once("function".len()))
+            .max()
+            .unwrap_or_default();
+        let header = ["stmts", "exprs", "nesting", "complexity", "params"];
+        for file in &self.files {
+            writeln!(f, "{}", file.file)?;
+            write!(f, "  {:<width$}", "function", width = width)?;
+            for column in header {
+                write!(f, " {:>10}", column)?;
+            }
+            writeln!(f)?;
+            for metrics in &file.functions {
+                writeln!(
+                    f, "  {:<width$} {:>10} {:>10} {:>10} {:>10} {:>10}",
+                    metrics.name, metrics.statements, metrics.expressions, metrics.max_nesting,
+                    metrics.complexity, metrics.params, width = width
+                )?;
+            }
+            let totals = file.totals();
+            writeln!(
+                f, "  {:<width$} {:>10} {:>10} {:>10} {:>10}",
+                "total", totals.statements, totals.expressions, totals.max_nesting, totals.complexity, width = width
+            )?;
+        }
+        let totals = self.totals();
+        write!(
+            f,
+            "{} files, {} functions: {} statements, {} expressions, complexity {} (max {})",
+            self.files.len(), totals.functions, totals.statements, totals.expressions,
+            totals.complexity, totals.max_complexity
+        )
+    }
+}
+
+/// Метрики функций и методов программы в порядке объявления
+pub fn program_metrics(program: &Program) -> Vec<FunctionMetrics> {
+    let mut functions = Vec::new();
+    for item in &program.items {
+        match item {
+            Item::Function(func) => functions.push(function_metrics(&func.name, func)),
+            Item::StructImpl(impl_block) => {
+                for method in &impl_block.methods {
+                    let name = format!("{}.{}", impl_block.struct_name, method.name);
+                    functions.push(function_metrics(&name, method));
+                }
+            }
+            Item::Struct(_) | Item::Import(_) | Item::Global(_) => {}
+        }
+    }
+    functions
+}
+
+pub fn function_metrics(name: &str, func: &Function) -> FunctionMetrics {
+    let mut metrics = FunctionMetrics {
+        name: name.to_string(),
+        params: func.params.len(),
+        complexity: 1,
+        ..FunctionMetrics::default()
+    };
+    metrics.block(&func.body, 0);
+    metrics
+}
+
+impl FunctionMetrics {
+    fn block(&mut self, block: &Block, depth: usize) {
+        self.max_nesting = self.max_nesting.max(depth);
+        for statement in &block.statements {
+            self.statements += 1;
+            self.statement(statement, depth);
+        }
+    }
+
+    /// Заголовок `for` и ветка `else { if ... }` не добавляют инструкций
+    fn statement(&mut self, statement: &Statement, depth: usize) {
+        match statement {
+            Statement::VarDecl(var_decl) => self.optional(var_decl.value.as_ref()),
+            Statement::Assignment(assignment) => {
+                self.expression(&assignment.target);
+                self.expression(&assignment.value);
+            }
+            Statement::Destructure(destructure) => self.expression(&destructure.value),
+            Statement::Expression(expression) => self.expression(expression),
+            Statement::If(if_stmt) => self.if_statement(if_stmt, depth),
+            Statement::For(for_stmt) => {
+                self.complexity += 1;
+                if let Some(init) = &for_stmt.init {
+                    self.statement(init, depth);
+                }
+                self.optional(for_stmt.condition.as_ref());
+                if let Some(update) = &for_stmt.update {
+                    self.statement(update, depth);
+                }
+                self.block(&for_stmt.body, depth + 1);
+            }
+            Statement::ForIn(for_in) => {
+                self.complexity += 1;
+                self.expression(&for_in.iterable);
+                self.block(&for_in.body, depth + 1);
+            }
+            Statement::While(while_stmt) => {
+                self.complexity += 1;
+                self.expression(&while_stmt.condition);
+                self.block(&while_stmt.body, depth + 1);
+            }
+            Statement::Switch(switch_stmt) => {
+                self.expression(&switch_stmt.expr);
+                for case in &switch_stmt.cases {
+                    self.complexity += 1;
+                    self.expression(&case.value);
+                    self.block(&case.body, depth + 1);
+                }
+                if let Some(default_case) = &switch_stmt.default_case {
+                    self.block(default_case, depth + 1);
+                }
+            }
+            Statement::Return(value) => self.optional(value.as_ref()),
+            Statement::Break | Statement::Continue => {}
+        }
+    }
+
+    /// `else { if (...) { } }` с единственной инструкцией считается как else-if:
+    /// на том же уровне вложенности и без лишней инструкции
+    fn if_statement(&mut self, if_stmt: &IfStatement, depth: usize) {
+        self.complexity += 1;
+        self.expression(&if_stmt.condition);
+        self.block(&if_stmt.then_block, depth + 1);
+        if let Some(else_block) = &if_stmt.else_block {
+            match else_block.statements.as_slice() {
+                [Statement::If(else_if)] => self.if_statement(else_if, depth),
+                _ => self.block(else_block, depth + 1),
+            }
+        }
+    }
+
+    fn optional(&mut self, expression: Option<&Expression>) {
+        if let Some(expression) = expression {
+            self.expression(expression);
+        }
+    }
+
+    fn expression(&mut self, expression: &Expression) {
+        self.expressions += 1;
+        match expression {
+            Expression::Literal(_) | Expression::Identifier(_) => {}
+            Expression::Binary(binary_op) => {
+                if matches!(binary_op.operator, BinaryOperator::And | BinaryOperator::Or) {
+                    self.complexity += 1;
+                }
+                self.expression(&binary_op.left);
+                self.expression(&binary_op.right);
+            }
+            Expression::Unary(unary_op) => self.expression(&unary_op.operand),
+            Expression::Call(call) => {
+                for arg in call.args.iter().chain(call.named_args.iter().map(|(_, arg)| arg)) {
+                    self.expression(arg);
+                }
+            }
+            Expression::MethodCall(method_call) => {
+                self.expression(&method_call.object);
+                for arg in &method_call.args {
+                    self.expression(arg);
+                }
+            }
+            Expression::Index(index_access) => {
+                self.expression(&index_access.object);
+                for index in &index_access.indices {
+                    self.expression(index);
+                }
+            }
+            Expression::FieldAccess(field_access) => self.expression(&field_access.object),
+            Expression::ArrayLiteral(elements) | Expression::TupleLiteral(elements) => {
+                for element in elements {
+                    self.expression(element);
+                }
+            }
+            Expression::MapLiteral(entries) => {
+                for (key, value) in entries {
+                    self.expression(key);
+                    self.expression(value);
+                }
+            }
+            Expression::StructLiteral(struct_literal) => {
+                for (_, value) in &struct_literal.fields {
+                    self.expression(value);
+                }
+                if let Some(base) = &struct_literal.base {
+                    self.expression(base);
+                }
+            }
+            Expression::Reference(AddressOf { target, .. }) => self.expression(target),
+            Expression::Dereference(target) => self.expression(target),
+        }
+    }
+}
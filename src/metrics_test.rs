@@ -0,0 +1,67 @@
+#[cfg(test)]
+mod tests {
+    use crate::metrics::{FileMetrics, FunctionMetrics, Metric, MetricLimit, MetricsReport, MetricsTotals};
+    use crate::parse_source;
+
+    const FIXTURE: &str = include_str!("../tests/metrics/fixture.rono");
+
+    fn fixture_report() -> MetricsReport {
+        let program = parse_source(FIXTURE).expect("fixture should parse");
+        MetricsReport { files: vec![FileMetrics::new("fixture.rono", &program)] }
+    }
+
+    fn metrics(name: &str, statements: usize, expressions: usize, max_nesting: usize, complexity: usize, params: usize) -> FunctionMetrics {
+        FunctionMetrics { name: name.to_string(), statements, expressions, max_nesting, complexity, params }
+    }
+
+    #[test]
+    fn test_metrics_of_the_fixture() {
+        let report = fixture_report();
+        assert_eq!(report.files[0].functions, [
+            metrics("Counter.bump", 1, 6, 0, 1, 2),
+            // if + && + else-if + else-if + ||; else-if chains stay at depth 1
+            metrics("classify", 5, 18, 1, 6, 2),
+            // for + while + for-in + two cases; default adds nothing
+            metrics("loops", 10, 27, 2, 6, 1),
+            metrics("main", 3, 13, 0, 1, 0),
+        ]);
+        assert_eq!(report.totals(), MetricsTotals {
+            functions: 4,
+            statements: 19,
+            expressions: 64,
+            max_nesting: 2,
+            complexity: 14,
+            max_complexity: 6,
+        });
+
+        let json = report.to_json();
+        assert_eq!(json["files"][0]["functions"][2]["complexity"], 6);
+        assert_eq!(json["files"][0]["totals"]["statements"], 19);
+        assert_eq!(json["totals"]["max_complexity"], 6);
+    }
+
+    #[test]
+    fn test_limits_report_functions_over_the_threshold() {
+        let limit = MetricLimit::parse("complexity=5").expect("limit should parse");
+        assert_eq!(limit, MetricLimit { metric: Metric::Complexity, max: 5 });
+        assert!(MetricLimit::parse("depth=3").unwrap_err().contains("unknown metric 'depth'"));
+        assert!(MetricLimit::parse("complexity").is_err());
+        assert!(MetricLimit::parse("params=-1").is_err());
+
+        let report = fixture_report();
+        let violations = report.violations(&[limit, MetricLimit::parse("nesting=1").unwrap()]);
+        let found: Vec<(&str, Metric, usize)> = violations.iter()
+            .map(|violation| (violation.function.as_str(), violation.metric, violation.value))
+            .collect();
+        assert_eq!(found, [
+            ("classify", Metric::Complexity, 6),
+            ("loops", Metric::Complexity, 6),
+            ("loops", Metric::Nesting, 2),
+        ]);
+        assert_eq!(
+            violations[0].to_string(),
+            "fixture.rono: function 'classify' has complexity 6, over the limit of 5"
+        );
+        assert!(report.violations(&[MetricLimit::parse("complexity=6").unwrap()]).is_empty());
+    }
+}
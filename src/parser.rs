@@ -1,16 +1,37 @@
 use crate::ast::*;
 use crate::error::{ChifError, Result};
-use crate::lexer::Token;
+use crate::lexer::{SpannedToken, Token};
 use crate::types::{ChifType, ChifValue};
 
+/// Предел вложенности блоков, типов и выражений: разбор рекурсивный, и без
+/// предела глубоко вложенный ввод переполняет стек вместо ошибки разбора
+const MAX_NESTING: usize = 48;
+
 pub struct Parser {
     tokens: Vec<Token>,
     current: usize,
+    /// (line, col) каждого токена, если парсер создан через `from_spanned`
+    locations: Vec<(u32, u32)>,
+    depth: usize,
 }
 
 impl Parser {
     pub fn new(tokens: Vec<Token>) -> Self {
-        Self { tokens, current: 0 }
+        Self { tokens, current: 0, locations: Vec::new(), depth: 0 }
+    }
+    
+    /// Парсер над выводом `Lexer::tokenize_spanned`; комментарии и пробелы пропускаются
+    pub fn from_spanned(tokens: Vec<SpannedToken>) -> Self {
+        let (locations, tokens) = tokens.into_iter()
+            .filter(|spanned| !spanned.token.is_trivia())
+            .map(|spanned| ((spanned.line, spanned.col), spanned.token))
+            .unzip();
+        Self { tokens, current: 0, locations, depth: 0 }
+    }
+    
+    /// Позиция текущего токена, если она известна
+    pub fn current_location(&self) -> Option<(u32, u32)> {
+        self.locations.get(self.current).copied()
     }
     
     pub fn parse(&mut self) -> Result<Program> {
@@ -38,6 +59,33 @@ impl Parser {
                 let func = self.parse_function(false)?;
                 Ok(Item::Function(func))
             }
+            Token::At => {
+                let deprecated = self.parse_deprecated_attribute()?;
+                let is_exported = self.match_token(&Token::Export);
+                if !self.check(&Token::Fn) {
+                    return Err(self.expected("'fn' after @deprecated attribute"));
+                }
+                let mut func = self.parse_function(false)?;
+                func.deprecated = Some(deprecated);
+                func.is_exported = is_exported;
+                Ok(Item::Function(func))
+            }
+            Token::Export => {
+                self.advance(); // consume 'export'
+                match self.peek() {
+                    Token::Fn => {
+                        let mut func = self.parse_function(false)?;
+                        func.is_exported = true;
+                        Ok(Item::Function(func))
+                    }
+                    Token::Struct => {
+                        let mut struct_def = self.parse_struct_def()?;
+                        struct_def.is_exported = true;
+                        Ok(Item::Struct(struct_def))
+                    }
+                    _ => Err(self.expected("'fn' or 'struct' after 'export'")),
+                }
+            }
             Token::FnFor => {
                 let impl_block = self.parse_struct_impl()?;
                 Ok(Item::StructImpl(impl_block))
@@ -46,36 +94,67 @@ impl Parser {
                 let struct_def = self.parse_struct_def()?;
                 Ok(Item::Struct(struct_def))
             }
-            _ => Err(ChifError::ParserError {
-                message: format!("Expected import, function, struct, or struct implementation, found {:?}", self.peek()),
-            }),
+            Token::Let | Token::Var | Token::Array | Token::List => {
+                match self.parse_var_decl()? {
+                    Statement::VarDecl(var_decl) => Ok(Item::Global(var_decl)),
+                    _ => Err(self.error("Global variables cannot be destructured; declare each one".to_string())),
+                }
+            }
+            _ => Err(self.expected("import, function, struct, struct implementation, or global variable")),
         }
     }
     
     fn parse_import(&mut self) -> Result<ImportStatement> {
+        let line = self.current_location().map_or(0, |(line, _)| line as usize);
         self.consume(Token::Import, "Expected 'import'")?;
         
-        let path = match self.advance() {
-            Token::StringLiteral(path) => path,
-            _ => return Err(ChifError::ParserError {
-                message: "Expected string literal after 'import'".to_string(),
-            }),
-        };
+        let path = self.string_literal("string literal after 'import'")?;
         
         let alias = if self.match_token(&Token::As) {
-            match self.advance() {
-                Token::Identifier(alias) => Some(alias),
-                _ => return Err(ChifError::ParserError {
-                    message: "Expected identifier after 'as'".to_string(),
-                }),
+            Some(self.identifier("identifier after 'as'")?)
+        } else {
+            None
+        };
+        
+        // `use` is not a keyword, so it stays available as an identifier elsewhere
+        let names = if matches!(self.peek(), Token::Identifier(word) if word == "use") {
+            self.advance(); // consume 'use'
+            self.consume(Token::LeftParen, "Expected '(' after 'use'")?;
+            let mut names = Vec::new();
+            loop {
+                names.push(self.identifier("a function or struct name in 'use (...)'")?);
+                if !self.match_token(&Token::Comma) {
+                    break;
+                }
             }
+            self.consume(Token::RightParen, "Expected ')' after imported names")?;
+            Some(names)
         } else {
             None
         };
         
         self.consume(Token::Semicolon, "Expected ';' after import statement")?;
         
-        Ok(ImportStatement { path, alias })
+        Ok(ImportStatement { path, alias, names, line })
+    }
+    
+    /// @deprecated("message")
+    fn parse_deprecated_attribute(&mut self) -> Result<String> {
+        self.consume(Token::At, "Expected '@'")?;
+        
+        match self.peek() {
+            Token::Identifier(name) if name == "deprecated" => {
+                self.advance();
+            }
+            Token::Identifier(name) => return Err(self.error(format!("Unknown attribute '{}'", name))),
+            _ => return Err(self.expected("attribute name after '@'")),
+        }
+        
+        self.consume(Token::LeftParen, "Expected '(' after 'deprecated'")?;
+        let message = self.string_literal("string literal in @deprecated")?;
+        self.consume(Token::RightParen, "Expected ')' after deprecation message")?;
+        
+        Ok(message)
     }
     
     fn parse_function(&mut self, is_main: bool) -> Result<Function> {
@@ -83,12 +162,7 @@ impl Parser {
             self.consume(Token::Fn, "Expected 'fn'")?;
         }
         
-        let name = match self.advance() {
-            Token::Identifier(name) => name,
-            _ => return Err(ChifError::ParserError {
-                message: "Expected function name".to_string(),
-            }),
-        };
+        let name = self.identifier("function name")?;
         
         self.consume(Token::LeftParen, "Expected '(' after function name")?;
         
@@ -96,26 +170,27 @@ impl Parser {
         if !self.check(&Token::RightParen) {
             loop {
                 // Check for 'ref' keyword
-                let is_reference = if self.check(&Token::Ref) {
+                let mut is_reference = if self.check(&Token::Ref) {
                     self.advance(); // consume 'ref'
                     true
                 } else {
                     false
                 };
                 
-                let param_name = match self.advance() {
-                    Token::Identifier(name) => name,
-                    _ => return Err(ChifError::ParserError {
-                        message: "Expected parameter name".to_string(),
-                    }),
-                };
+                let param_name = self.identifier("parameter name")?;
                 
                 // Special handling for 'self' parameter
                 let param_type = if param_name == "self" {
                     ChifType::Struct("Self".to_string()) // Special type for self
                 } else {
                     self.consume(Token::Colon, "Expected ':' after parameter name")?;
-                    self.parse_type()?
+                    if self.match_token(&Token::Reference) {
+                        // `&T` только читает значение вызывающего, `&mut T` может его менять
+                        is_reference |= self.match_contextual("mut");
+                        ChifType::Pointer(Box::new(self.parse_type()?))
+                    } else {
+                        self.parse_type()?
+                    }
                 };
                 
                 params.push(Parameter {
@@ -132,8 +207,9 @@ impl Parser {
         
         self.consume(Token::RightParen, "Expected ')' after parameters")?;
         
+        // `fn f() nil {` and `fn f() {` are the same function without a return value
         let return_type = if !self.check(&Token::LeftBrace) {
-            Some(self.parse_type()?)
+            Some(self.parse_type()?).filter(|return_type| *return_type != ChifType::Nil)
         } else {
             None
         };
@@ -146,29 +222,23 @@ impl Parser {
             return_type,
             body,
             is_main,
+            deprecated: None,
+            is_exported: false,
         })
     }
     
     fn parse_struct_def(&mut self) -> Result<StructDef> {
         self.consume(Token::Struct, "Expected 'struct'")?;
         
-        let name = match self.advance() {
-            Token::Identifier(name) => name,
-            _ => return Err(ChifError::ParserError {
-                message: "Expected struct name".to_string(),
-            }),
-        };
+        let name = self.identifier("struct name")?;
         
+        let open = self.current;
         self.consume(Token::LeftBrace, "Expected '{' after struct name")?;
         
         let mut fields = Vec::new();
         while !self.check(&Token::RightBrace) && !self.is_at_end() {
-            let field_name = match self.advance() {
-                Token::Identifier(name) => name,
-                _ => return Err(ChifError::ParserError {
-                    message: "Expected field name".to_string(),
-                }),
-            };
+            let is_private = self.match_token(&Token::Priv);
+            let field_name = self.identifier("field name")?;
             
             self.consume(Token::Colon, "Expected ':' after field name")?;
             let field_type = self.parse_type()?;
@@ -177,31 +247,28 @@ impl Parser {
             fields.push(StructField {
                 name: field_name,
                 field_type,
+                is_private,
             });
         }
         
-        self.consume(Token::RightBrace, "Expected '}' after struct fields")?;
+        self.consume_closing(open, Token::RightBrace, "Expected '}' after struct fields")?;
         
-        Ok(StructDef { name, fields })
+        Ok(StructDef { name, fields, is_exported: false })
     }
     
     fn parse_struct_impl(&mut self) -> Result<StructImpl> {
         self.consume(Token::FnFor, "Expected 'fn_for'")?;
         
-        let struct_name = match self.advance() {
-            Token::Identifier(name) => name,
-            _ => return Err(ChifError::ParserError {
-                message: "Expected struct name".to_string(),
-            }),
-        };
+        let struct_name = self.identifier("struct name")?;
         
+        let open = self.current;
         self.consume(Token::LeftBrace, "Expected '{' after struct name")?;
         
         let mut methods = Vec::new();
         while !self.check(&Token::RightBrace) && !self.is_at_end() {
             let mut method = self.parse_function(false)?;
             
-            // Replace 'Self' type with actual struct name in parameters
+            // Replace 'Self' type with actual struct name in parameters and return type
             for param in &mut method.params {
                 if let ChifType::Struct(type_name) = &param.param_type {
                     if type_name == "Self" {
@@ -209,11 +276,16 @@ impl Parser {
                     }
                 }
             }
+            if let Some(ChifType::Struct(type_name)) = &method.return_type {
+                if type_name == "Self" {
+                    method.return_type = Some(ChifType::Struct(struct_name.clone()));
+                }
+            }
             
             methods.push(method);
         }
         
-        self.consume(Token::RightBrace, "Expected '}' after struct methods")?;
+        self.consume_closing(open, Token::RightBrace, "Expected '}' after struct methods")?;
         
         Ok(StructImpl {
             struct_name,
@@ -222,6 +294,10 @@ impl Parser {
     }
     
     fn parse_type(&mut self) -> Result<ChifType> {
+        self.nested(Self::parse_type_inner)
+    }
+    
+    fn parse_type_inner(&mut self) -> Result<ChifType> {
         match self.advance() {
             Token::Int => Ok(ChifType::Int),
             Token::Float => Ok(ChifType::Float),
@@ -253,20 +329,7 @@ impl Parser {
                 } else {
                     // Old syntax: array type[size]
                     let inner_type = self.parse_type()?;
-                    let mut dimensions = Vec::new();
-                    
-                    while self.check(&Token::LeftBracket) {
-                        self.advance(); // consume '['
-                        if let Token::IntLiteral(size) = self.advance() {
-                            dimensions.push(size as usize);
-                        } else {
-                            return Err(ChifError::ParserError {
-                                message: "Expected array size".to_string(),
-                            });
-                        }
-                        self.consume(Token::RightBracket, "Expected ']' after array size")?;
-                    }
-                    
+                    let dimensions = self.parse_array_dimensions()?;
                     Ok(ChifType::Array(Box::new(inner_type), dimensions))
                 }
             }
@@ -280,43 +343,89 @@ impl Parser {
                     
                     // Check for additional dimensions [][]
                     let mut dimensions = vec![0]; // One dimension by default
-                    while self.check(&Token::LeftBracket) {
-                        self.advance(); // consume '['
-                        self.consume(Token::RightBracket, "Expected ']' for list dimension")?;
-                        dimensions.push(0);
-                    }
-                    
+                    dimensions.extend(self.parse_list_dimensions());
                     Ok(ChifType::List(Box::new(inner_type), dimensions))
+                } else if self.check(&Token::Assign) || self.check(&Token::Semicolon) {
+                    // Bare `list`: the element type comes from the initializer.
+                    // Nil marks it as unknown, the analyzer and interpreter reject `[]`
+                    Ok(ChifType::List(Box::new(ChifType::Nil), vec![0]))
                 } else {
                     // Old syntax: list type[]
                     let inner_type = self.parse_type()?;
-                    let mut dimensions = Vec::new();
-                    
-                    while self.check(&Token::LeftBracket) {
-                        self.advance(); // consume '['
-                        self.consume(Token::RightBracket, "Expected ']' for list dimension")?;
-                        dimensions.push(0); // Lists don't have fixed sizes
-                    }
-                    
+                    let dimensions = self.parse_list_dimensions();
                     Ok(ChifType::List(Box::new(inner_type), dimensions))
                 }
             }
             Token::Map => {
                 self.consume(Token::LeftBracket, "Expected '[' after 'map'")?;
                 let key_type = self.parse_type()?;
-                self.consume(Token::Colon, "Expected ':' in map type")?;
+                if !self.check(&Token::Colon) {
+                    return Err(self.error(format!(
+                        "Expected ':' and a value type in map type, as in map[{}:int]",
+                        key_type
+                    )));
+                }
+                self.advance(); // consume ':'
                 let value_type = self.parse_type()?;
                 self.consume(Token::RightBracket, "Expected ']' after map type")?;
                 Ok(ChifType::Map(Box::new(key_type), Box::new(value_type)))
             }
             Token::Identifier(name) => Ok(ChifType::Struct(name)),
-            token => Err(ChifError::ParserError {
-                message: format!("Expected type, found {:?}", token),
-            }),
+            Token::LeftParen => {
+                // Tuple type: (type, type, ...); a single type in parentheses is just grouping
+                let mut elements = vec![self.parse_type()?];
+                let mut trailing_comma = false;
+                while self.match_token(&Token::Comma) {
+                    trailing_comma = self.check(&Token::RightParen);
+                    if trailing_comma {
+                        break;
+                    }
+                    elements.push(self.parse_type()?);
+                }
+                self.consume(Token::RightParen, "Expected ')' after tuple type")?;
+                
+                match elements.len() {
+                    1 if !trailing_comma => Ok(elements.remove(0)),
+                    1 => Err(self.error_at_previous("Tuple type must have at least 2 elements".to_string())),
+                    _ => Ok(ChifType::Tuple(elements)),
+                }
+            }
+            token => Err(self.error_at_previous(format!("Expected type, found {}", token.describe()))),
         }
     }
     
+    /// Размеры массива `[3][4]`: первый — внешний, то есть 3 строки по 4 элемента
+    fn parse_array_dimensions(&mut self) -> Result<Vec<usize>> {
+        let mut dimensions = Vec::new();
+        while self.match_token(&Token::LeftBracket) {
+            match self.advance() {
+                Token::IntLiteral(size) if size > 0 => dimensions.push(size as usize),
+                Token::IntLiteral(_) => return Err(self.error_at_previous("Array size must be positive".to_string())),
+                token => return Err(self.error_at_previous(format!("Expected array size, found {}", token.describe()))),
+            }
+            self.consume(Token::RightBracket, "Expected ']' after array size")?;
+        }
+        Ok(dimensions)
+    }
+    
+    /// Измерения списка `[][]`. Берутся только пустые пары скобок, поэтому в
+    /// `array list[int][3]` размер `[3]` остаётся массиву
+    fn parse_list_dimensions(&mut self) -> Vec<usize> {
+        let mut dimensions = Vec::new();
+        while self.check(&Token::LeftBracket) && self.peek_next() == Token::RightBracket {
+            self.advance(); // consume '['
+            self.advance(); // consume ']'
+            dimensions.push(0); // Lists don't have fixed sizes
+        }
+        dimensions
+    }
+    
     fn parse_block(&mut self) -> Result<Block> {
+        self.nested(Self::parse_block_inner)
+    }
+    
+    fn parse_block_inner(&mut self) -> Result<Block> {
+        let open = self.current;
         self.consume(Token::LeftBrace, "Expected '{'")?;
         
         let mut statements = Vec::new();
@@ -324,7 +433,7 @@ impl Parser {
             statements.push(self.parse_statement()?);
         }
         
-        self.consume(Token::RightBrace, "Expected '}'")?;
+        self.consume_closing(open, Token::RightBrace, "Expected '}' at the end of the block")?;
         
         Ok(Block { statements })
     }
@@ -340,6 +449,11 @@ impl Parser {
             Token::Ret => self.parse_return_statement(),
             Token::Break => self.parse_break_statement(),
             Token::Continue => self.parse_continue_statement(),
+            Token::Identifier(name) if name == DISCARD && self.peek_next() == Token::Assign => {
+                self.advance(); // consume '_'
+                self.advance(); // consume '='
+                self.parse_discard()
+            }
             _ => {
                 let expr = self.parse_expression()?;
                 
@@ -365,17 +479,20 @@ impl Parser {
             Token::Var => (true, None),
             Token::Array => (false, Some("array")),
             Token::List => (false, Some("list")),
-            _ => return Err(ChifError::ParserError {
-                message: "Expected variable declaration".to_string(),
-            }),
+            _ => return Err(self.error("Expected variable declaration".to_string())),
         };
         
-        let name = match self.advance() {
-            Token::Identifier(name) => name,
-            _ => return Err(ChifError::ParserError {
-                message: "Expected variable name".to_string(),
-            }),
-        };
+        // Tuple destructuring: var (a, b) = expr;
+        if collection_type.is_none() && self.check(&Token::LeftParen) {
+            return self.parse_destructure(is_mutable);
+        }
+        
+        let name = self.identifier("variable name")?;
+        
+        // var _ = expr; needs no type: the value is dropped
+        if name == DISCARD && collection_type.is_none() && self.match_token(&Token::Assign) {
+            return self.parse_discard();
+        }
         
         self.consume(Token::Colon, "Expected ':' after variable name")?;
         
@@ -385,33 +502,13 @@ impl Parser {
                 "array" => {
                     // Parse array name: type[size][size]...
                     let inner_type = self.parse_type()?;
-                    let mut dimensions = Vec::new();
-                    
-                    while self.check(&Token::LeftBracket) {
-                        self.advance(); // consume '['
-                        if let Token::IntLiteral(size) = self.advance() {
-                            dimensions.push(size as usize);
-                        } else {
-                            return Err(ChifError::ParserError {
-                                message: "Expected array size".to_string(),
-                            });
-                        }
-                        self.consume(Token::RightBracket, "Expected ']' after array size")?;
-                    }
-                    
+                    let dimensions = self.parse_array_dimensions()?;
                     crate::types::ChifType::Array(Box::new(inner_type), dimensions)
                 }
                 "list" => {
                     // Parse list name: type[]...
                     let inner_type = self.parse_type()?;
-                    let mut dimensions = Vec::new();
-                    
-                    while self.check(&Token::LeftBracket) {
-                        self.advance(); // consume '['
-                        self.consume(Token::RightBracket, "Expected ']' for list dimension")?;
-                        dimensions.push(0); // Lists don't have fixed sizes
-                    }
-                    
+                    let dimensions = self.parse_list_dimensions();
                     crate::types::ChifType::List(Box::new(inner_type), dimensions)
                 }
                 _ => unreachable!(),
@@ -436,6 +533,66 @@ impl Parser {
         }))
     }
     
+    /// `_ = expr;` после `=`: значение вычисляется и отбрасывается
+    fn parse_discard(&mut self) -> Result<Statement> {
+        let value = self.parse_expression()?;
+        self.consume(Token::Semicolon, "Expected ';' after discarded value")?;
+        Ok(Statement::Assignment(Assignment {
+            target: Expression::Identifier(DISCARD.to_string()),
+            value,
+        }))
+    }
+    
+    fn parse_destructure(&mut self, is_mutable: bool) -> Result<Statement> {
+        self.consume(Token::LeftParen, "Expected '(' in destructuring declaration")?;
+        
+        let mut names = Vec::new();
+        loop {
+            names.push(self.identifier("variable name in destructuring declaration")?);
+            if !self.match_token(&Token::Comma) {
+                break;
+            }
+        }
+        
+        self.consume(Token::RightParen, "Expected ')' after destructured names")?;
+        
+        // Optional type annotation: var (a, b): (int, int) = ...
+        let var_type = if self.match_token(&Token::Colon) {
+            Some(self.parse_type()?)
+        } else {
+            None
+        };
+        
+        self.consume(Token::Assign, "Expected '=' in destructuring declaration")?;
+        let value = self.parse_expression()?;
+        self.consume(Token::Semicolon, "Expected ';' after destructuring declaration")?;
+        
+        Ok(Statement::Destructure(Destructure {
+            names,
+            var_type,
+            value,
+            is_mutable,
+        }))
+    }
+    
+    /// `for i in <iterable> { ... }`; `in` is not a keyword, so it is only
+    /// recognized right after the loop variable
+    fn parse_for_in(&mut self) -> Result<Statement> {
+        let variable = self.identifier("'(' or a loop variable after 'for'")?;
+        if !self.match_contextual("in") {
+            return Err(self.expected(&format!("'in' after loop variable '{}'", variable)));
+        }
+        
+        let iterable = self.parse_expression()?;
+        let body = self.parse_block()?;
+        
+        Ok(Statement::ForIn(ForInStatement {
+            variable,
+            iterable,
+            body,
+        }))
+    }
+    
     fn parse_if_statement(&mut self) -> Result<Statement> {
         self.consume(Token::If, "Expected 'if'")?;
         self.consume(Token::LeftParen, "Expected '(' after 'if'")?;
@@ -457,24 +614,55 @@ impl Parser {
         }))
     }
     
+    /// Type of a for-loop initializer without an annotation: literals and
+    /// arithmetic, comparisons and logic over them
+    fn infer_initializer_type(expression: &Expression) -> Option<ChifType> {
+        match expression {
+            Expression::Literal(ChifValue::Int(_)) => Some(ChifType::Int),
+            Expression::Literal(ChifValue::Float(_)) => Some(ChifType::Float),
+            Expression::Literal(ChifValue::Bool(_)) => Some(ChifType::Bool),
+            Expression::Literal(ChifValue::Str(_)) => Some(ChifType::Str),
+            Expression::Unary(unary_op) => match (&unary_op.operator, Self::infer_initializer_type(&unary_op.operand)?) {
+                (UnaryOperator::Minus, operand @ (ChifType::Int | ChifType::Float)) => Some(operand),
+                (UnaryOperator::Not, ChifType::Bool) => Some(ChifType::Bool),
+                _ => None,
+            },
+            Expression::Binary(binary_op) => {
+                let left = Self::infer_initializer_type(&binary_op.left)?;
+                let right = Self::infer_initializer_type(&binary_op.right)?;
+                match binary_op.operator {
+                    BinaryOperator::Add | BinaryOperator::Subtract | BinaryOperator::Multiply
+                    | BinaryOperator::Divide | BinaryOperator::Modulo => match (left, right) {
+                        (ChifType::Int, ChifType::Int) => Some(ChifType::Int),
+                        (ChifType::Int | ChifType::Float, ChifType::Int | ChifType::Float) => Some(ChifType::Float),
+                        _ => None,
+                    },
+                    _ => Some(ChifType::Bool),
+                }
+            }
+            _ => None,
+        }
+    }
+    
     fn parse_for_statement(&mut self) -> Result<Statement> {
         self.consume(Token::For, "Expected 'for'")?;
+        if !self.check(&Token::LeftParen) {
+            return self.parse_for_in();
+        }
         self.consume(Token::LeftParen, "Expected '(' after 'for'")?;
         
         // Parse initialization - support both var declaration and assignment
         let init = if !self.check(&Token::Semicolon) {
             if self.check(&Token::Var) {
-                // Parse variable declaration: var i: int = 0
+                // Parse variable declaration: var i: int = 0, or var i = 0 with the type inferred
                 self.advance(); // consume 'var'
-                let name = match self.advance() {
-                    Token::Identifier(name) => name,
-                    _ => return Err(ChifError::ParserError {
-                        message: "Expected variable name".to_string(),
-                    }),
-                };
+                let name = self.identifier("variable name")?;
                 
-                self.consume(Token::Colon, "Expected ':' after variable name")?;
-                let var_type = self.parse_type()?;
+                let declared_type = if self.match_token(&Token::Colon) {
+                    Some(self.parse_type()?)
+                } else {
+                    None
+                };
                 
                 let value = if self.match_token(&Token::Assign) {
                     Some(self.parse_expression()?)
@@ -482,6 +670,15 @@ impl Parser {
                     None
                 };
                 
+                // The type goes into the AST, so the analyzer and the compiler see a normal declaration
+                let var_type = match declared_type {
+                    Some(var_type) => var_type,
+                    None => value.as_ref().and_then(Self::infer_initializer_type).ok_or_else(|| self.error(format!(
+                        "Cannot infer the type of loop variable '{}' from its initializer; annotate it as var {}: <type>",
+                        name, name
+                    )))?,
+                };
+                
                 Some(Box::new(Statement::VarDecl(VarDecl {
                     name,
                     var_type,
@@ -490,12 +687,7 @@ impl Parser {
                 })))
             } else {
                 // Parse assignment: i = 0
-                let var_name = match self.advance() {
-                    Token::Identifier(name) => name,
-                    _ => return Err(ChifError::ParserError {
-                        message: "Expected variable name in for loop initialization".to_string(),
-                    }),
-                };
+                let var_name = self.identifier("variable name in for loop initialization")?;
                 
                 self.consume(Token::Assign, "Expected '=' in for loop initialization")?;
                 let value = self.parse_expression()?;
@@ -521,12 +713,7 @@ impl Parser {
         
         let update = if !self.check(&Token::RightParen) {
             // Parse update as assignment: i = i + 1
-            let var_name = match self.advance() {
-                Token::Identifier(name) => name,
-                _ => return Err(ChifError::ParserError {
-                    message: "Expected variable name in for loop update".to_string(),
-                }),
-            };
+            let var_name = self.identifier("variable name in for loop update")?;
             
             self.consume(Token::Assign, "Expected '=' in for loop update")?;
             let value = self.parse_expression()?;
@@ -571,7 +758,14 @@ impl Parser {
         
         while !self.is_at_end() && (self.check(&Token::Case) || self.check(&Token::Default)) {
             if self.match_token(&Token::Case) {
-                let value = self.parse_expression()?;
+                // `case name {` is a constant followed by the case body, not a struct literal
+                let value = match (self.peek(), self.peek_next()) {
+                    (Token::Identifier(name), Token::LeftBrace) => {
+                        self.advance();
+                        Expression::Identifier(name)
+                    }
+                    _ => self.parse_expression()?,
+                };
                 let body = self.parse_block()?;
                 cases.push(SwitchCase { value, body });
             } else if self.match_token(&Token::Default) {
@@ -613,6 +807,9 @@ impl Parser {
         Ok(Statement::Continue)
     }
     
+    /// Лестница приоритетов, от низшего к высшему: `||`, `&&`, равенство,
+    /// сравнение и `in`, `+ -`, `* / %`, унарные операторы, постфиксные (вызов, индекс,
+    /// поле, метод). Поэтому `-p.x` — это `-(p.x)`, а `-a * b` — `(-a) * b`
     fn parse_expression(&mut self) -> Result<Expression> {
         self.parse_or()
     }
@@ -626,6 +823,7 @@ impl Parser {
                 left: Box::new(expr),
                 operator: BinaryOperator::Or,
                 right: Box::new(right),
+                int_only: false,
             });
         }
         
@@ -641,6 +839,7 @@ impl Parser {
                 left: Box::new(expr),
                 operator: BinaryOperator::And,
                 right: Box::new(right),
+                int_only: false,
             });
         }
         
@@ -656,6 +855,7 @@ impl Parser {
                 left: Box::new(expr),
                 operator: op,
                 right: Box::new(right),
+                int_only: false,
             });
         }
         
@@ -671,6 +871,7 @@ impl Parser {
                 left: Box::new(expr),
                 operator: op,
                 right: Box::new(right),
+                int_only: false,
             });
         }
         
@@ -686,6 +887,7 @@ impl Parser {
                 left: Box::new(expr),
                 operator: op,
                 right: Box::new(right),
+                int_only: false,
             });
         }
         
@@ -701,6 +903,7 @@ impl Parser {
                 left: Box::new(expr),
                 operator: op,
                 right: Box::new(right),
+                int_only: false,
             });
         }
         
@@ -708,6 +911,10 @@ impl Parser {
     }
     
     fn parse_unary(&mut self) -> Result<Expression> {
+        self.nested(Self::parse_unary_inner)
+    }
+    
+    fn parse_unary_inner(&mut self) -> Result<Expression> {
         if let Some(op) = self.match_unary_op() {
             let operand = self.parse_unary()?;
             Ok(Expression::Unary(UnaryOp {
@@ -715,8 +922,13 @@ impl Parser {
                 operand: Box::new(operand),
             }))
         } else if self.match_token(&Token::Reference) {
+            // `mut` не ключевое слово: в `&mut x` за ним идёт имя переменной
+            let mutable = matches!(self.peek_next(), Token::Identifier(_)) && self.match_contextual("mut");
             let operand = self.parse_unary()?;
-            Ok(Expression::Reference(Box::new(operand)))
+            Ok(Expression::Reference(AddressOf {
+                target: Box::new(operand),
+                mutable,
+            }))
         } else if self.match_token(&Token::Multiply) {
             // In unary context, * is dereference
             let operand = self.parse_unary()?;
@@ -731,11 +943,20 @@ impl Parser {
         
         loop {
             if self.match_token(&Token::LeftParen) {
-                // Function call
+                // Function call: f(1, 2) or with named arguments f(x: 1, y: 2)
                 let mut args = Vec::new();
+                let mut named_args = Vec::new();
                 if !self.check(&Token::RightParen) {
                     loop {
-                        args.push(self.parse_expression()?);
+                        if let (Token::Identifier(arg_name), Token::Colon) = (self.peek(), self.peek_next()) {
+                            self.advance(); // name
+                            self.advance(); // ':'
+                            named_args.push((arg_name, self.parse_expression()?));
+                        } else if !named_args.is_empty() {
+                            return Err(self.error("Positional argument cannot follow a named argument".to_string()));
+                        } else {
+                            args.push(self.parse_expression()?);
+                        }
                         if !self.match_token(&Token::Comma) {
                             break;
                         }
@@ -744,11 +965,9 @@ impl Parser {
                 self.consume(Token::RightParen, "Expected ')' after function arguments")?;
                 
                 if let Expression::Identifier(name) = expr {
-                    expr = Expression::Call(FunctionCall { name, args });
+                    expr = Expression::Call(FunctionCall { name, args, named_args });
                 } else {
-                    return Err(ChifError::ParserError {
-                        message: "Invalid function call".to_string(),
-                    });
+                    return Err(self.error("Invalid function call".to_string()));
                 }
             } else if self.match_token(&Token::LeftBracket) {
                 // Index access
@@ -767,11 +986,21 @@ impl Parser {
                 });
             } else if self.match_token(&Token::Dot) {
                 // Field access or method call
-                let field_name = match self.advance() {
-                    Token::Identifier(name) => name,
-                    _ => return Err(ChifError::ParserError {
-                        message: "Expected field or method name after '.'".to_string(),
-                    }),
+                let field_name = match self.peek() {
+                    Token::Identifier(name) => {
+                        self.advance();
+                        name
+                    }
+                    // Positional tuple access: t.0
+                    Token::IntLiteral(index) => {
+                        self.advance();
+                        expr = Expression::FieldAccess(FieldAccess {
+                            object: Box::new(expr),
+                            field: index.to_string(),
+                        });
+                        continue;
+                    }
+                    _ => return Err(self.expected("field or method name after '.'")),
                 };
                 
                 if self.match_token(&Token::LeftParen) {
@@ -817,17 +1046,22 @@ impl Parser {
             Token::Identifier(name) => {
                 // Check if this is a struct literal: StructName { ... }
                 if self.check(&Token::LeftBrace) {
+                    let open = self.current;
                     self.advance(); // consume '{'
                     
                     let mut fields = Vec::new();
+                    let mut base = None;
                     if !self.check(&Token::RightBrace) {
                         loop {
-                            let field_name = match self.advance() {
-                                Token::Identifier(field) => field,
-                                _ => return Err(ChifError::ParserError {
-                                    message: "Expected field name in struct literal".to_string(),
-                                }),
-                            };
+                            // `..base` takes the remaining fields and closes the literal
+                            if self.check(&Token::Dot) && self.peek_next() == Token::Dot {
+                                self.advance(); // consume '.'
+                                self.advance(); // consume '.'
+                                base = Some(Box::new(self.parse_expression()?));
+                                break;
+                            }
+                            
+                            let field_name = self.identifier("field name in struct literal")?;
                             
                             self.consume(Token::Assign, "Expected '=' after field name")?;
                             let field_value = self.parse_expression()?;
@@ -843,18 +1077,40 @@ impl Parser {
                         }
                     }
                     
-                    self.consume(Token::RightBrace, "Expected '}' after struct fields")?;
+                    let message = if base.is_some() {
+                        "Expected '}' after the '..' base, which must come last in a struct literal"
+                    } else {
+                        "Expected '}' after struct fields"
+                    };
+                    self.consume_closing(open, Token::RightBrace, message)?;
                     
                     Ok(Expression::StructLiteral(StructLiteral {
                         struct_name: name,
                         fields,
+                        base,
                     }))
+                } else if name == DISCARD {
+                    Err(self.error(format!("cannot read from '{}': it only discards values", DISCARD)))
                 } else {
                     Ok(Expression::Identifier(name))
                 }
             }
             Token::LeftParen => {
                 let expr = self.parse_expression()?;
+                
+                // (a, b, ...) is a tuple literal
+                if self.match_token(&Token::Comma) {
+                    let mut elements = vec![expr];
+                    loop {
+                        elements.push(self.parse_expression()?);
+                        if !self.match_token(&Token::Comma) {
+                            break;
+                        }
+                    }
+                    self.consume(Token::RightParen, "Expected ')' after tuple elements")?;
+                    return Ok(Expression::TupleLiteral(elements));
+                }
+                
                 self.consume(Token::RightParen, "Expected ')' after expression")?;
                 Ok(expr)
             }
@@ -873,6 +1129,7 @@ impl Parser {
                 Ok(Expression::ArrayLiteral(elements))
             }
             Token::LeftBrace => {
+                let open = self.current - 1;
                 // Map literal or struct literal
                 if self.check(&Token::StringLiteral("".to_string())) || self.check(&Token::Identifier("".to_string())) {
                     // This is a heuristic - we'll need to improve this
@@ -888,17 +1145,13 @@ impl Parser {
                             }
                         }
                     }
-                    self.consume(Token::RightBrace, "Expected '}' after map elements")?;
+                    self.consume_closing(open, Token::RightBrace, "Expected '}' after map elements")?;
                     Ok(Expression::MapLiteral(pairs))
                 } else {
-                    return Err(ChifError::ParserError {
-                        message: "Unexpected '{'".to_string(),
-                    });
+                    Err(self.error("Unexpected '{'".to_string()))
                 }
             }
-            token => Err(ChifError::ParserError {
-                message: format!("Unexpected token: {:?}", token),
-            }),
+            token => Err(self.error_at_previous(format!("Expected expression, found {}", token.describe()))),
         }
     }
     
@@ -935,6 +1188,11 @@ impl Parser {
                 self.advance();
                 Some(BinaryOperator::GreaterEqual)
             }
+            // `in` is not a keyword: after an operand it can only be the operator
+            Token::Identifier(name) if name == "in" => {
+                self.advance();
+                Some(BinaryOperator::In)
+            }
             _ => None,
         }
     }
@@ -998,32 +1256,134 @@ impl Parser {
         std::mem::discriminant(&self.peek()) == std::mem::discriminant(token)
     }
     
+    /// Следующий токен; в конце ввода всегда `Eof`, без сдвига позиции
     fn advance(&mut self) -> Token {
-        if !self.is_at_end() {
-            self.current += 1;
+        if self.is_at_end() {
+            return Token::Eof;
         }
+        self.current += 1;
         self.previous()
     }
     
+    /// Consumes an identifier used as a contextual keyword, such as `mut` after `&`
+    fn match_contextual(&mut self, keyword: &str) -> bool {
+        if matches!(self.peek(), Token::Identifier(name) if name == keyword) {
+            self.advance();
+            true
+        } else {
+            false
+        }
+    }
+    
     fn is_at_end(&self) -> bool {
         matches!(self.peek(), Token::Eof)
     }
     
     fn peek(&self) -> Token {
-        self.tokens[self.current].clone()
+        self.tokens.get(self.current).cloned().unwrap_or(Token::Eof)
+    }
+    
+    fn peek_next(&self) -> Token {
+        self.tokens.get(self.current + 1).cloned().unwrap_or(Token::Eof)
     }
     
     fn previous(&self) -> Token {
         self.tokens[self.current - 1].clone()
     }
     
+    /// Разбор вложенной конструкции с проверкой `MAX_NESTING`
+    fn nested<T>(&mut self, parse: impl FnOnce(&mut Self) -> Result<T>) -> Result<T> {
+        if self.depth >= MAX_NESTING {
+            return Err(self.error(format!("Nesting is too deep (more than {} levels)", MAX_NESTING)));
+        }
+        self.depth += 1;
+        let result = parse(self);
+        self.depth -= 1;
+        result
+    }
+    
+    /// Ошибка разбора в позиции текущего токена; без позиций (`Parser::new`) строка и столбец равны 0
+    fn error(&self, message: String) -> ChifError {
+        let (line, column) = self.current_location()
+            .or_else(|| self.locations.last().copied())
+            .map_or((0, 0), |(line, col)| (line as usize, col as usize));
+        ChifError::ParserError { line, column, message }
+    }
+    
+    /// Ошибка в позиции токена с индексом `index`
+    fn error_at(&self, index: usize, message: String) -> ChifError {
+        match self.locations.get(index) {
+            Some(&(line, column)) => ChifError::ParserError { line: line as usize, column: column as usize, message },
+            None => self.error(message),
+        }
+    }
+    
+    /// Ошибка в позиции только что прочитанного токена
+    fn error_at_previous(&self, message: String) -> ChifError {
+        match self.current.checked_sub(1) {
+            Some(index) => self.error_at(index, message),
+            None => self.error(message),
+        }
+    }
+    
+    /// "Expected {what}, found {текущий токен}" в позиции текущего токена
+    fn expected(&self, what: &str) -> ChifError {
+        self.error(format!("Expected {}, found {}", what, self.peek().describe()))
+    }
+    
+    /// Имя на месте `what`; при ошибке позиция указывает на то, что найдено вместо него
+    fn identifier(&mut self, what: &str) -> Result<String> {
+        match self.peek() {
+            Token::Identifier(name) => {
+                self.advance();
+                Ok(name)
+            }
+            _ => Err(self.expected(what)),
+        }
+    }
+    
+    fn string_literal(&mut self, what: &str) -> Result<String> {
+        match self.peek() {
+            Token::StringLiteral(value) => {
+                self.advance();
+                Ok(value)
+            }
+            _ => Err(self.expected(what)),
+        }
+    }
+    
     fn consume(&mut self, token: Token, message: &str) -> Result<Token> {
         if std::mem::discriminant(&self.peek()) == std::mem::discriminant(&token) {
-            Ok(self.advance())
-        } else {
-            Err(ChifError::ParserError {
-                message: format!("{}, found {:?}", message, self.peek()),
-            })
+            return Ok(self.advance());
+        }
+        if token == Token::Semicolon {
+            if let Some(error) = self.missing_semicolon(message) {
+                return Err(error);
+            }
+        }
+        Err(self.error(format!("{}, found {}", message, self.peek().describe())))
+    }
+    
+    /// `;` забыта в конце строки: следующий токен уже на другой строке, и
+    /// ошибка указывает на последний токен инструкции, а не на начало следующей
+    fn missing_semicolon(&self, message: &str) -> Option<ChifError> {
+        let last = self.current.checked_sub(1)?;
+        let &(line, _) = self.locations.get(last)?;
+        let found = match self.current_location() {
+            Some((next_line, _)) if next_line == line => return None,
+            Some((next_line, _)) if !self.is_at_end() => format!("{} on line {}", self.peek().describe(), next_line),
+            _ => self.peek().describe(),
+        };
+        Some(self.error_at(last, format!("{}, found {}; add ';' at the end of line {}", message, found, line)))
+    }
+    
+    /// Закрывающая скобка к открывающей с индексом `open`. Если ввод кончился,
+    /// ошибка указывает на открывающую скобку: конец файла не говорит, какая не закрыта
+    fn consume_closing(&mut self, open: usize, token: Token, message: &str) -> Result<Token> {
+        if self.is_at_end() {
+            let opening = self.tokens.get(open).map_or_else(|| "bracket".to_string(), Token::describe);
+            return Err(self.error_at(open, format!("{}, found end of file; the {} opened here is never closed", message, opening)));
         }
+        self.consume(token, message)
     }
 }
\ No newline at end of file
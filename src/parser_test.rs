@@ -0,0 +1,363 @@
+#[cfg(test)]
+mod tests {
+    use crate::ast::*;
+    use crate::error::ChifError;
+    use crate::lexer::Lexer;
+    use crate::parser::Parser;
+    use crate::types::ChifType;
+
+    fn parse(source: &str) -> crate::error::Result<Program> {
+        let tokens = Lexer::new(source).tokenize().expect("source should tokenize");
+        Parser::new(tokens).parse()
+    }
+
+    /// The init clause of the first for loop in main
+    fn for_init(source: &str) -> Statement {
+        let program = parse(source).expect("source should parse");
+        let main = program.items.iter().find_map(|item| match item {
+            Item::Function(function) if function.is_main => Some(function),
+            _ => None,
+        }).expect("program should have main");
+        main.body.statements.iter().find_map(|statement| match statement {
+            Statement::For(for_stmt) => for_stmt.init.as_deref().cloned(),
+            _ => None,
+        }).expect("main should have a for loop with an init clause")
+    }
+
+    #[test]
+    fn test_for_init_infers_type_of_unannotated_var() {
+        let cases = [
+            ("var i = 0", ChifType::Int),
+            ("var i = -2 * 3", ChifType::Int),
+            ("var i = 0.5 + 1", ChifType::Float),
+            ("var i = 1 < 2", ChifType::Bool),
+            ("var i: float = 0", ChifType::Float),
+        ];
+        for (init, expected) in cases {
+            let source = format!("chif main() {{ for ({}; i < 3; i = i + 1) {{ }} }}", init);
+            match for_init(&source) {
+                Statement::VarDecl(var_decl) => {
+                    assert_eq!(var_decl.name, "i");
+                    assert_eq!(var_decl.var_type, expected, "{}", init);
+                    assert!(var_decl.is_mutable);
+                }
+                other => panic!("expected a declaration for '{}', got {:?}", init, other),
+            }
+        }
+    }
+
+    #[test]
+    fn test_for_init_without_inferable_type_is_rejected() {
+        let result = parse("fn start() int { ret 1; }\nchif main() { for (var i = start(); i < 3; i = i + 1) { } }");
+        match result {
+            Err(ChifError::ParserError { message, .. }) => {
+                assert!(message.contains("'i'") && message.contains("var i: <type>"), "{}", message)
+            }
+            other => panic!("expected a parser error, got {:?}", other.map(|_| ())),
+        }
+    }
+
+    #[test]
+    fn test_for_init_reusing_variable_is_an_assignment() {
+        let init = for_init("chif main() { var i: int = 5; for (i = 0; i < 3; i = i + 1) { } }");
+        match init {
+            Statement::Assignment(Assignment { target: Expression::Identifier(name), .. }) => assert_eq!(name, "i"),
+            other => panic!("expected an assignment, got {:?}", other),
+        }
+    }
+
+    fn spanned_parse(source: &str) -> crate::error::Result<Program> {
+        let tokens = Lexer::new(source).tokenize_spanned().expect("source should tokenize");
+        Parser::from_spanned(tokens).parse()
+    }
+
+    #[test]
+    fn test_parse_errors_report_positions() {
+        match spanned_parse("chif main() {\n    var x: int = 1;\n    ret\n}") {
+            Err(ChifError::ParserError { line, column, .. }) => assert_eq!((line, column), (4, 1)),
+            other => panic!("expected a parser error, got {:?}", other.map(|_| ())),
+        }
+        // Без позиций токенов строка и столбец неизвестны
+        match parse("chif main() { var x: int = 1 }") {
+            Err(ChifError::ParserError { line, column, .. }) => assert_eq!((line, column), (0, 0)),
+            other => panic!("expected a parser error, got {:?}", other.map(|_| ())),
+        }
+    }
+
+    #[test]
+    fn test_truncated_input_is_a_parse_error() {
+        for source in ["chif main() {\n    con.out(", "chif main() { var x: int = (", "chif main() { f(x: ", "fn f(a: list["] {
+            match spanned_parse(source) {
+                Err(ChifError::ParserError { message, .. }) => assert!(message.ends_with("found end of file"), "{}: {}", source, message),
+                other => panic!("expected a parser error for {:?}, got {:?}", source, other.map(|_| ())),
+            }
+        }
+    }
+
+    #[test]
+    fn test_errors_describe_the_found_token() {
+        let cases = [
+            ("import \"util\" }", (1, 15), "Expected ';' after import statement, found '}'"),
+            ("import util;", (1, 8), "Expected string literal after 'import', found identifier 'util'"),
+            ("export let x", (1, 8), "Expected 'fn' or 'struct' after 'export', found keyword 'let'"),
+            ("fn 42() { }", (1, 4), "Expected function name, found number 42"),
+            ("@inline fn f() { }", (1, 2), "Unknown attribute 'inline'"),
+            ("chif main() { var x: int = ; }", (1, 28), "Expected expression, found ';'"),
+            ("chif main() { for i of xs { } }", (1, 21), "Expected 'in' after loop variable 'i', found identifier 'of'"),
+            ("chif main() { p.\"x\"; }", (1, 17), "Expected field or method name after '.', found string literal"),
+        ];
+        for (source, expected_location, expected) in cases {
+            match spanned_parse(source) {
+                Err(ChifError::ParserError { line, column, message }) => {
+                    assert_eq!(message, expected, "{}", source);
+                    assert_eq!((line, column), expected_location, "{}: {}", source, message);
+                }
+                other => panic!("expected a parser error for {:?}, got {:?}", source, other.map(|_| ())),
+            }
+        }
+    }
+
+    #[test]
+    fn test_missing_semicolon_points_at_the_end_of_the_line() {
+        // Ошибка на последнем токене строки без ';', а не на начале следующей
+        match spanned_parse("chif main() {\n    var x: int = 1\n    var y: int = 2;\n}") {
+            Err(ChifError::ParserError { line, column, message }) => {
+                assert_eq!((line, column), (2, 18));
+                assert_eq!(message, "Expected ';' after variable declaration, found keyword 'var' on line 3; add ';' at the end of line 2");
+            }
+            other => panic!("expected a parser error, got {:?}", other.map(|_| ())),
+        }
+        // На той же строке подсказки нет
+        match spanned_parse("chif main() { var x: int = 1 var y: int = 2; }") {
+            Err(ChifError::ParserError { line, column, message }) => {
+                assert_eq!((line, column), (1, 30));
+                assert_eq!(message, "Expected ';' after variable declaration, found keyword 'var'");
+            }
+            other => panic!("expected a parser error, got {:?}", other.map(|_| ())),
+        }
+    }
+
+    #[test]
+    fn test_unclosed_brace_points_at_the_opening_brace() {
+        let cases = [
+            ("chif main() {\n    if (true) {\n        con.out(1);\n}\n", (1, 13), "Expected '}' at the end of the block, found end of file; the '{' opened here is never closed"),
+            ("struct P {\n    x: int,\n", (1, 10), "Expected '}' after struct fields, found end of file; the '{' opened here is never closed"),
+        ];
+        for (source, expected_location, expected) in cases {
+            match spanned_parse(source) {
+                Err(ChifError::ParserError { line, column, message }) => {
+                    assert_eq!(message, expected, "{}", source);
+                    assert_eq!((line, column), expected_location, "{}: {}", source, message);
+                }
+                other => panic!("expected a parser error for {:?}, got {:?}", source, other.map(|_| ())),
+            }
+        }
+    }
+
+    #[test]
+    fn test_nesting_limit() {
+        let nested = |depth: usize| format!("chif main() {{ var x: int = {}1{}; }}", "(".repeat(depth), ")".repeat(depth));
+        assert!(parse(&nested(40)).is_ok());
+        match parse(&nested(10_000)) {
+            Err(ChifError::ParserError { message, .. }) => assert!(message.contains("Nesting is too deep"), "{}", message),
+            other => panic!("expected a parser error, got {:?}", other.map(|_| ())),
+        }
+    }
+
+    /// Выражение со всеми скобками: так в тесте видно, что к чему применяется
+    fn grouped(expression: &Expression) -> String {
+        match expression {
+            Expression::Literal(crate::types::ChifValue::Int(value)) => value.to_string(),
+            Expression::Literal(crate::types::ChifValue::Bool(value)) => value.to_string(),
+            Expression::Identifier(name) => name.clone(),
+            Expression::Unary(unary) => {
+                let operator = match unary.operator {
+                    UnaryOperator::Minus => "-",
+                    UnaryOperator::Not => "!",
+                };
+                format!("{}({})", operator, grouped(&unary.operand))
+            }
+            Expression::Binary(binary) => format!("({} {:?} {})", grouped(&binary.left), binary.operator, grouped(&binary.right)),
+            Expression::Call(call) => format!("{}({})", call.name, call.args.iter().map(grouped).collect::<Vec<_>>().join(", ")),
+            Expression::MethodCall(call) => format!("{}.{}()", grouped(&call.object), call.method),
+            Expression::FieldAccess(access) => format!("{}.{}", grouped(&access.object), access.field),
+            Expression::Index(index) => format!("{}[{}]", grouped(&index.object), grouped(&index.indices[0])),
+            other => panic!("unexpected expression {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_postfix_binds_tighter_than_unary_and_unary_tighter_than_factor() {
+        let cases = [
+            ("-p.x", "-(p.x)"),
+            ("-f(2)", "-(f(2))"),
+            ("-arr[0]", "-(arr[0])"),
+            ("!flag.check()", "!(flag.check())"),
+            ("-2.abs()", "-(2.abs())"),
+            ("-p.q.x", "-(p.q.x)"),
+            ("-a * b", "(-(a) Multiply b)"),
+            ("a * -b", "(a Multiply -(b))"),
+            ("-a - -b", "(-(a) Subtract -(b))"),
+            ("!a == b", "(!(a) Equal b)"),
+            ("--a", "-(-(a))"),
+        ];
+        for (source, expected) in cases {
+            let program = parse(&format!("chif main() {{ _ = {}; }}", source)).expect("expression should parse");
+            let Some(Item::Function(main)) = program.items.first() else { panic!("main should be parsed") };
+            let Some(Statement::Assignment(assignment)) = main.body.statements.first() else {
+                panic!("expected a discard statement, got {:?}", main.body.statements)
+            };
+            assert_eq!(grouped(&assignment.value), expected, "{}", source);
+        }
+    }
+
+    #[test]
+    fn test_reference_parameters_and_arguments() {
+        let program = parse("fn f(a: &int, b: &mut int, c: pointer[int]) { } chif main() { var x: int = 1; f(&x, &mut x, &x); }")
+            .expect("references should parse");
+        let Some(Item::Function(f)) = program.items.first() else {
+            panic!("expected a function");
+        };
+        let params: Vec<_> = f.params.iter().map(|param| (param.param_type.clone(), param.is_reference)).collect();
+        let pointer = ChifType::Pointer(Box::new(ChifType::Int));
+        assert_eq!(params, vec![(pointer.clone(), false), (pointer.clone(), true), (pointer, false)]);
+        
+        let Some(Item::Function(main)) = program.items.last() else {
+            panic!("expected main");
+        };
+        let Some(Statement::Expression(Expression::Call(call))) = main.body.statements.last() else {
+            panic!("expected a call");
+        };
+        let mutable: Vec<_> = call.args.iter().map(|arg| match arg {
+            Expression::Reference(address_of) => address_of.mutable,
+            other => panic!("expected a reference, got {:?}", other),
+        }).collect();
+        assert_eq!(mutable, vec![false, true, false]);
+        // `mut` stays an ordinary name where it is not followed by one
+        assert!(parse("chif main() { var mut: int = 1; var p: pointer[int] = &mut; }").is_ok());
+    }
+
+    /// Type of the parameter `x` in `fn f(x: <type>)`
+    fn parameter_type(type_source: &str) -> crate::error::Result<ChifType> {
+        let program = spanned_parse(&format!("fn f(x: {}) {{ }}\nchif main() {{ }}", type_source))?;
+        match &program.items[0] {
+            Item::Function(function) => Ok(function.params[0].param_type.clone()),
+            other => panic!("expected a function, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_nested_type_syntax() {
+        let cases = [
+            ("int", "int"),
+            ("(int)", "int"),
+            ("((str))", "str"),
+            ("Point", "Point"),
+            ("pointer", "pointer[nil]"),
+            ("pointer[int]", "pointer[int]"),
+            ("pointer[Point]", "pointer[Point]"),
+            ("pointer[array[int]]", "pointer[array[int][0]]"),
+            ("pointer[pointer[int]]", "pointer[pointer[int]]"),
+            ("pointer[(int, int)]", "pointer[(int, int)]"),
+            ("array[int]", "array[int][0]"),
+            ("array int[3]", "array[int][3]"),
+            ("array int[3][4]", "array[int][3][4]"),
+            ("array[pointer[int]]", "array[pointer[int]][0]"),
+            ("array pointer[Point][5]", "array[pointer[Point]][5]"),
+            ("array list[int][3]", "array[list[int][]][3]"),
+            ("array[array[int]]", "array[array[int][0]][0]"),
+            ("list[int]", "list[int][]"),
+            ("list[int][][]", "list[int][][][]"),
+            ("list int[]", "list[int][]"),
+            ("list int[][]", "list[int][][]"),
+            ("list[map[str:int]]", "list[map[str:int]][]"),
+            ("list[pointer[Point]]", "list[pointer[Point]][]"),
+            ("list[(int, str)]", "list[(int, str)][]"),
+            ("map[str:int]", "map[str:int]"),
+            ("map[str:Point]", "map[str:Point]"),
+            ("map[int:list[array[int]]]", "map[int:list[array[int][0]][]]"),
+            ("map[str:map[str:float]]", "map[str:map[str:float]]"),
+            ("map[str:pointer[Point]]", "map[str:pointer[Point]]"),
+            ("(int, str)", "(int, str)"),
+            ("(int, (float, bool))", "(int, (float, bool))"),
+            ("((int), list[str])", "(int, list[str][])"),
+        ];
+        for (source, expected) in cases {
+            match parameter_type(source) {
+                Ok(parsed) => assert_eq!(parsed.to_string(), expected, "{}", source),
+                Err(error) => panic!("{}: {}", source, error),
+            }
+        }
+        
+        // The first size is the outer one: 3 rows of 4 elements
+        match parameter_type("array int[3][4]") {
+            Ok(ChifType::Array(element, dimensions)) => {
+                assert_eq!(*element, ChifType::Int);
+                assert_eq!(dimensions, vec![3, 4]);
+            }
+            other => panic!("expected an array type, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_malformed_types_report_position() {
+        // `fn f(x: ` takes 8 columns, the type starts at column 9
+        let cases = [
+            ("map[str]", 16, "Expected ':' and a value type in map type, as in map[str:int]"),
+            ("map[str:]", 17, "Expected type, found ']'"),
+            ("map str:int", 13, "Expected '[' after 'map'"),
+            ("array int[x]", 19, "Expected array size"),
+            ("array int[0]", 19, "Array size must be positive"),
+            ("(int,)", 14, "Tuple type must have at least 2 elements"),
+            ("pointer[int", 20, "Expected ']' after pointer type"),
+            ("list[int", 17, "Expected ']' after list type"),
+            ("(int, str]", 18, "Expected ')' after tuple type"),
+        ];
+        for (source, expected_column, expected) in cases {
+            match parameter_type(source) {
+                Err(ChifError::ParserError { line, column, message }) => {
+                    assert_eq!((line, column), (1, expected_column), "{}: {}", source, message);
+                    assert!(message.starts_with(expected), "{}: {}", source, message);
+                }
+                other => panic!("expected a parser error for {:?}, got {:?}", source, other),
+            }
+        }
+    }
+
+    #[test]
+    fn test_export_markers() {
+        let program = parse("export struct P { x: int, }\nstruct Q { y: int, }\nexport fn f() { }\n@deprecated(\"old\") export fn g() { }\nfn h() { }")
+            .expect("exports should parse");
+        let exported: Vec<(&str, bool)> = program.items.iter()
+            .map(|item| match item {
+                Item::Function(func) => (func.name.as_str(), func.is_exported),
+                Item::Struct(struct_def) => (struct_def.name.as_str(), struct_def.is_exported),
+                other => panic!("unexpected item {:?}", other),
+            })
+            .collect();
+        assert_eq!(exported, [("P", true), ("Q", false), ("f", true), ("g", true), ("h", false)]);
+        assert_eq!(program.exported_names(), Some(vec!["P".to_string(), "f".to_string(), "g".to_string()]));
+        assert_eq!(parse("fn h() { }").expect("source should parse").exported_names(), None);
+
+        for source in ["export fn_for P { }", "export chif main() { }", "export import \"utils\";"] {
+            let error = parse(source).expect_err("only fn and struct can be exported");
+            assert!(error.to_string().contains("after 'export'"), "{}: {}", source, error);
+        }
+    }
+
+    #[test]
+    fn test_global_variables() {
+        let program = parse("var calls: int = 0;\nlet names: list[str] = [\"a\"];\nlist queue: int[] = [];\nfn f() { }")
+            .expect("globals should parse");
+        let globals: Vec<(&str, bool)> = program.items.iter()
+            .filter_map(|item| match item {
+                Item::Global(var_decl) => Some((var_decl.name.as_str(), var_decl.is_mutable)),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(globals, [("calls", true), ("names", false), ("queue", false)]);
+
+        let error = parse("var (q, r) = divmod(7, 2);").expect_err("globals cannot be destructured");
+        assert!(error.to_string().contains("Global variables cannot be destructured"), "{}", error);
+    }
+}
@@ -0,0 +1,61 @@
+//! Прелюдия — модуль на Rono (`prelude.rono`), встроенный в крейт. Анализатор,
+//! интерпретатор и компилятор подключают его перед кодом программы, как
+//! импорт без префикса: `clamp(x, 0, 10)` вызывается без `import`. Функция или
+//! структура программы с тем же именем перекрывает прелюдию
+
+use crate::ast::{Item, Program};
+use std::collections::HashSet;
+
+/// Исходный текст прелюдии
+pub const SOURCE: &str = include_str!("prelude.rono");
+
+/// Разобранная прелюдия
+pub fn program() -> Program {
+    crate::load_module_source("prelude", SOURCE).expect("the prelude should parse")
+}
+
+/// Программа с прелюдией: перед элементами `program` идут функции прелюдии,
+/// которые она вызывает (прямо или через другие функции прелюдии) и не
+/// определяет сама. Второе значение — имена, которыми программа перекрыла прелюдию
+pub fn merge(program: &Program) -> (Program, Vec<String>) {
+    let defined: HashSet<&str> = program.items.iter()
+        .filter_map(|item| match item {
+            Item::Function(func) => Some(func.name.as_str()),
+            Item::Struct(struct_def) => Some(struct_def.name.as_str()),
+            _ => None,
+        })
+        .collect();
+    let mut shadowed = Vec::new();
+    let mut prelude_items = Vec::new();
+    for item in self::program().items {
+        match &item {
+            Item::Function(func) if defined.contains(func.name.as_str()) => shadowed.push(func.name.clone()),
+            Item::Struct(struct_def) if defined.contains(struct_def.name.as_str()) => shadowed.push(struct_def.name.clone()),
+            _ => prelude_items.push(item),
+        }
+    }
+
+    // Компилятор генерирует всё, что получил, поэтому остаются только функции,
+    // достижимые из функций и методов программы
+    let roots: Vec<String> = program.items.iter()
+        .filter_map(|item| match item {
+            Item::Function(func) => Some(func.name.clone()),
+            Item::StructImpl(impl_block) => Some(impl_block.struct_name.clone()),
+            _ => None,
+        })
+        .collect();
+    let prelude_names: HashSet<String> = prelude_items.iter()
+        .filter_map(|item| match item {
+            Item::Function(func) => Some(func.name.clone()),
+            _ => None,
+        })
+        .collect();
+    let mut merged = Program { items: prelude_items };
+    merged.items.extend(program.items.iter().cloned());
+    let reachable = merged.reachable(&roots).functions;
+    merged.items.retain(|item| match item {
+        Item::Function(func) => !prelude_names.contains(&func.name) || reachable.contains(&func.name),
+        _ => true,
+    });
+    (merged, shadowed)
+}
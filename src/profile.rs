@@ -0,0 +1,215 @@
+//! Профиль выполнения в интерпретаторе (`rono run --profile`): для каждой
+//! функции, метода и встроенной функции — число вызовов, полное и собственное
+//! время и счётчики копий коллекций и роста списков. Интерпретатор ведёт его,
+//! только если профиль включён (`Interpreter::enable_profile`)
+
+use crate::bench::format_duration;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::fmt;
+use std::time::{Duration, Instant};
+
+/// Чем была строка профиля
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CallKind {
+    /// Функция или метод программы, её модулей и прелюдии
+    Function,
+    /// `con.out`, `toInt`, `xs.len` и другие встроенные функции; их время
+    /// включает вычисление аргументов
+    Builtin,
+}
+
+impl CallKind {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            CallKind::Function => "function",
+            CallKind::Builtin => "builtin",
+        }
+    }
+}
+
+/// Одна строка профиля
+#[derive(Debug, Clone, PartialEq)]
+pub struct FunctionProfile {
+    /// Имя функции; метод — `Point.sum`, встроенная функция — `con.out`, `list.add`
+    pub name: String,
+    pub kind: CallKind,
+    pub calls: u64,
+    /// От входа до выхода, вместе с вложенными вызовами. Рекурсивный вызов
+    /// уже входит во время внешнего и второй раз не считается
+    pub total: Duration,
+    /// Время без вложенных вызовов из профиля
+    pub self_time: Duration,
+    /// Сколько раз список, массив, словарь или кортеж копировался: при чтении
+    /// переменной, передаче аргумента и изменении списка через `add`
+    pub collection_copies: u64,
+    /// Сколько раз `add` и `addAt` пришлось выделить списку память заново
+    pub list_growths: u64,
+}
+
+/// Выполняемый вызов: строка профиля, момент входа и время вложенных вызовов
+struct Frame {
+    index: usize,
+    started: Instant,
+    children: Duration,
+}
+
+/// Сбор профиля во время выполнения
+pub struct Profiler {
+    started: Instant,
+    functions: Vec<FunctionProfile>,
+    // Имена встроенных функций не совпадают с именами функций программы
+    by_name: HashMap<String, usize>,
+    // Сколько вызовов каждой строки сейчас на стеке: полное время
+    // добавляет только внешний из них
+    active: Vec<usize>,
+    stack: Vec<Frame>,
+}
+
+impl Profiler {
+    pub fn new() -> Self {
+        Self {
+            started: Instant::now(),
+            functions: Vec::new(),
+            by_name: HashMap::new(),
+            active: Vec::new(),
+            stack: Vec::new(),
+        }
+    }
+
+    fn row(&mut self, name: &str, kind: CallKind) -> usize {
+        if let Some(&index) = self.by_name.get(name) {
+            return index;
+        }
+        self.functions.push(FunctionProfile {
+            name: name.to_string(),
+            kind,
+            calls: 0,
+            total: Duration::ZERO,
+            self_time: Duration::ZERO,
+            collection_copies: 0,
+            list_growths: 0,
+        });
+        self.active.push(0);
+        self.by_name.insert(name.to_string(), self.functions.len() - 1);
+        self.functions.len() - 1
+    }
+
+    pub fn enter(&mut self, name: &str, kind: CallKind) {
+        let index = self.row(name, kind);
+        self.functions[index].calls += 1;
+        self.active[index] += 1;
+        self.stack.push(Frame { index, started: Instant::now(), children: Duration::ZERO });
+    }
+
+    /// Выход из последнего `enter`
+    pub fn exit(&mut self) {
+        let Some(frame) = self.stack.pop() else {
+            return;
+        };
+        let elapsed = frame.started.elapsed();
+        let function = &mut self.functions[frame.index];
+        function.self_time += elapsed.saturating_sub(frame.children);
+        self.active[frame.index] -= 1;
+        if self.active[frame.index] == 0 {
+            function.total += elapsed;
+        }
+        if let Some(parent) = self.stack.last_mut() {
+            parent.children += elapsed;
+        }
+    }
+
+    /// Строка, к которой относятся счётчики: выполняемый вызов, а вне
+    /// функций (инициализация глобальных переменных) — `(top level)`
+    fn current(&mut self) -> usize {
+        match self.stack.last() {
+            Some(frame) => frame.index,
+            None => self.row("(top level)", CallKind::Function),
+        }
+    }
+
+    pub fn collection_copied(&mut self) {
+        let index = self.current();
+        self.functions[index].collection_copies += 1;
+    }
+
+    pub fn list_grown(&mut self) {
+        let index = self.current();
+        self.functions[index].list_growths += 1;
+    }
+
+    /// Итог: вызовы, которые ещё не завершились (программа остановилась с
+    /// ошибкой), закрываются сейчас. Строки идут по убыванию собственного времени
+    pub fn finish(mut self) -> Profile {
+        while !self.stack.is_empty() {
+            self.exit();
+        }
+        let mut functions = self.functions;
+        functions.sort_by(|a, b| {
+            b.self_time.cmp(&a.self_time)
+                .then(b.calls.cmp(&a.calls))
+                .then_with(|| a.name.cmp(&b.name))
+        });
+        Profile { functions, elapsed: self.started.elapsed() }
+    }
+}
+
+impl Default for Profiler {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Профиль завершённого выполнения
+#[derive(Debug, Clone, PartialEq)]
+pub struct Profile {
+    /// По убыванию собственного времени
+    pub functions: Vec<FunctionProfile>,
+    /// С включения профиля до конца выполнения
+    pub elapsed: Duration,
+}
+
+impl Profile {
+    pub fn function(&self, name: &str) -> Option<&FunctionProfile> {
+        self.functions.iter().find(|function| function.name == name)
+    }
+
+    /// Время в JSON — в микросекундах
+    pub fn to_json(&self) -> Value {
+        json!({
+            "elapsed_us": self.elapsed.as_micros() as u64,
+            "functions": self.functions.iter().map(|function| json!({
+                "name": function.name,
+                "kind": function.kind.as_str(),
+                "calls": function.calls,
+                "total_us": function.total.as_micros() as u64,
+                "self_us": function.self_time.as_micros() as u64,
+                "collection_copies": function.collection_copies,
+                "list_growths": function.list_growths,
+            })).collect::<Vec<_>>(),
+        })
+    }
+}
+
+impl fmt::Display for Profile {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let width = self.functions.iter()
+            .map(|function| function.name.chars().count())
+            .chain(std::iter::once("function".len()))
+            .max()
+            .unwrap_or_default();
+        writeln!(
+            f, "{:<width$} {:>8} {:>10} {:>12} {:>12} {:>8} {:>8}",
+            "function", "kind", "calls", "self", "total", "copies", "growths", width = width
+        )?;
+        for function in &self.functions {
+            writeln!(
+                f, "{:<width$} {:>8} {:>10} {:>12} {:>12} {:>8} {:>8}",
+                function.name, function.kind.as_str(), function.calls, format_duration(function.self_time),
+                format_duration(function.total), function.collection_copies, function.list_growths, width = width
+            )?;
+        }
+        let calls: u64 = self.functions.iter().map(|function| function.calls).sum();
+        writeln!(f, "{} calls in {}", calls, format_duration(self.elapsed))
+    }
+}
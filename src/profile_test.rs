@@ -0,0 +1,57 @@
+#[cfg(test)]
+mod tests {
+    use crate::interpreter::Interpreter;
+    use crate::profile::{CallKind, Profile};
+
+    const HOT: &str = include_str!("../tests/profile/hot.rono");
+
+    fn profile(source: &str) -> Profile {
+        let program = crate::parse_source(source).expect("program should parse");
+        let mut interpreter = Interpreter::new();
+        interpreter.capture_output();
+        interpreter.enable_profile();
+        interpreter.execute(&program).expect("program should run");
+        interpreter.take_profile().expect("profile should be enabled")
+    }
+
+    #[test]
+    fn test_hot_function_is_ranked_first_with_exact_counts() {
+        let profile = profile(HOT);
+        let first = &profile.functions[0];
+        assert_eq!((first.name.as_str(), first.kind, first.calls), ("hot", CallKind::Function, 40), "{}", profile);
+
+        let calls = |name: &str| profile.function(name).map(|function| (function.kind, function.calls));
+        assert_eq!(calls("main"), Some((CallKind::Function, 1)));
+        assert_eq!(calls("cold"), Some((CallKind::Function, 1)));
+        assert_eq!(calls("Point.sum"), Some((CallKind::Function, 1)));
+        assert_eq!(calls("list.add"), Some((CallKind::Builtin, 40)));
+        assert_eq!(calls("con.out"), Some((CallKind::Builtin, 1)));
+
+        // main includes everything it called; its own time does not
+        let main = profile.function("main").unwrap();
+        assert!(main.total >= first.total && main.self_time < first.self_time, "{}", profile);
+        // Every `add` copies the list out of its variable, and the copy has no spare capacity
+        let add = profile.function("list.add").unwrap();
+        assert_eq!((add.collection_copies, add.list_growths), (40, 40), "{}", profile);
+        assert!(profile.to_string().lines().nth(1).unwrap().starts_with("hot "), "{}", profile);
+    }
+
+    #[test]
+    fn test_recursion_counts_every_call_and_the_outer_time_once() {
+        let profile = profile("fn fib(n: int) int {\n if (n < 2) { ret n; }\n ret fib(n - 1) + fib(n - 2);\n}\nchif main() { con.out(fib(15)); }");
+        let fib = profile.function("fib").unwrap();
+        assert_eq!(fib.calls, 1973);
+        let main = profile.function("main").unwrap();
+        assert!(fib.total <= main.total, "{}", profile);
+        assert!(fib.self_time <= fib.total, "{}", profile);
+    }
+
+    #[test]
+    fn test_profile_is_off_by_default() {
+        let program = crate::parse_source(HOT).expect("program should parse");
+        let mut interpreter = Interpreter::new();
+        interpreter.capture_output();
+        interpreter.execute(&program).expect("program should run");
+        assert!(interpreter.take_profile().is_none());
+    }
+}
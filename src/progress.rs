@@ -0,0 +1,135 @@
+//! Ход загрузки модулей и компиляции. События получает `ProgressSink`,
+//! подписанный в текущем потоке: загрузчик модулей (`load_module`) сообщает о
+//! каждом разбираемом файле, компилятор — о завершённых этапах и об итоге.
+//! CLI печатает их в stderr, библиотека передаёт подписчика через
+//! `Compiler::set_progress_sink`
+
+use crate::ast::{Item, Program};
+use std::cell::RefCell;
+use std::fmt;
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+/// Этап конвейера
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Stage {
+    Parse,
+    Analysis,
+    IrGeneration,
+    ObjectEmission,
+    Linking,
+}
+
+/// Итог компиляции
+#[derive(Debug, Clone, PartialEq)]
+pub struct CompileSummary {
+    /// Файлы программы: главный и импортированные
+    pub modules: usize,
+    /// Функции в объектном файле
+    pub functions: usize,
+    pub object_bytes: usize,
+    pub elapsed: Duration,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ProgressEvent {
+    /// Загрузчик начал разбирать модуль `file`; `total` — сколько модулей
+    /// ожидается, он растёт, если модули импортируют другие модули
+    ParsingModule { file: String, index: usize, total: usize },
+    StageFinished { stage: Stage, elapsed: Duration },
+    Finished(CompileSummary),
+}
+
+impl fmt::Display for ProgressEvent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ProgressEvent::ParsingModule { file, index, total } => write!(f, "Parsing {} ({}/{})", file, index, total),
+            ProgressEvent::StageFinished { stage, elapsed } => {
+                let stage = match stage {
+                    Stage::Parse => "Parsed",
+                    Stage::Analysis => "Analyzed",
+                    Stage::IrGeneration => "Generated IR",
+                    Stage::ObjectEmission => "Emitted object file",
+                    Stage::Linking => "Linked",
+                };
+                write!(f, "{} in {} ms", stage, elapsed.as_millis())
+            }
+            ProgressEvent::Finished(summary) => write!(
+                f,
+                "Compiled {} module{} ({} function{}, {} bytes of object code) in {} ms",
+                summary.modules,
+                if summary.modules == 1 { "" } else { "s" },
+                summary.functions,
+                if summary.functions == 1 { "" } else { "s" },
+                summary.object_bytes,
+                summary.elapsed.as_millis()
+            ),
+        }
+    }
+}
+
+/// Подписчик на события хода работы
+pub trait ProgressSink {
+    fn event(&mut self, event: &ProgressEvent);
+}
+
+struct Reporter {
+    sink: Box<dyn ProgressSink>,
+    parsed: usize,
+    expected: usize,
+}
+
+thread_local! {
+    static REPORTER: RefCell<Option<Reporter>> = const { RefCell::new(None) };
+}
+
+/// Подписывает `sink` на события этого потока (`None` — отписка) и
+/// возвращает прежнего подписчика
+pub fn set_sink(sink: Option<Box<dyn ProgressSink>>) -> Option<Box<dyn ProgressSink>> {
+    let reporter = sink.map(|sink| Reporter { sink, parsed: 0, expected: 0 });
+    REPORTER.with(|current| current.replace(reporter)).map(|previous| previous.sink)
+}
+
+/// Начало загрузки `program`: модули считаются заново, ожидаются её импорты
+pub fn start(program: &Program) {
+    let mut imports: Vec<&str> = program.items.iter()
+        .filter_map(|item| match item {
+            Item::Import(import) => Some(import.path.trim_end_matches(".rono")),
+            _ => None,
+        })
+        .collect();
+    imports.sort_unstable();
+    imports.dedup();
+    with_reporter(|reporter| {
+        reporter.parsed = 0;
+        reporter.expected = imports.len();
+    });
+}
+
+pub fn emit(event: ProgressEvent) {
+    with_reporter(|reporter| reporter.sink.event(&event));
+}
+
+/// Этап, начатый в `started`, завершён
+pub fn stage_finished(stage: Stage, started: Instant) {
+    emit(ProgressEvent::StageFinished { stage, elapsed: started.elapsed() });
+}
+
+/// Вызывается загрузчиком модулей перед разбором файла
+pub(crate) fn parsing_module(path: &Path) {
+    with_reporter(|reporter| {
+        reporter.parsed += 1;
+        reporter.expected = reporter.expected.max(reporter.parsed);
+        let file = path.file_name().map(|name| name.to_string_lossy().into_owned()).unwrap_or_default();
+        let event = ProgressEvent::ParsingModule { file, index: reporter.parsed, total: reporter.expected };
+        reporter.sink.event(&event);
+    });
+}
+
+fn with_reporter(report: impl FnOnce(&mut Reporter)) {
+    REPORTER.with(|current| {
+        if let Some(reporter) = current.borrow_mut().as_mut() {
+            report(reporter);
+        }
+    });
+}
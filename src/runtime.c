@@ -1,29 +1,253 @@
+#include <errno.h>
+#include <signal.h>
 #include <stdio.h>
 #include <stdint.h>
 #include <stdlib.h>
 #include <string.h>
 #include <time.h>
+#include <math.h>
+#ifndef RONO_NO_HTTP
 #include <curl/curl.h>
+#endif
+
+// Memory arena: every string or list buffer handed to compiled code is
+// allocated here and stays valid until mem.reset() frees them all at once
+typedef struct RonoAllocation {
+    struct RonoAllocation* next;
+    size_t size;
+} RonoAllocation;
+
+static RonoAllocation* rono_allocations = NULL;
+static int64_t rono_allocated_bytes = 0;
+
+static void* rono_alloc(size_t size) {
+    RonoAllocation* header = malloc(sizeof(RonoAllocation) + size);
+    if (header == NULL) {
+        return NULL;
+    }
+    header->next = rono_allocations;
+    header->size = size;
+    rono_allocations = header;
+    rono_allocated_bytes += (int64_t)size;
+    return header + 1;
+}
+
+// A struct returned from a function: the callee's stack frame is gone after
+// return, so compiled code copies the value here
+void* rono_alloc_struct(int64_t size) {
+    return rono_alloc((size_t)size);
+}
+
+// A large array literal that compiled code may write to: a fresh copy of its
+// read-only template for each evaluation
+void* rono_copy_data(const void* data, int64_t size) {
+    void* copy = rono_alloc((size_t)size);
+    if (copy != NULL) {
+        memcpy(copy, data, (size_t)size);
+    }
+    return copy;
+}
+
+// Frees every arena allocation; strings and lists obtained earlier become invalid
+void rono_mem_reset() {
+    while (rono_allocations != NULL) {
+        RonoAllocation* next = rono_allocations->next;
+        free(rono_allocations);
+        rono_allocations = next;
+    }
+    rono_allocated_bytes = 0;
+}
+
+// Bytes currently held by the arena
+int64_t rono_mem_used() {
+    return rono_allocated_bytes;
+}
+
+// Command-line arguments after the program name; main stores them before
+// running the program. The strings belong to the C runtime, not the arena
+static int64_t rono_arg_count = 0;
+static char** rono_arg_values = NULL;
+
+void rono_args_init(int32_t argc, char** argv) {
+    rono_arg_count = argc > 1 ? argc - 1 : 0;
+    rono_arg_values = argc > 1 ? argv + 1 : NULL;
+}
+
+int64_t rono_args_count() {
+    return rono_arg_count;
+}
+
+// args.get(i): "" outside 0..count
+const char* rono_args_get(int64_t index) {
+    if (index < 0 || index >= rono_arg_count) {
+        return "";
+    }
+    return rono_arg_values[index];
+}
+
+// Position of "--name" among the arguments, or -1
+static int64_t rono_args_flag_index(const char* name) {
+    for (int64_t i = 0; i < rono_arg_count; i++) {
+        const char* arg = rono_arg_values[i];
+        if (arg[0] == '-' && arg[1] == '-' && strcmp(arg + 2, name) == 0) {
+            return i;
+        }
+    }
+    return -1;
+}
+
+// args.has_flag(name): some argument is exactly "--name"
+int8_t rono_args_has_flag(const char* name) {
+    return rono_args_flag_index(name) >= 0;
+}
+
+// args.value_of(name): the argument after the first "--name", "" if there is none
+const char* rono_args_value_of(const char* name) {
+    int64_t flag = rono_args_flag_index(name);
+    return flag < 0 ? "" : rono_args_get(flag + 1);
+}
+
+// Exit code when the reader closes stdout (`./app | head`): what a shell shows
+// for a process killed by SIGPIPE, and what the interpreter exits with
+#define RONO_EXIT_OUTPUT_CLOSED 141
+
+static int rono_output_ready = 0;
+
+static void rono_check_output(int result) {
+    if (result < 0 && errno == EPIPE) {
+        _Exit(RONO_EXIT_OUTPUT_CLOSED);
+    }
+}
+
+// Output still buffered at exit is written here, so a reader that is gone by
+// then ends the program the same way
+static void rono_flush_output(void) {
+    rono_check_output(fflush(stdout));
+}
+
+// Every con.out line goes through here. SIGPIPE is ignored so a closed stdout
+// becomes a write error that ends the program quietly
+static void rono_print_line(const char* text) {
+    if (!rono_output_ready) {
+        rono_output_ready = 1;
+#ifdef SIGPIPE
+        signal(SIGPIPE, SIG_IGN);
+#endif
+        atexit(rono_flush_output);
+    }
+    rono_check_output(printf("%s\n", text));
+}
 
 // Runtime function for console output
 void rono_print_int(int64_t value) {
-    printf("%lld\n", (long long)value);
+    char text[32];
+    snprintf(text, sizeof(text), "%lld", (long long)value);
+    rono_print_line(text);
+}
+
+// Longest float text: sign, "0." and 323 zeros before the digits of the smallest subnormal
+#define RONO_FLOAT_TEXT_SIZE 352
+
+// Float text for con.out and toStr, as format_float in types.rs: the shortest
+// digits that read back as the same double, always with a '.' ("1.0", not "1"),
+// and an exponent only from 1e16 on ("1.0e16"). The runtime is C compiled
+// from this file at link time, so there is no Rust code in a compiled program
+// to call format_float from; the same algorithm is written here instead.
+// printf supplies only the digits and the exponent, strtod reads them back
+// without a separator, and the text is assembled here, so a locale with a
+// decimal comma, set by a C program that links the runtime as a library,
+// never reaches the output. The conformance tests compare the two under such
+// a locale
+static void rono_format_float(double value, char* out) {
+    if (isnan(value)) {
+        strcpy(out, "NaN");
+        return;
+    }
+    if (isinf(value)) {
+        strcpy(out, value < 0 ? "-inf" : "inf");
+        return;
+    }
+    char* p = out;
+    if (signbit(value)) {
+        *p++ = '-';
+        value = -value;
+    }
+    
+    // value = d.ddd × 10^exponent
+    char digits[24] = "0";
+    int count = 1;
+    int exponent = 0;
+    if (value != 0) {
+        for (int precision = 1; precision <= 17; precision++) {
+            char scientific[40];
+            snprintf(scientific, sizeof(scientific), "%.*e", precision - 1, value);
+            const char* c = scientific;
+            count = 0;
+            for (; *c != '\0' && *c != 'e'; c++) {
+                if (*c >= '0' && *c <= '9') {
+                    digits[count++] = *c;
+                }
+            }
+            exponent = atoi(c + 1);
+            // Read back without a separator: "12345e-4"
+            char check[48];
+            snprintf(check, sizeof(check), "%.*se%d", count, digits, exponent - (count - 1));
+            if (strtod(check, NULL) == value) {
+                break;
+            }
+        }
+        while (count > 1 && digits[count - 1] == '0') {
+            count--;
+        }
+    }
+    
+    if (exponent >= 16) {
+        *p++ = digits[0];
+        *p++ = '.';
+        if (count == 1) {
+            *p++ = '0';
+        }
+        for (int i = 1; i < count; i++) {
+            *p++ = digits[i];
+        }
+        sprintf(p, "e%d", exponent);
+    } else if (exponent >= 0) {
+        for (int i = 0; i <= exponent; i++) {
+            *p++ = i < count ? digits[i] : '0';
+        }
+        *p++ = '.';
+        if (count <= exponent + 1) {
+            *p++ = '0';
+        }
+        for (int i = exponent + 1; i < count; i++) {
+            *p++ = digits[i];
+        }
+        *p = '\0';
+    } else {
+        *p++ = '0';
+        *p++ = '.';
+        for (int i = -1; i > exponent; i--) {
+            *p++ = '0';
+        }
+        for (int i = 0; i < count; i++) {
+            *p++ = digits[i];
+        }
+        *p = '\0';
+    }
 }
 
 void rono_print_float(double value) {
-    printf("%f\n", value);
+    char text[RONO_FLOAT_TEXT_SIZE];
+    rono_format_float(value, text);
+    rono_print_line(text);
 }
 
 void rono_print_bool(int8_t value) {
-    printf("%s\n", value ? "true" : "false");
+    rono_print_line(value ? "true" : "false");
 }
 
 void rono_print_string(const char* str) {
-    if (str) {
-        printf("%s\n", str);
-    } else {
-        printf("(null)\n");
-    }
+    rono_print_line(str ? str : "(null)");
 }
 
 // String interpolation support
@@ -44,7 +268,7 @@ void rono_print_interpolated(const char* format, int64_t value) {
     }
     *dst = '\0';
     
-    printf("%s\n", result);
+    rono_print_line(result);
     free(result);
 }
 
@@ -52,69 +276,96 @@ void rono_print_interpolated(const char* format, int64_t value) {
 void rono_print_format_int(const char* format, int64_t value) {
     if (format == NULL) {
         // Default format for when we can't pass string constants yet
-        printf("%lld\n", (long long)value);
+        rono_print_int(value);
     } else {
         rono_print_interpolated(format, value);
     }
 }
 
 // Console input functions
-char* rono_input_string() {
-    char* buffer = malloc(1024); // Allocate buffer for input
+
+// Reads one line of any length and strips the line terminator: "\n" on POSIX,
+// "\r\n" on Windows. Any other whitespace is kept, use trim() for that.
+// Returns a malloc'd string the caller frees, or NULL at end of input.
+static char* rono_read_line(void) {
+    size_t capacity = 128;
+    size_t len = 0;
+    char* buffer = malloc(capacity);
     if (buffer == NULL) {
         return NULL;
     }
     
-    if (fgets(buffer, 1024, stdin) != NULL) {
-        // Remove trailing newline if present
-        size_t len = strlen(buffer);
+    // fgets stops at the end of the buffer; grow it until the line ends
+    while (fgets(buffer + len, (int)(capacity - len), stdin) != NULL) {
+        len += strlen(buffer + len);
         if (len > 0 && buffer[len - 1] == '\n') {
-            buffer[len - 1] = '\0';
+            break;
         }
-        return buffer;
-    } else {
+        if (len + 1 == capacity) {
+            char* grown = realloc(buffer, capacity * 2);
+            if (grown == NULL) {
+                free(buffer);
+                return NULL;
+            }
+            buffer = grown;
+            capacity *= 2;
+        }
+    }
+    
+    if (len == 0) {
         free(buffer);
         return NULL;
     }
+    if (buffer[len - 1] == '\n') {
+        buffer[--len] = '\0';
+        if (len > 0 && buffer[len - 1] == '\r') {
+            buffer[--len] = '\0';
+        }
+    }
+    return buffer;
+}
+
+// Returns an empty string at end of input
+char* rono_input_string() {
+    char* line = rono_read_line();
+    const char* text = line != NULL ? line : "";
+    
+    char* result = rono_alloc(strlen(text) + 1);
+    if (result != NULL) {
+        strcpy(result, text);
+    }
+    free(line);
+    return result;
 }
 
 int64_t rono_input_int() {
-    char* input = rono_input_string();
-    if (input == NULL) {
+    char* line = rono_read_line();
+    if (line == NULL) {
         return 0;
     }
-    
-    int64_t result = strtoll(input, NULL, 10);
-    free(input);
-    return result;
+    int64_t value = strtoll(line, NULL, 10);
+    free(line);
+    return value;
 }
 
 double rono_input_float() {
-    char* input = rono_input_string();
-    if (input == NULL) {
+    char* line = rono_read_line();
+    if (line == NULL) {
         return 0.0;
     }
-    
-    double result = strtod(input, NULL);
-    free(input);
-    return result;
+    double value = strtod(line, NULL);
+    free(line);
+    return value;
 }
 
 int8_t rono_input_bool() {
-    char* input = rono_input_string();
-    if (input == NULL) {
+    char* line = rono_read_line();
+    if (line == NULL) {
         return 0;
     }
-    
-    int8_t result = 0;
-    if (strcmp(input, "true") == 0 || strcmp(input, "1") == 0) {
-        result = 1;
-    } else if (strcmp(input, "false") == 0 || strcmp(input, "0") == 0) {
-        result = 0;
-    }
-    
-    free(input);
-    return result;
+    int8_t value = strcmp(line, "true") == 0 || strcmp(line, "1") == 0;
+    free(line);
+    return value;
 }
 
 // Random number generation functions
@@ -165,16 +416,28 @@ double rono_rand_float(double min, double max) {
     return min + ((double)rand() / RAND_MAX) * range;
 }
 
+// Random float in range [0.0, 1.0)
+double rono_rand_unit() {
+    rono_rand_init();
+    return (double)rand() / ((double)RAND_MAX + 1.0);
+}
+
+// Seed the generator for reproducible sequences
+void rono_rand_seed(int64_t seed) {
+    srand((unsigned int)seed);
+    rono_rand_initialized = 1;
+}
+
 char* rono_rand_string(int64_t length) {
     rono_rand_init();
     
     if (length <= 0) {
-        char* empty = malloc(1);
+        char* empty = rono_alloc(1);
         if (empty) empty[0] = '\0';
         return empty;
     }
     
-    char* result = malloc(length + 1);
+    char* result = rono_alloc(length + 1);
     if (!result) {
         return NULL;
     }
@@ -195,7 +458,7 @@ char* rono_rand_char_range(const char* from, const char* to) {
     rono_rand_init();
     
     if (!from || !to || strlen(from) == 0 || strlen(to) == 0) {
-        char* result = malloc(2);
+        char* result = rono_alloc(2);
         if (result) {
             result[0] = 'a';
             result[1] = '\0';
@@ -213,7 +476,7 @@ char* rono_rand_char_range(const char* from, const char* to) {
         to_char = temp;
     }
     
-    char* result = malloc(2);
+    char* result = rono_alloc(2);
     if (!result) {
         return NULL;
     }
@@ -226,12 +489,331 @@ char* rono_rand_char_range(const char* from, const char* to) {
     return result;
 }
 
-// HTTP response structure
+// List support functions
+static int rono_list_elem_equal(int64_t left, int64_t right, int64_t elem_type) {
+    if (elem_type == 1) {
+        double l, r;
+        memcpy(&l, &left, sizeof(double));
+        memcpy(&r, &right, sizeof(double));
+        return l == r;
+    }
+    if (elem_type == 2) {
+        const char* l = (const char*)left;
+        const char* r = (const char*)right;
+        if (l == r || !l || !r) {
+            return l == r;
+        }
+        return strcmp(l, r) == 0;
+    }
+    return left == right;
+}
+
+// Returns a new buffer with duplicates removed, keeping first occurrences.
+// The number of elements kept is stored in the 8 bytes just before the
+// returned pointer, where rono_list_len reads it.
+// elem_type: 0 = int, 1 = float, 2 = string
+int64_t rono_list_unique(int64_t ptr, int64_t len, int64_t elem_type) {
+    int64_t* items = (int64_t*)ptr;
+    int64_t* header = rono_alloc(sizeof(int64_t) * (len + 1));
+    if (!header) {
+        return 0;
+    }
+    int64_t* result = header + 1;
+    
+    int64_t count = 0;
+    for (int64_t i = 0; i < len; i++) {
+        int seen = 0;
+        for (int64_t j = 0; j < count; j++) {
+            if (rono_list_elem_equal(items[i], result[j], elem_type)) {
+                seen = 1;
+                break;
+            }
+        }
+        if (!seen) {
+            result[count++] = items[i];
+        }
+    }
+    header[0] = count;
+    
+    return (int64_t)result;
+}
+
+// Length of a list built by the runtime (unique()): the word before the first element
+int64_t rono_list_len(int64_t ptr) {
+    return ptr ? ((int64_t*)ptr)[-1] : 0;
+}
+
+// `value in list`: 1 if one of the `len` elements equals `value`
+int8_t rono_list_contains(int64_t ptr, int64_t len, int64_t value, int64_t elem_type) {
+    int64_t* items = (int64_t*)ptr;
+    for (int64_t i = 0; i < len; i++) {
+        if (rono_list_elem_equal(items[i], value, elem_type)) {
+            return 1;
+        }
+    }
+    return 0;
+}
+
+// String support functions
+// `part in s`: 1 if `part` occurs in `s`
+int8_t rono_str_contains(const char* s, const char* part) {
+    if (!s || !part) {
+        return 0;
+    }
+    return strstr(s, part) != NULL;
+}
+
+// Number of UTF-8 characters (continuation bytes 10xxxxxx are not counted)
+int64_t rono_str_char_count(const char* s) {
+    if (!s) {
+        return 0;
+    }
+    int64_t count = 0;
+    for (const unsigned char* p = (const unsigned char*)s; *p; p++) {
+        if ((*p & 0xC0) != 0x80) {
+            count++;
+        }
+    }
+    return count;
+}
+
+int64_t rono_str_byte_len(const char* s) {
+    return s ? (int64_t)strlen(s) : 0;
+}
+
+// Character (not byte) position of the first occurrence of sub, or -1
+int64_t rono_str_index_of(const char* s, const char* sub) {
+    if (!s || !sub) {
+        return -1;
+    }
+    const char* found = strstr(s, sub);
+    if (!found) {
+        return -1;
+    }
+    int64_t index = 0;
+    for (const unsigned char* p = (const unsigned char*)s; p < (const unsigned char*)found; p++) {
+        if ((*p & 0xC0) != 0x80) {
+            index++;
+        }
+    }
+    return index;
+}
+
+static int rono_is_trim_space(char c) {
+    return c == ' ' || c == '\t' || c == '\n' || c == '\r' || c == '\f';
+}
+
+// Copy of s without leading and trailing ASCII whitespace
+char* rono_str_trim(const char* s) {
+    if (!s) {
+        s = "";
+    }
+    const char* start = s;
+    while (*start && rono_is_trim_space(*start)) {
+        start++;
+    }
+    const char* end = start + strlen(start);
+    while (end > start && rono_is_trim_space(end[-1])) {
+        end--;
+    }
+    
+    size_t len = (size_t)(end - start);
+    char* result = rono_alloc(len + 1);
+    if (result == NULL) {
+        return NULL;
+    }
+    memcpy(result, start, len);
+    result[len] = '\0';
+    return result;
+}
+
+// a + b for strings; the result lives in the arena like other runtime strings
+char* rono_string_concat(const char* a, const char* b) {
+    if (a == NULL) {
+        a = "";
+    }
+    if (b == NULL) {
+        b = "";
+    }
+    size_t left = strlen(a);
+    size_t right = strlen(b);
+    char* result = rono_alloc(left + right + 1);
+    if (result == NULL) {
+        return NULL;
+    }
+    memcpy(result, a, left);
+    memcpy(result + left, b, right);
+    result[left + right] = '\0';
+    return result;
+}
+
+// for-in over range(start, end, 0) is an error, as in the interpreter
+void rono_range_check_step(int64_t step) {
+    if (step == 0) {
+        fprintf(stderr, "Runtime error: range() step cannot be zero\n");
+        exit(1);
+    }
+}
+
+// Compares string contents; used by == on str and switch over str.
+// Interned strings and repeated literals are the same pointer, so that is checked first
+int8_t rono_str_equal(const char* a, const char* b) {
+    if (a == b || a == NULL || b == NULL) {
+        return a == b;
+    }
+    return strcmp(a, b) == 0;
+}
+
+// Intern table: open addressing over FNV-1a hashes. Its strings are allocated
+// outside the arena, so they outlive mem.reset() like string literals do
+static const char** rono_interned = NULL;
+static size_t rono_interned_capacity = 0;
+static size_t rono_interned_count = 0;
+
+static uint64_t rono_str_hash(const char* s) {
+    uint64_t hash = 14695981039346656037ULL;
+    for (; *s; s++) {
+        hash = (hash ^ (unsigned char)*s) * 1099511628211ULL;
+    }
+    return hash;
+}
+
+static size_t rono_intern_slot(const char** table, size_t capacity, const char* s) {
+    size_t slot = (size_t)rono_str_hash(s) & (capacity - 1);
+    while (table[slot] != NULL && strcmp(table[slot], s) != 0) {
+        slot = (slot + 1) & (capacity - 1);
+    }
+    return slot;
+}
+
+static int rono_intern_grow() {
+    size_t capacity = rono_interned_capacity ? rono_interned_capacity * 2 : 64;
+    const char** table = calloc(capacity, sizeof(const char*));
+    if (table == NULL) {
+        return 0;
+    }
+    for (size_t i = 0; i < rono_interned_capacity; i++) {
+        if (rono_interned[i] != NULL) {
+            table[rono_intern_slot(table, capacity, rono_interned[i])] = rono_interned[i];
+        }
+    }
+    free(rono_interned);
+    rono_interned = table;
+    rono_interned_capacity = capacity;
+    return 1;
+}
+
+// intern(s): the canonical copy of s, so equal interned strings share one pointer
+const char* rono_str_intern(const char* s) {
+    if (s == NULL) {
+        return NULL;
+    }
+    if ((rono_interned_count + 1) * 2 > rono_interned_capacity && !rono_intern_grow()) {
+        return s;
+    }
+    size_t slot = rono_intern_slot(rono_interned, rono_interned_capacity, s);
+    if (rono_interned[slot] == NULL) {
+        char* copy = malloc(strlen(s) + 1);
+        if (copy == NULL) {
+            return s;
+        }
+        strcpy(copy, s);
+        rono_interned[slot] = copy;
+        rono_interned_count++;
+    }
+    return rono_interned[slot];
+}
+
+// Formats n in the given base without prefix; negative numbers get a leading '-'
+static char* rono_int_to_radix(int64_t n, unsigned base, const char* digits) {
+    char buffer[66]; // sign + 64 binary digits + terminator
+    char* p = buffer + sizeof(buffer) - 1;
+    *p = '\0';
+    uint64_t magnitude = n < 0 ? (uint64_t)0 - (uint64_t)n : (uint64_t)n;
+    do {
+        *--p = digits[magnitude % base];
+        magnitude /= base;
+    } while (magnitude > 0);
+    if (n < 0) {
+        *--p = '-';
+    }
+    size_t length = (size_t)(buffer + sizeof(buffer) - 1 - p);
+    char* result = rono_alloc(length + 1);
+    if (result) {
+        memcpy(result, p, length + 1);
+    }
+    return result;
+}
+
+char* rono_int_to_hex(int64_t n) {
+    return rono_int_to_radix(n, 16, "0123456789ABCDEF");
+}
+
+char* rono_int_to_hex_lower(int64_t n) {
+    return rono_int_to_radix(n, 16, "0123456789abcdef");
+}
+
+char* rono_int_to_binary(int64_t n) {
+    return rono_int_to_radix(n, 2, "01");
+}
+
+// toStr(x) in compiled code: the same text con.out prints, without the newline
+char* rono_int_to_str(int64_t n) {
+    return rono_int_to_radix(n, 10, "0123456789");
+}
+
+char* rono_float_to_str(double value) {
+    char text[RONO_FLOAT_TEXT_SIZE];
+    rono_format_float(value, text);
+    char* result = rono_alloc(strlen(text) + 1);
+    if (result) {
+        strcpy(result, text);
+    }
+    return result;
+}
+
+const char* rono_bool_to_str(int8_t value) {
+    return value ? "true" : "false";
+}
+
+// Integer division truncating toward zero (Rono's `/` on ints), as in the interpreter:
+// a zero divisor and INT64_MIN / -1 are runtime errors rather than a hardware trap
+int64_t rono_int_div(int64_t a, int64_t b) {
+    if (b == 0) {
+        fprintf(stderr, "Runtime error: Division by zero\n");
+        exit(1);
+    }
+    if (b == -1 && a == INT64_MIN) {
+        fprintf(stderr, "Runtime error: Integer overflow in %lld / -1\n", (long long)a);
+        exit(1);
+    }
+    return a / b;
+}
+
+// Float remainder with the sign of the dividend (Rono's `%` on floats)
+double rono_fmod(double a, double b) {
+    return fmod(a, b);
+}
+
+#ifndef RONO_NO_HTTP
+// Body of a response being received
 typedef struct {
     char* data;
     size_t size;
 } HttpResponse;
 
+// The Rono struct HttpResponse as compiled code lays it out (semantic::http_response_struct):
+// status @0, body @8, content_type @16, ok @24, error @32
+typedef struct {
+    int64_t status;
+    char* body;
+    char* content_type;
+    int8_t ok;
+    char* error;
+} RonoHttpResponse;
+
+enum { RONO_HTTP_GET = 0, RONO_HTTP_POST = 1, RONO_HTTP_PUT = 2, RONO_HTTP_DELETE = 3 };
+
 // Callback function for writing HTTP response data
 static size_t WriteCallback(void* contents, size_t size, size_t nmemb, HttpResponse* response) {
     size_t realsize = size * nmemb;
@@ -250,6 +832,72 @@ static size_t WriteCallback(void* contents, size_t size, size_t nmemb, HttpRespo
     return realsize;
 }
 
+// Moves a response body grown with realloc into the arena
+static char* rono_adopt_response(char* data) {
+    if (data == NULL) {
+        return NULL;
+    }
+    size_t size = strlen(data) + 1;
+    char* result = rono_alloc(size);
+    if (result) {
+        memcpy(result, data, size);
+    }
+    free(data);
+    return result;
+}
+
+// Arena copy of a C string; NULL becomes ""
+static char* rono_http_copy(const char* text) {
+    if (text == NULL) {
+        text = "";
+    }
+    size_t size = strlen(text) + 1;
+    char* result = rono_alloc(size);
+    if (result) {
+        memcpy(result, text, size);
+    }
+    return result;
+}
+
+// Same checks and messages as the interpreter: http or https scheme, non-empty host.
+// An invalid URL is a runtime error before any network I/O
+static void rono_http_validate_url(const char* url) {
+    const char* problem = NULL;
+    const char* rest = NULL;
+    if (url != NULL) {
+        const char* schemes[] = {"http://", "https://"};
+        for (int i = 0; i < 2 && rest == NULL; i++) {
+            size_t length = strlen(schemes[i]);
+            size_t matched = 0;
+            while (matched < length && url[matched] != '\0'
+                   && (url[matched] | 0x20) == (schemes[i][matched] | 0x20)) {
+                matched++;
+            }
+            if (matched == length) {
+                rest = url + length;
+            }
+        }
+    }
+    if (rest == NULL) {
+        problem = "scheme must be http or https";
+    } else {
+        const char* host_end = rest + strcspn(rest, "/?#");
+        const char* host = rest;
+        for (const char* p = rest; p < host_end; p++) {
+            if (*p == '@') {
+                host = p + 1;
+            }
+        }
+        if (host == host_end || *host == ':') {
+            problem = "host is empty";
+        }
+    }
+    if (problem != NULL) {
+        fprintf(stderr, "Runtime error: Invalid URL '%s': %s\n", url ? url : "", problem);
+        exit(1);
+    }
+}
+
 // Initialize curl (called once)
 static int curl_initialized = 0;
 
@@ -260,126 +908,86 @@ void rono_http_init() {
     }
 }
 
-// HTTP GET function
-char* rono_http_get(const char* url) {
+// Performs one request. A transport failure (DNS, refused connection, timeout) gives
+// ok = 0, status 0 and the curl message in error; any HTTP status, 500 included, is ok
+static RonoHttpResponse* rono_http_perform(int64_t method, const char* url, const char* data) {
+    rono_http_validate_url(url);
     rono_http_init();
     
-    CURL* curl;
-    CURLcode res;
+    RonoHttpResponse* result = rono_alloc(sizeof(RonoHttpResponse));
     HttpResponse response = {0};
+    char error[CURL_ERROR_SIZE] = "";
+    const char* content_type = NULL;
+    long status = 0;
     
-    curl = curl_easy_init();
+    CURL* curl = curl_easy_init();
+    CURLcode res = CURLE_FAILED_INIT;
     if (curl) {
         curl_easy_setopt(curl, CURLOPT_URL, url);
+        if (method == RONO_HTTP_PUT) {
+            curl_easy_setopt(curl, CURLOPT_CUSTOMREQUEST, "PUT");
+        } else if (method == RONO_HTTP_DELETE) {
+            curl_easy_setopt(curl, CURLOPT_CUSTOMREQUEST, "DELETE");
+        }
+        if (data != NULL) {
+            curl_easy_setopt(curl, CURLOPT_POSTFIELDS, data);
+        }
         curl_easy_setopt(curl, CURLOPT_WRITEFUNCTION, WriteCallback);
         curl_easy_setopt(curl, CURLOPT_WRITEDATA, &response);
+        curl_easy_setopt(curl, CURLOPT_ERRORBUFFER, error);
         curl_easy_setopt(curl, CURLOPT_USERAGENT, "Rono-HTTP/1.0");
         curl_easy_setopt(curl, CURLOPT_TIMEOUT, 30L); // 30 second timeout
         
         res = curl_easy_perform(curl);
-        curl_easy_cleanup(curl);
-        
-        if (res != CURLE_OK) {
-            if (response.data) {
-                free(response.data);
-            }
-            return NULL;
+        if (res == CURLE_OK) {
+            curl_easy_getinfo(curl, CURLINFO_RESPONSE_CODE, &status);
+            curl_easy_getinfo(curl, CURLINFO_CONTENT_TYPE, &content_type);
         }
+        result->content_type = rono_http_copy(res == CURLE_OK ? content_type : NULL);
+        curl_easy_cleanup(curl);
+    } else {
+        result->content_type = rono_http_copy(NULL);
     }
     
-    return response.data; // Caller must free this
+    if (res == CURLE_OK) {
+        result->status = status;
+        result->body = response.data ? rono_adopt_response(response.data) : rono_http_copy(NULL);
+        result->ok = 1;
+        result->error = rono_http_copy(NULL);
+    } else {
+        free(response.data);
+        result->status = 0;
+        result->body = rono_http_copy(NULL);
+        result->ok = 0;
+        result->error = rono_http_copy(error[0] ? error : curl_easy_strerror(res));
+    }
+    return result;
+}
+
+// http_get(url), http_post(url, body), ... -> HttpResponse
+RonoHttpResponse* rono_http_request(int64_t method, const char* url, const char* data) {
+    return rono_http_perform(method, url, data);
+}
+
+// http.get(url) and friends return only the body, NULL if the request failed
+static char* rono_http_body(int64_t method, const char* url, const char* data) {
+    RonoHttpResponse* response = rono_http_perform(method, url, data);
+    return response->ok ? response->body : NULL;
+}
+
+char* rono_http_get(const char* url) {
+    return rono_http_body(RONO_HTTP_GET, url, NULL);
 }
 
-// HTTP POST function
 char* rono_http_post(const char* url, const char* data) {
-    rono_http_init();
-    
-    CURL* curl;
-    CURLcode res;
-    HttpResponse response = {0};
-    
-    curl = curl_easy_init();
-    if (curl) {
-        curl_easy_setopt(curl, CURLOPT_URL, url);
-        curl_easy_setopt(curl, CURLOPT_POSTFIELDS, data);
-        curl_easy_setopt(curl, CURLOPT_WRITEFUNCTION, WriteCallback);
-        curl_easy_setopt(curl, CURLOPT_WRITEDATA, &response);
-        curl_easy_setopt(curl, CURLOPT_USERAGENT, "Rono-HTTP/1.0");
-        curl_easy_setopt(curl, CURLOPT_TIMEOUT, 30L);
-        
-        res = curl_easy_perform(curl);
-        curl_easy_cleanup(curl);
-        
-        if (res != CURLE_OK) {
-            if (response.data) {
-                free(response.data);
-            }
-            return NULL;
-        }
-    }
-    
-    return response.data; // Caller must free this
+    return rono_http_body(RONO_HTTP_POST, url, data);
 }
 
-// HTTP PUT function
 char* rono_http_put(const char* url, const char* data) {
-    rono_http_init();
-    
-    CURL* curl;
-    CURLcode res;
-    HttpResponse response = {0};
-    
-    curl = curl_easy_init();
-    if (curl) {
-        curl_easy_setopt(curl, CURLOPT_URL, url);
-        curl_easy_setopt(curl, CURLOPT_CUSTOMREQUEST, "PUT");
-        curl_easy_setopt(curl, CURLOPT_POSTFIELDS, data);
-        curl_easy_setopt(curl, CURLOPT_WRITEFUNCTION, WriteCallback);
-        curl_easy_setopt(curl, CURLOPT_WRITEDATA, &response);
-        curl_easy_setopt(curl, CURLOPT_USERAGENT, "Rono-HTTP/1.0");
-        curl_easy_setopt(curl, CURLOPT_TIMEOUT, 30L);
-        
-        res = curl_easy_perform(curl);
-        curl_easy_cleanup(curl);
-        
-        if (res != CURLE_OK) {
-            if (response.data) {
-                free(response.data);
-            }
-            return NULL;
-        }
-    }
-    
-    return response.data; // Caller must free this
+    return rono_http_body(RONO_HTTP_PUT, url, data);
 }
 
-// HTTP DELETE function
 char* rono_http_delete(const char* url) {
-    rono_http_init();
-    
-    CURL* curl;
-    CURLcode res;
-    HttpResponse response = {0};
-    
-    curl = curl_easy_init();
-    if (curl) {
-        curl_easy_setopt(curl, CURLOPT_URL, url);
-        curl_easy_setopt(curl, CURLOPT_CUSTOMREQUEST, "DELETE");
-        curl_easy_setopt(curl, CURLOPT_WRITEFUNCTION, WriteCallback);
-        curl_easy_setopt(curl, CURLOPT_WRITEDATA, &response);
-        curl_easy_setopt(curl, CURLOPT_USERAGENT, "Rono-HTTP/1.0");
-        curl_easy_setopt(curl, CURLOPT_TIMEOUT, 30L);
-        
-        res = curl_easy_perform(curl);
-        curl_easy_cleanup(curl);
-        
-        if (res != CURLE_OK) {
-            if (response.data) {
-                free(response.data);
-            }
-            return NULL;
-        }
-    }
-    
-    return response.data; // Caller must free this
-}
\ No newline at end of file
+    return rono_http_body(RONO_HTTP_DELETE, url, NULL);
+}
+#endif // RONO_NO_HTTP
@@ -1,23 +1,116 @@
+use crate::interner::{Interner, SymbolId};
 use crate::ast::*;
 use crate::types::{ChifType, ChifValue};
-use crate::compiler::SourceLocation;
-use std::collections::HashMap;
-use std::fs;
+use crate::error::SourceLocation;
+use crate::diagnostics::Code;
+use crate::lang::{self, LangVersion};
+use crate::prelude;
+use std::borrow::Cow;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
 use thiserror::Error;
 
+/// Имена встроенных объектов (включая зарезервированные на будущее).
+/// Их нельзя переобъявлять как переменные, параметры или функции.
+pub const BUILTIN_OBJECT_NAMES: &[&str] = &["con", "http", "mem", "args", "os", "rono", "fs", "env", "time", "math"];
+
+/// Встроенные объекты, появившиеся позже: программы уже называли так переменные
+/// и параметры, поэтому локальное объявление перекрывает объект, а не запрещено
+pub const SHADOWABLE_OBJECT_NAMES: &[&str] = &["args", "os", "rono"];
+
+/// Имена типов встроенных объектов, которые нельзя использовать для структур.
+pub const BUILTIN_OBJECT_TYPES: &[&str] = &["Console", "Http", "Memory", "ProgramArgs", "Os", "RonoInfo", "HttpResponse"];
+
+/// Ответ `http_get`/`http_post`/`http_put`/`http_delete`. Порядок полей задаёт и
+/// раскладку в скомпилированной программе (её повторяет `RonoHttpResponse` в runtime.c).
+/// `ok` ложно, только если запрос не дошёл до сервера: тогда `status` равен 0,
+/// а `error` описывает сбой. Ответ 500 — это `ok` и `status` 500
+pub fn http_response_struct() -> StructDef {
+    let field = |name: &str, field_type: ChifType| StructField {
+        name: name.to_string(),
+        field_type,
+        is_private: false,
+    };
+    StructDef {
+        name: "HttpResponse".to_string(),
+        fields: vec![
+            field("status", ChifType::Int),
+            field("body", ChifType::Str),
+            field("content_type", ChifType::Str),
+            field("ok", ChifType::Bool),
+            field("error", ChifType::Str),
+        ],
+        is_exported: false,
+    }
+}
+
+pub fn is_builtin_object_name(name: &str) -> bool {
+    BUILTIN_OBJECT_NAMES.contains(&name)
+}
+
+pub fn is_shadowable_object_name(name: &str) -> bool {
+    SHADOWABLE_OBJECT_NAMES.contains(&name)
+}
+
+pub fn is_builtin_object_type(name: &str) -> bool {
+    BUILTIN_OBJECT_TYPES.contains(&name)
+}
+
+/// Ближайшее по расстоянию Левенштейна имя из `candidates`, если опечатка
+/// правдоподобна: не больше трети длины имени, но хотя бы одна правка
+pub fn suggest_name<'a>(name: &str, candidates: impl Iterator<Item = &'a str>) -> Option<&'a str> {
+    let max_distance = (name.chars().count() / 3).max(1);
+    candidates
+        .map(|candidate| (edit_distance(name, candidate), candidate))
+        .filter(|(distance, _)| *distance <= max_distance)
+        .min()
+        .map(|(_, candidate)| candidate)
+}
+
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for (i, a_char) in a.chars().enumerate() {
+        let mut current = vec![i + 1];
+        for (j, b_char) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(a_char != *b_char);
+            current.push(substitution.min(previous[j + 1] + 1).min(current[j] + 1));
+        }
+        previous = current;
+    }
+    previous[b.len()]
+}
+
+/// Сообщение о методе, который определён дважды. `first` и `second` — модули
+/// обоих определений; `None` — сам файл, в котором идёт проверка или генерация
+pub(crate) fn duplicate_method_message(struct_name: &str, method: &str, first: Option<&Path>, second: Option<&Path>) -> String {
+    let file = |path: Option<&Path>| match path.and_then(Path::file_name) {
+        Some(name) => name.to_string_lossy().to_string(),
+        None => "this file".to_string(),
+    };
+    let mut message = format!("Method '{}' is already defined for struct '{}'", method, struct_name);
+    if first.is_some() || second.is_some() {
+        message.push_str(&format!(" (in {} and {})", file(first), file(second)));
+    }
+    message
+}
+
 #[derive(Debug, Error)]
 pub enum SemanticError {
-    #[error("Type mismatch at {location}: expected {expected:?}, found {found:?}")]
+    #[error("Type mismatch at {location}: expected {expected}, found {found}")]
     TypeMismatch {
         location: SourceLocation,
         expected: ChifType,
         found: ChifType,
     },
     
-    #[error("Undefined symbol '{symbol}' at {location}")]
+    #[error("Undefined symbol '{symbol}' at {location}{}", hint_suffix(.hint))]
     UndefinedSymbol {
         symbol: String,
         location: SourceLocation,
+        /// Почему имя не видно, например приватная функция модуля
+        hint: Option<String>,
     },
     
     #[error("Symbol '{symbol}' already defined at {location}")]
@@ -26,6 +119,12 @@ pub enum SemanticError {
         location: SourceLocation,
     },
     
+    #[error("'{name}' is a builtin object name and cannot be redeclared at {location}")]
+    ReservedName {
+        name: String,
+        location: SourceLocation,
+    },
+    
     #[error("Invalid operation at {location}: {message}")]
     InvalidOperation {
         location: SourceLocation,
@@ -39,6 +138,190 @@ pub enum SemanticError {
     InvalidContinue,
 }
 
+impl SemanticError {
+    /// Позиция ошибки, если она известна
+    pub fn location(&self) -> Option<&SourceLocation> {
+        let location = match self {
+            SemanticError::TypeMismatch { location, .. }
+            | SemanticError::UndefinedSymbol { location, .. }
+            | SemanticError::SymbolAlreadyDefined { location, .. }
+            | SemanticError::ReservedName { location, .. }
+            | SemanticError::InvalidOperation { location, .. } => location,
+            SemanticError::InvalidBreak | SemanticError::InvalidContinue => return None,
+        };
+        if location.line == 0 && location.file == SourceLocation::unknown().file {
+            None
+        } else {
+            Some(location)
+        }
+    }
+    
+    /// Текст ошибки без позиции
+    pub fn message(&self) -> String {
+        match self {
+            SemanticError::TypeMismatch { expected, found, .. } => {
+                format!("Type mismatch: expected {}, found {}", expected, found)
+            }
+            SemanticError::UndefinedSymbol { symbol, hint, .. } => {
+                format!("Undefined symbol '{}'{}", symbol, hint_suffix(hint))
+            }
+            SemanticError::SymbolAlreadyDefined { symbol, .. } => format!("Symbol '{}' already defined", symbol),
+            SemanticError::ReservedName { name, .. } => {
+                format!("'{}' is a builtin object name and cannot be redeclared", name)
+            }
+            SemanticError::InvalidOperation { message, .. } => message.clone(),
+            SemanticError::InvalidBreak | SemanticError::InvalidContinue => self.to_string(),
+        }
+    }
+    
+    /// Стабильный код ошибки (`rono --explain`)
+    pub fn code(&self) -> Code {
+        match self {
+            SemanticError::TypeMismatch { .. } => Code::TypeMismatch,
+            SemanticError::UndefinedSymbol { .. } => Code::UndefinedSymbol,
+            SemanticError::SymbolAlreadyDefined { .. } => Code::SymbolAlreadyDefined,
+            SemanticError::ReservedName { .. } => Code::ReservedName,
+            SemanticError::InvalidOperation { .. } => Code::InvalidOperation,
+            SemanticError::InvalidBreak => Code::InvalidBreak,
+            SemanticError::InvalidContinue => Code::InvalidContinue,
+        }
+    }
+    
+    /// Ошибка в файле `file`, если её позиция не знает файла
+    fn in_file(mut self, file: &str) -> Self {
+        match &mut self {
+            SemanticError::TypeMismatch { location, .. }
+            | SemanticError::UndefinedSymbol { location, .. }
+            | SemanticError::SymbolAlreadyDefined { location, .. }
+            | SemanticError::ReservedName { location, .. }
+            | SemanticError::InvalidOperation { location, .. } => {
+                if location.file == SourceLocation::unknown().file {
+                    location.file = file.to_string();
+                }
+            }
+            SemanticError::InvalidBreak | SemanticError::InvalidContinue => {}
+        }
+        self
+    }
+}
+
+fn hint_suffix(hint: &Option<String>) -> String {
+    hint.as_ref().map(|hint| format!(": {}", hint)).unwrap_or_default()
+}
+
+/// Предупреждения анализатора: не останавливают компиляцию
+#[derive(Debug, Clone)]
+pub enum Warning {
+    /// Вызов функции, помеченной `@deprecated("message")`
+    DeprecatedUse {
+        name: String,
+        message: String,
+        location: SourceLocation,
+    },
+    /// Встроенная функция в форме, которая устарела на уровне языка программы
+    DeprecatedBuiltin {
+        name: String,
+        version: LangVersion,
+        note: String,
+        location: SourceLocation,
+    },
+    /// Функция или структура программы с именем из прелюдии
+    PreludeShadowed {
+        name: String,
+        location: SourceLocation,
+    },
+    /// Функция модуля, вызванная по внутреннему имени `utils_helper(...)`
+    /// вместо `utils.helper(...)`
+    ModuleFunctionSpelling {
+        name: String,
+        module: String,
+        function: String,
+        location: SourceLocation,
+    },
+    /// Функция меняет структуру или коллекцию, полученную по значению:
+    /// вызывающий этих изменений не увидит
+    ValueParameterMutated {
+        function: String,
+        parameter: String,
+        location: SourceLocation,
+    },
+    /// Импорт по пути без схемы (`import "utils"`) вместо `./utils`,
+    /// абсолютного пути или `pkg:`
+    BareImportPath {
+        path: String,
+        location: SourceLocation,
+    },
+}
+
+impl Warning {
+    pub fn location(&self) -> &SourceLocation {
+        match self {
+            Warning::DeprecatedUse { location, .. }
+            | Warning::DeprecatedBuiltin { location, .. }
+            | Warning::PreludeShadowed { location, .. }
+            | Warning::ModuleFunctionSpelling { location, .. }
+            | Warning::ValueParameterMutated { location, .. }
+            | Warning::BareImportPath { location, .. } => location,
+        }
+    }
+    
+    /// Предупреждение в файле `file`, если его позиция не знает файла
+    fn in_file(&mut self, file: &str) {
+        let location = match self {
+            Warning::DeprecatedUse { location, .. }
+            | Warning::DeprecatedBuiltin { location, .. }
+            | Warning::PreludeShadowed { location, .. }
+            | Warning::ModuleFunctionSpelling { location, .. }
+            | Warning::ValueParameterMutated { location, .. }
+            | Warning::BareImportPath { location, .. } => location,
+        };
+        if location.file == SourceLocation::unknown().file {
+            location.file = file.to_string();
+        }
+    }
+    
+    /// Код предупреждения для диагностик, `--allow` и `--deny`
+    pub fn code(&self) -> Code {
+        match self {
+            Warning::DeprecatedUse { .. } => Code::DeprecatedUse,
+            Warning::DeprecatedBuiltin { .. } => Code::DeprecatedBuiltin,
+            Warning::PreludeShadowed { .. } => Code::PreludeShadowed,
+            Warning::ModuleFunctionSpelling { .. } => Code::ModuleFunctionSpelling,
+            Warning::ValueParameterMutated { .. } => Code::ValueParameterMutated,
+            Warning::BareImportPath { .. } => Code::BareImportPath,
+        }
+    }
+}
+
+impl std::fmt::Display for Warning {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Warning::DeprecatedUse { name, message, .. } => {
+                write!(f, "function '{}' is deprecated: {}", name, message)
+            }
+            Warning::DeprecatedBuiltin { name, version, note, .. } => {
+                write!(f, "'{}' is deprecated at language level {}: {}", name, version, note)
+            }
+            Warning::PreludeShadowed { name, .. } => {
+                write!(f, "'{}' shadows the prelude definition with the same name", name)
+            }
+            Warning::ModuleFunctionSpelling { name, module, function, .. } => {
+                write!(f, "'{}' is the internal name of '{}.{}'; call it as {}.{}(...)", name, module, function, module, function)
+            }
+            Warning::ValueParameterMutated { function, parameter, .. } => {
+                write!(
+                    f,
+                    "'{}' changes '{}', which is passed by value: the caller does not see the change; take it as &mut or return it",
+                    function, parameter
+                )
+            }
+            Warning::BareImportPath { path, .. } => {
+                write!(f, "import path '{}' has no scheme; write \"./{}\" (relative to this file), an absolute path or \"pkg:...\"", path, path)
+            }
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct Symbol {
     pub name: String,
@@ -61,6 +344,7 @@ pub struct FunctionSignature {
     pub parameters: Vec<Parameter>,
     pub return_type: ChifType,
     pub is_mutating: bool,  // Новое поле для отслеживания мутирующих методов
+    pub deprecated: Option<String>,  // Сообщение из @deprecated("...")
 }
 
 #[derive(Debug, Clone)]
@@ -69,11 +353,125 @@ pub struct StructDefinition {
     pub fields: Vec<StructField>,
 }
 
+/// Вид символа в `SemanticAnalyzer::symbols`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum SymbolKind {
+    Function,
+    Struct,
+    Variable,
+    Module,
+}
+
+impl SymbolKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SymbolKind::Function => "function",
+            SymbolKind::Struct => "struct",
+            SymbolKind::Variable => "variable",
+            SymbolKind::Module => "module",
+        }
+    }
+}
+
+/// Откуда пришёл символ
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SymbolOrigin {
+    Builtin,
+    Prelude,
+    // Сам анализируемый файл
+    Program,
+    // Модуль по каноническому пути
+    Module(PathBuf),
+}
+
+impl std::fmt::Display for SymbolOrigin {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SymbolOrigin::Builtin => write!(f, "builtin"),
+            SymbolOrigin::Prelude => write!(f, "prelude"),
+            SymbolOrigin::Program => write!(f, "this file"),
+            SymbolOrigin::Module(path) => write!(f, "imported from {}", path.display()),
+        }
+    }
+}
+
+/// Символ глобальной области после анализа. `name` — как его пишут в программе
+/// (`u.helper`, `Point.sum`), `symbol` — внутреннее имя в таблице (`u_helper`,
+/// `Point_sum`), `detail` — параметры и результат функции (`(x: int) int`),
+/// поля структуры, тип переменной или файл модуля
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SymbolInfo {
+    pub name: String,
+    pub symbol: String,
+    pub kind: SymbolKind,
+    pub detail: String,
+    pub origin: SymbolOrigin,
+}
+
+impl SymbolInfo {
+    pub fn to_json(&self) -> serde_json::Value {
+        let (origin, file) = match &self.origin {
+            SymbolOrigin::Builtin => ("builtin", None),
+            SymbolOrigin::Prelude => ("prelude", None),
+            SymbolOrigin::Program => ("program", None),
+            SymbolOrigin::Module(path) => ("module", Some(path.display().to_string())),
+        };
+        serde_json::json!({
+            "name": self.name,
+            "symbol": self.symbol,
+            "kind": self.kind.as_str(),
+            "detail": self.detail,
+            "origin": origin,
+            "file": file,
+        })
+    }
+}
+
+impl std::fmt::Display for SymbolInfo {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.kind {
+            SymbolKind::Function => write!(f, "function {}{}", self.name, self.detail)?,
+            SymbolKind::Variable => write!(f, "variable {}: {}", self.name, self.detail)?,
+            kind => write!(f, "{} {} {}", kind.as_str(), self.name, self.detail)?,
+        }
+        write!(f, " ({})", self.origin)?;
+        if self.symbol != self.name {
+            write!(f, " [{}]", self.symbol)?;
+        }
+        Ok(())
+    }
+}
+
+/// `(x: int, out: &mut str) int`, как в объявлении; у методов первый параметр — просто `self`
+fn render_signature(signature: &FunctionSignature) -> String {
+    let params: Vec<String> = signature.parameters.iter()
+        .map(|param| match (param.name.as_str(), &param.param_type) {
+            ("self", _) => "self".to_string(),
+            (name, ChifType::Pointer(target)) if param.is_reference => format!("{}: &mut {}", name, target),
+            (name, param_type) => format!("{}: {}", name, param_type),
+        })
+        .collect();
+    match &signature.return_type {
+        ChifType::Nil => format!("({})", params.join(", ")),
+        return_type => format!("({}) {}", params.join(", "), return_type),
+    }
+}
+
+/// `{ x: int, priv secret: str }`
+fn render_struct(definition: &StructDefinition) -> String {
+    let fields: Vec<String> = definition.fields.iter()
+        .map(|field| format!("{}{}: {}", if field.is_private { "priv " } else { "" }, field.name, field.field_type))
+        .collect();
+    format!("{{ {} }}", fields.join(", "))
+}
+
 
 
+/// Область видимости. Символы лежат по `SymbolId` из таблицы интернирования
+/// `SymbolTable`: имя хранится один раз, сколько бы областей его ни объявляли
 #[derive(Debug, Clone)]
 pub struct Scope {
-    pub symbols: HashMap<String, Symbol>,
+    pub symbols: HashMap<SymbolId, Symbol>,
     pub parent: Option<usize>,
 }
 
@@ -85,20 +483,20 @@ impl Scope {
         }
     }
     
-    pub fn define_symbol(&mut self, symbol: Symbol) -> Result<(), SemanticError> {
-        if self.symbols.contains_key(&symbol.name) {
+    pub fn define_symbol(&mut self, id: SymbolId, symbol: Symbol) -> Result<(), SemanticError> {
+        if self.symbols.contains_key(&id) {
             return Err(SemanticError::SymbolAlreadyDefined {
                 symbol: symbol.name.clone(),
                 location: symbol.location.clone(),
             });
         }
         
-        self.symbols.insert(symbol.name.clone(), symbol);
+        self.symbols.insert(id, symbol);
         Ok(())
     }
     
-    pub fn lookup_symbol(&self, name: &str) -> Option<&Symbol> {
-        self.symbols.get(name)
+    pub fn lookup_symbol(&self, id: SymbolId) -> Option<&Symbol> {
+        self.symbols.get(&id)
     }
 }
 
@@ -106,6 +504,7 @@ impl Scope {
 pub struct SymbolTable {
     pub scopes: Vec<Scope>,
     pub current_scope: usize,
+    pub names: Interner,
 }
 
 impl SymbolTable {
@@ -114,6 +513,7 @@ impl SymbolTable {
         Self {
             scopes: vec![global_scope],
             current_scope: 0,
+            names: Interner::new(),
         }
     }
     
@@ -141,14 +541,27 @@ impl SymbolTable {
     }
     
     pub fn define_symbol(&mut self, symbol: Symbol) -> Result<(), SemanticError> {
-        self.scopes[self.current_scope].define_symbol(symbol)
+        // `_` ничего не объявляет, поэтому несколько `_` в одной области не конфликтуют
+        if symbol.name == DISCARD {
+            return Ok(());
+        }
+        let id = self.names.intern(&symbol.name);
+        self.scopes[self.current_scope].define_symbol(id, symbol)
+    }
+    
+    /// Убирает из глобальной области символы, определённые в файле `file`
+    /// (по `location.file`); остальные файлы рабочей области не затрагиваются
+    pub fn remove_file_symbols(&mut self, file: &str) {
+        self.scopes[0].symbols.retain(|_, symbol| symbol.location.file != file);
     }
     
     pub fn lookup_symbol(&self, name: &str) -> Option<&Symbol> {
+        // Имя, которое ни разу не объявлялось, не интернировано и не ищется по областям
+        let id = self.names.get(name)?;
         let mut current_scope = self.current_scope;
         
         loop {
-            if let Some(symbol) = self.scopes[current_scope].lookup_symbol(name) {
+            if let Some(symbol) = self.scopes[current_scope].lookup_symbol(id) {
                 return Some(symbol);
             }
             
@@ -161,20 +574,94 @@ impl SymbolTable {
         
         None
     }
+    
+    /// Символ глобальной области, даже если его перекрывает локальный
+    pub fn lookup_global(&self, name: &str) -> Option<&Symbol> {
+        self.scopes[0].lookup_symbol(self.names.get(name)?)
+    }
 }
 
 pub struct SemanticAnalyzer {
     pub symbol_table: SymbolTable,
     pub in_loop: bool,
     pub current_function_return_type: Option<ChifType>,
+    // Проверяется тело `chif main`: его `ret expr;` становится кодом выхода процесса
+    pub in_main: bool,
+    // Структура из `fn_for`, методы которой сейчас анализируются: только им доступны `priv`-поля
+    pub current_struct: Option<String>,
     pub modules: HashMap<String, ModuleInfo>,
+    // Разобранные модули по каноническому пути файла, чтобы не разбирать их повторно
+    pub module_cache: HashMap<PathBuf, ModuleInfo>,
+    // Разобранные модули; компилятор забирает их для генерации IR
+    pub parsed_modules: HashMap<PathBuf, Program>,
+    // Предупреждения из тел функций модулей. Тело модуля проверяется один раз
+    // за анализ, сколько бы файлов его ни импортировали
+    pub module_diagnostics: HashMap<PathBuf, Vec<Warning>>,
+    // Сколько раз проверялись тела каждого модуля (для тестов кэша)
+    module_analysis_counts: HashMap<PathBuf, usize>,
+    // Файл модуля, из которого пришёл каждый символ глобальной области (функция
+    // с префиксом модуля, структура, метод `Struct_method`); символов самого
+    // анализируемого файла здесь нет
+    symbol_files: HashMap<String, PathBuf>,
+    // Имена встроенных функций и объектов и добавленных к программе функций и
+    // структур прелюдии — для происхождения в `symbols`
+    builtin_names: HashSet<String>,
+    prelude_names: HashSet<String>,
+    // Дополнительные сигнатуры встроенных функций, принимающих аргументы
+    // разных типов (`toInt` от str); таблица символов хранит одну на имя
+    builtin_overloads: HashMap<String, Vec<FunctionSignature>>,
+    pub warnings: Vec<Warning>,
+    // Уровень языка программы: от него зависит форма части встроенных функций
+    pub lang_version: LangVersion,
+    // Точка входа из `--entry`; проверяется вместе с назначенной в самой программе
+    pub entry: Option<EntryPoint>,
+    // Подключать прелюдию (`--no-prelude` выключает)
+    pub prelude: bool,
+    // Каталог анализируемого файла: от него ищутся `import "./utils"` и
+    // `import "utils"`; `None` — текущий каталог
+    pub source_dir: Option<PathBuf>,
 }
 
+/// Сигнатуры модуля собираются один раз на файл и общие для всех его
+/// импортов: копия `ModuleInfo` копирует только поля самого импорта
 #[derive(Debug, Clone)]
 pub struct ModuleInfo {
     pub name: String,
-    pub functions: HashMap<String, FunctionSignature>,
-    pub structs: HashMap<String, StructDefinition>,
+    pub path: PathBuf,
+    pub functions: Rc<HashMap<String, FunctionSignature>>,
+    pub structs: Rc<HashMap<String, StructDefinition>>,
+    // Методы из блоков `fn_for` модуля по имени `Struct_method`, в том числе
+    // добавленные структурам других модулей
+    pub methods: Rc<HashMap<String, FunctionSignature>>,
+    // Структура каждого метода из `methods`
+    pub method_owners: Rc<HashMap<String, String>>,
+    // Имена из `use (...)`, если импортирована только часть модуля
+    pub names: Option<Vec<String>>,
+    pub import_line: usize,
+    // Функции и структуры с `export`; `None` — в модуле нет `export`, открыто всё
+    pub exports: Option<Rc<[String]>>,
+}
+
+impl ModuleInfo {
+    pub fn is_exported(&self, name: &str) -> bool {
+        match &self.exports {
+            Some(exports) => exports.iter().any(|exported| exported == name),
+            None => true,
+        }
+    }
+    
+    /// Подсказка для имени, которое есть в модуле, но не экспортировано
+    pub fn private_hint(&self, name: &str) -> Option<String> {
+        let kind = if self.functions.contains_key(name) {
+            "function"
+        } else if self.structs.contains_key(name) {
+            "struct"
+        } else {
+            return None;
+        };
+        (!self.is_exported(name))
+            .then(|| format!("{} '{}' exists in module '{}' but is not exported", kind, name, self.name))
+    }
 }
 
 impl SemanticAnalyzer {
@@ -183,8 +670,113 @@ impl SemanticAnalyzer {
             symbol_table: SymbolTable::new(),
             in_loop: false,
             current_function_return_type: None,
+            in_main: false,
+            current_struct: None,
             modules: HashMap::new(),
+            module_cache: HashMap::new(),
+            parsed_modules: HashMap::new(),
+            module_diagnostics: HashMap::new(),
+            module_analysis_counts: HashMap::new(),
+            symbol_files: HashMap::new(),
+            builtin_names: HashSet::new(),
+            prelude_names: HashSet::new(),
+            builtin_overloads: HashMap::new(),
+            warnings: Vec::new(),
+            lang_version: LangVersion::default(),
+            entry: None,
+            prelude: true,
+            source_dir: None,
+        }
+    }
+    
+    /// Сверяет встроенную функцию с реестром: недоступная на уровне программы
+    /// форма — ошибка, устаревшая — предупреждение
+    fn check_builtin_level(&mut self, name: &str) -> Result<(), SemanticError> {
+        let Some(info) = lang::builtin_info(name) else {
+            return Ok(());
+        };
+        if !info.is_available(self.lang_version) {
+            return Err(SemanticError::InvalidOperation {
+                location: SourceLocation::unknown(),
+                message: format!(
+                    "'{}' requires language level {} (the program uses level {})",
+                    name, info.introduced, self.lang_version
+                ),
+            });
+        }
+        if info.is_deprecated(self.lang_version) {
+            self.warnings.push(Warning::DeprecatedBuiltin {
+                name: name.to_string(),
+                version: self.lang_version,
+                note: info.note.to_string(),
+                location: SourceLocation::unknown(),
+            });
+        }
+        Ok(())
+    }
+    
+    /// Методы `args` — аргументов командной строки после имени программы:
+    /// `count() int`, `get(i int) str`, `has_flag(name str) bool` и `value_of(name str) str`
+    fn analyze_args_method(&mut self, method_call: &MethodCall) -> Result<ChifType, SemanticError> {
+        let (parameter, return_type) = match method_call.method.as_str() {
+            "count" => (None, ChifType::Int),
+            "get" => (Some(ChifType::Int), ChifType::Str),
+            "has_flag" => (Some(ChifType::Str), ChifType::Bool),
+            "value_of" => (Some(ChifType::Str), ChifType::Str),
+            method => {
+                return Err(SemanticError::InvalidOperation {
+                    location: SourceLocation::unknown(),
+                    message: format!("args has no method '{}'; available: count, get, has_flag, value_of", method),
+                });
+            }
+        };
+        if method_call.args.len() != usize::from(parameter.is_some()) {
+            let expected = if parameter.is_some() { "1 argument" } else { "no arguments" };
+            return Err(SemanticError::InvalidOperation {
+                location: SourceLocation::unknown(),
+                message: format!("args.{} expects {}", method_call.method, expected),
+            });
+        }
+        if let (Some(expected), Some(arg)) = (parameter, method_call.args.first()) {
+            let found = self.analyze_value(arg)?;
+            if found != expected {
+                return Err(SemanticError::TypeMismatch {
+                    location: SourceLocation::unknown(),
+                    expected,
+                    found,
+                });
+            }
+        }
+        Ok(return_type)
+    }
+    
+    /// `os.name()`, `os.arch()` и `rono.version()`: строки без аргументов. При
+    /// компиляции это значения цели сборки, а не машины, на которой идёт сборка
+    fn analyze_platform_method(object: &str, method_call: &MethodCall) -> Result<ChifType, SemanticError> {
+        let available = if object == "os" { "name, arch" } else { "version" };
+        if !available.split(", ").any(|method| method == method_call.method) {
+            return Err(SemanticError::InvalidOperation {
+                location: SourceLocation::unknown(),
+                message: format!("{} has no method '{}'; available: {}", object, method_call.method, available),
+            });
+        }
+        if !method_call.args.is_empty() {
+            return Err(SemanticError::InvalidOperation {
+                location: SourceLocation::unknown(),
+                message: format!("{}.{} expects no arguments", object, method_call.method),
+            });
         }
+        Ok(ChifType::Str)
+    }
+    
+    /// Тип результата `http.<method>`: тело ответа до уровня 2, затем HttpResponse
+    fn http_method_type(&mut self, method: &str) -> Result<ChifType, SemanticError> {
+        self.check_builtin_level(&format!("http.{}", method))?;
+        Ok(if self.lang_version >= LangVersion::V2 {
+            ChifType::Struct(http_response_struct().name)
+        } else {
+            ChifType::Str
+        })
     }
     
     pub fn check_types(&mut self, program: &Program) -> Result<(), SemanticError> {
@@ -194,64 +786,95 @@ impl SemanticAnalyzer {
         Ok(())
     }
     
+    fn check_function_types(&mut self, func: &Function) -> Result<(), SemanticError> {
+        self.symbol_table.push_scope();
+        
+        // Set current function return type for validation
+        let old_return_type = self.current_function_return_type.clone();
+        self.current_function_return_type = func.return_type.clone();
+        let old_in_main = std::mem::replace(&mut self.in_main, func.is_main);
+        
+        // Add parameters to scope
+        for param in &func.params {
+            let symbol = Symbol {
+                name: param.name.clone(),
+                symbol_type: SymbolType::Variable(self.parameter_type(param)),
+                location: SourceLocation::unknown(),
+                is_mutable: Self::parameter_is_mutable(param),
+            };
+            self.symbol_table.define_symbol(symbol)?;
+        }
+        
+        // Check function body types
+        self.check_block_types(&func.body, &func.return_type)?;
+        
+        // Validate that all code paths return a value if needed
+        // For main function, we allow implicit nil return
+        if let Some(return_type) = &func.return_type {
+            if *return_type != ChifType::Nil && !func.is_main && !self.block_always_returns(&func.body) {
+                return Err(SemanticError::InvalidOperation {
+                    location: SourceLocation::unknown(),
+                    message: format!(
+                        "Function '{}' must return a value of type {:?} in all code paths",
+                        func.name, return_type
+                    ),
+                });
+            }
+        }
+        
+        // Restore previous function return type
+        self.current_function_return_type = old_return_type;
+        self.in_main = old_in_main;
+        
+        self.symbol_table.pop_scope()?;
+        Ok(())
+    }
+    
     fn check_item_types(&mut self, item: &Item) -> Result<(), SemanticError> {
         match item {
-            Item::Function(func) => {
-                self.symbol_table.push_scope();
-                
-                // Set current function return type for validation
-                let old_return_type = self.current_function_return_type.clone();
-                self.current_function_return_type = func.return_type.clone();
-                
-                // Add parameters to scope
-                for param in &func.params {
-                    let symbol = Symbol {
-                        name: param.name.clone(),
-                        symbol_type: SymbolType::Variable(param.param_type.clone()),
-                        location: SourceLocation::unknown(),
-                        is_mutable: false,
-                    };
-                    self.symbol_table.define_symbol(symbol)?;
-                }
-                
-                // Check function body types
-                self.check_block_types(&func.body, &func.return_type)?;
-                
-                // Validate that all code paths return a value if needed
-                // For main function, we allow implicit nil return
-                if let Some(return_type) = &func.return_type {
-                    if *return_type != ChifType::Nil && !func.is_main && !self.block_always_returns(&func.body) {
-                        return Err(SemanticError::InvalidOperation {
-                            location: SourceLocation::unknown(),
-                            message: format!(
-                                "Function '{}' must return a value of type {:?} in all code paths",
-                                func.name, return_type
-                            ),
-                        });
-                    }
-                }
-                
-                // Restore previous function return type
-                self.current_function_return_type = old_return_type;
-                
-                self.symbol_table.pop_scope()?;
-            }
+            Item::Function(func) => self.check_function_types(func)?,
             Item::Struct(_struct_def) => {
                 // Struct definitions are already handled in collect_definitions
                 // No need to redefine them here
             }
             Item::StructImpl(impl_block) => {
+                let old_struct = self.current_struct.replace(impl_block.struct_name.clone());
                 for method in &impl_block.methods {
-                    self.check_item_types(&Item::Function(method.clone()))?;
+                    self.check_function_types(method)?;
                 }
+                self.current_struct = old_struct;
             }
             Item::Import(_) => {
                 // Import type checking would be done during module resolution
             }
+            Item::Global(var_decl) => {
+                // Символ уже объявлен в analyze_program
+                self.check_var_decl_types(var_decl)?;
+            }
         }
         Ok(())
     }
     
+    /// Тип объявленной переменной после проверки её начального значения
+    fn check_var_decl_types(&mut self, var_decl: &VarDecl) -> Result<ChifType, SemanticError> {
+        let value_type = match &var_decl.value {
+            Some(expr) => Some(self.analyze_value(expr)?),
+            None => None,
+        };
+        let var_type = Self::declared_type(var_decl, value_type.as_ref())?;
+        if let (Some(expr_type), Some(expr)) = (value_type, &var_decl.value) {
+            if !self.types_compatible(&var_type, &expr_type) {
+                return Err(SemanticError::TypeMismatch {
+                    location: SourceLocation::unknown(),
+                    expected: var_type,
+                    found: expr_type,
+                });
+            }
+            Self::check_integer_division(&var_type, expr, &expr_type)?;
+        }
+        Ok(var_type)
+    }
+    
     fn check_block_types(&mut self, block: &Block, expected_return_type: &Option<ChifType>) -> Result<(), SemanticError> {
         for statement in &block.statements {
             self.check_statement_types(statement, expected_return_type)?;
@@ -262,28 +885,22 @@ impl SemanticAnalyzer {
     fn check_statement_types(&mut self, statement: &Statement, expected_return_type: &Option<ChifType>) -> Result<(), SemanticError> {
         match statement {
             Statement::VarDecl(var_decl) => {
-                if let Some(expr) = &var_decl.value {
-                    let expr_type = self.analyze_expression(expr)?;
-                    if !self.types_compatible(&var_decl.var_type, &expr_type) {
-                        return Err(SemanticError::TypeMismatch {
-                            location: SourceLocation::unknown(),
-                            expected: var_decl.var_type.clone(),
-                            found: expr_type,
-                        });
-                    }
-                }
-                
+                let var_type = self.check_var_decl_types(var_decl)?;
                 let symbol = Symbol {
                     name: var_decl.name.clone(),
-                    symbol_type: SymbolType::Variable(var_decl.var_type.clone()),
+                    symbol_type: SymbolType::Variable(var_type),
                     location: SourceLocation::unknown(),
                     is_mutable: var_decl.is_mutable,
                 };
                 self.symbol_table.define_symbol(symbol)?;
             }
+            Statement::Assignment(assignment) if Self::is_discard(&assignment.target) => {
+                self.analyze_value(&assignment.value)?;
+            }
             Statement::Assignment(assignment) => {
+                self.check_assignable(&assignment.target)?;
                 let target_type = self.analyze_expression(&assignment.target)?;
-                let value_type = self.analyze_expression(&assignment.value)?;
+                let value_type = self.analyze_value(&assignment.value)?;
                 
                 if !self.types_compatible(&target_type, &value_type) {
                     return Err(SemanticError::TypeMismatch {
@@ -292,13 +909,23 @@ impl SemanticAnalyzer {
                         found: value_type,
                     });
                 }
+                Self::check_integer_division(&target_type, &assignment.value, &value_type)?;
             }
             Statement::Return(expr) => {
                 // We're always in a function context during check_statement_types
                 // The current_function_return_type is set in analyze_item
                 
                 if let Some(expr) = expr {
-                    let return_type = self.analyze_expression(expr)?;
+                    let return_type = self.analyze_value(expr)?;
+                    if self.in_main && expected_return_type.is_none() && return_type != ChifType::Int {
+                        return Err(SemanticError::InvalidOperation {
+                            location: SourceLocation::unknown(),
+                            message: format!(
+                                "main can only return int (the process exit code); found {:?}",
+                                return_type
+                            ),
+                        });
+                    }
                     if let Some(expected) = expected_return_type {
                         if !self.types_compatible(expected, &return_type) {
                             return Err(SemanticError::TypeMismatch {
@@ -307,6 +934,7 @@ impl SemanticAnalyzer {
                                 found: return_type,
                             });
                         }
+                        Self::check_integer_division(expected, expr, &return_type)?;
                     }
                 } else {
                     // Empty return
@@ -322,7 +950,7 @@ impl SemanticAnalyzer {
                 }
             }
             Statement::If(if_stmt) => {
-                let condition_type = self.analyze_expression(&if_stmt.condition)?;
+                let condition_type = self.analyze_value(&if_stmt.condition)?;
                 if condition_type != ChifType::Bool {
                     return Err(SemanticError::TypeMismatch {
                         location: SourceLocation::unknown(),
@@ -337,7 +965,7 @@ impl SemanticAnalyzer {
                 }
             }
             Statement::While(while_stmt) => {
-                let condition_type = self.analyze_expression(&while_stmt.condition)?;
+                let condition_type = self.analyze_value(&while_stmt.condition)?;
                 if condition_type != ChifType::Bool {
                     return Err(SemanticError::TypeMismatch {
                         location: SourceLocation::unknown(),
@@ -363,7 +991,7 @@ impl SemanticAnalyzer {
                 }
                 
                 if let Some(condition) = &for_stmt.condition {
-                    let condition_type = self.analyze_expression(condition)?;
+                    let condition_type = self.analyze_value(condition)?;
                     if condition_type != ChifType::Bool {
                         return Err(SemanticError::TypeMismatch {
                             location: SourceLocation::unknown(),
@@ -388,6 +1016,17 @@ impl SemanticAnalyzer {
                 
                 self.symbol_table.pop_scope()?;
             }
+            Statement::ForIn(for_in) => {
+                self.symbol_table.push_scope();
+                self.define_range_variable(for_in)?;
+                
+                let old_in_loop = self.in_loop;
+                self.in_loop = true;
+                self.check_block_types(&for_in.body, expected_return_type)?;
+                self.in_loop = old_in_loop;
+                
+                self.symbol_table.pop_scope()?;
+            }
             Statement::Switch(switch_stmt) => {
                 let switch_type = self.analyze_expression(&switch_stmt.expr)?;
                 
@@ -407,6 +1046,9 @@ impl SemanticAnalyzer {
                     self.check_block_types(default_case, expected_return_type)?;
                 }
             }
+            Statement::Destructure(destructure) => {
+                self.define_destructured(destructure)?;
+            }
             Statement::Expression(expr) => {
                 self.analyze_expression(expr)?;
             }
@@ -427,38 +1069,147 @@ impl SemanticAnalyzer {
         Ok(())
     }
     
-    fn types_compatible(&self, expected: &ChifType, actual: &ChifType) -> bool {
-        match (expected, actual) {
-            // Exact matches
-            (ChifType::Int, ChifType::Int) => true,
-            (ChifType::Float, ChifType::Float) => true,
-            (ChifType::Str, ChifType::Str) => true,
-            (ChifType::Bool, ChifType::Bool) => true,
-            (ChifType::Nil, ChifType::Nil) => true,
-            
-            // Numeric conversions
-            (ChifType::Float, ChifType::Int) => true, // Int can be promoted to Float
-            
-            // Array/List compatibility
-            (ChifType::Array(expected_elem, _), ChifType::Array(actual_elem, _)) => {
-                self.types_compatible(expected_elem, actual_elem)
-            }
-            (ChifType::List(expected_elem, _), ChifType::List(actual_elem, _)) => {
-                self.types_compatible(expected_elem, actual_elem)
-            }
-            // Allow array literals to be assigned to list variables
-            (ChifType::List(expected_elem, _), ChifType::Array(actual_elem, _)) => {
-                self.types_compatible(expected_elem, actual_elem)
+    /// Проверяет арность и типы при распаковке кортежа и объявляет переменные
+    fn define_destructured(&mut self, destructure: &Destructure) -> Result<(), SemanticError> {
+        let value_type = self.analyze_expression(&destructure.value)?;
+        
+        if let Some(declared_type) = &destructure.var_type {
+            Self::check_type_without_nil(declared_type, || format!("Variables ({})", destructure.names.join(", ")))?;
+            if !self.types_compatible(declared_type, &value_type) {
+                return Err(SemanticError::TypeMismatch {
+                    location: SourceLocation::unknown(),
+                    expected: declared_type.clone(),
+                    found: value_type,
+                });
             }
-            // Allow list literals to be assigned to array variables
-            (ChifType::Array(expected_elem, _), ChifType::List(actual_elem, _)) => {
-                self.types_compatible(expected_elem, actual_elem)
+        }
+        
+        let element_types = match value_type {
+            ChifType::Tuple(element_types) => element_types,
+            other => {
+                return Err(SemanticError::InvalidOperation {
+                    location: SourceLocation::unknown(),
+                    message: format!("Cannot destructure non-tuple type {:?}", other),
+                });
             }
-            
-            // Map compatibility
-            (ChifType::Map(expected_key, expected_val), ChifType::Map(actual_key, actual_val)) => {
-                self.types_compatible(expected_key, actual_key) && 
-                self.types_compatible(expected_val, actual_val)
+        };
+        
+        if element_types.len() != destructure.names.len() {
+            return Err(SemanticError::InvalidOperation {
+                location: SourceLocation::unknown(),
+                message: format!(
+                    "Cannot destructure a tuple of {} elements into {} variables",
+                    element_types.len(),
+                    destructure.names.len()
+                ),
+            });
+        }
+        
+        for (name, element_type) in destructure.names.iter().zip(element_types) {
+            self.check_local_not_reserved(name)?;
+            
+            let symbol = Symbol {
+                name: name.clone(),
+                symbol_type: SymbolType::Variable(element_type),
+                location: SourceLocation::unknown(),
+                is_mutable: destructure.is_mutable,
+            };
+            self.symbol_table.define_symbol(symbol)?;
+        }
+        
+        Ok(())
+    }
+    
+    /// Type of a declared variable. A bare `list` takes its element type from a
+    /// non-empty initializer; for `[]` or no initializer there is nothing to infer it from
+    fn declared_type(var_decl: &VarDecl, value_type: Option<&ChifType>) -> Result<ChifType, SemanticError> {
+        match &var_decl.var_type {
+            ChifType::List(element_type, dimensions) if **element_type == ChifType::Nil => match value_type {
+                Some(ChifType::Array(inner, literal_dimensions)) if **inner != ChifType::Nil => {
+                    Ok(ChifType::List(inner.clone(), vec![0; literal_dimensions.len()]))
+                }
+                Some(ChifType::List(inner, dimensions)) if **inner != ChifType::Nil => {
+                    Ok(ChifType::List(inner.clone(), dimensions.clone()))
+                }
+                _ => Err(SemanticError::InvalidOperation {
+                    location: SourceLocation::unknown(),
+                    message: format!(
+                        "Cannot infer element type of empty list '{}'; annotate as list[<type>]",
+                        var_decl.name
+                    ),
+                }),
+            },
+            declared => Ok(declared.clone()),
+        }
+    }
+    
+    /// `int / int` делит нацело и там, где ожидается float: результат не становится
+    /// дробным от того, куда его записывают, поэтому такое место — ошибка с подсказкой
+    fn check_integer_division(expected: &ChifType, expr: &Expression, found: &ChifType) -> Result<(), SemanticError> {
+        match (expected, expr, found) {
+            (ChifType::Float, Expression::Binary(binary), ChifType::Int)
+                if binary.operator == BinaryOperator::Divide =>
+            {
+                Err(SemanticError::InvalidOperation {
+                    location: SourceLocation::unknown(),
+                    message: "int / int truncates toward zero even where float is expected; \
+                              use fdiv(a, b) for float division of ints".to_string(),
+                })
+            }
+            _ => Ok(()),
+        }
+    }
+    
+    /// Подходят ли аргументы с типами `arg_types` к сигнатуре `signature`
+    fn arguments_fit(&self, signature: &FunctionSignature, arg_types: &[ChifType]) -> bool {
+        signature.parameters.len() == arg_types.len()
+            && signature.parameters.iter().zip(arg_types).all(|(param, arg_type)| self.types_compatible(&param.param_type, arg_type))
+    }
+    
+    /// Тип элемента массива или списка: `Array(T, [d1, d2])` даёт `Array(T, [d2])`,
+    /// а одномерный `Array(T, [d])` — `T`
+    fn element_type(collection: &ChifType) -> Option<ChifType> {
+        match collection {
+            ChifType::Array(elem, dims) if dims.len() > 1 => Some(ChifType::Array(elem.clone(), dims[1..].to_vec())),
+            ChifType::List(elem, dims) if dims.len() > 1 => Some(ChifType::List(elem.clone(), dims[1..].to_vec())),
+            ChifType::Array(elem, _) | ChifType::List(elem, _) => Some((**elem).clone()),
+            _ => None,
+        }
+    }
+    
+    fn types_compatible(&self, expected: &ChifType, actual: &ChifType) -> bool {
+        match (expected, actual) {
+            // Exact matches
+            (ChifType::Int, ChifType::Int) => true,
+            (ChifType::Float, ChifType::Float) => true,
+            (ChifType::Str, ChifType::Str) => true,
+            (ChifType::Bool, ChifType::Bool) => true,
+            (ChifType::Nil, ChifType::Nil) => true,
+            
+            // Numeric conversions
+            (ChifType::Float, ChifType::Int) => true, // Int can be promoted to Float
+            
+            // An empty literal `[]` fits any element type
+            (ChifType::Array(..) | ChifType::List(..), ChifType::Array(actual_elem, _))
+                if **actual_elem == ChifType::Nil => true,
+            
+            // Array/List compatibility: сравниваем по одному измерению, чтобы
+            // `array[array[int]]` и `list[list[int]]` подходили к `[[1, 2], [3, 4]]`,
+            // а литералы массивов можно было присваивать спискам и наоборот
+            (ChifType::Array(..) | ChifType::List(..), ChifType::Array(..) | ChifType::List(..)) => {
+                match (Self::element_type(expected), Self::element_type(actual)) {
+                    (Some(expected_elem), Some(actual_elem)) => self.types_compatible(&expected_elem, &actual_elem),
+                    _ => false,
+                }
+            }
+            
+            // An empty literal `{}` fits any map
+            (ChifType::Map(..), ChifType::Map(actual_key, _)) if **actual_key == ChifType::Nil => true,
+            
+            // Map compatibility
+            (ChifType::Map(expected_key, expected_val), ChifType::Map(actual_key, actual_val)) => {
+                self.types_compatible(expected_key, actual_key) && 
+                self.types_compatible(expected_val, actual_val)
             }
             
             // Struct compatibility
@@ -474,6 +1225,13 @@ impl SemanticAnalyzer {
             // Nil can be assigned to any pointer type
             (ChifType::Pointer(_), ChifType::Nil) => true,
             
+            // Tuple compatibility: same arity, element-wise compatible
+            (ChifType::Tuple(expected_elems), ChifType::Tuple(actual_elems)) => {
+                expected_elems.len() == actual_elems.len() &&
+                expected_elems.iter().zip(actual_elems)
+                    .all(|(expected, actual)| self.types_compatible(expected, actual))
+            }
+            
             _ => false,
         }
     }
@@ -523,7 +1281,48 @@ impl SemanticAnalyzer {
         }
     }
     
+    /// Проверяет программу; результат содержит и функции прелюдии, которые она вызывает
     pub fn analyze(&mut self, program: &Program) -> Result<AnalyzedProgram, SemanticError> {
+        let program = self.with_prelude(Cow::Borrowed(program));
+        self.check_items(&program)?;
+        Ok(AnalyzedProgram::new(program.into_owned().items))
+    }
+    
+    /// Как `analyze`, но забирает программу и не копирует её AST
+    pub fn analyze_owned(&mut self, program: Program) -> Result<AnalyzedProgram, SemanticError> {
+        let program = self.with_prelude(Cow::Owned(program));
+        self.check_items(&program)?;
+        Ok(AnalyzedProgram::new(program.into_owned().items))
+    }
+    
+    pub(crate) fn check_program(&mut self, program: &Program) -> Result<(), SemanticError> {
+        let program = self.with_prelude(Cow::Borrowed(program));
+        self.check_items(&program)
+    }
+    
+    /// Добавляет к программе прелюдию и предупреждает о перекрытых ею именах
+    fn with_prelude<'a>(&mut self, program: Cow<'a, Program>) -> Cow<'a, Program> {
+        if !self.prelude {
+            return program;
+        }
+        let (merged, shadowed) = prelude::merge(&program);
+        // Элементы прелюдии идут перед элементами программы
+        let prelude_items = merged.items.len() - program.items.len();
+        self.prelude_names = merged.items[..prelude_items].iter()
+            .filter_map(|item| match item {
+                Item::Function(func) => Some(func.name.clone()),
+                Item::Struct(struct_def) => Some(struct_def.name.clone()),
+                _ => None,
+            })
+            .collect();
+        self.warnings.extend(shadowed.into_iter().map(|name| Warning::PreludeShadowed {
+            name,
+            location: SourceLocation::unknown(),
+        }));
+        Cow::Owned(merged)
+    }
+    
+    fn check_items(&mut self, program: &Program) -> Result<(), SemanticError> {
         // First pass: collect all function and struct definitions
         self.collect_definitions(program)?;
         
@@ -531,25 +1330,65 @@ impl SemanticAnalyzer {
         self.analyze_program(program)?;
         
         // Third pass: detailed type checking
+        // It walks the same expressions again, so drop the warnings it repeats
+        let warning_count = self.warnings.len();
         self.check_types(program)?;
+        self.warnings.truncate(warning_count);
         
-        Ok(AnalyzedProgram {
-            items: program.items.clone(), // TODO: Replace with analyzed items
-        })
+        self.check_entry_point(program)
+    }
+    
+    /// Назначенная точка входа существует, не принимает параметров и
+    /// возвращает int или ничего. Программа без точки входа (модуль) допустима
+    fn check_entry_point(&self, program: &Program) -> Result<(), SemanticError> {
+        let invalid = |message: String| SemanticError::InvalidOperation {
+            location: SourceLocation::unknown(),
+            message,
+        };
+        let Some(entry) = program.entry_point(self.entry.as_ref()).map_err(invalid)? else {
+            return Ok(());
+        };
+        let symbol_name = match &entry.module {
+            Some(module) if !self.modules.contains_key(module) => {
+                return Err(invalid(format!("Entry module '{}' is not imported", module)));
+            }
+            Some(module) => format!("{}_{}", module, entry.function),
+            None => entry.function.clone(),
+        };
+        match self.symbol_table.lookup_symbol(&symbol_name).map(|symbol| &symbol.symbol_type) {
+            Some(SymbolType::Function(signature)) => {
+                match entry_signature_problem(&entry, &signature.parameters, Some(&signature.return_type)) {
+                    Some(problem) => Err(invalid(problem)),
+                    None => Ok(()),
+                }
+            }
+            _ => Err(SemanticError::UndefinedSymbol {
+                symbol: entry.to_string(),
+                location: SourceLocation::unknown(),
+                hint: entry.module.as_ref()
+                    .and_then(|module| self.modules.get(module))
+                    .and_then(|module| module.private_hint(&entry.function)),
+            }),
+        }
     }
     
     fn collect_definitions(&mut self, program: &Program) -> Result<(), SemanticError> {
         // Add built-in functions
         self.add_builtin_functions()?;
+        self.builtin_names = self.symbol_table.scopes[0].symbols.values().map(|symbol| symbol.name.clone()).collect();
         
         for item in &program.items {
             match item {
                 Item::Function(func) => {
+                    self.check_not_reserved(&func.name)?;
+                    Self::check_signature_without_nil(&format!("function '{}'", func.name), &func.params, func.return_type.as_ref())?;
+                    
                     let signature = FunctionSignature {
                         name: func.name.clone(),
                         parameters: func.params.clone(),
                         return_type: func.return_type.clone().unwrap_or(ChifType::Nil),
                         is_mutating: false,  // Обычные функции по умолчанию не мутируют
+                        deprecated: func.deprecated.clone(),
                     };
                     
                     let symbol = Symbol {
@@ -562,6 +1401,25 @@ impl SemanticAnalyzer {
                     self.symbol_table.define_symbol(symbol)?;
                 }
                 Item::Struct(struct_def) => {
+                    if is_builtin_object_type(&struct_def.name) {
+                        return Err(SemanticError::ReservedName {
+                            name: struct_def.name.clone(),
+                            location: SourceLocation::unknown(),
+                        });
+                    }
+                    let mut field_names = HashSet::new();
+                    for field in &struct_def.fields {
+                        Self::check_type_without_nil(&field.field_type, || {
+                            format!("Field '{}' of struct '{}'", field.name, struct_def.name)
+                        })?;
+                        if !field_names.insert(field.name.as_str()) {
+                            return Err(SemanticError::InvalidOperation {
+                                location: SourceLocation::unknown(),
+                                message: format!("Struct '{}' declares field '{}' more than once", struct_def.name, field.name),
+                            });
+                        }
+                    }
+                    
                     let struct_definition = StructDefinition {
                         name: struct_def.name.clone(),
                         fields: struct_def.fields.clone(),
@@ -580,6 +1438,14 @@ impl SemanticAnalyzer {
                     // Add methods to symbol table with struct prefix
                     for method in &impl_block.methods {
                         let method_name = format!("{}_{}", impl_block.struct_name, method.name);
+                        if self.symbol_table.lookup_symbol(&method_name).is_some() {
+                            return Err(self.duplicate_method(&impl_block.struct_name, &method.name, None));
+                        }
+                        Self::check_signature_without_nil(
+                            &format!("method '{}.{}'", impl_block.struct_name, method.name),
+                            &method.params,
+                            method.return_type.as_ref(),
+                        )?;
                         
                         // Анализируем тело метода для определения мутабельности
                         let is_mutating = self.analyze_method_mutability(method);
@@ -589,6 +1455,7 @@ impl SemanticAnalyzer {
                             parameters: method.params.clone(),
                             return_type: method.return_type.clone().unwrap_or(ChifType::Nil),
                             is_mutating,  // Устанавливаем флаг мутабельности
+                            deprecated: None,
                         };
                         
                         let symbol = Symbol {
@@ -613,6 +1480,13 @@ impl SemanticAnalyzer {
     }
     
     fn analyze_program(&mut self, program: &Program) -> Result<(), SemanticError> {
+        // Глобальные переменные видны во всех функциях, а в значениях друг
+        // друга — только объявленные выше, в порядке инициализации
+        for item in &program.items {
+            if let Item::Global(var_decl) = item {
+                self.analyze_statement(&Statement::VarDecl(var_decl.clone()))?;
+            }
+        }
         for item in &program.items {
             self.analyze_item(item)?;
         }
@@ -620,56 +1494,157 @@ impl SemanticAnalyzer {
         Ok(())
     }
     
+    fn analyze_function(&mut self, func: &Function) -> Result<(), SemanticError> {
+        // Create new scope for function
+        self.symbol_table.push_scope();
+        
+        // Set current function return type for validation
+        let old_return_type = self.current_function_return_type.clone();
+        self.current_function_return_type = func.return_type.clone();
+        
+        // Add parameters to function scope
+        for param in &func.params {
+            self.check_local_not_reserved(&param.name)?;
+        
+            // For reference parameters, the type is already a pointer type
+            // We don't need to wrap it again
+            let symbol = Symbol {
+                name: param.name.clone(),
+                symbol_type: SymbolType::Variable(self.parameter_type(param)),
+                location: SourceLocation::unknown(),
+                is_mutable: Self::parameter_is_mutable(param),
+            };
+        
+            self.symbol_table.define_symbol(symbol)?;
+        }
+        
+        // Analyze function body
+        self.analyze_block(&func.body)?;
+        self.warn_value_parameter_mutations(func);
+        
+        // Restore previous function return type
+        self.current_function_return_type = old_return_type;
+        
+        // Pop function scope
+        self.symbol_table.pop_scope()?;
+        Ok(())
+    }
+    
     fn analyze_item(&mut self, item: &Item) -> Result<(), SemanticError> {
         match item {
-            Item::Function(func) => {
-                // Create new scope for function
-                self.symbol_table.push_scope();
-                
-                // Set current function return type for validation
-                let old_return_type = self.current_function_return_type.clone();
-                self.current_function_return_type = func.return_type.clone();
-                
-                // Add parameters to function scope
-                for param in &func.params {
-                    // For reference parameters, the type is already a pointer type
-                    // We don't need to wrap it again
-                    let symbol = Symbol {
-                        name: param.name.clone(),
-                        symbol_type: SymbolType::Variable(param.param_type.clone()),
-                        location: SourceLocation::unknown(),
-                        is_mutable: param.is_reference, // Reference parameters are mutable
-                    };
-                    
-                    self.symbol_table.define_symbol(symbol)?;
-                }
-                
-                // Analyze function body
-                self.analyze_block(&func.body)?;
-                
-                // Restore previous function return type
-                self.current_function_return_type = old_return_type;
-                
-                // Pop function scope
-                self.symbol_table.pop_scope()?;
-            }
+            Item::Function(func) => self.analyze_function(func)?,
             Item::Struct(_) => {
                 // Struct definitions are already handled in collect_definitions
             }
             Item::StructImpl(impl_block) => {
                 // Analyze methods in struct implementation
+                let old_struct = self.current_struct.replace(impl_block.struct_name.clone());
                 for method in &impl_block.methods {
-                    self.analyze_item(&Item::Function(method.clone()))?;
+                    self.analyze_function(method)?;
                 }
+                self.current_struct = old_struct;
             }
             Item::Import(_) => {
                 // Imports are already processed in collect_definitions
             }
+            Item::Global(_) => {
+                // Globals are declared at the start of analyze_program
+            }
         }
         
         Ok(())
     }
     
+    /// `_ = expr;`: значение вычисляется и отбрасывается
+    fn is_discard(target: &Expression) -> bool {
+        matches!(target, Expression::Identifier(name) if name == DISCARD)
+    }
+    
+    /// Тип параметра в теле функции. `self` метода с типом `Self` получает
+    /// структуру из `fn_for`, поэтому `self.x` имеет тип поля
+    fn parameter_type(&self, param: &Parameter) -> ChifType {
+        match (&param.param_type, &self.current_struct) {
+            (ChifType::Struct(name), Some(struct_name)) if name == "Self" => ChifType::Struct(struct_name.clone()),
+            (param_type, _) => param_type.clone(),
+        }
+    }
+    
+    /// Параметр по значению — своя копия функции, его можно переприсвоить.
+    /// `&mut T` тоже изменяем, а через `&T` только читают
+    fn parameter_is_mutable(param: &Parameter) -> bool {
+        param.is_reference || !matches!(param.param_type, ChifType::Pointer(_))
+    }
+    
+    /// Анализируется ли сейчас метод или связанная функция из `fn_for <struct_name>`
+    fn in_methods_of(&self, struct_name: &str) -> bool {
+        self.current_struct.as_deref() == Some(struct_name)
+    }
+    
+    /// Запрещает объявлять символы с именами встроенных объектов (con, http, ...)
+    fn check_not_reserved(&self, name: &str) -> Result<(), SemanticError> {
+        if is_builtin_object_name(name) {
+            return Err(SemanticError::ReservedName {
+                name: name.to_string(),
+                location: SourceLocation::unknown(),
+            });
+        }
+        Ok(())
+    }
+    
+    /// То же для переменных и параметров, которым можно перекрыть `args`, `os` и `rono`
+    fn check_local_not_reserved(&self, name: &str) -> Result<(), SemanticError> {
+        if is_shadowable_object_name(name) {
+            return Ok(());
+        }
+        self.check_not_reserved(name)
+    }
+    
+    /// Имя указывает на встроенный объект, а не на перекрывшую его переменную
+    fn is_builtin_object(&self, name: &str) -> bool {
+        matches!(
+            self.symbol_table.lookup_symbol(name).map(|symbol| &symbol.symbol_type),
+            Some(SymbolType::Variable(ChifType::Struct(type_name))) if is_builtin_object_type(type_name)
+        )
+    }
+    
+    /// nil означает «функция ничего не возвращает» и служит пустым значением
+    /// указателя, поэтому он не может быть типом переменной, параметра, поля или
+    /// элемента коллекции. `pointer` без типа (`pointer[nil]`) — обычный указатель
+    fn check_type_without_nil(declared: &ChifType, what: impl FnOnce() -> String) -> Result<(), SemanticError> {
+        fn contains_nil(chif_type: &ChifType) -> bool {
+            match chif_type {
+                ChifType::Nil => true,
+                ChifType::Pointer(inner) => **inner != ChifType::Nil && contains_nil(inner),
+                ChifType::Array(inner, _) | ChifType::List(inner, _) => contains_nil(inner),
+                ChifType::Map(key, value) => contains_nil(key) || contains_nil(value),
+                ChifType::Tuple(elements) => elements.iter().any(contains_nil),
+                _ => false,
+            }
+        }
+        if contains_nil(declared) {
+            return Err(SemanticError::InvalidOperation {
+                location: SourceLocation::unknown(),
+                message: format!(
+                    "{} cannot have type {}: nil only marks a function without a return value and is the empty value of a pointer",
+                    what(), declared
+                ),
+            });
+        }
+        Ok(())
+    }
+    
+    /// Параметры и возвращаемый тип функции или метода; сам nil как
+    /// возвращаемый тип — это функция без значения
+    fn check_signature_without_nil(owner: &str, params: &[Parameter], return_type: Option<&ChifType>) -> Result<(), SemanticError> {
+        for param in params {
+            Self::check_type_without_nil(&param.param_type, || format!("Parameter '{}' of {}", param.name, owner))?;
+        }
+        match return_type {
+            Some(ChifType::Nil) | None => Ok(()),
+            Some(return_type) => Self::check_type_without_nil(return_type, || format!("Return type of {}", owner)),
+        }
+    }
+    
     fn analyze_block(&mut self, block: &Block) -> Result<(), SemanticError> {
         for statement in &block.statements {
             self.analyze_statement(statement)?;
@@ -680,24 +1655,36 @@ impl SemanticAnalyzer {
     fn analyze_statement(&mut self, statement: &Statement) -> Result<(), SemanticError> {
         match statement {
             Statement::VarDecl(var_decl) => {
-                // Analyze the initial value if present
-                if let Some(expr) = &var_decl.value {
-                    let _expr_type = self.analyze_expression(expr)?;
-                    // TODO: Check type compatibility
+                self.check_local_not_reserved(&var_decl.name)?;
+                // Bare `list` is List(nil): the element type comes from the initializer
+                if !matches!(&var_decl.var_type, ChifType::List(element_type, _) if **element_type == ChifType::Nil) {
+                    Self::check_type_without_nil(&var_decl.var_type, || format!("Variable '{}'", var_decl.name))?;
                 }
                 
+                // Analyze the initial value if present
+                let value_type = match &var_decl.value {
+                    Some(expr) => Some(self.analyze_expression(expr)?),
+                    None => None,
+                };
+                // TODO: Check type compatibility
+                
                 let symbol = Symbol {
                     name: var_decl.name.clone(),
-                    symbol_type: SymbolType::Variable(var_decl.var_type.clone()),
+                    symbol_type: SymbolType::Variable(Self::declared_type(var_decl, value_type.as_ref())?),
                     location: SourceLocation::unknown(),
                     is_mutable: var_decl.is_mutable,
                 };
                 
                 self.symbol_table.define_symbol(symbol)?;
             }
+            Statement::Assignment(assignment) if Self::is_discard(&assignment.target) => {
+                self.analyze_expression(&assignment.value)?;
+            }
             Statement::Assignment(assignment) => {
                 self.analyze_expression(&assignment.target)?;
                 self.analyze_expression(&assignment.value)?;
+                self.check_assignable(&assignment.target)?;
+                self.check_write_through(&assignment.target)?;
                 // TODO: Check assignment compatibility
             }
             Statement::Expression(expr) => {
@@ -708,6 +1695,9 @@ impl SemanticAnalyzer {
                     self.analyze_expression(expr)?;
                 }
             }
+            Statement::Destructure(destructure) => {
+                self.define_destructured(destructure)?;
+            }
             Statement::If(if_stmt) => {
                 self.analyze_expression(&if_stmt.condition)?;
                 self.analyze_block(&if_stmt.then_block)?;
@@ -751,6 +1741,17 @@ impl SemanticAnalyzer {
                 
                 self.symbol_table.pop_scope()?;
             }
+            Statement::ForIn(for_in) => {
+                self.symbol_table.push_scope();
+                self.define_range_variable(for_in)?;
+                
+                let old_in_loop = self.in_loop;
+                self.in_loop = true;
+                self.analyze_block(&for_in.body)?;
+                self.in_loop = old_in_loop;
+                
+                self.symbol_table.pop_scope()?;
+            }
             Statement::Switch(switch_stmt) => {
                 self.analyze_expression(&switch_stmt.expr)?;
                 for case in &switch_stmt.cases {
@@ -778,6 +1779,114 @@ impl SemanticAnalyzer {
         Ok(())
     }
     
+    /// `for x in range(start, end[, step])` takes two or three int bounds and a step
+    /// that is not a literal zero; `x` is an int visible only inside the loop
+    fn define_range_variable(&mut self, for_in: &ForInStatement) -> Result<(), SemanticError> {
+        let args = for_in.range_args().ok_or_else(|| SemanticError::InvalidOperation {
+            location: SourceLocation::unknown(),
+            message: "for-in can only iterate over range(start, end[, step])".to_string(),
+        })?;
+        if args.len() != 2 && args.len() != 3 {
+            return Err(SemanticError::InvalidOperation {
+                location: SourceLocation::unknown(),
+                message: format!("range() expects 2 or 3 arguments, got {}", args.len()),
+            });
+        }
+        for arg in args {
+            let arg_type = self.analyze_value(arg)?;
+            if arg_type != ChifType::Int {
+                return Err(SemanticError::TypeMismatch {
+                    location: SourceLocation::unknown(),
+                    expected: ChifType::Int,
+                    found: arg_type,
+                });
+            }
+        }
+        let zero_step = match args.get(2) {
+            Some(Expression::Literal(ChifValue::Int(0))) => true,
+            Some(Expression::Unary(unary_op)) => matches!(
+                (&unary_op.operator, &*unary_op.operand),
+                (UnaryOperator::Minus, Expression::Literal(ChifValue::Int(0)))
+            ),
+            _ => false,
+        };
+        if zero_step {
+            return Err(SemanticError::InvalidOperation {
+                location: SourceLocation::unknown(),
+                message: "range() step cannot be zero".to_string(),
+            });
+        }
+        
+        self.check_local_not_reserved(&for_in.variable)?;
+        self.symbol_table.define_symbol(Symbol {
+            name: for_in.variable.clone(),
+            symbol_type: SymbolType::Variable(ChifType::Int),
+            location: SourceLocation::unknown(),
+            is_mutable: false,
+        })
+    }
+    
+    /// `x = v` needs `x` declared with `var` (or a parameter): a `let` binding,
+    /// a `for ... in` variable and a builtin object are immutable. This covers
+    /// `for (i = 0; ...)` reusing an outer variable too
+    fn check_assignable(&self, target: &Expression) -> Result<(), SemanticError> {
+        let Expression::Identifier(name) = target else {
+            return Ok(());
+        };
+        match self.symbol_table.lookup_symbol(name) {
+            Some(Symbol { symbol_type: SymbolType::Variable(_), is_mutable: false, .. }) => Err(SemanticError::InvalidOperation {
+                location: SourceLocation::unknown(),
+                message: format!("Cannot assign to immutable variable '{}'; declare it with var", name),
+            }),
+            _ => Ok(()),
+        }
+    }
+    
+    /// `*p = v` and `p.field = v` write through the pointer `p`, which needs a
+    /// `&mut` parameter (or a `var` holding the pointer)
+    fn check_write_through(&self, target: &Expression) -> Result<(), SemanticError> {
+        let pointer = match target {
+            Expression::Dereference(pointer) => pointer,
+            Expression::FieldAccess(FieldAccess { object, .. }) | Expression::Index(IndexAccess { object, .. }) => {
+                if !matches!(&**object, Expression::Identifier(_)) {
+                    return self.check_write_through(object);
+                }
+                object
+            }
+            _ => return Ok(()),
+        };
+        let Expression::Identifier(name) = &**pointer else {
+            return Ok(());
+        };
+        match self.symbol_table.lookup_symbol(name) {
+            Some(Symbol { symbol_type: SymbolType::Variable(ChifType::Pointer(_)), is_mutable: false, .. }) => {
+                Err(SemanticError::InvalidOperation {
+                    location: SourceLocation::unknown(),
+                    message: format!("Cannot write through '{}': it is a read-only reference; take it as &mut", name),
+                })
+            }
+            _ => Ok(()),
+        }
+    }
+    
+    /// Analyzes an expression whose result is used as a value: a call of a
+    /// function or method without a return value is an error there
+    fn analyze_value(&mut self, expression: &Expression) -> Result<ChifType, SemanticError> {
+        let value_type = self.analyze_expression(expression)?;
+        if value_type == ChifType::Nil {
+            let callee = match expression {
+                Expression::Call(func_call) => format!("function '{}'", func_call.name),
+                Expression::MethodCall(method_call) => format!("method '{}'", method_call.method),
+                _ => return Ok(value_type),
+            };
+            return Err(SemanticError::InvalidOperation {
+                location: SourceLocation::unknown(),
+                message: format!("{} has no return value and cannot be used in an expression", callee),
+            });
+        }
+        Ok(value_type)
+    }
+    
     fn analyze_expression(&mut self, expression: &Expression) -> Result<ChifType, SemanticError> {
         match expression {
             Expression::Literal(value) => {
@@ -786,15 +1895,20 @@ impl SemanticAnalyzer {
                     ChifValue::Float(_) => ChifType::Float,
                     ChifValue::Str(_) => ChifType::Str,
                     ChifValue::Bool(_) => ChifType::Bool,
-                    ChifValue::Nil => ChifType::Nil,
+                    ChifValue::Nil | ChifValue::Void => ChifType::Nil,
                     ChifValue::Array(_) => ChifType::Array(Box::new(ChifType::Nil), vec![0]), // TODO: Proper array type
                     ChifValue::List(_) => ChifType::List(Box::new(ChifType::Nil), vec![]), // TODO: Proper list type
                     ChifValue::Map(_) => ChifType::Map(Box::new(ChifType::Nil), Box::new(ChifType::Nil)), // TODO: Proper map type
                     ChifValue::Struct(_, _) => ChifType::Nil, // TODO: Proper struct type
                     ChifValue::Pointer(_) => ChifType::Pointer(Box::new(ChifType::Nil)), // TODO: Proper pointer type
                     ChifValue::Reference(_) => ChifType::Pointer(Box::new(ChifType::Nil)), // TODO: Proper reference type
+                    ChifValue::Tuple(_) => value.get_type(),
                 })
             }
+            Expression::Identifier(name) if name == DISCARD => Err(SemanticError::InvalidOperation {
+                location: SourceLocation::unknown(),
+                message: format!("cannot read from '{}'", DISCARD),
+            }),
             Expression::Identifier(name) => {
                 if let Some(symbol) = self.symbol_table.lookup_symbol(name) {
                     match &symbol.symbol_type {
@@ -808,12 +1922,13 @@ impl SemanticAnalyzer {
                     Err(SemanticError::UndefinedSymbol {
                         symbol: name.clone(),
                         location: SourceLocation::unknown(),
+                        hint: None,
                     })
                 }
             }
             Expression::Binary(binary_op) => {
-                let left_type = self.analyze_expression(&binary_op.left)?;
-                let right_type = self.analyze_expression(&binary_op.right)?;
+                let left_type = self.analyze_value(&binary_op.left)?;
+                let right_type = self.analyze_value(&binary_op.right)?;
                 
                 match binary_op.operator {
                     BinaryOperator::Add | BinaryOperator::Subtract | 
@@ -849,6 +1964,31 @@ impl SemanticAnalyzer {
                             }),
                         }
                     }
+                    BinaryOperator::In => {
+                        // Ключ словаря, элемент списка или массива, подстрока строки
+                        let container = match &right_type {
+                            ChifType::Pointer(inner) if Self::is_collection(inner) => &**inner,
+                            other => other,
+                        };
+                        let expected = match container {
+                            ChifType::Map(key_type, _) => (**key_type).clone(),
+                            ChifType::List(element_type, _) | ChifType::Array(element_type, _) => (**element_type).clone(),
+                            ChifType::Str => ChifType::Str,
+                            other => return Err(SemanticError::InvalidOperation {
+                                location: SourceLocation::unknown(),
+                                message: format!("'in' needs a map, list, array or str on the right, found {}", other),
+                            }),
+                        };
+                        if self.types_compatible(&expected, &left_type) {
+                            Ok(ChifType::Bool)
+                        } else {
+                            Err(SemanticError::TypeMismatch {
+                                location: SourceLocation::unknown(),
+                                expected,
+                                found: left_type,
+                            })
+                        }
+                    }
                     BinaryOperator::And | BinaryOperator::Or => {
                         // Logical operations
                         if left_type == ChifType::Bool && right_type == ChifType::Bool {
@@ -864,7 +2004,7 @@ impl SemanticAnalyzer {
                 }
             }
             Expression::Unary(unary_op) => {
-                let operand_type = self.analyze_expression(&unary_op.operand)?;
+                let operand_type = self.analyze_value(&unary_op.operand)?;
                 
                 match unary_op.operator {
                     UnaryOperator::Minus => {
@@ -891,16 +2031,40 @@ impl SemanticAnalyzer {
                 }
             }
             Expression::Call(func_call) => {
+                // Named arguments are checked against the parameter names; each
+                // argument is then paired with its parameter
+                let mut args: Vec<&Expression> = func_call.source_args().collect();
+                if !func_call.named_args.is_empty() {
+                    let parameters = match self.symbol_table.lookup_symbol(&func_call.name) {
+                        Some(Symbol { symbol_type: SymbolType::Function(signature), .. }) => &signature.parameters,
+                        _ => return Err(SemanticError::UndefinedSymbol {
+                            symbol: func_call.name.clone(),
+                            location: SourceLocation::unknown(),
+                            hint: None,
+                        }),
+                    };
+                    let positions = func_call.parameter_positions(parameters).map_err(|message| SemanticError::InvalidOperation {
+                        location: SourceLocation::unknown(),
+                        message,
+                    })?;
+                    args = in_parameter_order(args, &positions);
+                }
+                
                 // Analyze arguments first
                 let mut arg_types = Vec::new();
-                for arg in &func_call.args {
-                    arg_types.push(self.analyze_expression(arg)?);
+                for arg in &args {
+                    arg_types.push(self.analyze_value(arg)?);
                 }
+                self.check_builtin_level(&func_call.name)?;
                 
                 // Check if function exists
                 if let Some(symbol) = self.symbol_table.lookup_symbol(&func_call.name) {
                     match &symbol.symbol_type {
                         SymbolType::Function(signature) => {
+                            let signature = self.builtin_overloads.get(&func_call.name)
+                                .and_then(|overloads| overloads.iter().find(|overload| self.arguments_fit(overload, &arg_types)))
+                                .unwrap_or(signature);
+                            
                             // Check argument count
                             if arg_types.len() != signature.parameters.len() {
                                 return Err(SemanticError::InvalidOperation {
@@ -915,8 +2079,18 @@ impl SemanticAnalyzer {
                             }
                             
                             // Check argument types
-                            for (_i, (arg_type, param)) in arg_types.iter().zip(&signature.parameters).enumerate() {
+                            for (i, (arg_type, param)) in arg_types.iter().zip(&signature.parameters).enumerate() {
                                 if param.is_reference {
+                                    // Вызывающий явно разрешает изменение своей переменной
+                                    if let Expression::Reference(AddressOf { mutable: false, .. }) = args[i] {
+                                        return Err(SemanticError::InvalidOperation {
+                                            location: SourceLocation::unknown(),
+                                            message: format!(
+                                                "Parameter '{}' of '{}' is &mut; pass the argument as &mut",
+                                                param.name, func_call.name
+                                            ),
+                                        });
+                                    }
                                     // For reference parameters, the argument should match the parameter type
                                     // (which is already a pointer type)
                                     if !self.types_compatible(&param.param_type, arg_type) {
@@ -935,9 +2109,26 @@ impl SemanticAnalyzer {
                                             found: arg_type.clone(),
                                         });
                                     }
+                                    Self::check_integer_division(&param.param_type, args[i], arg_type)?;
                                 }
                             }
                             
+                            if let Some(message) = &signature.deprecated {
+                                self.warnings.push(Warning::DeprecatedUse {
+                                    name: func_call.name.clone(),
+                                    message: message.clone(),
+                                    location: SourceLocation::unknown(),
+                                });
+                            }
+                            if let Some((module, function)) = self.module_function_of(&func_call.name) {
+                                self.warnings.push(Warning::ModuleFunctionSpelling {
+                                    name: func_call.name.clone(),
+                                    module,
+                                    function,
+                                    location: SourceLocation::unknown(),
+                                });
+                            }
+                            
                             Ok(signature.return_type.clone())
                         }
                         _ => Err(SemanticError::InvalidOperation {
@@ -949,6 +2140,7 @@ impl SemanticAnalyzer {
                     Err(SemanticError::UndefinedSymbol {
                         symbol: func_call.name.clone(),
                         location: SourceLocation::unknown(),
+                        hint: None,
                     })
                 }
             }
@@ -959,24 +2151,61 @@ impl SemanticAnalyzer {
                         SymbolType::Struct(struct_def) => {
                             let struct_def = struct_def.clone(); // Clone to avoid borrow issues
                             
-                            // Check that all required fields are provided
-                            for field in &struct_def.fields {
-                                let field_provided = struct_literal.fields.iter()
-                                    .any(|(name, _)| name == &field.name);
-                                if !field_provided {
+                            if struct_def.fields.iter().any(|field| field.is_private)
+                                && !self.in_methods_of(&struct_literal.struct_name) {
+                                return Err(SemanticError::InvalidOperation {
+                                    location: SourceLocation::unknown(),
+                                    message: format!(
+                                        "Struct '{}' has private fields; construct it with an associated function from fn_for {}",
+                                        struct_literal.struct_name, struct_literal.struct_name
+                                    ),
+                                });
+                            }
+                            
+                            for (index, (field_name, _)) in struct_literal.fields.iter().enumerate() {
+                                if struct_literal.fields[..index].iter().any(|(earlier, _)| earlier == field_name) {
                                     return Err(SemanticError::InvalidOperation {
                                         location: SourceLocation::unknown(),
                                         message: format!(
-                                            "Missing field '{}' in struct literal for '{}'",
-                                            field.name, struct_literal.struct_name
+                                            "Field '{}' is specified more than once in struct literal for '{}'",
+                                            field_name, struct_literal.struct_name
                                         ),
                                     });
                                 }
                             }
                             
-                            // Check field types
-                            for (field_name, field_expr) in &struct_literal.fields {
-                                let expr_type = self.analyze_expression(field_expr)?;
+                            // `..base` supplies every field the literal does not list
+                            if let Some(base) = &struct_literal.base {
+                                let base_type = self.analyze_value(base)?;
+                                if base_type != ChifType::Struct(struct_literal.struct_name.clone()) {
+                                    return Err(SemanticError::InvalidOperation {
+                                        location: SourceLocation::unknown(),
+                                        message: format!(
+                                            "Base of a struct literal for '{}' must be a {}, found {}",
+                                            struct_literal.struct_name, struct_literal.struct_name, base_type
+                                        ),
+                                    });
+                                }
+                            }
+                            
+                            // Check that all required fields are provided
+                            for field in &struct_def.fields {
+                                let field_provided = struct_literal.base.is_some() || struct_literal.fields.iter()
+                                    .any(|(name, _)| name == &field.name);
+                                if !field_provided {
+                                    return Err(SemanticError::InvalidOperation {
+                                        location: SourceLocation::unknown(),
+                                        message: format!(
+                                            "Missing field '{}' in struct literal for '{}'",
+                                            field.name, struct_literal.struct_name
+                                        ),
+                                    });
+                                }
+                            }
+                            
+                            // Check field types
+                            for (field_name, field_expr) in &struct_literal.fields {
+                                let expr_type = self.analyze_value(field_expr)?;
                                 
                                 // Find the field definition
                                 if let Some(field_def) = struct_def.fields.iter()
@@ -988,6 +2217,7 @@ impl SemanticAnalyzer {
                                             found: expr_type,
                                         });
                                     }
+                                    Self::check_integer_division(&field_def.field_type, field_expr, &expr_type)?;
                                 } else {
                                     return Err(SemanticError::InvalidOperation {
                                         location: SourceLocation::unknown(),
@@ -1010,12 +2240,17 @@ impl SemanticAnalyzer {
                     Err(SemanticError::UndefinedSymbol {
                         symbol: struct_literal.struct_name.clone(),
                         location: SourceLocation::unknown(),
+                        hint: None,
                     })
                 }
             }
             Expression::FieldAccess(field_access) => {
-                // Analyze the object expression to get its type
-                let object_type = self.analyze_expression(&field_access.object)?;
+                // Analyze the object expression to get its type; through a pointer
+                // to a struct the fields are reached directly: `p.x`
+                let object_type = match self.analyze_expression(&field_access.object)? {
+                    ChifType::Pointer(target) if matches!(*target, ChifType::Struct(_)) => *target,
+                    object_type => object_type,
+                };
                 
                 match object_type {
                     ChifType::Struct(struct_name) => {
@@ -1026,6 +2261,15 @@ impl SemanticAnalyzer {
                                     // Find the field in the struct definition
                                     if let Some(field) = struct_def.fields.iter()
                                         .find(|f| f.name == field_access.field) {
+                                        if field.is_private && !self.in_methods_of(&struct_name) {
+                                            return Err(SemanticError::InvalidOperation {
+                                                location: SourceLocation::unknown(),
+                                                message: format!(
+                                                    "Field '{}' of struct '{}' is private; only methods in fn_for {} can read or assign it",
+                                                    field_access.field, struct_name, struct_name
+                                                ),
+                                            });
+                                        }
                                         Ok(field.field_type.clone())
                                     } else {
                                         Err(SemanticError::InvalidOperation {
@@ -1046,9 +2290,24 @@ impl SemanticAnalyzer {
                             Err(SemanticError::UndefinedSymbol {
                                 symbol: struct_name,
                                 location: SourceLocation::unknown(),
+                                hint: None,
                             })
                         }
                     }
+                    ChifType::Tuple(element_types) => {
+                        // Positional access: t.0, t.1, ...
+                        match field_access.field.parse::<usize>() {
+                            Ok(index) if index < element_types.len() => Ok(element_types[index].clone()),
+                            _ => Err(SemanticError::InvalidOperation {
+                                location: SourceLocation::unknown(),
+                                message: format!(
+                                    "Tuple of {} elements has no field '{}'",
+                                    element_types.len(),
+                                    field_access.field
+                                ),
+                            }),
+                        }
+                    }
                     _ => Err(SemanticError::InvalidOperation {
                         location: SourceLocation::unknown(),
                         message: format!("Cannot access field '{}' on non-struct type {:?}", field_access.field, object_type),
@@ -1061,21 +2320,34 @@ impl SemanticAnalyzer {
                     if object_name == "con" && method_call.method == "out" {
                         // Analyze arguments for con.out
                         for arg in &method_call.args {
-                            self.analyze_expression(arg)?;
+                            self.analyze_value(arg)?;
                         }
                         return Ok(ChifType::Nil); // con.out returns void
                     } else if object_name == "con" && method_call.method == "in" {
-                        // con.in takes no arguments and returns int for now
+                        // con.in reads one line without its terminator
                         if !method_call.args.is_empty() {
                             return Err(SemanticError::InvalidOperation {
                                 location: SourceLocation::unknown(),
                                 message: "con.in expects no arguments".to_string(),
                             });
                         }
-                        return Ok(ChifType::Int); // con.in returns int for now
+                        return Ok(ChifType::Str);
 
+                    } else if object_name == "mem" && (method_call.method == "reset" || method_call.method == "used") {
+                        // mem.reset() frees runtime strings and lists, mem.used() reports their size
+                        if !method_call.args.is_empty() {
+                            return Err(SemanticError::InvalidOperation {
+                                location: SourceLocation::unknown(),
+                                message: format!("mem.{} expects no arguments", method_call.method),
+                            });
+                        }
+                        return Ok(if method_call.method == "used" { ChifType::Int } else { ChifType::Nil });
+                    } else if object_name == "args" && self.is_builtin_object(object_name) {
+                        return self.analyze_args_method(method_call);
+                    } else if (object_name == "os" || object_name == "rono") && self.is_builtin_object(object_name) {
+                        return Self::analyze_platform_method(object_name, method_call);
                     } else if object_name == "http" && method_call.method == "get" {
-                        // http.get(url) returns string
+                        // http.get(url) returns the body, or HttpResponse from level 2
                         if method_call.args.len() != 1 {
                             return Err(SemanticError::InvalidOperation {
                                 location: SourceLocation::unknown(),
@@ -1091,9 +2363,9 @@ impl SemanticAnalyzer {
                                 found: arg_type,
                             });
                         }
-                        return Ok(ChifType::Str);
+                        return self.http_method_type(&method_call.method);
                     } else if object_name == "http" && method_call.method == "post" {
-                        // http.post(url, data) returns string
+                        // http.post(url, data) returns the body, or HttpResponse from level 2
                         if method_call.args.len() != 2 {
                             return Err(SemanticError::InvalidOperation {
                                 location: SourceLocation::unknown(),
@@ -1111,9 +2383,9 @@ impl SemanticAnalyzer {
                                 });
                             }
                         }
-                        return Ok(ChifType::Str);
+                        return self.http_method_type(&method_call.method);
                     } else if object_name == "http" && method_call.method == "put" {
-                        // http.put(url, data) returns string
+                        // http.put(url, data) returns the body, or HttpResponse from level 2
                         if method_call.args.len() != 2 {
                             return Err(SemanticError::InvalidOperation {
                                 location: SourceLocation::unknown(),
@@ -1131,9 +2403,9 @@ impl SemanticAnalyzer {
                                 });
                             }
                         }
-                        return Ok(ChifType::Str);
+                        return self.http_method_type(&method_call.method);
                     } else if object_name == "http" && method_call.method == "delete" {
-                        // http.delete(url) returns string
+                        // http.delete(url) returns the body, or HttpResponse from level 2
                         if method_call.args.len() != 1 {
                             return Err(SemanticError::InvalidOperation {
                                 location: SourceLocation::unknown(),
@@ -1149,17 +2421,44 @@ impl SemanticAnalyzer {
                                 found: arg_type,
                             });
                         }
-                        return Ok(ChifType::Str);
+                        return self.http_method_type(&method_call.method);
                     }
                 }
                 
-                // Analyze the object expression to get its type
-                let object_type = self.analyze_expression(&method_call.object)?;
+                // `utils.parse_config(...)`: a function of an imported module
+                if let Expression::Identifier(module_name) = &*method_call.object {
+                    if self.modules.contains_key(module_name) && self.symbol_table.lookup_symbol(module_name).is_none() {
+                        return self.analyze_module_call(module_name, method_call);
+                    }
+                }
+                
+                // `Point.new(1, 2)`: the object is a struct type name, call the associated function
+                if let Expression::Identifier(type_name) = &*method_call.object {
+                    let is_struct_type = matches!(
+                        self.symbol_table.lookup_symbol(type_name).map(|symbol| &symbol.symbol_type),
+                        Some(SymbolType::Struct(_))
+                    );
+                    if is_struct_type {
+                        return self.analyze_associated_call(type_name, method_call);
+                    }
+                }
+                
+                // Analyze the object expression to get its type; methods of a
+                // `&mut list[int]` parameter work on the caller's list
+                let object_type = match self.analyze_value(&method_call.object)? {
+                    ChifType::Pointer(target) if Self::is_collection(&target) => {
+                        if matches!(method_call.method.as_str(), "add" | "addAt" | "del") {
+                            self.check_write_through(&Expression::Dereference(method_call.object.clone()))?;
+                        }
+                        *target
+                    }
+                    object_type => object_type,
+                };
                 
                 // Analyze arguments
                 let mut arg_types = Vec::new();
                 for arg in &method_call.args {
-                    arg_types.push(self.analyze_expression(arg)?);
+                    arg_types.push(self.analyze_value(arg)?);
                 }
                 
                 match object_type {
@@ -1171,6 +2470,16 @@ impl SemanticAnalyzer {
                         if let Some(symbol) = self.symbol_table.lookup_symbol(&method_name) {
                             match &symbol.symbol_type {
                                 SymbolType::Function(signature) => {
+                                    if !matches!(signature.parameters.first(), Some(param) if param.name == "self") {
+                                        return Err(SemanticError::InvalidOperation {
+                                            location: SourceLocation::unknown(),
+                                            message: format!(
+                                                "'{}' has no self parameter, call it as {}.{}(...)",
+                                                method_call.method, struct_name, method_call.method
+                                            ),
+                                        });
+                                    }
+                                    
                                     // Check argument count (excluding self parameter)
                                     let expected_args = signature.parameters.len().saturating_sub(1); // Subtract self parameter
                                     if arg_types.len() != expected_args {
@@ -1207,9 +2516,173 @@ impl SemanticAnalyzer {
                             Err(SemanticError::UndefinedSymbol {
                                 symbol: method_name,
                                 location: SourceLocation::unknown(),
+                                hint: None,
                             })
                         }
                     }
+                    ChifType::List(element_type, dimensions) if matches!(method_call.method.as_str(), "add" | "addAt" | "del") => {
+                        // Rows of a multidimensional list are lists themselves
+                        let item_type = if dimensions.len() > 1 {
+                            ChifType::List(element_type, dimensions[1..].to_vec())
+                        } else {
+                            *element_type
+                        };
+                        let expected_args = match method_call.method.as_str() {
+                            "add" => vec![item_type],
+                            "addAt" => vec![item_type, ChifType::Int],
+                            _ => vec![ChifType::Int],
+                        };
+                        if arg_types.len() != expected_args.len() {
+                            return Err(SemanticError::InvalidOperation {
+                                location: SourceLocation::unknown(),
+                                message: format!(
+                                    "{} expects {} arguments, got {}",
+                                    method_call.method,
+                                    expected_args.len(),
+                                    arg_types.len()
+                                ),
+                            });
+                        }
+                        for (expected, found) in expected_args.iter().zip(&arg_types) {
+                            if !self.types_compatible(expected, found) {
+                                return Err(SemanticError::TypeMismatch {
+                                    location: SourceLocation::unknown(),
+                                    expected: expected.clone(),
+                                    found: found.clone(),
+                                });
+                            }
+                        }
+                        Ok(ChifType::Nil)
+                    }
+                    ChifType::List(element_type, _) | ChifType::Array(element_type, _) => {
+                        match method_call.method.as_str() {
+                            "len" => {
+                                if !arg_types.is_empty() {
+                                    return Err(SemanticError::InvalidOperation {
+                                        location: SourceLocation::unknown(),
+                                        message: "len expects no arguments".to_string(),
+                                    });
+                                }
+                                Ok(ChifType::Int)
+                            }
+                            "unique" => {
+                                // unique() returns a new list with the same element type
+                                if !arg_types.is_empty() {
+                                    return Err(SemanticError::InvalidOperation {
+                                        location: SourceLocation::unknown(),
+                                        message: "unique expects no arguments".to_string(),
+                                    });
+                                }
+                                Ok(ChifType::List(element_type, vec![]))
+                            }
+                            "join" => {
+                                if arg_types.len() != 1 {
+                                    return Err(SemanticError::InvalidOperation {
+                                        location: SourceLocation::unknown(),
+                                        message: format!("join expects 1 argument, got {}", arg_types.len()),
+                                    });
+                                }
+                                if !self.types_compatible(&ChifType::Str, &arg_types[0]) {
+                                    return Err(SemanticError::TypeMismatch {
+                                        location: SourceLocation::unknown(),
+                                        expected: ChifType::Str,
+                                        found: arg_types[0].clone(),
+                                    });
+                                }
+                                // Nil means the element type is not known statically (e.g. an empty literal)
+                                if !matches!(*element_type, ChifType::Str | ChifType::Nil) {
+                                    return Err(SemanticError::InvalidOperation {
+                                        location: SourceLocation::unknown(),
+                                        message: format!("join expects a list of str, found a list of {}", element_type),
+                                    });
+                                }
+                                Ok(ChifType::Str)
+                            }
+                            _ => Err(SemanticError::InvalidOperation {
+                                location: SourceLocation::unknown(),
+                                message: format!("Unknown method '{}' for list", method_call.method),
+                            }),
+                        }
+                    }
+                    ChifType::Str => {
+                        // len/byte_len считают байты, char_count и index_of — символы
+                        let str_list = || ChifType::List(Box::new(ChifType::Str), vec![]);
+                        let (expected_args, return_type): (&[ChifType], ChifType) = match method_call.method.as_str() {
+                            "len" | "byte_len" | "char_count" => (&[], ChifType::Int),
+                            "index_of" => (&[ChifType::Str], ChifType::Int),
+                            "trim" => (&[], ChifType::Str),
+                            "chars" => (&[], str_list()),
+                            "bytes" => (&[], ChifType::List(Box::new(ChifType::Int), vec![])),
+                            "split" => (&[ChifType::Str], str_list()),
+                            _ => return Err(SemanticError::InvalidOperation {
+                                location: SourceLocation::unknown(),
+                                message: format!("Unknown method '{}' for string", method_call.method),
+                            }),
+                        };
+                        if arg_types.len() != expected_args.len() {
+                            return Err(SemanticError::InvalidOperation {
+                                location: SourceLocation::unknown(),
+                                message: format!(
+                                    "{} expects {} arguments, got {}",
+                                    method_call.method,
+                                    expected_args.len(),
+                                    arg_types.len()
+                                ),
+                            });
+                        }
+                        for (expected, found) in expected_args.iter().zip(&arg_types) {
+                            if !self.types_compatible(expected, found) {
+                                return Err(SemanticError::TypeMismatch {
+                                    location: SourceLocation::unknown(),
+                                    expected: expected.clone(),
+                                    found: found.clone(),
+                                });
+                            }
+                        }
+                        Ok(return_type)
+                    }
+                    ChifType::Int => {
+                        match method_call.method.as_str() {
+                            "to_hex_str" | "to_hex_str_lower" | "to_binary_str" => {
+                                if !arg_types.is_empty() {
+                                    return Err(SemanticError::InvalidOperation {
+                                        location: SourceLocation::unknown(),
+                                        message: format!("{} expects no arguments", method_call.method),
+                                    });
+                                }
+                                Ok(ChifType::Str)
+                            }
+                            _ => Err(SemanticError::InvalidOperation {
+                                location: SourceLocation::unknown(),
+                                message: format!("Unknown method '{}' for int", method_call.method),
+                            }),
+                        }
+                    }
+                    ChifType::Map(key_type, _) => {
+                        let return_type = match method_call.method.as_str() {
+                            "len" => ChifType::Int,
+                            "keys" => ChifType::List(key_type, vec![0]),
+                            _ => return Err(SemanticError::InvalidOperation {
+                                location: SourceLocation::unknown(),
+                                message: format!("Unknown method '{}' for map", method_call.method),
+                            }),
+                        };
+                        if !arg_types.is_empty() {
+                            return Err(SemanticError::InvalidOperation {
+                                location: SourceLocation::unknown(),
+                                message: format!("{} expects no arguments", method_call.method),
+                            });
+                        }
+                        Ok(return_type)
+                    }
+                    // Тип объекта неизвестен статически (значение из пустого литерала):
+                    // метод и аргументы проверит интерпретатор по самому значению
+                    ChifType::Nil => Self::dynamic_method_type(&method_call.method).ok_or_else(|| {
+                        SemanticError::InvalidOperation {
+                            location: SourceLocation::unknown(),
+                            message: format!("Unknown method '{}'", method_call.method),
+                        }
+                    }),
                     _ => Err(SemanticError::InvalidOperation {
                         location: SourceLocation::unknown(),
                         message: format!("Cannot call method '{}' on non-struct type {:?}", method_call.method, object_type),
@@ -1223,11 +2696,11 @@ impl SemanticAnalyzer {
                 }
                 
                 // Analyze first element to determine array type
-                let first_type = self.analyze_expression(&elements[0])?;
+                let first_type = self.analyze_value(&elements[0])?;
                 
                 // Check that all elements have the same type
                 for (_i, element) in elements.iter().enumerate().skip(1) {
-                    let element_type = self.analyze_expression(element)?;
+                    let element_type = self.analyze_value(element)?;
                     if !self.types_compatible(&first_type, &element_type) {
                         return Err(SemanticError::TypeMismatch {
                             location: SourceLocation::unknown(),
@@ -1251,52 +2724,58 @@ impl SemanticAnalyzer {
                     }
                 }
             }
+            Expression::MapLiteral(entries) => {
+                // Тип словаря — по первой паре; пустой `{}` подходит любому словарю
+                let Some(((first_key, first_value), rest)) = entries.split_first() else {
+                    return Ok(ChifType::Map(Box::new(ChifType::Nil), Box::new(ChifType::Nil)));
+                };
+                let key_type = self.analyze_value(first_key)?;
+                let value_type = self.analyze_value(first_value)?;
+                for (key, value) in rest {
+                    for (expected, expr) in [(&key_type, key), (&value_type, value)] {
+                        let found = self.analyze_value(expr)?;
+                        if !self.types_compatible(expected, &found) {
+                            return Err(SemanticError::TypeMismatch {
+                                location: SourceLocation::unknown(),
+                                expected: expected.clone(),
+                                found,
+                            });
+                        }
+                    }
+                }
+                Ok(ChifType::Map(Box::new(key_type), Box::new(value_type)))
+            }
             Expression::Index(index_access) => {
-                // Analyze the array expression
-                let array_type = self.analyze_expression(&index_access.object)?;
-                
-                // Analyze all index expressions
+                // `config["servers"][0]["host"]` — один Index с несколькими индексами:
+                // каждый снимает один уровень словаря, массива или списка
+                let mut indexed_type = self.analyze_expression(&index_access.object)?;
                 for index_expr in &index_access.indices {
                     let index_type = self.analyze_expression(index_expr)?;
-                    
-                    // Check that index is an integer
-                    if index_type != ChifType::Int {
-                        return Err(SemanticError::TypeMismatch {
+                    indexed_type = self.indexed_element_type(indexed_type, index_type)?;
+                }
+                Ok(indexed_type)
+            }
+            Expression::Reference(address_of) => {
+                // `&mut x` lets the callee change x, so x must be declared with var
+                if let (true, Expression::Identifier(name)) = (address_of.mutable, &*address_of.target) {
+                    if let Some(Symbol { symbol_type: SymbolType::Variable(_), is_mutable: false, .. }) = self.symbol_table.lookup_symbol(name) {
+                        return Err(SemanticError::InvalidOperation {
                             location: SourceLocation::unknown(),
-                            expected: ChifType::Int,
-                            found: index_type,
+                            message: format!("Cannot take &mut of immutable variable '{}'; declare it with var", name),
                         });
                     }
                 }
-                
-                // Check that object is an array and return element type
-                match array_type {
-                    ChifType::Array(element_type, dimensions) => {
-                        // For now, assume single-dimensional indexing
-                        if index_access.indices.len() == 1 {
-                            Ok(*element_type)
-                        } else {
-                            // Multi-dimensional indexing - return array of lower dimension
-                            let remaining_dims: Vec<usize> = dimensions.into_iter().skip(index_access.indices.len()).collect();
-                            if remaining_dims.is_empty() {
-                                Ok(*element_type)
-                            } else {
-                                Ok(ChifType::Array(element_type, remaining_dims))
-                            }
-                        }
-                    }
-                    ChifType::List(element_type, _) => Ok(*element_type),
-                    _ => Err(SemanticError::InvalidOperation {
-                        location: SourceLocation::unknown(),
-                        message: format!("Cannot index non-array type {:?}", array_type),
-                    }),
-                }
-            }
-            Expression::Reference(expr) => {
                 // Address-of operation (&expr) returns a pointer to the expression's type
-                let expr_type = self.analyze_expression(expr)?;
+                let expr_type = self.analyze_expression(&address_of.target)?;
                 Ok(ChifType::Pointer(Box::new(expr_type)))
             }
+            Expression::TupleLiteral(elements) => {
+                let mut element_types = Vec::new();
+                for element in elements {
+                    element_types.push(self.analyze_value(element)?);
+                }
+                Ok(ChifType::Tuple(element_types))
+            }
             Expression::Dereference(expr) => {
                 // Dereference operation (*expr) returns the type pointed to by the expression
                 let expr_type = self.analyze_expression(expr)?;
@@ -1308,10 +2787,67 @@ impl SemanticAnalyzer {
                     }),
                 }
             }
-            _ => {
-                // TODO: Handle other expression types
-                Ok(ChifType::Nil)
+        }
+    }
+    
+    fn is_collection(value_type: &ChifType) -> bool {
+        matches!(value_type, ChifType::Array(..) | ChifType::List(..) | ChifType::Map(..))
+    }
+    
+    /// Результат встроенного метода, когда тип объекта неизвестен: у методов
+    /// с одним именем он одинаков для всех типов, у которых они есть
+    fn dynamic_method_type(method: &str) -> Option<ChifType> {
+        let list_of = |element: ChifType| ChifType::List(Box::new(element), vec![0]);
+        Some(match method {
+            "len" | "byte_len" | "char_count" | "index_of" => ChifType::Int,
+            "trim" | "join" | "to_hex_str" | "to_hex_str_lower" | "to_binary_str" => ChifType::Str,
+            "chars" | "split" => list_of(ChifType::Str),
+            "bytes" => list_of(ChifType::Int),
+            "unique" | "keys" => list_of(ChifType::Nil),
+            "add" | "addAt" | "del" => ChifType::Nil,
+            _ => return None,
+        })
+    }
+    
+    /// Тип `container[index]`. Словарь индексируется ключом своего типа и даёт
+    /// значение; массив и список — целым и дают элемент, а у многомерного —
+    /// строку. Nil — тип, неизвестный статически (элемент пустого литерала)
+    fn indexed_element_type(&self, container: ChifType, index_type: ChifType) -> Result<ChifType, SemanticError> {
+        // `xs[0]` for a `&list[int]` parameter indexes the list it points to
+        let container = match container {
+            ChifType::Pointer(target) if Self::is_collection(&target) => *target,
+            container => container,
+        };
+        let expected_index = match &container {
+            ChifType::Map(key_type, _) => (**key_type).clone(),
+            _ => ChifType::Int,
+        };
+        let index_matches = match (&container, &expected_index) {
+            (ChifType::Nil, _) | (_, ChifType::Nil) => true,
+            (ChifType::Map(..), _) => self.types_compatible(&expected_index, &index_type),
+            _ => index_type == ChifType::Int,
+        };
+        if !index_matches {
+            return Err(SemanticError::TypeMismatch {
+                location: SourceLocation::unknown(),
+                expected: expected_index,
+                found: index_type,
+            });
+        }
+        match container {
+            ChifType::Map(_, value_type) => Ok(*value_type),
+            ChifType::Array(element_type, dimensions) if dimensions.len() > 1 => {
+                Ok(ChifType::Array(element_type, dimensions[1..].to_vec()))
+            }
+            ChifType::List(element_type, dimensions) if dimensions.len() > 1 => {
+                Ok(ChifType::List(element_type, dimensions[1..].to_vec()))
             }
+            ChifType::Array(element_type, _) | ChifType::List(element_type, _) => Ok(*element_type),
+            ChifType::Nil => Ok(ChifType::Nil),
+            other => Err(SemanticError::InvalidOperation {
+                location: SourceLocation::unknown(),
+                message: format!("Cannot index non-array type {:?}", other),
+            }),
         }
     }
     
@@ -1335,6 +2871,7 @@ impl SemanticAnalyzer {
             ],
             return_type: ChifType::Int,
             is_mutating: false,  // Встроенные функции не мутируют
+            deprecated: None,
         };
         let randi_symbol = Symbol {
             name: "randi".to_string(),
@@ -1352,6 +2889,7 @@ impl SemanticAnalyzer {
             ],
             return_type: ChifType::Float,
             is_mutating: false,  // Встроенные функции не мутируют
+            deprecated: None,
         };
         let randf_symbol = Symbol {
             name: "randf".to_string(),
@@ -1369,6 +2907,7 @@ impl SemanticAnalyzer {
             ],
             return_type: ChifType::Str,
             is_mutating: false,  // Встроенные функции не мутируют
+            deprecated: None,
         };
         let rands_symbol = Symbol {
             name: "rands".to_string(),
@@ -1378,8 +2917,110 @@ impl SemanticAnalyzer {
         };
         self.symbol_table.define_symbol(rands_symbol)?;
         
+        // rando() возвращает случайное число в диапазоне [0.0, 1.0)
+        let rando_signature = FunctionSignature {
+            name: "rando".to_string(),
+            parameters: vec![],
+            return_type: ChifType::Float,
+            is_mutating: false,
+            deprecated: None,
+        };
+        let rando_symbol = Symbol {
+            name: "rando".to_string(),
+            symbol_type: SymbolType::Function(rando_signature),
+            location: SourceLocation::unknown(),
+            is_mutable: false,
+        };
+        self.symbol_table.define_symbol(rando_symbol)?;
+        
+        // rand_seed(seed) делает последовательность случайных чисел воспроизводимой
+        let rand_seed_signature = FunctionSignature {
+            name: "rand_seed".to_string(),
+            parameters: vec![
+                Parameter { name: "seed".to_string(), param_type: ChifType::Int, is_reference: false },
+            ],
+            return_type: ChifType::Nil,
+            is_mutating: false,
+            deprecated: None,
+        };
+        let rand_seed_symbol = Symbol {
+            name: "rand_seed".to_string(),
+            symbol_type: SymbolType::Function(rand_seed_signature),
+            location: SourceLocation::unknown(),
+            is_mutable: false,
+        };
+        self.symbol_table.define_symbol(rand_seed_symbol)?;
+        
+        // intern(s) возвращает ту же строку; равные интернированные строки хранятся один раз
+        let intern_signature = FunctionSignature {
+            name: "intern".to_string(),
+            parameters: vec![
+                Parameter { name: "s".to_string(), param_type: ChifType::Str, is_reference: false },
+            ],
+            return_type: ChifType::Str,
+            is_mutating: false,
+            deprecated: None,
+        };
+        let intern_symbol = Symbol {
+            name: "intern".to_string(),
+            symbol_type: SymbolType::Function(intern_signature),
+            location: SourceLocation::unknown(),
+            is_mutable: false,
+        };
+        self.symbol_table.define_symbol(intern_symbol)?;
+        
+        // fdiv(a, b) делит int на int без отбрасывания дробной части
+        let fdiv_signature = FunctionSignature {
+            name: "fdiv".to_string(),
+            parameters: vec![
+                Parameter { name: "a".to_string(), param_type: ChifType::Int, is_reference: false },
+                Parameter { name: "b".to_string(), param_type: ChifType::Int, is_reference: false },
+            ],
+            return_type: ChifType::Float,
+            is_mutating: false,
+            deprecated: None,
+        };
+        let fdiv_symbol = Symbol {
+            name: "fdiv".to_string(),
+            symbol_type: SymbolType::Function(fdiv_signature),
+            location: SourceLocation::unknown(),
+            is_mutable: false,
+        };
+        self.symbol_table.define_symbol(fdiv_symbol)?;
+
+        // Арифметика int с явным поведением при переполнении: wrapping_*
+        // отбрасывает старшие биты, saturating_* останавливается на границе int,
+        // checked_add возвращает (сумма, без_переполнения)
+        let overflow_builtins = [
+            ("wrapping_add", ChifType::Int),
+            ("wrapping_sub", ChifType::Int),
+            ("wrapping_mul", ChifType::Int),
+            ("saturating_add", ChifType::Int),
+            ("saturating_sub", ChifType::Int),
+            ("checked_add", ChifType::Tuple(vec![ChifType::Int, ChifType::Bool])),
+        ];
+        for (name, return_type) in overflow_builtins {
+            let signature = FunctionSignature {
+                name: name.to_string(),
+                parameters: vec![
+                    Parameter { name: "a".to_string(), param_type: ChifType::Int, is_reference: false },
+                    Parameter { name: "b".to_string(), param_type: ChifType::Int, is_reference: false },
+                ],
+                return_type,
+                is_mutating: false,
+                deprecated: None,
+            };
+            self.symbol_table.define_symbol(Symbol {
+                name: name.to_string(),
+                symbol_type: SymbolType::Function(signature),
+                location: SourceLocation::unknown(),
+                is_mutable: false,
+            })?;
+        }
+
         // Добавляем функции конвертации типов
-        // toInt() может принимать строку или число с плавающей точкой
+        // toInt() может принимать строку или число с плавающей точкой: вариант
+        // от str регистрируется как дополнительная сигнатура
         let int_signature = FunctionSignature {
             name: "toInt".to_string(),
             parameters: vec![
@@ -1387,6 +3028,7 @@ impl SemanticAnalyzer {
             ],
             return_type: ChifType::Int,
             is_mutating: false,
+            deprecated: None,
         };
         let int_symbol = Symbol {
             name: "toInt".to_string(),
@@ -1403,14 +3045,9 @@ impl SemanticAnalyzer {
             ],
             return_type: ChifType::Int,
             is_mutating: false,
+            deprecated: None,
         };
-        let int_str_symbol = Symbol {
-            name: "toInt".to_string(),
-            symbol_type: SymbolType::Function(int_str_signature),
-            location: SourceLocation::unknown(),
-            is_mutable: false,
-        };
-        self.symbol_table.define_symbol(int_str_symbol)?;
+        self.builtin_overloads.entry("toInt".to_string()).or_default().push(int_str_signature);
         
         // toFloat() может принимать строку или целое число
         let float_signature = FunctionSignature {
@@ -1420,6 +3057,7 @@ impl SemanticAnalyzer {
             ],
             return_type: ChifType::Float,
             is_mutating: false,
+            deprecated: None,
         };
         let float_symbol = Symbol {
             name: "toFloat".to_string(),
@@ -1436,46 +3074,28 @@ impl SemanticAnalyzer {
             ],
             return_type: ChifType::Float,
             is_mutating: false,
+            deprecated: None,
         };
-        let float_str_symbol = Symbol {
-            name: "toFloat".to_string(),
-            symbol_type: SymbolType::Function(float_str_signature),
-            location: SourceLocation::unknown(),
-            is_mutable: false,
-        };
-        self.symbol_table.define_symbol(float_str_symbol)?;
-        
-        // toStr() может принимать целое число или число с плавающей точкой
-        let str_int_signature = FunctionSignature {
-            name: "toStr".to_string(),
-            parameters: vec![
-                Parameter { name: "value".to_string(), param_type: ChifType::Int, is_reference: false },
-            ],
-            return_type: ChifType::Str,
-            is_mutating: false,
-        };
-        let str_int_symbol = Symbol {
-            name: "toStr".to_string(),
-            symbol_type: SymbolType::Function(str_int_signature),
-            location: SourceLocation::unknown(),
-            is_mutable: false,
-        };
-        self.symbol_table.define_symbol(str_int_symbol)?;
+        self.builtin_overloads.entry("toFloat".to_string()).or_default().push(float_str_signature);
         
-        let str_float_signature = FunctionSignature {
+        // toStr() принимает целое число или число с плавающей точкой: параметр
+        // float, потому что int к нему приводится
+        let str_signature = FunctionSignature {
             name: "toStr".to_string(),
             parameters: vec![
                 Parameter { name: "value".to_string(), param_type: ChifType::Float, is_reference: false },
             ],
             return_type: ChifType::Str,
             is_mutating: false,
+            deprecated: None,
         };
-        let str_float_symbol = Symbol {
+        let str_symbol = Symbol {
             name: "toStr".to_string(),
-            symbol_type: SymbolType::Function(str_float_signature),
+            symbol_type: SymbolType::Function(str_signature),
             location: SourceLocation::unknown(),
             is_mutable: false,
         };
+        self.symbol_table.define_symbol(str_symbol)?;
         let float_signature = FunctionSignature {
             name: "float".to_string(),
             parameters: vec![
@@ -1483,6 +3103,7 @@ impl SemanticAnalyzer {
             ],
             return_type: ChifType::Float,
             is_mutating: false,
+            deprecated: None,
         };
         let float_symbol = Symbol {
             name: "float".to_string(),
@@ -1500,6 +3121,7 @@ impl SemanticAnalyzer {
             ],
             return_type: ChifType::Str,
             is_mutating: false,
+            deprecated: None,
         };
         let str_symbol = Symbol {
             name: "str".to_string(),
@@ -1519,46 +3141,292 @@ impl SemanticAnalyzer {
         
         self.symbol_table.define_symbol(http_symbol)?;
         
+        // http_get(url), http_post(url, body), http_put(url, body), http_delete(url) -> HttpResponse
+        let response = http_response_struct();
+        let response_type = ChifType::Struct(response.name.clone());
+        self.symbol_table.define_symbol(Symbol {
+            name: response.name.clone(),
+            symbol_type: SymbolType::Struct(StructDefinition { name: response.name, fields: response.fields }),
+            location: SourceLocation::unknown(),
+            is_mutable: false,
+        })?;
+        for (name, takes_body) in [("http_get", false), ("http_post", true), ("http_put", true), ("http_delete", false)] {
+            let mut parameters = vec![Parameter { name: "url".to_string(), param_type: ChifType::Str, is_reference: false }];
+            if takes_body {
+                parameters.push(Parameter { name: "body".to_string(), param_type: ChifType::Str, is_reference: false });
+            }
+            self.symbol_table.define_symbol(Symbol {
+                name: name.to_string(),
+                symbol_type: SymbolType::Function(FunctionSignature {
+                    name: name.to_string(),
+                    parameters,
+                    return_type: response_type.clone(),
+                    is_mutating: false,
+                    deprecated: None,
+                }),
+                location: SourceLocation::unknown(),
+                is_mutable: false,
+            })?;
+        }
+        
+        // Add memory object 'mem'
+        let mem_symbol = Symbol {
+            name: "mem".to_string(),
+            symbol_type: SymbolType::Variable(ChifType::Struct("Memory".to_string())),
+            location: SourceLocation::unknown(),
+            is_mutable: false,
+        };
+        
+        self.symbol_table.define_symbol(mem_symbol)?;
+        
+        // Command-line arguments 'args'
+        self.symbol_table.define_symbol(Symbol {
+            name: "args".to_string(),
+            symbol_type: SymbolType::Variable(ChifType::Struct("ProgramArgs".to_string())),
+            location: SourceLocation::unknown(),
+            is_mutable: false,
+        })?;
+        
+        // Platform and language version: 'os' and 'rono'
+        for (name, type_name) in [("os", "Os"), ("rono", "RonoInfo")] {
+            self.symbol_table.define_symbol(Symbol {
+                name: name.to_string(),
+                symbol_type: SymbolType::Variable(ChifType::Struct(type_name.to_string())),
+                location: SourceLocation::unknown(),
+                is_mutable: false,
+            })?;
+        }
+        
         Ok(())
     }
     
     fn process_import(&mut self, import: &ImportStatement) -> Result<(), SemanticError> {
-        // Add .rono extension if not present
-        let file_path = if import.path.ends_with(".rono") {
-            import.path.clone()
-        } else {
-            format!("{}.rono", import.path)
+        let file_path = import.file_path();
+        let location = SourceLocation::new(SourceLocation::unknown().file, import.line, 1);
+        let module_name = import.module_name().map_err(|message| SemanticError::InvalidOperation {
+            location: location.clone(),
+            message,
+        })?;
+        
+        // `foo` и `../dir/foo` указывают на один файл — ключ кэша канонический путь
+        let source_dir = self.source_dir.as_deref();
+        let canonical_path = match crate::resolve_import(import, source_dir) {
+            Ok(path) => path,
+            Err(message) => match crate::import_candidate(import, source_dir) {
+                // Файл рабочей области может существовать только в памяти редактора
+                Ok(candidate) if self.module_cache.contains_key(&candidate) => candidate,
+                _ => return Err(SemanticError::InvalidOperation { location, message }),
+            },
         };
+        if import.scheme() == ImportScheme::Bare {
+            self.warnings.push(Warning::BareImportPath { path: import.path.clone(), location });
+        }
         
-        // Read the imported file
-        let source = fs::read_to_string(&file_path).map_err(|_| {
-            SemanticError::InvalidOperation {
-                location: SourceLocation::unknown(),
-                message: format!("Could not read module file: {}", file_path),
+        // Тот же файл под тем же именем уже импортирован: определяются только
+        // символы, которых ещё нет
+        let previous_names = match self.modules.get(&module_name) {
+            Some(module) if module.path == canonical_path => match &module.names {
+                None => return Ok(()),
+                Some(names) => names.clone(),
+            },
+            _ => Vec::new(),
+        };
+        
+        let cached = match self.module_cache.get(&canonical_path) {
+            Some(module_info) => module_info.clone(),
+            None => {
+                let program = crate::load_module(&file_path, &canonical_path).map_err(|message| {
+                    SemanticError::InvalidOperation {
+                        location: SourceLocation::unknown(),
+                        message,
+                    }
+                })?;
+                let module_info = Self::module_info(&program, canonical_path.clone());
+                // Сигнатуры кэшируются до проверки тел: модуль, который
+                // импортирует этот в ответ, найдёт их и не зациклится
+                self.module_cache.insert(canonical_path.clone(), module_info.clone());
+                self.analyze_module(&program, &canonical_path)?;
+                self.parsed_modules.insert(canonical_path, program);
+                module_info
             }
-        })?;
+        };
         
-        // Parse the imported file
-        use crate::{lexer::Lexer, parser::Parser};
-        let mut lexer = Lexer::new(&source);
-        let tokens = lexer.tokenize().map_err(|e| {
-            SemanticError::InvalidOperation {
-                location: SourceLocation::unknown(),
-                message: format!("Failed to tokenize module {}: {}", file_path, e),
+        if let Some(names) = &import.names {
+            for name in names {
+                if let Some(hint) = cached.private_hint(name) {
+                    return Err(SemanticError::UndefinedSymbol {
+                        symbol: name.clone(),
+                        location: SourceLocation::new(SourceLocation::unknown().file, import.line, 1),
+                        hint: Some(hint),
+                    });
+                }
+                if !cached.functions.contains_key(name) && !cached.structs.contains_key(name) {
+                    let available = cached.functions.keys().chain(cached.structs.keys())
+                        .filter(|available| cached.is_exported(available))
+                        .map(String::as_str);
+                    let suggestion = suggest_name(name, available)
+                        .map(|closest| format!("; did you mean '{}'?", closest))
+                        .unwrap_or_default();
+                    return Err(SemanticError::InvalidOperation {
+                        location: SourceLocation::new(SourceLocation::unknown().file, import.line, 1),
+                        message: format!("Module '{}' has no function or struct '{}'{}", import.path, name, suggestion),
+                    });
+                }
             }
-        })?;
+        }
+        
+        let selected = |name: &str| {
+            let listed = match &import.names {
+                Some(names) => names.iter().any(|listed| listed == name),
+                None => true,
+            };
+            listed && cached.is_exported(name) && !previous_names.iter().any(|previous| previous == name)
+        };
+        self.define_module_symbols(&module_name, &cached, selected)?;
+        
+        // Store module information
+        let names = import.names.as_ref().map(|names| {
+            let mut merged = previous_names.clone();
+            merged.extend(names.iter().filter(|name| !previous_names.contains(name)).cloned());
+            merged
+        });
+        let module_info = ModuleInfo {
+            name: module_name.clone(),
+            names,
+            import_line: import.line,
+            ..cached
+        };
+        self.modules.insert(module_name, module_info);
         
-        let mut parser = Parser::new(tokens);
-        let imported_program = parser.parse().map_err(|e| {
-            SemanticError::InvalidOperation {
+        Ok(())
+    }
+    
+    /// Проверяет тела функций модуля отдельным анализатором с общими кэшами:
+    /// модули, которые импортирует сам модуль, тоже проверяются и разбираются
+    /// один раз. Ошибка и предупреждения получают путь файла модуля
+    fn analyze_module(&mut self, program: &Program, canonical_path: &Path) -> Result<(), SemanticError> {
+        if self.module_diagnostics.contains_key(canonical_path) {
+            return Ok(());
+        }
+        self.module_diagnostics.insert(canonical_path.to_path_buf(), Vec::new());
+        *self.module_analysis_counts.entry(canonical_path.to_path_buf()).or_insert(0) += 1;
+        
+        let mut module_analyzer = SemanticAnalyzer::new();
+        module_analyzer.lang_version = self.lang_version;
+        // Прелюдия, импортированная как `pkg:prelude`, не подключает сама себя
+        module_analyzer.prelude = self.prelude && canonical_path != Path::new("pkg:prelude");
+        module_analyzer.source_dir = canonical_path.parent().map(Path::to_path_buf);
+        module_analyzer.module_cache = std::mem::take(&mut self.module_cache);
+        module_analyzer.parsed_modules = std::mem::take(&mut self.parsed_modules);
+        module_analyzer.module_diagnostics = std::mem::take(&mut self.module_diagnostics);
+        module_analyzer.module_analysis_counts = std::mem::take(&mut self.module_analysis_counts);
+        let result = module_analyzer.check_program(program);
+        self.module_cache = module_analyzer.module_cache;
+        self.parsed_modules = module_analyzer.parsed_modules;
+        self.module_diagnostics = module_analyzer.module_diagnostics;
+        self.module_analysis_counts = module_analyzer.module_analysis_counts;
+        
+        let file = canonical_path.display().to_string();
+        result.map_err(|error| error.in_file(&file))?;
+        // Модуль загружается без инициализации, поэтому его переменные некому вычислить
+        if let Some(Item::Global(var_decl)) = program.items.iter().find(|item| matches!(item, Item::Global(_))) {
+            let error = SemanticError::InvalidOperation {
                 location: SourceLocation::unknown(),
-                message: format!("Failed to parse module {}: {}", file_path, e),
+                message: format!(
+                    "Global variable '{}' is not allowed in an imported module; pass the value to its functions instead",
+                    var_decl.name
+                ),
+            };
+            return Err(error.in_file(&file));
+        }
+        // Предупреждения вложенных модулей уже помечены своими файлами
+        let mut warnings = module_analyzer.warnings;
+        for warning in &mut warnings {
+            warning.in_file(&file);
+        }
+        let own = warnings.iter()
+            .filter(|warning| warning.location().file == file)
+            .cloned()
+            .collect();
+        self.module_diagnostics.insert(canonical_path.to_path_buf(), own);
+        self.warnings.extend(warnings);
+        Ok(())
+    }
+    
+    /// Снимок глобальной области после анализа: функции (вместе с методами и
+    /// функциями модулей), структуры, встроенные объекты и импортированные
+    /// модули, по имени
+    pub fn symbols(&self) -> Vec<SymbolInfo> {
+        let mut symbols: Vec<SymbolInfo> = self.symbol_table.scopes[0].symbols.values()
+            .map(|symbol| {
+                let (kind, detail) = match &symbol.symbol_type {
+                    SymbolType::Function(signature) => (SymbolKind::Function, render_signature(signature)),
+                    SymbolType::Struct(definition) => (SymbolKind::Struct, render_struct(definition)),
+                    SymbolType::Variable(var_type) => (SymbolKind::Variable, var_type.to_string()),
+                    SymbolType::Module(module) => (SymbolKind::Module, module.path.display().to_string()),
+                };
+                let origin = if let Some(path) = self.symbol_files.get(&symbol.name) {
+                    SymbolOrigin::Module(path.clone())
+                } else if self.builtin_names.contains(&symbol.name) {
+                    SymbolOrigin::Builtin
+                } else if self.prelude_names.contains(&symbol.name) {
+                    SymbolOrigin::Prelude
+                } else {
+                    SymbolOrigin::Program
+                };
+                SymbolInfo {
+                    name: self.source_name(&symbol.name),
+                    symbol: symbol.name.clone(),
+                    kind,
+                    detail,
+                    origin,
+                }
+            })
+            .collect();
+        symbols.extend(self.modules.iter().map(|(name, module)| SymbolInfo {
+            name: name.clone(),
+            symbol: name.clone(),
+            kind: SymbolKind::Module,
+            detail: module.path.display().to_string(),
+            origin: SymbolOrigin::Program,
+        }));
+        symbols.sort_by(|a, b| (&a.name, a.kind, &a.symbol).cmp(&(&b.name, b.kind, &b.symbol)));
+        symbols
+    }
+    
+    /// Имя символа, как его пишут в программе: `u_helper` — `u.helper`,
+    /// `Point_sum` — `Point.sum`, `geo_Point_sum` — `geo.Point.sum`
+    fn source_name(&self, symbol: &str) -> String {
+        let mut modules: Vec<&String> = self.modules.keys().collect();
+        // Из вложенных префиксов (`a` и `a_b`) выбирается более длинный
+        modules.sort_by_key(|module| std::cmp::Reverse(module.len()));
+        for module in modules {
+            if let Some(rest) = symbol.strip_prefix(module.as_str()).and_then(|rest| rest.strip_prefix('_')) {
+                return format!("{}.{}", module, self.source_name(rest));
             }
-        })?;
-        
+        }
+        for (index, _) in symbol.match_indices('_') {
+            let (owner, method) = (&symbol[..index], &symbol[index + 1..]);
+            if matches!(self.symbol_table.lookup_global(owner).map(|owner| &owner.symbol_type), Some(SymbolType::Struct(_))) {
+                return format!("{}.{}", owner, method);
+            }
+        }
+        symbol.to_string()
+    }
+    
+    /// Сколько раз за этот анализ проверялись тела функций модуля `path`
+    /// (канонический путь): с кэшем — не больше одного
+    pub fn analysis_count(&self, path: &Path) -> usize {
+        self.module_analysis_counts.get(path).copied().unwrap_or(0)
+    }
+    
+    /// Собирает сигнатуры функций, структур и методов разобранного модуля
+    pub(crate) fn module_info(imported_program: &Program, canonical_path: PathBuf) -> ModuleInfo {
         // Extract functions and structs from imported module
         let mut module_functions = HashMap::new();
         let mut module_structs = HashMap::new();
+        let mut module_methods = HashMap::new();
+        let mut method_owners = HashMap::new();
         
         for item in &imported_program.items {
             match item {
@@ -1568,104 +3436,227 @@ impl SemanticAnalyzer {
                         parameters: func.params.clone(),
                         return_type: func.return_type.clone().unwrap_or(ChifType::Nil),
                         is_mutating: false,  // Импортированные функции по умолчанию не мутируют
+                        deprecated: func.deprecated.clone(),
                     };
-                    module_functions.insert(func.name.clone(), signature.clone());
-                    
-                    // Add function to global symbol table with module prefix
-                    let module_name = import.alias.clone().unwrap_or_else(|| {
-                        std::path::Path::new(&import.path)
-                            .file_stem()
-                            .unwrap()
-                            .to_string_lossy()
-                            .to_string()
-                    });
-                    
-                    let qualified_name = format!("{}_{}", module_name, func.name);
-                    let symbol = Symbol {
-                        name: qualified_name,
-                        symbol_type: SymbolType::Function(signature),
-                        location: SourceLocation::unknown(),
-                        is_mutable: false,
-                    };
-                    
-                    self.symbol_table.define_symbol(symbol)?;
+                    module_functions.insert(func.name.clone(), signature);
                 }
                 Item::Struct(struct_def) => {
                     let struct_definition = StructDefinition {
                         name: struct_def.name.clone(),
                         fields: struct_def.fields.clone(),
                     };
-                    module_structs.insert(struct_def.name.clone(), struct_definition.clone());
-                    
-                    // Add struct to global symbol table with module prefix
-                    let module_name = import.alias.clone().unwrap_or_else(|| {
-                        std::path::Path::new(&import.path)
-                            .file_stem()
-                            .unwrap()
-                            .to_string_lossy()
-                            .to_string()
-                    });
-                    
-                    let qualified_name = format!("{}_{}", module_name, struct_def.name);
-                    let symbol = Symbol {
-                        name: qualified_name,
-                        symbol_type: SymbolType::Struct(struct_definition),
-                        location: SourceLocation::unknown(),
-                        is_mutable: false,
-                    };
-                    
-                    self.symbol_table.define_symbol(symbol)?;
+                    module_structs.insert(struct_def.name.clone(), struct_definition);
                 }
                 Item::StructImpl(impl_block) => {
-                    // Add methods to symbol table with module and struct prefix
-                    let module_name = import.alias.clone().unwrap_or_else(|| {
-                        std::path::Path::new(&import.path)
-                            .file_stem()
-                            .unwrap()
-                            .to_string_lossy()
-                            .to_string()
-                    });
-                    
                     for method in &impl_block.methods {
-                        let method_name = format!("{}_{}_{}", module_name, impl_block.struct_name, method.name);
+                        let method_name = format!("{}_{}", impl_block.struct_name, method.name);
                         let signature = FunctionSignature {
                             name: method_name.clone(),
                             parameters: method.params.clone(),
                             return_type: method.return_type.clone().unwrap_or(ChifType::Nil),
                             is_mutating: false,  // Методы импортированных структур по умолчанию не мутируют
+                            deprecated: None,
                         };
-                        
-                        let symbol = Symbol {
-                            name: method_name,
-                            symbol_type: SymbolType::Function(signature),
-                            location: SourceLocation::unknown(),
-                            is_mutable: false,
-                        };
-                        
-                        self.symbol_table.define_symbol(symbol)?;
+                        method_owners.insert(method_name.clone(), impl_block.struct_name.clone());
+                        module_methods.insert(method_name, signature);
                     }
                 }
                 _ => {} // Ignore nested imports for now
             }
         }
         
-        // Store module information
-        let module_name = import.alias.clone().unwrap_or_else(|| {
-            std::path::Path::new(&import.path)
-                .file_stem()
-                .unwrap()
-                .to_string_lossy()
-                .to_string()
-        });
+        let name = canonical_path.file_stem()
+            .map(|stem| stem.to_string_lossy().to_string())
+            .unwrap_or_default();
+        ModuleInfo {
+            name,
+            path: canonical_path,
+            functions: Rc::new(module_functions),
+            structs: Rc::new(module_structs),
+            methods: Rc::new(module_methods),
+            method_owners: Rc::new(method_owners),
+            names: None,
+            import_line: 0,
+            exports: imported_program.exported_names().map(Rc::from),
+        }
+    }
+    
+    /// Добавляет выбранные символы модуля в глобальную таблицу с префиксом
+    /// `module_name`; методы структуры выбираются вместе с ней
+    fn define_module_symbols(
+        &mut self,
+        module_name: &str,
+        module_info: &ModuleInfo,
+        selected: impl Fn(&str) -> bool,
+    ) -> Result<(), SemanticError> {
+        for (name, signature) in module_info.functions.iter().filter(|(name, _)| selected(name)) {
+            self.define_imported_symbol(&module_info.path, Symbol {
+                name: format!("{}_{}", module_name, name),
+                symbol_type: SymbolType::Function(signature.clone()),
+                location: SourceLocation::unknown(),
+                is_mutable: false,
+            })?;
+        }
         
-        let module_info = ModuleInfo {
-            name: module_name.clone(),
-            functions: module_functions,
-            structs: module_structs,
+        // Структуры и их методы видны и без префикса модуля: значение структуры одно
+        // и то же, из какого бы файла оно ни пришло, и `fn_for` в другом файле
+        // добавляет методы к той же структуре. Повторный импорт того же файла
+        // (под другим именем или с другим `use`) их не переопределяет
+        let reimport = self.modules.values().any(|module| module.path == module_info.path);
+        
+        for (name, struct_definition) in module_info.structs.iter().filter(|(name, _)| selected(name)) {
+            self.define_imported_symbol(&module_info.path, Symbol {
+                name: format!("{}_{}", module_name, name),
+                symbol_type: SymbolType::Struct(struct_definition.clone()),
+                location: SourceLocation::unknown(),
+                is_mutable: false,
+            })?;
+            if !(reimport && self.symbol_table.lookup_symbol(name).is_some()) {
+                self.define_imported_symbol(&module_info.path, Symbol {
+                    name: name.clone(),
+                    symbol_type: SymbolType::Struct(struct_definition.clone()),
+                    location: SourceLocation::unknown(),
+                    is_mutable: false,
+                })?;
+            }
+        }
+        
+        // Методы, которые модуль добавил структуре другого модуля, тоже видны:
+        // `fn_for` в двух модулях, импортирующих общий, не может задать один метод дважды
+        for (name, signature) in module_info.methods.iter() {
+            let Some(struct_name) = module_info.method_owners.get(name).filter(|struct_name| selected(struct_name)) else {
+                continue;
+            };
+            let method = &name[struct_name.len() + 1..];
+            
+            let method_name = format!("{}_{}", module_name, name);
+            let mut prefixed = signature.clone();
+            prefixed.name = method_name.clone();
+            self.define_imported_symbol(&module_info.path, Symbol {
+                name: method_name,
+                symbol_type: SymbolType::Function(prefixed),
+                location: SourceLocation::unknown(),
+                is_mutable: false,
+            })?;
+            
+            if self.symbol_table.lookup_symbol(name).is_some() {
+                if reimport {
+                    continue;
+                }
+                return Err(self.duplicate_method(struct_name, method, Some(&module_info.path)));
+            }
+            self.define_imported_symbol(&module_info.path, Symbol {
+                name: name.clone(),
+                symbol_type: SymbolType::Function(signature.clone()),
+                location: SourceLocation::unknown(),
+                is_mutable: false,
+            })?;
+        }
+        
+        Ok(())
+    }
+    
+    /// Определяет символ, пришедший из модуля `path`
+    fn define_imported_symbol(&mut self, path: &Path, symbol: Symbol) -> Result<(), SemanticError> {
+        let name = symbol.name.clone();
+        self.symbol_table.define_symbol(symbol)?;
+        self.symbol_files.insert(name, path.to_path_buf());
+        Ok(())
+    }
+    
+    /// Два блока `fn_for`, в том числе из разных файлов, определяют один метод
+    /// структуры; `file` — модуль второго определения
+    fn duplicate_method(&self, struct_name: &str, method: &str, file: Option<&Path>) -> SemanticError {
+        let first = self.symbol_files.get(&format!("{}_{}", struct_name, method));
+        SemanticError::InvalidOperation {
+            location: SourceLocation::unknown(),
+            message: duplicate_method_message(struct_name, method, first.map(PathBuf::as_path), file),
+        }
+    }
+    
+    /// Вызов ассоциированной функции `Point.new(...)`: метод структуры без self,
+    /// вызываемый по имени типа
+    fn analyze_associated_call(&mut self, struct_name: &str, method_call: &MethodCall) -> Result<ChifType, SemanticError> {
+        let function_name = format!("{}_{}", struct_name, method_call.method);
+        let signature = match self.symbol_table.lookup_symbol(&function_name).map(|symbol| &symbol.symbol_type) {
+            Some(SymbolType::Function(signature)) => signature.clone(),
+            _ => return Err(SemanticError::UndefinedSymbol {
+                symbol: function_name,
+                location: SourceLocation::unknown(),
+                hint: None,
+            }),
         };
         
-        self.modules.insert(module_name, module_info);
+        if signature.parameters.iter().any(|param| param.name == "self") {
+            return Err(SemanticError::InvalidOperation {
+                location: SourceLocation::unknown(),
+                message: format!(
+                    "Method '{}' takes self and must be called on a value of type '{}'",
+                    method_call.method, struct_name
+                ),
+            });
+        }
         
+        self.check_call_arguments(&format!("{}.{}", struct_name, method_call.method), &signature, &method_call.args)?;
+        Ok(signature.return_type)
+    }
+    
+    /// Модуль и функция, если `name` — внутреннее имя `utils_helper` функции
+    /// импортированного модуля
+    fn module_function_of(&self, name: &str) -> Option<(String, String)> {
+        self.modules.iter().find_map(|(module_name, module)| {
+            let function = name.strip_prefix(module_name.as_str())?.strip_prefix('_')?;
+            module.functions.contains_key(function).then(|| (module_name.clone(), function.to_string()))
+        })
+    }
+    
+    /// Вызов функции импортированного модуля `utils.parse_config(...)`
+    fn analyze_module_call(&mut self, module_name: &str, method_call: &MethodCall) -> Result<ChifType, SemanticError> {
+        let display_name = format!("{}.{}", module_name, method_call.method);
+        let function_name = format!("{}_{}", module_name, method_call.method);
+        let signature = match self.symbol_table.lookup_symbol(&function_name).map(|symbol| &symbol.symbol_type) {
+            Some(SymbolType::Function(signature)) => signature.clone(),
+            _ => {
+                // Функция есть в модуле, но не перечислена в `use (...)` — указываем на строку import
+                let module = &self.modules[module_name];
+                let hint = module.private_hint(&method_call.method);
+                let location = if module.names.is_some() && module.functions.contains_key(&method_call.method) {
+                    SourceLocation::new(SourceLocation::unknown().file, module.import_line, 1)
+                } else {
+                    SourceLocation::unknown()
+                };
+                return Err(SemanticError::UndefinedSymbol { symbol: display_name, location, hint });
+            }
+        };
+        
+        self.check_call_arguments(&display_name, &signature, &method_call.args)?;
+        Ok(signature.return_type)
+    }
+    
+    fn check_call_arguments(&mut self, display_name: &str, signature: &FunctionSignature, args: &[Expression]) -> Result<(), SemanticError> {
+        if args.len() != signature.parameters.len() {
+            return Err(SemanticError::InvalidOperation {
+                location: SourceLocation::unknown(),
+                message: format!(
+                    "Function '{}' expects {} arguments, got {}",
+                    display_name,
+                    signature.parameters.len(),
+                    args.len()
+                ),
+            });
+        }
+        
+        for (arg, param) in args.iter().zip(&signature.parameters) {
+            let arg_type = self.analyze_value(arg)?;
+            if !self.types_compatible(&param.param_type, &arg_type) {
+                return Err(SemanticError::TypeMismatch {
+                    location: SourceLocation::unknown(),
+                    expected: param.param_type.clone(),
+                    found: arg_type,
+                });
+            }
+            Self::check_integer_division(&param.param_type, arg, &arg_type)?;
+        }
         Ok(())
     }
     
@@ -1712,6 +3703,7 @@ impl SemanticAnalyzer {
                 // Проверяем тело цикла
                 self.analyze_block_for_self_mutation(&for_stmt.body)
             }
+            Statement::ForIn(for_in) => self.analyze_block_for_self_mutation(&for_in.body),
             Statement::While(while_stmt) => {
                 // Проверяем тело цикла
                 self.analyze_block_for_self_mutation(&while_stmt.body)
@@ -1733,6 +3725,96 @@ impl SemanticAnalyzer {
     }
     
     /// Проверяет, является ли выражение доступом к полю self (например, self.x)
+    /// W0005: параметры передаются по значению, поэтому изменение структуры
+    /// или коллекции без `&mut` видно только самой функции. Параметр, который
+    /// функция возвращает или объявляет заново, не считается ошибкой
+    fn warn_value_parameter_mutations(&mut self, func: &Function) {
+        let mut changes = Vec::new();
+        let mut kept = HashSet::new();
+        Self::collect_parameter_changes(&func.body, &mut changes, &mut kept);
+        
+        for param in &func.params {
+            if param.name == "self" || param.is_reference || kept.contains(param.name.as_str()) {
+                continue;
+            }
+            let param_type = self.parameter_type(param);
+            let mutated = changes.iter().any(|(root, method)| {
+                *root == param.name && match (method, &param_type) {
+                    (None, ChifType::Struct(_)) => true,
+                    (None, value_type) => Self::is_collection(value_type),
+                    (Some(method), ChifType::Struct(struct_name)) => matches!(
+                        self.symbol_table.lookup_symbol(&format!("{}_{}", struct_name, method)),
+                        Some(Symbol { symbol_type: SymbolType::Function(signature), .. }) if signature.is_mutating
+                    ),
+                    (Some(method), ChifType::List(..)) => matches!(method.as_str(), "add" | "addAt" | "del"),
+                    _ => false,
+                }
+            });
+            if mutated {
+                self.warnings.push(Warning::ValueParameterMutated {
+                    function: func.name.clone(),
+                    parameter: param.name.clone(),
+                    location: SourceLocation::unknown(),
+                });
+            }
+        }
+    }
+    
+    /// Изменения в блоке: `(переменная, None)` для `p.x = v` и `xs[i] = v`,
+    /// `(переменная, Some(метод))` для `p.method(...)`. В `kept` попадают
+    /// переменные из `ret p` и объявленные в теле заново
+    fn collect_parameter_changes<'a>(block: &'a Block, changes: &mut Vec<(&'a str, Option<&'a String>)>, kept: &mut HashSet<&'a str>) {
+        fn root(expr: &Expression) -> Option<&str> {
+            match expr {
+                Expression::Identifier(name) => Some(name),
+                Expression::FieldAccess(FieldAccess { object, .. }) | Expression::Index(IndexAccess { object, .. }) => root(object),
+                _ => None,
+            }
+        }
+        
+        for statement in &block.statements {
+            match statement {
+                Statement::Assignment(assignment) => {
+                    if let (Expression::FieldAccess(_) | Expression::Index(_), Some(name)) = (&assignment.target, root(&assignment.target)) {
+                        changes.push((name, None));
+                    }
+                }
+                Statement::Expression(Expression::MethodCall(method_call)) => match &*method_call.object {
+                    Expression::Identifier(name) => changes.push((name, Some(&method_call.method))),
+                    object => {
+                        if let Some(name) = root(object) {
+                            changes.push((name, None));
+                        }
+                    }
+                },
+                Statement::Return(Some(Expression::Identifier(name))) => {
+                    kept.insert(name);
+                }
+                Statement::VarDecl(var_decl) => {
+                    kept.insert(&var_decl.name);
+                }
+                Statement::If(if_stmt) => {
+                    Self::collect_parameter_changes(&if_stmt.then_block, changes, kept);
+                    if let Some(else_block) = &if_stmt.else_block {
+                        Self::collect_parameter_changes(else_block, changes, kept);
+                    }
+                }
+                Statement::For(for_stmt) => Self::collect_parameter_changes(&for_stmt.body, changes, kept),
+                Statement::ForIn(for_in) => Self::collect_parameter_changes(&for_in.body, changes, kept),
+                Statement::While(while_stmt) => Self::collect_parameter_changes(&while_stmt.body, changes, kept),
+                Statement::Switch(switch_stmt) => {
+                    for case in &switch_stmt.cases {
+                        Self::collect_parameter_changes(&case.body, changes, kept);
+                    }
+                    if let Some(default_case) = &switch_stmt.default_case {
+                        Self::collect_parameter_changes(default_case, changes, kept);
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+    
     fn is_self_field_access(&self, expr: &Expression) -> bool {
         match expr {
             Expression::FieldAccess(field_access) => {
@@ -1751,4 +3833,16 @@ impl SemanticAnalyzer {
 #[derive(Debug, Clone)]
 pub struct AnalyzedProgram {
     pub items: Vec<Item>,
+    pub source_file: String,  // Путь к исходному файлу (для диагностики)
+    pub source_text: String,
+}
+
+impl AnalyzedProgram {
+    pub fn new(items: Vec<Item>) -> Self {
+        Self {
+            items,
+            source_file: SourceLocation::unknown().file,
+            source_text: String::new(),
+        }
+    }
 }
\ No newline at end of file
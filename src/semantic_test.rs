@@ -1,6 +1,9 @@
 #[cfg(test)]
 mod tests {
-    use crate::semantic::SemanticAnalyzer;
+    use crate::semantic::{SemanticAnalyzer, SemanticError, SymbolKind, SymbolOrigin, Warning};
+    use crate::interpreter::Interpreter;
+    use crate::lexer::Lexer;
+    use crate::parser::Parser;
     use crate::ast::*;
     use crate::types::{ChifType, ChifValue};
 
@@ -27,6 +30,8 @@ mod tests {
                         ]
                     },
                     is_main: false,
+                    deprecated: None,
+                    is_exported: false,
                 })
             ]
         };
@@ -52,6 +57,8 @@ mod tests {
                         ]
                     },
                     is_main: false,
+                    deprecated: None,
+                    is_exported: false,
                 })
             ]
         };
@@ -82,6 +89,8 @@ mod tests {
                         ]
                     },
                     is_main: false,
+                    deprecated: None,
+                    is_exported: false,
                 })
             ]
         };
@@ -110,6 +119,7 @@ mod tests {
                                     left: Box::new(Expression::Literal(ChifValue::Int(5))),
                                     operator: BinaryOperator::Add,
                                     right: Box::new(Expression::Literal(ChifValue::Int(3))),
+                                    int_only: false,
                                 })),
                                 is_mutable: false,
                             }),
@@ -117,6 +127,8 @@ mod tests {
                         ]
                     },
                     is_main: false,
+                    deprecated: None,
+                    is_exported: false,
                 })
             ]
         };
@@ -148,6 +160,8 @@ mod tests {
                         ]
                     },
                     is_main: false,
+                    deprecated: None,
+                    is_exported: false,
                 })
             ]
         };
@@ -191,6 +205,8 @@ mod tests {
                         ]
                     },
                     is_main: false,
+                    deprecated: None,
+                    is_exported: false,
                 })
             ]
         };
@@ -198,4 +214,1508 @@ mod tests {
         let result = analyzer.analyze(&program);
         assert!(result.is_ok(), "Semantic analysis should succeed for function with returns in all paths");
     }
-}
\ No newline at end of file
+    
+    fn main_with(statements: Vec<Statement>) -> Item {
+        Item::Function(Function {
+            name: "main".to_string(),
+            params: vec![],
+            return_type: None,
+            body: Block { statements },
+            is_main: true,
+            deprecated: None,
+            is_exported: false,
+        })
+    }
+    
+    #[test]
+    fn test_shadowing_builtin_object_is_rejected() {
+        // var con: int = 5; con.out("hi");
+        let program = Program {
+            items: vec![main_with(vec![
+                Statement::VarDecl(VarDecl {
+                    name: "con".to_string(),
+                    var_type: ChifType::Int,
+                    value: Some(Expression::Literal(ChifValue::Int(5))),
+                    is_mutable: true,
+                }),
+                Statement::Expression(Expression::MethodCall(MethodCall {
+                    object: Box::new(Expression::Identifier("con".to_string())),
+                    method: "out".to_string(),
+                    args: vec![Expression::Literal(ChifValue::Str("hi".to_string()))],
+                })),
+            ])]
+        };
+        
+        let mut analyzer = SemanticAnalyzer::new();
+        match analyzer.analyze(&program) {
+            Err(SemanticError::ReservedName { name, .. }) => assert_eq!(name, "con"),
+            other => panic!("Expected ReservedName error, got {:?}", other.map(|_| ())),
+        }
+        
+        // The interpreter must reject the same program
+        let mut interpreter = Interpreter::new();
+        assert!(interpreter.execute(&program).is_err(), "Interpreter should reject shadowing 'con'");
+    }
+    
+    #[test]
+    fn test_args_os_and_rono_can_be_local_names_but_not_functions() {
+        let analyze = |source: &str| {
+            let tokens = Lexer::new(source).tokenize().expect("source should tokenize");
+            SemanticAnalyzer::new().analyze(&Parser::new(tokens).parse().expect("source should parse"))
+        };
+        analyze(r#"
+            fn count(args: list[str]) int {
+                ret args.len();
+            }
+            chif main() {
+                var os: str = "linux";
+                con.out(os.len() + count(["a"]));
+                con.out(rono.version());
+            }
+        "#).expect("locals may shadow args, os and rono");
+        
+        match analyze("fn os() int { ret 1; }\nchif main() { }") {
+            Err(SemanticError::ReservedName { name, .. }) => assert_eq!(name, "os"),
+            other => panic!("Expected ReservedName error, got {:?}", other.map(|_| ())),
+        }
+    }
+    
+    #[test]
+    fn test_struct_named_like_builtin_type_is_rejected() {
+        let program = Program {
+            items: vec![
+                Item::Struct(StructDef {
+                    name: "Http".to_string(),
+                    fields: vec![],
+                    is_exported: false,
+                }),
+                main_with(vec![]),
+            ]
+        };
+        
+        let mut analyzer = SemanticAnalyzer::new();
+        assert!(matches!(analyzer.analyze(&program), Err(SemanticError::ReservedName { .. })));
+        
+        let mut interpreter = Interpreter::new();
+        assert!(interpreter.execute(&program).is_err(), "Interpreter should reject struct 'Http'");
+    }
+    
+    #[test]
+    fn test_struct_field_named_like_builtin_is_allowed() {
+        // struct Endpoint { http: str }  var e: Endpoint = Endpoint { http: "..." }; con.out(e.http);
+        let program = Program {
+            items: vec![
+                Item::Struct(StructDef {
+                    name: "Endpoint".to_string(),
+                    fields: vec![StructField {
+                        name: "http".to_string(),
+                        field_type: ChifType::Str,
+                        is_private: false,
+                    }],
+                    is_exported: false,
+                }),
+                main_with(vec![
+                    Statement::VarDecl(VarDecl {
+                        name: "e".to_string(),
+                        var_type: ChifType::Struct("Endpoint".to_string()),
+                        value: Some(Expression::StructLiteral(StructLiteral {
+                            struct_name: "Endpoint".to_string(),
+                            fields: vec![(
+                                "http".to_string(),
+                                Expression::Literal(ChifValue::Str("https://example.com".to_string())),
+                            )],
+                            base: None,
+                        })),
+                        is_mutable: false,
+                    }),
+                    Statement::Expression(Expression::MethodCall(MethodCall {
+                        object: Box::new(Expression::Identifier("con".to_string())),
+                        method: "out".to_string(),
+                        args: vec![Expression::FieldAccess(FieldAccess {
+                            object: Box::new(Expression::Identifier("e".to_string())),
+                            field: "http".to_string(),
+                        })],
+                    })),
+                ]),
+            ]
+        };
+        
+        let mut analyzer = SemanticAnalyzer::new();
+        let result = analyzer.analyze(&program);
+        assert!(result.is_ok(), "Struct fields may be named like builtin objects: {:?}", result.err());
+        
+        let mut interpreter = Interpreter::new();
+        assert!(interpreter.execute(&program).is_ok(), "Interpreter should accept struct field 'http'");
+    }
+    
+    #[test]
+    fn test_list_unique_keeps_element_type() {
+        // list u: int[] = [3, 1, 2, 1, 3].unique();
+        let numbers = vec![3, 1, 2, 1, 3].into_iter()
+            .map(|n| Expression::Literal(ChifValue::Int(n)))
+            .collect();
+        let program = Program {
+            items: vec![main_with(vec![
+                Statement::VarDecl(VarDecl {
+                    name: "u".to_string(),
+                    var_type: ChifType::List(Box::new(ChifType::Int), vec![]),
+                    value: Some(Expression::MethodCall(MethodCall {
+                        object: Box::new(Expression::ArrayLiteral(numbers)),
+                        method: "unique".to_string(),
+                        args: vec![],
+                    })),
+                    is_mutable: true,
+                }),
+            ])]
+        };
+        
+        let mut analyzer = SemanticAnalyzer::new();
+        let result = analyzer.analyze(&program);
+        assert!(result.is_ok(), "unique() should return a list of the same element type: {:?}", result.err());
+    }
+    
+    fn parse(source: &str) -> Program {
+        let tokens = Lexer::new(source).tokenize().expect("source should tokenize");
+        Parser::new(tokens).parse().expect("source should parse")
+    }
+    
+    #[test]
+    fn test_tuple_divmod_conformance() {
+        let program = parse(r#"
+            fn divmod(a: int, b: int) (int, int) {
+                ret (a / b, a % b);
+            }
+            
+            chif main() {
+                var (q, r) = divmod(7, 2);
+                let sum: int = q + r;
+            }
+        "#);
+        let mut analyzer = SemanticAnalyzer::new();
+        let result = analyzer.analyze(&program);
+        assert!(result.is_ok(), "divmod should pass semantic analysis: {:?}", result.err());
+    }
+    
+    #[test]
+    fn test_tuple_min_max_conformance() {
+        let program = parse(r#"
+            fn min_max(a: int, b: int) (int, int) {
+                if (a < b) {
+                    ret (a, b);
+                }
+                ret (b, a);
+            }
+            
+            chif main() {
+                let (low, high): (int, int) = min_max(9, 4);
+                let pair: (int, int) = min_max(1, 5);
+                let sum: int = low + high + pair.0 + pair.1;
+            }
+        "#);
+        let mut analyzer = SemanticAnalyzer::new();
+        let result = analyzer.analyze(&program);
+        assert!(result.is_ok(), "min_max should pass semantic analysis: {:?}", result.err());
+    }
+    
+    #[test]
+    fn test_struct_literal_base_rules() {
+        let analyze = |literal: &str| {
+            let program = parse(&format!(r#"
+                struct Point {{ x: int, y: int, }}
+                struct Size {{ width: int, height: int, }}
+                chif main() {{
+                    var p: Point = Point {{ x = 1, y = 2 }};
+                    var s: Size = Size {{ width = 3, height = 4 }};
+                    var q: Point = {};
+                }}
+            "#, literal));
+            SemanticAnalyzer::new().analyze(&program).err().map(|error| error.to_string())
+        };
+        assert_eq!(analyze("Point { x = 5, ..p }"), None);
+        assert_eq!(analyze("Point { ..p }"), None);
+        let message = analyze("Point { x = 5, x = 6, ..p }").expect("duplicate field should be rejected");
+        assert!(message.contains("Field 'x' is specified more than once"), "{}", message);
+        let message = analyze("Point { x = 5, ..s }").expect("base of another struct should be rejected");
+        assert!(message.contains("must be a Point, found Size"), "{}", message);
+        assert!(analyze("Point { x = 5 }").is_some(), "without a base every field is required");
+    }
+    
+    #[test]
+    fn test_args_method_signatures() {
+        let analyze = |statement: &str| {
+            let program = parse(&format!("chif main() {{ {} }}", statement));
+            SemanticAnalyzer::new().analyze(&program).err().map(|error| error.to_string())
+        };
+        assert_eq!(analyze("var n: int = args.count(); var first: str = args.get(0);"), None);
+        assert_eq!(analyze("var verbose: bool = args.has_flag(\"verbose\"); var port: str = args.value_of(\"port\");"), None);
+        let message = analyze("var port: int = args.value_of(\"port\");").expect("value_of returns str");
+        assert!(message.contains("Type mismatch"), "{}", message);
+        let message = analyze("args.get();").expect("get needs an index");
+        assert!(message.contains("args.get expects 1 argument"), "{}", message);
+        let message = analyze("args.flags();").expect("unknown method should be rejected");
+        assert!(message.contains("available: count, get, has_flag, value_of"), "{}", message);
+    }
+    
+    fn nil_type_error(source: &str) -> Option<String> {
+        SemanticAnalyzer::new().analyze(&parse(source)).err().map(|error| error.to_string())
+    }
+    
+    #[test]
+    fn test_nil_is_allowed_as_return_type_and_pointer_value() {
+        let result = nil_type_error(r#"
+            fn log(n: int) nil {
+                con.out(n);
+            }
+            chif main() {
+                var p: pointer = nil;
+                var q: pointer[int] = nil;
+                var xs: list = [1, 2];
+                log(1);
+            }
+        "#);
+        assert_eq!(result, None);
+    }
+    
+    #[test]
+    fn test_nil_variable_is_rejected() {
+        let message = nil_type_error("chif main() { var x: nil = nil; }").expect("nil variable should be rejected");
+        assert!(message.contains("Variable 'x' cannot have type nil: nil only marks a function without a return value"), "{}", message);
+    }
+    
+    #[test]
+    fn test_nil_parameter_is_rejected() {
+        let message = nil_type_error("fn f(n: nil) { }\nchif main() { }").expect("nil parameter should be rejected");
+        assert!(message.contains("Parameter 'n' of function 'f' cannot have type nil"), "{}", message);
+        
+        let message = nil_type_error("struct P { x: int, }\nfn_for P { fn set(self, v: nil) { } }\nchif main() { }")
+            .expect("nil method parameter should be rejected");
+        assert!(message.contains("Parameter 'v' of method 'P.set' cannot have type nil"), "{}", message);
+    }
+    
+    #[test]
+    fn test_nil_field_is_rejected() {
+        let message = nil_type_error("struct P { x: int, empty: nil, }\nchif main() { }").expect("nil field should be rejected");
+        assert!(message.contains("Field 'empty' of struct 'P' cannot have type nil"), "{}", message);
+    }
+    
+    #[test]
+    fn test_nil_array_element_is_rejected() {
+        let message = nil_type_error("chif main() { array xs: nil[3]; }").expect("array of nil should be rejected");
+        assert!(message.contains("Variable 'xs' cannot have type array[nil][3]"), "{}", message);
+    }
+    
+    #[test]
+    fn test_nil_list_element_is_rejected() {
+        let message = nil_type_error("fn f(xs: list[nil]) { }\nchif main() { }").expect("list of nil should be rejected");
+        assert!(message.contains("Parameter 'xs' of function 'f' cannot have type list[nil]"), "{}", message);
+        
+        let message = nil_type_error("fn f() list[nil] { ret []; }\nchif main() { }").expect("list of nil should be rejected");
+        assert!(message.contains("Return type of function 'f' cannot have type list[nil]"), "{}", message);
+    }
+    
+    #[test]
+    fn test_nil_map_key_and_value_are_rejected() {
+        let message = nil_type_error("chif main() { var m: map[nil:int]; }").expect("nil map key should be rejected");
+        assert!(message.contains("Variable 'm' cannot have type map[nil"), "{}", message);
+        
+        let message = nil_type_error("chif main() { var m: map[str:nil]; }").expect("nil map value should be rejected");
+        assert!(message.contains("Variable 'm' cannot have type map[str"), "{}", message);
+    }
+    
+    #[test]
+    fn test_nil_tuple_element_is_rejected() {
+        let message = nil_type_error("fn pair() (int, nil) { ret (1, nil); }\nchif main() { }").expect("nil tuple element should be rejected");
+        assert!(message.contains("Return type of function 'pair' cannot have type"), "{}", message);
+    }
+    
+    #[test]
+    fn test_tuple_destructure_arity_mismatch() {
+        let program = parse(r#"
+            fn divmod(a: int, b: int) (int, int) {
+                ret (a / b, a % b);
+            }
+            
+            chif main() {
+                var (q, r, extra) = divmod(7, 2);
+            }
+        "#);
+        
+        let mut analyzer = SemanticAnalyzer::new();
+        assert!(analyzer.analyze(&program).is_err(), "Destructuring 2 values into 3 names should fail");
+        
+        let mut interpreter = Interpreter::new();
+        assert!(interpreter.execute(&program).is_err(), "Interpreter should reject the arity mismatch too");
+    }
+    
+    #[test]
+    fn test_prelude_is_available_and_shadowed_with_a_warning() {
+        let program = parse(r#"
+            fn clamp(value: int, low: int, high: int) int {
+                ret value;
+            }
+            
+            chif main() {
+                var padded: str = pad_left("7", 3, "0");
+                var limited: int = clamp(15, 0, 10);
+            }
+        "#);
+        let mut analyzer = SemanticAnalyzer::new();
+        let analyzed = analyzer.analyze(&program).expect("prelude functions should be callable");
+        assert_eq!(analyzer.warnings.len(), 1, "{:?}", analyzer.warnings);
+        assert_eq!(analyzer.warnings[0].code(), "W0003");
+        assert_eq!(analyzer.warnings[0].to_string(), "'clamp' shadows the prelude definition with the same name");
+        
+        // Только вызванные функции прелюдии, с теми, что вызывают они сами
+        let mut names: Vec<&str> = analyzed.items.iter()
+            .filter_map(|item| match item {
+                Item::Function(func) => Some(func.name.as_str()),
+                _ => None,
+            })
+            .collect();
+        names.sort_unstable();
+        assert_eq!(names, ["clamp", "main", "pad_left", "repeat"]);
+        
+        let mut analyzer = SemanticAnalyzer::new();
+        analyzer.prelude = false;
+        let error = analyzer.analyze(&program).expect_err("without the prelude pad_left is undefined");
+        assert!(error.to_string().contains("pad_left"), "{}", error);
+        assert!(analyzer.warnings.is_empty());
+    }
+    
+    #[test]
+    fn test_prelude_type_checks_on_its_own() {
+        let mut analyzer = SemanticAnalyzer::new();
+        analyzer.prelude = false;
+        analyzer.analyze(&crate::prelude::program()).expect("every prelude function should type-check");
+    }
+    
+    #[test]
+    fn test_deprecated_function_call_warns() {
+        let program = parse(r#"
+            @deprecated("Use new_add instead")
+            fn old_add(a: int, b: int) int {
+                ret a + b;
+            }
+            
+            fn new_add(a: int, b: int) int {
+                ret a + b;
+            }
+            
+            chif main() {
+                let x: int = old_add(1, 2);
+                let y: int = new_add(x, 3);
+            }
+        "#);
+        
+        let mut analyzer = SemanticAnalyzer::new();
+        let result = analyzer.analyze(&program);
+        assert!(result.is_ok(), "Deprecated functions are still callable: {:?}", result.err());
+        
+        assert_eq!(analyzer.warnings.len(), 1, "Expected one warning, got {:?}", analyzer.warnings);
+        match &analyzer.warnings[0] {
+            Warning::DeprecatedUse { name, message, .. } => {
+                assert_eq!(name, "old_add");
+                assert_eq!(message, "Use new_add instead");
+            }
+            other => panic!("expected a deprecated function warning, got {:?}", other),
+        }
+        assert_eq!(analyzer.warnings[0].code(), "W0001");
+    }
+    
+    #[test]
+    fn test_uncalled_deprecated_function_does_not_warn() {
+        let program = parse(r#"
+            @deprecated("Use new_add instead")
+            fn old_add(a: int, b: int) int {
+                ret a + b;
+            }
+            
+            chif main() {
+            }
+        "#);
+        
+        let mut analyzer = SemanticAnalyzer::new();
+        assert!(analyzer.analyze(&program).is_ok());
+        assert!(analyzer.warnings.is_empty(), "No call, no warning: {:?}", analyzer.warnings);
+    }
+    
+    #[test]
+    fn test_string_index_of_counts_characters() {
+        // "é" takes two bytes, so byte and character positions differ after it
+        let source = r#"
+            chif main() {
+                let word: str = "héllo";
+                let at: int = word.index_of("l");
+                let missing: int = word.index_of("z");
+                let chars: int = word.char_count();
+                let bytes: int = word.byte_len();
+                let total: int = at + missing + chars + bytes;
+            }
+        "#;
+        
+        let mut analyzer = SemanticAnalyzer::new();
+        let result = analyzer.analyze(&parse(source));
+        assert!(result.is_ok(), "String methods should type-check as int: {:?}", result.err());
+    }
+    
+    #[test]
+    fn test_int_radix_string_methods() {
+        let source = r#"
+            chif main() {
+                let hex: str = 255.to_hex_str();
+                let lower: str = 48879.to_hex_str_lower();
+                let bits: str = 255.to_binary_str();
+                let negative: int = 0 - 1;
+                let all: str = hex + lower + bits + negative.to_hex_str();
+            }
+        "#;
+        
+        let mut analyzer = SemanticAnalyzer::new();
+        let result = analyzer.analyze(&parse(source));
+        assert!(result.is_ok(), "Radix methods should type-check as str: {:?}", result.err());
+        
+        let mut analyzer = SemanticAnalyzer::new();
+        let result = analyzer.analyze(&parse("chif main() { let s: str = 5.to_octal_str(); }"));
+        assert!(result.is_err(), "Unknown int methods should be rejected");
+    }
+    
+    #[test]
+    fn test_chars_bytes_split_and_join_types() {
+        let mut analyzer = SemanticAnalyzer::new();
+        let result = analyzer.analyze(&parse(r#"
+            chif main() {
+                list letters: str[] = "abc".chars();
+                list codes: int[] = "abc".bytes();
+                list words: str[] = "a b".split(" ");
+                let joined: str = words.join(", ");
+            }
+        "#));
+        assert!(result.is_ok(), "List helpers should type-check: {:?}", result.err());
+        
+        let mut analyzer = SemanticAnalyzer::new();
+        match analyzer.analyze(&parse(r#"
+            chif main() {
+                list codes: int[] = "abc".bytes();
+                let joined: str = codes.join(",");
+            }
+        "#)) {
+            Err(SemanticError::InvalidOperation { message, .. }) => assert!(message.contains("list of str"), "{}", message),
+            other => panic!("join on a list of int should be rejected, got {:?}", other),
+        }
+    }
+    
+    #[test]
+    fn test_void_call_used_as_value_is_rejected() {
+        let misuses = [
+            "var x: int = do_stuff();",
+            "var x: int = 0; x = do_stuff();",
+            "var x: int = 1 + do_stuff();",
+            "var b: bool = !do_stuff();",
+            "var b: bool = do_stuff() == 1;",
+            "var x: int = twice(do_stuff());",
+            "con.out(do_stuff());",
+            "if (do_stuff()) { }",
+            "var x: int = Counter { n = 1 }.bump();",
+        ];
+        for misuse in misuses {
+            let source = format!(r#"
+                struct Counter {{ n: int, }}
+                fn_for Counter {{
+                    fn bump(self) {{ }}
+                }}
+                fn do_stuff() {{ }}
+                fn twice(n: int) int {{ ret n * 2; }}
+                chif main() {{
+                    {}
+                }}
+            "#, misuse);
+            
+            let mut analyzer = SemanticAnalyzer::new();
+            match analyzer.analyze(&parse(&source)) {
+                Err(SemanticError::InvalidOperation { message, .. }) => assert!(
+                    message.contains("has no return value and cannot be used in an expression"),
+                    "{}: {}", misuse, message
+                ),
+                other => panic!("{} should be rejected, got {:?}", misuse, other),
+            }
+        }
+        
+        // Returning the result of a void call is a misuse as well
+        let mut analyzer = SemanticAnalyzer::new();
+        let result = analyzer.analyze(&parse(r#"
+            fn do_stuff() nil { }
+            fn forward() { ret do_stuff(); }
+            chif main() { do_stuff(); }
+        "#));
+        match result {
+            Err(SemanticError::InvalidOperation { message, .. }) => assert!(message.contains("function 'do_stuff'"), "{}", message),
+            other => panic!("returning a void call should be rejected, got {:?}", other),
+        }
+    }
+    
+    #[test]
+    fn test_explicit_nil_return_type_is_normalized() {
+        let program = parse("fn a() nil { } fn b() { } chif main() { a(); b(); }");
+        for item in &program.items {
+            if let Item::Function(func) = item {
+                assert_eq!(func.return_type, None, "{} should have no return type", func.name);
+            }
+        }
+        let mut analyzer = SemanticAnalyzer::new();
+        assert!(analyzer.analyze(&program).is_ok(), "Bare calls of void functions are fine");
+    }
+    
+    #[test]
+    fn test_string_switch_cases_accept_any_str_expression() {
+        let switch_on = |case_value: &str| {
+            let source = format!(r#"
+                fn code(command: str) int {{
+                    let stop: str = "stop";
+                    var pause: str = "pause";
+                    switch command:
+                    case {} {{
+                        ret 1;
+                    }}
+                    default {{
+                        ret 0;
+                    }}
+                }}
+                chif main() {{ }}
+            "#, case_value);
+            SemanticAnalyzer::new().analyze(&parse(&source))
+        };
+        
+        // Cases are compared by contents, so any str expression will do
+        for case_value in ["\"start\"", "stop", "pause", "command + \"!\""] {
+            assert!(switch_on(case_value).is_ok(), "case {} should be accepted", case_value);
+        }
+        match switch_on("1") {
+            Err(SemanticError::TypeMismatch { .. }) => {}
+            other => panic!("an int case of a str switch should be rejected, got {:?}", other.map(|_| ())),
+        }
+    }
+    
+    #[test]
+    fn test_module_imported_through_different_paths_is_parsed_once() {
+        let dir = tempfile::tempdir().expect("temp dir should be created");
+        std::fs::create_dir(dir.path().join("sub")).expect("sub dir should be created");
+        std::fs::write(dir.path().join("utils.rono"), r#"
+            struct Pair { a: int, b: int, }
+            fn_for Pair {
+                fn sum(self) int {
+                    ret self.a + self.b;
+                }
+            }
+            fn add(a: int, b: int) int {
+                ret a + b;
+            }
+        "#).expect("module should be written");
+        
+        let base = dir.path().display();
+        let source = format!(r#"
+            import "{base}/utils";
+            import "{base}/sub/../utils";
+            import "{base}/utils" as other;
+            chif main() {{
+            }}
+        "#, base = base);
+        
+        let mut analyzer = SemanticAnalyzer::new();
+        let result = analyzer.analyze(&parse(&source));
+        assert!(result.is_ok(), "Re-importing the same module should not redefine its symbols: {:?}", result.err());
+        assert_eq!(analyzer.module_cache.len(), 1);
+        assert!(analyzer.symbol_table.lookup_symbol("utils_add").is_some());
+        assert!(analyzer.symbol_table.lookup_symbol("other_add").is_some());
+        assert!(analyzer.symbol_table.lookup_symbol("other_Pair_sum").is_some());
+        // Оба импорта используют одни и те же сигнатуры из кэша, а не их копии
+        let (utils, other) = (&analyzer.modules["utils"], &analyzer.modules["other"]);
+        assert!(std::rc::Rc::ptr_eq(&utils.functions, &other.functions));
+        assert!(std::rc::Rc::ptr_eq(&utils.methods, &other.methods));
+    }
+    
+    fn named_call_error(call: &str) -> String {
+        let source = format!(r#"
+            fn make_rect(x: int, y: int, w: int, h: int) int {{
+                ret x + y + w + h;
+            }}
+            chif main() {{
+                let area: int = {};
+            }}
+        "#, call);
+        
+        let mut analyzer = SemanticAnalyzer::new();
+        let analyzer_error = match analyzer.analyze(&parse(&source)) {
+            Err(SemanticError::InvalidOperation { message, .. }) => message,
+            other => panic!("expected an invalid operation for {}, got {:?}", call, other),
+        };
+        
+        let mut interpreter = Interpreter::new();
+        assert!(interpreter.execute(&parse(&source)).is_err(), "Interpreter should reject {}", call);
+        analyzer_error
+    }
+    
+    #[test]
+    fn test_named_arguments_errors() {
+        assert!(named_call_error("make_rect(x: 1, y: 2, w: 3, depth: 4)").contains("no parameter named 'depth'"));
+        assert!(named_call_error("make_rect(x: 1, y: 2, w: 3, x: 4)").contains("'x'"));
+        assert!(named_call_error("make_rect(1, 2, 3, x: 4)").contains("more than once"));
+        assert!(named_call_error("make_rect(x: 1, y: 2, w: 3)").contains("Missing argument 'h'"));
+        
+        let tokens = Lexer::new("chif main() { f(x: 1, 2); }").tokenize().unwrap();
+        assert!(Parser::new(tokens).parse().is_err(), "Positional arguments after named ones should not parse");
+    }
+    
+    fn associated_call_error(call: &str) -> String {
+        let source = format!(r#"
+            struct Point {{ x: int, y: int, }}
+            fn_for Point {{
+                fn new(x: int, y: int) Self {{
+                    ret Point {{ x = x, y = y }};
+                }}
+                fn sum(self) int {{
+                    ret self.x + self.y;
+                }}
+            }}
+            chif main() {{
+                var p: Point = Point.new(1, 2);
+                let value: int = {};
+            }}
+        "#, call);
+        
+        match SemanticAnalyzer::new().analyze(&parse(&source)) {
+            Err(SemanticError::InvalidOperation { message, .. }) => message,
+            other => panic!("expected an invalid operation for {}, got {:?}", call, other),
+        }
+    }
+    
+    #[test]
+    fn test_associated_function_calls() {
+        assert!(associated_call_error("Point.sum()").contains("takes self"));
+        assert!(associated_call_error("p.new(1, 2).x").contains("call it as Point.new(...)"));
+        assert!(associated_call_error("Point.new(1).x").contains("expects 2 arguments, got 1"));
+        
+        let result = SemanticAnalyzer::new().analyze(&parse(r#"
+            struct Point { x: int, y: int, }
+            fn_for Point {
+                fn new(x: int, y: int) Self {
+                    ret Point { x = x, y = y };
+                }
+            }
+            chif main() {
+                var p: Point = Point.new(1, 2);
+                let x: int = p.x;
+            }
+        "#));
+        assert!(result.is_ok(), "{:?}", result.err());
+    }
+    
+    // `{decl}` declares xs
+    const LIST_PROGRAM: &str = r#"
+        chif main() {
+            {decl}
+            xs.add(5);
+            xs.add(7);
+            var last: int = xs[xs.len() - 1];
+        }
+    "#;
+    
+    fn list_program(decl: &str) -> String {
+        LIST_PROGRAM.replace("{decl}", decl)
+    }
+    
+    #[test]
+    fn test_list_add_then_index() {
+        for decl in ["var xs: list[int] = [];", "list xs: int[] = [];", "var xs: list = [1];"] {
+            let result = SemanticAnalyzer::new().analyze(&parse(&list_program(decl)));
+            assert!(result.is_ok(), "{}: {:?}", decl, result.err());
+        }
+        
+        // The element type, declared or inferred, is checked on every add
+        for decl in ["var xs: list[int] = [];", "var xs: list = [1];"] {
+            let program = parse(&list_program(decl).replace("xs.add(7);", "xs.add(\"seven\");"));
+            match SemanticAnalyzer::new().analyze(&program) {
+                Err(SemanticError::TypeMismatch { expected, found, .. }) => {
+                    assert_eq!((expected, found), (ChifType::Int, ChifType::Str));
+                }
+                other => panic!("{}: expected a type mismatch, got {:?}", decl, other),
+            }
+        }
+    }
+    
+    #[test]
+    fn test_empty_list_needs_element_type() {
+        for decl in ["var xs: list = [];", "var xs: list;"] {
+            let program = parse(&list_program(decl));
+            match SemanticAnalyzer::new().analyze(&program) {
+                Err(SemanticError::InvalidOperation { message, .. }) => {
+                    assert!(message.contains("'xs'") && message.contains("annotate as list[<type>]"), "{}", message);
+                }
+                other => panic!("{}: expected an inference error, got {:?}", decl, other),
+            }
+            
+            let error = Interpreter::new().execute(&program).expect_err("interpreter should reject it too");
+            assert!(error.to_string().contains("annotate as list[<type>]"), "{}", error);
+        }
+    }
+    
+    #[test]
+    fn test_impl_blocks_for_an_imported_struct() {
+        let dir = tempfile::tempdir().expect("temp dir should be created");
+        std::fs::write(dir.path().join("geometry.rono"), r#"
+            struct Point { x: int, y: int, }
+            fn_for Point {
+                fn sum(self) int {
+                    ret self.x + self.y;
+                }
+            }
+        "#).expect("module should be written");
+        let base = dir.path().display();
+        
+        // Methods from both files resolve under the struct's own name
+        let source = format!(r#"
+            import "{base}/geometry";
+            import "{base}/geometry" as geo;
+            fn_for Point {{
+                fn twice(self) int {{
+                    ret self.sum() * 2;
+                }}
+            }}
+            chif main() {{
+                var p: Point = Point {{ x = 1, y = 2 }};
+                let total: int = p.sum() + p.twice();
+            }}
+        "#, base = base);
+        let mut analyzer = SemanticAnalyzer::new();
+        let result = analyzer.analyze(&parse(&source));
+        assert!(result.is_ok(), "{:?}", result.err());
+        for name in ["Point", "Point_sum", "Point_twice", "geometry_Point_sum", "geo_Point_sum"] {
+            assert!(analyzer.symbol_table.lookup_symbol(name).is_some(), "{}", name);
+        }
+        
+        // The same method defined twice, across files or within one, is an error
+        for source in [
+            format!("import \"{}/geometry\";\nfn_for Point {{ fn sum(self) int {{ ret 0; }} }}\nchif main() {{ }}", base),
+            "struct P { x: int, }\nfn_for P { fn get(self) int { ret 1; } }\nfn_for P { fn get(self) int { ret 2; } }\nchif main() { }".to_string(),
+        ] {
+            match SemanticAnalyzer::new().analyze(&parse(&source)) {
+                Err(SemanticError::InvalidOperation { message, .. }) => {
+                    assert!(message.contains("is already defined for struct"), "{}", message);
+                }
+                other => panic!("expected a duplicate method error, got {:?}", other.map(|_| ())),
+            }
+        }
+    }
+    
+    #[test]
+    fn test_symbols_name_kind_and_origin() {
+        let dir = tempfile::tempdir().expect("temp dir should be created");
+        std::fs::write(dir.path().join("geometry.rono"), r#"
+            struct Point { x: int, y: int, }
+            fn_for Point {
+                fn sum(self) int {
+                    ret self.x + self.y;
+                }
+            }
+            fn origin() Point {
+                ret Point { x = 0, y = 0 };
+            }
+        "#).expect("module should be written");
+        let source = format!(r#"
+            import "{}/geometry" as geo;
+            chif main() {{
+                var p: Point = geo.origin();
+                con.out(clamp(p.sum(), 0, 10));
+            }}
+        "#, dir.path().display());
+        let mut analyzer = SemanticAnalyzer::new();
+        analyzer.analyze(&parse(&source)).expect("program should pass the analyzer");
+        let geometry = std::fs::canonicalize(dir.path().join("geometry.rono")).unwrap();
+        
+        let symbols = analyzer.symbols();
+        let find = |symbol: &str| symbols.iter().find(|info| info.symbol == symbol)
+            .unwrap_or_else(|| panic!("no symbol {}", symbol));
+        let origin = find("geo_origin");
+        assert_eq!((origin.name.as_str(), origin.kind, origin.detail.as_str()), ("geo.origin", SymbolKind::Function, "() Point"));
+        assert_eq!(origin.origin, SymbolOrigin::Module(geometry.clone()));
+        assert_eq!(find("geo_Point_sum").name, "geo.Point.sum");
+        let sum = find("Point_sum");
+        assert_eq!((sum.name.as_str(), sum.detail.as_str()), ("Point.sum", "(self) int"));
+        assert_eq!(find("Point").detail, "{ x: int, y: int }");
+        assert_eq!(find("geo").kind, SymbolKind::Module);
+        assert_eq!(find("clamp").origin, SymbolOrigin::Prelude);
+        let con = find("con");
+        assert_eq!((con.kind, &con.origin), (SymbolKind::Variable, &SymbolOrigin::Builtin));
+        assert_eq!(find("main").origin, SymbolOrigin::Program);
+        
+        // Порядок не зависит от HashMap: по имени, затем по виду
+        assert!(symbols.windows(2).all(|pair| (&pair[0].name, pair[0].kind) <= (&pair[1].name, pair[1].kind)));
+        assert_eq!(analyzer.symbols(), symbols);
+    }
+    
+    const UTILS_MODULE: &str = r#"
+        struct Config { port: int, }
+        fn helper(x: int) int {
+            ret x * 2;
+        }
+        fn parse_config(x: int) int {
+            ret helper(x) + 1;
+        }
+        fn unused() int {
+            ret 0;
+        }
+    "#;
+    
+    #[test]
+    fn test_selective_imports() {
+        let dir = tempfile::tempdir().expect("temp dir should be created");
+        std::fs::write(dir.path().join("utils.rono"), UTILS_MODULE).expect("module should be written");
+        let base = dir.path().display();
+        
+        let source = format!(r#"
+            import "{base}/utils" use (parse_config, Config);
+            import "{base}/utils" as u use (helper);
+            chif main() {{
+                let value: int = utils.parse_config(20) + u.helper(1);
+                ret value;
+            }}
+        "#, base = base);
+        let mut analyzer = SemanticAnalyzer::new();
+        let result = analyzer.analyze(&parse(&source));
+        assert!(result.is_ok(), "{:?}", result.err());
+        for (name, defined) in [("utils_parse_config", true), ("utils_Config", true), ("utils_helper", false),
+                                ("utils_unused", false), ("u_helper", true), ("u_parse_config", false)] {
+            assert_eq!(analyzer.symbol_table.lookup_symbol(name).is_some(), defined, "{}", name);
+        }
+        
+        // parse_config still reaches helper in the interpreter
+        let mut interpreter = Interpreter::new();
+        interpreter.load(&parse(&source)).expect("program should load");
+        assert_eq!(interpreter.call_function_by_name("main").unwrap().to_string(), "43");
+        
+        // A function left out of `use (...)` is undefined, and the error points at the import
+        let source = format!("\nimport \"{}/utils\" use (parse_config);\nchif main() {{ let x: int = utils.unused(); }}", base);
+        let program = crate::parse_source(&source).expect("source should parse");
+        match SemanticAnalyzer::new().analyze(&program) {
+            Err(SemanticError::UndefinedSymbol { symbol, location, .. }) => {
+                assert_eq!((symbol.as_str(), location.line), ("utils.unused", 2));
+            }
+            other => panic!("expected an undefined symbol, got {:?}", other),
+        }
+        assert!(Interpreter::new().execute(&program).is_err(), "Interpreter should reject it too");
+        
+        // Listed names must exist in the module
+        let source = format!("import \"{}/utils\" use (parse_confg);\nchif main() {{ }}", base);
+        match SemanticAnalyzer::new().analyze(&parse(&source)) {
+            Err(SemanticError::InvalidOperation { message, .. }) => {
+                assert!(message.contains("'parse_confg'") && message.contains("did you mean 'parse_config'"), "{}", message);
+            }
+            other => panic!("expected an invalid operation, got {:?}", other),
+        }
+        let error = Interpreter::new().execute(&parse(&source)).expect_err("interpreter should reject it too");
+        assert!(error.to_string().contains("did you mean 'parse_config'"), "{}", error);
+    }
+    
+    #[test]
+    fn test_import_paths_resolve_from_the_importing_file() {
+        let dir = tempfile::tempdir().expect("temp dir should be created");
+        let app = dir.path().join("app");
+        std::fs::create_dir_all(app.join("lib")).expect("module directories should be created");
+        std::fs::write(app.join("lib").join("net.rono"), "import \"./codec\";\nimport \"../shared\";\nfn fetch(n: int) int { ret codec.decode(n) + shared.base(); }")
+            .expect("module should be written");
+        std::fs::write(app.join("lib").join("codec.rono"), "fn decode(n: int) int { ret n * 10; }")
+            .expect("module should be written");
+        std::fs::write(app.join("shared.rono"), "fn base() int { ret 1; }")
+            .expect("module should be written");
+        
+        // `./` and `../` inside a module are relative to the module's own directory,
+        // not to the program or the working directory
+        let source = "import \"./lib/net\";\nimport \"pkg:prelude\";\nchif main() { con.out(prelude.clamp(net.fetch(4), 0, 100)); }";
+        let mut analyzer = SemanticAnalyzer::new();
+        analyzer.source_dir = Some(app.clone());
+        let result = analyzer.analyze(&parse(source));
+        assert!(result.is_ok(), "{:?}", result.err());
+        assert!(analyzer.warnings.is_empty(), "{:?}", analyzer.warnings);
+        let mut interpreter = Interpreter::new();
+        interpreter.capture_output();
+        interpreter.set_source_dir(Some(app.clone()));
+        interpreter.execute(&parse(source)).expect("program should run");
+        assert_eq!(interpreter.take_output(), ["41"]);
+        
+        // The legacy form resolves the same way and is deprecated
+        let mut analyzer = SemanticAnalyzer::new();
+        analyzer.source_dir = Some(app.clone());
+        let program = crate::parse_source("\nimport \"lib/net\";\nchif main() { let x: int = net.fetch(1); }").expect("source should parse");
+        let result = analyzer.analyze(&program);
+        assert!(result.is_ok(), "{:?}", result.err());
+        let warnings: Vec<(&str, usize)> = analyzer.warnings.iter()
+            .map(|warning| (warning.code().as_str(), warning.location().line))
+            .collect();
+        assert_eq!(warnings, [("W0006", 2)]);
+        assert!(analyzer.warnings[0].to_string().contains("\"./lib/net\""), "{}", analyzer.warnings[0]);
+        
+        // Unresolved imports say where the module was looked for
+        for (path, expected) in [
+            ("./missing", format!("looked for {} (relative to the importing file's directory {})",
+                app.join("./missing.rono").display(), app.display())),
+            ("lib/missing", "a path without './' is resolved like './lib/missing'".to_string()),
+            ("pkg:std/strings", "searched the bundled modules (pkg:prelude)".to_string()),
+        ] {
+            let mut analyzer = SemanticAnalyzer::new();
+            analyzer.source_dir = Some(app.clone());
+            let program = crate::parse_source(&format!("import \"{}\";\nchif main() {{ }}", path)).expect("source should parse");
+            match analyzer.analyze(&program) {
+                Err(SemanticError::InvalidOperation { message, location }) => {
+                    assert!(message.starts_with(&format!("Could not find module '{}'", path)), "{}", message);
+                    assert!(message.contains(&expected), "{}", message);
+                    assert_eq!(location.line, 1);
+                }
+                other => panic!("expected an unresolved import for {}, got {:?}", path, other),
+            }
+        }
+    }
+    
+    const EXPORTING_MODULE: &str = r#"
+        export struct Config { port: int, }
+        struct Secret { key: int, }
+        fn helper(x: int) int {
+            ret x * 2;
+        }
+        export fn parse_config(x: int) int {
+            ret helper(x) + 1;
+        }
+    "#;
+    
+    #[test]
+    fn test_only_exported_items_are_imported() {
+        let dir = tempfile::tempdir().expect("temp dir should be created");
+        std::fs::write(dir.path().join("utils.rono"), EXPORTING_MODULE).expect("module should be written");
+        let base = dir.path().display();
+        
+        let source = format!("import \"{}/utils\";\nchif main() {{ var c: Config = Config {{ port = utils.parse_config(20) }}; }}", base);
+        let mut analyzer = SemanticAnalyzer::new();
+        let result = analyzer.analyze(&parse(&source));
+        assert!(result.is_ok(), "{:?}", result.err());
+        for (name, defined) in [("utils_parse_config", true), ("utils_Config", true), ("utils_helper", false), ("utils_Secret", false), ("Secret", false)] {
+            assert_eq!(analyzer.symbol_table.lookup_symbol(name).is_some(), defined, "{}", name);
+        }
+        let mut interpreter = Interpreter::new();
+        assert!(interpreter.execute(&parse(&source)).is_ok(), "private callees still run behind an export");
+        
+        // Calling or listing a private function names the reason
+        for source in [
+            format!("import \"{}/utils\";\nchif main() {{ let x: int = utils.helper(1); }}", base),
+            format!("import \"{}/utils\" use (parse_config, helper);\nchif main() {{ }}", base),
+        ] {
+            let program = parse(&source);
+            match SemanticAnalyzer::new().analyze(&program) {
+                Err(error @ SemanticError::UndefinedSymbol { .. }) => assert!(
+                    error.message().contains("function 'helper' exists in module 'utils' but is not exported"),
+                    "{}",
+                    error
+                ),
+                other => panic!("expected an undefined symbol, got {:?}", other),
+            }
+            assert!(Interpreter::new().execute(&program).is_err(), "Interpreter should reject it too");
+        }
+        
+        let source = format!("import \"{}/utils\" use (Secret);\nchif main() {{ }}", base);
+        match SemanticAnalyzer::new().analyze(&parse(&source)) {
+            Err(error) => assert!(error.message().contains("struct 'Secret' exists in module 'utils' but is not exported"), "{}", error),
+            Ok(_) => panic!("a private struct should not be importable"),
+        }
+    }
+    
+    #[test]
+    fn test_for_init_reusing_immutable_variable_is_rejected() {
+        let source = "chif main() { let i: int = 0; for (i = 0; i < 3; i = i + 1) { } }";
+        match SemanticAnalyzer::new().analyze(&parse(source)) {
+            Err(SemanticError::InvalidOperation { message, .. }) => {
+                assert!(message.contains("'i'") && message.contains("var"), "{}", message);
+            }
+            other => panic!("expected an invalid operation, got {:?}", other),
+        }
+        
+        let source = "chif main() { var i: int = 0; for (i = 0; i < 3; i = i + 1) { } for (var j = 0.5; j < 2.0; j = j + 1.0) { } }";
+        let result = SemanticAnalyzer::new().analyze(&parse(source));
+        assert!(result.is_ok(), "{:?}", result.err());
+        
+        let source = "chif main() { for (k = 0; k < 3; k = k + 1) { } }";
+        assert!(matches!(
+            SemanticAnalyzer::new().analyze(&parse(source)),
+            Err(SemanticError::UndefinedSymbol { .. })
+        ));
+    }
+    
+    #[test]
+    fn test_assignment_to_immutable_binding_is_rejected() {
+        for (source, name) in [
+            ("chif main() { let x: int = 1; x = 2; }", "x"),
+            ("chif main() { for i in range(0, 3) { i = 5; } }", "i"),
+            ("chif main() { var n: int = 0; for i in range(0, 3) { n = n + i; } let total: int = n; if (total > 0) { total = 0; } }", "total"),
+            ("chif main() { mem = 1; }", "mem"),
+        ] {
+            match SemanticAnalyzer::new().analyze(&parse(source)) {
+                Err(SemanticError::InvalidOperation { message, .. }) => {
+                    assert!(message.contains(&format!("immutable variable '{}'", name)), "{}", message);
+                }
+                other => panic!("{}: expected an invalid operation, got {:?}", source, other.map(|_| ())),
+            }
+        }
+        
+        // var bindings and value parameters (the function's own copies) can be reassigned
+        let source = r#"
+            fn countdown(n: int) int {
+                var steps: int = 0;
+                while (n > 0) {
+                    n = n - 1;
+                    steps = steps + 1;
+                }
+                ret steps;
+            }
+            chif main() { con.out(countdown(3)); }
+        "#;
+        let result = SemanticAnalyzer::new().analyze(&parse(source));
+        assert!(result.is_ok(), "{:?}", result.err());
+    }
+    
+    fn account_program(main_body: &str) -> String {
+        format!(r#"
+            struct Account {{ owner: str, priv balance: int, }}
+            fn_for Account {{
+                fn open(owner: str) Self {{
+                    ret Account {{ owner = owner, balance = 0 }};
+                }}
+                fn deposit(self, amount: int) {{
+                    self.balance = self.balance + amount;
+                }}
+                fn balance(self) int {{
+                    ret self.balance;
+                }}
+            }}
+            chif main() {{
+                var account: Account = Account.open("ann");
+                {}
+            }}
+        "#, main_body)
+    }
+    
+    #[test]
+    fn test_private_fields_are_only_reachable_from_methods() {
+        let source = account_program("account.deposit(5); let total: int = account.balance(); let owner: str = account.owner;");
+        let result = SemanticAnalyzer::new().analyze(&parse(&source));
+        assert!(result.is_ok(), "{:?}", result.err());
+        
+        for (body, expected) in [
+            ("let total: int = account.balance;", "Field 'balance' of struct 'Account' is private"),
+            ("account.balance = 100;", "Field 'balance' of struct 'Account' is private"),
+            ("var other: Account = Account { owner = \"bob\", balance = 100 };", "construct it with an associated function"),
+        ] {
+            match SemanticAnalyzer::new().analyze(&parse(&account_program(body))) {
+                Err(SemanticError::InvalidOperation { message, .. }) => {
+                    assert!(message.contains(expected), "{}: {}", body, message);
+                }
+                other => panic!("expected an invalid operation for {}, got {:?}", body, other),
+            }
+        }
+    }
+    
+    #[test]
+    fn test_discard_binds_nothing() {
+        // Repeated `_` in one scope does not collide
+        let source = r#"
+            fn divmod(a: int, b: int) (int, int) {
+                ret (a / b, a % b);
+            }
+            chif main() {
+                var _ = divmod(7, 2);
+                var _ = 1 + 2;
+                _ = divmod(9, 4);
+                var (_, r) = divmod(7, 2);
+                var (q, _) = divmod(7, 2);
+                let sum: int = q + r;
+            }
+        "#;
+        let mut analyzer = SemanticAnalyzer::new();
+        let result = analyzer.analyze(&parse(source));
+        assert!(result.is_ok(), "{:?}", result.err());
+        assert!(analyzer.symbol_table.scopes.iter().all(|scope| scope.symbols.values().all(|symbol| symbol.name != "_")));
+        
+        let tokens = Lexer::new("chif main() { var _ = 1; var x: int = _ + 1; }").tokenize().unwrap();
+        let error = Parser::new(tokens).parse().expect_err("reading _ should not parse");
+        assert!(error.to_string().contains("cannot read from '_'"), "{}", error);
+        
+        // An AST built by hand is still rejected by the analyzer
+        let program = Program {
+            items: vec![main_with(vec![
+                Statement::Expression(Expression::Identifier("_".to_string())),
+            ])],
+        };
+        match SemanticAnalyzer::new().analyze(&program) {
+            Err(SemanticError::InvalidOperation { message, .. }) => assert_eq!(message, "cannot read from '_'"),
+            other => panic!("expected an invalid operation, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_range_loop_checks_its_arguments() {
+        let analyze = |body: &str| {
+            SemanticAnalyzer::new().analyze(&parse(&format!("chif main() {{ var n: int = 3; {} }}", body)))
+        };
+        
+        assert!(analyze("for i in range(0, n) { let x: int = i * 2; }").is_ok());
+        assert!(analyze("for i in range(n, 0, -1) { } for i in range(0, 9, n) { }").is_ok());
+        
+        let invalid = [
+            ("for i in range(0, 10, 0) { }", "step cannot be zero"),
+            ("for i in range(0, 10, -0) { }", "step cannot be zero"),
+            ("for i in range(10) { }", "expects 2 or 3 arguments, got 1"),
+            ("for i in range(0, 1, 2, 3) { }", "expects 2 or 3 arguments, got 4"),
+            ("for i in [1, 2] { }", "can only iterate over range"),
+        ];
+        for (body, expected) in invalid {
+            match analyze(body) {
+                Err(SemanticError::InvalidOperation { message, .. }) => assert!(message.contains(expected), "{}: {}", body, message),
+                other => panic!("{}: expected an invalid operation, got {:?}", body, other),
+            }
+        }
+        
+        assert!(matches!(
+            analyze("for i in range(0, 2.5) { }"),
+            Err(SemanticError::TypeMismatch { expected: ChifType::Int, found: ChifType::Float, .. })
+        ));
+        // The loop variable is scoped to the loop
+        assert!(analyze("for i in range(0, 3) { } let x: int = i;").is_err());
+    }
+
+    #[test]
+    fn test_writes_through_references_need_mut() {
+        let analyze = |source: &str| SemanticAnalyzer::new().analyze(&parse(source));
+        
+        let valid = r#"
+            struct Counter { n: int, }
+            fn swap(a: &mut int, b: &mut int) { var t: int = *a; *a = *b; *b = t; }
+            fn bump(c: &mut Counter) { c.n = c.n + 1; }
+            fn total(v: &int, c: &Counter) int { ret *v + c.n; }
+            chif main() {
+                var x: int = 1;
+                var y: int = 2;
+                swap(&mut x, &mut y);
+                var c: Counter = Counter { n = 0 };
+                bump(&mut c);
+                let sum: int = total(&x, &c);
+            }
+        "#;
+        assert!(analyze(valid).is_ok(), "{:?}", analyze(valid).err());
+        
+        let invalid = [
+            ("fn set(p: &int) { *p = 1; } chif main() { var x: int = 0; set(&x); }", "Cannot write through 'p'"),
+            ("struct C { n: int, } fn set(p: &C) { p.n = 1; } chif main() { }", "Cannot write through 'p'"),
+            ("fn set(p: pointer[int]) { *p = 1; } chif main() { }", "Cannot write through 'p'"),
+            ("fn set(p: &mut int) { *p = 1; } chif main() { let x: int = 0; set(&mut x); }", "immutable variable 'x'"),
+            ("fn set(p: &mut int) { *p = 1; } chif main() { var x: int = 0; set(&x); }", "pass the argument as &mut"),
+        ];
+        for (source, expected) in invalid {
+            match analyze(source) {
+                Err(SemanticError::InvalidOperation { message, .. }) => assert!(message.contains(expected), "{}: {}", source, message),
+                other => panic!("{}: expected an invalid operation, got {:?}", source, other),
+            }
+        }
+    }
+
+    #[test]
+    fn test_int_division_is_not_promoted_where_float_is_expected() {
+        let analyze = |body: &str| {
+            let source = format!(
+                "struct P {{ x: float, }}\nfn half(x: float) float {{ ret x / 2.0; }}\nchif main() {{ var a: int = 7; var b: int = 2; {} }}",
+                body
+            );
+            SemanticAnalyzer::new().analyze(&parse(&source))
+        };
+        
+        for valid in ["var q: int = a / b;", "var f: float = fdiv(a, b);", "var f: float = a;", "var f: float = 7.0 / 2.0;"] {
+            assert!(analyze(valid).is_ok(), "{}: {:?}", valid, analyze(valid).err());
+        }
+        
+        let invalid = [
+            "var f: float = a / b;",
+            "var f: float = 0.0; f = a / 2;",
+            "let h: float = half(a / b);",
+            "var p: P = P { x = a / b };",
+        ];
+        for body in invalid {
+            match analyze(body) {
+                Err(SemanticError::InvalidOperation { message, .. }) => assert!(message.contains("use fdiv(a, b)"), "{}: {}", body, message),
+                other => panic!("{}: expected an invalid operation, got {:?}", body, other),
+            }
+        }
+        
+        let returned = "fn ratio(a: int, b: int) float { ret a / b; } chif main() { }";
+        assert!(matches!(
+            SemanticAnalyzer::new().analyze(&parse(returned)),
+            Err(SemanticError::InvalidOperation { .. })
+        ));
+    }
+    
+    #[test]
+    fn test_conversions_accept_strings_and_numbers() {
+        let analyze = |body: &str| {
+            let source = format!("chif main() {{ var s: str = \"12\"; {} }}", body);
+            SemanticAnalyzer::new().analyze(&parse(&source))
+        };
+        
+        for valid in ["var i: int = toInt(s);", "var i: int = toInt(1.5);", "var f: float = toFloat(s);", "var f: float = toFloat(3);"] {
+            assert!(analyze(valid).is_ok(), "{}: {:?}", valid, analyze(valid).err());
+        }
+        // Вариант от str возвращает тот же тип, что и числовой
+        assert!(matches!(analyze("var b: bool = toInt(s);"), Err(SemanticError::TypeMismatch { .. })));
+        assert!(matches!(analyze("var i: int = toInt(true);"), Err(SemanticError::TypeMismatch { .. })));
+    }
+    
+    #[test]
+    fn test_main_returns_only_int() {
+        for valid in ["ret;", "ret 3;", "var code: int = 2; if (code > 1) { ret code; }", "for i in range(0, 3) { ret i; }"] {
+            let source = format!("chif main() {{ {} }}", valid);
+            assert!(SemanticAnalyzer::new().analyze(&parse(&source)).is_ok(), "{}", valid);
+        }
+        
+        for invalid in ["ret \"oops\";", "ret 1.5;", "ret true;", "while (true) { if (true) { ret \"done\"; } }"] {
+            let source = format!("chif main() {{ {} }}", invalid);
+            match SemanticAnalyzer::new().analyze(&parse(&source)) {
+                Err(SemanticError::InvalidOperation { message, .. }) => {
+                    assert!(message.starts_with("main can only return int"), "{}: {}", invalid, message)
+                }
+                other => panic!("{}: expected an invalid operation, got {:?}", invalid, other),
+            }
+        }
+        
+        // Правило касается только main: у других функций без типа свои проверки
+        let helper = "fn helper() { ret; } chif main() { helper(); ret 0; }";
+        assert!(SemanticAnalyzer::new().analyze(&parse(helper)).is_ok());
+    }
+    
+    #[test]
+    fn test_http_response_fields_are_typed() {
+        let valid = r#"chif main() {
+            var response: HttpResponse = http_put("http://localhost/items", "{}");
+            let ok: bool = response.ok;
+            let status: int = response.status;
+            let error: str = response.error;
+            let content_type: str = response.content_type;
+            ret 0;
+        }"#;
+        assert!(SemanticAnalyzer::new().analyze(&parse(valid)).is_ok());
+        
+        let invalid = r#"chif main() {
+            var response: HttpResponse = http_delete("http://localhost/items");
+            let ok: int = response.ok;
+            ret 0;
+        }"#;
+        assert!(SemanticAnalyzer::new().analyze(&parse(invalid)).is_err());
+    }
+    
+    #[test]
+    fn test_assignment_targets_of_every_shape_are_type_checked() {
+        let list_of = |element: ChifType| ChifType::List(Box::new(element), vec![0]);
+        let prelude = r#"
+            struct Inner { xs: list[int], n: int, }
+            struct Outer { inner: Inner, name: str, }
+        "#;
+        let body = |statement: &str| format!(r#"
+            chif main() {{
+                array a: int[3] = [1, 2, 3];
+                array grid: int[2][3] = [[1, 2, 3], [4, 5, 6]];
+                list l: int[] = [1, 2];
+                list rows: int[][] = [[1], [2]];
+                list ratios: float[] = [1.5];
+                var m: map[str:int] = {{"a": 1}};
+                var p: Outer = Outer {{ inner = Inner {{ xs = [1, 2, 3], n = 1 }}, name = "o" }};
+                array ps: Inner[2] = [Inner {{ xs = [1], n = 1 }}, Inner {{ xs = [1], n = 2 }}];
+                var x: int = 1;
+                var q: pointer[Outer] = &p;
+                var r: pointer[int] = &x;
+                {}
+                ret 0;
+            }}
+        "#, statement);
+        let function = |statement: &str| format!(r#"
+            fn set(target: &mut int, outer: &mut Outer) {{ {} }}
+            chif main() {{ ret 0; }}
+        "#, statement);
+        let method = |statement: &str| format!(r#"
+            fn_for Inner {{
+                fn set(self) {{ {} }}
+            }}
+            chif main() {{ ret 0; }}
+        "#, statement);
+        let analyze = |source: String| {
+            let tokens = Lexer::new(&format!("{}{}", prelude, source)).tokenize().expect("source should tokenize");
+            let program = Parser::new(tokens).parse().expect("source should parse");
+            SemanticAnalyzer::new().analyze(&program)
+        };
+        
+        let mismatches = [
+            (body(r#"a[0] = "s";"#), ChifType::Int, ChifType::Str),
+            (body("grid[0][1] = 1.5;"), ChifType::Int, ChifType::Float),
+            (body(r#"l[0] = "s";"#), ChifType::Int, ChifType::Str),
+            (body(r#"rows[0][0] = "s";"#), ChifType::Int, ChifType::Str),
+            (body(r#"rows[0] = ["s"];"#), list_of(ChifType::Int), ChifType::Array(Box::new(ChifType::Str), vec![1])),
+            (body(r#"m["a"] = "s";"#), ChifType::Int, ChifType::Str),
+            (body("m[1] = 2;"), ChifType::Str, ChifType::Int),
+            (body("p.name = 5;"), ChifType::Str, ChifType::Int),
+            (body(r#"p.inner.n = "s";"#), ChifType::Int, ChifType::Str),
+            (body("p.inner.xs[2] = 1.5;"), ChifType::Int, ChifType::Float),
+            (body("p.inner = p;"), ChifType::Struct("Inner".to_string()), ChifType::Struct("Outer".to_string())),
+            (body(r#"ps[0].n = "s";"#), ChifType::Int, ChifType::Str),
+            (body("q.name = 1;"), ChifType::Str, ChifType::Int),
+            (body("(*q).name = 1;"), ChifType::Str, ChifType::Int),
+            (body(r#"*r = "s";"#), ChifType::Int, ChifType::Str),
+            (function(r#"*target = "s";"#), ChifType::Int, ChifType::Str),
+            (function(r#"outer.inner.n = "s";"#), ChifType::Int, ChifType::Str),
+            (method(r#"self.n = "s";"#), ChifType::Int, ChifType::Str),
+            (method("self.xs[0] = 1.5;"), ChifType::Int, ChifType::Float),
+            (method(r#"self.xs = "s";"#), list_of(ChifType::Int), ChifType::Str),
+        ];
+        for (source, expected_type, found_type) in mismatches {
+            match analyze(source.clone()) {
+                Err(SemanticError::TypeMismatch { expected, found, .. }) => {
+                    assert_eq!((expected, found), (expected_type, found_type), "{}", source)
+                }
+                other => panic!("expected a type mismatch, got {:?} for {}", other, source),
+            }
+        }
+        
+        let valid = [
+            body("a[0] = 7; grid[1] = [0, 0, 0]; grid[1][2] = 9;"),
+            body("l[0] = 3; rows[0] = [1, 2]; rows[1][0] = 4; ratios[0] = 2;"),
+            body(r#"m["b"] = 2; var v: int = m["a"];"#),
+            body(r#"p.name = "n"; p.inner.xs[0] = 4; ps[1].n = 3; q.name = "q"; (*q).inner.n = 2; *r = 5;"#),
+            function("*target = 1; outer.inner.n = 2; outer.name = \"n\";"),
+            method("self.n = 2; self.xs[0] = 3; self.xs = [4, 5];"),
+        ];
+        for source in valid {
+            assert!(analyze(source.clone()).is_ok(), "{}", source);
+        }
+    }
+    
+    #[test]
+    fn test_nested_map_and_list_access_types() {
+        let check = |statement: &str| {
+            let source = format!(r#"
+                chif main() {{
+                    var config: map[str:list[map[str:str]]] = {{"servers": [{{"host": "alpha"}}]}};
+                    list empty: int[] = [];
+                    {}
+                    ret 0;
+                }}
+            "#, statement);
+            SemanticAnalyzer::new().analyze(&parse(&source))
+        };
+        
+        assert!(check(r#"let host: str = config["servers"][0]["host"];"#).is_ok());
+        assert!(check(r#"let n: int = config["servers"][0]["host"].len() + config["servers"].len();"#).is_ok());
+        assert!(check(r#"list keys: str[] = config["servers"][0].keys();"#).is_ok());
+        
+        let mismatches = [
+            (r#"let host: int = config["servers"][0]["host"];"#, ChifType::Int, ChifType::Str),
+            (r#"let host: str = config["servers"]["0"];"#, ChifType::Int, ChifType::Str),
+            (r#"let count: str = config["servers"][0].len();"#, ChifType::Str, ChifType::Int),
+            ("let host: str = config[0];", ChifType::Str, ChifType::Int),
+        ];
+        for (statement, expected_type, found_type) in mismatches {
+            match check(statement) {
+                Err(SemanticError::TypeMismatch { expected, found, .. }) => {
+                    assert_eq!((expected, found), (expected_type, found_type), "{}", statement)
+                }
+                other => panic!("{}: expected a type mismatch, got {:?}", statement, other),
+            }
+        }
+        match check(r#"config["servers"][0].push("x");"#) {
+            Err(SemanticError::InvalidOperation { message, .. }) => assert!(message.contains("Unknown method 'push' for map"), "{}", message),
+            other => panic!("unknown map method should be rejected, got {:?}", other),
+        }
+    }
+    
+    #[test]
+    fn test_nested_collection_types_accept_nested_literals() {
+        let source = r#"
+            chif main() int {
+                var g: array[array[int]] = [[1, 2], [3, 4]];
+                var xs: list[list[int]] = [[1]];
+                list rows: int[][] = [[5, 6]];
+                ret g[1][0] * 100 + xs[0][0] * 10 + rows[0][1];
+            }
+        "#;
+        let program = parse(source);
+        let result = SemanticAnalyzer::new().analyze(&program);
+        assert!(result.is_ok(), "nested collection types should accept nested literals: {:?}", result.err());
+        
+        let mut interpreter = Interpreter::new();
+        interpreter.load(&program).expect("program should load");
+        match interpreter.call_function_by_name("main") {
+            Ok(ChifValue::Int(value)) => assert_eq!(value, 316),
+            other => panic!("main should return an int, got {:?}", other),
+        }
+        
+        // A flat literal is not a list of lists, and the message names the types as they are written
+        match SemanticAnalyzer::new().analyze(&parse("chif main() { var xs: list[list[int]] = [1]; }")) {
+            Err(error @ SemanticError::TypeMismatch { .. }) => {
+                assert_eq!(error.message(), "Type mismatch: expected list[list[int][]][], found array[int][1]");
+            }
+            other => panic!("a flat literal should not fit list[list[int]], got {:?}", other),
+        }
+    }
+    
+    #[test]
+    fn test_global_variables_are_shared_by_functions() {
+        let program = parse(r#"
+            var calls: int = 0;
+            let limit: int = default_limit();
+
+            fn default_limit() int {
+                ret 3;
+            }
+
+            fn tick() {
+                calls = calls + 1;
+            }
+
+            fn shadowed() int {
+                var calls: int = 100;
+                calls = calls + 1;
+                ret calls;
+            }
+
+            chif main() int {
+                tick();
+                tick();
+                ret calls * 1000 + shadowed() + limit * 10;
+            }
+        "#);
+        let result = SemanticAnalyzer::new().analyze(&program);
+        assert!(result.is_ok(), "globals should be visible in functions: {:?}", result.err());
+        
+        let mut interpreter = Interpreter::new();
+        interpreter.load(&program).expect("program should load");
+        match interpreter.call_function_by_name("main") {
+            Ok(ChifValue::Int(value)) => assert_eq!(value, 2131),
+            other => panic!("main should return an int, got {:?}", other),
+        }
+        
+        let rejected = |source: &str| SemanticAnalyzer::new().analyze(&parse(source)).expect_err(source);
+        assert!(matches!(rejected("let limit: int = 3;\nfn f() { limit = 4; }"), SemanticError::InvalidOperation { message, .. } if message.contains("immutable variable 'limit'")));
+        assert!(matches!(rejected("var a: int = b;\nvar b: int = 1;"), SemanticError::UndefinedSymbol { symbol, .. } if symbol == "b"));
+        assert!(matches!(rejected("var a: int = \"x\";"), SemanticError::TypeMismatch { .. }));
+        assert!(matches!(rejected("var f: int = 1;\nfn f() { }"), SemanticError::SymbolAlreadyDefined { .. }));
+        
+        // Модуль загружается без инициализации, поэтому переменных в нём нет
+        let dir = tempfile::tempdir().expect("temp dir should be created");
+        std::fs::write(dir.path().join("state.rono"), "var count: int = 0;\nfn get() int { ret count; }\n").expect("module should be written");
+        let source = format!("import \"{}/state\";\nchif main() {{ }}", dir.path().display());
+        match rejected(&source) {
+            SemanticError::InvalidOperation { message, .. } => {
+                assert!(message.contains("Global variable 'count' is not allowed in an imported module"), "{}", message)
+            }
+            other => panic!("globals in a module should be rejected, got {:?}", other),
+        }
+    }
+}
@@ -0,0 +1,96 @@
+use std::ffi::OsStr;
+use std::fmt::Display;
+use std::io::IsTerminal;
+use std::sync::atomic::{AtomicU8, Ordering};
+
+/// Когда раскрашивать вывод (`--color`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ColorChoice {
+    /// Только если поток — терминал и `NO_COLOR` не задан
+    Auto,
+    Always,
+    Never,
+}
+
+impl ColorChoice {
+    pub fn parse(name: &str) -> Option<Self> {
+        match name {
+            "auto" => Some(ColorChoice::Auto),
+            "always" => Some(ColorChoice::Always),
+            "never" => Some(ColorChoice::Never),
+            _ => None,
+        }
+    }
+}
+
+/// Поток, в который выводится текст: от него зависит `auto`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Stream {
+    Stdout,
+    Stderr,
+}
+
+/// Роль фрагмента текста; цвет выбирается здесь, а не в местах вывода
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Style {
+    Error,
+    Warning,
+    Info,
+    Success,
+    /// Путь и позиция в исходнике
+    Location,
+}
+
+impl Style {
+    fn code(self) -> &'static str {
+        match self {
+            Style::Error => "1;31",
+            Style::Warning => "1;33",
+            Style::Info => "1;36",
+            Style::Success => "1;32",
+            Style::Location => "1;34",
+        }
+    }
+}
+
+// Библиотека сама не раскрашивает: политику задаёт CLI
+static COLOR: AtomicU8 = AtomicU8::new(ColorChoice::Never as u8);
+
+pub fn set_color_choice(choice: ColorChoice) {
+    COLOR.store(choice as u8, Ordering::Relaxed);
+}
+
+pub fn color_choice() -> ColorChoice {
+    match COLOR.load(Ordering::Relaxed) {
+        0 => ColorChoice::Auto,
+        1 => ColorChoice::Always,
+        _ => ColorChoice::Never,
+    }
+}
+
+/// Решение без обращения к окружению. Явный `always` сильнее `NO_COLOR`;
+/// пустой `NO_COLOR` не считается заданным
+pub fn use_color(choice: ColorChoice, no_color: Option<&OsStr>, is_terminal: bool) -> bool {
+    match choice {
+        ColorChoice::Always => true,
+        ColorChoice::Never => false,
+        ColorChoice::Auto => is_terminal && no_color.map(OsStr::is_empty).unwrap_or(true),
+    }
+}
+
+pub fn enabled(stream: Stream) -> bool {
+    let is_terminal = match stream {
+        Stream::Stdout => std::io::stdout().is_terminal(),
+        Stream::Stderr => std::io::stderr().is_terminal(),
+    };
+    use_color(color_choice(), std::env::var_os("NO_COLOR").as_deref(), is_terminal)
+}
+
+/// `text` в цвете `style`, если политика разрешает цвет для `stream`
+pub fn paint(text: impl Display, style: Style, stream: Stream) -> String {
+    if enabled(stream) {
+        format!("\x1b[{}m{}\x1b[0m", style.code(), text)
+    } else {
+        text.to_string()
+    }
+}
@@ -0,0 +1,266 @@
+//! `rono test`: функции `test_*` без параметров выполняются в интерпретаторе,
+//! каждая в своём экземпляре, параллельно. Тест проходит, если он завершился
+//! без ошибки и не вернул `false`. Необязательные `setup()` и `teardown()`
+//! выполняются до и после каждого теста в том же экземпляре интерпретатора.
+//! Общее состояние тестов — глобальные переменные файла: каждый тест получает
+//! их заново вычисленными, а `setup` может их изменить
+
+use crate::ast::{Item, Program};
+use crate::error::{ChifError, RonoError};
+use crate::interpreter::Interpreter;
+use crate::semantic::SemanticAnalyzer;
+use crate::types::ChifValue;
+use std::fmt;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Сколько самых медленных тестов показывает итог
+const SLOWEST_LISTED: usize = 5;
+
+/// Параметры запуска `rono test`
+#[derive(Debug, Clone)]
+pub struct TestOptions {
+    /// Лимит времени теста без комментария `// timeout: ...`
+    pub timeout: Duration,
+    /// Сколько тестов выполняется одновременно
+    pub jobs: usize,
+}
+
+impl Default for TestOptions {
+    fn default() -> Self {
+        Self {
+            timeout: Duration::from_secs(30),
+            jobs: std::thread::available_parallelism().map_or(1, |jobs| jobs.get()),
+        }
+    }
+}
+
+/// Тест программы: функция `test_*` и её лимит из комментария `// timeout: 5s`
+#[derive(Debug, Clone, PartialEq)]
+pub struct TestCase {
+    pub name: String,
+    pub timeout: Option<Duration>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum TestStatus {
+    Passed,
+    /// Сообщение начинается с `setup: ` или `teardown: `, если упали они
+    Failed(String),
+    TimedOut(Duration),
+}
+
+impl fmt::Display for TestStatus {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            TestStatus::Passed => write!(f, "ok"),
+            TestStatus::Failed(message) => write!(f, "{}", message),
+            TestStatus::TimedOut(limit) => write!(f, "timed out after {:?}", limit),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct TestResult {
+    pub name: String,
+    pub status: TestStatus,
+    pub duration: Duration,
+    /// Строки con.out теста; печатаются только у непрошедших
+    pub output: Vec<String>,
+}
+
+/// Результаты в порядке объявления тестов
+#[derive(Debug, Clone, Default)]
+pub struct TestReport {
+    pub results: Vec<TestResult>,
+}
+
+impl TestReport {
+    pub fn passed(&self) -> usize {
+        self.results.iter().filter(|result| result.status == TestStatus::Passed).count()
+    }
+
+    pub fn failed(&self) -> usize {
+        self.results.iter().filter(|result| matches!(result.status, TestStatus::Failed(_))).count()
+    }
+
+    pub fn timed_out(&self) -> usize {
+        self.results.iter().filter(|result| matches!(result.status, TestStatus::TimedOut(_))).count()
+    }
+
+    pub fn success(&self) -> bool {
+        self.passed() == self.results.len()
+    }
+
+    /// Не больше `count` тестов, от самого долгого
+    pub fn slowest(&self, count: usize) -> Vec<&TestResult> {
+        let mut results: Vec<&TestResult> = self.results.iter().collect();
+        results.sort_by_key(|result| std::cmp::Reverse(result.duration));
+        results.truncate(count);
+        results
+    }
+}
+
+impl fmt::Display for TestReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for result in &self.results {
+            let duration = crate::bench::format_duration(result.duration);
+            match &result.status {
+                TestStatus::Passed => writeln!(f, "test {} ... ok ({})", result.name, duration)?,
+                TestStatus::Failed(message) => writeln!(f, "test {} ... FAILED ({}): {}", result.name, duration, message)?,
+                TestStatus::TimedOut(_) => writeln!(f, "test {} ... TIMEOUT: {}", result.name, result.status)?,
+            }
+            if result.status != TestStatus::Passed {
+                for line in &result.output {
+                    writeln!(f, "    | {}", line)?;
+                }
+            }
+        }
+
+        writeln!(
+            f,
+            "\n{} passed; {} failed; {} timed out",
+            self.passed(), self.failed(), self.timed_out()
+        )?;
+        if self.results.len() > 1 {
+            writeln!(f, "slowest tests:")?;
+            for result in self.slowest(SLOWEST_LISTED) {
+                writeln!(f, "  {:>12}  {}", crate::bench::format_duration(result.duration), result.name)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// `5s`, `500ms`, `2m` или `1.5s`; число без единицы — секунды
+pub fn parse_duration(text: &str) -> Result<Duration, String> {
+    let text = text.trim();
+    let (number, unit) = match text.find(|c: char| c.is_ascii_alphabetic()) {
+        Some(split) => text.split_at(split),
+        None => (text, "s"),
+    };
+    let value: f64 = number.trim().parse()
+        .map_err(|_| format!("invalid duration '{}': expected a number with ms, s or m, like 5s", text))?;
+    let seconds = match unit {
+        "ms" => value / 1000.0,
+        "s" => value,
+        "m" => value * 60.0,
+        _ => return Err(format!("invalid duration '{}': unknown unit '{}', use ms, s or m", text, unit)),
+    };
+    Duration::try_from_secs_f64(seconds)
+        .map_err(|_| format!("invalid duration '{}': it must be positive", text))
+}
+
+/// Тесты программы в порядке объявления. Лимит теста берётся из комментария
+/// `// timeout: 5s` в строках прямо перед `fn test_...`
+pub fn discover(program: &Program, source: &str) -> Result<Vec<TestCase>, ChifError> {
+    let lines: Vec<&str> = source.lines().map(str::trim).collect();
+    let mut cases = Vec::new();
+    for item in &program.items {
+        let Item::Function(func) = item else { continue };
+        if !func.name.starts_with("test_") || !func.params.is_empty() {
+            continue;
+        }
+
+        let header = format!("fn {}(", func.name);
+        let line = lines.iter().position(|line| line.starts_with(&header) || line.starts_with(&format!("export {}", header)));
+        let mut timeout = None;
+        for comment in line.map_or(&[][..], |line| &lines[..line]).iter().rev() {
+            let Some(text) = comment.strip_prefix("//") else { break };
+            if let Some(value) = text.trim().strip_prefix("timeout:") {
+                timeout = Some(parse_duration(value).map_err(|message| ChifError::InvalidOperation {
+                    message: format!("{}: {}", func.name, message),
+                })?);
+            }
+        }
+        cases.push(TestCase { name: func.name.clone(), timeout });
+    }
+    Ok(cases)
+}
+
+/// Проверяет программу из `source` анализатором и выполняет её тесты в
+/// `options.jobs` потоках. Интерпретатор не передаётся между потоками, поэтому
+/// каждый поток разбирает программу сам
+pub fn run_tests(source: &str, options: &TestOptions) -> Result<TestReport, RonoError> {
+    let program = crate::parse_source(source)?;
+    SemanticAnalyzer::new().check_program(&program)?;
+    let cases = discover(&program, source).map_err(RonoError::runtime)?;
+    let has_function = |name: &str| program.items.iter().any(|item| matches!(item, Item::Function(func) if func.name == name));
+    let hooks = (has_function("setup"), has_function("teardown"));
+
+    let next = AtomicUsize::new(0);
+    let results = Mutex::new(vec![None; cases.len()]);
+    std::thread::scope(|scope| {
+        for _ in 0..options.jobs.clamp(1, cases.len().max(1)) {
+            scope.spawn(|| {
+                let program = crate::parse_source(source).expect("the source was parsed before the tests started");
+                loop {
+                    let index = next.fetch_add(1, Ordering::Relaxed);
+                    let Some(case) = cases.get(index) else { break };
+                    let result = run_test(&program, case, case.timeout.unwrap_or(options.timeout), hooks);
+                    results.lock().expect("a test thread panicked")[index] = Some(result);
+                }
+            });
+        }
+    });
+
+    let results = results.into_inner().expect("a test thread panicked");
+    Ok(TestReport {
+        results: results.into_iter().map(|result| result.expect("every test was run")).collect(),
+    })
+}
+
+/// Один тест в новом интерпретаторе: глобальные переменные вычисляются при
+/// загрузке программы, и их изменения видны только этому тесту. `teardown`
+/// выполняется, если `setup` прошёл, и получает свой лимит времени
+fn run_test(program: &Program, case: &TestCase, timeout: Duration, (setup, teardown): (bool, bool)) -> TestResult {
+    let started = Instant::now();
+    let mut interpreter = Interpreter::new();
+    interpreter.capture_output();
+    interpreter.set_time_limit(Some(timeout));
+
+    let mut status = match interpreter.load(program) {
+        Ok(()) if setup => hook_status(interpreter.call_function_by_name("setup"), "setup"),
+        Ok(()) => TestStatus::Passed,
+        Err(error) => TestStatus::Failed(error.to_string()),
+    };
+
+    if status == TestStatus::Passed {
+        status = match interpreter.call_function_by_name(&case.name) {
+            Ok(ChifValue::Bool(false)) => TestStatus::Failed("returned false".to_string()),
+            Ok(_) => TestStatus::Passed,
+            Err(error) => failure(error, ""),
+        };
+        if teardown {
+            interpreter.set_time_limit(Some(timeout));
+            let teardown_status = hook_status(interpreter.call_function_by_name("teardown"), "teardown");
+            // Первой сообщается ошибка самого теста
+            if status == TestStatus::Passed {
+                status = teardown_status;
+            }
+        }
+    }
+
+    TestResult {
+        name: case.name.clone(),
+        status,
+        duration: started.elapsed(),
+        output: interpreter.take_output(),
+    }
+}
+
+fn hook_status(result: crate::error::Result<ChifValue>, hook: &str) -> TestStatus {
+    match result {
+        Ok(_) => TestStatus::Passed,
+        Err(error) => failure(error, hook),
+    }
+}
+
+fn failure(error: ChifError, hook: &str) -> TestStatus {
+    match error {
+        ChifError::TimeLimitExceeded { limit } if hook.is_empty() => TestStatus::TimedOut(limit),
+        error if hook.is_empty() => TestStatus::Failed(error.to_string()),
+        error => TestStatus::Failed(format!("{}: {}", hook, error)),
+    }
+}
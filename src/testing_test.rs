@@ -0,0 +1,136 @@
+#[cfg(test)]
+mod tests {
+    use crate::testing::{discover, parse_duration, run_tests, TestCase, TestOptions, TestStatus};
+    use std::time::{Duration, Instant};
+
+    const TIMEOUT: &str = include_str!("../tests/testing/timeout.rono");
+    const FAILING: &str = include_str!("../tests/testing/failing.rono");
+    const ISOLATION: &str = include_str!("../tests/testing/isolation.rono");
+
+    fn statuses(source: &str, options: &TestOptions) -> Vec<(String, TestStatus)> {
+        let report = run_tests(source, options).expect("tests should run");
+        report.results.into_iter().map(|result| (result.name, result.status)).collect()
+    }
+
+    #[test]
+    fn test_parse_duration() {
+        assert_eq!(parse_duration("5s"), Ok(Duration::from_secs(5)));
+        assert_eq!(parse_duration("250ms"), Ok(Duration::from_millis(250)));
+        assert_eq!(parse_duration("2m"), Ok(Duration::from_secs(120)));
+        assert_eq!(parse_duration("1.5s"), Ok(Duration::from_millis(1500)));
+        assert_eq!(parse_duration("3"), Ok(Duration::from_secs(3)));
+        assert!(parse_duration("5h").unwrap_err().contains("unknown unit 'h'"));
+        assert!(parse_duration("-1s").is_err());
+        assert!(parse_duration("soon").is_err());
+    }
+
+    #[test]
+    fn test_discover_reads_timeout_comments() {
+        let program = crate::parse_source(TIMEOUT).unwrap();
+        assert_eq!(discover(&program, TIMEOUT).unwrap(), [
+            TestCase { name: "test_spins_forever".to_string(), timeout: Some(Duration::from_millis(200)) },
+            TestCase { name: "test_quick".to_string(), timeout: None },
+        ]);
+
+        let source = "// timeout: often\nfn test_x() {\n}\n";
+        let error = discover(&crate::parse_source(source).unwrap(), source).unwrap_err();
+        assert!(error.to_string().contains("test_x: invalid duration 'often'"), "{}", error);
+    }
+
+    #[test]
+    fn test_runaway_test_hits_its_timeout() {
+        let started = Instant::now();
+        assert_eq!(statuses(TIMEOUT, &TestOptions::default()), [
+            ("test_spins_forever".to_string(), TestStatus::TimedOut(Duration::from_millis(200))),
+            ("test_quick".to_string(), TestStatus::Passed),
+        ]);
+        assert!(started.elapsed() < Duration::from_secs(10), "{:?}", started.elapsed());
+
+        // --timeout applies to tests without a comment
+        let source = "fn test_spins() {\n    while (true) {\n    }\n}\n";
+        let options = TestOptions { timeout: Duration::from_millis(100), ..TestOptions::default() };
+        assert_eq!(statuses(source, &options), [
+            ("test_spins".to_string(), TestStatus::TimedOut(Duration::from_millis(100))),
+        ]);
+    }
+
+    #[test]
+    fn test_failures_are_reported_with_output_and_summary() {
+        let report = run_tests(FAILING, &TestOptions::default()).expect("tests should run");
+        let statuses: Vec<(&str, &TestStatus)> = report.results.iter()
+            .map(|result| (result.name.as_str(), &result.status))
+            .collect();
+        assert_eq!(statuses, [
+            ("test_divides", &TestStatus::Passed),
+            ("test_rounds_up", &TestStatus::Failed("returned false".to_string())),
+            ("test_by_zero", &TestStatus::Failed("Division by zero".to_string())),
+        ]);
+        assert_eq!(report.results[1].output, ["7 / 2 rounds down"]);
+        assert_eq!((report.passed(), report.failed(), report.timed_out()), (1, 2, 0));
+        assert!(!report.success());
+
+        let text = report.to_string();
+        assert!(text.contains("test test_rounds_up ... FAILED ("), "{}", text);
+        assert!(text.contains("    | 7 / 2 rounds down"), "{}", text);
+        assert!(text.contains("1 passed; 2 failed; 0 timed out"), "{}", text);
+        assert!(text.contains("slowest tests:"), "{}", text);
+        assert_eq!(report.slowest(2).len(), 2);
+    }
+
+    #[test]
+    fn test_parallel_tests_get_their_own_globals() {
+        for jobs in [1, 4] {
+            let report = run_tests(ISOLATION, &TestOptions { jobs, ..TestOptions::default() }).expect("tests should run");
+            assert!(report.success(), "jobs {}: {}", jobs, report);
+            assert_eq!(report.results.len(), 4);
+            assert!(report.results.iter().all(|result| result.output == ["teardown"]));
+        }
+    }
+
+    #[test]
+    fn test_programs_are_analyzed_before_tests_run() {
+        // Переменные setup — её локальные переменные, тестам они не видны
+        let source = r#"
+            fn setup() {
+                var counter: int = 10;
+            }
+
+            fn test_reads_setup_local() bool {
+                ret counter == 10;
+            }
+        "#;
+        let error = run_tests(source, &TestOptions::default()).expect_err("the analyzer should reject the program");
+        assert!(error.to_string().contains("Undefined symbol 'counter'"), "{}", error);
+    }
+
+    #[test]
+    fn test_setup_and_teardown_failures_are_attributed() {
+        let source = r#"
+            var divisor: int = 1;
+
+            fn setup() {
+                divisor = 0;
+            }
+
+            fn teardown() {
+                var q: int = 10 / divisor;
+            }
+
+            fn test_passes() {
+            }
+
+            fn test_fails() bool {
+                ret false;
+            }
+        "#;
+        assert_eq!(statuses(source, &TestOptions::default()), [
+            ("test_passes".to_string(), TestStatus::Failed("teardown: Division by zero".to_string())),
+            ("test_fails".to_string(), TestStatus::Failed("returned false".to_string())),
+        ]);
+
+        let source = "fn setup() {\n    var x: int = 1 / 0;\n}\n\nfn test_never_runs() {\n    con.out(\"ran\");\n}\n";
+        let report = run_tests(source, &TestOptions::default()).expect("tests should run");
+        assert_eq!(report.results[0].status, TestStatus::Failed("setup: Division by zero".to_string()));
+        assert!(report.results[0].output.is_empty());
+    }
+}
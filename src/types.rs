@@ -1,5 +1,6 @@
 use std::collections::HashMap;
 use std::fmt;
+use std::sync::Arc;
 
 #[derive(Debug, Clone, PartialEq)]
 pub enum ChifType {
@@ -13,6 +14,7 @@ pub enum ChifType {
     Map(Box<ChifType>, Box<ChifType>), // key_type, value_type
     Struct(String),                   // struct name
     Pointer(Box<ChifType>),
+    Tuple(Vec<ChifType>),             // element types
 }
 
 #[derive(Debug, Clone)]
@@ -22,12 +24,60 @@ pub enum ChifValue {
     Str(String),
     Bool(bool),
     Nil,
+    /// Результат вызова без значения: функции без `ret значение`, `con.out`, `add`.
+    /// Из исходного кода не создаётся; интерпретатор отбрасывает его в позиции
+    /// оператора и сообщает об ошибке, если его используют как значение
+    Void,
     Array(Vec<ChifValue>),
     List(Vec<ChifValue>),
-    Map(HashMap<String, ChifValue>),
-    Struct(String, HashMap<String, ChifValue>),
+    // Ключи берутся из таблицы интернирования интерпретатора: копия словаря не копирует строки
+    Map(HashMap<Arc<str>, ChifValue>),
+    Struct(String, StructFields),
     Pointer(Box<ChifValue>),
     Reference(String), // Reference to a variable name
+    Tuple(Vec<ChifValue>),
+}
+
+/// Поля значения структуры в порядке объявления
+#[derive(Debug, Clone, Default)]
+pub struct StructFields(Vec<(String, ChifValue)>);
+
+impl StructFields {
+    pub fn new() -> Self {
+        Self(Vec::new())
+    }
+
+    /// Заменяет значение существующего поля на месте, новое поле добавляет в конец
+    pub fn insert(&mut self, name: String, value: ChifValue) {
+        match self.0.iter_mut().find(|(field, _)| *field == name) {
+            Some((_, slot)) => *slot = value,
+            None => self.0.push((name, value)),
+        }
+    }
+
+    pub fn get(&self, name: &str) -> Option<&ChifValue> {
+        self.0.iter().find(|(field, _)| field == name).map(|(_, value)| value)
+    }
+
+    pub fn contains_key(&self, name: &str) -> bool {
+        self.get(name).is_some()
+    }
+
+    pub fn keys(&self) -> impl Iterator<Item = &String> {
+        self.0.iter().map(|(field, _)| field)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&String, &ChifValue)> {
+        self.0.iter().map(|(field, value)| (field, value))
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
 }
 
 impl fmt::Display for ChifType {
@@ -55,64 +105,177 @@ impl fmt::Display for ChifType {
             ChifType::Map(key, value) => write!(f, "map[{}:{}]", key, value),
             ChifType::Struct(name) => write!(f, "{}", name),
             ChifType::Pointer(inner) => write!(f, "pointer[{}]", inner),
+            ChifType::Tuple(elements) => {
+                write!(f, "(")?;
+                for (i, element) in elements.iter().enumerate() {
+                    if i > 0 { write!(f, ", ")?; }
+                    write!(f, "{}", element)?;
+                }
+                write!(f, ")")
+            }
         }
     }
 }
 
-impl fmt::Display for ChifValue {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        match self {
-            ChifValue::Int(i) => write!(f, "{}", i),
-            ChifValue::Float(fl) => write!(f, "{}", fl),
-            ChifValue::Str(s) => write!(f, "{}", s),
-            ChifValue::Bool(b) => write!(f, "{}", b),
-            ChifValue::Nil => write!(f, "nil"),
-            ChifValue::Array(arr) => {
-                write!(f, "[")?;
-                for (i, val) in arr.iter().enumerate() {
-                    if i > 0 { write!(f, ", ")?; }
-                    write!(f, "{}", val)?;
-                }
-                write!(f, "]")
-            }
-            ChifValue::List(list) => {
-                write!(f, "[")?;
-                for (i, val) in list.iter().enumerate() {
-                    if i > 0 { write!(f, ", ")?; }
-                    write!(f, "{}", val)?;
-                }
-                write!(f, "]")
-            }
+/// С этой величины float печатается с экспонентой
+pub const FLOAT_EXPONENT_THRESHOLD: f64 = 1e16;
+
+/// Текст float для con.out, toStr и интерполяции: кратчайшие цифры, которые
+/// читаются обратно в то же число, всегда с '.' (`1.0`, не `1`), экспонента
+/// только начиная с 1e16 (`1.0e16`). От локали не зависит. Так же печатает
+/// `rono_format_float` в runtime.c: рантайм собирается из C, поэтому алгоритм
+/// повторён там, а не вызывается отсюда
+pub fn format_float(value: f64) -> String {
+    if !value.is_finite() {
+        return value.to_string();
+    }
+    if value.abs() >= FLOAT_EXPONENT_THRESHOLD {
+        let scientific = format!("{:e}", value);
+        return match scientific.split_once('e') {
+            Some((mantissa, exponent)) if !mantissa.contains('.') => format!("{}.0e{}", mantissa, exponent),
+            _ => scientific,
+        };
+    }
+    let text = value.to_string();
+    if text.contains('.') {
+        text
+    } else {
+        text + ".0"
+    }
+}
+
+/// Наибольшая вложенность контейнеров при печати значения; глубже печатается `...`
+pub const MAX_DISPLAY_DEPTH: usize = 8;
+/// Наибольшая длина напечатанного значения в символах; остаток заменяется на `...`
+pub const MAX_DISPLAY_CHARS: usize = 10_000;
+
+/// Печать значения с ограничением глубины и длины.
+/// Ссылки на переменные раскрываются через `resolve`; ссылка на переменную,
+/// которая уже раскрывается выше по пути, печатается как `<cycle>`
+struct ValueWriter<'a> {
+    resolve: &'a dyn Fn(&str) -> Option<&'a ChifValue>,
+    out: String,
+    chars: usize,
+    truncated: bool,
+    path: Vec<&'a str>,
+}
+
+impl<'a> ValueWriter<'a> {
+    fn push(&mut self, text: &str) {
+        if self.truncated {
+            return;
+        }
+        let count = text.chars().count();
+        if self.chars + count <= MAX_DISPLAY_CHARS {
+            self.out.push_str(text);
+            self.chars += count;
+            return;
+        }
+        self.out.extend(text.chars().take(MAX_DISPLAY_CHARS - self.chars));
+        self.out.push_str("...");
+        self.truncated = true;
+    }
+
+    fn sequence(&mut self, open: &str, values: &'a [ChifValue], close: &str, depth: usize) {
+        self.push(open);
+        for (i, val) in values.iter().enumerate() {
+            if self.truncated { break; }
+            if i > 0 { self.push(", "); }
+            self.write(val, depth + 1);
+        }
+        self.push(close);
+    }
+
+    fn write(&mut self, value: &'a ChifValue, depth: usize) {
+        let nested = matches!(
+            value,
+            ChifValue::Array(_) | ChifValue::List(_) | ChifValue::Map(_) | ChifValue::Struct(..) | ChifValue::Tuple(_)
+        );
+        if nested && depth >= MAX_DISPLAY_DEPTH {
+            self.push("...");
+            return;
+        }
+        match value {
+            ChifValue::Int(i) => self.push(&i.to_string()),
+            ChifValue::Float(fl) => self.push(&format_float(*fl)),
+            ChifValue::Str(s) => self.push(s),
+            ChifValue::Bool(b) => self.push(&b.to_string()),
+            ChifValue::Nil | ChifValue::Void => self.push("nil"),
+            ChifValue::Array(arr) => self.sequence("[", arr, "]", depth),
+            ChifValue::List(list) => self.sequence("[", list, "]", depth),
             ChifValue::Map(map) => {
-                write!(f, "{{")?;
+                self.push("{");
                 for (i, (key, val)) in map.iter().enumerate() {
-                    if i > 0 { write!(f, ", ")?; }
-                    write!(f, "\"{}\": {}", key, val)?;
+                    if self.truncated { break; }
+                    if i > 0 { self.push(", "); }
+                    self.push(&format!("\"{}\": ", key));
+                    self.write(val, depth + 1);
                 }
-                write!(f, "}}")
+                self.push("}");
             }
             ChifValue::Struct(name, fields) => {
-                write!(f, "{} {{ ", name)?;
+                self.push(&format!("{} {{ ", name));
                 for (i, (key, val)) in fields.iter().enumerate() {
-                    if i > 0 { write!(f, ", ")?; }
-                    write!(f, "{}: {}", key, val)?;
+                    if self.truncated { break; }
+                    if i > 0 { self.push(", "); }
+                    self.push(&format!("{}: ", key));
+                    self.write(val, depth + 1);
                 }
-                write!(f, " }}")
+                self.push(" }");
+            }
+            ChifValue::Pointer(val) => {
+                self.push("&");
+                self.write(val, depth);
             }
-            ChifValue::Pointer(val) => write!(f, "&{}", val),
-            ChifValue::Reference(var_name) => write!(f, "&{}", var_name),
+            ChifValue::Reference(var_name) => {
+                if self.path.contains(&var_name.as_str()) {
+                    self.push("<cycle>");
+                    return;
+                }
+                match (self.resolve)(var_name) {
+                    Some(target) => {
+                        self.path.push(var_name);
+                        self.push("&");
+                        self.write(target, depth);
+                        self.path.pop();
+                    }
+                    None => self.push(&format!("&{}", var_name)),
+                }
+            }
+            ChifValue::Tuple(values) => self.sequence("(", values, ")", depth),
+        }
+    }
+}
+
+impl fmt::Display for ChifValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ChifValue::Str(s) => write!(f, "{}", s),
+            _ => write!(f, "{}", self.render(&|_| None)),
         }
     }
 }
 
 impl ChifValue {
+    /// Текст значения для вывода: ссылки на переменные раскрываются через `resolve`,
+    /// циклы печатаются как `<cycle>`, вложенность и длина ограничены
+    /// `MAX_DISPLAY_DEPTH` и `MAX_DISPLAY_CHARS`. Строка верхнего уровня не обрезается
+    pub fn render<'a>(&'a self, resolve: &'a dyn Fn(&str) -> Option<&'a ChifValue>) -> String {
+        if let ChifValue::Str(s) = self {
+            return s.clone();
+        }
+        let mut writer = ValueWriter { resolve, out: String::new(), chars: 0, truncated: false, path: Vec::new() };
+        writer.write(self, 0);
+        writer.out
+    }
+
     pub fn get_type(&self) -> ChifType {
         match self {
             ChifValue::Int(_) => ChifType::Int,
             ChifValue::Float(_) => ChifType::Float,
             ChifValue::Str(_) => ChifType::Str,
             ChifValue::Bool(_) => ChifType::Bool,
-            ChifValue::Nil => ChifType::Nil,
+            ChifValue::Nil | ChifValue::Void => ChifType::Nil,
             ChifValue::Array(arr) => {
                 if let Some(first) = arr.first() {
                     ChifType::Array(Box::new(first.get_type()), vec![arr.len()])
@@ -137,6 +300,7 @@ impl ChifValue {
             ChifValue::Struct(name, _) => ChifType::Struct(name.clone()),
             ChifValue::Pointer(val) => ChifType::Pointer(Box::new(val.get_type())),
             ChifValue::Reference(_) => ChifType::Pointer(Box::new(ChifType::Nil)),
+            ChifValue::Tuple(values) => ChifType::Tuple(values.iter().map(|v| v.get_type()).collect()),
         }
     }
 }
\ No newline at end of file
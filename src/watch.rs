@@ -0,0 +1,155 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::thread;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+/// Снимок файла: время изменения и размер. Размер ловит запись, которая
+/// попала в тот же такт часов файловой системы, что и предыдущая
+type Stamp = Option<(SystemTime, u64)>;
+
+fn stamp(path: &Path) -> Stamp {
+    let metadata = fs::metadata(path).ok()?;
+    Some((metadata.modified().ok()?, metadata.len()))
+}
+
+/// Файлы, за которыми следит `--watch`: точка входа и модули, которые
+/// загрузчик прочитал за последний прогон, со снимками на момент прогона
+#[derive(Debug, Clone, PartialEq)]
+pub struct WatchSet {
+    files: Vec<(PathBuf, Stamp)>,
+}
+
+impl WatchSet {
+    pub fn new(entry: &Path, modules: impl IntoIterator<Item = PathBuf>) -> Self {
+        let mut files: Vec<(PathBuf, Stamp)> = Vec::new();
+        for path in std::iter::once(entry.to_path_buf()).chain(modules) {
+            let path = fs::canonicalize(&path).unwrap_or(path);
+            if !files.iter().any(|(known, _)| *known == path) {
+                let stamp = stamp(&path);
+                files.push((path, stamp));
+            }
+        }
+        WatchSet { files }
+    }
+
+    /// Точка входа первой, затем модули в порядке загрузки
+    pub fn files(&self) -> impl Iterator<Item = &Path> {
+        self.files.iter().map(|(path, _)| path.as_path())
+    }
+
+    pub fn len(&self) -> usize {
+        self.files.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.files.is_empty()
+    }
+
+    /// Файлы, которые изменились, появились или пропали после снимка
+    pub fn changed(&self) -> Vec<PathBuf> {
+        self.files.iter()
+            .filter(|(path, snapshot)| stamp(path) != *snapshot)
+            .map(|(path, _)| path.clone())
+            .collect()
+    }
+
+    /// Те же файлы с новыми снимками
+    fn refreshed(&self) -> Self {
+        WatchSet {
+            files: self.files.iter().map(|(path, _)| (path.clone(), stamp(path))).collect(),
+        }
+    }
+}
+
+/// Итог одного прогона в режиме наблюдения
+#[derive(Debug, Clone, PartialEq)]
+pub struct WatchRun {
+    pub ok: bool,
+    pub duration: Duration,
+    /// Файлы, из-за которых был прогон; у первого прогона пусто
+    pub changed: Vec<PathBuf>,
+    /// Сколько файлов отслеживается после прогона
+    pub watched: usize,
+}
+
+impl WatchRun {
+    /// Строка состояния: время (UTC), итог, длительность и число файлов
+    pub fn status_line(&self, at: SystemTime) -> String {
+        let seconds = at.duration_since(UNIX_EPOCH).map(|since| since.as_secs()).unwrap_or(0) % 86_400;
+        let mut line = format!(
+            "[{:02}:{:02}:{:02}] {} in {} ms, watching {} file{}",
+            seconds / 3600,
+            seconds / 60 % 60,
+            seconds % 60,
+            if self.ok { "ok" } else { "failed" },
+            self.duration.as_millis(),
+            self.watched,
+            if self.watched == 1 { "" } else { "s" },
+        );
+        if let Some(first) = self.changed.first() {
+            let name = first.file_name().map_or_else(|| first.display().to_string(), |name| name.to_string_lossy().to_string());
+            line.push_str(&format!(" (changed: {}", name));
+            if self.changed.len() > 1 {
+                line.push_str(&format!(" and {} more", self.changed.len() - 1));
+            }
+            line.push(')');
+        }
+        line
+    }
+}
+
+/// Ядро `--watch` без цикла ожидания: `run` выполняет конвейер и сообщает,
+/// успешно ли; набор файлов после каждого прогона берётся у `load_module`
+pub struct Watcher<F> {
+    entry: PathBuf,
+    set: WatchSet,
+    run: F,
+    /// Сколько файлы должны не меняться, прежде чем начнётся прогон:
+    /// редактор может сохранить файл в несколько записей
+    pub debounce: Duration,
+}
+
+impl<F: FnMut() -> bool> Watcher<F> {
+    pub fn new(entry: impl Into<PathBuf>, run: F) -> Self {
+        let entry = entry.into();
+        Watcher {
+            set: WatchSet::new(&entry, Vec::new()),
+            entry,
+            run,
+            debounce: Duration::from_millis(100),
+        }
+    }
+
+    pub fn watch_set(&self) -> &WatchSet {
+        &self.set
+    }
+
+    /// Прогон и новый набор файлов: модули могли появиться или исчезнуть из импортов
+    pub fn rerun(&mut self, changed: Vec<PathBuf>) -> WatchRun {
+        crate::take_loaded_modules();
+        let started = Instant::now();
+        let ok = (self.run)();
+        let duration = started.elapsed();
+        self.set = WatchSet::new(&self.entry, crate::take_loaded_modules());
+        WatchRun { ok, duration, changed, watched: self.set.len() }
+    }
+
+    /// Одна проверка: если файлы изменились, дожидается, пока сохранения
+    /// утихнут, и перезапускает; иначе `None`
+    pub fn poll(&mut self) -> Option<WatchRun> {
+        let changed = self.set.changed();
+        if changed.is_empty() {
+            return None;
+        }
+        let mut last = self.set.refreshed();
+        loop {
+            thread::sleep(self.debounce);
+            let current = last.refreshed();
+            if current == last {
+                break;
+            }
+            last = current;
+        }
+        Some(self.rerun(changed))
+    }
+}
@@ -0,0 +1,72 @@
+#[cfg(test)]
+mod tests {
+    use crate::watch::{WatchRun, Watcher};
+    use std::cell::Cell;
+    use std::fs;
+    use std::path::PathBuf;
+    use std::time::{Duration, UNIX_EPOCH};
+
+    #[test]
+    fn test_watcher_follows_loaded_modules_and_reruns_on_change() {
+        let dir = tempfile::tempdir().expect("temp dir should be created");
+        let main = dir.path().join("main.rono");
+        let util = dir.path().join("util.rono");
+        let program_with_import = format!(r#"
+            import "{}/util";
+            chif main() {{
+                let x: int = util.twice(2);
+            }}
+        "#, dir.path().display());
+        fs::write(&util, "fn twice(n: int) int { ret n * 2; }").expect("module should be written");
+        fs::write(&main, &program_with_import).expect("program should be written");
+
+        let runs = Cell::new(0);
+        let mut watcher = Watcher::new(&main, || {
+            runs.set(runs.get() + 1);
+            fs::read_to_string(&main).is_ok_and(|source| crate::run_source(&source).is_ok())
+        });
+        watcher.debounce = Duration::from_millis(10);
+        let watched = |watcher: &Watcher<_>| -> Vec<PathBuf> { watcher.watch_set().files().map(PathBuf::from).collect() };
+        let canonical = |path: &PathBuf| fs::canonicalize(path).expect("file should exist");
+
+        let first = watcher.rerun(Vec::new());
+        assert!(first.ok);
+        assert_eq!(watched(&watcher), vec![canonical(&main), canonical(&util)]);
+        assert!(watcher.poll().is_none(), "nothing changed yet");
+        assert_eq!(runs.get(), 1);
+
+        // Правка модуля перезапускает прогон; ошибка в нём не убирает его из набора
+        fs::write(&util, "fn twice(n: int) int { ret n * 2 }").expect("module should be written");
+        let broken = watcher.poll().expect("module change should rerun");
+        assert!(!broken.ok);
+        assert_eq!(broken.changed, vec![canonical(&util)]);
+        assert_eq!(watched(&watcher), vec![canonical(&main), canonical(&util)]);
+
+        fs::write(&util, "fn twice(n: int) int { ret n + n; }").expect("module should be written");
+        assert!(watcher.poll().expect("fix should rerun").ok);
+
+        // Импорт убран: модуль больше не отслеживается
+        fs::write(&main, "chif main() { let x: int = 1; }").expect("program should be written");
+        let rerun = watcher.poll().expect("program change should rerun");
+        assert_eq!((rerun.ok, rerun.watched), (true, 1));
+        assert_eq!(watched(&watcher), vec![canonical(&main)]);
+        fs::write(&util, "fn twice(n: int) int { ret 0; }").expect("module should be written");
+        assert!(watcher.poll().is_none(), "a module that is no longer imported is not watched");
+        assert_eq!(runs.get(), 4);
+    }
+
+    #[test]
+    fn test_watch_status_line() {
+        let run = WatchRun {
+            ok: false,
+            duration: Duration::from_millis(42),
+            changed: vec![PathBuf::from("/project/util.rono"), PathBuf::from("/project/main.rono")],
+            watched: 2,
+        };
+        let at = UNIX_EPOCH + Duration::from_secs(3 * 86_400 + 13 * 3600 + 5 * 60 + 9);
+        assert_eq!(run.status_line(at), "[13:05:09] failed in 42 ms, watching 2 files (changed: util.rono and 1 more)");
+
+        let first = WatchRun { ok: true, duration: Duration::from_millis(3), changed: Vec::new(), watched: 1 };
+        assert_eq!(first.status_line(UNIX_EPOCH), "[00:00:00] ok in 3 ms, watching 1 file");
+    }
+}
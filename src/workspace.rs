@@ -0,0 +1,157 @@
+use crate::ast::*;
+use crate::error::SourceLocation;
+use crate::semantic::{ModuleInfo, SemanticAnalyzer, SemanticError, Symbol, SymbolTable, SymbolType, Warning};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Результат анализа одного файла рабочей области. Анализатор останавливается
+/// на первой ошибке, поэтому она одна
+#[derive(Debug, Default)]
+pub struct FileDiagnostics {
+    pub error: Option<SemanticError>,
+    pub warnings: Vec<Warning>,
+}
+
+/// Все файлы проекта, проанализированные вместе (для LSP и подобных сценариев).
+/// Сигнатуры каждого файла разбираются один раз и служат модулем для файлов,
+/// которые его импортируют; после правки одного файла `reanalyze_file`
+/// перепроверяет только его и файлы, импортирующие его напрямую
+#[derive(Debug)]
+pub struct WorkspaceAnalysis {
+    /// Файлы в порядке, в котором их передали
+    order: Vec<PathBuf>,
+    programs: HashMap<PathBuf, Program>,
+    /// Сигнатуры файлов — то же, что анализатор получает при импорте модуля
+    modules: HashMap<PathBuf, ModuleInfo>,
+    /// Глобальные символы всех файлов с префиксом модуля (`geometry_area`);
+    /// `location.file` — путь файла, который их определяет
+    pub symbols: SymbolTable,
+    /// Файл → файлы рабочей области, которые он импортирует
+    imports: HashMap<PathBuf, Vec<PathBuf>>,
+    diagnostics: HashMap<PathBuf, FileDiagnostics>,
+    analysis_counts: HashMap<PathBuf, usize>,
+}
+
+impl SemanticAnalyzer {
+    /// Анализирует файлы проекта с общей таблицей глобальных символов.
+    /// Импорт файла рабочей области берёт его программу отсюда, а не с диска
+    pub fn analyze_workspace(files: &[(PathBuf, Program)]) -> WorkspaceAnalysis {
+        let mut workspace = WorkspaceAnalysis {
+            order: Vec::new(),
+            programs: HashMap::new(),
+            modules: HashMap::new(),
+            symbols: SymbolTable::new(),
+            imports: HashMap::new(),
+            diagnostics: HashMap::new(),
+            analysis_counts: HashMap::new(),
+        };
+
+        for (path, program) in files {
+            let key = module_key(path);
+            workspace.order.push(key.clone());
+            workspace.programs.insert(key, program.clone());
+        }
+        for key in workspace.order.clone() {
+            workspace.update_file(&key);
+        }
+        for key in workspace.order.clone() {
+            workspace.check_file(&key);
+        }
+        workspace
+    }
+}
+
+impl WorkspaceAnalysis {
+    /// Заменяет программу файла и перепроверяет его и его прямых зависимых.
+    /// Возвращает перепроверенные файлы
+    pub fn reanalyze_file(&mut self, path: &Path, new_program: Program) -> Vec<PathBuf> {
+        let key = module_key(path);
+        if !self.order.contains(&key) {
+            self.order.push(key.clone());
+        }
+        self.programs.insert(key.clone(), new_program);
+        self.update_file(&key);
+
+        let mut rechecked = vec![key.clone()];
+        rechecked.extend(self.dependents(&key).into_iter().filter(|dependent| *dependent != key));
+        for file in &rechecked {
+            self.check_file(file);
+        }
+        rechecked
+    }
+
+    pub fn diagnostics(&self, path: &Path) -> Option<&FileDiagnostics> {
+        self.diagnostics.get(&module_key(path))
+    }
+
+    /// Файлы рабочей области, которые импортируют `path`
+    pub fn dependents(&self, path: &Path) -> Vec<PathBuf> {
+        let key = module_key(path);
+        self.order.iter()
+            .filter(|file| self.imports.get(*file).is_some_and(|imports| imports.contains(&key)))
+            .cloned()
+            .collect()
+    }
+
+    /// Сколько раз файл был проанализирован с момента `analyze_workspace`
+    pub fn analysis_count(&self, path: &Path) -> usize {
+        self.analysis_counts.get(&module_key(path)).copied().unwrap_or(0)
+    }
+
+    /// Пересобирает сигнатуры, глобальные символы и импорты одного файла
+    fn update_file(&mut self, key: &Path) {
+        let program = &self.programs[key];
+        let module = SemanticAnalyzer::module_info(program, key.to_path_buf());
+        let imports = program.items.iter()
+            .filter_map(|item| match item {
+                Item::Import(import) => crate::import_candidate(import, None).ok().map(|path| module_key(&path)),
+                _ => None,
+            })
+            .filter(|imported| self.programs.contains_key(imported))
+            .collect();
+
+        let file = key.display().to_string();
+        self.symbols.remove_file_symbols(&file);
+        let location = SourceLocation::new(file, 0, 0);
+        // Как при импорте: только то, что модуль экспортирует, и методы его структур
+        let exported_method = |name: &str| module.structs.keys().any(|struct_name| {
+            module.is_exported(struct_name)
+                && name.strip_prefix(struct_name.as_str()).is_some_and(|method| method.starts_with('_'))
+        });
+        let functions = module.functions.iter()
+            .filter(|(name, _)| module.is_exported(name))
+            .chain(module.methods.iter().filter(|(name, _)| exported_method(name)))
+            .map(|(name, signature)| (name, SymbolType::Function(signature.clone())));
+        let structs = module.structs.iter()
+            .filter(|(name, _)| module.is_exported(name))
+            .map(|(name, definition)| (name, SymbolType::Struct(definition.clone())));
+        // Одинаковые имена модулей из разных каталогов: первый файл сохраняет символ
+        for (name, symbol_type) in functions.chain(structs) {
+            let _ = self.symbols.define_symbol(Symbol {
+                name: format!("{}_{}", module.name, name),
+                symbol_type,
+                location: location.clone(),
+                is_mutable: false,
+            });
+        }
+
+        self.imports.insert(key.to_path_buf(), imports);
+        self.modules.insert(key.to_path_buf(), module);
+    }
+
+    fn check_file(&mut self, key: &Path) {
+        let mut analyzer = SemanticAnalyzer::new();
+        analyzer.module_cache = self.modules.clone();
+        let error = analyzer.check_program(&self.programs[key]).err();
+
+        self.diagnostics.insert(key.to_path_buf(), FileDiagnostics { error, warnings: analyzer.warnings });
+        *self.analysis_counts.entry(key.to_path_buf()).or_insert(0) += 1;
+    }
+}
+
+/// Ключ файла — канонический путь, как в кэше модулей анализатора; файл,
+/// которого нет на диске, остаётся под своим путём
+fn module_key(path: &Path) -> PathBuf {
+    fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf())
+}
@@ -0,0 +1,80 @@
+#[cfg(test)]
+mod tests {
+    use crate::parse_source;
+    use crate::semantic::{SemanticAnalyzer, SemanticError};
+    use crate::workspace::WorkspaceAnalysis;
+    use std::path::{Path, PathBuf};
+
+    // Файлов нет на диске: рабочая область отдаёт импортам свои программы
+    const GEOMETRY: &str = r#"
+        struct Rect { w: int, h: int, }
+        fn area(w: int, h: int) int {
+            ret w * h;
+        }
+    "#;
+
+    const APP: &str = r#"
+        import "ws/geometry";
+        chif main() {
+            let a: int = geometry.area(2, 3);
+        }
+    "#;
+
+    const TOOLS: &str = r#"
+        fn twice(x: int) int {
+            ret x * 2;
+        }
+    "#;
+
+    fn workspace() -> WorkspaceAnalysis {
+        let files: Vec<(PathBuf, _)> = [("ws/geometry.rono", GEOMETRY), ("ws/app.rono", APP), ("ws/tools.rono", TOOLS)]
+            .into_iter()
+            .map(|(path, source)| (PathBuf::from(path), parse_source(source).expect("file should parse")))
+            .collect();
+        SemanticAnalyzer::analyze_workspace(&files)
+    }
+
+    #[test]
+    fn test_workspace_shares_module_symbols() {
+        let workspace = workspace();
+        for file in ["ws/geometry.rono", "ws/app.rono", "ws/tools.rono"] {
+            let diagnostics = workspace.diagnostics(Path::new(file)).expect("file should be analyzed");
+            assert!(diagnostics.error.is_none(), "{}: {:?}", file, diagnostics.error);
+            assert_eq!(workspace.analysis_count(Path::new(file)), 1, "{}", file);
+        }
+
+        for name in ["geometry_area", "geometry_Rect", "tools_twice"] {
+            assert!(workspace.symbols.lookup_symbol(name).is_some(), "{}", name);
+        }
+        let area = workspace.symbols.lookup_symbol("geometry_area").expect("area should be defined");
+        assert_eq!(area.location.file, "ws/geometry.rono");
+        assert_eq!(workspace.dependents(Path::new("ws/geometry.rono")), [PathBuf::from("ws/app.rono")]);
+        assert!(workspace.dependents(Path::new("ws/tools.rono")).is_empty());
+    }
+
+    #[test]
+    fn test_reanalyze_file_rechecks_only_direct_dependents() {
+        let mut workspace = workspace();
+        let changed = parse_source("fn area(side: int) int { ret side * side; }").expect("file should parse");
+        let rechecked = workspace.reanalyze_file(Path::new("ws/geometry.rono"), changed);
+        assert_eq!(rechecked, [PathBuf::from("ws/geometry.rono"), PathBuf::from("ws/app.rono")]);
+
+        // Новая сигнатура видна в глобальной таблице, удалённая структура исчезла
+        assert!(workspace.symbols.lookup_symbol("geometry_Rect").is_none());
+        assert!(workspace.symbols.lookup_symbol("geometry_area").is_some());
+
+        match &workspace.diagnostics(Path::new("ws/app.rono")).expect("app should be analyzed").error {
+            Some(SemanticError::InvalidOperation { message, .. }) => assert!(message.contains("area"), "{}", message),
+            other => panic!("the call site should no longer match, got {:?}", other),
+        }
+        assert_eq!(workspace.analysis_count(Path::new("ws/geometry.rono")), 2);
+        assert_eq!(workspace.analysis_count(Path::new("ws/app.rono")), 2);
+        assert_eq!(workspace.analysis_count(Path::new("ws/tools.rono")), 1);
+
+        // Исправленный вызов снова проходит; geometry не перепроверяется
+        let fixed = parse_source("import \"ws/geometry\";\nchif main() { let a: int = geometry.area(4); }").expect("file should parse");
+        workspace.reanalyze_file(Path::new("ws/app.rono"), fixed);
+        assert!(workspace.diagnostics(Path::new("ws/app.rono")).expect("app should be analyzed").error.is_none());
+        assert_eq!(workspace.analysis_count(Path::new("ws/geometry.rono")), 2);
+    }
+}
@@ -0,0 +1,858 @@
+use serde_json::Value;
+use std::io::{BufRead, BufReader};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output, Stdio};
+
+const HELLO: &str = r#"
+chif main() {
+    con.out("hello");
+}
+"#;
+
+const DIVIDE_BY_ZERO: &str = r#"
+chif main() {
+    var zero: int = 0;
+    con.out(1 / zero);
+}
+"#;
+
+const TYPE_ERROR: &str = r#"
+chif main() {
+    var x: int = "text";
+}
+"#;
+
+const NESTED_FAILURE: &str = r#"
+fn divide(a: int, b: int) int {
+    ret a / b;
+}
+
+fn half_of_nothing() int {
+    ret divide(1, 0);
+}
+
+chif main() {
+    con.out(half_of_nothing());
+}
+"#;
+
+const MISPLACED_TOKEN: &str = "chif main() {\n    var x: int = (1 + ;\n}\n";
+
+fn write_program(dir: &Path, name: &str, source: &str) -> PathBuf {
+    let path = dir.join(name);
+    std::fs::write(&path, source).expect("program should be written");
+    path
+}
+
+fn rono(dir: &Path, args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_rono"))
+        .args(args)
+        .current_dir(dir)
+        .output()
+        .expect("rono should run")
+}
+
+fn stdout(output: &Output) -> String {
+    String::from_utf8_lossy(&output.stdout).into_owned()
+}
+
+fn stderr(output: &Output) -> String {
+    String::from_utf8_lossy(&output.stderr).into_owned()
+}
+
+/// The last stdout line, which `--status-json` guarantees to be JSON
+fn status_line(output: &Output) -> Value {
+    let out = stdout(output);
+    let line = out.lines().last().expect("stdout should not be empty");
+    serde_json::from_str(line).unwrap_or_else(|e| panic!("'{}' is not JSON: {}", line, e))
+}
+
+/// The runtime built into `rono compile` builds and links with libcurl here
+fn runtime_links(dir: &Path) -> bool {
+    let runtime = concat!(env!("CARGO_MANIFEST_DIR"), "/src/runtime.c");
+    Command::new("cc")
+        .arg(runtime)
+        .args(["-shared", "-fPIC", "-lcurl", "-lm", "-o"])
+        .arg(dir.join("runtime_check.so"))
+        .stderr(Stdio::null())
+        .status()
+        .map(|status| status.success())
+        .unwrap_or(false)
+}
+
+#[test]
+fn test_run_prints_program_output_only() {
+    let dir = tempfile::tempdir().unwrap();
+    write_program(dir.path(), "hello.rono", HELLO);
+
+    let output = rono(dir.path(), &["run", "hello.rono"]);
+    assert_eq!(output.status.code(), Some(0));
+    assert_eq!(stdout(&output), "hello\n");
+    assert_eq!(stderr(&output), "");
+}
+
+const ENDLESS_OUTPUT: &str = r#"
+chif main() {
+    for (var i: int = 0; i < 1000000; i = i + 1) {
+        con.out(i);
+    }
+}
+"#;
+
+/// Запускает `command`, читает первую строку stdout и закрывает его, как `| head -1`
+fn run_with_closed_stdout(command: &mut Command) -> (Option<i32>, String, String) {
+    let mut child = command
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .expect("program should start");
+    let mut first_line = String::new();
+    BufReader::new(child.stdout.take().unwrap()).read_line(&mut first_line).expect("first line should be read");
+    let output = child.wait_with_output().expect("program should finish");
+    (output.status.code(), first_line, stderr(&output))
+}
+
+#[test]
+fn test_closed_stdout_ends_the_program_quietly() {
+    let dir = tempfile::tempdir().unwrap();
+    write_program(dir.path(), "endless.rono", ENDLESS_OUTPUT);
+
+    let (code, first_line, errors) = run_with_closed_stdout(
+        Command::new(env!("CARGO_BIN_EXE_rono")).args(["run", "endless.rono"]).current_dir(dir.path()),
+    );
+    assert_eq!(first_line, "0\n");
+    assert_eq!(code, Some(141), "{}", errors);
+    assert!(!errors.contains("panic"), "{}", errors);
+    assert_eq!(errors, "");
+
+    if !runtime_links(dir.path()) {
+        return;
+    }
+    let output = rono(dir.path(), &["compile", "endless.rono", "-o", "endless"]);
+    assert_eq!(output.status.code(), Some(0), "{}", stderr(&output));
+    let (code, first_line, errors) = run_with_closed_stdout(&mut Command::new(dir.path().join("build/endless")));
+    assert_eq!(first_line, "0\n");
+    assert_eq!(code, Some(141), "{}", errors);
+    assert_eq!(errors, "");
+}
+
+#[test]
+fn test_run_passes_arguments_after_double_dash() {
+    let dir = tempfile::tempdir().unwrap();
+    write_program(dir.path(), "serve.rono", r#"
+chif main() {
+    con.out(args.count());
+    con.out(args.get(0));
+    con.out(args.value_of("port"));
+}
+"#);
+
+    // `-v` after `--` belongs to the program, not to rono
+    let output = rono(dir.path(), &["run", "serve.rono", "--", "serve", "--port", "80", "-v"]);
+    assert_eq!(output.status.code(), Some(0), "{}", stderr(&output));
+    assert_eq!(stdout(&output), "4\nserve\n80\n");
+    assert_eq!(stderr(&output), "");
+}
+
+#[test]
+fn test_run_verbose_reports_timings() {
+    let dir = tempfile::tempdir().unwrap();
+    write_program(dir.path(), "hello.rono", HELLO);
+
+    let output = rono(dir.path(), &["run", "-v", "hello.rono"]);
+    assert_eq!(output.status.code(), Some(0));
+    let out = stdout(&output);
+    assert!(out.contains("hello\n") && out.contains("Finished in"), "{}", out);
+    // Stage timings are progress output, which goes to stderr
+    assert!(stderr(&output).contains("Parsed in"), "{}", stderr(&output));
+}
+
+#[test]
+fn test_run_status_json_is_the_last_stdout_line() {
+    let dir = tempfile::tempdir().unwrap();
+    write_program(dir.path(), "hello.rono", HELLO);
+
+    // Verbose messages move to stderr so stdout stays machine-readable
+    let output = rono(dir.path(), &["--status-json", "run", "-v", "hello.rono"]);
+    assert_eq!(output.status.code(), Some(0));
+    let out = stdout(&output);
+    assert_eq!(out.lines().count(), 2, "{}", out);
+    assert!(out.starts_with("hello\n"));
+    assert!(stderr(&output).contains("Finished in"));
+
+    let status = status_line(&output);
+    assert_eq!(status["status"], "ok");
+    assert_eq!(status["warnings"], 0);
+    assert!(status["duration_ms"].is_u64());
+}
+
+#[test]
+fn test_run_failure_reports_errors() {
+    let dir = tempfile::tempdir().unwrap();
+    write_program(dir.path(), "divide.rono", DIVIDE_BY_ZERO);
+
+    let output = rono(dir.path(), &["run", "--status-json", "divide.rono"]);
+    assert_eq!(output.status.code(), Some(1));
+    assert!(stderr(&output).contains("Division by zero"));
+
+    let status = status_line(&output);
+    assert_eq!(status["status"], "error");
+    assert_eq!(status["errors"][0]["stage"], "runtime");
+    assert!(status["errors"][0]["message"].as_str().unwrap().contains("Division by zero"));
+}
+
+#[test]
+fn test_run_error_formats() {
+    let dir = tempfile::tempdir().unwrap();
+    write_program(dir.path(), "nested.rono", NESTED_FAILURE);
+    write_program(dir.path(), "misplaced.rono", MISPLACED_TOKEN);
+    let run = |format: &str, file: &str| {
+        let output = rono(dir.path(), &["run", "--error-format", format, file]);
+        assert_eq!(output.status.code(), Some(1), "{}", stderr(&output));
+        stderr(&output)
+    };
+
+    let pretty = run("pretty", "nested.rono");
+    assert!(pretty.starts_with("error[runtime]: Division by zero\n  --> nested.rono\n"), "{}", pretty);
+    assert!(pretty.contains("trace:\n  0: divide\n  1: half_of_nothing\n  2: main"), "{}", pretty);
+    let pretty = run("pretty", "misplaced.rono");
+    assert!(pretty.contains("--> misplaced.rono:2:23\n  |\n2 |     var x: int = (1 + ;\n  |"), "{}", pretty);
+
+    // Plain is one line per error, and the default when stderr is not a terminal
+    let plain = run("plain", "nested.rono");
+    assert_eq!(plain, "nested.rono: runtime error: Division by zero (trace: divide <- half_of_nothing <- main)\n");
+    assert_eq!(rono(dir.path(), &["run", "nested.rono"]).stderr, plain.as_bytes());
+    assert_eq!(run("plain", "misplaced.rono"), "misplaced.rono:2:23: parse error: Expected expression, found ';'\n");
+
+    let json: Value = serde_json::from_str(&run("json", "nested.rono")).expect("error should be JSON");
+    assert_eq!(json["kind"], "runtime");
+    assert_eq!(json["message"], "Division by zero");
+    assert_eq!(json["trace"], serde_json::json!(["divide", "half_of_nothing", "main"]));
+    assert!(json["location"].is_null());
+    let json: Value = serde_json::from_str(&run("json", "misplaced.rono")).expect("error should be JSON");
+    assert_eq!(json["kind"], "parse");
+    assert_eq!(json["location"], serde_json::json!({"file": "misplaced.rono", "line": 2, "column": 23}));
+    assert_eq!(json["trace"], serde_json::json!([]));
+}
+
+#[test]
+fn test_check_analyzes_without_running() {
+    let dir = tempfile::tempdir().unwrap();
+    write_program(dir.path(), "divide.rono", DIVIDE_BY_ZERO);
+    write_program(dir.path(), "type_error.rono", TYPE_ERROR);
+
+    // Деление на ноль обнаруживается только при выполнении
+    let output = rono(dir.path(), &["check", "divide.rono"]);
+    assert_eq!(output.status.code(), Some(0), "{}", stderr(&output));
+    assert_eq!(stdout(&output), "No errors found in divide.rono\n");
+
+    let output = rono(dir.path(), &["check", "--status-json", "type_error.rono"]);
+    assert_eq!(output.status.code(), Some(1));
+    assert!(stderr(&output).contains("Type mismatch"), "{}", stderr(&output));
+    assert_eq!(status_line(&output)["errors"][0]["stage"], "semantic");
+
+    // --clear имеет смысл только вместе с --watch
+    let output = rono(dir.path(), &["check", "--clear", "divide.rono"]);
+    assert_eq!(output.status.code(), Some(2));
+}
+
+#[test]
+fn test_no_prelude_hides_prelude_functions() {
+    let dir = tempfile::tempdir().unwrap();
+    write_program(dir.path(), "pad.rono", "chif main() {\n    con.out(pad_left(\"7\", 3, \"0\"));\n}\n");
+
+    let output = rono(dir.path(), &["run", "pad.rono"]);
+    assert_eq!(output.status.code(), Some(0), "{}", stderr(&output));
+    assert_eq!(stdout(&output), "007\n");
+
+    let output = rono(dir.path(), &["check", "--no-prelude", "pad.rono"]);
+    assert_eq!(output.status.code(), Some(1));
+    assert!(stderr(&output).contains("pad_left"), "{}", stderr(&output));
+}
+
+#[test]
+fn test_check_dump_symbols_lists_symbols_with_origins() {
+    let dir = tempfile::tempdir().unwrap();
+    std::fs::create_dir(dir.path().join("lib")).unwrap();
+    write_program(dir.path(), "lib/utils.rono", "fn helper(x: int) int {\n    ret x * 2;\n}\n");
+    write_program(dir.path(), "app.rono", r#"
+import "lib/utils" as u;
+struct Point { x: int, y: int, }
+fn_for Point {
+    fn sum(self) int {
+        ret self.x + self.y;
+    }
+}
+chif main() {
+    con.out(u.helper(1));
+}
+"#);
+    let utils = std::fs::canonicalize(dir.path().join("lib/utils.rono")).unwrap();
+
+    let output = rono(dir.path(), &["check", "--dump-symbols", "app.rono"]);
+    assert_eq!(output.status.code(), Some(0), "{}", stderr(&output));
+    let text = stdout(&output);
+    let lines: Vec<&str> = text.lines().collect();
+    for expected in [
+        format!("function u.helper(x: int) int (imported from {}) [u_helper]", utils.display()),
+        "function Point.sum(self) int (this file) [Point_sum]".to_string(),
+        "struct Point { x: int, y: int } (this file)".to_string(),
+        "function randi(min: int, max: int) int (builtin)".to_string(),
+        format!("module u {} (this file)", utils.display()),
+    ] {
+        assert!(lines.contains(&expected.as_str()), "missing {:?} in\n{}", expected, text);
+    }
+    // Символы идут по имени; последняя строка — итог check
+    assert_eq!(lines.last(), Some(&"No errors found in app.rono"));
+    let names: Vec<&str> = lines[..lines.len() - 1].iter()
+        .map(|line| line.split([' ', '(']).nth(1).unwrap())
+        .collect();
+    assert!(names.windows(2).all(|pair| pair[0] <= pair[1]), "{:?}", names);
+
+    let output = rono(dir.path(), &["check", "--dump-symbols=json", "app.rono"]);
+    assert_eq!(output.status.code(), Some(0), "{}", stderr(&output));
+    let symbols: Value = serde_json::from_str(stdout(&output).lines().next().unwrap()).unwrap();
+    let helper = symbols.as_array().unwrap().iter().find(|symbol| symbol["name"] == "u.helper").unwrap();
+    assert_eq!(helper["symbol"], "u_helper");
+    assert_eq!(helper["kind"], "function");
+    assert_eq!(helper["origin"], "module");
+    assert_eq!(helper["file"], utils.display().to_string());
+}
+
+#[test]
+fn test_explain_prints_the_explanation_of_a_code() {
+    let dir = tempfile::tempdir().unwrap();
+    let output = rono(dir.path(), &["--explain", "E0002"]);
+    assert_eq!(output.status.code(), Some(0), "{}", stderr(&output));
+    assert!(stdout(&output).starts_with("E0002: "), "{}", stdout(&output));
+    assert!(stdout(&output).contains("var count: int = \"три\";"), "{}", stdout(&output));
+
+    let output = rono(dir.path(), &["--explain", "E9999"]);
+    assert_eq!(output.status.code(), Some(1));
+    assert!(stderr(&output).contains("E0001"), "{}", stderr(&output));
+}
+
+#[test]
+fn test_error_codes_and_warning_policy() {
+    let dir = tempfile::tempdir().unwrap();
+    write_program(dir.path(), "type_error.rono", TYPE_ERROR);
+    write_program(dir.path(), "clamp.rono", "fn clamp(value: int) int {\n    ret value;\n}\n\nchif main() {\n    con.out(clamp(3));\n}\n");
+
+    let output = rono(dir.path(), &["check", "--status-json", "--error-format", "plain", "type_error.rono"]);
+    assert!(stderr(&output).contains("semantic error[E0002]: "), "{}", stderr(&output));
+    assert_eq!(status_line(&output)["errors"][0]["code"], "E0002");
+
+    let output = rono(dir.path(), &["check", "clamp.rono"]);
+    assert_eq!(output.status.code(), Some(0), "{}", stderr(&output));
+    assert!(stderr(&output).contains("Code: W0003"), "{}", stderr(&output));
+
+    let output = rono(dir.path(), &["check", "--allow", "W0003", "clamp.rono"]);
+    assert_eq!(output.status.code(), Some(0), "{}", stderr(&output));
+    assert!(!stderr(&output).contains("W0003"), "{}", stderr(&output));
+
+    let output = rono(dir.path(), &["check", "--status-json", "--deny", "W0003", "clamp.rono"]);
+    assert_eq!(output.status.code(), Some(1));
+    assert!(stderr(&output).contains("error: clamp.rono"), "{}", stderr(&output));
+    assert_eq!(status_line(&output)["errors"][0]["code"], "W0003");
+
+    // Ошибки нельзя разрешить
+    let output = rono(dir.path(), &["check", "--allow", "E0002", "type_error.rono"]);
+    assert_eq!(output.status.code(), Some(2));
+}
+
+const APP_MODULE: &str = r#"
+fn greeting() int {
+    ret 7;
+}
+
+chif main() {
+    con.out("main");
+    ret greeting();
+}
+
+fn start() {
+    con.out("start");
+}
+"#;
+
+#[test]
+fn test_entry_point_in_an_imported_module() {
+    let dir = tempfile::tempdir().unwrap();
+    write_program(dir.path(), "app.rono", APP_MODULE);
+    write_program(dir.path(), "thin.rono", "import \"app\" use (main);\n");
+    write_program(dir.path(), "plain.rono", "import \"app\";\n");
+
+    let output = rono(dir.path(), &["run", "thin.rono"]);
+    assert_eq!(output.status.code(), Some(0), "{}", stderr(&output));
+    assert_eq!(stdout(&output), "main\n");
+    let output = rono(dir.path(), &["run", "--entry", "app.start", "plain.rono"]);
+    assert_eq!(stdout(&output), "start\n", "{}", stderr(&output));
+
+    let output = rono(dir.path(), &["run", "plain.rono"]);
+    assert_eq!(output.status.code(), Some(1));
+    assert!(stderr(&output).contains("No main function found"), "{}", stderr(&output));
+    let output = rono(dir.path(), &["check", "--entry", "app.start", "thin.rono"]);
+    assert_eq!(output.status.code(), Some(1));
+    assert!(stderr(&output).contains("Duplicate entry point"), "{}", stderr(&output));
+    let output = rono(dir.path(), &["check", "--entry", "app.", "plain.rono"]);
+    assert_eq!(output.status.code(), Some(2));
+
+    if !runtime_links(dir.path()) {
+        return;
+    }
+    // Код выхода исполняемого файла — результат функции входа
+    for (args, expected, code) in [(&["thin.rono"][..], "main\n", 7), (&["--entry", "app.start", "plain.rono"][..], "start\n", 0)] {
+        let output = rono(dir.path(), &[&["compile", "-o", "entry"][..], args].concat());
+        assert_eq!(output.status.code(), Some(0), "{}", stderr(&output));
+        let run = Command::new(dir.path().join("build/entry")).output().expect("program should run");
+        assert_eq!((stdout(&run).as_str(), run.status.code()), (expected, Some(code)));
+    }
+}
+
+#[test]
+fn test_imports_resolve_from_the_program_file() {
+    let dir = tempfile::tempdir().unwrap();
+    let app = dir.path().join("app");
+    std::fs::create_dir(&app).unwrap();
+    write_program(&app, "utils.rono", "fn twice(x: int) int { ret x * 2; }\n");
+    write_program(&app, "main.rono", "import \"./utils\";\nchif main() { con.out(utils.twice(21)); }\n");
+    write_program(&app, "legacy.rono", "import \"utils\";\nchif main() { con.out(utils.twice(2)); }\n");
+
+    // Run from the parent directory: `./utils` is next to the program, not in the working directory
+    let output = rono(dir.path(), &["run", "app/main.rono"]);
+    assert_eq!(stdout(&output), "42\n", "{}", stderr(&output));
+    let output = rono(dir.path(), &["run", "app/legacy.rono"]);
+    assert_eq!(stdout(&output), "4\n", "{}", stderr(&output));
+
+    let output = rono(dir.path(), &["check", "app/legacy.rono"]);
+    assert_eq!(output.status.code(), Some(0), "{}", stderr(&output));
+    assert!(stderr(&output).contains("W0006"), "{}", stderr(&output));
+    let output = rono(dir.path(), &["check", "app/main.rono"]);
+    assert!(!stderr(&output).contains("W0006"), "{}", stderr(&output));
+}
+
+const GEOMETRY_MODULE: &str = r#"
+struct Rect {
+    w: int,
+    h: int,
+}
+
+fn_for Rect {
+    fn area(self) int {
+        ret self.w * self.h;
+    }
+
+    fn scaled(self, k: int) Rect {
+        ret Rect { w = self.w * k, h = self.h * k };
+    }
+
+    fn is_larger(self, other: Rect) bool {
+        ret self.area() > other.area();
+    }
+}
+
+fn make_rect(w: int, h: int) Rect {
+    ret Rect { w = w, h = h };
+}
+"#;
+
+const SHAPES_MODULE: &str = r#"
+import "geometry";
+
+fn unit() Rect {
+    ret geometry.make_rect(1, 1);
+}
+"#;
+
+const SHAPES_MAIN: &str = r#"
+import "geometry";
+import "shapes";
+
+chif main() {
+    var r: Rect = geometry.make_rect(2, 3);
+    con.out(r.area());
+    var big: Rect = r.scaled(2);
+    con.out(big.area());
+    con.out(geometry.make_rect(4, 5).area());
+    con.out(shapes.unit().scaled(7).area());
+    con.out(big.is_larger(r));
+}
+"#;
+
+#[test]
+fn test_methods_of_imported_structs_in_both_backends() {
+    let dir = tempfile::tempdir().unwrap();
+    write_program(dir.path(), "geometry.rono", GEOMETRY_MODULE);
+    write_program(dir.path(), "shapes.rono", SHAPES_MODULE);
+    write_program(dir.path(), "main.rono", SHAPES_MAIN);
+
+    let output = rono(dir.path(), &["run", "main.rono"]);
+    assert_eq!(output.status.code(), Some(0), "{}", stderr(&output));
+    assert_eq!(stdout(&output), "6\n24\n20\n49\ntrue\n");
+
+    if !runtime_links(dir.path()) {
+        return;
+    }
+    // Методы Rect генерируются один раз, хотя geometry импортируют и main, и shapes
+    let output = rono(dir.path(), &["compile", "main.rono", "-o", "shapes"]);
+    assert_eq!(output.status.code(), Some(0), "{}", stderr(&output));
+    let run = Command::new(dir.path().join("build/shapes")).output().expect("program should run");
+    assert_eq!(stdout(&run), "6\n24\n20\n49\ntrue\n");
+}
+
+#[test]
+fn test_color_policy() {
+    let dir = tempfile::tempdir().unwrap();
+    write_program(dir.path(), "misplaced.rono", MISPLACED_TOKEN);
+    let run = |args: &[&str], no_color: Option<&str>| {
+        let mut command = Command::new(env!("CARGO_BIN_EXE_rono"));
+        command.args(args).current_dir(dir.path()).env_remove("NO_COLOR");
+        if let Some(value) = no_color {
+            command.env("NO_COLOR", value);
+        }
+        command.output().expect("rono should run")
+    };
+    let colored = |output: &Output| stderr(output).contains('\x1b') || stdout(output).contains('\x1b');
+
+    let always = run(&["--color", "always", "run", "--error-format", "pretty", "misplaced.rono"], None);
+    assert!(stderr(&always).starts_with("\x1b[1;31merror[parse]\x1b[0m: "), "{:?}", stderr(&always));
+    assert!(stderr(&always).contains("\x1b[1;31m^\x1b[0m"), "{:?}", stderr(&always));
+    // Explicit `always` wins over NO_COLOR; the flag also works after the subcommand
+    assert!(colored(&run(&["run", "--color=always", "--error-format", "pretty", "misplaced.rono"], Some("1"))));
+
+    // Piped output is not a terminal, so `auto` (the default) stays plain
+    for args in [
+        &["--color", "never", "run", "--error-format", "pretty", "misplaced.rono"][..],
+        &["run", "--error-format", "pretty", "misplaced.rono"][..],
+    ] {
+        let output = run(args, None);
+        assert_eq!(output.status.code(), Some(1));
+        assert!(!colored(&output), "{:?}: {:?}", args, stderr(&output));
+    }
+
+    // Structured output never carries escape codes
+    let json = run(&["--color", "always", "run", "--error-format", "json", "--status-json", "misplaced.rono"], None);
+    assert!(!colored(&json), "{:?} {:?}", stdout(&json), stderr(&json));
+    assert_eq!(status_line(&json)["status"], "error");
+}
+
+#[test]
+fn test_quiet_keeps_only_errors() {
+    let dir = tempfile::tempdir().unwrap();
+    write_program(dir.path(), "broken.rono", "chif main( {");
+
+    let output = rono(dir.path(), &["-q", "run", "broken.rono"]);
+    assert_eq!(output.status.code(), Some(1));
+    assert_eq!(stdout(&output), "");
+    assert!(stderr(&output).contains("parse error"), "{}", stderr(&output));
+
+    let output = rono(dir.path(), &["run", "--status-json", "missing.rono"]);
+    assert_eq!(output.status.code(), Some(1));
+    assert_eq!(status_line(&output)["errors"][0]["stage"], "io");
+}
+
+#[test]
+fn test_quiet_and_verbose_conflict() {
+    let dir = tempfile::tempdir().unwrap();
+    let output = rono(dir.path(), &["run", "-q", "-v", "hello.rono"]);
+    assert_eq!(output.status.code(), Some(2));
+}
+
+#[test]
+fn test_compile_failure_status_json() {
+    let dir = tempfile::tempdir().unwrap();
+    write_program(dir.path(), "types.rono", TYPE_ERROR);
+
+    let output = rono(dir.path(), &["compile", "--status-json", "types.rono"]);
+    assert_eq!(output.status.code(), Some(1));
+    let status = status_line(&output);
+    assert_eq!(status["status"], "error");
+    assert_eq!(status["errors"].as_array().unwrap().len(), 1);
+    assert!(status["errors"][0]["message"].as_str().unwrap().contains("Type mismatch"), "{}", status);
+    assert_eq!(status["errors"][0]["code"], "E0002");
+}
+
+#[test]
+fn test_compile_levels_and_status_json() {
+    let dir = tempfile::tempdir().unwrap();
+    if !runtime_links(dir.path()) {
+        return;
+    }
+    write_program(dir.path(), "hello.rono", HELLO);
+
+    let output = rono(dir.path(), &["compile", "hello.rono", "-o", "app"]);
+    assert_eq!(output.status.code(), Some(0), "{}", stderr(&output));
+    assert_eq!(stdout(&output), "Compilation successful! Output: app\n");
+
+    let output = rono(dir.path(), &["-q", "compile", "hello.rono", "-o", "app"]);
+    assert_eq!(output.status.code(), Some(0));
+    assert_eq!(stdout(&output), "");
+    assert_eq!(stderr(&output), "");
+
+    let output = rono(dir.path(), &["-v", "compile", "hello.rono", "-o", "app"]);
+    let out = stdout(&output);
+    assert!(out.contains("Performing semantic analysis..."), "{}", out);
+    let err = stderr(&output);
+    assert!(err.contains("Linked in") && err.contains("Compiled 1 module ("), "{}", err);
+
+    let output = rono(dir.path(), &["compile", "--status-json", "hello.rono", "-o", "app"]);
+    assert_eq!(output.status.code(), Some(0));
+    assert_eq!(stdout(&output).lines().count(), 1);
+    let status = status_line(&output);
+    assert_eq!(status["status"], "ok");
+    assert_eq!(status["output"], "app");
+}
+
+#[test]
+fn test_compile_needs_nothing_but_the_program_in_the_working_directory() {
+    let dir = tempfile::tempdir().unwrap();
+    if !runtime_links(dir.path()) {
+        return;
+    }
+    let work = dir.path().join("work");
+    std::fs::create_dir(&work).unwrap();
+    write_program(&work, "hello.rono", HELLO);
+
+    let output = rono(&work, &["compile", "hello.rono", "-o", "app"]);
+    assert_eq!(output.status.code(), Some(0), "{}", stderr(&output));
+    let run = Command::new(work.join("build/app")).output().expect("app should run");
+    assert_eq!(stdout(&run), "hello\n");
+
+    // --runtime replaces the built-in runtime with a prebuilt object
+    let output = rono(&work, &["compile", "hello.rono", "-o", "app", "--runtime", "missing.o"]);
+    assert_eq!(output.status.code(), Some(1));
+    assert!(stderr(&output).contains("Runtime 'missing.o' not found"), "{}", stderr(&output));
+
+    let custom = dir.path().join("custom_runtime.o");
+    let built = Command::new("cc")
+        .arg("-c")
+        .arg(concat!(env!("CARGO_MANIFEST_DIR"), "/src/runtime.c"))
+        .arg("-o")
+        .arg(&custom)
+        .status()
+        .expect("cc should run");
+    assert!(built.success());
+    let output = rono(&work, &["compile", "hello.rono", "-o", "custom", "--runtime", custom.to_str().unwrap()]);
+    assert_eq!(output.status.code(), Some(0), "{}", stderr(&output));
+    let run = Command::new(work.join("build/custom")).output().expect("custom should run");
+    assert_eq!(stdout(&run), "hello\n");
+}
+
+/// Файлы каталога build, по алфавиту
+#[cfg(unix)]
+fn build_files(dir: &Path) -> Vec<String> {
+    let mut names: Vec<String> = std::fs::read_dir(dir.join("build"))
+        .expect("build should exist")
+        .map(|entry| entry.unwrap().file_name().to_string_lossy().into_owned())
+        .collect();
+    names.sort();
+    names
+}
+
+#[cfg(unix)]
+#[test]
+fn test_failed_compile_keeps_the_previous_output() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let dir = tempfile::tempdir().unwrap();
+    write_program(dir.path(), "hello.rono", HELLO);
+    // Заглушки вместо cc: первая оставляет недописанный файл и падает, вторая
+    // «линкует» скрипт; рантайм не нужен ни одной из них
+    let failing = write_program(dir.path(), "failing_linker.sh", "#!/bin/sh\nprintf partial > \"$2\"\necho 'undefined reference' >&2\nexit 1\n");
+    let working = write_program(dir.path(), "working_linker.sh", "#!/bin/sh\nprintf '#!/bin/sh\\necho new\\n' > \"$2\"\n");
+    for linker in [&failing, &working] {
+        std::fs::set_permissions(linker, std::fs::Permissions::from_mode(0o755)).unwrap();
+    }
+    write_program(dir.path(), "stub_runtime.o", "");
+    std::fs::create_dir_all(dir.path().join("build/nested")).unwrap();
+    write_program(dir.path(), "build/app", "old executable");
+    write_program(dir.path(), "build/app.o", "old object");
+    let compile = |output: &str, linker: &Path| {
+        rono(dir.path(), &["compile", "hello.rono", "-o", output, "--runtime", "stub_runtime.o", "--linker", linker.to_str().unwrap()])
+    };
+
+    let output = compile("app", &failing);
+    assert_eq!(output.status.code(), Some(1));
+    assert!(stderr(&output).contains("undefined reference"), "{}", stderr(&output));
+    assert_eq!(std::fs::read_to_string(dir.path().join("build/app")).unwrap(), "old executable");
+    assert_eq!(std::fs::read_to_string(dir.path().join("build/app.o")).unwrap(), "old object");
+    assert_eq!(build_files(dir.path()), ["app", "app.o", "nested"]);
+
+    // Каталог и файл только для чтения не заменяются, до генерации кода
+    let output = compile("nested", &working);
+    assert_eq!(output.status.code(), Some(1));
+    assert!(stderr(&output).contains("'build/nested' is a directory"), "{}", stderr(&output));
+    std::fs::set_permissions(dir.path().join("build/app"), std::fs::Permissions::from_mode(0o444)).unwrap();
+    let output = compile("app", &working);
+    assert_eq!(output.status.code(), Some(1));
+    assert!(stderr(&output).contains("'build/app' is not writable"), "{}", stderr(&output));
+    assert_eq!(build_files(dir.path()), ["app", "app.o", "nested"]);
+
+    std::fs::set_permissions(dir.path().join("build/app"), std::fs::Permissions::from_mode(0o644)).unwrap();
+    let output = compile("app", &working);
+    assert_eq!(output.status.code(), Some(0), "{}", stderr(&output));
+    assert_eq!(build_files(dir.path()), ["app", "app.o", "nested"]);
+    let mode = std::fs::metadata(dir.path().join("build/app")).unwrap().permissions().mode();
+    assert_eq!(mode & 0o777, 0o755);
+    let run = Command::new(dir.path().join("build/app")).output().expect("app should run");
+    assert_eq!(stdout(&run), "new\n");
+}
+
+const LIBRARY: &str = r#"
+fn add(a: int, b: int) int {
+    ret a + b;
+}
+
+fn concat(a: str, b: str) str {
+    ret a + b;
+}
+"#;
+
+const LIBRARY_CALLER: &str = r#"
+#include <stdio.h>
+#include "build/mathlib.h"
+
+int main(void) {
+    printf("%lld\n", (long long)add(40, 2));
+    printf("%s\n", concat("foo", "bar"));
+    return 0;
+}
+"#;
+
+#[test]
+fn test_compile_library_links_from_c() {
+    let dir = tempfile::tempdir().unwrap();
+    if !runtime_links(dir.path()) {
+        return;
+    }
+    write_program(dir.path(), "mathlib.rono", LIBRARY);
+    write_program(dir.path(), "main.c", LIBRARY_CALLER);
+
+    // Without --crate-type lib a program needs chif main
+    let output = rono(dir.path(), &["compile", "mathlib.rono"]);
+    assert_eq!(output.status.code(), Some(1));
+    assert!(stderr(&output).contains("--crate-type lib"), "{}", stderr(&output));
+
+    let output = rono(dir.path(), &["compile", "mathlib.rono", "--crate-type", "lib", "--emit-header"]);
+    assert_eq!(output.status.code(), Some(0), "{}", stderr(&output));
+
+    let linked = Command::new("cc")
+        .args(["main.c", "build/mathlib.o", "build/runtime.o", "-lm", "-lcurl", "-o", "caller"])
+        .current_dir(dir.path())
+        .output()
+        .expect("cc should run");
+    assert!(linked.status.success(), "{}", stderr(&linked));
+
+    let run = Command::new(dir.path().join("caller")).output().expect("caller should run");
+    assert_eq!(stdout(&run), "42\nfoobar\n");
+}
+
+#[test]
+fn test_compile_library_writes_runtime_next_to_the_object() {
+    let dir = tempfile::tempdir().unwrap();
+    write_program(dir.path(), "mathlib.rono", LIBRARY);
+    write_program(dir.path(), "stub_runtime.o", "");
+    let compile = |output: &str| {
+        rono(dir.path(), &["compile", "mathlib.rono", "--crate-type", "lib", "-o", output, "--runtime", "stub_runtime.o"])
+    };
+
+    // The directory of the output is not created implicitly
+    let output = compile("libs/mathlib");
+    assert_eq!(output.status.code(), Some(1));
+    assert!(stderr(&output).contains("Output directory 'build/libs' does not exist"), "{}", stderr(&output));
+    assert!(!dir.path().join("build/libs").exists());
+
+    std::fs::create_dir_all(dir.path().join("build/libs")).unwrap();
+    let output = compile("libs/mathlib");
+    assert_eq!(output.status.code(), Some(0), "{}", stderr(&output));
+    assert!(dir.path().join("build/libs/mathlib.o").is_file());
+    assert!(dir.path().join("build/libs/runtime.o").is_file());
+    assert!(!dir.path().join("build/runtime.o").exists());
+
+    // A library named like the runtime would be overwritten by it
+    let output = compile("runtime");
+    assert_eq!(output.status.code(), Some(1));
+    assert!(stderr(&output).contains("would overwrite the runtime object"), "{}", stderr(&output));
+}
+
+#[test]
+fn test_compile_library_rejects_non_c_signatures() {
+    let dir = tempfile::tempdir().unwrap();
+    write_program(dir.path(), "pairs.rono", "fn pair() (int, int) {\n    ret (1, 2);\n}\n");
+
+    let output = rono(dir.path(), &["compile", "pairs.rono", "--crate-type", "lib"]);
+    assert_eq!(output.status.code(), Some(1));
+    assert!(stderr(&output).contains("'pair'"), "{}", stderr(&output));
+}
+
+#[test]
+fn test_inspect_metrics_and_thresholds() {
+    let dir = tempfile::tempdir().unwrap();
+    write_program(dir.path(), "nested.rono", NESTED_FAILURE);
+
+    let output = rono(dir.path(), &["inspect", "--metrics", "--format", "json", "nested.rono"]);
+    assert_eq!(output.status.code(), Some(0), "{}", stderr(&output));
+    let report: Value = serde_json::from_str(&stdout(&output)).expect("report should be JSON");
+    let names: Vec<&str> = report["files"][0]["functions"].as_array().unwrap().iter()
+        .map(|function| function["name"].as_str().unwrap())
+        .collect();
+    assert_eq!(names, ["divide", "half_of_nothing", "main"]);
+    assert_eq!(report["files"][0]["functions"][0]["params"], 2);
+    assert_eq!(report["totals"]["functions"], 3);
+
+    // A function over the limit fails the run and is named on stderr
+    let output = rono(dir.path(), &["inspect", "--metrics", "--fail-over", "params=1", "nested.rono"]);
+    assert_eq!(output.status.code(), Some(1));
+    assert!(stdout(&output).contains("half_of_nothing"));
+    assert_eq!(
+        stderr(&output).trim(),
+        "nested.rono: function 'divide' has params 2, over the limit of 1"
+    );
+}
+
+#[test]
+fn test_test_command_runs_tests_and_fails_on_failures() {
+    let dir = tempfile::tempdir().unwrap();
+    write_program(dir.path(), "checks.rono", r#"
+fn test_adds() bool {
+    ret 1 + 1 == 2;
+}
+
+// timeout: 100ms
+fn test_spins() {
+    while (true) {
+    }
+}
+"#);
+
+    let output = rono(dir.path(), &["test", "checks.rono", "--jobs", "2"]);
+    assert_eq!(output.status.code(), Some(1), "{}", stderr(&output));
+    let text = stdout(&output);
+    assert!(text.contains("test test_adds ... ok"), "{}", text);
+    assert!(text.contains("test test_spins ... TIMEOUT: timed out after 100ms"), "{}", text);
+    assert!(text.contains("1 passed; 0 failed; 1 timed out"), "{}", text);
+
+    let output = rono(dir.path(), &["test", "checks.rono", "--timeout", "often"]);
+    assert_eq!(output.status.code(), Some(2));
+    assert!(stderr(&output).contains("invalid duration 'often'"), "{}", stderr(&output));
+}
+
+#[test]
+fn test_run_profile_prints_a_table_and_writes_json() {
+    let dir = tempfile::tempdir().unwrap();
+    let program = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/profile/hot.rono");
+    let output = rono(dir.path(), &["run", "--profile-out", "profile.json", program.to_str().unwrap()]);
+    assert_eq!(output.status.code(), Some(0), "{}", stderr(&output));
+    assert_eq!(stdout(&output), "159964\n");
+    let table = stderr(&output);
+    assert!(table.lines().next().unwrap().starts_with("function"), "{}", table);
+    assert!(table.lines().nth(1).unwrap().starts_with("hot "), "{}", table);
+
+    let json: Value = serde_json::from_str(&std::fs::read_to_string(dir.path().join("profile.json")).unwrap()).unwrap();
+    assert_eq!(json["functions"][0]["name"], "hot");
+    assert_eq!(json["functions"][0]["calls"], 40);
+
+    // Without the flag nothing is measured or printed
+    let output = rono(dir.path(), &["run", program.to_str().unwrap()]);
+    assert_eq!(stderr(&output), "");
+}
@@ -0,0 +1,70 @@
+//! Compiles a synthetic program of about 50 000 statements, half of them in an
+//! imported module, and prints the peak resident set size of the process.
+//!
+//! `cargo test --release --test compile_memory -- --ignored --nocapture`
+//!
+//! The number is only printed: it depends on the allocator and the platform,
+//! and is meant for comparing the compiler against itself.
+
+use rono_lang::{detect_host_target, parse_source, Compiler, OptLevel};
+use std::fmt::Write;
+use std::time::Instant;
+
+const STATEMENTS: usize = 50_000;
+const MODULE_FUNCTIONS: usize = 100;
+
+/// Declares a variable and updates it; every fifth one is printed
+fn statements(out: &mut String, count: usize, indent: &str) {
+    for n in 0..count {
+        writeln!(out, "{}var v{}: int = {};", indent, n, n % 97).unwrap();
+        writeln!(out, "{}v{} = v{} * 3 + 1;", indent, n, n).unwrap();
+        if n % 5 == 0 {
+            writeln!(out, "{}con.out(v{});", indent, n).unwrap();
+        }
+    }
+}
+
+/// Peak RSS in kilobytes from /proc, where it is available
+fn peak_rss_kb() -> Option<u64> {
+    let status = std::fs::read_to_string("/proc/self/status").ok()?;
+    let line = status.lines().find(|line| line.starts_with("VmHWM:"))?;
+    line.split_whitespace().nth(1)?.parse().ok()
+}
+
+#[test]
+#[ignore]
+fn compile_large_program_peak_rss() {
+    let dir = tempfile::tempdir().expect("temp dir should be created");
+
+    // statements() emits two statements per variable, plus a con.out for every fifth
+    let per_function = STATEMENTS / 2 / MODULE_FUNCTIONS / 2;
+    let mut module = String::new();
+    for f in 0..MODULE_FUNCTIONS {
+        writeln!(module, "fn step{}(x: int) int {{", f).unwrap();
+        statements(&mut module, per_function, "    ");
+        writeln!(module, "    ret x + v0;\n}}").unwrap();
+    }
+    std::fs::write(dir.path().join("big.rono"), module).expect("module should be written");
+
+    let mut source = format!("import \"{}/big\";\nchif main() {{\n", dir.path().display());
+    for f in 0..MODULE_FUNCTIONS {
+        writeln!(source, "    con.out(big_step{}({}));", f, f).unwrap();
+    }
+    statements(&mut source, STATEMENTS / 2 / 2, "    ");
+    source.push_str("}\n");
+
+    let program = parse_source(&source).expect("program should parse");
+    let before = peak_rss_kb();
+    let started = Instant::now();
+    let mut compiler = Compiler::new(detect_host_target(), OptLevel::None, false)
+        .expect("compiler should be created for the host target");
+    let artifact = compiler.compile_to_object(&program).expect("program should compile");
+
+    println!(
+        "compiled {} bytes of object code in {} ms; peak RSS before {:?} KB, after {:?} KB",
+        artifact.object.len(),
+        started.elapsed().as_millis(),
+        before,
+        peak_rss_kb()
+    );
+}
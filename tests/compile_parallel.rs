@@ -0,0 +1,67 @@
+//! Compiles a synthetic program of 500 functions with one job and with one job
+//! per CPU, and prints both times and the speedup.
+//!
+//! `cargo test --release --test compile_parallel -- --ignored --nocapture`
+//!
+//! Function bodies are generated to IR and compiled to machine code in
+//! parallel; parsing, analysis and declaring the functions take the same time
+//! with any number of jobs.
+
+use rono_lang::{detect_host_target, parse_source, Compiler, OptLevel};
+use std::fmt::Write;
+use std::time::{Duration, Instant};
+
+const FUNCTIONS: usize = 500;
+
+/// A loop, a branch and a few locals per function, so each has some code to optimize
+fn program() -> String {
+    let mut source = String::new();
+    for f in 0..FUNCTIONS {
+        writeln!(source, "fn work{}(x: int) int {{", f).unwrap();
+        writeln!(source, "    var total: int = {};", f).unwrap();
+        writeln!(source, "    for (var i: int = 0; i < x; i = i + 1) {{").unwrap();
+        writeln!(source, "        if (i % 3 == 0) {{ total = total + i * {}; }} else {{ total = total - i; }}", f % 7 + 1).unwrap();
+        writeln!(source, "    }}").unwrap();
+        for n in 0..10 {
+            writeln!(source, "    var v{}: int = total * {} + x;", n, n + 1).unwrap();
+            writeln!(source, "    total = total + v{} % 13;", n).unwrap();
+        }
+        writeln!(source, "    ret total;\n}}").unwrap();
+    }
+    source.push_str("chif main() {\n");
+    for f in 0..FUNCTIONS {
+        writeln!(source, "    con.out(work{}({}));", f, f % 10).unwrap();
+    }
+    source.push_str("}\n");
+    source
+}
+
+fn compile(source: &str, jobs: usize) -> (Vec<u8>, Duration) {
+    let program = parse_source(source).expect("program should parse");
+    let mut compiler = Compiler::new(detect_host_target(), OptLevel::Speed, false)
+        .expect("compiler should be created for the host target");
+    compiler.set_jobs(jobs);
+    let started = Instant::now();
+    let artifact = compiler.compile_to_object(&program).expect("program should compile");
+    (artifact.object, started.elapsed())
+}
+
+#[test]
+#[ignore]
+fn compile_500_functions_in_parallel() {
+    let source = program();
+    let jobs = std::thread::available_parallelism().map_or(1, |jobs| jobs.get());
+
+    let (sequential, one_job) = compile(&source, 1);
+    let (parallel, all_jobs) = compile(&source, jobs);
+    assert!(parallel == sequential, "the object should not depend on the number of jobs");
+
+    println!(
+        "{} functions: 1 job {} ms, {} jobs {} ms, speedup {:.2}x",
+        FUNCTIONS,
+        one_job.as_millis(),
+        jobs,
+        all_jobs.as_millis(),
+        one_job.as_secs_f64() / all_jobs.as_secs_f64()
+    );
+}
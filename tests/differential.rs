@@ -0,0 +1,449 @@
+//! Differential testing: generated programs run in the interpreter and as
+//! compiled executables, and their outputs must match.
+//!
+//! The generator sticks to constructs both backends support: int, float and bool
+//! variables, arithmetic, comparisons, `!`, if/else, bounded for and while loops,
+//! break/continue and `con.out` of scalars. A divergent program is shrunk by
+//! removing statements while the outputs still differ.
+//!
+//! `cargo test --test differential -- --ignored` runs the fuzzer;
+//! `RONO_FUZZ_SEED` and `RONO_FUZZ_CASES` pick the seed and the number of programs.
+//! Programs it found are kept in `tests/differential/` and checked on every run.
+
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use rono_lang::{analyze_source, detect_host_target, parse_source, Compiler, Interpreter, OptLevel};
+use std::ffi::OsStr;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::OnceLock;
+
+static NEXT_BINARY: AtomicUsize = AtomicUsize::new(0);
+
+/// A statement of a generated program. Blocks keep their bodies as statements,
+/// so the shrinker can remove statements at any depth
+#[derive(Debug, Clone)]
+enum Stmt {
+    Line(String),
+    If { condition: String, then_body: Vec<Stmt>, else_body: Vec<Stmt> },
+    /// `close` ends the body and is never removed: it holds the while counter update
+    Loop { open: String, body: Vec<Stmt>, close: String },
+}
+
+fn render(body: &[Stmt]) -> String {
+    fn render_block(body: &[Stmt], depth: usize, out: &mut String) {
+        let indent = "    ".repeat(depth);
+        let push_lines = |text: &str, out: &mut String| {
+            for line in text.lines() {
+                out.push_str(&format!("{}{}\n", indent, line));
+            }
+        };
+        for stmt in body {
+            match stmt {
+                Stmt::Line(line) => push_lines(line, out),
+                Stmt::If { condition, then_body, else_body } => {
+                    out.push_str(&format!("{}if ({}) {{\n", indent, condition));
+                    render_block(then_body, depth + 1, out);
+                    if else_body.is_empty() {
+                        out.push_str(&format!("{}}}\n", indent));
+                    } else {
+                        out.push_str(&format!("{}}} else {{\n", indent));
+                        render_block(else_body, depth + 1, out);
+                        out.push_str(&format!("{}}}\n", indent));
+                    }
+                }
+                Stmt::Loop { open, body, close } => {
+                    push_lines(open, out);
+                    render_block(body, depth + 1, out);
+                    push_lines(close, out);
+                }
+            }
+        }
+    }
+
+    let mut out = String::from("chif main() {\n");
+    render_block(body, 1, &mut out);
+    out.push_str("}\n");
+    out
+}
+
+/// Generates random well-typed programs from a small grammar
+struct Generator {
+    rng: StdRng,
+    ints: Vec<String>,
+    floats: Vec<String>,
+    bools: Vec<String>,
+    // Loop counters can be read but never assigned, so every loop terminates
+    counters: Vec<String>,
+    next_name: usize,
+    in_for: bool,
+    in_loop: bool,
+}
+
+impl Generator {
+    fn new(seed: u64) -> Self {
+        Self {
+            rng: StdRng::seed_from_u64(seed),
+            ints: Vec::new(),
+            floats: Vec::new(),
+            bools: Vec::new(),
+            counters: Vec::new(),
+            next_name: 0,
+            in_for: false,
+            in_loop: false,
+        }
+    }
+
+    fn program(&mut self) -> Vec<Stmt> {
+        self.ints.clear();
+        self.floats.clear();
+        self.bools.clear();
+        self.counters.clear();
+        self.next_name = 0;
+
+        let mut body = Vec::new();
+        for _ in 0..self.rng.gen_range(1..=3) {
+            let name = self.fresh("n");
+            body.push(Stmt::Line(format!("var {}: int = {};", name, self.rng.gen_range(-20..=20))));
+            self.ints.push(name);
+        }
+        for _ in 0..self.rng.gen_range(1..=2) {
+            let name = self.fresh("f");
+            let value = self.float_literal();
+            body.push(Stmt::Line(format!("var {}: float = {};", name, value)));
+            self.floats.push(name);
+        }
+        let name = self.fresh("b");
+        body.push(Stmt::Line(format!("var {}: bool = {};", name, self.rng.gen_bool(0.5))));
+        self.bools.push(name);
+
+        body.extend(self.statements(0));
+        for name in self.ints.clone().iter().chain(&self.floats.clone()).chain(&self.bools.clone()) {
+            body.push(Stmt::Line(format!("con.out({});", name)));
+        }
+        body
+    }
+
+    fn fresh(&mut self, prefix: &str) -> String {
+        self.next_name += 1;
+        format!("{}{}", prefix, self.next_name)
+    }
+
+    fn statements(&mut self, depth: usize) -> Vec<Stmt> {
+        let count = if depth == 0 { self.rng.gen_range(3..=8) } else { self.rng.gen_range(1..=3) };
+        (0..count).map(|_| self.statement(depth)).collect()
+    }
+
+    fn statement(&mut self, depth: usize) -> Stmt {
+        let choice = if depth >= 2 { self.rng.gen_range(0..6) } else { self.rng.gen_range(0..9) };
+        match choice {
+            0 | 1 => {
+                let target = self.ints[self.rng.gen_range(0..self.ints.len())].clone();
+                // The remainder keeps values small, so nothing overflows
+                Stmt::Line(format!("{} = {} % 1000;", target, self.int_expression(2)))
+            }
+            2 => {
+                let target = self.floats[self.rng.gen_range(0..self.floats.len())].clone();
+                Stmt::Line(format!("{} = {};", target, self.float_expression(2)))
+            }
+            3 => {
+                let target = self.bools[self.rng.gen_range(0..self.bools.len())].clone();
+                Stmt::Line(format!("{} = {};", target, self.bool_expression(1)))
+            }
+            4 => {
+                let value = match self.rng.gen_range(0..3) {
+                    0 => self.int_expression(2),
+                    1 => self.float_expression(1),
+                    _ => self.bool_expression(1),
+                };
+                Stmt::Line(format!("con.out({});", value))
+            }
+            5 if self.in_loop => {
+                let condition = self.bool_expression(1);
+                let jump = if self.in_for && self.rng.gen_bool(0.5) { "continue;" } else { "break;" };
+                Stmt::If { condition, then_body: vec![Stmt::Line(jump.to_string())], else_body: Vec::new() }
+            }
+            5 | 6 => {
+                let condition = self.bool_expression(2);
+                let then_body = self.statements(depth + 1);
+                let else_body = if self.rng.gen_bool(0.5) { self.statements(depth + 1) } else { Vec::new() };
+                Stmt::If { condition, then_body, else_body }
+            }
+            7 => self.for_loop(depth),
+            _ => self.while_loop(depth),
+        }
+    }
+
+    fn for_loop(&mut self, depth: usize) -> Stmt {
+        let counter = self.fresh("i");
+        let open = format!(
+            "for (var {c}: int = 0; {c} < {}; {c} = {c} + 1) {{",
+            self.rng.gen_range(0..=4),
+            c = counter
+        );
+        let (in_for, in_loop) = (self.in_for, self.in_loop);
+        (self.in_for, self.in_loop) = (true, true);
+        self.counters.push(counter);
+        let body = self.statements(depth + 1);
+        self.counters.pop();
+        (self.in_for, self.in_loop) = (in_for, in_loop);
+        Stmt::Loop { open, body, close: "}".to_string() }
+    }
+
+    fn while_loop(&mut self, depth: usize) -> Stmt {
+        let counter = self.fresh("w");
+        let open = format!("var {c}: int = 0;\nwhile ({c} < {}) {{", self.rng.gen_range(0..=4), c = counter);
+        // continue would skip the counter update at the end of the body
+        let (in_for, in_loop) = (self.in_for, self.in_loop);
+        (self.in_for, self.in_loop) = (false, true);
+        self.counters.push(counter.clone());
+        let body = self.statements(depth + 1);
+        self.counters.pop();
+        (self.in_for, self.in_loop) = (in_for, in_loop);
+        Stmt::Loop { open, body, close: format!("    {c} = {c} + 1;\n}}", c = counter) }
+    }
+
+    fn int_expression(&mut self, depth: usize) -> String {
+        if depth == 0 || self.rng.gen_bool(0.3) {
+            let readable = self.ints.len() + self.counters.len();
+            return match self.rng.gen_range(0..3) {
+                0 => self.rng.gen_range(-20..=20).to_string(),
+                _ => {
+                    let index = self.rng.gen_range(0..readable);
+                    self.ints.iter().chain(&self.counters).nth(index).unwrap().clone()
+                }
+            };
+        }
+        match self.rng.gen_range(0..6) {
+            0 => format!("({} + {})", self.int_expression(depth - 1), self.int_expression(depth - 1)),
+            1 => format!("({} - {})", self.int_expression(depth - 1), self.int_expression(depth - 1)),
+            2 => format!("({} * {})", self.int_expression(depth - 1), self.int_expression(depth - 1)),
+            // Literal divisors are never zero
+            3 => format!("({} / {})", self.int_expression(depth - 1), self.rng.gen_range(1..=7)),
+            4 => format!("({} % {})", self.int_expression(depth - 1), self.rng.gen_range(1..=7)),
+            _ => format!("-{}", self.int_expression(depth - 1)),
+        }
+    }
+
+    fn float_literal(&mut self) -> String {
+        format!("{:.2}", self.rng.gen_range(-40..=40) as f64 * 0.25)
+    }
+
+    fn float_expression(&mut self, depth: usize) -> String {
+        if depth == 0 || self.rng.gen_bool(0.3) {
+            return if self.rng.gen_bool(0.4) {
+                self.float_literal()
+            } else {
+                self.floats[self.rng.gen_range(0..self.floats.len())].clone()
+            };
+        }
+        match self.rng.gen_range(0..3) {
+            0 => format!("({} + {})", self.float_expression(depth - 1), self.float_expression(depth - 1)),
+            1 => format!("({} - {})", self.float_expression(depth - 1), self.float_expression(depth - 1)),
+            // Only a literal factor, so values cannot grow without bound in loops
+            _ => format!("({} * {})", self.float_expression(depth - 1), self.float_literal()),
+        }
+    }
+
+    fn bool_expression(&mut self, depth: usize) -> String {
+        const COMPARISONS: [&str; 6] = ["<", ">", "<=", ">=", "==", "!="];
+        if depth == 0 || self.rng.gen_bool(0.3) {
+            return self.bools[self.rng.gen_range(0..self.bools.len())].clone();
+        }
+        let comparison = COMPARISONS[self.rng.gen_range(0..COMPARISONS.len())];
+        // && and || are not compiled yet
+        match self.rng.gen_range(0..3) {
+            0 => format!("{} {} {}", self.int_expression(1), comparison, self.int_expression(1)),
+            1 => format!("{} {} {}", self.float_expression(1), comparison, self.float_expression(1)),
+            _ => format!("!({})", self.bool_expression(depth - 1)),
+        }
+    }
+}
+
+/// The C runtime built once per test binary, without HTTP so libcurl is not needed
+fn runtime_object() -> Option<&'static Path> {
+    static RUNTIME: OnceLock<Option<PathBuf>> = OnceLock::new();
+    RUNTIME.get_or_init(|| {
+        let object = std::env::temp_dir().join(format!("rono_differential_runtime_{}.o", std::process::id()));
+        let status = Command::new("cc")
+            .arg("-c")
+            .arg(concat!(env!("CARGO_MANIFEST_DIR"), "/src/runtime.c"))
+            .args(["-DRONO_NO_HTTP", "-o"])
+            .arg(&object)
+            .status();
+        matches!(status, Ok(status) if status.success()).then_some(object)
+    }).as_deref()
+}
+
+/// Output of the interpreter, or None when the program is invalid: shrinking may
+/// remove a declaration, and the interpreter alone would accept the assignment
+fn run_interpreted(source: &str) -> Option<Vec<String>> {
+    analyze_source(source).ok()?;
+    let program = parse_source(source).ok()?;
+    let mut interpreter = Interpreter::new();
+    interpreter.capture_output();
+    interpreter.execute(&program).ok()?;
+    Some(interpreter.take_output())
+}
+
+/// Output of the compiled program, or a description of how compiling or running it failed
+fn run_compiled(source: &str, runtime: &Path) -> Result<Vec<String>, String> {
+    let program = parse_source(source).map_err(|e| e.to_string())?;
+    let mut compiler = Compiler::new(detect_host_target(), OptLevel::None, false).map_err(|e| e.to_string())?;
+    // A panic in code generation is a divergence too, not the end of the run
+    let artifact = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| compiler.compile_to_object(&program)))
+        .map_err(|_| "the compiler panicked".to_string())?
+        .map_err(|e| format!("compile error: {}", e))?;
+
+    let id = NEXT_BINARY.fetch_add(1, Ordering::SeqCst);
+    let binary = std::env::temp_dir().join(format!("rono_differential_{}_{}", std::process::id(), id));
+    let object = binary.with_extension("o");
+    std::fs::write(&object, &artifact.object).map_err(|e| e.to_string())?;
+    let linked = Command::new("cc").arg(&object).arg(runtime).args(["-lm", "-o"]).arg(&binary).output();
+    let _ = std::fs::remove_file(&object);
+    match linked {
+        Ok(output) if output.status.success() => {}
+        Ok(output) => return Err(format!("linking failed: {}", String::from_utf8_lossy(&output.stderr))),
+        Err(e) => return Err(format!("linking failed: {}", e)),
+    }
+
+    let output = Command::new(&binary).output().map_err(|e| e.to_string());
+    let _ = std::fs::remove_file(&binary);
+    let output = output?;
+    if !output.status.success() {
+        return Err(format!("exited with {:?}", output.status.code()));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).lines().map(str::to_string).collect())
+}
+
+/// Lines must match exactly: both backends print floats as the shortest text
+/// that reads back as the same number
+fn same_output(interpreted: &[String], compiled: &[String]) -> bool {
+    interpreted == compiled
+}
+
+/// Interpreter output and the compiled program's output or failure
+type Outputs = (Vec<String>, Result<Vec<String>, String>);
+
+/// Both outputs when the program runs in the interpreter and the compiled program disagrees
+fn divergence(source: &str, runtime: &Path) -> Option<Outputs> {
+    let interpreted = run_interpreted(source)?;
+    let compiled = run_compiled(source, runtime);
+    match &compiled {
+        Ok(lines) if same_output(&interpreted, lines) => None,
+        _ => Some((interpreted, compiled)),
+    }
+}
+
+fn count_statements(body: &[Stmt]) -> usize {
+    body.iter().map(|stmt| 1 + match stmt {
+        Stmt::Line(_) => 0,
+        Stmt::If { then_body, else_body, .. } => count_statements(then_body) + count_statements(else_body),
+        Stmt::Loop { body, .. } => count_statements(body),
+    }).sum()
+}
+
+/// A copy of `body` without the statement at `target`, counting in pre-order
+fn without(body: &[Stmt], target: usize) -> Vec<Stmt> {
+    fn remove(body: &[Stmt], target: usize, index: &mut usize) -> Vec<Stmt> {
+        let mut kept = Vec::new();
+        for stmt in body {
+            let current = *index;
+            *index += 1;
+            if current == target {
+                *index += count_statements(std::slice::from_ref(stmt)) - 1;
+                continue;
+            }
+            kept.push(match stmt {
+                Stmt::Line(_) => stmt.clone(),
+                Stmt::If { condition, then_body, else_body } => Stmt::If {
+                    condition: condition.clone(),
+                    then_body: remove(then_body, target, index),
+                    else_body: remove(else_body, target, index),
+                },
+                Stmt::Loop { open, body, close } => Stmt::Loop {
+                    open: open.clone(),
+                    body: remove(body, target, index),
+                    close: close.clone(),
+                },
+            });
+        }
+        kept
+    }
+    remove(body, target, &mut 0)
+}
+
+/// Removes statements one at a time for as long as the outputs still differ
+fn shrink(mut body: Vec<Stmt>, runtime: &Path) -> Vec<Stmt> {
+    'restart: loop {
+        for target in 0..count_statements(&body) {
+            let candidate = without(&body, target);
+            if divergence(&render(&candidate), runtime).is_some() {
+                body = candidate;
+                continue 'restart;
+            }
+        }
+        return body;
+    }
+}
+
+fn env_number(name: &str, default: u64) -> u64 {
+    std::env::var(name).ok().and_then(|value| value.parse().ok()).unwrap_or(default)
+}
+
+#[test]
+#[ignore = "differential fuzzing; run with --ignored"]
+fn fuzz_interpreter_against_compiled() {
+    let Some(runtime) = runtime_object() else {
+        eprintln!("cc is not available, skipping");
+        return;
+    };
+    let seed = env_number("RONO_FUZZ_SEED", 435);
+    let cases = env_number("RONO_FUZZ_CASES", 200);
+
+    let mut generator = Generator::new(seed);
+    let mut reports = Vec::new();
+    for case in 0..cases {
+        let body = generator.program();
+        if divergence(&render(&body), runtime).is_none() {
+            continue;
+        }
+        let shrunk = render(&shrink(body, runtime));
+        let (interpreted, compiled) = divergence(&shrunk, runtime).expect("shrunk program should still diverge");
+        reports.push(format!(
+            "case {} (seed {}):\n{}interpreter: {:?}\ncompiled:    {:?}\n",
+            case, seed, shrunk, interpreted, compiled
+        ));
+    }
+    assert!(reports.is_empty(), "{} of {} programs diverged:\n\n{}", reports.len(), cases, reports.join("\n"));
+}
+
+#[test]
+fn test_generated_programs_are_reproducible() {
+    let source = render(&Generator::new(7).program());
+    assert_eq!(source, render(&Generator::new(7).program()));
+    assert!(run_interpreted(&source).is_some(), "generated programs should run:\n{}", source);
+}
+
+#[test]
+fn test_divergent_corpus_now_agrees() {
+    let Some(runtime) = runtime_object() else {
+        return;
+    };
+    let corpus = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/differential");
+    let mut checked = 0;
+    for entry in std::fs::read_dir(&corpus).expect("corpus directory should exist") {
+        let path = entry.expect("corpus entry should be readable").path();
+        if path.extension() != Some(OsStr::new("rono")) {
+            continue;
+        }
+        let source = std::fs::read_to_string(&path).expect("corpus program should be readable");
+        assert!(run_interpreted(&source).is_some(), "{} should run in the interpreter", path.display());
+        if let Some((interpreted, compiled)) = divergence(&source, runtime) {
+            panic!("{} diverges again\ninterpreter: {:?}\ncompiled:    {:?}", path.display(), interpreted, compiled);
+        }
+        checked += 1;
+    }
+    assert!(checked > 0, "the corpus should not be empty");
+}
@@ -0,0 +1,62 @@
+//! Публичный API при разных наборах cargo features. Front end и интерпретатор
+//! доступны всегда; остальное проверяется под своим `cfg`:
+//!
+//! `cargo test --test features`
+//! `cargo test --test features --no-default-features --features interpreter-only`
+//! `cargo test --test features --no-default-features --features http`
+
+use rono_lang::{analyze_source, parse_source, run_source, FileMetrics, Interpreter, MetricsReport, SemanticAnalyzer};
+
+const PROGRAM: &str = r#"
+fn square(x: int) int {
+    ret x * x;
+}
+
+chif main() {
+    con.out(square(7));
+}
+"#;
+
+#[test]
+fn front_end_and_interpreter_are_always_available() {
+    let program = parse_source(PROGRAM).expect("program should parse");
+    let analyzed = SemanticAnalyzer::new().analyze(&program).expect("program should pass analysis");
+    assert_eq!(analyzed.items.len(), 2);
+    analyze_source(PROGRAM).expect("program should pass analysis");
+    run_source("chif main() { }").expect("program should run");
+
+    let mut interpreter = Interpreter::new();
+    interpreter.capture_output();
+    interpreter.execute(&program).expect("program should run");
+    assert_eq!(interpreter.take_output(), vec!["49".to_string()]);
+
+    let report = MetricsReport { files: vec![FileMetrics::new("features.rono", &program)] };
+    assert_eq!(report.totals().functions, 2);
+}
+
+#[cfg(feature = "backend-cranelift")]
+#[test]
+fn backend_compiles_to_an_object_file() {
+    use rono_lang::{detect_host_target, Compiler, OptLevel};
+
+    let program = parse_source(PROGRAM).expect("program should parse");
+    let mut compiler = Compiler::new(detect_host_target(), OptLevel::None, false).expect("compiler should start");
+    compiler.compile_to_object(&program).expect("program should compile");
+}
+
+#[cfg(not(feature = "http"))]
+#[test]
+fn http_builtins_report_the_missing_feature() {
+    let error = run_source("chif main() { var r: HttpResponse = http_get(\"http://localhost\"); }")
+        .expect_err("http_get should fail without the http feature");
+    assert!(error.to_string().contains("`http` feature"), "{}", error);
+}
+
+#[cfg(feature = "http")]
+#[test]
+fn http_builtins_are_dispatched() {
+    // Неверное число аргументов проверяется до обращения к сети
+    let error = run_source("chif main() { http_get(); }").expect_err("http_get() without a URL should fail");
+    assert!(!error.to_string().contains("`http` feature"), "{}", error);
+    assert!(error.to_string().contains("http_get"), "{}", error);
+}
@@ -0,0 +1,71 @@
+//! Parses and analyzes a generated program of about 100 000 lines and prints
+//! the time of each stage and the peak resident set size of the process.
+//!
+//! `cargo test --release --test front_end_scale -- --ignored --nocapture`
+//!
+//! As in `compile_memory.rs`, the numbers are only printed: they are meant for
+//! comparing the front end against itself.
+
+use rono_lang::{parse_source, SemanticAnalyzer};
+use std::fmt::Write;
+use std::time::Instant;
+
+const LINES: usize = 100_000;
+/// Lines of one generated function, including its header and closing brace
+const FUNCTION_LINES: usize = 25;
+
+/// A function with declarations, arithmetic, a loop, a condition and calls
+fn function(out: &mut String, n: usize) {
+    writeln!(out, "fn step{}(x: int, y: int) int {{", n).unwrap();
+    writeln!(out, "    var total: int = x * {} + y;", n % 89).unwrap();
+    for k in 0..(FUNCTION_LINES - 11) / 2 {
+        writeln!(out, "    var v{}: int = total - {} * x;", k, k + 1).unwrap();
+        writeln!(out, "    total = total + v{} % 7;", k).unwrap();
+    }
+    writeln!(out, "    for (var i: int = 0; i < 3; i = i + 1) {{").unwrap();
+    writeln!(out, "        total = total + i;").unwrap();
+    writeln!(out, "    }}").unwrap();
+    writeln!(out, "    if (total > 1000) {{").unwrap();
+    writeln!(out, "        total = total / 2;").unwrap();
+    writeln!(out, "    }}").unwrap();
+    if n > 0 {
+        writeln!(out, "    ret total + step{}(y, x);", n - 1).unwrap();
+    } else {
+        writeln!(out, "    ret total;").unwrap();
+    }
+    writeln!(out, "}}\n").unwrap();
+}
+
+/// Peak RSS in kilobytes from /proc, where it is available
+fn peak_rss_kb() -> Option<u64> {
+    let status = std::fs::read_to_string("/proc/self/status").ok()?;
+    let line = status.lines().find(|line| line.starts_with("VmHWM:"))?;
+    line.split_whitespace().nth(1)?.parse().ok()
+}
+
+#[test]
+#[ignore]
+fn parse_and_analyze_large_program() {
+    let mut source = String::new();
+    let functions = LINES / (FUNCTION_LINES + 1);
+    for n in 0..functions {
+        function(&mut source, n);
+    }
+    writeln!(source, "chif main() {{\n    con.out(step{}(1, 2));\n}}", functions - 1).unwrap();
+
+    let before = peak_rss_kb();
+    let started = Instant::now();
+    let program = parse_source(&source).expect("program should parse");
+    let parsed = started.elapsed();
+    SemanticAnalyzer::new().analyze_owned(program).expect("program should pass analysis");
+    let analyzed = started.elapsed() - parsed;
+
+    println!(
+        "{} lines: parsed in {} ms, analyzed in {} ms; peak RSS before {:?} KB, after {:?} KB",
+        source.lines().count(),
+        parsed.as_millis(),
+        analyzed.as_millis(),
+        before,
+        peak_rss_kb()
+    );
+}
@@ -0,0 +1,168 @@
+//! Robustness of the front end: the lexer, the parser and the semantic analyzer
+//! must reject malformed input with an error and never panic.
+//!
+//! Inputs are random bytes, soups of Rono tokens and mutated example programs
+//! (truncated, with spans deleted or duplicated). A short run with a fixed seed
+//! is part of the normal test suite; `cargo test --test parser_fuzz -- --ignored`
+//! runs the long one, with `RONO_FUZZ_SEED` and `RONO_FUZZ_CASES` as in
+//! `tests/differential.rs`. Inputs that used to crash the front end are kept in
+//! `tests/parser_fuzz/` and checked on every run.
+
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use rono_lang::analyze_source;
+use std::panic::{self, AssertUnwindSafe};
+use std::path::Path;
+
+/// Fragments the token soup is built from: keywords, punctuation, literals,
+/// and pieces that are only valid in some positions
+const FRAGMENTS: &[&str] = &[
+    "chif", "fn", "struct", "impl", "import", "export", "var", "let", "if", "else",
+    "for", "in", "while", "ret", "break", "continue", "match", "null", "true", "false",
+    "int", "float", "bool", "str", "list", "map", "pointer", "self", "main", "range",
+    "con", "con.out", "con.in", "x", "y", "_", "Point", "(", ")", "{", "}", "[", "]",
+    "<", ">", "<=", ">=", "==", "!=", "=", "+", "-", "*", "/", "%", "!", "&", "&&",
+    "||", "|", ".", ",", ":", "::", ";", "->", "=>", "0", "1", "-1", "3.14", "1.",
+    "9999999999999999999999", "\"text\"", "\"{x}\"", "\"{\"", "\"}\"", "\"{}\"",
+    "\"unterminated", "'a'", "\\", "//", "/*", "*/", "\n", " ", "@", "#", "$", "é",
+];
+
+fn soup(rng: &mut StdRng) -> String {
+    let length = rng.gen_range(0..40);
+    let mut out = String::new();
+    for _ in 0..length {
+        out.push_str(FRAGMENTS[rng.gen_range(0..FRAGMENTS.len())]);
+        if rng.gen_bool(0.7) {
+            out.push(' ');
+        }
+    }
+    out
+}
+
+fn random_bytes(rng: &mut StdRng) -> String {
+    let length = rng.gen_range(0..64);
+    let bytes: Vec<u8> = (0..length).map(|_| rng.gen()).collect();
+    String::from_utf8_lossy(&bytes).into_owned()
+}
+
+/// Nearest char boundary at or below `index`
+fn boundary(text: &str, mut index: usize) -> usize {
+    while !text.is_char_boundary(index) {
+        index -= 1;
+    }
+    index
+}
+
+fn mutate(rng: &mut StdRng, seed: &str) -> String {
+    if seed.is_empty() {
+        return String::new();
+    }
+    let start = boundary(seed, rng.gen_range(0..seed.len()));
+    let end = boundary(seed, rng.gen_range(start..=seed.len()));
+    match rng.gen_range(0..3) {
+        0 => seed[..start].to_string(),
+        1 => format!("{}{}", &seed[..start], &seed[end..]),
+        _ => format!("{}{}{}", &seed[..end], &seed[start..end], &seed[end..]),
+    }
+}
+
+/// Sources of the programs the mutations start from: examples, programs found by
+/// the differential fuzzer and code blocks of the documentation
+fn seed_programs() -> Vec<String> {
+    let root = Path::new(env!("CARGO_MANIFEST_DIR"));
+    let mut programs = Vec::new();
+    for dir in ["examples", "tests/differential"] {
+        let Ok(entries) = std::fs::read_dir(root.join(dir)) else { continue };
+        for entry in entries.flatten() {
+            if let Ok(source) = std::fs::read_to_string(entry.path()) {
+                programs.push(source);
+            }
+        }
+    }
+    // ```rono blocks of the documentation cover most of the syntax
+    for doc in ["README.md", "RONO_LANGUAGE_DOCUMENTATION.md"] {
+        let Ok(text) = std::fs::read_to_string(root.join(doc)) else { continue };
+        for block in text.split("```rono\n").skip(1) {
+            programs.push(block.split("```").next().unwrap_or_default().to_string());
+        }
+    }
+    programs.sort();
+    programs
+}
+
+/// The panic message when the front end panics on `source`
+fn front_end_panic(source: &str) -> Option<String> {
+    let result = panic::catch_unwind(AssertUnwindSafe(|| {
+        let _ = analyze_source(source);
+    }));
+    let payload = result.err()?;
+    Some(
+        payload.downcast_ref::<String>().cloned()
+            .or_else(|| payload.downcast_ref::<&str>().map(|message| message.to_string()))
+            .unwrap_or_else(|| "non-string panic".to_string()),
+    )
+}
+
+fn run_fuzzer(seed: u64, cases: u64) {
+    let seeds = seed_programs();
+    let mut rng = StdRng::seed_from_u64(seed);
+    let mut reports = Vec::new();
+    for case in 0..cases {
+        let source = match rng.gen_range(0..4) {
+            0 => random_bytes(&mut rng),
+            1 => soup(&mut rng),
+            // Inside a function body the soup gets past the item parser more often
+            2 => format!("chif main() {{\n{}\n}}\n", soup(&mut rng)),
+            _ if seeds.is_empty() => soup(&mut rng),
+            _ => {
+                let seed_program = &seeds[rng.gen_range(0..seeds.len())];
+                mutate(&mut rng, seed_program)
+            }
+        };
+        if let Some(message) = front_end_panic(&source) {
+            reports.push(format!("case {} (seed {}): {}\n{:?}\n", case, seed, message, source));
+        }
+    }
+    assert!(reports.is_empty(), "the front end panicked on {} input(s):\n{}", reports.len(), reports.join("\n"));
+}
+
+fn env_number(name: &str, default: u64) -> u64 {
+    std::env::var(name).ok().and_then(|value| value.parse().ok()).unwrap_or(default)
+}
+
+#[test]
+fn front_end_survives_known_crashers() {
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/parser_fuzz");
+    let mut paths: Vec<_> = std::fs::read_dir(&dir).expect("crasher corpus should exist")
+        .flatten().map(|entry| entry.path()).collect();
+    paths.sort();
+    assert!(!paths.is_empty(), "crasher corpus should not be empty");
+    for path in paths {
+        let source = std::fs::read_to_string(&path).expect("crasher should be readable");
+        if let Some(message) = front_end_panic(&source) {
+            panic!("{} panicked: {}", path.display(), message);
+        }
+    }
+}
+
+#[test]
+fn front_end_survives_every_prefix_of_the_examples() {
+    for source in seed_programs() {
+        for end in (0..=source.len()).filter(|&end| source.is_char_boundary(end)) {
+            if let Some(message) = front_end_panic(&source[..end]) {
+                panic!("panicked on {:?}: {}", &source[..end], message);
+            }
+        }
+    }
+}
+
+#[test]
+fn fuzz_front_end_quick() {
+    run_fuzzer(442, 2000);
+}
+
+#[test]
+#[ignore = "front end fuzzing; run with --ignored"]
+fn fuzz_front_end() {
+    run_fuzzer(env_number("RONO_FUZZ_SEED", 442), env_number("RONO_FUZZ_CASES", 200_000));
+}